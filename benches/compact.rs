@@ -0,0 +1,40 @@
+//! Encode/decode benchmarks for every Berachain storage codec.
+//!
+//! Run with: `cargo bench --bench compact --features test-utils`
+
+use bera_reth::test_utils::compact_corpus::{self, CorpusEntry};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use reth_codecs::Compact;
+use std::hint::black_box;
+
+fn bench_codec<T: Compact>(c: &mut Criterion, group_name: &str, corpus: Vec<CorpusEntry<T>>) {
+    let mut group = c.benchmark_group(group_name);
+
+    for CorpusEntry { name, value } in &corpus {
+        group.bench_with_input(BenchmarkId::new("encode", name), value, |b, value| {
+            b.iter(|| {
+                let mut buf = Vec::with_capacity(256);
+                black_box(value.to_compact(&mut buf));
+                buf
+            })
+        });
+
+        let mut encoded = Vec::new();
+        let len = value.to_compact(&mut encoded);
+        group.bench_with_input(BenchmarkId::new("decode", name), &encoded, |b, encoded| {
+            b.iter(|| T::from_compact(black_box(encoded), len).0)
+        });
+    }
+
+    group.finish();
+}
+
+fn compact_benches(c: &mut Criterion) {
+    bench_codec(c, "compact/header", compact_corpus::headers());
+    bench_codec(c, "compact/tx_envelope", compact_corpus::tx_envelopes());
+    bench_codec(c, "compact/tx_type", compact_corpus::tx_types());
+    bench_codec(c, "compact/receipt", compact_corpus::receipts());
+}
+
+criterion_group!(benches, compact_benches);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+//! Example node replacing the transaction pool while reusing every other Berachain component.
+//!
+//! Run it like the regular binary, e.g.:
+//!
+//! ```sh
+//! cargo run --example custom_pool -- node --dev
+//! ```
+
+use bera_reth::{
+    cli::{BerachainCli, install_berachain_services},
+    node::BerachainNode,
+    pool::BerachainPoolBuilder,
+    rpc::BerachainAddOns,
+};
+use clap::Parser;
+use reth_node_api::FullNodeTypes;
+use reth_node_builder::{BuilderContext, NodeHandle, components::PoolBuilder};
+use reth_transaction_pool::TransactionPool;
+use tracing::info;
+
+/// Builds the stock Berachain pool and logs every transaction that becomes pending.
+#[derive(Debug, Clone)]
+struct LoggingPoolBuilder(BerachainPoolBuilder);
+
+impl<Node> PoolBuilder<Node> for LoggingPoolBuilder
+where
+    Node: FullNodeTypes<Types = BerachainNode>,
+    BerachainPoolBuilder: PoolBuilder<Node>,
+{
+    type Pool = <BerachainPoolBuilder as PoolBuilder<Node>>::Pool;
+
+    async fn build_pool(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::Pool> {
+        let pool = self.0.build_pool(ctx).await?;
+
+        let mut pending = pool.pending_transactions_listener();
+        ctx.task_executor().spawn(async move {
+            while let Some(hash) = pending.recv().await {
+                info!(target: "example::pool", %hash, "Transaction pending");
+            }
+        });
+
+        Ok(pool)
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    BerachainCli::parse().run_with(async move |builder, berachain| {
+        let pool = LoggingPoolBuilder(BerachainPoolBuilder::new(berachain.config()));
+        let NodeHandle { node, node_exit_future } = builder
+            .with_types::<BerachainNode>()
+            .with_components(berachain.components().pool(pool))
+            .with_add_ons(BerachainAddOns::new(&berachain))
+            .launch_with_debug_capabilities()
+            .await?;
+        install_berachain_services(&node, &berachain).await?;
+
+        node_exit_future.await
+    })
+}
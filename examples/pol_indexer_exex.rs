@@ -0,0 +1,70 @@
+//! Example execution extension indexing PoL distributions in-process.
+//!
+//! Run it like the regular binary, e.g.:
+//!
+//! ```sh
+//! cargo run --example pol_indexer_exex -- node --dev
+//! ```
+
+use bera_reth::{
+    cli::{BerachainCli, install_berachain_services},
+    node::exex::{PolEvent, pol_notifications},
+    primitives::BerachainPrimitives,
+};
+use clap::Parser;
+use futures::TryStreamExt;
+use reth_exex::{ExExContext, ExExEvent};
+use reth_node_api::{FullNodeComponents, NodeTypes};
+use reth_node_builder::NodeHandle;
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// Follows the canonical chain and keeps PoL distributions by block number.
+async fn pol_indexer<Node>(mut ctx: ExExContext<Node>) -> eyre::Result<()>
+where
+    Node: FullNodeComponents<Types: NodeTypes<Primitives = BerachainPrimitives>>,
+{
+    let mut distributions = BTreeMap::<u64, PolEvent>::new();
+    let mut notifications = pol_notifications(&mut ctx.notifications);
+
+    while let Some(notification) = notifications.try_next().await? {
+        // Reverted blocks are dropped before committed blocks of a reorg are indexed
+        for event in &notification.reverted {
+            distributions.remove(&event.block.number);
+            info!(
+                target: "exex::pol",
+                number = event.block.number,
+                "Dropped reverted PoL distribution"
+            );
+        }
+
+        for event in notification.committed {
+            info!(
+                target: "exex::pol",
+                number = event.block.number,
+                pubkey = %event.pubkey,
+                tx_hash = %event.tx_hash,
+                "PoL distribution"
+            );
+            distributions.insert(event.block.number, event);
+        }
+        if let Some(tip) = notification.committed_tip {
+            ctx.events.send(ExExEvent::FinishedHeight(tip))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> eyre::Result<()> {
+    BerachainCli::parse().run_with(async move |builder, berachain| {
+        let NodeHandle { node, node_exit_future } = builder
+            .node(berachain.clone())
+            .install_exex("pol-indexer", async move |ctx| Ok(pol_indexer(ctx)))
+            .launch_with_debug_capabilities()
+            .await?;
+        install_berachain_services(&node, &berachain).await?;
+
+        node_exit_future.await
+    })
+}
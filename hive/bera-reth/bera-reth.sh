@@ -0,0 +1,55 @@
+#!/bin/bash
+# Starts bera-reth from the files and HIVE_* variables hive provides:
+#
+#   /genesis.json   genesis of the test chain, converted by mapper.jq
+#   /chain.rlp      blocks imported before starting, if present
+#   /blocks/        further blocks imported one file at a time, if present
+set -e
+
+bera_reth=/usr/local/bin/bera-reth
+datadir=/bera-reth-hive-datadir
+mkdir -p $datadir
+
+case "$HIVE_LOGLEVEL" in
+    0|1) verbosity="-v" ;;
+    2)   verbosity="-vv" ;;
+    3)   verbosity="-vvv" ;;
+    4)   verbosity="-vvvv" ;;
+    *)   verbosity="-vvvvv" ;;
+esac
+
+# Fails on Berachain-incompatible fork schedules, see mapper.jq
+mv /genesis.json /genesis-input.json
+jq -f /mapper.jq /genesis-input.json > /genesis.json
+echo "Supplied genesis state:"
+cat /genesis.json
+
+flags="--datadir $datadir --chain /genesis.json $verbosity"
+$bera_reth init $flags
+
+if [ -f /chain.rlp ]; then
+    echo "Importing /chain.rlp"
+    $bera_reth import $flags /chain.rlp
+fi
+if [ -d /blocks ]; then
+    for file in $(ls /blocks | sort -n); do
+        echo "Importing /blocks/$file"
+        $bera_reth import $flags /blocks/$file
+    done
+fi
+
+flags="$flags --nat none --disable-discovery"
+apis=admin,debug,eth,net,txpool,web3
+flags="$flags --http --http.addr=0.0.0.0 --http.port=8545 --http.api=$apis"
+flags="$flags --ws --ws.addr=0.0.0.0 --ws.port=8546 --ws.api=$apis"
+
+# The engine API secret hive's simulators sign their requests with
+echo -n 7365637265747365637265747365637265747365637265747365637265747365 > /jwt.secret
+flags="$flags --authrpc.addr=0.0.0.0 --authrpc.port=8551 --authrpc.jwtsecret=/jwt.secret"
+
+if [ "$HIVE_BOOTNODE" != "" ]; then
+    flags="$flags --bootnodes=$HIVE_BOOTNODE"
+fi
+
+echo "Running bera-reth with flags $flags"
+exec $bera_reth node $flags
@@ -0,0 +1,7 @@
+#!/bin/bash
+# Prints the enode URL of the running client, as hive expects
+set -e
+
+curl -s -X POST -H "Content-Type: application/json" \
+    --data '{"jsonrpc":"2.0","method":"admin_nodeInfo","params":[],"id":1}' \
+    localhost:8545 | jq -r '.result.enode'
@@ -0,0 +1,14 @@
+//! `bera-consensus-mock`: drives bera-reth nodes through the engine API in place of BeaconKit.
+//!
+//! See [`bera_reth::engine::mock_consensus`].
+
+use bera_reth::engine::mock_consensus::{self, MockConsensusArgs};
+use clap::Parser;
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+    mock_consensus::run(MockConsensusArgs::parse()).await
+}
@@ -0,0 +1,34 @@
+//! `bera-diff-fuzz`: differential fuzzing of bera-reth against bera-geth.
+//!
+//! See [`bera_reth::test_utils::diff_fuzz`].
+
+use bera_reth::test_utils::diff_fuzz::{self, DiffFuzzArgs, Divergence};
+use clap::Parser;
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let report = diff_fuzz::run(DiffFuzzArgs::parse()).await?;
+    println!(
+        "seed {}: {} blocks, {} transactions ({} refused by the pool), {} divergences",
+        report.seed,
+        report.blocks,
+        report.transactions,
+        report.refused,
+        report.divergences.len()
+    );
+    for divergence in &report.divergences {
+        match divergence {
+            Divergence::Rejected { number, status } => {
+                println!("block {number} rejected by bera-geth: {status}");
+            }
+            Divergence::Rpc { number, method, diffs } => {
+                println!("{method} of block {number} differs:");
+                for diff in diffs {
+                    println!("  {diff}");
+                }
+            }
+        }
+    }
+    eyre::ensure!(report.divergences.is_empty(), "bera-reth and bera-geth diverge");
+    Ok(())
+}
@@ -1,7 +1,7 @@
 //! Berachain chain specification with Ethereum hardforks plus Prague1 minimum base fee
 
 use crate::{
-    genesis::BerachainGenesisConfig,
+    genesis::{BerachainConfigError, BerachainForkConfig, BerachainGenesisConfig},
     hardforks::{BerachainHardfork, BerachainHardforks},
     primitives::{BerachainHeader, header::BlsPublicKey},
 };
@@ -23,9 +23,81 @@ use reth::{
 };
 use reth_chainspec::{ChainSpec, DepositContract, EthChainSpec, Hardforks, make_genesis_header};
 use reth_cli::chainspec::{ChainSpecParser, parse_genesis};
-use reth_ethereum_cli::chainspec::SUPPORTED_CHAINS;
 use reth_evm::eth::spec::EthExecutorSpec;
-use std::{fmt::Display, sync::Arc};
+use std::{
+    fmt::Display,
+    sync::{Arc, LazyLock},
+};
+use thiserror::Error;
+
+/// Invariant of a Berachain genesis violated by a genesis file.
+#[derive(Debug, Error)]
+pub enum BerachainChainSpecError {
+    /// The `berachain` section is missing or invalid.
+    #[error(
+        "Failed to parse berachain genesis config: {0}. Please ensure the genesis file contains a \
+         valid 'berachain' configuration section with Prague1 settings"
+    )]
+    Config(#[from] BerachainConfigError),
+    /// Cancun is missing or not active at genesis.
+    #[error(
+        "Berachain networks require Cancun hardfork at genesis (time = 0), set `cancunTime` to 0"
+    )]
+    CancunNotAtGenesis,
+    /// A block-based fork before Cancun activates after genesis.
+    #[error(
+        "Berachain networks require {hardfork} hardfork at genesis (block 0), got block {block}"
+    )]
+    ForkNotAtGenesis {
+        /// The fork activating late.
+        hardfork: EthereumHardfork,
+        /// Its activation block.
+        block: u64,
+    },
+    /// Shanghai activates after genesis.
+    #[error(
+        "Berachain networks require Shanghai hardfork at genesis (time = 0), got time {0}, set \
+         `shanghaiTime` to 0"
+    )]
+    ShanghaiNotAtGenesis(u64),
+    /// Prague1 activates before Prague.
+    #[error(
+        "Prague1 hardfork must activate at or after Prague hardfork. Prague time: {prague_time}, \
+         Prague1 time: {prague1_time}. Check that Prague1 time is not malformed (should be a valid \
+         Unix timestamp)."
+    )]
+    Prague1BeforePrague {
+        /// Activation time of Prague.
+        prague_time: u64,
+        /// Activation time of Prague1.
+        prague1_time: u64,
+    },
+    /// Prague2 activates before Prague1.
+    #[error(
+        "Prague2 hardfork must activate at or after Prague1 hardfork. Prague1 time: \
+         {prague1_time}, Prague2 time: {prague2_time}"
+    )]
+    Prague2BeforePrague1 {
+        /// Activation time of Prague1.
+        prague1_time: u64,
+        /// Activation time of Prague2.
+        prague2_time: u64,
+    },
+    /// The terminal total difficulty is not set.
+    #[error(
+        "Berachain networks require terminal_total_difficulty to be set to 0, set \
+         `terminalTotalDifficulty` to 0"
+    )]
+    MissingTerminalTotalDifficulty,
+    /// The terminal total difficulty is not zero.
+    #[error(
+        "Berachain networks require terminal total difficulty of 0 (merge at genesis), got {0}"
+    )]
+    NonZeroTerminalTotalDifficulty(U256),
+    /// The merge netsplit block is after genesis.
+    #[error("Berachain networks require merge at genesis (block 0), got block {0}")]
+    MergeNotAtGenesis(u64),
+}
 
 /// Default minimum base fee when Prague1 is not active.
 const DEFAULT_MIN_BASE_FEE_WEI: u64 = 0;
@@ -36,16 +108,81 @@ pub struct BerachainChainSpec {
     /// The underlying Reth chain specification
     pub inner: ChainSpec,
     pub genesis_header: BerachainHeader,
-    /// PoL contract address loaded from configuration
+    /// PoL contract address loaded from the Prague1 configuration
     pub pol_contract_address: Address,
-    /// The minimum base fee in wei
+    /// The minimum base fee in wei from the Prague1 configuration
     pub prague1_minimum_base_fee: u64,
+    /// Gas limit of the PoL transaction loaded from the Prague1 configuration
+    pub pol_tx_gas_limit: u64,
+    /// Prague2 configuration, replacing the Prague1 values above from its activation on
+    pub prague2: Option<BerachainForkConfig>,
 }
 
 impl BerachainChainSpec {
+    /// Returns the Prague1 PoL contract address, see [`Self::pol_contract_at_timestamp`].
     pub fn pol_contract(&self) -> Address {
         self.pol_contract_address
     }
+
+    /// Returns the Prague2 configuration if Prague2 is active at `timestamp`.
+    fn prague2_at_timestamp(&self, timestamp: u64) -> Option<&BerachainForkConfig> {
+        self.prague2.as_ref().filter(|_| self.is_prague2_active_at_timestamp(timestamp))
+    }
+
+    /// Returns the PoL contract called by blocks at `timestamp`.
+    pub fn pol_contract_at_timestamp(&self, timestamp: u64) -> Address {
+        self.prague2_at_timestamp(timestamp)
+            .map_or(self.pol_contract_address, |prague2| prague2.pol_distributor_address)
+    }
+
+    /// Returns the gas limit of the PoL transaction of blocks at `timestamp`.
+    pub fn pol_tx_gas_limit_at_timestamp(&self, timestamp: u64) -> u64 {
+        self.prague2_at_timestamp(timestamp)
+            .map_or(self.pol_tx_gas_limit, |prague2| prague2.pol_tx_gas_limit)
+    }
+
+    /// Returns the minimum base fee of a block built on a parent at `timestamp`, or `None` before
+    /// Prague1.
+    pub fn minimum_base_fee_at_timestamp(&self, timestamp: u64) -> Option<u64> {
+        if let Some(prague2) = self.prague2_at_timestamp(timestamp) {
+            return Some(prague2.minimum_base_fee_wei)
+        }
+        self.is_prague1_active_at_timestamp(timestamp).then_some(self.prague1_minimum_base_fee)
+    }
+
+    /// Returns the base fee of the block built on `parent`, with the base fee parameters and
+    /// minimum base fee of the forks active at the parent. Works on any header type, so RPC
+    /// projections match [`EthChainSpec::next_block_base_fee`].
+    pub fn next_base_fee_after(&self, parent: &impl BlockHeader) -> Option<u64> {
+        let (raw, min_base_fee) = self.next_base_fee_parts(parent)?;
+        Some(raw.max(min_base_fee))
+    }
+
+    /// Returns whether the base fee of the block built on `parent` is raised to the minimum base
+    /// fee.
+    pub fn is_base_fee_raised_to_minimum_after(&self, parent: &impl BlockHeader) -> bool {
+        self.is_prague1_active_at_timestamp(parent.timestamp()) &&
+            self.next_base_fee_parts(parent).is_some_and(|(raw, minimum)| raw < minimum)
+    }
+
+    /// Returns the base fee of the block built on `parent` given by the base fee parameters, and
+    /// the minimum base fee it is raised to.
+    fn next_base_fee_parts(&self, parent: &impl BlockHeader) -> Option<(u64, u64)> {
+        // Note that we use this parent block timestamp to determine whether Prague 1 is active.
+        // This means that we technically start the base_fee changes the block after the fork
+        // block. This is a conscious decision to minimize fork diffs across execution clients.
+        let raw = calc_next_block_base_fee(
+            parent.gas_used(),
+            parent.gas_limit(),
+            parent.base_fee_per_gas()?,
+            self.base_fee_params_at_timestamp(parent.timestamp()),
+        );
+
+        let min_base_fee = self
+            .minimum_base_fee_at_timestamp(parent.timestamp())
+            .unwrap_or(DEFAULT_MIN_BASE_FEE_WEI);
+        Some((raw, min_base_fee))
+    }
 }
 
 impl EthChainSpec for BerachainChainSpec {
@@ -94,7 +231,10 @@ impl EthChainSpec for BerachainChainSpec {
     }
 
     fn bootnodes(&self) -> Option<Vec<reth_network_peers::node_record::NodeRecord>> {
-        self.inner.bootnodes()
+        named_chain(self.chain().id())
+            .filter(|chain| !chain.bootnodes.is_empty())
+            .map(|chain| chain.bootnodes.iter().filter_map(|enode| enode.parse().ok()).collect())
+            .or_else(|| self.inner.bootnodes())
     }
 
     fn final_paris_total_difficulty(&self) -> Option<U256> {
@@ -102,22 +242,7 @@ impl EthChainSpec for BerachainChainSpec {
     }
 
     fn next_block_base_fee(&self, parent: &Self::Header, _: u64) -> Option<u64> {
-        // Note that we use this parent block timestamp to determine whether Prague 1 is active.
-        // This means that we technically start the base_fee changes the block after the fork
-        // block. This is a conscious decision to minimize fork diffs across execution clients.
-        let raw = calc_next_block_base_fee(
-            parent.gas_used(),
-            parent.gas_limit(),
-            parent.base_fee_per_gas()?,
-            self.base_fee_params_at_timestamp(parent.timestamp()),
-        );
-
-        let min_base_fee = if self.is_prague1_active_at_timestamp(parent.timestamp()) {
-            self.prague1_minimum_base_fee
-        } else {
-            DEFAULT_MIN_BASE_FEE_WEI
-        };
-        Some(raw.max(min_base_fee))
+        self.next_base_fee_after(parent)
     }
 }
 
@@ -161,6 +286,83 @@ impl EthExecutorSpec for BerachainChainSpec {
     }
 }
 
+/// Chain name of [`BERACHAIN_DEV`], implied by `--dev`.
+pub const DEV_CHAIN: &str = "dev";
+
+/// Development chain: reth's dev genesis and funded dev accounts, with every Ethereum fork up to
+/// Prague and Berachain's Prague1 active at genesis. Its 60M gas limit leaves room for user
+/// transactions next to the gas the payload builder reserves for the PoL transaction.
+pub static BERACHAIN_DEV: LazyLock<Arc<BerachainChainSpec>> = LazyLock::new(|| {
+    let mut genesis = reth_chainspec::DEV.genesis().clone();
+    genesis.gas_limit = 60_000_000;
+    genesis.config.shanghai_time = Some(0);
+    genesis.config.cancun_time = Some(0);
+    genesis.config.prague_time = Some(0);
+    genesis.config.terminal_total_difficulty = Some(U256::ZERO);
+    genesis.config.extra_fields.insert(
+        "berachain".to_string(),
+        serde_json::to_value(BerachainGenesisConfig::default())
+            .expect("default berachain config serializes"),
+    );
+    Arc::new(BerachainChainSpec::try_from(genesis).expect("dev genesis is valid"))
+});
+
+/// Public Berachain network resolved by `--chain <NAME>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NamedChain {
+    /// Name passed to `--chain`.
+    pub name: &'static str,
+    /// Chain id of the network.
+    pub chain_id: u64,
+    /// Canonical genesis JSON embedded in the binary.
+    pub genesis: Option<&'static str>,
+    /// Enode URLs of the network's bootnodes.
+    pub bootnodes: &'static [&'static str],
+}
+
+impl NamedChain {
+    /// Builds the chainspec from the embedded genesis.
+    pub fn chain_spec(&self) -> eyre::Result<BerachainChainSpec> {
+        let genesis = self.genesis.ok_or_else(|| {
+            eyre::eyre!(
+                "the {} genesis is not embedded in this build; pass the published genesis file \
+                 with `--chain <FILE>`",
+                self.name
+            )
+        })?;
+        let genesis: Genesis = serde_json::from_str(genesis)?;
+        eyre::ensure!(
+            genesis.config.chain_id == self.chain_id,
+            "{} genesis has the wrong chain id",
+            self.name
+        );
+        Ok(genesis.try_into()?)
+    }
+}
+
+/// Public Berachain networks.
+///
+/// The canonical mainnet and Bepolia genesis files and bootnodes have not been committed to this
+/// repository yet. Until they are, their names are left out of [`SUPPORTED_CHAINS`] and fail with
+/// an error pointing to the published genesis file, rather than resolving to the Ethereum network
+/// of the same name.
+pub const NAMED_CHAINS: [NamedChain; 2] = [
+    NamedChain { name: "mainnet", chain_id: 80094, genesis: None, bootnodes: &[] },
+    NamedChain { name: "bepolia", chain_id: 80069, genesis: None, bootnodes: &[] },
+];
+
+/// Chain names [`BerachainChainSpecParser`] resolves to a built-in chainspec.
+///
+/// Reth makes the first one the default of `--chain`, but bera-reth refuses to start without an
+/// explicit `--chain` unless `--dev` is set. Names of [`NAMED_CHAINS`] are added once their
+/// genesis is embedded.
+pub const SUPPORTED_CHAINS: &[&str] = &[DEV_CHAIN];
+
+/// Returns the public network with `chain_id`.
+pub fn named_chain(chain_id: u64) -> Option<&'static NamedChain> {
+    NAMED_CHAINS.iter().find(|chain| chain.chain_id == chain_id)
+}
+
 /// Parser for Berachain chain specifications
 #[derive(Debug, Clone, Default)]
 #[non_exhaustive]
@@ -171,25 +373,47 @@ impl ChainSpecParser for BerachainChainSpecParser {
 
     const SUPPORTED_CHAINS: &'static [&'static str] = SUPPORTED_CHAINS;
 
+    fn help_message() -> String {
+        format!(
+            "The chain this node is running.\nPossible values are either a built-in chain or the \
+             path to a chain specification file. Required unless --dev is set.\n\nBuilt-in \
+             chains:\n    {}\n\nThe mainnet and Bepolia genesis files are not embedded yet; pass \
+             the published genesis file instead.",
+            SUPPORTED_CHAINS.join(", ")
+        )
+    }
+
     fn parse(s: &str) -> eyre::Result<Arc<Self::ChainSpec>> {
-        Ok(Arc::new(parse_genesis(s)?.into()))
+        if s == DEV_CHAIN {
+            return Ok(BERACHAIN_DEV.clone());
+        }
+        if let Some(chain) = NAMED_CHAINS.iter().find(|chain| chain.name == s) {
+            return chain.chain_spec().map(Arc::new)
+        }
+        Ok(Arc::new(BerachainChainSpec::try_from(parse_genesis(s)?)?))
     }
 }
 
 impl From<Genesis> for BerachainChainSpec {
-    /// Intentionally panics if required fields are missing from genesis or invalid.
+    /// Panics if the genesis is not a valid Berachain genesis.
+    ///
+    /// Deprecated: use [`BerachainChainSpec::try_from`], which returns the violated invariant as a
+    /// [`BerachainChainSpecError`].
     fn from(genesis: Genesis) -> Self {
+        Self::try_from(genesis).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl TryFrom<Genesis> for BerachainChainSpec {
+    type Error = BerachainChainSpecError;
+
+    fn try_from(genesis: Genesis) -> Result<Self, Self::Error> {
         let berachain_genesis_config =
-            BerachainGenesisConfig::try_from(&genesis.config.extra_fields).unwrap_or_else(|e| {
-                panic!("Failed to parse berachain genesis config: {e}. Please ensure the genesis file contains a valid 'berachain' configuration section with Prague1 settings");
-            });
+            BerachainGenesisConfig::try_from(&genesis.config.extra_fields)?;
 
         // Berachain networks must start with Cancun at genesis
         if genesis.config.cancun_time != Some(0) {
-            panic!(
-                "Berachain networks require {} hardfork at genesis (time = 0)",
-                EthereumHardfork::Cancun
-            );
+            return Err(BerachainChainSpecError::CancunNotAtGenesis)
         }
 
         // All pre-Cancun forks must be at genesis (block 0)
@@ -211,10 +435,8 @@ impl From<Genesis> for BerachainChainSpec {
 
         for (hardfork, block) in pre_cancun_forks {
             match block {
-                Some(block_num) if block_num != 0 => {
-                    panic!(
-                        "Berachain networks require {hardfork} hardfork at genesis (block 0), got block {block_num}"
-                    );
+                Some(block) if block != 0 => {
+                    return Err(BerachainChainSpecError::ForkNotAtGenesis { hardfork, block })
                 }
                 _ => {}
             }
@@ -222,11 +444,8 @@ impl From<Genesis> for BerachainChainSpec {
 
         // Shanghai must be at genesis if configured
         match genesis.config.shanghai_time {
-            Some(shanghai_time) if shanghai_time != 0 => {
-                panic!(
-                    "Berachain networks require {} hardfork at genesis (time = 0), got time {shanghai_time}",
-                    EthereumHardfork::Shanghai
-                );
+            Some(time) if time != 0 => {
+                return Err(BerachainChainSpecError::ShanghaiNotAtGenesis(time))
             }
             _ => {}
         }
@@ -234,28 +453,36 @@ impl From<Genesis> for BerachainChainSpec {
         // Validate Prague1 comes after Prague if both are configured
         match (genesis.config.prague_time, berachain_genesis_config.prague1.time) {
             (Some(prague_time), prague1_time) if prague1_time < prague_time => {
-                panic!(
-                    "Prague1 hardfork must activate at or after Prague hardfork. Prague time: {prague_time}, Prague1 time: {prague1_time}. Check that Prague1 time is not malformed (should be a valid Unix timestamp).",
-                );
+                return Err(BerachainChainSpecError::Prague1BeforePrague {
+                    prague_time,
+                    prague1_time,
+                })
+            }
+            _ => {}
+        }
+
+        // Validate Prague2 comes after Prague1 if it is scheduled
+        match (berachain_genesis_config.prague1.time, berachain_genesis_config.prague2) {
+            (prague1_time, Some(prague2)) if prague2.time < prague1_time => {
+                return Err(BerachainChainSpecError::Prague2BeforePrague1 {
+                    prague1_time,
+                    prague2_time: prague2.time,
+                })
             }
             _ => {}
         }
 
         // Berachain networks don't support proof-of-work or non-genesis merge
-        if let Some(ttd) = genesis.config.terminal_total_difficulty {
-            if !ttd.is_zero() {
-                panic!(
-                    "Berachain networks require terminal total difficulty of 0 (merge at genesis)"
-                );
+        match genesis.config.terminal_total_difficulty {
+            Some(ttd) if !ttd.is_zero() => {
+                return Err(BerachainChainSpecError::NonZeroTerminalTotalDifficulty(ttd))
             }
-        } else {
-            panic!("Berachain networks require terminal_total_difficulty to be set to 0");
+            Some(_) => {}
+            None => return Err(BerachainChainSpecError::MissingTerminalTotalDifficulty),
         }
         match genesis.config.merge_netsplit_block {
-            Some(merge_block) if merge_block != 0 => {
-                panic!(
-                    "Berachain networks require merge at genesis (block 0), got block {merge_block}"
-                );
+            Some(block) if block != 0 => {
+                return Err(BerachainChainSpecError::MergeNotAtGenesis(block))
             }
             _ => {}
         }
@@ -293,6 +520,10 @@ impl From<Genesis> for BerachainChainSpec {
             BerachainHardfork::Prague1.boxed(),
             ForkCondition::Timestamp(berachain_genesis_config.prague1.time),
         ));
+        if let Some(prague2) = berachain_genesis_config.prague2 {
+            hardforks
+                .push((BerachainHardfork::Prague2.boxed(), ForkCondition::Timestamp(prague2.time)));
+        }
 
         let paris_block_and_final_difficulty =
             Some((0, genesis.config.terminal_total_difficulty.unwrap_or_default()));
@@ -315,8 +546,10 @@ impl From<Genesis> for BerachainChainSpec {
 
         let hardforks = ChainHardforks::new(hardforks);
 
-        // Create base fee parameters based on Prague1 configuration
-        let base_fee_params = if berachain_genesis_config.prague1.time == 0 {
+        // Create base fee parameters based on Prague1 and Prague2 configuration
+        let base_fee_params = if berachain_genesis_config.prague1.time == 0 &&
+            berachain_genesis_config.prague2.is_none()
+        {
             // Prague1 active at genesis - use constant params with Berachain's denominator
             BaseFeeParamsKind::Constant(BaseFeeParams {
                 max_change_denominator: berachain_genesis_config
@@ -325,8 +558,8 @@ impl From<Genesis> for BerachainChainSpec {
                 elasticity_multiplier: 2, // Standard Ethereum value
             })
         } else {
-            // Prague1 activates later - use variable params
-            let fork_base_fee_params = vec![
+            // Prague1 activates later or Prague2 is scheduled - use variable params
+            let mut fork_base_fee_params = vec![
                 // Pre-Prague1: standard Ethereum params
                 (
                     EthereumHardfork::London.boxed(),
@@ -346,6 +579,16 @@ impl From<Genesis> for BerachainChainSpec {
                     },
                 ),
             ];
+            // Post-Prague2: Prague2 params
+            if let Some(prague2) = berachain_genesis_config.prague2 {
+                fork_base_fee_params.push((
+                    BerachainHardfork::Prague2.boxed(),
+                    BaseFeeParams {
+                        max_change_denominator: prague2.base_fee_change_denominator,
+                        elasticity_multiplier: 2,
+                    },
+                ));
+            }
             BaseFeeParamsKind::Variable(fork_base_fee_params.into())
         };
 
@@ -368,19 +611,23 @@ impl From<Genesis> for BerachainChainSpec {
         if is_prague1_at_genesis {
             genesis_header.prev_proposer_pubkey = Some(BlsPublicKey::ZERO);
         }
-        Self {
+        Ok(Self {
             inner,
             genesis_header,
             pol_contract_address: berachain_genesis_config.prague1.pol_distributor_address,
             prague1_minimum_base_fee: berachain_genesis_config.prague1.minimum_base_fee_wei,
-        }
+            pol_tx_gas_limit: berachain_genesis_config.prague1.pol_tx_gas_limit,
+            prague2: berachain_genesis_config.prague2,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transaction::pol::POL_TX_GAS_LIMIT;
     use alloy_genesis::Genesis;
+    use alloy_primitives::address;
     use jsonrpsee_core::__reexports::serde_json::json;
 
     #[test]
@@ -419,7 +666,7 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let chain_spec = BerachainChainSpec::from(genesis);
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
 
         // Should create a valid chain spec
         assert_eq!(
@@ -448,7 +695,7 @@ mod tests {
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
 
-        let chain_spec = BerachainChainSpec::from(genesis);
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
 
         // At genesis, should use Berachain's base fee params
         let params = chain_spec.base_fee_params_at_timestamp(0);
@@ -481,7 +728,7 @@ mod tests {
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
 
-        let chain_spec = BerachainChainSpec::from(genesis);
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
 
         // Before Prague1, should use standard Ethereum params
         let params = chain_spec.base_fee_params_at_timestamp(999);
@@ -519,7 +766,7 @@ mod tests {
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
 
-        let chain_spec = BerachainChainSpec::from(genesis);
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
 
         let params = chain_spec.base_fee_params_at_timestamp(0);
         assert_eq!(params.max_change_denominator, 100);
@@ -527,7 +774,6 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Failed to parse berachain genesis config")]
     fn test_base_fee_params_missing_berachain_config() {
         // Test panic when berachain config is missing
         let mut genesis = Genesis::default();
@@ -536,11 +782,11 @@ mod tests {
         genesis.config.terminal_total_difficulty = Some(U256::ZERO); // Required for Berachain
         // No berachain config in extra_fields - should panic
 
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(err.contains("Failed to parse berachain genesis config"), "{err}");
     }
 
     #[test]
-    #[should_panic(expected = "Failed to parse berachain genesis config")]
     fn test_missing_pol_distributor_address() {
         // Test panic when polDistributorAddress is missing
         let mut genesis = Genesis::default();
@@ -559,7 +805,8 @@ mod tests {
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
 
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(err.contains("Failed to parse berachain genesis config"), "{err}");
     }
 
     #[test]
@@ -581,7 +828,7 @@ mod tests {
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
 
-        let chain_spec = BerachainChainSpec::from(genesis);
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
 
         // Check Prague1 activation
         assert!(!chain_spec.is_prague1_active_at_timestamp(1499));
@@ -610,7 +857,7 @@ mod tests {
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
 
-        let chain_spec = BerachainChainSpec::from(genesis);
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
 
         // Create a parent block before Prague1
         let parent_header = BerachainHeader {
@@ -633,12 +880,16 @@ mod tests {
         // After Prague1, base fee should be at least 10 gwei
         let next_base_fee = chain_spec.next_block_base_fee(&parent_header, 0);
         assert_eq!(next_base_fee.unwrap(), prague1_base_fee);
+        assert!(chain_spec.is_base_fee_raised_to_minimum_after(&parent_header));
+
+        // Base fees above the minimum and base fees before Prague1 are not raised
+        let above = BerachainHeader { base_fee_per_gas: Some(20_000_000_000), ..parent_header };
+        assert!(!chain_spec.is_base_fee_raised_to_minimum_after(&above));
+        let before = BerachainHeader { timestamp: 999, ..parent_header };
+        assert!(!chain_spec.is_base_fee_raised_to_minimum_after(&before));
     }
 
     #[test]
-    #[should_panic(
-        expected = "Berachain networks require terminal_total_difficulty to be set to 0"
-    )]
     fn test_panic_on_missing_ttd() {
         let mut genesis = Genesis::default();
         genesis.config.cancun_time = Some(0);
@@ -655,11 +906,14 @@ mod tests {
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
         // No terminal_total_difficulty set
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains("Berachain networks require terminal_total_difficulty to be set to 0"),
+            "{err}"
+        );
     }
 
     #[test]
-    #[should_panic(expected = "Berachain networks require Cancun hardfork at genesis (time = 0)")]
     fn test_panic_on_missing_cancun() {
         let mut genesis = Genesis::default();
         genesis.config.terminal_total_difficulty = Some(U256::ZERO);
@@ -676,11 +930,14 @@ mod tests {
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
         // No cancun_time set - should panic
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains("Berachain networks require Cancun hardfork at genesis (time = 0)"),
+            "{err}"
+        );
     }
 
     #[test]
-    #[should_panic(expected = "Berachain networks require Cancun hardfork at genesis (time = 0)")]
     fn test_panic_on_cancun_not_at_genesis() {
         let mut genesis = Genesis::default();
         genesis.config.cancun_time = Some(100);
@@ -697,13 +954,14 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains("Berachain networks require Cancun hardfork at genesis (time = 0)"),
+            "{err}"
+        );
     }
 
     #[test]
-    #[should_panic(
-        expected = "Berachain networks require London hardfork at genesis (block 0), got block 5"
-    )]
     fn test_panic_on_london_not_at_genesis() {
         let mut genesis = Genesis::default();
         genesis.config.cancun_time = Some(0);
@@ -721,13 +979,16 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains(
+                "Berachain networks require London hardfork at genesis (block 0), got block 5"
+            ),
+            "{err}"
+        );
     }
 
     #[test]
-    #[should_panic(
-        expected = "Berachain networks require Shanghai hardfork at genesis (time = 0), got time 500"
-    )]
     fn test_panic_on_shanghai_not_at_genesis() {
         let mut genesis = Genesis::default();
         genesis.config.cancun_time = Some(0);
@@ -745,11 +1006,16 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains(
+                "Berachain networks require Shanghai hardfork at genesis (time = 0), got time 500"
+            ),
+            "{err}"
+        );
     }
 
     #[test]
-    #[should_panic(expected = "Prague1 hardfork must activate at or after Prague hardfork")]
     fn test_panic_on_prague1_before_prague() {
         let mut genesis = Genesis::default();
         genesis.config.cancun_time = Some(0);
@@ -766,7 +1032,11 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains("Prague1 hardfork must activate at or after Prague hardfork"),
+            "{err}"
+        );
     }
 
     #[test]
@@ -787,14 +1057,130 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let chain_spec = BerachainChainSpec::from(genesis);
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
         assert!(chain_spec.is_prague1_active_at_timestamp(2000));
     }
 
     #[test]
-    #[should_panic(
-        expected = "Berachain networks require terminal total difficulty of 0 (merge at genesis)"
-    )]
+    fn test_prague2_hardfork_activation() {
+        let mut genesis = Genesis::default();
+        genesis.config.cancun_time = Some(0);
+        genesis.config.terminal_total_difficulty = Some(U256::ZERO);
+        let extra_fields_json = json!({
+            "berachain": {
+                "prague1": {
+                    "time": 0,
+                    "baseFeeChangeDenominator": 48,
+                    "minimumBaseFeeWei": 1000000000,
+                    "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+                },
+                "prague2": {
+                    "time": 3000,
+                    "baseFeeChangeDenominator": 64,
+                    "minimumBaseFeeWei": 1000000000,
+                    "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+                }
+            }
+        });
+        genesis.config.extra_fields =
+            reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
+
+        assert!(!chain_spec.is_prague2_active_at_timestamp(2999));
+        assert!(chain_spec.is_prague2_active_at_timestamp(3000));
+        assert_eq!(chain_spec.base_fee_params_at_timestamp(2999).max_change_denominator, 48);
+        assert_eq!(chain_spec.base_fee_params_at_timestamp(3000).max_change_denominator, 64);
+
+        // Prague2 is a fork of its own in the fork id
+        let prague1 = chain_spec.fork_id(&Head { timestamp: 2999, ..Default::default() });
+        let prague2 = chain_spec.fork_id(&Head { timestamp: 3000, ..Default::default() });
+        assert_eq!(prague1.next, 3000);
+        assert_ne!(prague1.hash, prague2.hash);
+    }
+
+    #[test]
+    fn test_prague2_replaces_prague1_settings() {
+        let mut genesis = Genesis::default();
+        genesis.config.cancun_time = Some(0);
+        genesis.config.terminal_total_difficulty = Some(U256::ZERO);
+        let extra_fields_json = json!({
+            "berachain": {
+                "prague1": {
+                    "time": 0,
+                    "baseFeeChangeDenominator": 48,
+                    "minimumBaseFeeWei": 1000000000,
+                    "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+                },
+                "prague2": {
+                    "time": 3000,
+                    "baseFeeChangeDenominator": 48,
+                    "minimumBaseFeeWei": 5000000000u64,
+                    "polDistributorAddress": "0x4200000000000000000000000000000000000043",
+                    "polTxGasLimit": 5000000
+                }
+            }
+        });
+        genesis.config.extra_fields =
+            reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
+
+        assert_eq!(chain_spec.minimum_base_fee_at_timestamp(2999), Some(1_000_000_000));
+        assert_eq!(chain_spec.minimum_base_fee_at_timestamp(3000), Some(5_000_000_000));
+        assert_eq!(
+            chain_spec.pol_contract_at_timestamp(2999),
+            address!("0x4200000000000000000000000000000000000042")
+        );
+        assert_eq!(
+            chain_spec.pol_contract_at_timestamp(3000),
+            address!("0x4200000000000000000000000000000000000043")
+        );
+        assert_eq!(chain_spec.pol_tx_gas_limit_at_timestamp(2999), POL_TX_GAS_LIMIT);
+        assert_eq!(chain_spec.pol_tx_gas_limit_at_timestamp(3000), 5_000_000);
+
+        // Parents at their gas target keep the base fee, raised to the minimum of their fork
+        let parent = |timestamp| BerachainHeader {
+            timestamp,
+            gas_used: 15_000_000,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(2_000_000_000),
+            ..Default::default()
+        };
+        assert_eq!(chain_spec.next_base_fee_after(&parent(2999)), Some(2_000_000_000));
+        assert_eq!(chain_spec.next_base_fee_after(&parent(3000)), Some(5_000_000_000));
+        assert!(chain_spec.is_base_fee_raised_to_minimum_after(&parent(3000)));
+    }
+
+    #[test]
+    fn test_panic_on_prague2_before_prague1() {
+        let mut genesis = Genesis::default();
+        genesis.config.cancun_time = Some(0);
+        genesis.config.terminal_total_difficulty = Some(U256::ZERO);
+        let extra_fields_json = json!({
+            "berachain": {
+                "prague1": {
+                    "time": 2000,
+                    "baseFeeChangeDenominator": 48,
+                    "minimumBaseFeeWei": 1000000000,
+                    "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+                },
+                "prague2": {
+                    "time": 1000,
+                    "baseFeeChangeDenominator": 48,
+                    "minimumBaseFeeWei": 1000000000,
+                    "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+                }
+            }
+        });
+        genesis.config.extra_fields =
+            reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains("Prague2 hardfork must activate at or after Prague1 hardfork"),
+            "{err}"
+        );
+    }
+
+    #[test]
     fn test_panic_on_non_zero_ttd() {
         let mut genesis = Genesis::default();
         genesis.config.cancun_time = Some(0);
@@ -811,11 +1197,16 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains(
+                "Berachain networks require terminal total difficulty of 0 (merge at genesis)"
+            ),
+            "{err}"
+        );
     }
 
     #[test]
-    #[should_panic(expected = "Berachain networks require merge at genesis (block 0), got block 5")]
     fn test_panic_on_merge_not_at_genesis() {
         let mut genesis = Genesis::default();
         genesis.config.cancun_time = Some(0);
@@ -833,13 +1224,14 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains("Berachain networks require merge at genesis (block 0), got block 5"),
+            "{err}"
+        );
     }
 
     #[test]
-    #[should_panic(
-        expected = "Berachain networks require Dao hardfork at genesis (block 0), got block 5"
-    )]
     fn test_panic_on_dao_fork_not_at_genesis() {
         let mut genesis = Genesis::default();
         genesis.config.cancun_time = Some(0);
@@ -857,11 +1249,16 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(
+            err.contains(
+                "Berachain networks require Dao hardfork at genesis (block 0), got block 5"
+            ),
+            "{err}"
+        );
     }
 
     #[test]
-    #[should_panic(expected = "Failed to parse berachain genesis config")]
     fn test_invalid_base_fee_denominator() {
         let mut genesis = Genesis::default();
         genesis.config.cancun_time = Some(0);
@@ -878,7 +1275,8 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let _chain_spec = BerachainChainSpec::from(genesis);
+        let err = BerachainChainSpec::try_from(genesis).unwrap_err().to_string();
+        assert!(err.contains("Failed to parse berachain genesis config"), "{err}");
     }
 
     #[test]
@@ -898,7 +1296,7 @@ mod tests {
         });
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        let chain_spec = BerachainChainSpec::from(genesis);
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
 
         let parent_header =
             BerachainHeader { timestamp: 0, base_fee_per_gas: None, ..Default::default() };
@@ -906,4 +1304,73 @@ mod tests {
         let result = chain_spec.next_block_base_fee(&parent_header, 0);
         assert!(result.is_none()); // Correctly returns None when parent has no base fee
     }
+
+    #[test]
+    fn test_dev_chain_spec() {
+        let chain_spec = BerachainChainSpecParser::parse(DEV_CHAIN).unwrap();
+
+        assert!(chain_spec.is_prague_active_at_timestamp(0));
+        assert!(chain_spec.is_prague1_active_at_timestamp(0));
+        assert_eq!(chain_spec.prague1_minimum_base_fee, 1_000_000_000);
+
+        // The standard dev account (first key of the `test test ... junk` mnemonic) is funded
+        let dev_account = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse::<Address>().unwrap();
+        assert!(
+            chain_spec.genesis().alloc.get(&dev_account).is_some_and(|a| a.balance > U256::ZERO)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse berachain genesis config")]
+    fn test_from_panics_on_invalid_genesis() {
+        let _chain_spec = BerachainChainSpec::from(Genesis::default());
+    }
+
+    #[test]
+    fn test_try_from_reports_the_violated_invariant() {
+        let mut genesis = BERACHAIN_DEV.genesis().clone();
+        genesis.config.berlin_block = Some(7);
+        assert!(matches!(
+            BerachainChainSpec::try_from(genesis),
+            Err(BerachainChainSpecError::ForkNotAtGenesis {
+                hardfork: EthereumHardfork::Berlin,
+                block: 7
+            })
+        ));
+    }
+
+    #[test]
+    fn test_named_chains() {
+        for name in ["mainnet", "bepolia"] {
+            let chain = NAMED_CHAINS.iter().find(|chain| chain.name == name).unwrap();
+            assert_eq!(named_chain(chain.chain_id), Some(chain));
+            let supported = BerachainChainSpecParser::SUPPORTED_CHAINS.contains(&name);
+            match chain.genesis {
+                Some(_) => {
+                    assert!(supported);
+                    let chain_spec = BerachainChainSpecParser::parse(name).unwrap();
+                    assert_eq!(chain_spec.chain_id(), chain.chain_id);
+                }
+                None => {
+                    assert!(!supported);
+                    let err = BerachainChainSpecParser::parse(name).unwrap_err().to_string();
+                    assert!(err.contains("--chain <FILE>"), "{err}");
+                }
+            }
+        }
+        assert!(named_chain(1).is_none());
+    }
+
+    #[test]
+    fn test_named_chain_bootnodes_parse() {
+        for chain in NAMED_CHAINS {
+            for enode in chain.bootnodes {
+                assert!(
+                    enode.parse::<reth_network_peers::NodeRecord>().is_ok(),
+                    "{}: invalid enode {enode}",
+                    chain.name
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,297 @@
+//! `bera-reth bench` commands
+//!
+//! `bench execute` re-executes already synced blocks on the state of their parents and reports
+//! throughput and per-block latency percentiles, so validators can compare hardware on the same
+//! blocks. Execution and the state root can be measured separately: the state root of a block is
+//! computed from its execution output, which is not timed in `state-root` mode. Nothing is written
+//! to the datadir.
+//!
+//! `bench profile` re-executes blocks with an
+//! [`OpcodeProfiler`](crate::node::evm::profiler::OpcodeProfiler) and writes the gas and time spent
+//! per opcode and per contract as `opcodes.csv` and `contracts.csv`.
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    node::{
+        BerachainNode,
+        evm::{config::BerachainEvmConfig, profiler::profile_blocks},
+    },
+};
+use alloy_consensus::BlockHeader;
+use alloy_eips::BlockHashOrNumber;
+use alloy_primitives::BlockNumber;
+use clap::{Parser, Subcommand, ValueEnum};
+use reth::{
+    providers::{
+        BlockNumReader, BlockReader, ChainSpecProvider, HashedPostStateProvider, StateRootProvider,
+        TransactionVariant,
+    },
+    revm::database::StateProviderDatabase,
+};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_evm::{ConfigureEvm, EthEvmFactory, execute::Executor};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// `bera-reth bench` command
+#[derive(Debug, Parser)]
+pub struct BenchCommand<C: ChainSpecParser> {
+    #[command(subcommand)]
+    command: BenchSubcommand<C>,
+}
+
+/// `bera-reth bench` subcommands
+#[derive(Debug, Subcommand)]
+pub enum BenchSubcommand<C: ChainSpecParser> {
+    /// Re-execute a range of synced blocks and report throughput and latency
+    Execute(BenchExecuteCommand<C>),
+    /// Profile gas and time per opcode and per contract over a range of synced blocks
+    Profile(BenchProfileCommand<C>),
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> BenchCommand<C> {
+    /// Execute `bench` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            BenchSubcommand::Execute(command) => command.execute().await,
+            BenchSubcommand::Profile(command) => command.execute().await,
+        }
+    }
+}
+
+/// Phases measured by `bench execute`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum BenchMode {
+    /// Block execution only.
+    Execution,
+    /// State root computation only.
+    StateRoot,
+    /// Execution and state root.
+    #[default]
+    Both,
+}
+
+/// `bera-reth bench execute` command
+#[derive(Debug, Parser)]
+pub struct BenchExecuteCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// First block to execute.
+    #[arg(long)]
+    from: BlockNumber,
+
+    /// Last block to execute (inclusive). Defaults to the best stored block.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+
+    /// Phases to measure.
+    #[arg(long, value_enum, default_value_t = BenchMode::Both)]
+    mode: BenchMode,
+}
+
+/// Throughput and latency of a phase.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseReport {
+    /// Time spent in the phase over all blocks, in seconds.
+    pub total_seconds: f64,
+    /// Gas processed per second, in billions.
+    pub ggas_per_second: f64,
+    /// Median time per block, in milliseconds.
+    pub p50_ms: f64,
+    /// 90th percentile of the time per block, in milliseconds.
+    pub p90_ms: f64,
+    /// 99th percentile of the time per block, in milliseconds.
+    pub p99_ms: f64,
+    /// Longest time per block, in milliseconds.
+    pub max_ms: f64,
+}
+
+impl PhaseReport {
+    /// Summarizes the per-block `durations` of blocks using `gas` in total.
+    fn new(mut durations: Vec<Duration>, gas: u64) -> Self {
+        durations.sort_unstable();
+        let total: Duration = durations.iter().sum();
+        let ms = |p: f64| percentile(&durations, p).as_secs_f64() * 1000.0;
+        Self {
+            total_seconds: total.as_secs_f64(),
+            ggas_per_second: if total.is_zero() {
+                0.0
+            } else {
+                gas as f64 / total.as_secs_f64() / 1e9
+            },
+            p50_ms: ms(50.0),
+            p90_ms: ms(90.0),
+            p99_ms: ms(99.0),
+            max_ms: ms(100.0),
+        }
+    }
+}
+
+/// Report printed by `bench execute`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchReport {
+    /// First executed block.
+    pub from: BlockNumber,
+    /// Last executed block.
+    pub to: BlockNumber,
+    /// Gas used by the executed blocks.
+    pub gas_used: u64,
+    /// Execution, if measured.
+    pub execution: Option<PhaseReport>,
+    /// State root computation, if measured.
+    pub state_root: Option<PhaseReport>,
+    /// Blocks whose computed state root did not match their header.
+    pub state_root_mismatches: Vec<BlockNumber>,
+}
+
+/// Returns the `p`th percentile of the sorted `durations` by the nearest-rank method.
+fn percentile(durations: &[Duration], p: f64) -> Duration {
+    if durations.is_empty() {
+        return Duration::ZERO
+    }
+    let rank = (p / 100.0 * durations.len() as f64).ceil() as usize;
+    durations[rank.clamp(1, durations.len()) - 1]
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> BenchExecuteCommand<C> {
+    /// Execute `bench execute` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let Environment { provider_factory, .. } =
+            self.env.init::<BerachainNode>(AccessRights::RO)?;
+        let evm_config = BerachainEvmConfig::new_with_evm_factory(
+            provider_factory.chain_spec(),
+            EthEvmFactory::default(),
+        );
+        let to = match self.to {
+            Some(to) => to,
+            None => provider_factory.best_block_number()?,
+        };
+        eyre::ensure!(self.from > 0 && self.from <= to, "invalid block range {}..={to}", self.from);
+        info!(target: "reth::cli", from = self.from, to, mode = ?self.mode, "Executing blocks");
+
+        let measure_execution = self.mode != BenchMode::StateRoot;
+        let measure_state_root = self.mode != BenchMode::Execution;
+        let mut gas_used = 0;
+        let mut execution = Vec::new();
+        let mut state_root = Vec::new();
+        let mut state_root_mismatches = Vec::new();
+        for number in self.from..=to {
+            let block = provider_factory
+                .recovered_block(BlockHashOrNumber::Number(number), TransactionVariant::WithHash)?
+                .ok_or_else(|| eyre::eyre!("block {number} not found"))?;
+            let state = provider_factory.history_by_block_number(number - 1)?;
+
+            let started = Instant::now();
+            let output = evm_config.executor(StateProviderDatabase::new(&state)).execute(&block)?;
+            if measure_execution {
+                execution.push(started.elapsed());
+            }
+            gas_used += block.header().gas_used();
+
+            if measure_state_root {
+                let started = Instant::now();
+                let root = state.state_root(state.hashed_post_state(&output.state))?;
+                state_root.push(started.elapsed());
+                if root != block.header().state_root() {
+                    warn!(target: "reth::cli", number, %root, "State root mismatch");
+                    state_root_mismatches.push(number);
+                }
+            }
+        }
+
+        let report = BenchReport {
+            from: self.from,
+            to,
+            gas_used,
+            execution: measure_execution.then(|| PhaseReport::new(execution, gas_used)),
+            state_root: measure_state_root.then(|| PhaseReport::new(state_root, gas_used)),
+            state_root_mismatches,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        Ok(())
+    }
+}
+
+/// `bera-reth bench profile` command
+#[derive(Debug, Parser)]
+pub struct BenchProfileCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// First block to profile.
+    #[arg(long)]
+    from: BlockNumber,
+
+    /// Last block to profile (inclusive). Defaults to the best stored block.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+
+    /// Directory to write `opcodes.csv` and `contracts.csv` to.
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    out: PathBuf,
+
+    /// Number of contracts to print, by decreasing gas.
+    #[arg(long, default_value_t = 20)]
+    top: usize,
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> BenchProfileCommand<C> {
+    /// Execute `bench profile` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let Environment { provider_factory, .. } =
+            self.env.init::<BerachainNode>(AccessRights::RO)?;
+        let evm_config = BerachainEvmConfig::new_with_evm_factory(
+            provider_factory.chain_spec(),
+            EthEvmFactory::default(),
+        );
+        let to = match self.to {
+            Some(to) => to,
+            None => provider_factory.best_block_number()?,
+        };
+        info!(target: "reth::cli", from = self.from, to, "Profiling blocks");
+
+        let mut profile = profile_blocks(&provider_factory, &evm_config, self.from, to)?;
+        std::fs::create_dir_all(&self.out)?;
+        std::fs::write(self.out.join("opcodes.csv"), profile.opcodes_csv())?;
+        std::fs::write(self.out.join("contracts.csv"), profile.contracts_csv())?;
+        info!(target: "reth::cli", out = %self.out.display(), "Wrote profile");
+
+        profile.contracts.truncate(self.top);
+        println!("{}", serde_json::to_string_pretty(&profile)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_and_throughput() {
+        let mut durations: Vec<_> = (1..=100).rev().map(Duration::from_millis).collect();
+        durations.sort_unstable();
+        assert_eq!(percentile(&durations, 50.0), Duration::from_millis(50));
+        assert_eq!(percentile(&durations, 99.0), Duration::from_millis(99));
+        assert_eq!(percentile(&durations, 100.0), Duration::from_millis(100));
+
+        let report = PhaseReport::new(
+            vec![Duration::from_millis(1500), Duration::from_millis(500)],
+            4_000_000_000,
+        );
+        assert_eq!(report.total_seconds, 2.0);
+        assert_eq!(report.ggas_per_second, 2.0);
+        assert_eq!(report.p50_ms, 500.0);
+        assert_eq!(report.max_ms, 1500.0);
+
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+        assert_eq!(PhaseReport::new(Vec::new(), 0).ggas_per_second, 0.0);
+    }
+}
@@ -0,0 +1,185 @@
+//! `bera-reth build-block` command
+//!
+//! Runs [`BerachainPayloadBuilder`] once on top of a stored block and prints the result instead of
+//! handing it to a consensus client. Transactions are taken from a file and go through a temporary
+//! transaction pool, so they are validated and ordered like on a live node. Nothing is written to
+//! the datadir.
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    engine::{
+        builder::BerachainPayloadBuilder,
+        payload::{BerachainPayloadAttributes, BerachainPayloadBuilderAttributes},
+    },
+    node::{BerachainNode, evm::config::BerachainEvmConfig},
+    pool::transaction::BerachainPooledTransaction,
+    primitives::BerachainHeader,
+    transaction::BerachainTxEnvelope,
+};
+use alloy_consensus::BlockHeader;
+use alloy_eips::Decodable2718;
+use alloy_primitives::{B256, Bytes, U256};
+use clap::Parser;
+use reth::{
+    api::BuiltPayload,
+    providers::{BlockNumReader, HeaderProvider, providers::BlockchainProvider},
+    tasks::TaskManager,
+    transaction_pool::{
+        EthTransactionPool, Pool, PoolConfig, TransactionOrigin, TransactionPool,
+        TransactionValidationTaskExecutor, blobstore::InMemoryBlobStore,
+    },
+};
+use reth_basic_payload_builder::{BuildArguments, BuildOutcome, PayloadBuilder, PayloadConfig};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_ethereum_payload_builder::EthereumBuilderConfig;
+use reth_evm::EthEvmFactory;
+use reth_payload_primitives::PayloadBuilderAttributes;
+use reth_primitives_traits::{SealedHeader, SignedTransaction};
+use serde::Serialize;
+use std::{path::PathBuf, sync::Arc};
+use tracing::info;
+
+/// `bera-reth build-block` command
+#[derive(Debug, Parser)]
+pub struct BuildBlockCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// Block to build on. Defaults to the latest block.
+    #[arg(long, value_name = "HASH")]
+    parent: Option<B256>,
+
+    /// Payload attributes as sent in `engine_forkchoiceUpdated`, as JSON.
+    #[arg(long, value_name = "FILE")]
+    attributes: PathBuf,
+
+    /// JSON array of EIP-2718 encoded transactions offered to the builder. They are validated
+    /// against the latest state.
+    #[arg(long, value_name = "FILE")]
+    transactions: Option<PathBuf>,
+
+    /// Target gas limit of the block. Defaults to the parent's gas limit.
+    #[arg(long)]
+    gas_limit: Option<u64>,
+}
+
+/// A transaction rejected by the temporary pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RejectedTransaction {
+    /// Transaction hash.
+    pub hash: B256,
+    /// Reason given by the pool.
+    pub error: String,
+}
+
+/// Block produced by `build-block`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuiltBlockReport {
+    /// Hash of the built block.
+    pub hash: B256,
+    /// Header of the built block.
+    pub header: BerachainHeader,
+    /// Hashes of the included transactions, starting with the PoL transaction after Prague1.
+    pub transactions: Vec<B256>,
+    /// Gas used by the block.
+    pub gas_used: u64,
+    /// Priority fees paid to the fee recipient.
+    pub fees: U256,
+    /// Transactions accepted by the pool but left out of the block.
+    pub excluded: Vec<B256>,
+    /// Transactions rejected by the pool.
+    pub rejected: Vec<RejectedTransaction>,
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> BuildBlockCommand<C> {
+    /// Execute `build-block` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let Environment { provider_factory, .. } =
+            self.env.init::<BerachainNode>(AccessRights::RO)?;
+        let client = BlockchainProvider::new(provider_factory)?;
+        let chain_spec = self.env.chain.clone();
+
+        let parent = match self.parent {
+            Some(hash) => SealedHeader::new(
+                client.header(&hash)?.ok_or_else(|| eyre::eyre!("block {hash} not found"))?,
+                hash,
+            ),
+            None => {
+                let number = client.best_block_number()?;
+                client
+                    .sealed_header(number)?
+                    .ok_or_else(|| eyre::eyre!("block {number} not found"))?
+            }
+        };
+        let attributes: BerachainPayloadAttributes =
+            serde_json::from_slice(&std::fs::read(&self.attributes)?)?;
+        let transactions: Vec<Bytes> = match &self.transactions {
+            Some(path) => serde_json::from_slice(&std::fs::read(path)?)?,
+            None => Vec::new(),
+        };
+
+        let tasks = TaskManager::current();
+        let blob_store = InMemoryBlobStore::default();
+        let validator = TransactionValidationTaskExecutor::eth_builder(client.clone())
+            .with_head_timestamp(parent.timestamp())
+            .build_with_tasks(tasks.executor(), blob_store.clone());
+        let pool: EthTransactionPool<_, _, BerachainPooledTransaction> =
+            Pool::eth_pool(validator, blob_store, PoolConfig::default());
+
+        let mut accepted = Vec::new();
+        let mut rejected = Vec::new();
+        for raw in transactions {
+            let tx = BerachainTxEnvelope::decode_2718(&mut raw.as_ref())?;
+            let hash = *tx.tx_hash();
+            let tx =
+                tx.try_into_recovered().map_err(|_| eyre::eyre!("invalid signature {hash}"))?;
+            match pool.add_consensus_transaction(tx, TransactionOrigin::Local).await {
+                Ok(_) => accepted.push(hash),
+                Err(err) => rejected.push(RejectedTransaction { hash, error: err.to_string() }),
+            }
+        }
+
+        info!(
+            target: "reth::cli",
+            parent = %parent.hash(),
+            number = parent.number() + 1,
+            transactions = accepted.len(),
+            "Building block"
+        );
+        let gas_limit = self.gas_limit.unwrap_or(parent.gas_limit());
+        let builder = BerachainPayloadBuilder::new(
+            client,
+            pool,
+            BerachainEvmConfig::new_with_evm_factory(chain_spec, EthEvmFactory::default()),
+            EthereumBuilderConfig::new().with_gas_limit(gas_limit),
+        );
+        let attributes = BerachainPayloadBuilderAttributes::try_new(parent.hash(), attributes, 3)?;
+        let config = PayloadConfig::new(Arc::new(parent), attributes);
+        let payload = match builder.try_build(BuildArguments::new(
+            Default::default(),
+            config,
+            Default::default(),
+            None,
+        ))? {
+            BuildOutcome::Better { payload, .. } | BuildOutcome::Freeze(payload) => payload,
+            outcome => eyre::bail!("payload builder returned no payload: {outcome:?}"),
+        };
+
+        let block = payload.block();
+        let included: Vec<B256> =
+            block.body().transactions.iter().map(|tx| *tx.tx_hash()).collect();
+        let report = BuiltBlockReport {
+            hash: block.hash(),
+            header: block.header().clone(),
+            gas_used: block.header().gas_used(),
+            fees: payload.fees(),
+            excluded: accepted.into_iter().filter(|hash| !included.contains(hash)).collect(),
+            transactions: included,
+            rejected,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+//! `bera-reth db-check` command
+
+use crate::{
+    chainspec::BerachainChainSpec, consensus::BerachainBeaconConsensus, node::BerachainNode,
+    primitives::BerachainBlock, transaction::BerachainTxType,
+};
+use alloy_consensus::{BlockHeader, TxReceipt, proofs::calculate_receipt_root};
+use alloy_primitives::{BlockNumber, Bloom};
+use clap::Parser;
+use reth::{
+    consensus::Consensus,
+    providers::{
+        BlockHashReader, BlockNumReader, BlockReader, ChainSpecProvider, DatabaseProviderFactory,
+        ReceiptProvider, StaticFileProviderFactory, UnifiedStorageWriter,
+    },
+};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_ethereum_primitives::Receipt;
+use reth_primitives_traits::SealedBlock;
+use std::{fmt, ops::RangeInclusive};
+use tracing::{info, warn};
+
+/// A problem found while checking a single block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockIssue {
+    /// Header or body is missing although the block is below the best block.
+    Missing,
+    /// The stored canonical hash does not match the hash of the stored header.
+    HeaderHashMismatch,
+    /// Pre-execution validation failed (transactions root, withdrawals root, PoL placement, ...).
+    Body(String),
+    /// Receipts are missing (and not expected to be pruned).
+    MissingReceipts,
+    /// The receipts root recomputed from stored receipts does not match the header.
+    ReceiptsRootMismatch,
+    /// The logs bloom recomputed from stored receipts does not match the header.
+    LogsBloomMismatch,
+}
+
+impl fmt::Display for BlockIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "block missing"),
+            Self::HeaderHashMismatch => write!(f, "canonical hash does not match stored header"),
+            Self::Body(err) => write!(f, "invalid body: {err}"),
+            Self::MissingReceipts => write!(f, "receipts missing"),
+            Self::ReceiptsRootMismatch => write!(f, "receipts root mismatch"),
+            Self::LogsBloomMismatch => write!(f, "logs bloom mismatch"),
+        }
+    }
+}
+
+/// `bera-reth db-check` command
+///
+/// Walks stored headers, bodies and receipts and re-derives everything the header commits to:
+/// transactions root, withdrawals root, receipts root, logs bloom and, for Prague1 blocks, the PoL
+/// transaction placement. Corrupted blocks are merged into ranges and reported, together with the
+/// `stage unwind` invocation that drops them.
+///
+/// With `--repair`, the database is unwound to the last block below the first corrupted range
+/// instead, removing the blocks above it and reverting their state changes as an engine reorg
+/// does. The restarted node then downloads and executes the dropped blocks again.
+#[derive(Debug, Parser)]
+pub struct DbCheckCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// First block to check.
+    #[arg(long, default_value_t = 1)]
+    from: BlockNumber,
+
+    /// Last block to check (inclusive). Defaults to the best stored block.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+
+    /// Skip receipt checks, e.g. on nodes that prune receipts.
+    #[arg(long)]
+    skip_receipts: bool,
+
+    /// Unwind to the last good block below the first corrupted range.
+    #[arg(long)]
+    repair: bool,
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> DbCheckCommand<C> {
+    /// Execute `db-check` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let access = if self.repair { AccessRights::RW } else { AccessRights::RO };
+        let Environment { provider_factory, .. } = self.env.init::<BerachainNode>(access)?;
+        let consensus = BerachainBeaconConsensus::new(provider_factory.chain_spec());
+        let provider = provider_factory.provider()?;
+
+        let to = match self.to {
+            Some(to) => to,
+            None => provider.best_block_number()?,
+        };
+        eyre::ensure!(self.from <= to, "--from ({}) must not exceed --to ({to})", self.from);
+
+        info!(target: "reth::cli", from = self.from, to, "Checking stored blocks");
+
+        let mut corrupted: Vec<RangeInclusive<BlockNumber>> = Vec::new();
+        let mut issues = 0usize;
+
+        for number in self.from..=to {
+            let Some(issue) = self.check_block(&provider, &consensus, number)? else {
+                if number % 100_000 == 0 {
+                    info!(target: "reth::cli", number, issues, "Checked blocks");
+                }
+                continue
+            };
+
+            issues += 1;
+            warn!(target: "reth::cli", number, %issue, "Corrupted block");
+            match corrupted.last_mut() {
+                Some(range) if *range.end() + 1 == number => *range = *range.start()..=number,
+                _ => corrupted.push(number..=number),
+            }
+        }
+
+        if corrupted.is_empty() {
+            info!(target: "reth::cli", from = self.from, to, "No corruption found");
+            return Ok(());
+        }
+
+        if self.repair {
+            // Everything above the last good block goes, which covers the later ranges too
+            let unwind_to = corrupted[0].start().saturating_sub(1);
+            drop(provider);
+            warn!(target: "reth::cli", issues, ranges = corrupted.len(), unwind_to, "Unwinding");
+            let provider_rw = provider_factory.database_provider_rw()?;
+            let static_file_provider = provider_factory.static_file_provider();
+            UnifiedStorageWriter::from(&provider_rw, &static_file_provider)
+                .remove_blocks_above(unwind_to)?;
+            UnifiedStorageWriter::commit_unwind(provider_rw)?;
+            info!(target: "reth::cli", unwind_to, "Unwound, restart to sync the dropped blocks");
+            return Ok(())
+        }
+
+        for range in &corrupted {
+            warn!(
+                target: "reth::cli",
+                start = range.start(),
+                end = range.end(),
+                "Corrupted range, re-sync with `bera-reth stage unwind to-block {}` or `--repair`",
+                range.start().saturating_sub(1)
+            );
+        }
+
+        eyre::bail!("found {issues} corrupted blocks in {} ranges", corrupted.len())
+    }
+
+    fn check_block<P>(
+        &self,
+        provider: &P,
+        consensus: &BerachainBeaconConsensus,
+        number: BlockNumber,
+    ) -> eyre::Result<Option<BlockIssue>>
+    where
+        P: BlockReader<Block = BerachainBlock>
+            + BlockHashReader
+            + ReceiptProvider<Receipt = Receipt<BerachainTxType>>,
+    {
+        let (Some(block), Some(canonical_hash)) =
+            (provider.block(number.into())?, provider.block_hash(number)?)
+        else {
+            return Ok(Some(BlockIssue::Missing));
+        };
+
+        let block = SealedBlock::seal_slow(block);
+        if block.hash() != canonical_hash {
+            return Ok(Some(BlockIssue::HeaderHashMismatch));
+        }
+
+        if let Err(err) = consensus.validate_block_pre_execution(&block) {
+            return Ok(Some(BlockIssue::Body(err.to_string())));
+        }
+
+        if self.skip_receipts {
+            return Ok(None);
+        }
+
+        let Some(receipts) = provider.receipts_by_block(number.into())? else {
+            return Ok(Some(BlockIssue::MissingReceipts));
+        };
+
+        let receipts_with_bloom: Vec<_> = receipts.iter().map(TxReceipt::with_bloom_ref).collect();
+        if calculate_receipt_root(&receipts_with_bloom) != block.receipts_root() {
+            return Ok(Some(BlockIssue::ReceiptsRootMismatch));
+        }
+
+        let mut logs_bloom = Bloom::ZERO;
+        for receipt in &receipts_with_bloom {
+            logs_bloom.accrue_bloom(&receipt.logs_bloom);
+        }
+        if logs_bloom != block.logs_bloom() {
+            return Ok(Some(BlockIssue::LogsBloomMismatch));
+        }
+
+        Ok(None)
+    }
+}
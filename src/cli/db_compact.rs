@@ -0,0 +1,99 @@
+//! `bera-reth db-compact` command
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    node::{BerachainNode, config::BerachainConfig},
+    storage::{
+        compression::CompressionArgs,
+        create_tables,
+        tables::{BlobSidecars, StoredBlobSidecar},
+    },
+};
+use clap::Parser;
+use reth::providers::DBProvider;
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_db_api::{
+    cursor::DbCursorRO,
+    table::Table,
+    transaction::{DbTx, DbTxMut},
+};
+use tracing::info;
+
+/// `bera-reth db-compact` command
+///
+/// Rewrites every value of the compressible Berachain tables with the selected codec, e.g. after
+/// changing `[berachain.compression]` or to compress data written before compression was enabled.
+/// Codecs are read from the node config file; `--db.compression.*` flags override them. Only
+/// retained blob sidecars are compressible: transactions and receipts live in reth's static files,
+/// which cannot be compressed in this reth version.
+#[derive(Debug, Parser)]
+pub struct DbCompactCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    #[command(flatten)]
+    compression: CompressionArgs,
+
+    /// Number of values to rewrite before committing the write transaction.
+    #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u64).range(1..))]
+    commit_threshold: u64,
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> DbCompactCommand<C> {
+    /// Execute `db-compact` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let Environment { provider_factory, data_dir, .. } =
+            self.env.init::<BerachainNode>(AccessRights::RW)?;
+        create_tables(provider_factory.db_ref())?;
+
+        let config_path = self.env.config.clone().unwrap_or_else(|| data_dir.config());
+        let config = BerachainConfig::load(&config_path)?.compression.with_args(&self.compression);
+
+        info!(target: "reth::cli", table = BlobSidecars::NAME, codec = %config.blob_sidecars, "Compacting table");
+
+        let (mut rewritten, mut uncompressed_bytes, mut stored_bytes) = (0u64, 0usize, 0usize);
+        let mut next = None;
+        loop {
+            let provider = provider_factory.database_provider_rw()?;
+
+            // Read one value past the batch to know where the next batch starts.
+            let mut batch = provider
+                .tx_ref()
+                .cursor_read::<BlobSidecars>()?
+                .walk(next)?
+                .take(self.commit_threshold as usize + 1)
+                .collect::<Result<Vec<_>, _>>()?;
+            next = (batch.len() as u64 > self.commit_threshold)
+                .then(|| batch.pop())
+                .flatten()
+                .map(|(key, _)| key);
+
+            for (key, value) in batch {
+                let encoded = value.decode()?;
+                let value = StoredBlobSidecar::new(&encoded, config.blob_sidecars);
+                uncompressed_bytes += encoded.len();
+                stored_bytes += value.stored_len();
+                provider.tx_ref().put::<BlobSidecars>(key, value)?;
+                rewritten += 1;
+            }
+            provider.commit()?;
+
+            info!(target: "reth::cli", rewritten, "Rewrote values");
+            if next.is_none() {
+                break;
+            }
+        }
+
+        info!(
+            target: "reth::cli",
+            table = BlobSidecars::NAME,
+            rewritten,
+            uncompressed_bytes,
+            stored_bytes,
+            "Compaction finished"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+//! `bera-reth export` command
+//!
+//! Writes canonical blocks as a plain concatenation of RLP encoded [`BerachainBlock`]s, i.e. with
+//! `BerachainHeader`s (including `prevProposerPubkey`) and PoL transactions. This is the format
+//! read by `bera-reth import`, so an export can seed another datadir offline or be re-executed
+//! deterministically for benchmarks.
+
+use crate::primitives::BerachainBlock;
+use alloy_primitives::BlockNumber;
+use alloy_rlp::Encodable;
+use clap::Parser;
+use reth::providers::{BlockNumReader, BlockReader};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, CliNodeTypes, Environment, EnvironmentArgs};
+use reth_primitives_traits::NodePrimitives;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+use tracing::info;
+
+/// `bera-reth export` command
+#[derive(Debug, Parser)]
+pub struct ExportBlocksCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// Destination file.
+    #[arg(long, value_name = "FILE")]
+    path: PathBuf,
+
+    /// First block to export. Genesis is derived from the chain spec and never exported.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    from: BlockNumber,
+
+    /// Last block to export (inclusive). Defaults to the best stored block.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+}
+
+impl<C: ChainSpecParser> ExportBlocksCommand<C> {
+    /// Execute `export` command
+    pub async fn execute<N>(self) -> eyre::Result<()>
+    where
+        N: CliNodeTypes<
+                ChainSpec = C::ChainSpec,
+                Primitives: NodePrimitives<Block = BerachainBlock>,
+            >,
+    {
+        let Environment { provider_factory, .. } = self.env.init::<N>(AccessRights::RO)?;
+        let provider = provider_factory.provider()?;
+
+        let to = match self.to {
+            Some(to) => to,
+            None => provider.best_block_number()?,
+        };
+        eyre::ensure!(self.from <= to, "--from ({}) must not exceed --to ({to})", self.from);
+
+        info!(target: "reth::cli", from = self.from, to, path = %self.path.display(), "Exporting blocks");
+
+        let mut writer = BufWriter::new(File::create(&self.path)?);
+        let mut buf = Vec::new();
+        let mut total_transactions = 0;
+
+        for number in self.from..=to {
+            let block = provider
+                .block(number.into())?
+                .ok_or_else(|| eyre::eyre!("block {number} not found"))?;
+            total_transactions += block.body.transactions.len();
+
+            buf.clear();
+            block.encode(&mut buf);
+            writer.write_all(&buf)?;
+
+            if number % 100_000 == 0 {
+                info!(target: "reth::cli", number, total_transactions, "Exported blocks");
+            }
+        }
+
+        writer.flush()?;
+        info!(target: "reth::cli", blocks = to - self.from + 1, total_transactions, "Block export finished");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        primitives::{BerachainBlock, BerachainHeader, header::BlsPublicKey},
+        test_utils::compact_corpus,
+    };
+    use alloy_consensus::BlockBody;
+    use alloy_rlp::{Decodable, Encodable};
+
+    #[test]
+    fn test_exported_blocks_decode_back() {
+        let transactions: Vec<_> =
+            compact_corpus::tx_envelopes().into_iter().map(|entry| entry.value).collect();
+        let blocks: Vec<_> = (1..=3u64)
+            .map(|number| BerachainBlock {
+                header: BerachainHeader {
+                    number,
+                    prev_proposer_pubkey: Some(BlsPublicKey::repeat_byte(number as u8)),
+                    ..Default::default()
+                },
+                body: BlockBody {
+                    transactions: transactions.clone(),
+                    ommers: Vec::new(),
+                    withdrawals: Some(Default::default()),
+                },
+            })
+            .collect();
+
+        let mut file = Vec::new();
+        for block in &blocks {
+            block.encode(&mut file);
+        }
+
+        let mut data = file.as_slice();
+        let mut decoded = Vec::new();
+        while !data.is_empty() {
+            decoded.push(BerachainBlock::decode(&mut data).unwrap());
+        }
+
+        assert_eq!(decoded.len(), blocks.len());
+        for (decoded, block) in decoded.iter().zip(&blocks) {
+            assert_eq!(decoded.header, block.header);
+            let hashes = |block: &BerachainBlock| {
+                block.body.transactions.iter().map(|tx| *tx.hash()).collect::<Vec<_>>()
+            };
+            assert_eq!(hashes(decoded), hashes(block));
+        }
+    }
+}
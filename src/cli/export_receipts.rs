@@ -0,0 +1,80 @@
+//! `bera-reth export-receipts` command
+
+use crate::cli::receipts_file::ReceiptFileBlock;
+use alloy_primitives::BlockNumber;
+use alloy_rlp::Encodable;
+use clap::Parser;
+use reth::providers::{BlockHashReader, BlockNumReader, ReceiptProvider};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, CliNodeTypes, Environment, EnvironmentArgs};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+use tracing::info;
+
+/// `bera-reth export-receipts` command
+///
+/// Writes the receipts of a block range to a portable file that `import-receipts` can load on
+/// another node.
+#[derive(Debug, Parser)]
+pub struct ExportReceiptsCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// Destination file.
+    #[arg(long, value_name = "FILE")]
+    path: PathBuf,
+
+    /// First block to export.
+    #[arg(long, default_value_t = 0)]
+    from: BlockNumber,
+
+    /// Last block to export (inclusive). Defaults to the best stored block.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+}
+
+impl<C: ChainSpecParser> ExportReceiptsCommand<C> {
+    /// Execute `export-receipts` command
+    pub async fn execute<N: CliNodeTypes<ChainSpec = C::ChainSpec>>(self) -> eyre::Result<()> {
+        let Environment { provider_factory, .. } = self.env.init::<N>(AccessRights::RO)?;
+        let provider = provider_factory.provider()?;
+
+        let to = match self.to {
+            Some(to) => to,
+            None => provider.best_block_number()?,
+        };
+        eyre::ensure!(self.from <= to, "--from ({}) must not exceed --to ({to})", self.from);
+
+        info!(target: "reth::cli", from = self.from, to, path = %self.path.display(), "Exporting receipts");
+
+        let mut writer = BufWriter::new(File::create(&self.path)?);
+        let mut buf = Vec::new();
+        let mut total_receipts = 0;
+
+        for number in self.from..=to {
+            let hash = provider
+                .block_hash(number)?
+                .ok_or_else(|| eyre::eyre!("block {number} not found"))?;
+            let receipts = provider.receipts_by_block(number.into())?.ok_or_else(|| {
+                eyre::eyre!("receipts for block {number} not found, they may have been pruned")
+            })?;
+            total_receipts += receipts.len();
+
+            buf.clear();
+            ReceiptFileBlock::new(number, hash, &receipts).encode(&mut buf);
+            writer.write_all(&buf)?;
+
+            if number % 100_000 == 0 {
+                info!(target: "reth::cli", number, total_receipts, "Exported receipts");
+            }
+        }
+
+        writer.flush()?;
+        info!(target: "reth::cli", blocks = to - self.from + 1, total_receipts, "Receipt export finished");
+
+        Ok(())
+    }
+}
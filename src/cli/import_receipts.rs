@@ -0,0 +1,122 @@
+//! `bera-reth import-receipts` command
+
+use crate::{cli::receipts_file::ReceiptFileReader, storage::create_tables};
+use alloy_consensus::{BlockHeader, TxReceipt, proofs::calculate_receipt_root};
+use clap::Parser;
+use reth::providers::{
+    ExecutionOutcome, HeaderProvider, OriginalValuesKnown, StateWriter, StaticFileProviderFactory,
+    StorageLocation, UnifiedStorageWriter,
+};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, CliNodeTypes, Environment, EnvironmentArgs};
+use reth_node_api::ReceiptTy;
+use reth_static_file_types::StaticFileSegment;
+use std::{fs::File, io::BufReader, path::PathBuf};
+use tracing::info;
+
+/// `bera-reth import-receipts` command
+///
+/// Loads a file produced by `export-receipts`, decoding `--chunk-size` blocks at a time. Every
+/// block is checked against the local canonical header (hash and receipts root) before its chunk
+/// is written, and blocks whose receipts are already present are skipped.
+#[derive(Debug, Parser)]
+pub struct ImportReceiptsCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// Receipt file to import.
+    #[arg(value_name = "FILE")]
+    path: PathBuf,
+
+    /// Number of blocks to write per commit.
+    #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u64).range(1..))]
+    chunk_size: u64,
+}
+
+impl<C: ChainSpecParser> ImportReceiptsCommand<C> {
+    /// Execute `import-receipts` command
+    pub async fn execute<N: CliNodeTypes<ChainSpec = C::ChainSpec>>(self) -> eyre::Result<()> {
+        let Environment { provider_factory, .. } = self.env.init::<N>(AccessRights::RW)?;
+        create_tables(provider_factory.db_ref())?;
+
+        info!(target: "reth::cli", path = %self.path.display(), "Importing receipts");
+        let mut blocks = ReceiptFileReader::new(BufReader::new(File::open(&self.path)?));
+
+        let highest_stored = provider_factory
+            .static_file_provider()
+            .get_highest_static_file_block(StaticFileSegment::Receipts);
+        let mut next_block = highest_stored.map_or(0, |block| block + 1);
+        let mut skipped = 0;
+        let mut imported = 0;
+
+        loop {
+            let chunk =
+                blocks.by_ref().take(self.chunk_size as usize).collect::<eyre::Result<Vec<_>>>()?;
+            if chunk.is_empty() {
+                break
+            }
+            let provider = provider_factory.database_provider_rw()?;
+            let mut first_block = None;
+            let mut receipts = Vec::with_capacity(chunk.len());
+
+            for block in &chunk {
+                if block.number < next_block {
+                    skipped += 1;
+                    continue;
+                }
+                eyre::ensure!(
+                    block.number == next_block,
+                    "receipt file is not contiguous: expected block {next_block}, found {}",
+                    block.number
+                );
+
+                let header = provider.sealed_header(block.number)?.ok_or_else(|| {
+                    eyre::eyre!(
+                        "block {} must be synced before importing its receipts",
+                        block.number
+                    )
+                })?;
+                eyre::ensure!(
+                    header.hash() == block.hash,
+                    "block {} hash mismatch: local {}, file {}",
+                    block.number,
+                    header.hash(),
+                    block.hash
+                );
+
+                let block_receipts = block.decode_receipts::<ReceiptTy<N>>()?;
+                let receipts_root = calculate_receipt_root(
+                    &block_receipts.iter().map(TxReceipt::with_bloom_ref).collect::<Vec<_>>(),
+                );
+                eyre::ensure!(
+                    receipts_root == header.receipts_root(),
+                    "block {} receipts root mismatch: header {}, computed {receipts_root}",
+                    block.number,
+                    header.receipts_root()
+                );
+
+                first_block.get_or_insert(block.number);
+                receipts.push(block_receipts);
+                next_block += 1;
+            }
+
+            let Some(first_block) = first_block else { continue };
+            imported += receipts.len();
+
+            let outcome =
+                ExecutionOutcome::new(Default::default(), receipts, first_block, Vec::new());
+            provider.write_state(
+                &outcome,
+                OriginalValuesKnown::Yes,
+                StorageLocation::StaticFiles,
+            )?;
+            UnifiedStorageWriter::commit(provider)?;
+
+            info!(target: "reth::cli", imported, last_block = next_block - 1, "Imported receipts");
+        }
+
+        info!(target: "reth::cli", imported, skipped, "Receipt import finished");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! `bera-reth init` command
+//!
+//! Reth's `init` writes the genesis alloc with `init_genesis`, which takes many minutes for allocs
+//! with hundreds of thousands of entries. This command takes the same arguments and writes it with
+//! the [batched writer](crate::genesis::init) instead, as the node does on launch.
+//!
+//! Reth's `init` stays in the command tree so `--help` lists it as usual, but it keeps its
+//! arguments private. [`BerachainCli::run_with`](super::BerachainCli::run_with) therefore reads
+//! them again through [`InitCli`] once `init` is selected.
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    genesis::init::{DEFAULT_GENESIS_BATCH_SIZE, init_genesis_batched},
+    node::{BerachainNode, json_logs::JsonLogArgs, otlp::OtlpArgs},
+    storage::create_tables,
+};
+use clap::{Parser, Subcommand};
+use reth::providers::{ProviderFactory, providers::StaticFileProvider};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::EnvironmentArgs;
+use reth_db::{DatabaseEnv, init_db};
+use reth_node_builder::NodeTypesWithDBAdapter;
+use reth_node_core::{
+    args::LogArgs,
+    dirs::{ChainPath, DataDirPath},
+};
+use std::sync::Arc;
+use tracing::info;
+
+/// Command line of `bera-reth init`, with the global arguments of [`BerachainCli`].
+///
+/// [`BerachainCli`]: super::BerachainCli
+#[derive(Debug, Parser)]
+pub struct InitCli<C: ChainSpecParser> {
+    #[command(subcommand)]
+    command: InitSubcommand<C>,
+
+    #[command(flatten)]
+    logs: LogArgs,
+
+    #[command(flatten)]
+    json_logs: JsonLogArgs,
+
+    #[command(flatten)]
+    otlp: OtlpArgs,
+}
+
+impl<C: ChainSpecParser> InitCli<C> {
+    /// Returns the `init` command.
+    pub fn into_command(self) -> InitCommand<C> {
+        let InitSubcommand::Init(command) = self.command;
+        command
+    }
+}
+
+/// The only command of [`InitCli`].
+#[derive(Debug, Subcommand)]
+enum InitSubcommand<C: ChainSpecParser> {
+    /// Initialize the database with the genesis block
+    #[command(name = "init")]
+    Init(InitCommand<C>),
+}
+
+/// `bera-reth init` command
+#[derive(Debug, Parser)]
+pub struct InitCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> InitCommand<C> {
+    /// Execute `init` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        info!(target: "reth::cli", "bera-reth init starting");
+        let data_dir = self.env.datadir.clone().resolve_datadir(self.env.chain.chain());
+        let db = Arc::new(init_db(data_dir.db(), self.env.db.database_args())?);
+        create_tables(&db)?;
+
+        let factory = provider_factory(db, self.env.chain.clone(), &data_dir)?;
+        let hash = init_genesis_batched(&factory, DEFAULT_GENESIS_BATCH_SIZE)?;
+        info!(target: "reth::cli", ?hash, "Genesis block written");
+        Ok(())
+    }
+}
+
+/// Opens the provider factory the genesis writer and checkpoint sync run on.
+///
+/// It holds the static files read-write, so it must be dropped before the node's launcher opens
+/// its own.
+pub fn provider_factory(
+    db: Arc<DatabaseEnv>,
+    chain: Arc<BerachainChainSpec>,
+    data_dir: &ChainPath<DataDirPath>,
+) -> eyre::Result<ProviderFactory<NodeTypesWithDBAdapter<BerachainNode, Arc<DatabaseEnv>>>> {
+    Ok(ProviderFactory::new(db, chain, StaticFileProvider::read_write(data_dir.static_files())?))
+}
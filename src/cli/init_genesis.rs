@@ -0,0 +1,242 @@
+//! `bera-reth init-genesis` command
+//!
+//! Generates a Berachain genesis file instead of handcrafting one: every Ethereum fork up to Prague
+//! is active at genesis, the `berachain.prague1` section carries the given settings, and the
+//! Prague system contracts are deployed next to the allocations read from a file. The invariants
+//! the chainspec enforces are checked before anything is written, and the output is run through
+//! the same round trip as `test-genesis`, so the file loads with `--chain <FILE>`.
+
+use crate::{
+    cli::test_genesis,
+    genesis::{BerachainConfigError, BerachainForkConfig, BerachainGenesisConfig},
+};
+use alloy_eips::{
+    eip2935::{HISTORY_STORAGE_ADDRESS, HISTORY_STORAGE_CODE},
+    eip4788::{BEACON_ROOTS_ADDRESS, BEACON_ROOTS_CODE},
+    eip7002::{WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS, WITHDRAWAL_REQUEST_PREDEPLOY_CODE},
+    eip7251::{CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS, CONSOLIDATION_REQUEST_PREDEPLOY_CODE},
+    eip7840::BlobParams,
+};
+use alloy_genesis::{ChainConfig, Genesis, GenesisAccount};
+use alloy_primitives::{Address, U256};
+use clap::Parser;
+use std::{collections::BTreeMap, path::PathBuf};
+use tracing::info;
+
+/// Default block gas limit of generated genesis files.
+pub const DEFAULT_GAS_LIMIT: u64 = 30_000_000;
+
+/// `bera-reth init-genesis` command
+#[derive(Debug, Parser)]
+pub struct InitGenesisCommand {
+    /// Chain id of the network.
+    #[arg(long, value_name = "CHAIN_ID")]
+    chain_id: u64,
+
+    /// Unix timestamp of the genesis block.
+    #[arg(long, value_name = "TIMESTAMP", default_value_t = 0)]
+    timestamp: u64,
+
+    /// Gas limit of the genesis block.
+    #[arg(long, value_name = "GAS", default_value_t = DEFAULT_GAS_LIMIT)]
+    gas_limit: u64,
+
+    /// Unix timestamp at which Prague1 activates. [default: 0]
+    #[arg(long = "prague1.time", value_name = "TIMESTAMP")]
+    prague1_time: Option<u64>,
+
+    /// Base fee change denominator from Prague1 on. [default: 48]
+    #[arg(long = "prague1.base-fee-change-denominator", value_name = "DENOMINATOR")]
+    base_fee_change_denominator: Option<u128>,
+
+    /// Minimum base fee in wei from Prague1 on. [default: 1000000000]
+    #[arg(long = "prague1.minimum-base-fee-wei", value_name = "WEI")]
+    minimum_base_fee_wei: Option<u64>,
+
+    /// Address of the PoL distributor contract.
+    /// [default: 0x4200000000000000000000000000000000000042]
+    #[arg(long = "prague1.pol-distributor-address", value_name = "ADDRESS")]
+    pol_distributor_address: Option<Address>,
+
+    /// Gas limit of the PoL transaction. [default: 30000000]
+    #[arg(long = "prague1.pol-tx-gas-limit", value_name = "GAS")]
+    pol_tx_gas_limit: Option<u64>,
+
+    /// Address of the deposit contract read by the consensus client.
+    #[arg(long, value_name = "ADDRESS")]
+    deposit_contract_address: Option<Address>,
+
+    /// JSON file of genesis allocations, keyed by address as in the `alloc` section of a genesis
+    /// file. Entries for the Prague system contracts replace the built-in deployments.
+    #[arg(long, value_name = "FILE")]
+    alloc: Option<PathBuf>,
+
+    /// Write the genesis JSON to this file instead of stdout.
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+impl InitGenesisCommand {
+    /// Execute `init-genesis` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let alloc = match &self.alloc {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|err| eyre::eyre!("failed to read {}: {err}", path.display()))?;
+                serde_json::from_str(&contents).map_err(|err| {
+                    eyre::eyre!("invalid allocations in {}: {err}", path.display())
+                })?
+            }
+            None => BTreeMap::new(),
+        };
+        let genesis = self.genesis(alloc)?;
+        let round_trip = test_genesis::round_trip(&serde_json::to_string(&genesis)?)?;
+
+        let json = serde_json::to_string_pretty(&genesis)?;
+        match &self.output {
+            Some(output) => std::fs::write(output, json)?,
+            None => println!("{json}"),
+        }
+
+        info!(
+            target: "reth::cli",
+            chain_id = round_trip.chain_id,
+            genesis_hash = %round_trip.genesis_hash,
+            "Generated genesis"
+        );
+        Ok(())
+    }
+
+    /// Returns the Berachain configuration of the genesis.
+    fn berachain_config(&self) -> Result<BerachainGenesisConfig, BerachainConfigError> {
+        let defaults = BerachainGenesisConfig::default().prague1;
+        let prague1 = BerachainForkConfig::new(
+            self.prague1_time.unwrap_or(defaults.time),
+            self.base_fee_change_denominator.unwrap_or(defaults.base_fee_change_denominator),
+            self.minimum_base_fee_wei.unwrap_or(defaults.minimum_base_fee_wei),
+            self.pol_distributor_address.unwrap_or(defaults.pol_distributor_address),
+        )?
+        .with_pol_tx_gas_limit(self.pol_tx_gas_limit.unwrap_or(defaults.pol_tx_gas_limit))?;
+        if prague1.pol_distributor_address.is_zero() {
+            return Err(BerachainConfigError::MissingPoLDistributorAddress)
+        }
+        Ok(BerachainGenesisConfig { prague1, prague2: None })
+    }
+
+    /// Builds the genesis with `alloc` and the Prague system contracts.
+    fn genesis(&self, mut alloc: BTreeMap<Address, GenesisAccount>) -> eyre::Result<Genesis> {
+        let berachain = self.berachain_config()?;
+        eyre::ensure!(self.chain_id != 0, "chain id must not be 0");
+        eyre::ensure!(self.gas_limit != 0, "gas limit must not be 0");
+
+        for (address, code) in [
+            (BEACON_ROOTS_ADDRESS, BEACON_ROOTS_CODE),
+            (HISTORY_STORAGE_ADDRESS, HISTORY_STORAGE_CODE),
+            (WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS, WITHDRAWAL_REQUEST_PREDEPLOY_CODE),
+            (CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS, CONSOLIDATION_REQUEST_PREDEPLOY_CODE),
+        ] {
+            alloc.entry(address).or_insert_with(|| {
+                GenesisAccount::default().with_nonce(Some(1)).with_code(Some(code))
+            });
+        }
+
+        let mut config = ChainConfig {
+            chain_id: self.chain_id,
+            homestead_block: Some(0),
+            eip150_block: Some(0),
+            eip155_block: Some(0),
+            eip158_block: Some(0),
+            byzantium_block: Some(0),
+            constantinople_block: Some(0),
+            petersburg_block: Some(0),
+            istanbul_block: Some(0),
+            muir_glacier_block: Some(0),
+            berlin_block: Some(0),
+            london_block: Some(0),
+            arrow_glacier_block: Some(0),
+            gray_glacier_block: Some(0),
+            merge_netsplit_block: Some(0),
+            shanghai_time: Some(0),
+            cancun_time: Some(0),
+            prague_time: Some(0),
+            terminal_total_difficulty: Some(U256::ZERO),
+            terminal_total_difficulty_passed: true,
+            deposit_contract_address: self.deposit_contract_address,
+            blob_schedule: BTreeMap::from([
+                ("cancun".to_string(), BlobParams::cancun()),
+                ("prague".to_string(), BlobParams::prague()),
+            ]),
+            ..Default::default()
+        };
+        config.extra_fields.insert("berachain".to_string(), serde_json::to_value(berachain)?);
+
+        let mut genesis = Genesis::default()
+            .with_nonce(0)
+            .with_timestamp(self.timestamp)
+            .with_gas_limit(self.gas_limit)
+            .extend_accounts(alloc);
+        genesis.config = config;
+        Ok(genesis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chainspec::BerachainChainSpec;
+    use reth_chainspec::EthChainSpec;
+
+    fn command(args: &[&str]) -> InitGenesisCommand {
+        InitGenesisCommand::parse_from(
+            [&["init-genesis", "--chain-id", "80069"][..], args].concat(),
+        )
+    }
+
+    #[test]
+    fn test_generated_genesis_round_trips() {
+        let command = command(&[
+            "--prague1.time",
+            "1750000000",
+            "--prague1.minimum-base-fee-wei",
+            "10000000000",
+            "--prague1.pol-tx-gas-limit",
+            "5000000",
+        ]);
+        let genesis = command.genesis(BTreeMap::new()).unwrap();
+        let round_trip =
+            test_genesis::round_trip(&serde_json::to_string(&genesis).unwrap()).unwrap();
+        assert_eq!(round_trip.chain_id, 80069);
+
+        let chain_spec = BerachainChainSpec::try_from(genesis.clone()).unwrap();
+        assert_eq!(chain_spec.prague1_minimum_base_fee, 10_000_000_000);
+        assert_eq!(chain_spec.pol_tx_gas_limit, 5_000_000);
+        assert_eq!(chain_spec.genesis().alloc.len(), 4);
+        assert_eq!(chain_spec.genesis_header().gas_limit, DEFAULT_GAS_LIMIT);
+    }
+
+    #[test]
+    fn test_alloc_overrides_system_contracts() {
+        let account = GenesisAccount::default().with_balance(U256::from(1));
+        let alloc = BTreeMap::from([
+            (Address::repeat_byte(0x11), account.clone()),
+            (BEACON_ROOTS_ADDRESS, account.clone()),
+        ]);
+        let genesis = command(&[]).genesis(alloc).unwrap();
+        assert_eq!(genesis.alloc.len(), 5);
+        assert_eq!(genesis.alloc[&BEACON_ROOTS_ADDRESS], account);
+    }
+
+    #[test]
+    fn test_invalid_settings_are_errors() {
+        let err = command(&["--prague1.base-fee-change-denominator", "0"])
+            .genesis(BTreeMap::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("denominator"), "{err}");
+
+        let zero = Address::ZERO.to_string();
+        let err = command(&["--prague1.pol-distributor-address", &zero])
+            .genesis(BTreeMap::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("PoL distributor"), "{err}");
+    }
+}
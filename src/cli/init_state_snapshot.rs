@@ -0,0 +1,163 @@
+//! `bera-reth init-state-snapshot` command
+//!
+//! Initializes an empty datadir from a state dump taken at some block instead of syncing from
+//! genesis. The dump is the JSONL format written by `debug_dumpState`-style exporters and read by
+//! reth's `init-state`: a first line `{"root": ...}` followed by one account per line. The snapshot
+//! block's header is read from the JSON block returned by `eth_getBlockByNumber` on a bera-reth
+//! node, so it carries `prevProposerPubkey` and hashes to the canonical block hash.
+//!
+//! Reth's own `init-state` takes the header as RLP plus a separately supplied hash; it remains
+//! available under its usual name.
+
+use crate::{
+    chainspec::BerachainChainSpec, node::BerachainNode, primitives::BerachainHeader,
+    storage::create_tables,
+};
+use alloy_consensus::BlockHeader;
+use alloy_primitives::{B256, U256};
+use clap::Parser;
+use reth::{
+    chainspec::EthereumHardforks,
+    providers::{BlockNumReader, ChainSpecProvider, ProviderFactory, StaticFileProviderFactory},
+};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::{
+    common::{AccessRights, Environment, EnvironmentArgs},
+    init_state::without_evm::setup_without_evm,
+};
+use reth_config::config::EtlConfig;
+use reth_db::DatabaseEnv;
+use reth_db_common::init::init_from_state_dump;
+use reth_node_builder::NodeTypesWithDBAdapter;
+use reth_primitives_traits::SealedHeader;
+use serde::Deserialize;
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::info;
+
+/// Snapshot block as returned over JSON-RPC: the header plus, optionally, its hash.
+#[derive(Debug, Deserialize)]
+struct SnapshotBlock {
+    #[serde(flatten)]
+    header: BerachainHeader,
+    #[serde(default)]
+    hash: Option<B256>,
+}
+
+/// Reads the snapshot header from `path` and checks it against the hash stated in the file.
+pub fn read_snapshot_header(path: &Path) -> eyre::Result<SealedHeader<BerachainHeader>> {
+    let block: SnapshotBlock = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    let header = SealedHeader::seal_slow(block.header);
+
+    if let Some(expected) = block.hash {
+        eyre::ensure!(
+            header.hash() == expected,
+            "snapshot header hashes to {}, but the file states {expected}",
+            header.hash()
+        );
+    }
+    Ok(header)
+}
+
+/// `bera-reth init-state-snapshot` command
+#[derive(Debug, Parser)]
+pub struct InitStateSnapshotCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// JSONL state dump taken at the snapshot block.
+    #[arg(value_name = "STATE_DUMP_FILE")]
+    state: PathBuf,
+
+    /// JSON header (or full block) of the snapshot block, as served by `eth_getBlockByNumber`.
+    #[arg(long, value_name = "HEADER_FILE")]
+    header: PathBuf,
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> InitStateSnapshotCommand<C> {
+    /// Execute `init-state-snapshot` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let header = read_snapshot_header(&self.header)?;
+        let Environment { config, provider_factory, .. } =
+            self.env.init::<BerachainNode>(AccessRights::RW)?;
+        create_tables(provider_factory.db_ref())?;
+        init_from_snapshot(&provider_factory, header, &self.state, config.stages.etl)
+    }
+}
+
+/// Writes `header` as the local head of an empty datadir and imports the JSONL state dump at
+/// `state`, which must match the header's state root.
+pub fn init_from_snapshot(
+    provider_factory: &ProviderFactory<NodeTypesWithDBAdapter<BerachainNode, Arc<DatabaseEnv>>>,
+    header: SealedHeader<BerachainHeader>,
+    state: &Path,
+    etl: EtlConfig,
+) -> eyre::Result<()> {
+    eyre::ensure!(header.number() > 0, "snapshot block must be above genesis");
+    let last_block_number = provider_factory.provider()?.last_block_number()?;
+    eyre::ensure!(
+        last_block_number == 0,
+        "data directory must be empty, found blocks up to {last_block_number}"
+    );
+
+    info!(target: "reth::cli", number = header.number(), hash = %header.hash(), "Writing snapshot block as local head");
+
+    // Berachain has been post-merge since genesis, so the final TD is the genesis difficulty.
+    let total_difficulty =
+        provider_factory.chain_spec().final_paris_total_difficulty().unwrap_or(U256::ZERO);
+
+    let static_file_provider = provider_factory.static_file_provider();
+    let provider_rw = provider_factory.database_provider_rw()?;
+    setup_without_evm(&provider_rw, header.clone(), total_difficulty)?;
+    // Static files are unwound to the database checkpoints should the state import fail.
+    static_file_provider.commit()?;
+
+    info!(target: "reth::cli", state = %state.display(), "Importing state dump");
+    let state_root = init_from_state_dump(BufReader::new(File::open(state)?), &provider_rw, etl)?;
+    eyre::ensure!(
+        state_root == header.state_root(),
+        "state dump root {state_root} does not match snapshot header root {}",
+        header.state_root()
+    );
+    provider_rw.commit()?;
+
+    info!(target: "reth::cli", number = header.number(), %state_root, "Initialized from state snapshot");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::header::BlsPublicKey;
+    use alloy_consensus::Header;
+
+    #[test]
+    fn test_read_snapshot_header_from_rpc_block() {
+        let mut header = BerachainHeader::from(Header { number: 42, ..Default::default() });
+        header.prev_proposer_pubkey = Some(BlsPublicKey::repeat_byte(3));
+        let hash = SealedHeader::seal_slow(header.clone()).hash();
+
+        let mut block = serde_json::to_value(&header).unwrap();
+        block["hash"] = serde_json::to_value(hash).unwrap();
+        block["transactions"] = serde_json::json!([]);
+
+        let dir = std::env::temp_dir().join(format!("bera-snapshot-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("header.json");
+
+        std::fs::write(&path, serde_json::to_vec(&block).unwrap()).unwrap();
+        let sealed = read_snapshot_header(&path).unwrap();
+        assert_eq!(sealed.hash(), hash);
+        assert_eq!(sealed.header(), &header);
+
+        block["hash"] = serde_json::to_value(B256::ZERO).unwrap();
+        std::fs::write(&path, serde_json::to_vec(&block).unwrap()).unwrap();
+        assert!(read_snapshot_header(&path).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+//! `bera-reth db-migrate-from-reth` command
+//!
+//! Converts a datadir written by vanilla reth in place, so a node running on an Ethereum-compatible
+//! Berachain devnet can switch binaries without a resync. The compact encodings of
+//! [`BerachainHeader`] and [`BerachainTxEnvelope`] extend those of reth's `Header` and
+//! `TransactionSigned`, so every row of the `Headers` and `Transactions` tables is decoded in
+//! reth's format, converted, checked against the hash it is indexed by (`CanonicalHeaders` and
+//! `TransactionHashNumbers`) and written back in the Berachain format. Headers and transactions
+//! already moved to static files use the same encodings and are left as they are; `db-check`
+//! verifies them. The datadir is stamped with the current Berachain storage format at the end.
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    node::BerachainNode,
+    primitives::BerachainHeader,
+    storage::{
+        create_tables,
+        version::{DatadirVersion, check_and_stamp},
+    },
+    transaction::BerachainTxEnvelope,
+};
+use alloy_consensus::Header;
+use alloy_primitives::Sealable;
+use clap::Parser;
+use reth::providers::{ChainSpecProvider, DBProvider, ProviderFactory};
+use reth_chainspec::EthChainSpec;
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_db::DatabaseEnv;
+use reth_db_api::{
+    cursor::DbCursorRO,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_ethereum_primitives::TransactionSigned;
+use reth_node_builder::NodeTypesWithDBAdapter;
+use reth_primitives_traits::SignedTransaction;
+use std::sync::Arc;
+use tracing::info;
+
+/// Counters reported at the end of a migration.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MigrationStats {
+    /// Header rows converted.
+    pub headers: u64,
+    /// Transaction rows converted.
+    pub transactions: u64,
+    /// Rows whose hash could not be checked because their index entry is missing or pruned.
+    pub unverified: u64,
+}
+
+/// `bera-reth db-migrate-from-reth` command
+#[derive(Debug, Parser)]
+pub struct MigrateFromRethCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// Only decode and verify the rows without writing them.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Number of rows to convert before committing the write transaction.
+    #[arg(long, default_value_t = 100_000, value_parser = clap::value_parser!(u64).range(1..))]
+    commit_threshold: u64,
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> MigrateFromRethCommand<C> {
+    /// Execute `db-migrate-from-reth` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let Environment { provider_factory, data_dir, .. } =
+            self.env.init::<BerachainNode>(AccessRights::RW)?;
+        create_tables(provider_factory.db_ref())?;
+
+        info!(target: "reth::cli", dry_run = self.dry_run, "Migrating reth tables");
+        let mut stats = MigrationStats::default();
+        self.migrate_headers(&provider_factory, &mut stats)?;
+        self.migrate_transactions(&provider_factory, &mut stats)?;
+
+        if !self.dry_run {
+            let chain_spec = provider_factory.chain_spec();
+            check_and_stamp(
+                data_dir.data_dir(),
+                &DatadirVersion::current(chain_spec.chain_id(), chain_spec.genesis_hash()),
+            )?;
+        }
+
+        info!(
+            target: "reth::cli",
+            headers = stats.headers,
+            transactions = stats.transactions,
+            unverified = stats.unverified,
+            dry_run = self.dry_run,
+            "Migration finished"
+        );
+        Ok(())
+    }
+
+    /// Converts the `Headers` table, one batch of `commit_threshold` rows per write transaction.
+    fn migrate_headers(
+        &self,
+        provider_factory: &ProviderFactory<NodeTypesWithDBAdapter<BerachainNode, Arc<DatabaseEnv>>>,
+        stats: &mut MigrationStats,
+    ) -> eyre::Result<()> {
+        let mut next = 0;
+        loop {
+            let provider = provider_factory.database_provider_rw()?;
+            let rows = provider
+                .tx_ref()
+                .cursor_read::<tables::Headers<Header>>()?
+                .walk(Some(next))?
+                .take(self.commit_threshold as usize)
+                .collect::<Result<Vec<_>, _>>()?;
+            let Some(&(last, _)) = rows.last() else { break };
+
+            for (number, header) in rows {
+                let header = BerachainHeader::from(header);
+                let hash = header.hash_slow();
+                match provider.tx_ref().get::<tables::CanonicalHeaders>(number)? {
+                    Some(canonical) if canonical != hash => eyre::bail!(
+                        "header {number} hashes to {hash} after conversion, but the canonical \
+                         hash is {canonical}"
+                    ),
+                    Some(_) => {}
+                    None => stats.unverified += 1,
+                }
+                if !self.dry_run {
+                    provider.tx_ref().put::<tables::Headers<BerachainHeader>>(number, header)?;
+                }
+                stats.headers += 1;
+            }
+
+            if !self.dry_run {
+                provider.commit()?;
+            }
+            info!(
+                target: "reth::cli",
+                block_number = last,
+                headers = stats.headers,
+                "Migrated headers"
+            );
+            next = last + 1;
+        }
+        Ok(())
+    }
+
+    /// Converts the `Transactions` table, one batch of `commit_threshold` rows per write
+    /// transaction.
+    fn migrate_transactions(
+        &self,
+        provider_factory: &ProviderFactory<NodeTypesWithDBAdapter<BerachainNode, Arc<DatabaseEnv>>>,
+        stats: &mut MigrationStats,
+    ) -> eyre::Result<()> {
+        let mut next = 0;
+        loop {
+            let provider = provider_factory.database_provider_rw()?;
+            let rows = provider
+                .tx_ref()
+                .cursor_read::<tables::Transactions<TransactionSigned>>()?
+                .walk(Some(next))?
+                .take(self.commit_threshold as usize)
+                .collect::<Result<Vec<_>, _>>()?;
+            let Some(&(last, _)) = rows.last() else { break };
+
+            for (tx_number, tx) in rows {
+                let tx = BerachainTxEnvelope::from(tx);
+                let hash = *tx.tx_hash();
+                match provider.tx_ref().get::<tables::TransactionHashNumbers>(hash)? {
+                    Some(stored) if stored != tx_number => eyre::bail!(
+                        "transaction {tx_number} hashes to {hash} after conversion, which is \
+                         indexed as transaction {stored}"
+                    ),
+                    Some(_) => {}
+                    None => stats.unverified += 1,
+                }
+                if !self.dry_run {
+                    provider
+                        .tx_ref()
+                        .put::<tables::Transactions<BerachainTxEnvelope>>(tx_number, tx)?;
+                }
+                stats.transactions += 1;
+            }
+
+            if !self.dry_run {
+                provider.commit()?;
+            }
+            info!(
+                target: "reth::cli",
+                tx_number = last,
+                transactions = stats.transactions,
+                "Migrated transactions"
+            );
+            next = last + 1;
+        }
+        Ok(())
+    }
+}
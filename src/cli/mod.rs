@@ -0,0 +1,563 @@
+//! Bera-Reth command line interface: reth's commands plus Berachain-specific tooling
+
+pub mod bench;
+pub mod build_block;
+pub mod db_check;
+pub mod db_compact;
+pub mod export_blocks;
+pub mod export_receipts;
+pub mod import_receipts;
+pub mod init;
+pub mod init_genesis;
+pub mod init_state_snapshot;
+pub mod migrate_from_reth;
+pub mod prune;
+pub mod receipts_file;
+pub mod repair_tx_lookup;
+pub mod snapshot;
+pub mod test_genesis;
+
+use crate::{
+    chainspec::{BerachainChainSpec, BerachainChainSpecParser},
+    consensus::BerachainBeaconConsensus,
+    genesis::init::{DEFAULT_GENESIS_BATCH_SIZE, init_genesis_batched},
+    network::BerachainNetworkPrimitives,
+    node::{
+        BerachainNode,
+        args::BerachainArgs,
+        caches::ExplicitCaches,
+        config::BerachainConfig,
+        evm::config::BerachainEvmConfig,
+        fee_history::install_fee_history,
+        fee_market::install_fee_market_metrics,
+        health::install_health_endpoints,
+        json_logs::JsonLogArgs,
+        logging,
+        otlp::OtlpArgs,
+        runtime::RuntimeConfig,
+        shutdown::install_shutdown_hooks,
+        standby::install_standby,
+        startup_report::StartupReport,
+        state_root_sampling::install_state_root_sampling,
+        supervisor::{PidFile, install_sd_notify},
+        sync::SyncConfig,
+    },
+    storage::{
+        create_tables,
+        readers::install_reader_monitor,
+        version::{DatadirVersion, check_and_stamp},
+    },
+};
+use clap::{ArgMatches, CommandFactory, Parser, Subcommand, parser::ValueSource};
+use reth::{CliRunner, providers::providers::BlockchainProvider};
+use reth_chainspec::EthChainSpec;
+use reth_cli_commands::node::NodeCommand;
+use reth_config::config::EtlConfig;
+use reth_db::DatabaseEnv;
+use reth_ethereum_cli::{Cli, interface::Commands as RethCommands};
+use reth_evm::EthEvmFactory;
+use reth_network::NetworkHandle;
+use reth_node_api::FullNodeComponents;
+use reth_node_builder::{
+    FullNode, NodeBuilder, NodeHandle, NodeTypesWithDBAdapter, WithLaunchContext,
+    rpc::RethRpcAddOns,
+};
+use reth_node_core::{args::LogArgs, node_config::NodeConfig};
+use std::sync::Arc;
+use tracing::info;
+
+/// The main bera-reth cli interface.
+///
+/// Berachain-specific commands live next to the stock reth commands. They use their own top level
+/// names because reth's command groups (e.g. `db`) are not extensible.
+#[derive(Debug, Parser)]
+#[command(author, version, about = "Bera-Reth", long_about = None)]
+pub struct BerachainCli {
+    /// The command to run
+    #[command(subcommand)]
+    pub command: Commands,
+
+    #[command(flatten)]
+    pub logs: LogArgs,
+
+    #[command(flatten)]
+    pub json_logs: JsonLogArgs,
+
+    #[command(flatten)]
+    pub otlp: OtlpArgs,
+}
+
+/// Commands to be executed
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Benchmark block processing on already synced blocks
+    #[command(name = "bench")]
+    Bench(bench::BenchCommand<BerachainChainSpecParser>),
+    /// Build a block on top of a stored block and print it without submitting it
+    #[command(name = "build-block")]
+    BuildBlock(build_block::BuildBlockCommand<BerachainChainSpecParser>),
+    /// Verify stored headers, bodies and receipts against their header commitments
+    #[command(name = "db-check")]
+    DbCheck(db_check::DbCheckCommand<BerachainChainSpecParser>),
+    /// Rewrite retained blob sidecars with the selected compression
+    #[command(name = "db-compact")]
+    DbCompact(db_compact::DbCompactCommand<BerachainChainSpecParser>),
+    /// Convert the headers and transactions of a vanilla reth datadir in place
+    #[command(name = "db-migrate-from-reth")]
+    DbMigrateFromReth(migrate_from_reth::MigrateFromRethCommand<BerachainChainSpecParser>),
+    /// Export canonical blocks to an RLP file readable by `import`
+    #[command(name = "export")]
+    Export(export_blocks::ExportBlocksCommand<BerachainChainSpecParser>),
+    /// Export receipts of a block range to a portable file
+    #[command(name = "export-receipts")]
+    ExportReceipts(export_receipts::ExportReceiptsCommand<BerachainChainSpecParser>),
+    /// Import receipts previously written by `export-receipts`
+    #[command(name = "import-receipts")]
+    ImportReceipts(import_receipts::ImportReceiptsCommand<BerachainChainSpecParser>),
+    /// Generate a Berachain genesis file
+    #[command(name = "init-genesis")]
+    InitGenesis(init_genesis::InitGenesisCommand),
+    /// Initialize an empty datadir from a state dump taken at a recent block
+    #[command(name = "init-state-snapshot")]
+    InitStateSnapshot(init_state_snapshot::InitStateSnapshotCommand<BerachainChainSpecParser>),
+    /// Prune selected segments below a block, keeping PoL and deposit receipts
+    #[command(name = "prune-segments")]
+    PruneSegments(prune::PruneCommand<BerachainChainSpecParser>),
+    /// Backfill missing transaction hash lookup entries, including PoL transactions
+    #[command(name = "repair-tx-lookup")]
+    RepairTxLookup(repair_tx_lookup::Command<BerachainChainSpecParser>),
+    /// Create or restore portable archives of a datadir
+    #[command(name = "snapshot")]
+    Snapshot(snapshot::SnapshotCommand<BerachainChainSpecParser>),
+    /// Check that a genesis file survives a round trip through the chainspec unchanged
+    #[command(name = "test-genesis")]
+    TestGenesis(test_genesis::TestGenesisCommand),
+    /// Stock reth commands operating on Berachain primitives
+    #[command(flatten)]
+    Reth(RethCommands<BerachainChainSpecParser, BerachainArgs>),
+}
+
+/// Node builder handed to launchers passed to [`BerachainCli::run_with`].
+pub type BerachainNodeBuilder =
+    WithLaunchContext<NodeBuilder<Arc<DatabaseEnv>, BerachainChainSpec>>;
+
+impl BerachainCli {
+    /// Executes the selected command, launching a stock [`BerachainNode`] for `node`.
+    pub fn run(self) -> eyre::Result<()> {
+        self.run_with(async move |builder, berachain| {
+            let NodeHandle { node, node_exit_future } =
+                builder.node(berachain.clone()).launch_with_debug_capabilities().await?;
+            install_berachain_services(&node, &berachain).await?;
+
+            node_exit_future.await
+        })
+    }
+
+    /// Executes the selected command, launching the node with `launcher` for `node`.
+    ///
+    /// This is the entry point for customized nodes, e.g. with execution extensions:
+    ///
+    /// ```ignore
+    /// BerachainCli::parse().run_with(async move |builder, berachain| {
+    ///     let handle = builder
+    ///         .node(berachain.clone())
+    ///         .install_exex("my-exex", async move |ctx| Ok(my_exex(ctx)))
+    ///         .launch_with_debug_capabilities()
+    ///         .await?;
+    ///     install_berachain_services(&handle.node, &berachain).await?;
+    ///     handle.node_exit_future.await
+    /// })
+    /// ```
+    ///
+    /// `launcher` receives a [`BerachainNode`] carrying the Berachain configuration resolved from
+    /// the config file and command line and the log levels of `admin_setLogLevel`. Berachain
+    /// storage setup (tables, compression, genesis, checkpoint sync) and the database reader
+    /// monitor run before it is called. The services started after launch are up to the launcher:
+    /// call [`install_berachain_services`] to get those of [`Self::run`].
+    /// See `examples/pol_indexer_exex.rs` for a complete ExEx.
+    pub fn run_with<L>(mut self, launcher: L) -> eyre::Result<()>
+    where
+        L: AsyncFnOnce(BerachainNodeBuilder, BerachainNode) -> eyre::Result<()>,
+    {
+        // `self` no longer knows which arguments were given and which defaulted, so read them again
+        let matches = Self::command().get_matches();
+        let matches = selected_command(&matches);
+        ensure_chain_selected(matches)?;
+        let explicit_caches = ExplicitCaches::from_matches(matches);
+
+        // Thread pools are sized from the node's configuration before anything runs on them
+        let runner = match &self.command {
+            Commands::Reth(RethCommands::Node(command)) => {
+                CliRunner::from_runtime(node_runtime_config(command)?.build()?)
+            }
+            _ => CliRunner::try_default_runtime()?,
+        };
+
+        match self.command {
+            Commands::Bench(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::BuildBlock(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::DbCheck(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::DbCompact(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::DbMigrateFromReth(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::Export(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute::<BerachainNode>())
+            }
+            Commands::ExportReceipts(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute::<BerachainNode>())
+            }
+            Commands::ImportReceipts(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute::<BerachainNode>())
+            }
+            Commands::InitStateSnapshot(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::PruneSegments(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::RepairTxLookup(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute::<BerachainNode>())
+            }
+            Commands::Snapshot(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::InitGenesis(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::TestGenesis(command) => {
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::Reth(RethCommands::Init(_)) => {
+                // Same arguments, written with the batched genesis writer
+                let command = init::InitCli::<BerachainChainSpecParser>::parse().into_command();
+                let _guard = self.logs.init_tracing()?;
+                runner.run_blocking_until_ctrl_c(command.execute())
+            }
+            Commands::Reth(command) => {
+                // Set up before reth initializes tracing, which then keeps this subscriber. Log
+                // files go to the same per-chain directory reth would use.
+                let log_file_directory = self.logs.log_file_directory.clone();
+                if let Some(chain_spec) = command.chain_spec() {
+                    self.logs.log_file_directory =
+                        log_file_directory.join(chain_spec.chain().to_string());
+                }
+                let (log_levels, _file_guard, _otlp_guard) =
+                    logging::init_tracing(&self.logs, &self.json_logs, &self.otlp)?;
+                self.logs.log_file_directory = log_file_directory;
+
+                Cli { command, logs: self.logs }.with_runner_and_components::<BerachainNode>(
+                    runner,
+                    components,
+                    async move |mut builder, args| {
+                        info!(target: "reth::cli", "Launching Berachain node");
+                        let mut config = load_berachain_config(builder.config(), &args)?;
+                        let node_config = builder.config_mut();
+                        if let Some(memory_mb) = config.caches.apply(
+                            explicit_caches,
+                            &mut node_config.engine,
+                            &mut node_config.rpc.rpc_state_cache,
+                        ) {
+                            info!(target: "reth::cli", memory_mb, "Sized caches after memory");
+                        }
+                        let chain_spec = builder.config().chain.clone();
+                        check_and_stamp(
+                            builder.config().datadir().data_dir(),
+                            &DatadirVersion::current(
+                                chain_spec.chain_id(),
+                                chain_spec.genesis_hash(),
+                            ),
+                        )?;
+                        let report = StartupReport::new(builder.config(), &config);
+                        report.log();
+                        if config.write_startup_report {
+                            report.write(builder.config().datadir().data_dir())?;
+                        }
+                        config.datadir.apply(&mut builder.config_mut().datadir);
+                        create_tables(builder.db())?;
+                        init_state(builder.db().clone(), builder.config_mut(), &config.sync)?;
+                        // Removed once the node exits
+                        let _pid_file = config
+                            .supervisor
+                            .pid_file
+                            .as_deref()
+                            .map(PidFile::create)
+                            .transpose()?;
+                        let berachain = BerachainNode::new(config).with_log_levels(log_levels);
+                        install_reader_monitor(
+                            builder.db().clone(),
+                            berachain.inflight_calls().clone(),
+                            builder.task_executor(),
+                        );
+                        launcher(builder, berachain).await
+                    },
+                )
+            }
+        }
+    }
+}
+
+/// Returns the matches of the innermost selected subcommand.
+fn selected_command(mut matches: &ArgMatches) -> &ArgMatches {
+    while let Some((_, subcommand)) = matches.subcommand() {
+        matches = subcommand;
+    }
+    matches
+}
+
+/// Fails if the selected command takes `--chain` and neither `--chain` nor `--dev` was given.
+///
+/// Reth fills in the first [`SUPPORTED_CHAINS`](crate::chainspec::SUPPORTED_CHAINS) entry, the dev
+/// chain, when `--chain` is missing, which would silently run or inspect a dev datadir.
+fn ensure_chain_selected(matches: &ArgMatches) -> eyre::Result<()> {
+    if !matches.ids().any(|id| id == "chain") {
+        return Ok(())
+    }
+    let dev = matches.ids().any(|id| id == "dev") && matches.get_flag("dev");
+    eyre::ensure!(
+        dev || matches.value_source("chain") != Some(ValueSource::DefaultValue),
+        "no chain selected: pass `--chain <FILE>` with the network's genesis file, or `--dev`"
+    );
+    Ok(())
+}
+
+/// Starts the services bera-reth runs next to a launched node: shutdown hooks, metrics, fee
+/// history persistence, health endpoints, standby and supervisor notifications. Sidecar retention,
+/// the payload latency breakdown, the RPC response cache, canonical chain changes, sync progress,
+/// debug bundles, the profiler and static peers are started by the components and add-ons of
+/// [`BerachainNode`] instead.
+///
+/// [`BerachainCli::run`] calls it right after launch. Custom launchers passed to
+/// [`BerachainCli::run_with`] should do the same, with the node they are handed. Any
+/// pool, payload builder, execution extension and add-ons work; the EVM and network must be the
+/// Berachain ones.
+pub async fn install_berachain_services<N, AddOns>(
+    node: &FullNode<N, AddOns>,
+    berachain: &BerachainNode,
+) -> eyre::Result<()>
+where
+    N: FullNodeComponents<
+            Types = BerachainNode,
+            Provider = BlockchainProvider<NodeTypesWithDBAdapter<BerachainNode, Arc<DatabaseEnv>>>,
+            Evm = BerachainEvmConfig,
+            Network = NetworkHandle<BerachainNetworkPrimitives>,
+        >,
+    AddOns: RethRpcAddOns<N>,
+{
+    let config = berachain.config();
+    install_shutdown_hooks(node.provider.clone(), &node.task_executor);
+    install_fee_market_metrics(node.provider.clone(), node.chain_spec(), &node.task_executor);
+    install_fee_history(
+        berachain.fee_history().clone(),
+        node.data_dir.data_dir(),
+        node.provider.clone(),
+        node.chain_spec(),
+        &node.task_executor,
+    );
+    install_state_root_sampling(
+        config.state_root_sampling,
+        node.provider.clone(),
+        node.evm_config.clone(),
+        &node.task_executor,
+    );
+    install_health_endpoints(
+        config.health,
+        node.provider.clone(),
+        node.network.clone(),
+        node.add_ons_handle.engine_events.new_listener(),
+        &node.payload_builder_handle,
+        &node.task_executor,
+    )
+    .await?;
+    install_standby(
+        config.standby.clone(),
+        node.chain_spec(),
+        node.add_ons_handle.beacon_engine_handle.clone(),
+        node.add_ons_handle.engine_events.new_listener(),
+        &node.task_executor,
+    )?;
+    install_sd_notify(
+        &config.supervisor,
+        node.add_ons_handle.engine_events.new_listener(),
+        &node.task_executor,
+    );
+    Ok(())
+}
+
+/// Reads the thread pool settings of the `node` command ahead of its launch.
+fn node_runtime_config(
+    command: &NodeCommand<BerachainChainSpecParser, BerachainArgs>,
+) -> eyre::Result<RuntimeConfig> {
+    let path = command
+        .config
+        .clone()
+        .unwrap_or_else(|| command.datadir.clone().resolve_datadir(command.chain.chain()).config());
+    Ok(BerachainConfig::load(&path)?.with_args(&command.ext).runtime)
+}
+
+/// Loads the `[berachain]` section of the node config file and applies command line overrides.
+fn load_berachain_config(
+    config: &NodeConfig<BerachainChainSpec>,
+    args: &BerachainArgs,
+) -> eyre::Result<BerachainConfig> {
+    let path = config.config.clone().unwrap_or_else(|| config.datadir().config());
+    let berachain = BerachainConfig::load(&path)?.with_args(args);
+    info!(target: "reth::cli", path = %path.display(), ?berachain, "Loaded Berachain configuration");
+    Ok(berachain)
+}
+
+/// Writes the genesis state with the batched writer before reth's launcher gets to it, then
+/// prepares checkpoint sync.
+///
+/// The launcher's own genesis initialization then finds the genesis block and only verifies it,
+/// as does the writer on datadirs set up with `bera-reth init`. If history has to be executed up
+/// to the checkpoint, it becomes the pipeline's sync target. Both steps share one provider factory
+/// on the builder's database, which is dropped before the launcher opens the static files again.
+fn init_state(
+    db: Arc<DatabaseEnv>,
+    config: &mut NodeConfig<BerachainChainSpec>,
+    sync: &SyncConfig,
+) -> eyre::Result<()> {
+    let factory = init::provider_factory(db, config.chain.clone(), &config.datadir())?;
+    init_genesis_batched(&factory, DEFAULT_GENESIS_BATCH_SIZE)?;
+
+    let etl = EtlConfig::new(
+        Some(EtlConfig::from_datadir(config.datadir().data_dir())),
+        EtlConfig::default_file_size(),
+    );
+    if let Some(checkpoint) = sync.prepare(&factory, etl)? {
+        config.debug.tip = Some(checkpoint);
+    }
+    Ok(())
+}
+
+/// Components used by offline commands (import, stage, re-execute, ...).
+///
+/// Uses the Berachain consensus, so `stage run` and `import` enforce the same PoL and base fee
+/// rules as the live node.
+fn components(spec: Arc<BerachainChainSpec>) -> (BerachainEvmConfig, BerachainBeaconConsensus) {
+    (
+        BerachainEvmConfig::new_with_evm_factory(spec.clone(), EthEvmFactory::default()),
+        BerachainBeaconConsensus::new(spec),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+
+    fn parse(args: &[&str]) -> Commands {
+        BerachainCli::try_parse_from([&["bera-reth"], args].concat()).unwrap().command
+    }
+
+    #[test]
+    fn test_stage_commands_are_exposed() {
+        for args in [
+            ["stage", "run", "--chain", "dev", "execution", "--from", "1", "--to", "10"].as_slice(),
+            &["stage", "unwind", "--chain", "dev", "to-block", "100"],
+            &["stage", "drop", "--chain", "dev", "execution"],
+        ] {
+            assert!(matches!(parse(args), Commands::Reth(RethCommands::Stage(_))), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_init_arguments_are_read_again() {
+        let args = ["bera-reth", "-vvv", "init", "--chain", "dev", "--datadir", "datadir"];
+        assert!(matches!(
+            BerachainCli::try_parse_from(args).unwrap().command,
+            Commands::Reth(RethCommands::Init(_))
+        ));
+        init::InitCli::<BerachainChainSpecParser>::try_parse_from(args).unwrap().into_command();
+    }
+
+    #[test]
+    fn test_berachain_commands_are_exposed() {
+        assert!(matches!(
+            parse(&["bench", "execute", "--chain", "dev", "--from", "1", "--mode", "state-root"]),
+            Commands::Bench(_)
+        ));
+        assert!(matches!(
+            parse(&["bench", "profile", "--chain", "dev", "--from", "1", "--out", "profile"]),
+            Commands::Bench(_)
+        ));
+        assert!(matches!(
+            parse(&["build-block", "--chain", "dev", "--attributes", "attributes.json"]),
+            Commands::BuildBlock(_)
+        ));
+        assert!(matches!(parse(&["db-check", "--chain", "dev"]), Commands::DbCheck(_)));
+        assert!(matches!(parse(&["db-check", "--chain", "dev", "--repair"]), Commands::DbCheck(_)));
+        assert!(matches!(
+            parse(&["db-migrate-from-reth", "--chain", "dev", "--dry-run"]),
+            Commands::DbMigrateFromReth(_)
+        ));
+        assert!(matches!(
+            parse(&["export", "--chain", "dev", "--path", "blocks.rlp"]),
+            Commands::Export(_)
+        ));
+        assert!(matches!(
+            parse(&[
+                "prune-segments",
+                "--chain",
+                "dev",
+                "--segments",
+                "tx-lookup,receipts",
+                "--before",
+                "1000"
+            ]),
+            Commands::PruneSegments(_)
+        ));
+        assert!(matches!(
+            parse(&["repair-tx-lookup", "--chain", "dev", "--dry-run"]),
+            Commands::RepairTxLookup(_)
+        ));
+        assert!(matches!(
+            parse(&["snapshot", "create", "--chain", "dev", "--path", "dev.tar.zst"]),
+            Commands::Snapshot(_)
+        ));
+        assert!(matches!(
+            parse(&["test-genesis", "genesis.json", "--genesis-hash", &B256::ZERO.to_string()]),
+            Commands::TestGenesis(_)
+        ));
+    }
+
+    #[test]
+    fn test_chain_must_be_selected() {
+        let check = |args: &[&str]| {
+            let matches =
+                BerachainCli::command().try_get_matches_from([&["bera-reth"], args].concat());
+            ensure_chain_selected(selected_command(&matches.unwrap()))
+        };
+        assert!(check(&["db-check"]).is_err());
+        assert!(check(&["node"]).is_err());
+        assert!(check(&["stage", "unwind", "to-block", "100"]).is_err());
+
+        check(&["db-check", "--chain", "dev"]).unwrap();
+        check(&["node", "--dev"]).unwrap();
+        check(&["test-genesis", "genesis.json", "--genesis-hash", &B256::ZERO.to_string()])
+            .unwrap();
+    }
+}
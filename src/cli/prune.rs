@@ -0,0 +1,245 @@
+//! `bera-reth prune-segments` command
+//!
+//! Prunes selected segments up to a block right away, instead of waiting for the background
+//! pruner's schedule (reth's own `prune` command runs the configured pruner as a whole). Receipts
+//! of PoL transactions and receipts with deposit contract logs are kept: the former back the PoL
+//! reward history, the latter are needed to serve deposits.
+//!
+//! Work is committed in batches with progress logged after each, and `--throttle` pauses between
+//! batches so the disk stays usable for other processes.
+
+use crate::{
+    chainspec::BerachainChainSpec, node::BerachainNode, storage::create_tables,
+    transaction::BerachainTxType,
+};
+use alloy_primitives::{Address, BlockNumber, TxNumber};
+use clap::{Parser, ValueEnum};
+use reth::providers::{
+    BlockBodyIndicesProvider, DBProvider, DatabaseProviderFactory, PruneCheckpointReader,
+    StaticFileProviderFactory,
+};
+use reth_chainspec::EthChainSpec;
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_db_api::{
+    cursor::{DbCursorRO, DbCursorRW},
+    tables,
+    transaction::DbTxMut,
+};
+use reth_ethereum_primitives::Receipt;
+use reth_prune::segments::{AccountHistory, PruneInput, Segment, TransactionLookup};
+use reth_prune_types::{PruneLimiter, PruneMode};
+use reth_static_file_types::StaticFileSegment;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Segments `prune-segments` can prune.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PruneTarget {
+    /// Transaction hash lookup entries.
+    TxLookup,
+    /// Receipts, except PoL receipts and receipts with deposit contract logs.
+    Receipts,
+    /// Account changesets and history indices.
+    AccountHistory,
+}
+
+/// `bera-reth prune-segments` command
+#[derive(Debug, Parser)]
+pub struct PruneCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// Segments to prune.
+    #[arg(long, value_delimiter = ',', required = true)]
+    segments: Vec<PruneTarget>,
+
+    /// Prune data of all blocks below this block.
+    #[arg(long, value_name = "BLOCK")]
+    before: BlockNumber,
+
+    /// Number of entries to delete before committing the write transaction.
+    #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u64).range(1..))]
+    batch_size: u64,
+
+    /// Pause between batches, in milliseconds.
+    #[arg(long, default_value_t = 0, value_name = "MS")]
+    throttle: u64,
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> PruneCommand<C> {
+    /// Execute `prune-segments` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let Environment { provider_factory, .. } =
+            self.env.init::<BerachainNode>(AccessRights::RW)?;
+        create_tables(provider_factory.db_ref())?;
+        let Some(to_block) = self.before.checked_sub(1) else {
+            info!(target: "reth::cli", "Nothing to prune below block 0");
+            return Ok(())
+        };
+        let mode = PruneMode::Before(self.before);
+
+        for target in &self.segments {
+            info!(target: "reth::cli", segment = ?target, before = self.before, "Pruning segment");
+            let pruned = match target {
+                PruneTarget::TxLookup => {
+                    self.prune_segment(&provider_factory, TransactionLookup::new(mode), to_block)
+                        .await?
+                }
+                PruneTarget::AccountHistory => {
+                    self.prune_segment(&provider_factory, AccountHistory::new(mode), to_block)
+                        .await?
+                }
+                PruneTarget::Receipts => {
+                    let keep = self.env.chain.deposit_contract_address();
+                    self.prune_receipts(&provider_factory, keep, to_block).await?
+                }
+            };
+            info!(target: "reth::cli", segment = ?target, pruned, "Segment pruned");
+        }
+
+        Ok(())
+    }
+
+    /// Runs a reth prune segment in batches, returning the number of pruned entries.
+    async fn prune_segment<F, S>(&self, factory: &F, segment: S, to_block: u64) -> eyre::Result<u64>
+    where
+        F: DatabaseProviderFactory<ProviderRW: PruneCheckpointReader>,
+        S: Segment<F::ProviderRW>,
+    {
+        let mut pruned = 0;
+        loop {
+            let provider = factory.database_provider_rw()?;
+            let input = PruneInput {
+                previous_checkpoint: provider.get_prune_checkpoint(segment.segment())?,
+                to_block,
+                limiter: PruneLimiter::default()
+                    .set_deleted_entries_limit(self.batch_size as usize),
+            };
+            let output = segment.prune(&provider, input)?;
+            if let Some(checkpoint) = output.checkpoint {
+                segment.save_checkpoint(
+                    &provider,
+                    checkpoint.as_prune_checkpoint(PruneMode::Before(self.before)),
+                )?;
+            }
+            provider.commit()?;
+
+            pruned += output.pruned as u64;
+            let block = output.checkpoint.and_then(|checkpoint| checkpoint.block_number);
+            info!(target: "reth::cli", segment = %segment.segment(), pruned, ?block, "Pruned");
+            if output.progress.is_finished() {
+                return Ok(pruned)
+            }
+            self.pause().await;
+        }
+    }
+
+    /// Deletes receipts stored in the database up to `to_block`, keeping PoL receipts and receipts
+    /// with logs of `keep`.
+    ///
+    /// Receipts already moved to static files can only be removed as whole files, so they are
+    /// left in place. Kept receipts are few, so every run scans the range from the start.
+    async fn prune_receipts<F>(
+        &self,
+        factory: &F,
+        keep: Option<Address>,
+        to_block: u64,
+    ) -> eyre::Result<u64>
+    where
+        F: DatabaseProviderFactory<Provider: BlockBodyIndicesProvider> + StaticFileProviderFactory,
+    {
+        if let Some(highest) = factory
+            .static_file_provider()
+            .get_highest_static_file_block(StaticFileSegment::Receipts)
+        {
+            warn!(
+                target: "reth::cli",
+                highest,
+                "Receipts in static files cannot be pruned selectively and are kept"
+            );
+        }
+
+        let end = factory
+            .database_provider_ro()?
+            .block_body_indices(to_block)?
+            .ok_or_else(|| eyre::eyre!("block {to_block} not found"))?
+            .next_tx_num();
+
+        let mut next: TxNumber = 0;
+        let mut pruned = 0;
+        while next < end {
+            let provider = factory.database_provider_rw()?;
+            let (mut deleted, mut last) = (0, None);
+            {
+                let mut cursor = provider
+                    .tx_ref()
+                    .cursor_write::<tables::Receipts<Receipt<BerachainTxType>>>()?;
+                let mut walker = cursor.walk_range(next..end)?;
+                while let Some((tx_number, receipt)) = walker.next().transpose()? {
+                    last = Some(tx_number);
+                    if !is_kept(&receipt, keep) {
+                        walker.delete_current()?;
+                        deleted += 1;
+                        if deleted == self.batch_size {
+                            break
+                        }
+                    }
+                }
+            }
+
+            let Some(last) = last else { break };
+            provider.commit()?;
+
+            pruned += deleted;
+            next = last + 1;
+            info!(target: "reth::cli", segment = "receipts", pruned, last, "Pruned batch");
+            if next < end {
+                self.pause().await;
+            }
+        }
+        Ok(pruned)
+    }
+
+    /// Waits for the configured throttle.
+    async fn pause(&self) {
+        if self.throttle > 0 {
+            tokio::time::sleep(Duration::from_millis(self.throttle)).await;
+        }
+    }
+}
+
+/// Returns whether a receipt survives pruning: PoL receipts and receipts with logs of `keep`.
+fn is_kept(receipt: &Receipt<BerachainTxType>, keep: Option<Address>) -> bool {
+    receipt.tx_type == BerachainTxType::Berachain ||
+        keep.is_some_and(|keep| receipt.logs.iter().any(|log| log.address == keep))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::TxType;
+    use alloy_primitives::{Log, address};
+
+    #[test]
+    fn test_pol_and_deposit_receipts_are_kept() {
+        let deposit = address!("0x4242424242424242424242424242424242424242");
+        let receipt = |tx_type, logs| Receipt::<BerachainTxType> {
+            tx_type,
+            success: true,
+            cumulative_gas_used: 21_000,
+            logs,
+        };
+
+        let pol = receipt(BerachainTxType::Berachain, Vec::new());
+        assert!(is_kept(&pol, Some(deposit)));
+
+        let transfer = receipt(BerachainTxType::Ethereum(TxType::Eip1559), Vec::new());
+        assert!(!is_kept(&transfer, Some(deposit)));
+
+        let deposit_log = Log::new_unchecked(deposit, Vec::new(), Default::default());
+        let deposit_call = receipt(BerachainTxType::Ethereum(TxType::Eip1559), vec![deposit_log]);
+        assert!(is_kept(&deposit_call, Some(deposit)));
+        assert!(!is_kept(&deposit_call, None));
+    }
+}
@@ -0,0 +1,182 @@
+//! Portable receipt file format shared by `export-receipts` and `import-receipts`.
+//!
+//! The file is a plain concatenation of RLP lists, one per block:
+//!
+//! ```text
+//! [block_number, block_hash, [receipt_0, receipt_1, ...]]
+//! ```
+//!
+//! where each receipt is its EIP-2718 encoding (`type || rlp([status, cumulative_gas, bloom,
+//! logs])`, no type byte for legacy). PoL receipts carry type `0x7e`, matching what
+//! [`BerachainReceiptEnvelope`](crate::rpc::receipt::BerachainReceiptEnvelope) serves over RPC.
+
+use alloy_eips::eip2718::{Decodable2718, Encodable2718};
+use alloy_primitives::{B256, BlockNumber, Bytes};
+use alloy_rlp::{Decodable, Encodable, Header};
+use bytes::BufMut;
+use std::io::{self, Read};
+
+/// Receipts of a single block as stored in a receipt file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptFileBlock {
+    /// Block number.
+    pub number: BlockNumber,
+    /// Block hash, checked against the canonical chain on import.
+    pub hash: B256,
+    /// EIP-2718 encoded receipts, in transaction order.
+    pub receipts: Vec<Bytes>,
+}
+
+impl ReceiptFileBlock {
+    /// Creates a new entry by 2718-encoding `receipts`.
+    pub fn new<R: Encodable2718>(number: BlockNumber, hash: B256, receipts: &[R]) -> Self {
+        Self { number, hash, receipts: receipts.iter().map(|r| r.encoded_2718().into()).collect() }
+    }
+
+    /// Decodes the contained receipts.
+    pub fn decode_receipts<R: Decodable2718>(&self) -> eyre::Result<Vec<R>> {
+        self.receipts
+            .iter()
+            .enumerate()
+            .map(|(index, encoded)| {
+                R::decode_2718(&mut encoded.as_ref()).map_err(|err| {
+                    eyre::eyre!("invalid receipt {index} in block {}: {err}", self.number)
+                })
+            })
+            .collect()
+    }
+
+    fn receipts_payload_length(&self) -> usize {
+        self.receipts.iter().map(Encodable::length).sum()
+    }
+
+    fn payload_length(&self) -> usize {
+        let receipts_length = self.receipts_payload_length();
+        self.number.length() +
+            self.hash.length() +
+            Header { list: true, payload_length: receipts_length }.length() +
+            receipts_length
+    }
+}
+
+impl Encodable for ReceiptFileBlock {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header { list: true, payload_length: self.payload_length() }.encode(out);
+        self.number.encode(out);
+        self.hash.encode(out);
+        Header { list: true, payload_length: self.receipts_payload_length() }.encode(out);
+        for receipt in &self.receipts {
+            receipt.encode(out);
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        Header { list: true, payload_length }.length() + payload_length
+    }
+}
+
+impl Decodable for ReceiptFileBlock {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let header = Header::decode(buf)?;
+        if !header.list {
+            return Err(alloy_rlp::Error::UnexpectedString);
+        }
+        let started_len = buf.len();
+
+        let this = Self {
+            number: Decodable::decode(buf)?,
+            hash: Decodable::decode(buf)?,
+            receipts: Decodable::decode(buf)?,
+        };
+
+        let consumed = started_len - buf.len();
+        if consumed != header.payload_length {
+            return Err(alloy_rlp::Error::ListLengthMismatch {
+                expected: header.payload_length,
+                got: consumed,
+            });
+        }
+        Ok(this)
+    }
+}
+
+/// Decodes the block entries of a receipt file one at a time, so files of any size are imported
+/// in constant memory.
+#[derive(Debug)]
+pub struct ReceiptFileReader<R> {
+    reader: R,
+    /// Encoding of the entry being decoded.
+    buf: Vec<u8>,
+}
+
+impl<R: Read> ReceiptFileReader<R> {
+    /// Creates a reader decoding the entries of `reader`, which should be buffered.
+    pub fn new(reader: R) -> Self {
+        Self { reader, buf: Vec::new() }
+    }
+
+    /// Reads the next entry, `None` at the end of the file.
+    fn read_entry(&mut self) -> eyre::Result<Option<ReceiptFileBlock>> {
+        let mut first = [0u8];
+        // Only the first byte of an entry may be the end of the file
+        match self.reader.read_exact(&mut first) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let header_length = match first[0] {
+            0xc0..=0xf7 => 1,
+            byte @ 0xf8..=0xff => 1 + (byte - 0xf7) as usize,
+            _ => eyre::bail!("receipt file entry is not an RLP list"),
+        };
+
+        self.buf.clear();
+        self.buf.resize(header_length, first[0]);
+        self.reader.read_exact(&mut self.buf[1..])?;
+        let payload_length = Header::decode(&mut self.buf.as_slice())?.payload_length;
+        self.buf.resize(header_length + payload_length, 0);
+        self.reader.read_exact(&mut self.buf[header_length..])?;
+        Ok(Some(ReceiptFileBlock::decode(&mut self.buf.as_slice())?))
+    }
+}
+
+impl<R: Read> Iterator for ReceiptFileReader<R> {
+    type Item = eyre::Result<ReceiptFileBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_entry().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::compact_corpus::{self, BerachainReceipt};
+
+    #[test]
+    fn test_receipt_file_roundtrip() {
+        let receipts: Vec<BerachainReceipt> =
+            compact_corpus::receipts().into_iter().map(|entry| entry.value).collect();
+
+        let blocks = vec![
+            ReceiptFileBlock::new(1, B256::repeat_byte(1), &receipts),
+            ReceiptFileBlock::new::<BerachainReceipt>(2, B256::repeat_byte(2), &[]),
+        ];
+
+        let mut file = Vec::new();
+        for block in &blocks {
+            block.encode(&mut file);
+        }
+
+        let decoded =
+            ReceiptFileReader::new(file.as_slice()).collect::<eyre::Result<Vec<_>>>().unwrap();
+        assert_eq!(decoded, blocks);
+        assert_eq!(decoded[0].decode_receipts::<BerachainReceipt>().unwrap(), receipts);
+
+        // A truncated entry is an error rather than the end of the file
+        let mut truncated = ReceiptFileReader::new(&file[..file.len() - 1]);
+        assert!(truncated.next().unwrap().is_ok());
+        assert!(truncated.next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,121 @@
+//! `bera-reth repair-tx-lookup` command
+//!
+//! PoL system transactions never pass through the pool, so the only place their hashes get indexed
+//! is block insertion. Datadirs written by older releases (or imported with pruned lookups) can be
+//! missing those entries, which makes `eth_getTransactionByHash` return `null` for PoL hashes. This
+//! command walks stored block bodies and backfills `TransactionHashNumbers` from them.
+
+use crate::{storage::create_tables, transaction::POL_TX_TYPE};
+use alloy_eips::Typed2718;
+use alloy_primitives::BlockNumber;
+use clap::Parser;
+use reth::providers::{BlockBodyIndicesProvider, BlockNumReader, DBProvider, TransactionsProvider};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, CliNodeTypes, Environment, EnvironmentArgs};
+use reth_db_api::{
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_primitives_traits::SignedTransaction;
+use tracing::info;
+
+/// Counters reported at the end of a repair run.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RepairStats {
+    /// Transactions inspected.
+    pub scanned: u64,
+    /// Hashes that had no lookup entry.
+    pub missing: u64,
+    /// Hashes whose lookup entry pointed at the wrong transaction number.
+    pub mismatched: u64,
+    /// Missing or mismatched entries belonging to PoL transactions.
+    pub pol: u64,
+}
+
+/// `bera-reth repair-tx-lookup` command
+#[derive(Debug, Parser)]
+pub struct Command<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// First block to scan.
+    #[arg(long, default_value_t = 0)]
+    from: BlockNumber,
+
+    /// Last block to scan (inclusive). Defaults to the best stored block.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+
+    /// Only report missing entries without writing them.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Number of blocks to process before committing the write transaction.
+    #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u64).range(1..))]
+    commit_threshold: u64,
+}
+
+impl<C: ChainSpecParser> Command<C> {
+    /// Execute `repair-tx-lookup` command
+    pub async fn execute<N: CliNodeTypes<ChainSpec = C::ChainSpec>>(self) -> eyre::Result<()> {
+        let Environment { provider_factory, .. } = self.env.init::<N>(AccessRights::RW)?;
+        create_tables(provider_factory.db_ref())?;
+
+        let to = match self.to {
+            Some(to) => to,
+            None => provider_factory.provider()?.best_block_number()?,
+        };
+        eyre::ensure!(self.from <= to, "--from ({}) must not exceed --to ({to})", self.from);
+
+        info!(target: "reth::cli", from = self.from, to, dry_run = self.dry_run, "Scanning transaction lookup entries");
+
+        let mut stats = RepairStats::default();
+        let mut provider = provider_factory.database_provider_rw()?;
+
+        for block_number in self.from..=to {
+            let Some(indices) = provider.block_body_indices(block_number)? else { continue };
+            let transactions =
+                provider.transactions_by_block(block_number.into())?.unwrap_or_default();
+
+            for (tx_number, tx) in indices.tx_num_range().zip(transactions) {
+                stats.scanned += 1;
+                let hash = *tx.tx_hash();
+
+                match provider.tx_ref().get::<tables::TransactionHashNumbers>(hash)? {
+                    Some(stored) if stored == tx_number => continue,
+                    Some(_) => stats.mismatched += 1,
+                    None => stats.missing += 1,
+                }
+                if tx.ty() == POL_TX_TYPE {
+                    stats.pol += 1;
+                }
+
+                if !self.dry_run {
+                    provider.tx_ref().put::<tables::TransactionHashNumbers>(hash, tx_number)?;
+                }
+            }
+
+            if !self.dry_run && (block_number - self.from + 1) % self.commit_threshold == 0 {
+                provider.commit()?;
+                provider = provider_factory.database_provider_rw()?;
+                info!(target: "reth::cli", block_number, ?stats, "Committed repaired entries");
+            }
+        }
+
+        if !self.dry_run {
+            provider.commit()?;
+        }
+
+        info!(
+            target: "reth::cli",
+            scanned = stats.scanned,
+            missing = stats.missing,
+            mismatched = stats.mismatched,
+            pol = stats.pol,
+            dry_run = self.dry_run,
+            "Transaction lookup repair finished"
+        );
+
+        Ok(())
+    }
+}
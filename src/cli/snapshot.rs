@@ -0,0 +1,295 @@
+//! `bera-reth snapshot` command
+//!
+//! Packs the database and static files of a stopped node into a single zstd-compressed tar archive
+//! and unpacks it into an empty datadir. The archive starts with a metadata entry recording the
+//! chain, genesis hash, tip and bera-reth version, which `restore` checks before writing anything.
+//!
+//! `create` opens the datadir read-write, which takes reth's storage lock, so it fails while a node
+//! is running on the same datadir instead of archiving files that are being written.
+
+use crate::{chainspec::BerachainChainSpec, node::BerachainNode, storage::create_tables};
+use alloy_primitives::{B256, BlockNumber};
+use clap::{Parser, Subcommand};
+use reth::providers::{BlockHashReader, BlockNumReader, ChainSpecProvider};
+use reth_chainspec::EthChainSpec;
+use reth_cli::chainspec::ChainSpecParser;
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_node_core::version::CARGO_PKG_VERSION;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read},
+    path::{Component, Path, PathBuf},
+};
+use tracing::{info, warn};
+
+/// Name of the metadata entry, always the first entry of an archive.
+pub const METADATA_ENTRY: &str = "bera-reth-snapshot.json";
+
+/// Archive directory holding the database.
+const DB_DIR: &str = "db";
+
+/// Archive directory holding the static files.
+const STATIC_FILES_DIR: &str = "static_files";
+
+/// Description of the datadir contained in a snapshot archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotMetadata {
+    /// bera-reth version that created the archive.
+    pub version: String,
+    /// Chain id of the datadir.
+    pub chain_id: u64,
+    /// Genesis hash of the datadir.
+    pub genesis_hash: B256,
+    /// Last block stored in the datadir.
+    pub block_number: BlockNumber,
+    /// Hash of `block_number`.
+    pub block_hash: B256,
+}
+
+impl SnapshotMetadata {
+    /// Checks that the snapshot belongs to `chain_spec`.
+    pub fn ensure_chain(&self, chain_spec: &BerachainChainSpec) -> eyre::Result<()> {
+        eyre::ensure!(
+            self.chain_id == chain_spec.chain_id() &&
+                self.genesis_hash == chain_spec.genesis_hash(),
+            "snapshot is for chain {} with genesis {}, but --chain is {} with genesis {}",
+            self.chain_id,
+            self.genesis_hash,
+            chain_spec.chain_id(),
+            chain_spec.genesis_hash()
+        );
+        Ok(())
+    }
+}
+
+/// `bera-reth snapshot` command
+#[derive(Debug, Parser)]
+pub struct SnapshotCommand<C: ChainSpecParser> {
+    #[command(subcommand)]
+    command: Subcommands<C>,
+}
+
+/// `bera-reth snapshot` subcommands
+#[derive(Debug, Subcommand)]
+pub enum Subcommands<C: ChainSpecParser> {
+    /// Archive the database and static files of a stopped node
+    Create(CreateCommand<C>),
+    /// Unpack an archive into an empty datadir
+    Restore(RestoreCommand<C>),
+}
+
+/// `bera-reth snapshot create` command
+#[derive(Debug, Parser)]
+pub struct CreateCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// Destination archive.
+    #[arg(long, value_name = "FILE")]
+    path: PathBuf,
+
+    /// Expected tip of the datadir. Creation fails if the datadir is at a different block; use
+    /// `stage unwind` to bring it to this block first.
+    #[arg(long)]
+    block: Option<BlockNumber>,
+
+    /// zstd compression level.
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(i32).range(1..=22))]
+    level: i32,
+}
+
+/// `bera-reth snapshot restore` command
+#[derive(Debug, Parser)]
+pub struct RestoreCommand<C: ChainSpecParser> {
+    #[command(flatten)]
+    env: EnvironmentArgs<C>,
+
+    /// Archive written by `snapshot create`.
+    #[arg(long, value_name = "FILE")]
+    path: PathBuf,
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> SnapshotCommand<C> {
+    /// Execute `snapshot` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            Subcommands::Create(command) => command.execute(),
+            Subcommands::Restore(command) => command.execute(),
+        }
+    }
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> CreateCommand<C> {
+    fn execute(self) -> eyre::Result<()> {
+        let data_dir = self.env.datadir.clone().resolve_datadir(self.env.chain.chain());
+        let Environment { provider_factory, .. } =
+            self.env.init::<BerachainNode>(AccessRights::RW)?;
+        create_tables(provider_factory.db_ref())?;
+        let chain_spec = provider_factory.chain_spec();
+
+        let provider = provider_factory.provider()?;
+        let block_number = provider.last_block_number()?;
+        if let Some(expected) = self.block {
+            eyre::ensure!(
+                expected == block_number,
+                "datadir is at block {block_number}, not {expected}; run `bera-reth stage unwind \
+                 to-block {expected}` first"
+            );
+        }
+        let metadata = SnapshotMetadata {
+            version: CARGO_PKG_VERSION.to_string(),
+            chain_id: chain_spec.chain_id(),
+            genesis_hash: chain_spec.genesis_hash(),
+            block_number,
+            block_hash: provider
+                .block_hash(block_number)?
+                .ok_or_else(|| eyre::eyre!("hash of block {block_number} not found"))?,
+        };
+        drop(provider);
+
+        info!(target: "reth::cli", path = %self.path.display(), ?metadata, "Creating snapshot");
+
+        let encoder = zstd::Encoder::new(BufWriter::new(File::create(&self.path)?), self.level)?;
+        let mut archive = tar::Builder::new(encoder);
+        append_metadata(&mut archive, &metadata)?;
+        archive.append_dir_all(DB_DIR, data_dir.db())?;
+        archive.append_dir_all(STATIC_FILES_DIR, data_dir.static_files())?;
+        archive.into_inner()?.finish()?.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+
+        info!(target: "reth::cli", path = %self.path.display(), block_number, "Snapshot created");
+        Ok(())
+    }
+}
+
+impl<C: ChainSpecParser<ChainSpec = BerachainChainSpec>> RestoreCommand<C> {
+    fn execute(self) -> eyre::Result<()> {
+        let data_dir = self.env.datadir.clone().resolve_datadir(self.env.chain.chain());
+        let (db, static_files) = (data_dir.db(), data_dir.static_files());
+        for dir in [&db, &static_files] {
+            eyre::ensure!(
+                !dir.exists() || dir.read_dir()?.next().is_none(),
+                "{} is not empty, restore into a fresh datadir",
+                dir.display()
+            );
+        }
+
+        let decoder = zstd::Decoder::new(BufReader::new(File::open(&self.path)?))?;
+        let mut archive = tar::Archive::new(decoder);
+        let mut entries = archive.entries()?;
+
+        let mut first =
+            entries.next().ok_or_else(|| eyre::eyre!("snapshot archive is empty"))??;
+        eyre::ensure!(
+            first.path()?.as_ref() == Path::new(METADATA_ENTRY),
+            "not a bera-reth snapshot: first entry is {}",
+            first.path()?.display()
+        );
+        let mut contents = String::new();
+        first.read_to_string(&mut contents)?;
+        let metadata: SnapshotMetadata = serde_json::from_str(&contents)?;
+        metadata.ensure_chain(&self.env.chain)?;
+        if metadata.version != CARGO_PKG_VERSION {
+            warn!(
+                target: "reth::cli",
+                snapshot = %metadata.version,
+                current = CARGO_PKG_VERSION,
+                "Snapshot was created by a different bera-reth version"
+            );
+        }
+
+        info!(target: "reth::cli", path = %self.path.display(), ?metadata, "Restoring snapshot");
+
+        for entry in entries {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+            let Some(target) = restore_path(&path, &db, &static_files) else {
+                eyre::bail!("unexpected entry {} in snapshot", path.display());
+            };
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            entry.unpack(&target)?;
+        }
+
+        info!(target: "reth::cli", block_number = metadata.block_number, "Snapshot restored");
+        Ok(())
+    }
+}
+
+/// Writes `metadata` as the archive's first entry.
+fn append_metadata<W: std::io::Write>(
+    archive: &mut tar::Builder<W>,
+    metadata: &SnapshotMetadata,
+) -> eyre::Result<()> {
+    let contents = serde_json::to_vec_pretty(metadata)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive.append_data(&mut header, METADATA_ENTRY, contents.as_slice())?;
+    Ok(())
+}
+
+/// Maps an archive entry to its location in the datadir.
+///
+/// Returns `None` for entries outside the database and static file directories, including paths
+/// trying to escape them.
+fn restore_path(path: &Path, db: &Path, static_files: &Path) -> Option<PathBuf> {
+    let mut components = path.components();
+    let root = match components.next()? {
+        Component::Normal(dir) if dir == DB_DIR => db,
+        Component::Normal(dir) if dir == STATIC_FILES_DIR => static_files,
+        _ => return None,
+    };
+    let rest = components.as_path();
+    rest.components()
+        .all(|component| matches!(component, Component::Normal(_)))
+        .then(|| root.join(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_path_stays_inside_datadir() {
+        let (db, static_files) = (Path::new("/data/db"), Path::new("/volume/static_files"));
+
+        assert_eq!(
+            restore_path(Path::new("db/mdbx.dat"), db, static_files),
+            Some(db.join("mdbx.dat"))
+        );
+        assert_eq!(
+            restore_path(Path::new("static_files/static_file_headers_0_499999"), db, static_files),
+            Some(static_files.join("static_file_headers_0_499999"))
+        );
+        assert_eq!(restore_path(Path::new("db"), db, static_files), Some(db.to_path_buf()));
+        assert_eq!(restore_path(Path::new("db/../../etc/passwd"), db, static_files), None);
+        assert_eq!(restore_path(Path::new("/db/mdbx.dat"), db, static_files), None);
+        assert_eq!(restore_path(Path::new("blobstore/x"), db, static_files), None);
+    }
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let metadata = SnapshotMetadata {
+            version: CARGO_PKG_VERSION.to_string(),
+            chain_id: 80094,
+            genesis_hash: B256::repeat_byte(1),
+            block_number: 42,
+            block_hash: B256::repeat_byte(2),
+        };
+
+        let mut archive = tar::Builder::new(Vec::new());
+        append_metadata(&mut archive, &metadata).unwrap();
+        let bytes = archive.into_inner().unwrap();
+
+        let mut archive = tar::Archive::new(bytes.as_slice());
+        let mut entry = archive.entries().unwrap().next().unwrap().unwrap();
+        assert_eq!(entry.path().unwrap().as_ref(), Path::new(METADATA_ENTRY));
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).unwrap();
+        assert_eq!(serde_json::from_str::<SnapshotMetadata>(&contents).unwrap(), metadata);
+    }
+}
@@ -0,0 +1,182 @@
+//! `bera-reth test-genesis` command
+//!
+//! Checks that a genesis file survives a round trip through the node: the chainspec built from the
+//! file is serialized back to genesis JSON, re-parsed and rebuilt. The regenerated JSON must be a
+//! fixed point, the genesis hash and header must not change, and no field of the `berachain`
+//! section may be silently dropped by its typed configuration. Run it on every genesis file before
+//! launching a network.
+
+use crate::{chainspec::BerachainChainSpec, genesis::BerachainGenesisConfig};
+use alloy_genesis::Genesis;
+use alloy_primitives::B256;
+use clap::Parser;
+use reth_chainspec::EthChainSpec;
+use serde_json::Value;
+use std::path::PathBuf;
+use tracing::info;
+
+/// `bera-reth test-genesis` command
+#[derive(Debug, Parser)]
+pub struct TestGenesisCommand {
+    /// Genesis file to check.
+    #[arg(value_name = "FILE")]
+    genesis: PathBuf,
+
+    /// Expected genesis hash, e.g. the one published for the network.
+    #[arg(long, value_name = "HASH")]
+    genesis_hash: Option<B256>,
+
+    /// Write the regenerated genesis JSON to this file.
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+impl TestGenesisCommand {
+    /// Execute `test-genesis` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let contents = std::fs::read_to_string(&self.genesis)
+            .map_err(|err| eyre::eyre!("failed to read {}: {err}", self.genesis.display()))?;
+        let round_trip = round_trip(&contents)?;
+
+        if let Some(expected) = self.genesis_hash {
+            eyre::ensure!(
+                round_trip.genesis_hash == expected,
+                "genesis hash is {}, expected {expected}",
+                round_trip.genesis_hash
+            );
+        }
+        if let Some(output) = &self.output {
+            std::fs::write(output, serde_json::to_vec_pretty(&round_trip.regenerated)?)?;
+        }
+
+        info!(
+            target: "reth::cli",
+            chain_id = round_trip.chain_id,
+            genesis_hash = %round_trip.genesis_hash,
+            "Genesis round trip succeeded"
+        );
+        Ok(())
+    }
+}
+
+/// Outcome of a successful round trip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTrip {
+    /// Chain id of the genesis.
+    pub chain_id: u64,
+    /// Genesis hash, identical before and after the round trip.
+    pub genesis_hash: B256,
+    /// Genesis JSON regenerated from the chainspec.
+    pub regenerated: Value,
+}
+
+/// Builds the chainspec of `contents`, regenerates its genesis JSON and checks that nothing is lost
+/// or changed on the way.
+pub fn round_trip(contents: &str) -> eyre::Result<RoundTrip> {
+    let genesis: Genesis = serde_json::from_str(contents)?;
+
+    let raw = genesis
+        .config
+        .extra_fields
+        .get("berachain")
+        .cloned()
+        .ok_or_else(|| eyre::eyre!("genesis has no `config.berachain` section"))?;
+    let typed =
+        serde_json::to_value(BerachainGenesisConfig::try_from(&genesis.config.extra_fields)?)?;
+    let mut ignored = Vec::new();
+    ignored_fields(&raw, &typed, "config.berachain", &mut ignored);
+    eyre::ensure!(
+        ignored.is_empty(),
+        "genesis fields ignored by bera-reth: {}",
+        ignored.join(", ")
+    );
+
+    let chain_spec = chain_spec(genesis)?;
+    let regenerated = serde_json::to_value(chain_spec.genesis())?;
+    let reparsed: Genesis = serde_json::from_value(regenerated.clone())?;
+    eyre::ensure!(
+        serde_json::to_value(&reparsed)? == regenerated,
+        "regenerated genesis JSON is not a fixed point"
+    );
+    eyre::ensure!(&reparsed == chain_spec.genesis(), "genesis changed after re-parsing");
+
+    let rebuilt = chain_spec(reparsed)?;
+    eyre::ensure!(
+        rebuilt.genesis_hash() == chain_spec.genesis_hash(),
+        "genesis hash changed from {} to {} after the round trip",
+        chain_spec.genesis_hash(),
+        rebuilt.genesis_hash()
+    );
+    eyre::ensure!(
+        rebuilt.genesis_header() == chain_spec.genesis_header(),
+        "genesis header changed after the round trip"
+    );
+    eyre::ensure!(
+        rebuilt.pol_contract() == chain_spec.pol_contract() &&
+            rebuilt.prague1_minimum_base_fee == chain_spec.prague1_minimum_base_fee &&
+            rebuilt.pol_tx_gas_limit == chain_spec.pol_tx_gas_limit &&
+            rebuilt.prague2 == chain_spec.prague2,
+        "Berachain settings changed after the round trip"
+    );
+
+    Ok(RoundTrip {
+        chain_id: chain_spec.chain_id(),
+        genesis_hash: chain_spec.genesis_hash(),
+        regenerated,
+    })
+}
+
+/// Builds the chainspec of `genesis`.
+fn chain_spec(genesis: Genesis) -> eyre::Result<BerachainChainSpec> {
+    BerachainChainSpec::try_from(genesis).map_err(|err| eyre::eyre!("invalid genesis: {err}"))
+}
+
+/// Collects the paths of object fields in `raw` that are missing from `typed`.
+fn ignored_fields(raw: &Value, typed: &Value, path: &str, ignored: &mut Vec<String>) {
+    let (Value::Object(raw), Value::Object(typed)) = (raw, typed) else { return };
+    for (key, value) in raw {
+        let field = format!("{path}.{key}");
+        match typed.get(key) {
+            Some(typed) => ignored_fields(value, typed, &field, ignored),
+            None => ignored.push(field),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chainspec::BERACHAIN_DEV;
+
+    fn dev_genesis() -> Value {
+        serde_json::to_value(BERACHAIN_DEV.genesis()).unwrap()
+    }
+
+    #[test]
+    fn test_dev_genesis_round_trip() {
+        let round_trip = round_trip(&dev_genesis().to_string()).unwrap();
+        assert_eq!(round_trip.genesis_hash, BERACHAIN_DEV.genesis_hash());
+        assert_eq!(round_trip.chain_id, BERACHAIN_DEV.chain_id());
+        assert_eq!(round_trip.regenerated, dev_genesis());
+    }
+
+    #[test]
+    fn test_ignored_berachain_field_is_reported() {
+        let mut genesis = dev_genesis();
+        genesis["config"]["berachain"]["prague1"]["minimumBaseFee"] = 1.into();
+        genesis["config"]["berachain"]["prague2"] = serde_json::json!({ "time": 0 });
+
+        let err = round_trip(&genesis.to_string()).unwrap_err().to_string();
+        assert!(err.contains("config.berachain.prague1.minimumBaseFee"), "{err}");
+        assert!(err.contains("config.berachain.prague2"), "{err}");
+    }
+
+    #[test]
+    fn test_invalid_genesis_is_an_error() {
+        let mut genesis = dev_genesis();
+        genesis["config"]["cancunTime"] = 10.into();
+
+        let err = round_trip(&genesis.to_string()).unwrap_err().to_string();
+        assert!(err.contains("Cancun"), "{err}");
+    }
+}
@@ -0,0 +1,62 @@
+use reth::consensus::ConsensusError;
+use reth_payload_primitives::NewPayloadError;
+use serde::{Deserialize, Serialize};
+
+/// Berachain-specific reasons for rejecting a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BerachainPayloadError {
+    /// Prague1 block without a transaction.
+    #[error("Prague1 block must contain at least one PoL transaction")]
+    MissingPolTransaction,
+    /// Prague1 block whose first transaction is not a PoL transaction.
+    #[error("First transaction in Prague1 block must be a PoL transaction")]
+    PolTransactionNotFirst,
+    /// PoL transaction after the first transaction.
+    #[error("PoL transaction found at invalid position {index}, only first transaction can be PoL")]
+    MisplacedPolTransaction {
+        /// Position of the transaction in the block.
+        index: usize,
+    },
+    /// PoL transaction that differs from the one expected for the block.
+    #[error("Invalid PoL transaction: {reason}")]
+    InvalidPolTransaction {
+        /// Why the transaction was rejected.
+        reason: String,
+    },
+    /// Prague1 block without the previous proposer's public key.
+    #[error("Prague1 active but parent proposer pubkey missing")]
+    MissingProposerPubkey,
+    /// Prague1 block whose header does not carry the previous proposer's public key.
+    #[error("Prague1 active but parent proposer pubkey mismatch")]
+    ProposerPubkeyMismatch,
+    /// Block before Prague1 with a previous proposer's public key.
+    #[error("Prague1 not active but parent proposer pubkey present")]
+    UnexpectedProposerPubkey,
+    /// Block after Prague1 whose base fee is below the minimum base fee.
+    #[error("base fee {base_fee} is below the Prague1 minimum base fee of {minimum} wei")]
+    BaseFeeBelowMinimum {
+        /// Base fee of the block.
+        base_fee: u64,
+        /// Minimum base fee of the chain.
+        minimum: u64,
+    },
+    /// PoL transaction in a block before Prague1.
+    #[error("Prague1 not active but PoL transaction found at position {index}")]
+    UnexpectedPolTransaction {
+        /// Position of the transaction in the block.
+        index: usize,
+    },
+}
+
+impl From<BerachainPayloadError> for ConsensusError {
+    fn from(err: BerachainPayloadError) -> Self {
+        Self::Other(err.to_string())
+    }
+}
+
+impl From<BerachainPayloadError> for NewPayloadError {
+    fn from(err: BerachainPayloadError) -> Self {
+        Self::Other(Box::new(err))
+    }
+}
@@ -1,6 +1,11 @@
+pub mod error;
+pub mod quarantine;
+
 use crate::{
     chainspec::BerachainChainSpec,
+    consensus::{error::BerachainPayloadError, quarantine::BadBlocks},
     hardforks::BerachainHardforks,
+    node::evm::pol_metrics,
     primitives::{BerachainBlock, BerachainHeader, BerachainPrimitives},
     transaction::{BerachainTxEnvelope, pol::validate_pol_transaction},
 };
@@ -15,8 +20,11 @@ use reth_node_builder::{BuilderContext, components::ConsensusBuilder};
 use reth_primitives_traits::{NodePrimitives, RecoveredBlock, SealedBlock, SealedHeader};
 use std::{fmt::Debug, sync::Arc};
 
-#[derive(Debug, Default, Clone, Copy)]
-pub struct BerachainConsensusBuilder;
+#[derive(Debug, Default, Clone)]
+pub struct BerachainConsensusBuilder {
+    /// Quarantine shared with the engine validator.
+    pub bad_blocks: Arc<BadBlocks>,
+}
 
 impl<Node> ConsensusBuilder<Node> for BerachainConsensusBuilder
 where
@@ -27,33 +35,52 @@ where
     type Consensus = Arc<dyn FullConsensus<BerachainPrimitives, Error = ConsensusError>>;
 
     async fn build_consensus(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::Consensus> {
-        Ok(Arc::new(BerachainBeaconConsensus::new(ctx.chain_spec())))
+        Ok(Arc::new(
+            BerachainBeaconConsensus::new(ctx.chain_spec()).with_bad_blocks(self.bad_blocks),
+        ))
     }
 }
 
+/// Ethereum beacon consensus with the Berachain block rules.
+///
+/// On top of [`EthBeaconConsensus`], headers must carry the previous proposer's public key exactly
+/// from Prague1 on and a base fee following from their parent's with the Berachain denominator and
+/// minimum base fee, and bodies must start with the canonical PoL transaction from Prague1 on and
+/// contain no other PoL transaction. Blocks before Prague1 contain no PoL transaction at all. The
+/// rules run on every block, so blocks downloaded during sync are held to them as well as payloads
+/// received through the Engine API.
 #[derive(Debug, Clone)]
 pub struct BerachainBeaconConsensus {
     /// Inner Ethereum beacon consensus implementation
     inner: EthBeaconConsensus<BerachainChainSpec>,
     chain_spec: Arc<BerachainChainSpec>,
+    bad_blocks: Arc<BadBlocks>,
 }
 
 impl BerachainBeaconConsensus {
     pub fn new(chain_spec: Arc<BerachainChainSpec>) -> Self {
-        Self { inner: EthBeaconConsensus::new(chain_spec.clone()), chain_spec }
+        Self {
+            inner: EthBeaconConsensus::new(chain_spec.clone()),
+            chain_spec,
+            bad_blocks: Default::default(),
+        }
+    }
+
+    /// Records rejected blocks in `bad_blocks`, shared with the engine validator.
+    pub fn with_bad_blocks(mut self, bad_blocks: Arc<BadBlocks>) -> Self {
+        self.bad_blocks = bad_blocks;
+        self
     }
 
     /// Will ensure the PoL transaction is the first tx in the block and has the correct hash
     fn validate_pol_transaction(
         &self,
         block: &SealedBlock<BerachainBlock>,
-    ) -> Result<(), ConsensusError> {
+    ) -> Result<(), BerachainPayloadError> {
         let transactions: Vec<_> = block.body().transactions().collect();
 
         if transactions.is_empty() {
-            return Err(ConsensusError::Other(
-                "Prague1 block must contain at least one PoL transaction".into(),
-            ));
+            return Err(BerachainPayloadError::MissingPolTransaction);
         }
 
         // Check first transaction is PoL and validate its shape
@@ -61,47 +88,103 @@ impl BerachainBeaconConsensus {
         if let BerachainTxEnvelope::Berachain(pol_tx) = first_tx {
             self.validate_pol_transaction_shape(pol_tx, block)?;
         } else {
-            return Err(ConsensusError::Other(
-                "First transaction in Prague1 block must be a PoL transaction".into(),
-            ));
+            return Err(BerachainPayloadError::PolTransactionNotFirst);
         }
 
         // Check no other transactions are PoL
         for (index, tx) in transactions.iter().enumerate().skip(1) {
             if matches!(tx, BerachainTxEnvelope::Berachain(_)) {
-                return Err(ConsensusError::Other(format!(
-                    "PoL transaction found at invalid position {index}, only first transaction can be PoL"
-                )));
+                return Err(BerachainPayloadError::MisplacedPolTransaction { index });
             }
         }
 
         Ok(())
     }
 
+    /// Ensures the previous proposer's public key is present exactly when Prague1 is active.
+    fn validate_proposer_pubkey(
+        &self,
+        header: &BerachainHeader,
+    ) -> Result<(), BerachainPayloadError> {
+        check_proposer_pubkey(
+            self.chain_spec.is_prague1_active_at_timestamp(header.timestamp),
+            header.prev_proposer_pubkey.is_some(),
+        )
+    }
+
     fn validate_pol_transaction_shape(
         &self,
         pol_tx: &alloy_primitives::Sealed<crate::transaction::PoLTx>,
         block: &SealedBlock<BerachainBlock>,
-    ) -> Result<(), ConsensusError> {
+    ) -> Result<(), BerachainPayloadError> {
         let header = block.header();
 
-        let expected_pubkey = header.prev_proposer_pubkey.ok_or_else(|| {
-            ConsensusError::Other(
-                "Block header missing prev_proposer_pubkey for PoL transaction validation".into(),
-            )
-        })?;
+        let expected_pubkey =
+            header.prev_proposer_pubkey.ok_or(BerachainPayloadError::MissingProposerPubkey)?;
 
-        let base_fee = header
-            .base_fee_per_gas
-            .ok_or_else(|| ConsensusError::Other("Base fee must be present in header".into()))?;
+        let base_fee = header.base_fee_per_gas.ok_or_else(|| {
+            BerachainPayloadError::InvalidPolTransaction {
+                reason: "Base fee must be present in header".into(),
+            }
+        })?;
 
         validate_pol_transaction(
             pol_tx,
             self.chain_spec.clone(),
             expected_pubkey,
             alloy_primitives::U256::from(header.number),
+            header.timestamp,
             base_fee,
         )
+        .map_err(|err| {
+            pol_metrics::record_hash_mismatch(&expected_pubkey);
+            BerachainPayloadError::InvalidPolTransaction { reason: err.to_string() }
+        })
+    }
+}
+
+/// Checks that a header carries a previous proposer's public key exactly when Prague1 is active.
+fn check_proposer_pubkey(
+    prague1_active: bool,
+    has_pubkey: bool,
+) -> Result<(), BerachainPayloadError> {
+    match (prague1_active, has_pubkey) {
+        (true, false) => Err(BerachainPayloadError::MissingProposerPubkey),
+        (false, true) => Err(BerachainPayloadError::UnexpectedProposerPubkey),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the base fee of `header` is not below the minimum base fee once Prague1 is active
+/// at `parent`.
+///
+/// The exact EIP-1559 update is checked by the Ethereum rules, which take the expected base fee
+/// from [`BerachainChainSpec::next_base_fee_after`] and so apply the Berachain denominator and
+/// minimum. This check only runs first, so blocks below the minimum get their own error.
+fn check_minimum_base_fee(
+    chain_spec: &BerachainChainSpec,
+    header: &BerachainHeader,
+    parent: &BerachainHeader,
+) -> Result<(), BerachainPayloadError> {
+    let (Some(base_fee), Some(minimum)) =
+        (header.base_fee_per_gas, chain_spec.minimum_base_fee_at_timestamp(parent.timestamp))
+    else {
+        // Missing base fees are rejected by the Ethereum rules
+        return Ok(())
+    };
+    if base_fee < minimum {
+        return Err(BerachainPayloadError::BaseFeeBelowMinimum { base_fee, minimum })
+    }
+    Ok(())
+}
+
+/// Checks that a block before Prague1 contains no PoL transaction.
+fn check_no_pol_transaction<'a>(
+    transactions: impl IntoIterator<Item = &'a BerachainTxEnvelope>,
+) -> Result<(), BerachainPayloadError> {
+    match transactions.into_iter().position(|tx| matches!(tx, BerachainTxEnvelope::Berachain(_))) {
+        Some(index) => Err(BerachainPayloadError::UnexpectedPolTransaction { index }),
+        None => Ok(()),
     }
 }
 
@@ -134,14 +217,21 @@ impl Consensus<BerachainBlock> for BerachainBeaconConsensus {
         &self,
         block: &SealedBlock<BerachainBlock>,
     ) -> Result<(), Self::Error> {
+        self.bad_blocks.check(&block.hash())?;
+
         <EthBeaconConsensus<BerachainChainSpec> as Consensus<BerachainBlock>>::validate_block_pre_execution(
             &self.inner,
             block,
         )?;
 
-        if self.chain_spec.is_prague1_active_at_timestamp(block.header().timestamp) {
-            self.validate_pol_transaction(block)?;
-        }
+        let result = if self.chain_spec.is_prague1_active_at_timestamp(block.header().timestamp) {
+            self.validate_pol_transaction(block)
+        } else {
+            check_no_pol_transaction(block.body().transactions())
+        };
+        result.inspect_err(|err| {
+            self.bad_blocks.insert(block.num_hash(), err.clone());
+        })?;
         Ok(())
     }
 }
@@ -154,7 +244,12 @@ impl HeaderValidator<BerachainHeader> for BerachainBeaconConsensus {
         <EthBeaconConsensus<BerachainChainSpec> as HeaderValidator<BerachainHeader>>::validate_header(
             &self.inner,
             header,
-        )
+        )?;
+
+        self.validate_proposer_pubkey(header.header()).inspect_err(|err| {
+            self.bad_blocks.insert(header.num_hash(), err.clone());
+        })?;
+        Ok(())
     }
 
     fn validate_header_against_parent(
@@ -162,6 +257,12 @@ impl HeaderValidator<BerachainHeader> for BerachainBeaconConsensus {
         header: &SealedHeader<BerachainHeader>,
         parent: &SealedHeader<BerachainHeader>,
     ) -> Result<(), ConsensusError> {
+        check_minimum_base_fee(&self.chain_spec, header.header(), parent.header()).inspect_err(
+            |err| {
+                self.bad_blocks.insert(header.num_hash(), err.clone());
+            },
+        )?;
+
         <EthBeaconConsensus<BerachainChainSpec> as HeaderValidator<BerachainHeader>>::validate_header_against_parent(&self.inner, header, parent)
     }
 }
@@ -170,11 +271,12 @@ impl HeaderValidator<BerachainHeader> for BerachainBeaconConsensus {
 mod tests {
     use super::*;
     use crate::{
-        chainspec::BerachainChainSpec,
+        chainspec::{BERACHAIN_DEV, BerachainChainSpec},
         primitives::header::BlsPublicKey,
         transaction::pol::{create_pol_transaction, validate_pol_transaction},
     };
-    use alloy_primitives::U256;
+    use alloy_consensus::{Signed, TxEnvelope, TxLegacy};
+    use alloy_primitives::{Signature, U256};
     use reth_chainspec::EthChainSpec;
     use std::sync::Arc;
 
@@ -194,6 +296,91 @@ mod tests {
         assert_eq!(consensus.chain_spec.chain_id(), 1);
     }
 
+    #[test]
+    fn test_proposer_pubkey_matches_prague1_activation() {
+        assert_eq!(check_proposer_pubkey(true, true), Ok(()));
+        assert_eq!(check_proposer_pubkey(false, false), Ok(()));
+        assert_eq!(
+            check_proposer_pubkey(true, false),
+            Err(BerachainPayloadError::MissingProposerPubkey)
+        );
+        assert_eq!(
+            check_proposer_pubkey(false, true),
+            Err(BerachainPayloadError::UnexpectedProposerPubkey)
+        );
+    }
+
+    #[test]
+    fn test_base_fee_follows_berachain_rules() {
+        let chain_spec = BERACHAIN_DEV.clone();
+        let consensus = BerachainBeaconConsensus::new(chain_spec.clone());
+        let minimum = chain_spec.prague1_minimum_base_fee;
+        let parent = SealedHeader::seal_slow(BerachainHeader {
+            gas_used: 30_000_000,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(4_800_000_000),
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(0),
+            ..Default::default()
+        });
+        let child = |parent: &SealedHeader<BerachainHeader>, base_fee| {
+            SealedHeader::seal_slow(BerachainHeader {
+                parent_hash: parent.hash(),
+                number: parent.number + 1,
+                timestamp: parent.timestamp + 2,
+                base_fee_per_gas: Some(base_fee),
+                ..parent.header().clone()
+            })
+        };
+
+        // A full block raises the base fee by 1/48 with the Berachain denominator
+        assert_eq!(
+            consensus.validate_header_against_parent(&child(&parent, 4_900_000_000), &parent),
+            Ok(())
+        );
+        assert!(matches!(
+            consensus.validate_header_against_parent(&child(&parent, 5_400_000_000), &parent),
+            Err(ConsensusError::BaseFeeDiff(diff))
+                if diff.got == 5_400_000_000 && diff.expected == 4_900_000_000
+        ));
+
+        let quiet = SealedHeader::seal_slow(BerachainHeader {
+            gas_used: 0,
+            base_fee_per_gas: Some(minimum),
+            ..parent.header().clone()
+        });
+        assert_eq!(
+            consensus.validate_header_against_parent(&child(&quiet, minimum), &quiet),
+            Ok(())
+        );
+        assert_eq!(
+            check_minimum_base_fee(&chain_spec, child(&quiet, minimum - 1).header(), &quiet),
+            Err(BerachainPayloadError::BaseFeeBelowMinimum { base_fee: minimum - 1, minimum })
+        );
+        assert_eq!(
+            consensus.validate_header_against_parent(&child(&quiet, minimum - 1), &quiet),
+            Err(BerachainPayloadError::BaseFeeBelowMinimum { base_fee: minimum - 1, minimum }
+                .into())
+        );
+    }
+
+    #[test]
+    fn test_no_pol_transaction_before_prague1() {
+        let chain_spec = mock_berachain_chainspec();
+        let pol_tx =
+            create_pol_transaction(chain_spec, mock_bls_pubkey(), U256::from(10), 0, 1000).unwrap();
+        let user_tx = BerachainTxEnvelope::Ethereum(TxEnvelope::Legacy(Signed::new_unhashed(
+            TxLegacy::default(),
+            Signature::test_signature(),
+        )));
+
+        assert_eq!(check_no_pol_transaction([&user_tx, &user_tx]), Ok(()));
+        assert_eq!(
+            check_no_pol_transaction([&user_tx, &pol_tx]),
+            Err(BerachainPayloadError::UnexpectedPolTransaction { index: 1 })
+        );
+    }
+
     #[test]
     fn test_pol_transaction_creation_and_validation() {
         let chain_spec = mock_berachain_chainspec();
@@ -202,7 +389,7 @@ mod tests {
         let base_fee = 1000u64;
 
         let pol_tx_envelope =
-            create_pol_transaction(chain_spec.clone(), pubkey, block_number, base_fee);
+            create_pol_transaction(chain_spec.clone(), pubkey, block_number, 0, base_fee);
 
         assert!(pol_tx_envelope.is_ok(), "PoL transaction creation should succeed");
 
@@ -211,8 +398,14 @@ mod tests {
             _ => panic!("Expected PoL transaction"),
         };
 
-        let validation_result =
-            validate_pol_transaction(&pol_tx, chain_spec.clone(), pubkey, block_number, base_fee);
+        let validation_result = validate_pol_transaction(
+            &pol_tx,
+            chain_spec.clone(),
+            pubkey,
+            block_number,
+            0,
+            base_fee,
+        );
 
         assert!(validation_result.is_ok(), "Valid PoL transaction should pass validation");
     }
@@ -226,7 +419,7 @@ mod tests {
         let base_fee = 1000u64;
 
         let pol_tx_envelope =
-            create_pol_transaction(chain_spec.clone(), correct_pubkey, block_number, base_fee)
+            create_pol_transaction(chain_spec.clone(), correct_pubkey, block_number, 0, base_fee)
                 .unwrap();
 
         let pol_tx = match pol_tx_envelope {
@@ -235,7 +428,7 @@ mod tests {
         };
 
         let validation_result =
-            validate_pol_transaction(&pol_tx, chain_spec, wrong_pubkey, block_number, base_fee);
+            validate_pol_transaction(&pol_tx, chain_spec, wrong_pubkey, block_number, 0, base_fee);
 
         assert!(
             validation_result.is_err(),
@@ -253,7 +446,7 @@ mod tests {
         let wrong_base_fee = 2000u64;
 
         let pol_tx_envelope =
-            create_pol_transaction(chain_spec.clone(), pubkey, block_number, correct_base_fee)
+            create_pol_transaction(chain_spec.clone(), pubkey, block_number, 0, correct_base_fee)
                 .unwrap();
 
         let pol_tx = match pol_tx_envelope {
@@ -262,7 +455,7 @@ mod tests {
         };
 
         let validation_result =
-            validate_pol_transaction(&pol_tx, chain_spec, pubkey, block_number, wrong_base_fee);
+            validate_pol_transaction(&pol_tx, chain_spec, pubkey, block_number, 0, wrong_base_fee);
 
         assert!(
             validation_result.is_err(),
@@ -280,7 +473,7 @@ mod tests {
         let base_fee = 1000u64;
 
         let pol_tx_envelope =
-            create_pol_transaction(chain_spec.clone(), pubkey, correct_block_number, base_fee)
+            create_pol_transaction(chain_spec.clone(), pubkey, correct_block_number, 0, base_fee)
                 .unwrap();
 
         let pol_tx = match pol_tx_envelope {
@@ -289,7 +482,7 @@ mod tests {
         };
 
         let validation_result =
-            validate_pol_transaction(&pol_tx, chain_spec, pubkey, wrong_block_number, base_fee);
+            validate_pol_transaction(&pol_tx, chain_spec, pubkey, wrong_block_number, 0, base_fee);
 
         assert!(
             validation_result.is_err(),
@@ -306,10 +499,10 @@ mod tests {
         let base_fee = 1337u64;
 
         let pol_tx1_envelope =
-            create_pol_transaction(chain_spec.clone(), pubkey, block_number, base_fee).unwrap();
+            create_pol_transaction(chain_spec.clone(), pubkey, block_number, 0, base_fee).unwrap();
 
         let pol_tx2_envelope =
-            create_pol_transaction(chain_spec, pubkey, block_number, base_fee).unwrap();
+            create_pol_transaction(chain_spec, pubkey, block_number, 0, base_fee).unwrap();
 
         let pol_tx1 = match pol_tx1_envelope {
             crate::transaction::BerachainTxEnvelope::Berachain(sealed_tx) => sealed_tx,
@@ -0,0 +1,131 @@
+//! Quarantine of blocks that failed Berachain-specific validation.
+//!
+//! A block with a misplaced or wrong PoL transaction or a wrong previous proposer public key can
+//! never become valid, yet consensus clients re-send payloads and syncing peers serve the same
+//! bodies again. The first failure records the block hash with its [`BerachainPayloadError`];
+//! afterwards the block is rejected with the recorded error before any other validation runs.
+//! Quarantined blocks are listed by `admin_badBlocks`.
+
+use crate::consensus::error::BerachainPayloadError;
+use alloy_eips::BlockNumHash;
+use alloy_primitives::BlockHash;
+use reth_metrics::{Metrics, metrics::Counter};
+use schnellru::{ByLength, LruMap};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::warn;
+
+/// Number of quarantined blocks remembered.
+pub const DEFAULT_QUARANTINED_BLOCKS: u32 = 128;
+
+/// Bad block quarantine metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.bad_blocks")]
+struct BadBlocksMetrics {
+    /// Blocks quarantined after failing Berachain-specific validation.
+    quarantined: Counter,
+    /// Quarantined blocks received again.
+    rejected_again: Counter,
+}
+
+/// A quarantined block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadBlock {
+    /// Number and hash of the block.
+    pub block: BlockNumHash,
+    /// Why the block was rejected.
+    pub error: BerachainPayloadError,
+    /// Unix time of the first rejection, in seconds.
+    pub first_seen: u64,
+    /// How often the block was received after it was quarantined.
+    pub seen_again: u64,
+}
+
+/// Blocks that failed Berachain-specific validation, by hash.
+pub struct BadBlocks {
+    blocks: Mutex<LruMap<BlockHash, BadBlock, ByLength>>,
+    metrics: BadBlocksMetrics,
+}
+
+impl std::fmt::Debug for BadBlocks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BadBlocks").finish_non_exhaustive()
+    }
+}
+
+impl Default for BadBlocks {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUARANTINED_BLOCKS)
+    }
+}
+
+impl BadBlocks {
+    /// Creates a quarantine holding up to `capacity` blocks.
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            blocks: Mutex::new(LruMap::new(ByLength::new(capacity))),
+            metrics: BadBlocksMetrics::default(),
+        }
+    }
+
+    /// Quarantines `block`, which failed validation with `error`.
+    pub fn insert(&self, block: BlockNumHash, error: BerachainPayloadError) {
+        let mut blocks = self.blocks.lock().expect("not poisoned");
+        if blocks.peek(&block.hash).is_some() {
+            return
+        }
+        warn!(target: "consensus::berachain", ?block, %error, "Quarantined bad block");
+        let first_seen = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        blocks.insert(block.hash, BadBlock { block, error, first_seen, seen_again: 0 });
+        self.metrics.quarantined.increment(1);
+    }
+
+    /// Returns the error of `hash` if it is quarantined, counting it as received again.
+    pub fn check(&self, hash: &BlockHash) -> Result<(), BerachainPayloadError> {
+        let mut blocks = self.blocks.lock().expect("not poisoned");
+        let Some(bad) = blocks.get(hash) else { return Ok(()) };
+        bad.seen_again += 1;
+        self.metrics.rejected_again.increment(1);
+        Err(bad.error.clone())
+    }
+
+    /// Returns the quarantined blocks, most recently rejected first.
+    pub fn list(&self) -> Vec<BadBlock> {
+        self.blocks.lock().expect("not poisoned").iter().map(|(_, bad)| bad.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+
+    #[test]
+    fn test_quarantined_blocks_are_rejected_again() {
+        let bad_blocks = BadBlocks::new(2);
+        let block = BlockNumHash::new(10, B256::repeat_byte(1));
+        assert!(bad_blocks.check(&block.hash).is_ok());
+
+        bad_blocks.insert(block, BerachainPayloadError::PolTransactionNotFirst);
+        bad_blocks.insert(block, BerachainPayloadError::MissingPolTransaction);
+        assert_eq!(
+            bad_blocks.check(&block.hash),
+            Err(BerachainPayloadError::PolTransactionNotFirst)
+        );
+
+        let listed = bad_blocks.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].seen_again, 1);
+    }
+
+    #[test]
+    fn test_errors_serialize_with_kind() {
+        let error = BerachainPayloadError::MisplacedPolTransaction { index: 3 };
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "misplacedPolTransaction", "index": 3 }));
+    }
+}
@@ -1,16 +1,32 @@
 use crate::{
     chainspec::BerachainChainSpec,
-    engine::payload::{
-        BerachainBuiltPayload, BerachainPayloadAttributes, BerachainPayloadBuilderAttributes,
+    engine::{
+        payload::{
+            BerachainBuiltPayload, BerachainPayloadAttributes, BerachainPayloadBuilderAttributes,
+        },
+        report::{BuilderReport, BuilderReports, ExclusionReason, Inclusion},
+    },
+    hardforks::BerachainHardforks,
+    node::{
+        config::BerachainConfig,
+        evm::{
+            config::{BerachainEvmConfig, BerachainNextBlockEnvAttributes},
+            pol_metrics,
+        },
     },
-    node::evm::config::{BerachainEvmConfig, BerachainNextBlockEnvAttributes},
     primitives::{BerachainHeader, BerachainPrimitives},
+    storage::{
+        blob_sidecars::{DEFAULT_LOCAL_SIDECARS, LocalSidecars, install_sidecar_retention},
+        compression::CompressionConfig,
+    },
     transaction::BerachainTxEnvelope,
 };
-use alloy_consensus::Transaction;
+use alloy_consensus::{Transaction, Typed2718};
+use alloy_eips::eip7594::BlobTransactionSidecarVariant;
 use alloy_primitives::U256;
+use clap::Args;
 use reth::{
-    api::{FullNodeTypes, NodeTypes, PayloadBuilderError, PayloadTypes, TxTy},
+    api::{BuiltPayload, FullNodeTypes, NodeTypes, PayloadBuilderError, PayloadTypes, TxTy},
     chainspec::EthereumHardforks,
     providers::StateProviderFactory,
     revm::{State, context::Block, database::StateProviderDatabase},
@@ -35,9 +51,52 @@ use reth_transaction_pool::{
     BestTransactions, BestTransactionsAttributes, ValidPoolTransaction,
     error::{Eip4844PoolTransactionError, InvalidPoolTransactionError},
 };
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Instant};
 use tracing::{debug, trace, warn};
 
+/// Settings of the payload builder.
+///
+/// From Prague1 on, the executor runs the PoL transaction before any user transaction and the
+/// assembler inserts it at index 0, so every built block starts with it. The PoL transaction
+/// consumes no block gas, but the builder withholds its gas limit from user transactions of each
+/// Prague1 block by default, so they never take the room of the distribution. The reservation can
+/// be lowered, down to 0, and is capped at the gas limit of the PoL transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BlockBuilderConfig {
+    /// Gas withheld from user transactions in Prague1 blocks, the gas limit of the PoL transaction
+    /// if unset.
+    pub pol_gas_reservation: Option<u64>,
+}
+
+impl BlockBuilderConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &BlockBuilderArgs) -> Self {
+        if let Some(pol_gas_reservation) = args.pol_gas_reservation {
+            self.pol_gas_reservation = Some(pol_gas_reservation);
+        }
+        self
+    }
+
+    /// Returns the gas withheld from user transactions of a block: `pol_tx_gas_limit`, which is set
+    /// once Prague1 is active, or the configured reservation capped at it.
+    pub fn pol_gas_reservation(&self, pol_tx_gas_limit: Option<u64>) -> u64 {
+        pol_tx_gas_limit
+            .map_or(0, |limit| self.pol_gas_reservation.map_or(limit, |gas| gas.min(limit)))
+    }
+}
+
+/// Command line arguments of the payload builder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Berachain builder")]
+pub struct BlockBuilderArgs {
+    /// Gas of each Prague1 block withheld from user transactions for the PoL distribution, at
+    /// most the gas limit of the PoL transaction [default: the gas limit of the PoL transaction]
+    #[arg(long = "builder.pol-gas-reservation", value_name = "GAS")]
+    pub pol_gas_reservation: Option<u64>,
+}
+
 type BestTransactionsIter<Pool> = Box<
     dyn BestTransactions<Item = Arc<ValidPoolTransaction<<Pool as TransactionPool>::Transaction>>>,
 >;
@@ -49,7 +108,27 @@ type BestTransactionsIter<Pool> = Box<
 /// Berachain payload attributes and Ethereum payload building logic.
 #[derive(Clone, Default, Debug)]
 #[non_exhaustive]
-pub struct BerachainPayloadServiceBuilder;
+pub struct BerachainPayloadServiceBuilder {
+    /// Settings of the built blocks.
+    pub config: BlockBuilderConfig,
+    /// Codecs the sidecars of built blocks are stored with.
+    pub compression: CompressionConfig,
+    /// Inclusion reports of the built payloads, served by `bera_builderReport`.
+    pub reports: Arc<BuilderReports>,
+}
+
+impl BerachainPayloadServiceBuilder {
+    /// Creates a service builder with the builder and compression settings of `config`.
+    pub fn new(config: &BerachainConfig) -> Self {
+        Self { config: config.builder, compression: config.compression, ..Default::default() }
+    }
+
+    /// Records the inclusion reports of built payloads in `reports`.
+    pub fn with_reports(mut self, reports: Arc<BuilderReports>) -> Self {
+        self.reports = reports;
+        self
+    }
+}
 
 impl<Types, Node, Pool> PayloadBuilderBuilder<Node, Pool, BerachainEvmConfig>
     for BerachainPayloadServiceBuilder
@@ -77,12 +156,24 @@ where
         let chain = ctx.chain_spec().chain();
         let gas_limit = conf.gas_limit_for(chain);
 
+        // Sidecars of built blocks are kept until the block is canonical and they are stored
+        let local_sidecars =
+            Arc::new(LocalSidecars::new(DEFAULT_LOCAL_SIDECARS, self.compression.blob_sidecars));
+        install_sidecar_retention(
+            local_sidecars.clone(),
+            ctx.provider().clone(),
+            ctx.task_executor(),
+        );
+
         Ok(BerachainPayloadBuilder::new(
             ctx.provider().clone(),
             pool,
             evm_config,
             EthereumBuilderConfig::new().with_gas_limit(gas_limit),
-        ))
+        )
+        .with_config(self.config)
+        .with_local_sidecars(local_sidecars)
+        .with_reports(self.reports))
     }
 }
 
@@ -102,17 +193,74 @@ pub struct BerachainPayloadBuilder<Pool, Client> {
     evm_config: BerachainEvmConfig,
     /// Payload builder configuration
     builder_config: EthereumBuilderConfig,
+    /// Berachain settings of the built blocks
+    config: BlockBuilderConfig,
+    /// Sidecars of the blob transactions in built blocks, kept until the blocks are canonical
+    local_sidecars: Option<Arc<LocalSidecars>>,
+    /// Inclusion reports of built payloads
+    reports: Arc<BuilderReports>,
 }
 
 impl<Pool, Client> BerachainPayloadBuilder<Pool, Client> {
     /// Create a new Berachain payload builder
-    pub const fn new(
+    pub fn new(
         client: Client,
         pool: Pool,
         evm_config: BerachainEvmConfig,
         builder_config: EthereumBuilderConfig,
     ) -> Self {
-        Self { client, pool, evm_config, builder_config }
+        Self {
+            client,
+            pool,
+            evm_config,
+            builder_config,
+            config: BlockBuilderConfig::default(),
+            local_sidecars: None,
+            reports: Arc::default(),
+        }
+    }
+
+    /// Builds blocks with the Berachain settings of `config`.
+    pub fn with_config(mut self, config: BlockBuilderConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Keeps the sidecars of the blob transactions in built blocks in `local_sidecars`.
+    pub fn with_local_sidecars(mut self, local_sidecars: Arc<LocalSidecars>) -> Self {
+        self.local_sidecars = Some(local_sidecars);
+        self
+    }
+
+    /// Records the inclusion reports of built payloads in `reports`.
+    pub fn with_reports(mut self, reports: Arc<BuilderReports>) -> Self {
+        self.reports = reports;
+        self
+    }
+
+    /// Keeps the sidecars of the blob transactions in `payload` until its block is canonical.
+    fn keep_sidecars(&self, payload: &BerachainBuiltPayload) {
+        let Some(local_sidecars) = &self.local_sidecars else { return };
+        // Sidecars are pushed in the order their transactions were included
+        let blob_txs = payload.block().body().transactions.iter().filter(|tx| tx.is_eip4844());
+        for (index, tx) in blob_txs.enumerate() {
+            // Payloads are rebuilt several times per slot with mostly the same transactions
+            if local_sidecars.contains(tx.hash()) {
+                continue
+            }
+            let sidecar = match &payload.sidecars {
+                BlobSidecars::Empty => None,
+                BlobSidecars::Eip4844(sidecars) => {
+                    sidecars.get(index).cloned().map(BlobTransactionSidecarVariant::Eip4844)
+                }
+                BlobSidecars::Eip7594(sidecars) => {
+                    sidecars.get(index).cloned().map(BlobTransactionSidecarVariant::Eip7594)
+                }
+            };
+            if let Some(sidecar) = sidecar {
+                local_sidecars.insert(*tx.hash(), &sidecar);
+            }
+        }
     }
 }
 
@@ -128,14 +276,20 @@ where
         &self,
         args: BuildArguments<Self::Attributes, BerachainBuiltPayload>,
     ) -> Result<BuildOutcome<BerachainBuiltPayload>, PayloadBuilderError> {
-        default_berachain_payload(
+        let outcome = default_berachain_payload(
             self.evm_config.clone(),
             self.client.clone(),
             self.pool.clone(),
             self.builder_config.clone(),
+            self.config,
+            &self.reports,
             args,
             |attributes| self.pool.best_transactions_with_attributes(attributes),
-        )
+        )?;
+        if let BuildOutcome::Better { payload, .. } = &outcome {
+            self.keep_sidecars(payload);
+        }
+        Ok(outcome)
     }
 
     fn on_missing_payload(
@@ -160,6 +314,8 @@ where
             self.client.clone(),
             self.pool.clone(),
             self.builder_config.clone(),
+            self.config,
+            &self.reports,
             args,
             |attributes| self.pool.best_transactions_with_attributes(attributes),
         )?
@@ -179,6 +335,8 @@ pub fn default_berachain_payload<Client, Pool, F>(
     client: Client,
     pool: Pool,
     builder_config: EthereumBuilderConfig,
+    config: BlockBuilderConfig,
+    reports: &BuilderReports,
     args: BuildArguments<BerachainPayloadBuilderAttributes, BerachainBuiltPayload>,
     best_txs: F,
 ) -> Result<BuildOutcome<BerachainBuiltPayload>, PayloadBuilderError>
@@ -187,6 +345,7 @@ where
     Pool: TransactionPool<Transaction: PoolTransaction<Consensus = BerachainTxEnvelope>>,
     F: FnOnce(BestTransactionsAttributes) -> BestTransactionsIter<Pool>,
 {
+    let started = Instant::now();
     let BuildArguments { mut cached_reads, config, cancel, best_payload } = args;
     let PayloadConfig { parent_header, attributes } = config;
 
@@ -215,7 +374,13 @@ where
 
     debug!(target: "payload_builder", id=%attributes.id, parent_header = ?parent_header.hash(), parent_number = parent_header.number, "building new payload");
     let mut cumulative_gas_used = 0;
-    let block_gas_limit: u64 = builder.evm_mut().block().gas_limit;
+    let pol_gas_reservation = config.pol_gas_reservation(
+        chain_spec
+            .is_prague1_active_at_timestamp(attributes.timestamp())
+            .then(|| chain_spec.pol_tx_gas_limit_at_timestamp(attributes.timestamp())),
+    );
+    let block_gas_limit: u64 =
+        builder.evm_mut().block().gas_limit.saturating_sub(pol_gas_reservation);
     let base_fee = builder.evm_mut().block().basefee;
 
     let mut best_txs = best_txs(BestTransactionsAttributes::new(
@@ -223,6 +388,10 @@ where
         builder.evm_mut().block().blob_gasprice().map(|gasprice| gasprice as u64),
     ));
     let mut total_fees = U256::ZERO;
+    let mut inclusion = Inclusion::default();
+    let tip = |pool_tx: &ValidPoolTransaction<Pool::Transaction>| {
+        pool_tx.transaction.effective_tip_per_gas(base_fee).unwrap_or_default()
+    };
 
     builder.apply_pre_execution_changes().map_err(|err| {
         warn!(target: "payload_builder", %err, "failed to apply pre-execution changes");
@@ -245,6 +414,7 @@ where
             // we can't fit this transaction into the block, so we need to mark it as invalid
             // which also removes all dependent transaction from the iterator before we can
             // continue
+            inclusion.exclude(ExclusionReason::GasLimit, pool_tx.gas_limit(), tip(&pool_tx));
             best_txs.mark_invalid(
                 &pool_tx,
                 InvalidPoolTransactionError::ExceedsGasLimit(pool_tx.gas_limit(), block_gas_limit),
@@ -272,6 +442,7 @@ where
                 // the iterator. This is similar to the gas limit condition
                 // for regular transactions above.
                 trace!(target: "payload_builder", tx=?tx.hash(), ?block_blob_count, "skipping blob transaction because it would exceed the max blob count per block");
+                inclusion.exclude(ExclusionReason::BlobLimit, pool_tx.gas_limit(), tip(&pool_tx));
                 best_txs.mark_invalid(
                     &pool_tx,
                     InvalidPoolTransactionError::Eip4844(
@@ -307,6 +478,11 @@ where
             blob_tx_sidecar = match blob_sidecar_result {
                 Ok(sidecar) => Some(sidecar),
                 Err(error) => {
+                    inclusion.exclude(
+                        ExclusionReason::BlobSidecar,
+                        pool_tx.gas_limit(),
+                        tip(&pool_tx),
+                    );
                     best_txs.mark_invalid(&pool_tx, InvalidPoolTransactionError::Eip4844(error));
                     continue
                 }
@@ -321,10 +497,16 @@ where
                 if error.is_nonce_too_low() {
                     // if the nonce is too low, we can skip this transaction
                     trace!(target: "payload_builder", %error, ?tx, "skipping nonce too low transaction");
+                    inclusion.exclude(
+                        ExclusionReason::NonceTooLow,
+                        pool_tx.gas_limit(),
+                        tip(&pool_tx),
+                    );
                 } else {
                     // if the transaction is invalid, we can skip it and all of its
                     // descendants
                     trace!(target: "payload_builder", %error, ?tx, "skipping invalid transaction and its descendants");
+                    inclusion.exclude(ExclusionReason::Invalid, pool_tx.gas_limit(), tip(&pool_tx));
                     best_txs.mark_invalid(
                         &pool_tx,
                         InvalidPoolTransactionError::Consensus(
@@ -352,6 +534,7 @@ where
         let miner_fee =
             tx.effective_tip_per_gas(base_fee).expect("fee is always valid; execution succeeded");
         total_fees += U256::from(miner_fee) * U256::from(gas_used);
+        inclusion.include(gas_used, miner_fee);
         cumulative_gas_used += gas_used;
 
         // Add blob tx sidecar to the payload.
@@ -377,9 +560,40 @@ where
     let sealed_block = Arc::new(block.sealed_block().clone());
     debug!(target: "payload_builder", id=%attributes.id, sealed_block_header = ?sealed_block.sealed_header(), "sealed built block");
 
+    reports.insert(BuilderReport {
+        payload_id: attributes.id,
+        block_hash: sealed_block.hash(),
+        block_number: sealed_block.header().number,
+        pending: pool.pool_size().pending as u64,
+        inclusion,
+    });
+
     let payload = BerachainBuiltPayload::new(attributes.id, sealed_block, total_fees, requests)
         // add blob sidecars from the executed txs
         .with_sidecars(blob_sidecars);
 
+    if chain_spec.is_prague1_active_at_timestamp(attributes.timestamp()) {
+        pol_metrics::record_payload_build(started.elapsed());
+    }
     Ok(BuildOutcome::Better { payload, cached_reads })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pol_gas_reservation() {
+        let config = BlockBuilderConfig { pol_gas_reservation: Some(1_000_000) };
+        assert_eq!(config.pol_gas_reservation(None), 0);
+        assert_eq!(config.pol_gas_reservation(Some(30_000_000)), 1_000_000);
+        assert_eq!(config.pol_gas_reservation(Some(500_000)), 500_000);
+
+        // The PoL transaction gas limit is reserved unless overridden
+        let default = BlockBuilderConfig::default();
+        assert_eq!(default.pol_gas_reservation(None), 0);
+        assert_eq!(default.pol_gas_reservation(Some(5_000_000)), 5_000_000);
+        let args = BlockBuilderArgs { pol_gas_reservation: Some(0) };
+        assert_eq!(default.with_args(&args).pol_gas_reservation(Some(30_000_000)), 0);
+    }
+}
@@ -0,0 +1,201 @@
+//! Latency breakdown of `engine_newPayload`.
+//!
+//! A Berachain block must be processed well within its 2 second slot. The
+//! `berachain_new_payload_*_seconds` histograms split the processing of a payload into its phases
+//! so operators can tell which one threatens the budget on their hardware:
+//!
+//! - `decode`: converting the payload into a block and sealing its header,
+//! - `pubkey_validation`: checking the previous proposer public key against the Prague1 rules,
+//! - `sender_recovery`: recovering the transaction senders,
+//! - `execution`: executing the block, including the PoL distribution,
+//! - `state_root`: the rest of inserting the block, dominated by computing the state root,
+//! - `persistence`: from the block becoming canonical until it is written to the database, as
+//!   observed by polling the last persisted block every [`PERSISTENCE_POLL_INTERVAL`].
+//!
+//! `execution` also covers blocks executed by the payload builder and during sync; the other
+//! phases only cover payloads received from the consensus client.
+
+use crate::primitives::BerachainPrimitives;
+use alloy_consensus::BlockHeader;
+use alloy_primitives::{BlockHash, BlockNumber};
+use futures::{Stream, StreamExt};
+use reth::{
+    providers::{BlockNumReader, DatabaseProviderFactory},
+    tasks::TaskExecutor,
+};
+use reth_engine_primitives::ConsensusEngineEvent;
+use reth_metrics::{Metrics, metrics::Histogram};
+use schnellru::{ByLength, LruMap};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Number of recent blocks whose execution time and insertion are remembered.
+const TRACKED_BLOCKS: u32 = 64;
+
+/// Interval at which the last persisted block is read while inserted blocks await persistence.
+pub const PERSISTENCE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Phase of processing a payload that is timed by its caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadPhase {
+    /// Converting the payload into a block.
+    Decode,
+    /// Checking the previous proposer public key.
+    PubkeyValidation,
+    /// Recovering the transaction senders.
+    SenderRecovery,
+}
+
+/// Latency metrics of `engine_newPayload`.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.new_payload")]
+struct PayloadLatencyMetrics {
+    /// Time to convert a payload into a block, in seconds.
+    decode_seconds: Histogram,
+    /// Time to check the previous proposer public key, in seconds.
+    pubkey_validation_seconds: Histogram,
+    /// Time to recover the transaction senders, in seconds.
+    sender_recovery_seconds: Histogram,
+    /// Time to execute a block, in seconds.
+    execution_seconds: Histogram,
+    /// Time to insert a block not spent executing it, in seconds.
+    state_root_seconds: Histogram,
+    /// Time from a block becoming canonical until it was written, in seconds.
+    persistence_seconds: Histogram,
+}
+
+/// Tracks blocks across the phases timed in different components.
+pub struct PayloadLatency {
+    metrics: PayloadLatencyMetrics,
+    /// Execution time of recently executed blocks, by number and parent hash.
+    executions: Mutex<LruMap<(BlockNumber, BlockHash), Duration, ByLength>>,
+    /// When recently inserted blocks became canonical, by number.
+    inserted: Mutex<LruMap<BlockNumber, Instant, ByLength>>,
+}
+
+impl Default for PayloadLatency {
+    fn default() -> Self {
+        Self {
+            metrics: PayloadLatencyMetrics::default(),
+            executions: Mutex::new(LruMap::new(ByLength::new(TRACKED_BLOCKS))),
+            inserted: Mutex::new(LruMap::new(ByLength::new(TRACKED_BLOCKS))),
+        }
+    }
+}
+
+impl std::fmt::Debug for PayloadLatency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PayloadLatency").finish_non_exhaustive()
+    }
+}
+
+impl PayloadLatency {
+    /// Records the duration of `phase`.
+    pub fn record(&self, phase: PayloadPhase, elapsed: Duration) {
+        let histogram = match phase {
+            PayloadPhase::Decode => &self.metrics.decode_seconds,
+            PayloadPhase::PubkeyValidation => &self.metrics.pubkey_validation_seconds,
+            PayloadPhase::SenderRecovery => &self.metrics.sender_recovery_seconds,
+        };
+        histogram.record(elapsed);
+    }
+
+    /// Records the execution of block `number` on top of `parent_hash`.
+    pub fn on_executed(&self, number: BlockNumber, parent_hash: BlockHash, elapsed: Duration) {
+        self.metrics.execution_seconds.record(elapsed);
+        self.executions.lock().expect("not poisoned").insert((number, parent_hash), elapsed);
+    }
+
+    /// Records the insertion of block `number`, which took `elapsed` including its execution.
+    fn on_inserted(&self, number: BlockNumber, parent_hash: BlockHash, elapsed: Duration) {
+        let execution =
+            self.executions.lock().expect("not poisoned").remove(&(number, parent_hash));
+        if let Some(execution) = execution {
+            self.metrics.state_root_seconds.record(elapsed.saturating_sub(execution));
+        }
+        self.inserted.lock().expect("not poisoned").insert(number, Instant::now());
+    }
+
+    /// Returns whether inserted blocks await persistence.
+    fn awaits_persistence(&self) -> bool {
+        !self.inserted.lock().expect("not poisoned").is_empty()
+    }
+
+    /// Records that the blocks up to `number` were written to the database.
+    pub fn on_persisted(&self, number: BlockNumber) {
+        let mut inserted = self.inserted.lock().expect("not poisoned");
+        let persisted: Vec<_> =
+            inserted.iter().map(|(inserted, _)| *inserted).filter(|n| *n <= number).collect();
+        for number in persisted {
+            if let Some(inserted) = inserted.remove(&number) {
+                self.metrics.persistence_seconds.record(inserted.elapsed());
+            }
+        }
+    }
+}
+
+/// Starts attributing the blocks inserted into `latency` to the state root and persistence phases.
+///
+/// Blocks count as persisted once the last block of `provider`'s database reaches them.
+pub fn install_payload_latency<Ev, P>(
+    latency: Arc<PayloadLatency>,
+    engine_events: Ev,
+    provider: P,
+    executor: &TaskExecutor,
+) where
+    Ev: Stream<Item = ConsensusEngineEvent<BerachainPrimitives>> + Send + 'static,
+    P: DatabaseProviderFactory<Provider: BlockNumReader> + Send + Sync + 'static,
+{
+    executor.spawn(async move {
+        let mut engine_events = std::pin::pin!(engine_events);
+        let mut interval = tokio::time::interval(PERSISTENCE_POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                event = engine_events.next() => {
+                    let Some(event) = event else { return };
+                    if let ConsensusEngineEvent::CanonicalBlockAdded(block, elapsed) = event {
+                        let header = block.recovered_block().header();
+                        latency.on_inserted(header.number(), header.parent_hash(), elapsed);
+                    }
+                }
+                _ = interval.tick() => {
+                    if !latency.awaits_persistence() {
+                        continue
+                    }
+                    let persisted =
+                        provider.database_provider_ro().and_then(|db| db.last_block_number());
+                    if let Ok(persisted) = persisted {
+                        latency.on_persisted(persisted);
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_executions_are_matched_once() {
+        let latency = PayloadLatency::default();
+        let parent = BlockHash::repeat_byte(1);
+        latency.on_executed(10, parent, Duration::from_millis(300));
+
+        latency.on_inserted(10, parent, Duration::from_millis(500));
+        assert!(latency.executions.lock().unwrap().peek(&(10, parent)).is_none());
+        assert!(latency.inserted.lock().unwrap().peek(&10).is_some());
+
+        latency.on_inserted(11, parent, Duration::from_millis(500));
+        latency.on_inserted(12, parent, Duration::from_millis(500));
+        latency.on_persisted(11);
+        assert!(latency.inserted.lock().unwrap().peek(&10).is_none());
+        assert!(latency.inserted.lock().unwrap().peek(&11).is_none());
+        assert!(latency.awaits_persistence());
+        latency.on_persisted(12);
+        assert!(!latency.awaits_persistence());
+    }
+}
@@ -0,0 +1,329 @@
+//! Mock consensus client for local multi-node devnets.
+//!
+//! The `bera-consensus-mock` binary plays BeaconKit's role for a set of bera-reth nodes, so a
+//! devnet of several execution clients can run without the consensus layer. Each slot, the next
+//! node in turn proposes: it receives a forkchoice update with [`BerachainPayloadAttributes`]
+//! naming the proposer of the parent block as `parentProposerPubKey`, and the built payload is
+//! fetched and handed to every node as `newPayload` followed by a forkchoice update. Like
+//! CometBFT, the mock finalizes each block as soon as it is accepted, so the forkchoice state
+//! names the new block as head, safe and finalized block.
+//!
+//! Proposer public keys rotate through `--proposer-pubkey`, or through keys derived from the
+//! node index if none are given. Engine API versions follow the forks of `--chain`, as BeaconKit
+//! picks them.
+
+use crate::{
+    chainspec::{BerachainChainSpec, BerachainChainSpecParser},
+    engine::payload::BerachainPayloadAttributes,
+    hardforks::BerachainHardforks,
+    primitives::header::BlsPublicKey,
+};
+use alloy_eips::eip4844::kzg_to_versioned_hash;
+use alloy_primitives::{Address, B256, Bytes, U64, keccak256};
+use alloy_rpc_types::engine::{ForkchoiceState, ForkchoiceUpdated, PayloadId, PayloadStatus};
+use clap::Parser;
+use jsonrpsee_core::{client::ClientT, params::ArrayParams, rpc_params};
+use jsonrpsee_http_client::{HttpClient, HttpClientBuilder, transport::HttpBackend};
+use reth::chainspec::EthereumHardforks;
+use reth_cli::chainspec::ChainSpecParser;
+use reth_node_ethereum::engine::EthPayloadAttributes;
+use reth_rpc_layer::{AuthClientLayer, AuthClientService, JwtSecret};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::{info, warn};
+
+/// Client of an engine API endpoint.
+pub type EngineClient = HttpClient<AuthClientService<HttpBackend>>;
+
+/// Returns a client of the engine API at `url`, authenticated with the secret in `jwt_secret`.
+pub fn engine_client(url: &str, jwt_secret: &Path) -> eyre::Result<EngineClient> {
+    let secret = JwtSecret::from_file(jwt_secret)?;
+    HttpClientBuilder::default()
+        .set_http_middleware(tower::ServiceBuilder::new().layer(AuthClientLayer::new(secret)))
+        .build(url)
+        .map_err(|err| eyre::eyre!("invalid engine API URL {url}: {err}"))
+}
+
+/// Arguments of `bera-consensus-mock`.
+#[derive(Debug, Parser)]
+#[command(about = "Drives bera-reth nodes through the engine API in place of BeaconKit")]
+pub struct MockConsensusArgs {
+    /// Chain the nodes run
+    #[arg(long, value_name = "CHAIN_OR_PATH", value_parser = BerachainChainSpecParser::parser())]
+    pub chain: Arc<BerachainChainSpec>,
+
+    /// Engine API URL of a node. Can be repeated; nodes propose in turn
+    #[arg(long = "engine", value_name = "URL", required = true)]
+    pub engines: Vec<String>,
+
+    /// File holding the hex encoded engine API secret shared by the nodes
+    #[arg(long = "jwtsecret", value_name = "FILE")]
+    pub jwt_secret: PathBuf,
+
+    /// Milliseconds between slots
+    #[arg(long, value_name = "MS", default_value_t = 2000)]
+    pub block_time: u64,
+
+    /// BLS public key of a proposer. Can be repeated; proposers rotate every block
+    #[arg(long = "proposer-pubkey", value_name = "HEX")]
+    pub proposer_pubkeys: Vec<BlsPublicKey>,
+
+    /// Address block rewards are paid to
+    #[arg(long, value_name = "ADDRESS", default_value_t = Address::ZERO)]
+    pub fee_recipient: Address,
+
+    /// Stop after this many blocks
+    #[arg(long)]
+    pub blocks: Option<u64>,
+}
+
+/// Returns the proposer public keys to rotate through, derived from the node index if none are
+/// configured.
+fn proposers(configured: &[BlsPublicKey], nodes: usize) -> Vec<BlsPublicKey> {
+    if !configured.is_empty() {
+        return configured.to_vec()
+    }
+    (0..nodes as u8).map(|index| BlsPublicKey::repeat_byte(0xb0 | (index & 0x0f))).collect()
+}
+
+/// Fields of the head block read from a node.
+#[derive(Debug, Deserialize)]
+struct Head {
+    hash: B256,
+    number: U64,
+    timestamp: U64,
+}
+
+/// Engine API methods of the forks active at a timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Methods {
+    fork_choice_updated: &'static str,
+    get_payload: &'static str,
+    new_payload: &'static str,
+}
+
+impl Methods {
+    /// Returns the methods BeaconKit uses for a block at `timestamp`.
+    fn at(chain_spec: &BerachainChainSpec, timestamp: u64) -> Self {
+        if chain_spec.is_prague1_active_at_timestamp(timestamp) {
+            let get_payload = if chain_spec.is_osaka_active_at_timestamp(timestamp) {
+                "engine_getPayloadV5"
+            } else {
+                "engine_getPayloadV4P11"
+            };
+            Self {
+                fork_choice_updated: "engine_forkchoiceUpdatedV3P11",
+                get_payload,
+                new_payload: "engine_newPayloadV4P11",
+            }
+        } else if chain_spec.is_prague_active_at_timestamp(timestamp) {
+            Self {
+                fork_choice_updated: "engine_forkchoiceUpdatedV3",
+                get_payload: "engine_getPayloadV4",
+                new_payload: "engine_newPayloadV4",
+            }
+        } else {
+            Self {
+                fork_choice_updated: "engine_forkchoiceUpdatedV3",
+                get_payload: "engine_getPayloadV3",
+                new_payload: "engine_newPayloadV3",
+            }
+        }
+    }
+}
+
+/// Returns the `newPayload` parameters of a `getPayload` response.
+fn new_payload_params(
+    methods: Methods,
+    envelope: &Value,
+    parent_beacon_block_root: B256,
+    prev_proposer_pubkey: BlsPublicKey,
+) -> eyre::Result<ArrayParams> {
+    let commitments: Vec<Bytes> = match envelope.pointer("/blobsBundle/commitments") {
+        Some(commitments) => serde_json::from_value(commitments.clone())?,
+        None => Vec::new(),
+    };
+    let versioned_hashes: Vec<B256> =
+        commitments.iter().map(|commitment| kzg_to_versioned_hash(commitment)).collect();
+
+    let mut params = ArrayParams::new();
+    params.insert(&envelope["executionPayload"])?;
+    params.insert(versioned_hashes)?;
+    params.insert(parent_beacon_block_root)?;
+    if methods.new_payload != "engine_newPayloadV3" {
+        let requests = envelope.get("executionRequests").cloned().unwrap_or(Value::Array(vec![]));
+        params.insert(requests)?;
+    }
+    if methods.new_payload == "engine_newPayloadV4P11" {
+        params.insert(prev_proposer_pubkey)?;
+    }
+    Ok(params)
+}
+
+/// Mock consensus client driving a set of nodes.
+pub struct MockConsensus {
+    chain_spec: Arc<BerachainChainSpec>,
+    nodes: Vec<EngineClient>,
+    proposers: Vec<BlsPublicKey>,
+    fee_recipient: Address,
+    head: B256,
+    number: u64,
+    timestamp: u64,
+}
+
+impl MockConsensus {
+    /// Connects to the nodes of `args` and reads the head of the first one.
+    pub async fn new(args: &MockConsensusArgs) -> eyre::Result<Self> {
+        let nodes = args
+            .engines
+            .iter()
+            .map(|url| engine_client(url, &args.jwt_secret))
+            .collect::<eyre::Result<Vec<_>>>()?;
+        let head: Head =
+            nodes[0].request("eth_getBlockByNumber", rpc_params!["latest", false]).await?;
+        Ok(Self {
+            chain_spec: args.chain.clone(),
+            proposers: proposers(&args.proposer_pubkeys, nodes.len()),
+            nodes,
+            fee_recipient: args.fee_recipient,
+            head: head.hash,
+            number: head.number.to(),
+            timestamp: head.timestamp.to(),
+        })
+    }
+
+    /// Has the next node in turn propose a block on the head and makes it canonical on every node.
+    pub async fn propose(&mut self) -> eyre::Result<B256> {
+        let number = self.number + 1;
+        let slot = number as usize;
+        let proposer = &self.nodes[slot % self.nodes.len()];
+        let prev_proposer_pubkey = self.proposers[(slot - 1) % self.proposers.len()];
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let timestamp = now.max(self.timestamp + 1);
+        let methods = Methods::at(&self.chain_spec, timestamp);
+
+        let parent_beacon_block_root = keccak256(number.to_be_bytes());
+        let attributes = BerachainPayloadAttributes {
+            inner: EthPayloadAttributes {
+                timestamp,
+                prev_randao: keccak256(parent_beacon_block_root),
+                suggested_fee_recipient: self.fee_recipient,
+                withdrawals: Some(Vec::new()),
+                parent_beacon_block_root: Some(parent_beacon_block_root),
+            },
+            prev_proposer_pubkey: self
+                .chain_spec
+                .is_prague1_active_at_timestamp(timestamp)
+                .then_some(prev_proposer_pubkey),
+        };
+        let updated: ForkchoiceUpdated = proposer
+            .request(methods.fork_choice_updated, rpc_params![self.forkchoice(), attributes])
+            .await?;
+        let payload_id: PayloadId = updated
+            .payload_id
+            .ok_or_else(|| eyre::eyre!("no payload started: {:?}", updated.payload_status))?;
+        let envelope: Value =
+            proposer.request(methods.get_payload, rpc_params![payload_id]).await?;
+        let hash: B256 = serde_json::from_value(envelope["executionPayload"]["blockHash"].clone())?;
+
+        let params =
+            new_payload_params(methods, &envelope, parent_beacon_block_root, prev_proposer_pubkey)?;
+        for (index, node) in self.nodes.iter().enumerate() {
+            let status: PayloadStatus = node.request(methods.new_payload, params.clone()).await?;
+            if !status.is_valid() {
+                warn!(
+                    target: "consensus::mock",
+                    index,
+                    number,
+                    %hash,
+                    ?status,
+                    "Payload not valid"
+                );
+            }
+        }
+
+        self.head = hash;
+        for (index, node) in self.nodes.iter().enumerate() {
+            let updated: ForkchoiceUpdated = node
+                .request("engine_forkchoiceUpdatedV3", rpc_params![self.forkchoice(), Value::Null])
+                .await?;
+            if !updated.is_valid() {
+                let status = updated.payload_status;
+                warn!(target: "consensus::mock", index, number, %hash, ?status, "Head not valid");
+            }
+        }
+        self.number = number;
+        self.timestamp = timestamp;
+        Ok(hash)
+    }
+
+    /// Returns the forkchoice state with the head as head, safe and finalized block.
+    const fn forkchoice(&self) -> ForkchoiceState {
+        ForkchoiceState {
+            head_block_hash: self.head,
+            safe_block_hash: self.head,
+            finalized_block_hash: self.head,
+        }
+    }
+}
+
+/// Proposes a block every `--block-time` until `--blocks` are built or the process is stopped.
+pub async fn run(args: MockConsensusArgs) -> eyre::Result<()> {
+    let mut consensus = MockConsensus::new(&args).await?;
+    info!(target: "consensus::mock", nodes = args.engines.len(), "Driving nodes");
+    let mut interval = tokio::time::interval(Duration::from_millis(args.block_time.max(1)));
+    let mut built = 0;
+    while args.blocks.is_none_or(|blocks| built < blocks) {
+        interval.tick().await;
+        match consensus.propose().await {
+            Ok(hash) => {
+                built += 1;
+                info!(target: "consensus::mock", number = consensus.number, %hash, "Block built");
+            }
+            Err(err) => warn!(target: "consensus::mock", %err, "Failed to build block"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chainspec::BERACHAIN_DEV;
+    use jsonrpsee_core::traits::ToRpcParams;
+    use serde_json::json;
+
+    #[test]
+    fn test_proposers_rotate() {
+        let derived = proposers(&[], 3);
+        assert_eq!(derived.len(), 3);
+        assert_eq!(derived.iter().collect::<std::collections::HashSet<_>>().len(), 3);
+        let configured = [BlsPublicKey::repeat_byte(1)];
+        assert_eq!(proposers(&configured, 3), configured.to_vec());
+    }
+
+    #[test]
+    fn test_prague1_methods() {
+        let methods = Methods::at(&BERACHAIN_DEV, 1);
+        assert_eq!(methods.fork_choice_updated, "engine_forkchoiceUpdatedV3P11");
+        assert_eq!(methods.new_payload, "engine_newPayloadV4P11");
+
+        let envelope = json!({
+            "executionPayload": { "blockHash": B256::ZERO },
+            "blobsBundle": { "commitments": [Bytes::from(vec![0u8; 48])] },
+            "executionRequests": []
+        });
+        let pubkey = BlsPublicKey::repeat_byte(2);
+        let params = new_payload_params(methods, &envelope, B256::ZERO, pubkey).unwrap();
+        let params: Value =
+            serde_json::from_str(params.to_rpc_params().unwrap().unwrap().get()).unwrap();
+        assert_eq!(params.as_array().unwrap().len(), 5);
+        assert_eq!(params[1][0], json!(kzg_to_versioned_hash(&[0u8; 48])));
+        assert_eq!(params[4], json!(pubkey));
+    }
+}
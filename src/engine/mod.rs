@@ -12,7 +12,10 @@
 //! - [`validator::BerachainEngineValidator`]: Engine validation logic
 
 pub mod builder;
+pub mod latency;
+pub mod mock_consensus;
 pub mod payload;
+pub mod report;
 pub mod rpc;
 pub mod validator;
 
@@ -97,6 +100,9 @@ pub struct BerachainExecutionPayloadEnvelopeV4 {
     pub parent_proposer_pub_key: Option<BlsPublicKey>,
 }
 
+/// Berachain payloads with blob sidecars fail to convert into V1 and V2 envelopes with a
+/// [`BuiltPayloadConversionError`](reth_ethereum_engine_primitives::BuiltPayloadConversionError),
+/// which `engine_getPayloadV1` and `engine_getPayloadV2` return to the caller as an error.
 impl EngineTypes for BerachainEngineTypes {
     type ExecutionPayloadEnvelopeV1 = ExecutionPayloadV1;
     type ExecutionPayloadEnvelopeV2 = ExecutionPayloadEnvelopeV2;
@@ -284,7 +290,7 @@ mod tests {
 
         genesis.config.extra_fields =
             reth::rpc::types::serde_helpers::OtherFields::try_from(extra_fields_json).unwrap();
-        Arc::new(BerachainChainSpec::from(genesis))
+        Arc::new(BerachainChainSpec::try_from(genesis).unwrap())
     }
 
     #[test]
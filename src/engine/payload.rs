@@ -1,5 +1,6 @@
 use crate::{
     chainspec::BerachainChainSpec,
+    hardforks::BerachainHardforks,
     primitives::{BerachainBlock, BerachainPrimitives, header::BlsPublicKey},
 };
 use alloy_eips::{
@@ -8,9 +9,9 @@ use alloy_eips::{
 };
 use alloy_primitives::{Address, B256, U256};
 use alloy_rpc_types::engine::{
-    BlobsBundleV1, ExecutionPayloadEnvelopeV2, ExecutionPayloadEnvelopeV3,
-    ExecutionPayloadEnvelopeV4, ExecutionPayloadEnvelopeV5, ExecutionPayloadV1, ExecutionPayloadV3,
-    PayloadId,
+    BlobsBundleV1, BlobsBundleV2, ExecutionPayloadEnvelopeV2, ExecutionPayloadEnvelopeV3,
+    ExecutionPayloadEnvelopeV4, ExecutionPayloadEnvelopeV5, ExecutionPayloadFieldV2,
+    ExecutionPayloadV1, ExecutionPayloadV3, PayloadId,
 };
 use reth::{
     api::PayloadAttributes,
@@ -142,6 +143,10 @@ impl BerachainPayloadBuilderAttributes {
     }
 }
 
+/// Previous proposer reported in locally built (`--dev`) payloads, which have no consensus client
+/// to supply one. It only feeds the PoL transaction's `distributeFor` call.
+pub const DEV_PROPOSER_PUBKEY: BlsPublicKey = BlsPublicKey::repeat_byte(0xbe);
+
 /// Implementation for LocalPayloadAttributesBuilder to build BerachainPayloadAttributes
 impl PayloadAttributesBuilder<BerachainPayloadAttributes>
     for LocalPayloadAttributesBuilder<BerachainChainSpec>
@@ -161,7 +166,10 @@ impl PayloadAttributesBuilder<BerachainPayloadAttributes>
                     .is_cancun_active_at_timestamp(timestamp)
                     .then(B256::random),
             },
-            prev_proposer_pubkey: None,
+            prev_proposer_pubkey: self
+                .chain_spec
+                .is_prague1_active_at_timestamp(timestamp)
+                .then_some(DEV_PROPOSER_PUBKEY),
         }
     }
 }
@@ -233,23 +241,76 @@ impl BerachainBuiltPayload {
         })
     }
 
+    /// Returns an error if the payload carries blob sidecars, which envelopes before V3 cannot
+    /// hold.
+    fn ensure_no_sidecars(&self) -> Result<(), BuiltPayloadConversionError> {
+        match self.sidecars {
+            BlobSidecars::Empty => Ok(()),
+            BlobSidecars::Eip4844(_) => Err(BuiltPayloadConversionError::UnexpectedEip4844Sidecars),
+            BlobSidecars::Eip7594(_) => Err(BuiltPayloadConversionError::UnexpectedEip7594Sidecars),
+        }
+    }
+
     pub fn try_into_v4(self) -> Result<ExecutionPayloadEnvelopeV4, BuiltPayloadConversionError> {
         Ok(ExecutionPayloadEnvelopeV4 {
             execution_requests: self.requests.clone().unwrap_or_default(),
             envelope_inner: self.try_into()?,
         })
     }
+
+    /// Try converting built payload into [`ExecutionPayloadEnvelopeV5`].
+    ///
+    /// Returns an error if the payload contains non EIP-7594 sidecar.
+    pub fn try_into_v5(self) -> Result<ExecutionPayloadEnvelopeV5, BuiltPayloadConversionError> {
+        let Self { block, fees, sidecars, requests, .. } = self;
+
+        let blobs_bundle = match sidecars {
+            BlobSidecars::Empty => BlobsBundleV2::empty(),
+            BlobSidecars::Eip7594(sidecars) => BlobsBundleV2::from(sidecars),
+            BlobSidecars::Eip4844(_) => {
+                return Err(BuiltPayloadConversionError::UnexpectedEip4844Sidecars)
+            }
+        };
+
+        Ok(ExecutionPayloadEnvelopeV5 {
+            execution_payload: ExecutionPayloadV3::from_block_unchecked(
+                block.hash(),
+                &Arc::unwrap_or_clone(block).into_block(),
+            ),
+            block_value: fees,
+            // See `try_into_v3`
+            should_override_builder: false,
+            blobs_bundle,
+            execution_requests: requests.unwrap_or_default(),
+        })
+    }
 }
 
-impl From<BerachainBuiltPayload> for ExecutionPayloadV1 {
-    fn from(_value: BerachainBuiltPayload) -> Self {
-        panic!("ExecutionPayloadV1 conversion not supported for Berachain - use V3+ for Prague1")
+impl TryFrom<BerachainBuiltPayload> for ExecutionPayloadV1 {
+    type Error = BuiltPayloadConversionError;
+
+    fn try_from(value: BerachainBuiltPayload) -> Result<Self, Self::Error> {
+        value.ensure_no_sidecars()?;
+        Ok(Self::from_block_unchecked(
+            value.block.hash(),
+            &Arc::unwrap_or_clone(value.block).into_block(),
+        ))
     }
 }
 
-impl From<BerachainBuiltPayload> for ExecutionPayloadEnvelopeV2 {
-    fn from(_value: BerachainBuiltPayload) -> Self {
-        panic!("ExecutionPayloadV2 conversion not supported for Berachain - use V3+ for Prague1")
+impl TryFrom<BerachainBuiltPayload> for ExecutionPayloadEnvelopeV2 {
+    type Error = BuiltPayloadConversionError;
+
+    fn try_from(value: BerachainBuiltPayload) -> Result<Self, Self::Error> {
+        value.ensure_no_sidecars()?;
+        let BerachainBuiltPayload { block, fees, .. } = value;
+        Ok(Self {
+            block_value: fees,
+            execution_payload: ExecutionPayloadFieldV2::from_block_unchecked(
+                block.hash(),
+                &Arc::unwrap_or_clone(block).into_block(),
+            ),
+        })
     }
 }
 
@@ -269,9 +330,11 @@ impl TryFrom<BerachainBuiltPayload> for ExecutionPayloadEnvelopeV4 {
     }
 }
 
-impl From<BerachainBuiltPayload> for ExecutionPayloadEnvelopeV5 {
-    fn from(_value: BerachainBuiltPayload) -> Self {
-        panic!("ExecutionPayloadV5 conversion not yet supported for Berachain")
+impl TryFrom<BerachainBuiltPayload> for ExecutionPayloadEnvelopeV5 {
+    type Error = BuiltPayloadConversionError;
+
+    fn try_from(value: BerachainBuiltPayload) -> Result<Self, Self::Error> {
+        value.try_into_v5()
     }
 }
 
@@ -290,3 +353,47 @@ impl BuiltPayload for BerachainBuiltPayload {
         self.requests.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(sidecars: BlobSidecars) -> BerachainBuiltPayload {
+        let block = SealedBlock::seal_slow(BerachainBlock::default());
+        BerachainBuiltPayload::new(PayloadId::new([1; 8]), Arc::new(block), U256::from(7), None)
+            .with_sidecars(sidecars)
+    }
+
+    #[test]
+    fn test_envelopes_before_v3_reject_sidecars() {
+        let envelope = ExecutionPayloadEnvelopeV2::try_from(payload(BlobSidecars::Empty)).unwrap();
+        assert_eq!(envelope.block_value, U256::from(7));
+        assert!(ExecutionPayloadV1::try_from(payload(BlobSidecars::Empty)).is_ok());
+
+        assert!(matches!(
+            ExecutionPayloadV1::try_from(payload(BlobSidecars::Eip4844(Vec::new()))),
+            Err(BuiltPayloadConversionError::UnexpectedEip4844Sidecars)
+        ));
+        assert!(matches!(
+            ExecutionPayloadEnvelopeV2::try_from(payload(BlobSidecars::Eip7594(Vec::new()))),
+            Err(BuiltPayloadConversionError::UnexpectedEip7594Sidecars)
+        ));
+    }
+
+    #[test]
+    fn test_envelope_v5_requires_eip7594_sidecars() {
+        let envelope = ExecutionPayloadEnvelopeV5::try_from(payload(BlobSidecars::Empty)).unwrap();
+        assert_eq!(envelope.block_value, U256::from(7));
+        assert!(envelope.blobs_bundle.blobs.is_empty());
+        assert!(envelope.execution_requests.is_empty());
+
+        assert!(matches!(
+            ExecutionPayloadEnvelopeV5::try_from(payload(BlobSidecars::Eip4844(Vec::new()))),
+            Err(BuiltPayloadConversionError::UnexpectedEip4844Sidecars)
+        ));
+        assert!(matches!(
+            ExecutionPayloadEnvelopeV3::try_from(payload(BlobSidecars::Eip7594(Vec::new()))),
+            Err(BuiltPayloadConversionError::UnexpectedEip7594Sidecars)
+        ));
+    }
+}
@@ -0,0 +1,227 @@
+//! Inclusion reports of locally built payloads.
+//!
+//! Validators want to know whether their builder leaves tips on the table. For every payload that
+//! improves on the previous one, the builder records how many transactions it included and which
+//! eligible pool transactions it passed over, grouped by the reason. Reports of recent payloads
+//! are kept by block hash in the node's [`BuilderReports`] and served by `bera_builderReport`; the
+//! `berachain_builder_*` metrics describe the latest built payload.
+//!
+//! Excluded transactions were not executed, so their gas is their gas limit and their fees are an
+//! upper bound: the tip they offered times their gas limit. Descendants of an excluded
+//! transaction are dropped with it and are not counted.
+
+use alloy_primitives::{BlockHash, BlockNumber, U256};
+use alloy_rpc_types::engine::PayloadId;
+use reth_metrics::{Metrics, metrics::Gauge};
+use schnellru::{ByLength, LruMap};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Mutex};
+
+/// Number of recently built payloads whose reports are kept.
+const MAX_REPORTS: u32 = 256;
+
+/// Why the builder passed over an eligible pool transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExclusionReason {
+    /// Its gas limit exceeded the gas left in the block.
+    GasLimit,
+    /// Its blobs exceeded the blobs left in the block.
+    BlobLimit,
+    /// Its blob sidecar was missing or of the wrong version.
+    BlobSidecar,
+    /// Its nonce was already used.
+    NonceTooLow,
+    /// It failed to execute.
+    Invalid,
+}
+
+impl ExclusionReason {
+    /// All reasons.
+    pub const ALL: [Self; 5] =
+        [Self::GasLimit, Self::BlobLimit, Self::BlobSidecar, Self::NonceTooLow, Self::Invalid];
+
+    /// Returns the metrics label of the reason.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::GasLimit => "gas_limit",
+            Self::BlobLimit => "blob_limit",
+            Self::BlobSidecar => "blob_sidecar",
+            Self::NonceTooLow => "nonce_too_low",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+/// Count, gas and fees of a set of transactions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTotals {
+    /// Number of transactions.
+    pub count: u64,
+    /// Gas of the transactions.
+    pub gas: u64,
+    /// Priority fees of the transactions, in wei.
+    pub fees: U256,
+}
+
+impl TransactionTotals {
+    /// Adds a transaction using `gas` at a priority fee of `tip` per gas.
+    pub fn add(&mut self, gas: u64, tip: u128) {
+        self.count += 1;
+        self.gas += gas;
+        self.fees += U256::from(tip) * U256::from(gas);
+    }
+}
+
+/// Transactions included and passed over while building a payload.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Inclusion {
+    /// Included transactions, with the gas they used.
+    pub included: TransactionTotals,
+    /// Excluded eligible transactions by reason, with their gas limits.
+    pub excluded: BTreeMap<ExclusionReason, TransactionTotals>,
+}
+
+impl Inclusion {
+    /// Records an included transaction.
+    pub fn include(&mut self, gas_used: u64, tip: u128) {
+        self.included.add(gas_used, tip);
+    }
+
+    /// Records a transaction passed over for `reason`.
+    pub fn exclude(&mut self, reason: ExclusionReason, gas_limit: u64, tip: u128) {
+        self.excluded.entry(reason).or_default().add(gas_limit, tip);
+    }
+}
+
+/// Report of a built payload, as returned by `bera_builderReport`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuilderReport {
+    /// Id of the payload job.
+    pub payload_id: PayloadId,
+    /// Hash of the built block.
+    pub block_hash: BlockHash,
+    /// Number of the built block.
+    pub block_number: BlockNumber,
+    /// Transactions pending in the pool when the payload was built.
+    pub pending: u64,
+    /// Transactions included and passed over.
+    #[serde(flatten)]
+    pub inclusion: Inclusion,
+}
+
+/// Metrics of the latest built payload.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.builder")]
+struct BuilderMetrics {
+    /// Transactions pending in the pool.
+    pending_transactions: Gauge,
+    /// Included transactions.
+    included_transactions: Gauge,
+    /// Gas used by the included transactions.
+    included_gas: Gauge,
+    /// Priority fees of the included transactions, in wei.
+    included_fees: Gauge,
+}
+
+/// Metrics of the transactions of the latest built payload passed over for a reason, labeled with
+/// the reason.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.builder.excluded")]
+struct ExcludedMetrics {
+    /// Excluded transactions.
+    transactions: Gauge,
+    /// Gas limit of the excluded transactions.
+    gas: Gauge,
+    /// Priority fees offered by the excluded transactions, in wei.
+    fees: Gauge,
+}
+
+/// Reports of recently built payloads, by block hash.
+pub struct BuilderReports {
+    metrics: BuilderMetrics,
+    excluded: [ExcludedMetrics; ExclusionReason::ALL.len()],
+    reports: Mutex<LruMap<BlockHash, BuilderReport, ByLength>>,
+}
+
+impl Default for BuilderReports {
+    fn default() -> Self {
+        Self {
+            metrics: BuilderMetrics::default(),
+            excluded: ExclusionReason::ALL
+                .map(|reason| ExcludedMetrics::new_with_labels(&[("reason", reason.as_str())])),
+            reports: Mutex::new(LruMap::new(ByLength::new(MAX_REPORTS))),
+        }
+    }
+}
+
+impl std::fmt::Debug for BuilderReports {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BuilderReports").finish_non_exhaustive()
+    }
+}
+
+impl BuilderReports {
+    /// Records the report of a built payload.
+    pub fn insert(&self, report: BuilderReport) {
+        let included = &report.inclusion.included;
+        self.metrics.pending_transactions.set(report.pending as f64);
+        self.metrics.included_transactions.set(included.count as f64);
+        self.metrics.included_gas.set(included.gas as f64);
+        self.metrics.included_fees.set(included.fees.saturating_to::<u128>() as f64);
+        for (reason, metrics) in ExclusionReason::ALL.iter().zip(&self.excluded) {
+            let excluded = report.inclusion.excluded.get(reason).copied().unwrap_or_default();
+            metrics.transactions.set(excluded.count as f64);
+            metrics.gas.set(excluded.gas as f64);
+            metrics.fees.set(excluded.fees.saturating_to::<u128>() as f64);
+        }
+        self.reports.lock().expect("not poisoned").insert(report.block_hash, report);
+    }
+
+    /// Returns the report of the payload that built `block_hash`, if it is still kept.
+    pub fn get(&self, block_hash: &BlockHash) -> Option<BuilderReport> {
+        self.reports.lock().expect("not poisoned").peek(block_hash).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inclusion_totals() {
+        let mut inclusion = Inclusion::default();
+        inclusion.include(21_000, 2);
+        inclusion.include(50_000, 1);
+        inclusion.exclude(ExclusionReason::GasLimit, 1_000_000, 3);
+        inclusion.exclude(ExclusionReason::GasLimit, 500_000, 1);
+
+        assert_eq!(
+            inclusion.included,
+            TransactionTotals { count: 2, gas: 71_000, fees: U256::from(92_000) }
+        );
+        assert_eq!(
+            inclusion.excluded[&ExclusionReason::GasLimit],
+            TransactionTotals { count: 2, gas: 1_500_000, fees: U256::from(3_500_000) }
+        );
+        assert!(!inclusion.excluded.contains_key(&ExclusionReason::Invalid));
+    }
+
+    #[test]
+    fn test_reports_by_block_hash() {
+        let reports = BuilderReports::default();
+        let report = BuilderReport {
+            payload_id: PayloadId::new([1; 8]),
+            block_hash: BlockHash::repeat_byte(2),
+            block_number: 3,
+            pending: 4,
+            inclusion: Inclusion::default(),
+        };
+        reports.insert(report.clone());
+        assert_eq!(reports.get(&report.block_hash), Some(report));
+        assert_eq!(reports.get(&BlockHash::ZERO), None);
+    }
+}
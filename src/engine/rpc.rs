@@ -37,7 +37,7 @@ use reth_rpc_engine_api::{
 };
 use reth_transaction_pool::TransactionPool;
 use std::sync::Arc;
-use tracing::{debug, trace};
+use tracing::{Span, debug, field::Empty, instrument, trace};
 
 /// Builder for basic [`EngineApi`] implementation.
 ///
@@ -49,6 +49,13 @@ pub struct BerachainEngineApiBuilder<EV> {
     engine_validator_builder: EV,
 }
 
+impl<EV> BerachainEngineApiBuilder<EV> {
+    /// Creates a builder validating payloads with the validator of `engine_validator_builder`.
+    pub fn new(engine_validator_builder: EV) -> Self {
+        Self { engine_validator_builder }
+    }
+}
+
 impl<N, EV> EngineApiBuilder<N> for BerachainEngineApiBuilder<EV>
 where
     N: FullNodeComponents<
@@ -363,11 +370,7 @@ where
 
         // Validate that no proposer pubkey is provided (should be None)
         validate_proposer_pubkey_prague1(chain_spec, timestamp, None).map_err(|error| {
-            EngineApiError::other(jsonrpsee_types::ErrorObject::owned(
-                INVALID_PAYLOAD_ATTRIBUTES,
-                error.to_string(),
-                None::<()>,
-            ))
+            EngineApiError::other(error.into_rpc_error(INVALID_PAYLOAD_ATTRIBUTES))
         })?;
 
         Ok(())
@@ -391,11 +394,7 @@ where
         // Validate that proposer pubkey is required for P11
         validate_proposer_pubkey_prague1(chain_spec, timestamp, Some(parent_proposer_pub_key))
             .map_err(|error| {
-                EngineApiError::other(jsonrpsee_types::ErrorObject::owned(
-                    INVALID_PAYLOAD_ATTRIBUTES,
-                    error.to_string(),
-                    None::<()>,
-                ))
+                EngineApiError::other(error.into_rpc_error(INVALID_PAYLOAD_ATTRIBUTES))
             })?;
 
         Ok(())
@@ -412,18 +411,39 @@ where
     Validator: EngineValidator<EngineT>,
     ChainSpec: EthereumHardforks + BerachainHardforks + Send + Sync + 'static,
 {
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_newPayloadV1",
+        level = "debug",
+        skip_all,
+        fields(block_hash = %payload.block_hash)
+    )]
     async fn new_payload_v1(&self, payload: ExecutionPayloadV1) -> RpcResult<PayloadStatus> {
         trace!(target: "rpc::engine", "Serving engine_newPayloadV1");
         let berachain_payload = BerachainExecutionData::from(payload);
         Ok(self.inner.new_payload_v1_metered(berachain_payload).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_newPayloadV2",
+        level = "debug",
+        skip_all,
+        fields(block_hash = %payload.execution_payload.block_hash)
+    )]
     async fn new_payload_v2(&self, payload: ExecutionPayloadInputV2) -> RpcResult<PayloadStatus> {
         trace!(target: "rpc::engine", "Serving engine_newPayloadV2");
         let berachain_payload = BerachainExecutionData::from(payload);
         Ok(self.inner.new_payload_v2_metered(berachain_payload).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_newPayloadV3",
+        level = "debug",
+        skip_all,
+        fields(block_hash = %payload.payload_inner.payload_inner.block_hash)
+    )]
     async fn new_payload_v3(
         &self,
         payload: ExecutionPayloadV3,
@@ -441,6 +461,13 @@ where
         Ok(self.inner.new_payload_v3_metered(berachain_payload).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_newPayloadV4",
+        level = "debug",
+        skip_all,
+        fields(block_hash = %payload.payload_inner.payload_inner.block_hash)
+    )]
     async fn new_payload_v4(
         &self,
         payload: ExecutionPayloadV3,
@@ -469,6 +496,13 @@ where
         Ok(self.inner.new_payload_v4_metered(berachain_payload).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_newPayloadV4P11",
+        level = "debug",
+        skip_all,
+        fields(block_hash = %payload.payload_inner.payload_inner.block_hash)
+    )]
     async fn new_payload_v4_p11(
         &self,
         payload: ExecutionPayloadV3,
@@ -503,42 +537,97 @@ where
         Ok(self.inner.new_payload_v4_metered(berachain_payload).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_forkchoiceUpdatedV1",
+        level = "debug",
+        skip_all,
+        fields(head_block_hash = %fork_choice_state.head_block_hash, payload_id = Empty)
+    )]
     async fn fork_choice_updated_v1(
         &self,
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<EngineT::PayloadAttributes>,
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV1");
-        Ok(self.inner.fork_choice_updated_v1_metered(fork_choice_state, payload_attributes).await?)
+        let updated = self
+            .inner
+            .fork_choice_updated_v1_metered(fork_choice_state, payload_attributes)
+            .await?;
+        record_payload_id(&updated);
+        Ok(updated)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_forkchoiceUpdatedV2",
+        level = "debug",
+        skip_all,
+        fields(head_block_hash = %fork_choice_state.head_block_hash, payload_id = Empty)
+    )]
     async fn fork_choice_updated_v2(
         &self,
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<EngineT::PayloadAttributes>,
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV2");
-        Ok(self.inner.fork_choice_updated_v2_metered(fork_choice_state, payload_attributes).await?)
+        let updated = self
+            .inner
+            .fork_choice_updated_v2_metered(fork_choice_state, payload_attributes)
+            .await?;
+        record_payload_id(&updated);
+        Ok(updated)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_forkchoiceUpdatedV3",
+        level = "debug",
+        skip_all,
+        fields(head_block_hash = %fork_choice_state.head_block_hash, payload_id = Empty)
+    )]
     async fn fork_choice_updated_v3(
         &self,
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<EngineT::PayloadAttributes>,
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV3");
-        Ok(self.inner.fork_choice_updated_v3_metered(fork_choice_state, payload_attributes).await?)
+        let updated = self
+            .inner
+            .fork_choice_updated_v3_metered(fork_choice_state, payload_attributes)
+            .await?;
+        record_payload_id(&updated);
+        Ok(updated)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_forkchoiceUpdatedV3P11",
+        level = "debug",
+        skip_all,
+        fields(head_block_hash = %fork_choice_state.head_block_hash, payload_id = Empty)
+    )]
     async fn fork_choice_updated_v3_p11(
         &self,
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<EngineT::PayloadAttributes>,
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV3P11");
-        Ok(self.inner.fork_choice_updated_v3_metered(fork_choice_state, payload_attributes).await?)
+        let updated = self
+            .inner
+            .fork_choice_updated_v3_metered(fork_choice_state, payload_attributes)
+            .await?;
+        record_payload_id(&updated);
+        Ok(updated)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_getPayloadV1",
+        level = "debug",
+        skip_all,
+        fields(payload_id = %payload_id)
+    )]
     async fn get_payload_v1(
         &self,
         payload_id: PayloadId,
@@ -547,6 +636,13 @@ where
         Ok(self.inner.get_payload_v1_metered(payload_id).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_getPayloadV2",
+        level = "debug",
+        skip_all,
+        fields(payload_id = %payload_id)
+    )]
     async fn get_payload_v2(
         &self,
         payload_id: PayloadId,
@@ -555,6 +651,13 @@ where
         Ok(self.inner.get_payload_v2_metered(payload_id).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_getPayloadV3",
+        level = "debug",
+        skip_all,
+        fields(payload_id = %payload_id)
+    )]
     async fn get_payload_v3(
         &self,
         payload_id: PayloadId,
@@ -563,6 +666,13 @@ where
         Ok(self.inner.get_payload_v3_metered(payload_id).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_getPayloadV4",
+        level = "debug",
+        skip_all,
+        fields(payload_id = %payload_id)
+    )]
     async fn get_payload_v4(
         &self,
         payload_id: PayloadId,
@@ -571,6 +681,13 @@ where
         Ok(self.inner.get_payload_v4_metered(payload_id).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_getPayloadV4P11",
+        level = "debug",
+        skip_all,
+        fields(payload_id = %payload_id)
+    )]
     async fn get_payload_v4_p11(
         &self,
         payload_id: PayloadId,
@@ -579,6 +696,13 @@ where
         Ok(self.inner.get_payload_v4_metered(payload_id).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_getPayloadV5",
+        level = "debug",
+        skip_all,
+        fields(payload_id = %payload_id)
+    )]
     async fn get_payload_v5(
         &self,
         payload_id: PayloadId,
@@ -587,6 +711,12 @@ where
         Ok(self.inner.get_payload_v5_metered(payload_id).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_getPayloadBodiesByHashV1",
+        level = "debug",
+        skip_all
+    )]
     async fn get_payload_bodies_by_hash_v1(
         &self,
         block_hashes: Vec<BlockHash>,
@@ -595,6 +725,12 @@ where
         Ok(self.inner.get_payload_bodies_by_hash_v1_metered(block_hashes).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_getPayloadBodiesByRangeV1",
+        level = "debug",
+        skip_all
+    )]
     async fn get_payload_bodies_by_range_v1(
         &self,
         start: U64,
@@ -604,6 +740,12 @@ where
         Ok(self.inner.get_payload_bodies_by_range_v1_metered(start.to(), count.to()).await?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_getClientVersionV1",
+        level = "debug",
+        skip_all
+    )]
     async fn get_client_version_v1(
         &self,
         client_version: ClientVersionV1,
@@ -612,10 +754,17 @@ where
         Ok(self.inner.get_client_version_v1(client_version)?)
     }
 
+    #[instrument(
+        target = "rpc::engine",
+        name = "engine_exchangeCapabilities",
+        level = "debug",
+        skip_all
+    )]
     async fn exchange_capabilities(&self, _capabilities: Vec<String>) -> RpcResult<Vec<String>> {
         Ok(self.inner.capabilities().list())
     }
 
+    #[instrument(target = "rpc::engine", name = "engine_getBlobsV1", level = "debug", skip_all)]
     async fn get_blobs_v1(
         &self,
         versioned_hashes: Vec<B256>,
@@ -624,6 +773,7 @@ where
         Ok(self.inner.get_blobs_v1_metered(versioned_hashes)?)
     }
 
+    #[instrument(target = "rpc::engine", name = "engine_getBlobsV2", level = "debug", skip_all)]
     async fn get_blobs_v2(
         &self,
         versioned_hashes: Vec<B256>,
@@ -643,3 +793,10 @@ where
         self.into_rpc().remove_context()
     }
 }
+
+/// Records the id of the payload being built, if any, on the current forkchoice span.
+fn record_payload_id(updated: &ForkchoiceUpdated) {
+    if let Some(payload_id) = updated.payload_id {
+        Span::current().record("payload_id", tracing::field::display(payload_id));
+    }
+}
@@ -2,12 +2,16 @@
 
 use crate::{
     chainspec::BerachainChainSpec,
+    consensus::{error::BerachainPayloadError, quarantine::BadBlocks},
     engine::{
         BerachainEngineTypes, BerachainExecutionData, BerachainExecutionPayloadSidecar,
+        latency::{PayloadLatency, PayloadPhase},
         payload::BerachainPayloadAttributes,
     },
     hardforks::BerachainHardforks,
+    node::evm::error::BerachainExecutionError,
     primitives::{BerachainBlock, BerachainHeader, BerachainPrimitives},
+    storage::header_cache::SealedHeaderCache,
     transaction::BerachainTxEnvelope,
 };
 use reth::chainspec::EthereumHardforks;
@@ -21,20 +25,37 @@ use reth_payload_primitives::{
     validate_execution_requests, validate_version_specific_fields,
 };
 use reth_payload_validator::{cancun, prague, shanghai};
-use reth_primitives_traits::{Block, RecoveredBlock, SealedBlock};
-use std::{marker::PhantomData, sync::Arc};
+use reth_primitives_traits::{RecoveredBlock, SealedBlock};
+use std::{sync::Arc, time::Instant};
 
 #[derive(Debug, Clone)]
 pub struct BerachainEngineValidator {
     inner: EthereumExecutionPayloadValidator<BerachainChainSpec>,
     /// The inner chainspec is private, so we need this.
     chain_spec: Arc<BerachainChainSpec>,
+    /// Quarantine shared with consensus.
+    bad_blocks: Arc<BadBlocks>,
+    /// Payload latency breakdown shared with the executor.
+    latency: Arc<PayloadLatency>,
+    /// Node-wide cache payload headers are sealed through.
+    headers: Arc<SealedHeaderCache>,
 }
 
 impl BerachainEngineValidator {
     /// Instantiates a new validator.
-    pub fn new(chain_spec: Arc<BerachainChainSpec>) -> Self {
-        Self { inner: EthereumExecutionPayloadValidator::new(chain_spec.clone()), chain_spec }
+    pub fn new(
+        chain_spec: Arc<BerachainChainSpec>,
+        bad_blocks: Arc<BadBlocks>,
+        latency: Arc<PayloadLatency>,
+        headers: Arc<SealedHeaderCache>,
+    ) -> Self {
+        Self {
+            inner: EthereumExecutionPayloadValidator::new(chain_spec.clone()),
+            chain_spec,
+            bad_blocks,
+            latency,
+            headers,
+        }
     }
 
     /// Returns the chain spec used by the validator.
@@ -70,10 +91,10 @@ impl BerachainEngineValidator {
                 ommers: berachain_ommers,
                 withdrawals: standard_block.body.withdrawals.clone(),
             };
-        let berachain_block =
-            alloy_consensus::Block { header: berachain_header, body: berachain_body };
 
-        Ok(berachain_block.seal_slow())
+        // Consensus clients re-send payloads, so the header may already have been sealed.
+        let sealed_header = self.headers.seal(berachain_header);
+        Ok(SealedBlock::from_sealed_parts(sealed_header, berachain_body))
     }
 
     /// Validate hardfork-specific fields
@@ -99,11 +120,14 @@ impl BerachainEngineValidator {
             self.chain_spec.is_prague_active_at_timestamp(sealed_block.timestamp),
         )?;
 
+        let started = Instant::now();
         prague1::ensure_well_formed_fields(
             sealed_block,
             sidecar.parent_proposer_pub_key,
             self.chain_spec.is_prague1_active_at_timestamp(sealed_block.timestamp),
-        )?;
+        )
+        .inspect_err(|err| self.bad_blocks.insert(sealed_block.num_hash(), err.clone()))?;
+        self.latency.record(PayloadPhase::PubkeyValidation, started.elapsed());
 
         Ok(())
     }
@@ -119,26 +143,29 @@ impl PayloadValidator for BerachainEngineValidator {
     ) -> Result<RecoveredBlock<Self::Block>, NewPayloadError> {
         let BerachainExecutionData { payload, sidecar } = payload;
         let expected_hash = payload.block_hash();
+        self.bad_blocks.check(&expected_hash)?;
 
         // Parse the block directly to BerachainBlock
+        let started = Instant::now();
         let sealed_block = self.parse_berachain_block(payload, &sidecar)?;
+        self.latency.record(PayloadPhase::Decode, started.elapsed());
 
         // Validate block hash
         if expected_hash != sealed_block.hash() {
-            return Err(NewPayloadError::Other(
-                format!(
-                    "Block hash mismatch: expected {}, got {}",
-                    expected_hash,
-                    sealed_block.hash()
-                )
-                .into(),
-            ));
+            return Err(BerachainExecutionError::BlockHashMismatch {
+                received_hash: sealed_block.hash(),
+                expected_hash,
+            }
+            .into())
         }
 
         // Apply standard + Berachain hardfork validations
         self.validate_hardfork_fields(&sealed_block, &sidecar)?;
 
-        sealed_block.try_recover().map_err(|e| NewPayloadError::Other(e.into()))
+        let started = Instant::now();
+        let block = sealed_block.try_recover().map_err(|e| NewPayloadError::Other(e.into()))?;
+        self.latency.record(PayloadPhase::SenderRecovery, started.elapsed());
+        Ok(block)
     }
 }
 
@@ -188,7 +215,21 @@ where
 /// Builder for BerachainEngineValidator that works with BerachainPayloadAttributes
 #[derive(Debug, Default, Clone)]
 pub struct BerachainEngineValidatorBuilder {
-    _phantom: PhantomData<BerachainChainSpec>,
+    bad_blocks: Arc<BadBlocks>,
+    latency: Arc<PayloadLatency>,
+    headers: Arc<SealedHeaderCache>,
+}
+
+impl BerachainEngineValidatorBuilder {
+    /// Creates a builder of validators sharing `bad_blocks` with consensus, `latency` with the
+    /// executor and `headers` with the RPC.
+    pub fn new(
+        bad_blocks: Arc<BadBlocks>,
+        latency: Arc<PayloadLatency>,
+        headers: Arc<SealedHeaderCache>,
+    ) -> Self {
+        Self { bad_blocks, latency, headers }
+    }
 }
 
 impl<Node, Types> EngineValidatorBuilder<Node> for BerachainEngineValidatorBuilder
@@ -203,7 +244,12 @@ where
     type Validator = BerachainEngineValidator;
 
     async fn build(self, ctx: &AddOnsContext<'_, Node>) -> eyre::Result<Self::Validator> {
-        Ok(BerachainEngineValidator::new(ctx.config.chain.clone()))
+        Ok(BerachainEngineValidator::new(
+            ctx.config.chain.clone(),
+            self.bad_blocks,
+            self.latency,
+            self.headers,
+        ))
     }
 }
 
@@ -220,7 +266,7 @@ pub mod prague1 {
         sealed_block: &SealedBlock<BerachainBlock>,
         parent_proposer_pub_key: Option<BlsPublicKey>,
         is_prague1_active: bool,
-    ) -> Result<(), NewPayloadError> {
+    ) -> Result<(), BerachainPayloadError> {
         if is_prague1_active {
             validate_prague1_active(sealed_block, parent_proposer_pub_key)
         } else {
@@ -231,16 +277,13 @@ pub mod prague1 {
     fn validate_prague1_active(
         sealed_block: &SealedBlock<BerachainBlock>,
         parent_proposer_pub_key: Option<BlsPublicKey>,
-    ) -> Result<(), NewPayloadError> {
-        let parent_pubkey = parent_proposer_pub_key.ok_or_else(|| {
-            NewPayloadError::Other("Prague1 active but parent proposer pubkey missing".into())
-        })?;
+    ) -> Result<(), BerachainPayloadError> {
+        let parent_pubkey =
+            parent_proposer_pub_key.ok_or(BerachainPayloadError::MissingProposerPubkey)?;
 
         let header_pubkey = sealed_block.header().prev_proposer_pubkey;
         if header_pubkey != Some(parent_pubkey) {
-            return Err(NewPayloadError::Other(
-                "Prague1 active but parent proposer pubkey mismatch".into(),
-            ));
+            return Err(BerachainPayloadError::ProposerPubkeyMismatch);
         }
 
         Ok(())
@@ -249,17 +292,10 @@ pub mod prague1 {
     fn validate_prague1_inactive(
         sealed_block: &SealedBlock<BerachainBlock>,
         parent_proposer_pub_key: Option<BlsPublicKey>,
-    ) -> Result<(), NewPayloadError> {
-        if parent_proposer_pub_key.is_some() {
-            return Err(NewPayloadError::Other(
-                "Prague1 not active but parent proposer pubkey present".into(),
-            ));
-        }
-
-        if sealed_block.header().prev_proposer_pubkey.is_some() {
-            return Err(NewPayloadError::Other(
-                "Prague1 not active but header contains proposer pubkey".into(),
-            ));
+    ) -> Result<(), BerachainPayloadError> {
+        if parent_proposer_pub_key.is_some() || sealed_block.header().prev_proposer_pubkey.is_some()
+        {
+            return Err(BerachainPayloadError::UnexpectedProposerPubkey);
         }
 
         Ok(())
@@ -0,0 +1,210 @@
+//! Batched genesis state initialization.
+//!
+//! Reth's `init_genesis` hands the whole alloc to each writer in one go and inserts hashed state
+//! entry by entry, in random key order. For allocs with hundreds of thousands of entries (e.g.
+//! bepolia) this takes many minutes. This writer hashes the alloc in parallel, sorts it by hashed
+//! key so the hashed tables can be appended to, and writes plain state in fixed-size batches with
+//! progress reporting. Everything is written in a single transaction, so a failed run leaves no
+//! partial genesis behind.
+
+use alloy_genesis::GenesisAccount;
+use alloy_primitives::{Address, B256, keccak256};
+use rayon::prelude::*;
+use reth::providers::{
+    BlockHashReader, ChainSpecProvider, DBProvider, DatabaseProviderFactory, HashingWriter,
+    HeaderProvider, HistoryWriter, StageCheckpointReader, StageCheckpointWriter, StateWriter,
+    StaticFileProviderFactory, TrieWriter, UnifiedStorageWriter,
+};
+use reth_chainspec::EthChainSpec;
+use reth_codecs::Compact;
+use reth_db_api::{
+    cursor::{DbCursorRW, DbDupCursorRW},
+    tables,
+    transaction::DbTxMut,
+};
+use reth_db_common::init::{
+    InitStorageError, compute_state_root, init_genesis, insert_genesis_header,
+    insert_genesis_history, insert_genesis_state,
+};
+use reth_primitives_traits::{Account, NodePrimitives, StorageEntry};
+use reth_stages_types::StageId;
+use reth_static_file_types::StaticFileSegment;
+use std::{collections::BTreeMap, time::Instant};
+use tracing::info;
+
+/// Default number of alloc entries written per batch.
+pub const DEFAULT_GENESIS_BATCH_SIZE: usize = 10_000;
+
+/// Hashed accounts and storages of a genesis alloc, sorted by hashed key.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HashedGenesisState {
+    /// `keccak256(address)` to account, ascending.
+    pub accounts: Vec<(B256, Account)>,
+    /// `keccak256(address)` to non-zero storage entries keyed by `keccak256(slot)`, both
+    /// ascending.
+    pub storages: Vec<(B256, Vec<StorageEntry>)>,
+}
+
+impl HashedGenesisState {
+    /// Hashes and sorts `alloc` using all available cores.
+    pub fn from_alloc(alloc: &BTreeMap<Address, GenesisAccount>) -> Self {
+        let mut accounts: Vec<_> = alloc
+            .par_iter()
+            .map(|(address, account)| (keccak256(address), Account::from(account)))
+            .collect();
+        accounts.par_sort_unstable_by_key(|(hashed_address, _)| *hashed_address);
+
+        let mut storages: Vec<_> = alloc
+            .par_iter()
+            .filter_map(|(address, account)| {
+                let mut entries: Vec<_> = account
+                    .storage
+                    .as_ref()?
+                    .iter()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(slot, value)| StorageEntry {
+                        key: keccak256(slot),
+                        value: (*value).into(),
+                    })
+                    .collect();
+                if entries.is_empty() {
+                    return None;
+                }
+                entries.sort_unstable_by_key(|entry| entry.key);
+                Some((keccak256(address), entries))
+            })
+            .collect();
+        storages.par_sort_unstable_by_key(|(hashed_address, _)| *hashed_address);
+
+        Self { accounts, storages }
+    }
+}
+
+/// Writes the genesis block and state if the database is empty, using batched alloc inserts.
+///
+/// Databases that already contain a genesis block are handed to reth's `init_genesis`, which
+/// verifies the stored hash and handles partially deleted datadirs.
+pub fn init_genesis_batched<PF>(factory: &PF, batch_size: usize) -> Result<B256, InitStorageError>
+where
+    PF: DatabaseProviderFactory
+        + StaticFileProviderFactory<Primitives: NodePrimitives<BlockHeader: Compact>>
+        + ChainSpecProvider
+        + StageCheckpointReader
+        + BlockHashReader,
+    PF::ProviderRW: DBProvider<Tx: DbTxMut>
+        + StaticFileProviderFactory<Primitives = PF::Primitives>
+        + StageCheckpointWriter
+        + HistoryWriter
+        + HeaderProvider
+        + HashingWriter
+        + StateWriter
+        + TrieWriter
+        + AsRef<PF::ProviderRW>,
+    PF::ChainSpec: EthChainSpec<Header = <PF::Primitives as NodePrimitives>::BlockHeader>,
+{
+    if !matches!(factory.block_hash(0), Ok(None)) {
+        return init_genesis(factory);
+    }
+
+    let chain = factory.chain_spec();
+    let alloc = &chain.genesis().alloc;
+    let batch_size = batch_size.max(1);
+    let total = alloc.len();
+    let started = Instant::now();
+
+    info!(target: "reth::cli", accounts = total, batch_size, "Writing genesis state");
+
+    let hashed = HashedGenesisState::from_alloc(alloc);
+    info!(target: "reth::cli", elapsed = ?started.elapsed(), "Hashed genesis alloc");
+
+    let provider_rw = factory.database_provider_rw()?;
+
+    let mut accounts_cursor = provider_rw.tx_ref().cursor_write::<tables::HashedAccounts>()?;
+    for (hashed_address, account) in &hashed.accounts {
+        accounts_cursor.append(*hashed_address, account)?;
+    }
+    let mut storages_cursor = provider_rw.tx_ref().cursor_dup_write::<tables::HashedStorages>()?;
+    for (hashed_address, entries) in &hashed.storages {
+        for entry in entries {
+            storages_cursor.append_dup(*hashed_address, *entry)?;
+        }
+    }
+    drop((accounts_cursor, storages_cursor));
+    info!(target: "reth::cli", elapsed = ?started.elapsed(), "Wrote hashed genesis state");
+
+    // The alloc is a `BTreeMap`, so batches are ascending by address and changeset entries keep
+    // being appended across batches.
+    let entries: Vec<_> = alloc.iter().collect();
+    let mut written = 0;
+    for batch in entries.chunks(batch_size) {
+        insert_genesis_history(&provider_rw, batch.iter().copied())?;
+        insert_genesis_state(&provider_rw, batch.iter().copied())?;
+
+        written += batch.len();
+        info!(target: "reth::cli", written, total, elapsed = ?started.elapsed(), "Wrote genesis accounts");
+    }
+
+    insert_genesis_header(&provider_rw, &chain)?;
+
+    info!(target: "reth::cli", "Computing genesis state root");
+    compute_state_root(&provider_rw)?;
+
+    for stage in StageId::ALL {
+        provider_rw.save_stage_checkpoint(stage, Default::default())?;
+    }
+
+    // Static file segments start empty, so the genesis block needs to be accounted for.
+    let static_file_provider = provider_rw.static_file_provider();
+    static_file_provider.latest_writer(StaticFileSegment::Receipts)?.increment_block(0)?;
+    static_file_provider.latest_writer(StaticFileSegment::Transactions)?.increment_block(0)?;
+
+    // Commits the database before the static files, as required for genesis.
+    UnifiedStorageWriter::commit_unwind(provider_rw)?;
+
+    info!(target: "reth::cli", hash = %chain.genesis_hash(), elapsed = ?started.elapsed(), "Genesis written");
+
+    Ok(chain.genesis_hash())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+
+    #[test]
+    fn test_hashed_genesis_state_is_sorted() {
+        let alloc: BTreeMap<_, _> = (0..1_000u64)
+            .map(|i| {
+                let mut account = GenesisAccount::default().with_balance(U256::from(i));
+                if i % 10 == 0 {
+                    account = account.with_storage(Some(
+                        (0..5u64)
+                            .map(|slot| {
+                                (B256::from(U256::from(slot)), B256::from(U256::from(slot)))
+                            })
+                            .collect(),
+                    ));
+                }
+                (Address::from_word(keccak256(i.to_be_bytes())), account)
+            })
+            .collect();
+
+        let hashed = HashedGenesisState::from_alloc(&alloc);
+
+        assert_eq!(hashed.accounts.len(), alloc.len());
+        assert!(hashed.accounts.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(hashed.storages.windows(2).all(|w| w[0].0 < w[1].0));
+
+        assert_eq!(hashed.storages.len(), 100);
+        for (_, entries) in &hashed.storages {
+            // Slot 0 holds a zero value and is skipped
+            assert_eq!(entries.len(), 4);
+            assert!(entries.windows(2).all(|w| w[0].key < w[1].key));
+        }
+
+        let (address, account) = alloc.iter().next().unwrap();
+        let hashed_address = keccak256(address);
+        let stored = hashed.accounts.iter().find(|(key, _)| *key == hashed_address).unwrap();
+        assert_eq!(stored.1, Account::from(account));
+    }
+}
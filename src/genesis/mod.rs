@@ -1,5 +1,8 @@
 //! Berachain genesis configuration parsing and validation
 
+pub mod init;
+
+use crate::transaction::pol::POL_TX_GAS_LIMIT;
 use jsonrpsee_core::__reexports::serde_json;
 use reth::{
     revm::primitives::{Address, address},
@@ -30,6 +33,10 @@ pub enum BerachainConfigError {
     /// PoL distributor address is missing from Prague1 configuration
     #[error("PoL distributor address is required in Prague1 configuration but was not provided")]
     MissingPoLDistributorAddress,
+
+    /// PoL transaction gas limit cannot be zero as the distribution could never run
+    #[error("PoL transaction gas limit cannot be zero")]
+    InvalidPoLTxGasLimit,
 }
 
 /// Configuration for a Berachain hardfork activation
@@ -44,6 +51,10 @@ pub struct BerachainForkConfig {
     pub minimum_base_fee_wei: u64,
     /// PoL distributor contract address
     pub pol_distributor_address: Address,
+    /// Gas limit the PoL transaction is executed with, 30M by default. The distribution fails if
+    /// it needs more, and blocks whose PoL transaction carries another limit are invalid.
+    #[serde(default = "default_pol_tx_gas_limit")]
+    pub pol_tx_gas_limit: u64,
 }
 
 /// Complete Berachain genesis configuration from JSON "berachain" field
@@ -52,6 +63,11 @@ pub struct BerachainForkConfig {
 pub struct BerachainGenesisConfig {
     /// Configuration for the Prague1 hardfork, which introduces minimum base fee enforcement
     pub prague1: BerachainForkConfig,
+    /// Configuration for the Prague2 hardfork, if scheduled. Its base fee change denominator,
+    /// minimum base fee, PoL distributor and PoL transaction gas limit replace Prague1's from its
+    /// activation on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prague2: Option<BerachainForkConfig>,
 }
 
 /// Default PoL contract address
@@ -59,6 +75,11 @@ fn default_pol_contract_address() -> Address {
     address!("4200000000000000000000000000000000000042")
 }
 
+/// Default PoL transaction gas limit
+const fn default_pol_tx_gas_limit() -> u64 {
+    POL_TX_GAS_LIMIT
+}
+
 impl Default for BerachainGenesisConfig {
     /// Default config with Prague1 activated immediately at genesis
     fn default() -> Self {
@@ -68,13 +89,16 @@ impl Default for BerachainGenesisConfig {
                 base_fee_change_denominator: 48,     // Berachain standard value
                 minimum_base_fee_wei: 1_000_000_000, // 1 gwei
                 pol_distributor_address: default_pol_contract_address(),
+                pol_tx_gas_limit: default_pol_tx_gas_limit(),
             },
+            prague2: None,
         }
     }
 }
 
 impl BerachainForkConfig {
-    /// Creates validated config. Returns error if denominator is 0.
+    /// Creates validated config with the default PoL transaction gas limit. Returns error if
+    /// denominator is 0.
     pub fn new(
         time: u64,
         base_fee_change_denominator: u128,
@@ -89,8 +113,35 @@ impl BerachainForkConfig {
             base_fee_change_denominator,
             minimum_base_fee_wei,
             pol_distributor_address,
+            pol_tx_gas_limit: default_pol_tx_gas_limit(),
         })
     }
+
+    /// Sets the gas limit of the PoL transaction. Returns error if it is 0.
+    pub const fn with_pol_tx_gas_limit(
+        mut self,
+        pol_tx_gas_limit: u64,
+    ) -> Result<Self, BerachainConfigError> {
+        if pol_tx_gas_limit == 0 {
+            return Err(BerachainConfigError::InvalidPoLTxGasLimit);
+        }
+        self.pol_tx_gas_limit = pol_tx_gas_limit;
+        Ok(self)
+    }
+
+    /// Checks the values that deserialization alone cannot reject.
+    pub fn validate(&self) -> Result<(), BerachainConfigError> {
+        if self.base_fee_change_denominator == 0 {
+            return Err(BerachainConfigError::InvalidDenominator)
+        }
+        if self.pol_distributor_address.is_zero() {
+            return Err(BerachainConfigError::MissingPoLDistributorAddress)
+        }
+        if self.pol_tx_gas_limit == 0 {
+            return Err(BerachainConfigError::InvalidPoLTxGasLimit)
+        }
+        Ok(())
+    }
 }
 
 impl TryFrom<&OtherFields> for BerachainGenesisConfig {
@@ -103,11 +154,9 @@ impl TryFrom<&OtherFields> for BerachainGenesisConfig {
         match others.get_deserialized::<Self>("berachain") {
             Some(Ok(cfg)) => {
                 // Validate the parsed configuration
-                if cfg.prague1.base_fee_change_denominator == 0 {
-                    return Err(BerachainConfigError::InvalidDenominator);
-                }
-                if cfg.prague1.pol_distributor_address.is_zero() {
-                    return Err(BerachainConfigError::MissingPoLDistributorAddress);
+                cfg.prague1.validate()?;
+                if let Some(prague2) = &cfg.prague2 {
+                    prague2.validate()?;
                 }
 
                 info!(
@@ -196,6 +245,39 @@ mod tests {
         assert_eq!(cfg.prague1.time, 1620000000);
         assert_eq!(cfg.prague1.minimum_base_fee_wei, 1000000000);
         assert_eq!(cfg.prague1.base_fee_change_denominator, 48);
+        assert_eq!(cfg.prague1.pol_tx_gas_limit, POL_TX_GAS_LIMIT);
+    }
+
+    #[test]
+    fn test_genesis_config_pol_tx_gas_limit() {
+        let json = r#"
+        {
+          "berachain": {
+            "prague1": {
+                "time": 1620000000,
+                "baseFeeChangeDenominator": 48,
+                "minimumBaseFeeWei": 1000000000,
+                "polDistributorAddress": "0x4200000000000000000000000000000000000042",
+                "polTxGasLimit": 5000000
+            }
+          }
+        }
+        "#;
+
+        let v: Value = serde_json::from_str(json).unwrap();
+        let other_fields = OtherFields::try_from(v).expect("must be a valid genesis config");
+        let cfg = BerachainGenesisConfig::try_from(&other_fields)
+            .expect("berachain field must deserialize");
+        assert_eq!(cfg.prague1.pol_tx_gas_limit, 5_000_000);
+
+        let v: Value = serde_json::from_str(&json.replace("5000000", "0")).unwrap();
+        let other_fields = OtherFields::try_from(v).expect("must be a valid genesis config");
+        let res = BerachainGenesisConfig::try_from(&other_fields);
+        assert!(
+            res.expect_err("must be an error")
+                .to_string()
+                .contains("PoL transaction gas limit cannot be zero")
+        );
     }
 
     #[test]
@@ -248,4 +330,32 @@ mod tests {
                 .contains("PoL distributor address is required")
         );
     }
+
+    #[test]
+    fn test_genesis_config_prague2_is_validated() {
+        let json = r#"
+        {
+          "berachain": {
+            "prague1": {
+                "time": 0,
+                "baseFeeChangeDenominator": 48,
+                "minimumBaseFeeWei": 1000000000,
+                "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+            },
+            "prague2": {
+                "time": 1620000000,
+                "baseFeeChangeDenominator": 0,
+                "minimumBaseFeeWei": 1000000000,
+                "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+            }
+          }
+        }
+        "#;
+
+        let v: Value = serde_json::from_str(json).unwrap();
+        let other_fields = OtherFields::try_from(v).expect("must be a valid genesis config");
+
+        let res = BerachainGenesisConfig::try_from(&other_fields);
+        assert!(matches!(res, Err(BerachainConfigError::InvalidDenominator)));
+    }
 }
@@ -7,6 +7,8 @@ hardfork!(
     BerachainHardfork {
         /// Prague1 hardfork: Enforces 1 gwei minimum base fee
         Prague1,
+        /// Prague2 hardfork: Parameters scheduled by the `prague2` genesis section
+        Prague2,
     }
 );
 
@@ -19,6 +21,11 @@ pub trait BerachainHardforks: EthereumHardforks {
     fn is_prague1_active_at_timestamp(&self, timestamp: u64) -> bool {
         self.berachain_fork_activation(BerachainHardfork::Prague1).active_at_timestamp(timestamp)
     }
+
+    /// Checks if Prague2 hardfork is active at given timestamp
+    fn is_prague2_active_at_timestamp(&self, timestamp: u64) -> bool {
+        self.berachain_fork_activation(BerachainHardfork::Prague2).active_at_timestamp(timestamp)
+    }
 }
 
 #[cfg(test)]
@@ -38,6 +45,7 @@ mod tests {
         fn berachain_fork_activation(&self, fork: BerachainHardfork) -> ForkCondition {
             match fork {
                 BerachainHardfork::Prague1 => ForkCondition::Timestamp(0),
+                BerachainHardfork::Prague2 => ForkCondition::Timestamp(1000),
             }
         }
     }
@@ -59,5 +67,9 @@ mod tests {
         // Test Prague1 active at timestamp using trait method
         assert!(hardforks.is_prague1_active_at_timestamp(0));
         assert!(hardforks.is_prague1_active_at_timestamp(100));
+
+        // Test Prague2 activation at a later timestamp
+        assert!(!hardforks.is_prague2_active_at_timestamp(999));
+        assert!(hardforks.is_prague2_active_at_timestamp(1000));
     }
 }
@@ -3,12 +3,17 @@
 //! Built on Reth SDK with Ethereum compatibility plus Prague1 hardfork for minimum base fee.
 
 pub mod chainspec;
+pub mod cli;
 pub mod consensus;
 pub mod engine;
 pub mod genesis;
 pub mod hardforks;
+pub mod network;
 pub mod node;
 pub mod pool;
 pub mod primitives;
 pub mod rpc;
+pub mod storage;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 pub mod transaction;
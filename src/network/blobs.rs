@@ -0,0 +1,259 @@
+//! `bblob/1`: blob sidecar retrieval between bera-reth peers.
+//!
+//! eth/68 only exchanges sidecars of pooled transactions, so a sidecar is gone from the network
+//! once every pool dropped its transaction. bera-reth peers additionally speak this RLPx
+//! subprotocol: `GetBlobs` asks for the sidecars of up to [`MAX_BLOBS_PER_REQUEST`] transactions
+//! and `Blobs` answers with their network encodings, in request order, empty for sidecars the
+//! node does not have. Sidecars are looked up in the pool first and then among the
+//! [retained sidecars](crate::storage::blob_sidecars) of locally built blocks.
+
+use crate::storage::blob_sidecars::read_sidecar;
+use alloy_primitives::{Bytes, TxHash};
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use bytes::{BufMut, BytesMut};
+use futures::{Stream, StreamExt};
+use reth::providers::{DBProvider, DatabaseProviderFactory};
+use reth_eth_wire::{
+    Capability, capability::SharedCapabilities, multiplex::ProtocolConnection, protocol::Protocol,
+};
+use reth_metrics::{Metrics, metrics::Counter};
+use reth_network::protocol::{ConnectionHandler, OnNotSupported, ProtocolHandler};
+use reth_network_api::Direction;
+use reth_network_peers::PeerId;
+use reth_transaction_pool::TransactionPool;
+use std::{fmt, net::SocketAddr, pin::Pin, sync::Arc};
+use tracing::{debug, trace};
+
+/// Maximum number of sidecars answered per request.
+pub const MAX_BLOBS_PER_REQUEST: usize = 64;
+
+/// Blob sidecar availability metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.blob_sidecars")]
+struct BlobsMetrics {
+    /// Sidecars requested by peers.
+    requested: Counter,
+    /// Sidecars served to peers.
+    served: Counter,
+    /// Sidecars requested by peers that the node did not have.
+    missing: Counter,
+}
+
+/// Request for the sidecars of blob transactions.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct GetBlobs {
+    /// Id echoed in the response.
+    pub request_id: u64,
+    /// Hashes of the blob transactions.
+    pub hashes: Vec<TxHash>,
+}
+
+/// Sidecars of the requested transactions, empty for unknown ones.
+#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct Blobs {
+    /// Id of the request.
+    pub request_id: u64,
+    /// RLP encoded `BlobTransactionSidecarVariant`s.
+    pub sidecars: Vec<Bytes>,
+}
+
+/// Messages of `bblob/1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlobsMessage {
+    /// [`GetBlobs`], id `0x00`.
+    GetBlobs(GetBlobs),
+    /// [`Blobs`], id `0x01`.
+    Blobs(Blobs),
+}
+
+impl BlobsMessage {
+    /// Returns the capability of the protocol.
+    pub fn capability() -> Capability {
+        Capability::new_static("bblob", 1)
+    }
+
+    /// Returns the protocol with its number of messages.
+    pub fn protocol() -> Protocol {
+        Protocol::new(Self::capability(), 2)
+    }
+
+    /// Encodes the message with its id.
+    pub fn encoded(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        match self {
+            Self::GetBlobs(request) => {
+                buf.put_u8(0x00);
+                request.encode(&mut buf);
+            }
+            Self::Blobs(response) => {
+                buf.put_u8(0x01);
+                response.encode(&mut buf);
+            }
+        }
+        buf
+    }
+
+    /// Decodes a message with its id.
+    pub fn decode_message(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let (&id, rest) = buf.split_first().ok_or(alloy_rlp::Error::InputTooShort)?;
+        *buf = rest;
+        match id {
+            0x00 => Ok(Self::GetBlobs(GetBlobs::decode(buf)?)),
+            0x01 => Ok(Self::Blobs(Blobs::decode(buf)?)),
+            _ => Err(alloy_rlp::Error::Custom("unknown bblob message id")),
+        }
+    }
+}
+
+type SidecarLookup = Arc<dyn Fn(TxHash) -> Option<Bytes> + Send + Sync>;
+
+/// Serves `bblob/1` requests of peers.
+#[derive(Clone)]
+pub struct BlobsProtocolHandler {
+    lookup: SidecarLookup,
+    metrics: Arc<BlobsMetrics>,
+}
+
+impl fmt::Debug for BlobsProtocolHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlobsProtocolHandler").finish_non_exhaustive()
+    }
+}
+
+impl BlobsProtocolHandler {
+    /// Serves sidecars from `pool` and the retained sidecars in the database of `provider`.
+    pub fn new<Pool, Provider>(pool: Pool, provider: Provider) -> Self
+    where
+        Pool: TransactionPool + 'static,
+        Provider: DatabaseProviderFactory + Send + Sync + 'static,
+    {
+        let lookup = move |tx_hash| {
+            if let Ok(Some(sidecar)) = pool.get_blob(tx_hash) {
+                return Some(alloy_rlp::encode(&*sidecar).into())
+            }
+            let provider = provider.database_provider_ro().ok()?;
+            read_sidecar(provider.tx_ref(), tx_hash).ok().flatten()
+        };
+        Self::with_lookup(Arc::new(lookup))
+    }
+
+    fn with_lookup(lookup: SidecarLookup) -> Self {
+        Self { lookup, metrics: Default::default() }
+    }
+
+    /// Answers `message`, if it is a request.
+    fn respond(&self, peer_id: PeerId, message: &[u8]) -> Option<BytesMut> {
+        let request = match BlobsMessage::decode_message(&mut &message[..]) {
+            Ok(BlobsMessage::GetBlobs(request)) => request,
+            // Requests are never sent, so responses are unsolicited
+            Ok(BlobsMessage::Blobs(_)) => return None,
+            Err(err) => {
+                debug!(target: "net::bblob", %peer_id, %err, "Invalid message");
+                return None
+            }
+        };
+
+        let hashes = &request.hashes[..request.hashes.len().min(MAX_BLOBS_PER_REQUEST)];
+        let sidecars: Vec<_> =
+            hashes.iter().map(|hash| (self.lookup)(*hash).unwrap_or_default()).collect();
+        let served = sidecars.iter().filter(|sidecar| !sidecar.is_empty()).count();
+        self.metrics.requested.increment(hashes.len() as u64);
+        self.metrics.served.increment(served as u64);
+        self.metrics.missing.increment((hashes.len() - served) as u64);
+        trace!(target: "net::bblob", %peer_id, requested = hashes.len(), served, "Served sidecars");
+
+        let response = Blobs { request_id: request.request_id, sidecars };
+        Some(BlobsMessage::Blobs(response).encoded())
+    }
+}
+
+impl ProtocolHandler for BlobsProtocolHandler {
+    type ConnectionHandler = Self;
+
+    fn on_incoming(&self, _socket_addr: SocketAddr) -> Option<Self::ConnectionHandler> {
+        Some(self.clone())
+    }
+
+    fn on_outgoing(
+        &self,
+        _socket_addr: SocketAddr,
+        _peer_id: PeerId,
+    ) -> Option<Self::ConnectionHandler> {
+        Some(self.clone())
+    }
+}
+
+impl ConnectionHandler for BlobsProtocolHandler {
+    type Connection = Pin<Box<dyn Stream<Item = BytesMut> + Send>>;
+
+    fn protocol(&self) -> Protocol {
+        BlobsMessage::protocol()
+    }
+
+    fn on_unsupported_by_peer(
+        self,
+        _supported: &SharedCapabilities,
+        _direction: Direction,
+        _peer_id: PeerId,
+    ) -> OnNotSupported {
+        OnNotSupported::KeepAlive
+    }
+
+    fn into_connection(
+        self,
+        _direction: Direction,
+        peer_id: PeerId,
+        conn: ProtocolConnection,
+    ) -> Self::Connection {
+        Box::pin(conn.filter_map(move |message| {
+            let handler = self.clone();
+            async move {
+                // Sidecars may be read from the database
+                tokio::task::spawn_blocking(move || handler.respond(peer_id, &message))
+                    .await
+                    .ok()
+                    .flatten()
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip() {
+        let messages = [
+            BlobsMessage::GetBlobs(GetBlobs { request_id: 7, hashes: vec![TxHash::ZERO] }),
+            BlobsMessage::Blobs(Blobs { request_id: 7, sidecars: vec![Bytes::from_static(b"s")] }),
+        ];
+        for message in messages {
+            let encoded = message.encoded();
+            assert_eq!(BlobsMessage::decode_message(&mut &encoded[..]).unwrap(), message);
+        }
+        assert!(BlobsMessage::decode_message(&mut &[0x02, 0xc0][..]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_sidecars_are_empty() {
+        let known = TxHash::repeat_byte(1);
+        let handler = BlobsProtocolHandler::with_lookup(Arc::new(move |hash| {
+            (hash == known).then(|| Bytes::from_static(b"sidecar"))
+        }));
+        let request = BlobsMessage::GetBlobs(GetBlobs {
+            request_id: 3,
+            hashes: vec![TxHash::repeat_byte(2), known],
+        });
+
+        let response = handler.respond(PeerId::ZERO, &request.encoded()).unwrap();
+        assert_eq!(
+            BlobsMessage::decode_message(&mut &response[..]).unwrap(),
+            BlobsMessage::Blobs(Blobs {
+                request_id: 3,
+                sidecars: vec![Bytes::new(), Bytes::from_static(b"sidecar")],
+            })
+        );
+        assert!(handler.respond(PeerId::ZERO, &response).is_none());
+    }
+}
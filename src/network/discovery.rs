@@ -0,0 +1,115 @@
+//! DNS discovery (EIP-1459) of Berachain peers.
+//!
+//! reth only bootstraps DNS discovery from the trees of Ethereum networks. Berachain nodes use the
+//! trees registered for their chain id below, or those given with `--discovery.dns-trees`, which
+//! replace the registered ones. `--disable-dns-discovery` turns DNS discovery off altogether.
+//!
+//! NAT traversal uses reth's flags: `--nat upnp` asks the gateway for the external address,
+//! `--nat extip:<IP>` advertises a fixed one, and `--port`, `--discovery.port` and
+//! `--discovery.v5.port` set the RLPx, discv4 and discv5 ports, which must be forwarded unchanged
+//! since the advertised ports are the listening ports.
+
+use clap::Args;
+use reth_dns_discovery::tree::LinkEntry;
+use reth_eth_wire_types::NetworkPrimitives;
+use reth_network::NetworkConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tracing::info;
+
+/// EIP-1459 trees of public Berachain networks, by chain id.
+///
+/// No tree has been published for Berachain mainnet (80094) or Bepolia (80069) yet, so their nodes
+/// only use DNS discovery with `--discovery.dns-trees`.
+const DNS_TREES: &[(u64, &[&str])] = &[(80094, &[]), (80069, &[])];
+
+/// Returns the registered DNS discovery trees of `chain_id`.
+pub fn dns_trees(chain_id: u64) -> &'static [&'static str] {
+    DNS_TREES.iter().find(|(id, _)| *id == chain_id).map_or(&[], |(_, trees)| trees)
+}
+
+/// Settings of peer discovery.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DiscoveryConfig {
+    /// `enrtree://` URLs replacing the registered trees of the chain.
+    pub dns_trees: Vec<String>,
+}
+
+impl DiscoveryConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &DiscoveryArgs) -> Self {
+        if !args.dns_trees.is_empty() {
+            self.dns_trees = args.dns_trees.clone();
+        }
+        self
+    }
+
+    /// Returns the trees to bootstrap DNS discovery from on chain `chain_id`, if any.
+    pub fn dns_networks(&self, chain_id: u64) -> eyre::Result<Option<HashSet<LinkEntry>>> {
+        let trees: Vec<&str> = if self.dns_trees.is_empty() {
+            dns_trees(chain_id).to_vec()
+        } else {
+            self.dns_trees.iter().map(String::as_str).collect()
+        };
+        if trees.is_empty() {
+            return Ok(None)
+        }
+        trees
+            .into_iter()
+            .map(|tree| {
+                tree.parse().map_err(|err| eyre::eyre!("invalid DNS discovery tree {tree}: {err}"))
+            })
+            .collect::<eyre::Result<_>>()
+            .map(Some)
+    }
+
+    /// Points DNS discovery of `config` at the Berachain trees, unless it is disabled.
+    pub fn apply<C, N: NetworkPrimitives>(
+        &self,
+        chain_id: u64,
+        config: &mut NetworkConfig<C, N>,
+    ) -> eyre::Result<()> {
+        let Some(dns) = &mut config.dns_discovery_config else { return Ok(()) };
+        if let Some(networks) = self.dns_networks(chain_id)? {
+            info!(target: "reth::cli", trees = networks.len(), "Using DNS discovery trees");
+            dns.bootstrap_dns_networks = Some(networks);
+        }
+        Ok(())
+    }
+}
+
+/// Command line arguments of peer discovery.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Berachain discovery")]
+pub struct DiscoveryArgs {
+    /// Comma separated `enrtree://` URLs to discover peers from, replacing the chain's trees
+    #[arg(long = "discovery.dns-trees", value_delimiter = ',', value_name = "URL")]
+    pub dns_trees: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TREE: &str =
+        "enrtree://AKA3AM6LPBYEUDMVNU3BSVQJ5AD45Y7YPOHJLEF6W26QOE4VTUDPE@all.mainnet.ethdisco.net";
+
+    #[test]
+    fn test_configured_trees_replace_registered_ones() {
+        let config = DiscoveryConfig::default();
+        assert!(config.dns_networks(80094).unwrap().is_none());
+        assert!(config.dns_networks(1).unwrap().is_none());
+
+        let config = config.with_args(&DiscoveryArgs { dns_trees: vec![TREE.to_string()] });
+        let networks = config.dns_networks(80094).unwrap().unwrap();
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks.into_iter().next().unwrap().to_string(), TREE);
+    }
+
+    #[test]
+    fn test_invalid_tree_is_rejected() {
+        let config = DiscoveryConfig { dns_trees: vec!["enrtree://not-a-key".to_string()] };
+        assert!(config.dns_networks(80094).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+//! Berachain devp2p networking.
+//!
+//! Peers exchange Berachain headers (with `prev_proposer_pubkey`), bodies carrying PoL
+//! transactions (type `0x7E`) and receipts typed with [`BerachainTxType`], so every eth message is
+//! encoded with [`BerachainNetworkPrimitives`]. Transactions gossiped to peers use the consensus
+//! envelope; transactions served from the pool use the Ethereum pooled variant, which PoL
+//! transactions never enter.
+//!
+//! eth/69 is always advertised next to eth/68. Its receipts drop the bloom and carry the
+//! transaction type as a plain field, `0x7E` for PoL receipts, so eth/69 peers never fall back to
+//! eth/68 to sync receipts from bera-reth.
+//!
+//! Peers are scored as described in [`peers`], transaction gossip follows the rules in
+//! [`transactions`], and [`static_peers`] keeps pinned peers connected. Peers are discovered
+//! through the DNS trees of the chain as described in [`discovery`], history served to syncing
+//! peers is limited as described in [`serve_limits`], and blob sidecars are served to bera-reth
+//! peers over [`blobs`].
+//!
+//! [`BerachainTxType`]: crate::transaction::BerachainTxType
+
+pub mod blobs;
+pub mod discovery;
+pub mod peers;
+pub mod serve_limits;
+pub mod static_peers;
+pub mod transactions;
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    node::config::BerachainConfig,
+    pool::transaction::BerachainPooledTransactionVariant,
+    primitives::{BerachainBlock, BerachainBlockBody, BerachainHeader, BerachainPrimitives},
+    transaction::BerachainTxEnvelope,
+};
+use blobs::BlobsProtocolHandler;
+use discovery::DiscoveryConfig;
+use reth::api::{FullNodeTypes, NodeTypes};
+use reth_chainspec::EthChainSpec;
+use reth_eth_wire::{EthVersion, HelloMessageWithProtocols, protocol::Protocol};
+use reth_eth_wire_types::{NetworkPrimitives, NewBlock};
+use reth_network::{
+    NetworkHandle, NetworkManager,
+    eth_requests::EthRequestHandler,
+    transactions::{TransactionsManager, config::NetworkPolicies},
+};
+use reth_node_builder::{BuilderContext, components::NetworkBuilder};
+use reth_primitives_traits::NodePrimitives;
+use reth_transaction_pool::{PoolTransaction, TransactionPool};
+use serve_limits::{ETH_REQUEST_CHANNEL_CAPACITY, ServeLimitsConfig, install_serve_limits};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use transactions::{
+    ANNOUNCED_SIZE_OVERHEAD, BerachainAnnouncementFilter, TxIngressConfig,
+    install_tx_ingress_limits,
+};
+
+/// Types exchanged with Berachain peers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct BerachainNetworkPrimitives;
+
+impl NetworkPrimitives for BerachainNetworkPrimitives {
+    type BlockHeader = BerachainHeader;
+    type BlockBody = BerachainBlockBody;
+    type Block = BerachainBlock;
+    type BroadcastedTransaction = BerachainTxEnvelope;
+    type PooledTransaction = BerachainPooledTransactionVariant;
+    type Receipt = <BerachainPrimitives as NodePrimitives>::Receipt;
+    type NewBlockPayload = NewBlock<Self::Block>;
+}
+
+/// Builds the devp2p network of a Berachain node.
+#[derive(Debug, Default, Clone)]
+pub struct BerachainNetworkBuilder {
+    /// DNS discovery trees.
+    pub discovery: DiscoveryConfig,
+    /// Limits on transactions received from peers.
+    pub tx_ingress: TxIngressConfig,
+    /// Limits on history served to peers.
+    pub serve_limits: ServeLimitsConfig,
+}
+
+impl BerachainNetworkBuilder {
+    /// Creates a network builder with the discovery, ingress and serving settings of `config`.
+    pub fn new(config: &BerachainConfig) -> Self {
+        Self {
+            discovery: config.discovery.clone(),
+            tx_ingress: config.tx_ingress,
+            serve_limits: config.serve_limits,
+        }
+    }
+}
+
+impl<Node, Pool> NetworkBuilder<Node, Pool> for BerachainNetworkBuilder
+where
+    Node: FullNodeTypes<
+        Types: NodeTypes<ChainSpec = BerachainChainSpec, Primitives = BerachainPrimitives>,
+    >,
+    Pool: TransactionPool<
+            Transaction: PoolTransaction<
+                Consensus = BerachainTxEnvelope,
+                Pooled = BerachainPooledTransactionVariant,
+            >,
+        > + Unpin
+        + 'static,
+{
+    type Network = NetworkHandle<BerachainNetworkPrimitives>;
+
+    async fn build_network(
+        self,
+        ctx: &BuilderContext<Node>,
+        pool: Pool,
+    ) -> eyre::Result<Self::Network> {
+        let identity = &ctx.config().network.identity;
+        let builder = ctx.network_config_builder()?.apply(|builder| {
+            let mut hello = HelloMessageWithProtocols::builder(builder.get_peer_id())
+                .client_version(identity)
+                .build();
+            advertise_eth69(&mut hello);
+            builder.hello_message(hello)
+        });
+        let mut config = ctx.build_network_config(builder);
+        self.discovery.apply(ctx.chain_spec().chain().id(), &mut config)?;
+        peers::apply_peer_scoring(&mut config.peers_config);
+        config
+            .extra_protocols
+            .push(BlobsProtocolHandler::new(pool.clone(), ctx.provider().clone()));
+        // `BuilderContext::start_network` takes neither an announcement filter nor the channels
+        // the ingress and serving limits sit on, so the managers are wired up here the same way
+        let (mut manager, (), ()) =
+            NetworkManager::<BerachainNetworkPrimitives>::builder(config).await?.split();
+        let (to_transactions, from_network) = mpsc::unbounded_channel();
+        manager.set_transactions(to_transactions);
+        let ingress = self.tx_ingress;
+        let max_announced_size = ingress
+            .max_announced_size
+            .unwrap_or(ctx.config().txpool.max_tx_input_bytes + ANNOUNCED_SIZE_OVERHEAD);
+        let transactions = TransactionsManager::with_policy(
+            manager.handle().clone(),
+            pool,
+            install_tx_ingress_limits(ingress, from_network, ctx.task_executor()),
+            ctx.config().network.transactions_manager_config(),
+            NetworkPolicies::new(
+                ctx.config().network.tx_propagation_policy,
+                BerachainAnnouncementFilter::new(max_announced_size),
+            ),
+        );
+        let (to_eth, from_network) = mpsc::channel(ETH_REQUEST_CHANNEL_CAPACITY);
+        manager.set_eth_request_handler(to_eth);
+        let eth = EthRequestHandler::new(
+            ctx.provider().clone(),
+            manager.peers_handle(),
+            install_serve_limits(self.serve_limits, from_network, ctx.task_executor()),
+        );
+        let (handle, network, (), ()) = manager.into_builder().split_with_handle();
+
+        ctx.task_executor().spawn_critical("p2p txpool", Box::pin(transactions));
+        ctx.task_executor().spawn_critical("p2p eth request handler", Box::pin(eth));
+
+        let known_peers_file =
+            ctx.config().network.persistent_peers_file(ctx.config().datadir().known_peers());
+        ctx.task_executor().spawn_critical_with_graceful_shutdown_signal(
+            "p2p network task",
+            |shutdown| {
+                network.run_until_graceful_shutdown(shutdown, |network| {
+                    let Some(peers_file) = known_peers_file else { return };
+                    match network.write_peers_to_file(&peers_file) {
+                        Ok(()) => {
+                            info!(target: "reth::cli", ?peers_file, "Wrote network peers to file")
+                        }
+                        Err(err) => {
+                            warn!(target: "reth::cli", %err, "Failed to write network peers")
+                        }
+                    }
+                })
+            },
+        );
+
+        let enode = handle.local_node_record();
+        info!(target: "reth::cli", %enode, "P2P networking initialized");
+        Ok(handle)
+    }
+}
+
+/// Adds eth/69 to the advertised protocols, preferring it over the older versions.
+fn advertise_eth69(hello: &mut HelloMessageWithProtocols) {
+    let eth69 = Protocol::eth(EthVersion::Eth69);
+    if !hello.protocols.iter().any(|protocol| protocol.cap == eth69.cap) {
+        hello.protocols.insert(0, eth69);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::compact_corpus, transaction::BerachainTxType};
+    use alloy_consensus::TxReceipt;
+    use alloy_rlp::{Decodable, Encodable};
+    use reth_eth_wire_types::{BlockBodies, BlockHeaders, Receipts, Receipts69, Transactions};
+    use reth_network_peers::PeerId;
+
+    fn rlp_roundtrip<T: Encodable + Decodable + PartialEq + std::fmt::Debug>(value: T) {
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        assert_eq!(T::decode(&mut buf.as_slice()).unwrap(), value);
+    }
+
+    #[test]
+    fn test_eth_messages_carry_berachain_types() {
+        let headers: Vec<_> =
+            compact_corpus::headers().into_iter().map(|entry| entry.value).collect();
+        rlp_roundtrip(BlockHeaders::<BerachainHeader>(headers));
+
+        // Bodies and gossip keep PoL transactions
+        let transactions: Vec<_> =
+            compact_corpus::tx_envelopes().into_iter().map(|entry| entry.value).collect();
+        assert!(transactions.iter().any(|tx| matches!(tx, BerachainTxEnvelope::Berachain(_))));
+        let body = BerachainBlockBody {
+            transactions: transactions.clone(),
+            ommers: Vec::new(),
+            withdrawals: None,
+        };
+        rlp_roundtrip(BlockBodies::<BerachainBlockBody>(vec![body]));
+        rlp_roundtrip(Transactions::<BerachainTxEnvelope>(transactions));
+    }
+
+    #[test]
+    fn test_receipts_of_both_eth_versions_keep_pol_type() {
+        let receipts: Vec<_> =
+            compact_corpus::receipts().into_iter().map(|entry| entry.value).collect();
+        assert!(receipts.iter().any(|receipt| receipt.tx_type == BerachainTxType::Berachain));
+
+        rlp_roundtrip(Receipts69(vec![receipts.clone()]));
+        let with_bloom = receipts.into_iter().map(TxReceipt::into_with_bloom).collect();
+        rlp_roundtrip(Receipts(vec![with_bloom]));
+    }
+
+    #[test]
+    fn test_eth69_is_advertised_once() {
+        let mut hello = HelloMessageWithProtocols::builder(PeerId::ZERO)
+            .protocol(Protocol::eth(EthVersion::Eth68))
+            .build();
+        advertise_eth69(&mut hello);
+        advertise_eth69(&mut hello);
+
+        let eth69 = Protocol::eth(EthVersion::Eth69).cap;
+        assert_eq!(hello.protocols[0].cap, eth69);
+        assert_eq!(hello.protocols.iter().filter(|protocol| protocol.cap == eth69).count(), 1);
+    }
+}
@@ -0,0 +1,82 @@
+//! Peer scoring of Berachain nodes.
+//!
+//! Peers of another chain or with another Prague1 schedule are rejected in the eth handshake: the
+//! fork id covers the genesis hash and every scheduled fork including Prague1, so their `Status`
+//! fails fork filter validation. A peer that passes the handshake but serves headers failing
+//! Berachain validation (e.g. a node that missed a Berachain hardfork) is banned by the first
+//! invalid response instead of being kept around until its reputation slowly decays.
+
+use reth_network_types::{PeersConfig, ReputationWeights, peers::reputation::BANNED_REPUTATION};
+use tracing::debug;
+
+/// Reputation weights of Berachain nodes: invalid headers, blocks and messages ban the peer.
+pub fn berachain_reputation_weights() -> ReputationWeights {
+    ReputationWeights {
+        bad_message: BANNED_REPUTATION,
+        bad_block: BANNED_REPUTATION,
+        ..Default::default()
+    }
+}
+
+/// Applies the Berachain reputation weights, unless weights were set in the `[peers]` section of
+/// the config file.
+pub fn apply_peer_scoring(peers: &mut PeersConfig) {
+    if peers.reputation_weights == ReputationWeights::default() {
+        debug!(target: "reth::cli", "Banning peers serving invalid headers");
+        peers.reputation_weights = berachain_reputation_weights();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chainspec::BerachainChainSpec;
+    use alloy_eips::eip2124::Head;
+    use alloy_genesis::Genesis;
+    use alloy_primitives::U256;
+    use reth_chainspec::{EthChainSpec, MAINNET};
+    use serde_json::json;
+
+    fn chain_spec(prague1_time: u64) -> BerachainChainSpec {
+        let mut genesis = Genesis::default();
+        genesis.config.chain_id = 80094;
+        genesis.config.london_block = Some(0);
+        genesis.config.cancun_time = Some(0);
+        genesis.config.terminal_total_difficulty = Some(U256::ZERO);
+        genesis.config.extra_fields = serde_json::from_value(json!({
+            "berachain": {
+                "prague1": {
+                    "time": prague1_time,
+                    "baseFeeChangeDenominator": 48,
+                    "minimumBaseFeeWei": 1000000000,
+                    "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+                }
+            }
+        }))
+        .unwrap();
+        genesis.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_fork_id_rejects_other_chains_and_prague1_schedules() {
+        let local = chain_spec(1000);
+        let head = Head { timestamp: 1500, ..Default::default() };
+        let filter = local.fork_filter(head);
+
+        assert!(filter.validate(local.fork_id(&head)).is_ok());
+        assert!(filter.validate(chain_spec(2000).fork_id(&head)).is_err());
+        assert!(filter.validate(MAINNET.latest_fork_id()).is_err());
+    }
+
+    #[test]
+    fn test_configured_weights_are_kept() {
+        let mut peers = PeersConfig::default();
+        apply_peer_scoring(&mut peers);
+        assert_eq!(peers.reputation_weights.bad_block, BANNED_REPUTATION);
+
+        let configured = ReputationWeights { bad_block: -1, ..Default::default() };
+        let mut peers = PeersConfig { reputation_weights: configured, ..Default::default() };
+        apply_peer_scoring(&mut peers);
+        assert_eq!(peers.reputation_weights, configured);
+    }
+}
@@ -0,0 +1,287 @@
+//! Limits on history served to peers.
+//!
+//! Header, body and receipt requests of syncing peers are answered from the database, so an
+//! archive node that many peers sync from at once (e.g. after a network upgrade) spends its disk
+//! bandwidth on them instead of following the chain. With `--serve-limits.requests-per-second`
+//! and `--serve-limits.bytes-per-second` every peer gets a budget that refills continuously and
+//! allows bursts of one second worth of requests. Requests over budget are left unanswered, which
+//! makes the peer time out and ask another peer; the bytes of a response are charged once it was
+//! served, so a peer that received a large response waits until its budget recovered.
+
+use crate::network::BerachainNetworkPrimitives;
+use alloy_rlp::Encodable;
+use clap::Args;
+use reth::tasks::TaskExecutor;
+use reth_network::eth_requests::IncomingEthRequest;
+use reth_network_peers::PeerId;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::{mpsc, oneshot};
+use tracing::debug;
+
+/// Capacity of the channels carrying requests of peers, as in reth.
+pub const ETH_REQUEST_CHANNEL_CAPACITY: usize = 256;
+
+/// Budgets of peers idle for this long are forgotten.
+const IDLE_PEER_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Settings of the limits on history served to peers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ServeLimitsConfig {
+    /// Header, body and receipt requests answered per peer and second. Unlimited if unset.
+    pub requests_per_second: Option<u64>,
+    /// Bytes of headers, bodies and receipts served per peer and second. Unlimited if unset.
+    pub bytes_per_second: Option<u64>,
+}
+
+impl ServeLimitsConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &ServeLimitsArgs) -> Self {
+        if let Some(requests_per_second) = args.requests_per_second {
+            self.requests_per_second = Some(requests_per_second);
+        }
+        if let Some(bytes_per_second) = args.bytes_per_second {
+            self.bytes_per_second = Some(bytes_per_second);
+        }
+        self
+    }
+
+    fn is_unlimited(&self) -> bool {
+        self.requests_per_second.is_none() && self.bytes_per_second.is_none()
+    }
+}
+
+/// Command line arguments of the limits on history served to peers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "History serving limits")]
+pub struct ServeLimitsArgs {
+    /// Header, body and receipt requests answered per peer and second
+    #[arg(long = "serve-limits.requests-per-second", value_name = "N")]
+    pub requests_per_second: Option<u64>,
+
+    /// Bytes of headers, bodies and receipts served per peer and second
+    #[arg(long = "serve-limits.bytes-per-second", value_name = "BYTES")]
+    pub bytes_per_second: Option<u64>,
+}
+
+/// Remaining budget of a peer.
+#[derive(Debug)]
+struct PeerBudget {
+    requests: f64,
+    bytes: f64,
+    updated: Instant,
+}
+
+impl PeerBudget {
+    fn new(limits: &ServeLimitsConfig, now: Instant) -> Self {
+        Self {
+            requests: limits.requests_per_second.unwrap_or_default() as f64,
+            bytes: limits.bytes_per_second.unwrap_or_default() as f64,
+            updated: now,
+        }
+    }
+
+    /// Refills the budget up to one second worth.
+    fn refill(&mut self, limits: &ServeLimitsConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.updated = now;
+        if let Some(rate) = limits.requests_per_second {
+            self.requests = (self.requests + elapsed * rate as f64).min(rate as f64);
+        }
+        if let Some(rate) = limits.bytes_per_second {
+            self.bytes = (self.bytes + elapsed * rate as f64).min(rate as f64);
+        }
+    }
+
+    /// Takes a request from the budget, unless it is exhausted.
+    fn admit(&mut self, limits: &ServeLimitsConfig, now: Instant) -> bool {
+        self.refill(limits, now);
+        let requests_left = limits.requests_per_second.is_none() || self.requests >= 1.0;
+        let bytes_left = limits.bytes_per_second.is_none() || self.bytes > 0.0;
+        if requests_left && bytes_left {
+            self.requests -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Budgets of all peers.
+#[derive(Debug)]
+struct ServeLimiter {
+    limits: ServeLimitsConfig,
+    peers: Mutex<HashMap<PeerId, PeerBudget>>,
+}
+
+impl ServeLimiter {
+    fn admit(&self, peer_id: PeerId) -> bool {
+        let now = Instant::now();
+        let mut peers = self.peers.lock().expect("not poisoned");
+        if !peers.contains_key(&peer_id) {
+            peers.retain(|_, budget| now.duration_since(budget.updated) < IDLE_PEER_TIMEOUT);
+        }
+        let admitted = peers
+            .entry(peer_id)
+            .or_insert_with(|| PeerBudget::new(&self.limits, now))
+            .admit(&self.limits, now);
+        if !admitted {
+            debug!(target: "net::eth", %peer_id, "Peer exceeded its serving budget");
+        }
+        admitted
+    }
+
+    fn charge(&self, peer_id: PeerId, bytes: usize) {
+        if let Some(budget) = self.peers.lock().expect("not poisoned").get_mut(&peer_id) {
+            budget.bytes -= bytes as f64;
+        }
+    }
+
+    /// Returns a response channel charging the size of the response to `peer_id`.
+    fn metered<T, E>(
+        self: &Arc<Self>,
+        peer_id: PeerId,
+        response: oneshot::Sender<Result<T, E>>,
+    ) -> oneshot::Sender<Result<T, E>>
+    where
+        T: Encodable + Send + 'static,
+        E: Send + 'static,
+    {
+        if self.limits.bytes_per_second.is_none() {
+            return response
+        }
+        let (tx, rx) = oneshot::channel();
+        let limiter = self.clone();
+        tokio::spawn(async move {
+            let Ok(result) = rx.await else { return };
+            if let Ok(value) = &result {
+                limiter.charge(peer_id, value.length());
+            }
+            let _ = response.send(result);
+        });
+        tx
+    }
+}
+
+/// Applies the serving limits to the requests of peers, returning the requests to answer.
+pub fn install_serve_limits(
+    limits: ServeLimitsConfig,
+    mut requests: mpsc::Receiver<IncomingEthRequest<BerachainNetworkPrimitives>>,
+    executor: &TaskExecutor,
+) -> mpsc::Receiver<IncomingEthRequest<BerachainNetworkPrimitives>> {
+    if limits.is_unlimited() {
+        return requests
+    }
+    let (to_handler, admitted) = mpsc::channel(ETH_REQUEST_CHANNEL_CAPACITY);
+    let limiter = Arc::new(ServeLimiter { limits, peers: Default::default() });
+
+    executor.spawn_critical("p2p eth serve limits", async move {
+        while let Some(request) = requests.recv().await {
+            // Requests over budget are dropped together with their response channel
+            let request = match request {
+                IncomingEthRequest::GetBlockHeaders { peer_id, request, response } => {
+                    if !limiter.admit(peer_id) {
+                        continue
+                    }
+                    let response = limiter.metered(peer_id, response);
+                    IncomingEthRequest::GetBlockHeaders { peer_id, request, response }
+                }
+                IncomingEthRequest::GetBlockBodies { peer_id, request, response } => {
+                    if !limiter.admit(peer_id) {
+                        continue
+                    }
+                    let response = limiter.metered(peer_id, response);
+                    IncomingEthRequest::GetBlockBodies { peer_id, request, response }
+                }
+                IncomingEthRequest::GetReceipts { peer_id, request, response } => {
+                    if !limiter.admit(peer_id) {
+                        continue
+                    }
+                    let response = limiter.metered(peer_id, response);
+                    IncomingEthRequest::GetReceipts { peer_id, request, response }
+                }
+                IncomingEthRequest::GetReceipts69 { peer_id, request, response } => {
+                    if !limiter.admit(peer_id) {
+                        continue
+                    }
+                    let response = limiter.metered(peer_id, response);
+                    IncomingEthRequest::GetReceipts69 { peer_id, request, response }
+                }
+                // Node data isn't served from history, so it's not worth a budget
+                request @ IncomingEthRequest::GetNodeData { .. } => request,
+            };
+            if to_handler.send(request).await.is_err() {
+                return
+            }
+        }
+    });
+    admitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth::tasks::TaskManager;
+    use reth_eth_wire_types::GetReceipts;
+
+    #[test]
+    fn test_request_budget_refills() {
+        let limits = ServeLimitsConfig { requests_per_second: Some(2), bytes_per_second: None };
+        let start = Instant::now();
+        let mut budget = PeerBudget::new(&limits, start);
+
+        assert!(budget.admit(&limits, start));
+        assert!(budget.admit(&limits, start));
+        assert!(!budget.admit(&limits, start));
+        assert!(budget.admit(&limits, start + Duration::from_millis(500)));
+        assert!(!budget.admit(&limits, start + Duration::from_millis(500)));
+
+        // Idle time refills at most one second worth
+        let later = start + Duration::from_secs(60);
+        assert!(budget.admit(&limits, later));
+        assert!(budget.admit(&limits, later));
+        assert!(!budget.admit(&limits, later));
+    }
+
+    #[test]
+    fn test_large_responses_exhaust_bandwidth() {
+        let limits = ServeLimitsConfig { requests_per_second: None, bytes_per_second: Some(1000) };
+        let start = Instant::now();
+        let mut budget = PeerBudget::new(&limits, start);
+
+        assert!(budget.admit(&limits, start));
+        budget.bytes -= 3000.0;
+        assert!(!budget.admit(&limits, start + Duration::from_secs(1)));
+        assert!(budget.admit(&limits, start + Duration::from_millis(2100)));
+    }
+
+    #[tokio::test]
+    async fn test_eth69_receipt_requests_are_limited() {
+        let tasks = TaskManager::current();
+        let limits = ServeLimitsConfig { requests_per_second: Some(1), bytes_per_second: None };
+        let (to_limiter, requests) = mpsc::channel(ETH_REQUEST_CHANNEL_CAPACITY);
+        let mut admitted = install_serve_limits(limits, requests, &tasks.executor());
+
+        let peer_id = PeerId::repeat_byte(1);
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            let (response, rx) = oneshot::channel();
+            responses.push(rx);
+            let request = GetReceipts(Vec::new());
+            to_limiter
+                .send(IncomingEthRequest::GetReceipts69 { peer_id, request, response })
+                .await
+                .unwrap();
+        }
+
+        let first = admitted.recv().await;
+        assert!(matches!(first, Some(IncomingEthRequest::GetReceipts69 { .. })));
+        // The request over budget is dropped together with its response channel
+        assert!(responses.pop().unwrap().await.is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Static peers kept connected for the lifetime of the node.
+//!
+//! Peers given with `--p2p.static-peers` (enode URLs, hostnames are resolved on every attempt) and
+//! peers added at runtime with `admin_addPeer` are redialed every `--p2p.reconnect-interval`
+//! seconds while they are disconnected, so operators can pin a mesh between their own nodes.
+//! Peers added with `admin_addPeer` are written to `static-peers.json` in the datadir and restored
+//! on restart until they are removed with `admin_removePeer`. The remaining known peers are written
+//! to `known-peers.json` on shutdown, as in reth.
+
+use clap::Args;
+use reth::tasks::TaskExecutor;
+use reth_network::Peers;
+use reth_network_peers::{NodeRecord, PeerId, TrustedPeer};
+use reth_network_types::PeerKind;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{debug, info, warn};
+
+/// File in the datadir holding the peers added with `admin_addPeer`.
+pub const STATIC_PEERS_FILE: &str = "static-peers.json";
+
+/// Default interval between reconnection attempts, in seconds.
+pub const DEFAULT_RECONNECT_INTERVAL: u64 = 30;
+
+/// Settings of static peers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct StaticPeersConfig {
+    /// Peers to keep connected.
+    pub static_peers: Vec<TrustedPeer>,
+    /// Interval between reconnection attempts, in seconds.
+    pub reconnect_interval: u64,
+}
+
+impl Default for StaticPeersConfig {
+    fn default() -> Self {
+        Self { static_peers: Vec::new(), reconnect_interval: DEFAULT_RECONNECT_INTERVAL }
+    }
+}
+
+impl StaticPeersConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &StaticPeersArgs) -> Self {
+        if !args.static_peers.is_empty() {
+            self.static_peers = args.static_peers.clone();
+        }
+        if let Some(reconnect_interval) = args.reconnect_interval {
+            self.reconnect_interval = reconnect_interval;
+        }
+        self
+    }
+}
+
+/// Command line arguments of static peers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Static peers")]
+pub struct StaticPeersArgs {
+    /// Comma separated enode URLs of peers to keep connected, hostnames are allowed
+    #[arg(long = "p2p.static-peers", value_delimiter = ',', value_name = "ENODE")]
+    pub static_peers: Vec<TrustedPeer>,
+
+    /// Interval between reconnection attempts to static peers, in seconds [default: 30]
+    #[arg(long = "p2p.reconnect-interval", value_name = "SECONDS")]
+    pub reconnect_interval: Option<u64>,
+}
+
+/// Static peers of a running node.
+#[derive(Debug)]
+pub struct StaticPeers<N> {
+    network: N,
+    configured: Vec<TrustedPeer>,
+    added: Mutex<BTreeMap<PeerId, NodeRecord>>,
+    file: PathBuf,
+}
+
+impl<N: Peers> StaticPeers<N> {
+    /// Adds `record` as a static peer, connects to it and remembers it across restarts.
+    pub fn add(&self, record: NodeRecord) -> eyre::Result<()> {
+        let mut added = self.added.lock().expect("not poisoned");
+        added.insert(record.id, record);
+        write_peers(&self.file, added.values())?;
+        drop(added);
+
+        info!(target: "net::peers", peer_id = %record.id, "Added static peer");
+        self.connect(&record);
+        Ok(())
+    }
+
+    /// Removes the static peer `peer_id` and disconnects it. Returns whether it was added with
+    /// [`StaticPeers::add`]; peers given on the command line come back on restart.
+    pub fn remove(&self, peer_id: PeerId) -> eyre::Result<bool> {
+        let mut added = self.added.lock().expect("not poisoned");
+        let removed = added.remove(&peer_id).is_some();
+        if removed {
+            write_peers(&self.file, added.values())?;
+        }
+        drop(added);
+
+        self.network.remove_peer(peer_id, PeerKind::Static);
+        self.network.disconnect_peer(peer_id);
+        info!(target: "net::peers", %peer_id, "Removed static peer");
+        Ok(removed)
+    }
+
+    /// Dials every static peer that is not connected.
+    async fn reconnect(&self) {
+        let mut records = Vec::with_capacity(self.configured.len());
+        for peer in &self.configured {
+            match peer.resolve().await {
+                Ok(record) => records.push(record),
+                Err(err) => {
+                    warn!(target: "net::peers", %peer, %err, "Failed to resolve static peer")
+                }
+            }
+        }
+        records.extend(self.added.lock().expect("not poisoned").values().copied());
+
+        for record in records {
+            if matches!(self.network.get_peer_by_id(record.id).await, Ok(Some(_))) {
+                continue
+            }
+            debug!(target: "net::peers", peer_id = %record.id, "Reconnecting static peer");
+            self.connect(&record);
+        }
+    }
+
+    fn connect(&self, record: &NodeRecord) {
+        self.network.add_peer_kind(
+            record.id,
+            PeerKind::Static,
+            record.tcp_addr(),
+            Some(record.udp_addr()),
+        );
+        self.network.connect_peer_kind(
+            record.id,
+            PeerKind::Static,
+            record.tcp_addr(),
+            Some(record.udp_addr()),
+        );
+    }
+}
+
+/// Reads the peers stored in `path`, or none if the file does not exist.
+fn read_peers(path: &Path) -> eyre::Result<Vec<NodeRecord>> {
+    match std::fs::read(path) {
+        Ok(contents) => serde_json::from_slice(&contents)
+            .map_err(|err| eyre::eyre!("invalid static peers file {}: {err}", path.display())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(eyre::eyre!("failed to read {}: {err}", path.display())),
+    }
+}
+
+/// Writes `peers` to `path`, replacing the file atomically.
+fn write_peers<'a>(path: &Path, peers: impl Iterator<Item = &'a NodeRecord>) -> eyre::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(&peers.collect::<Vec<_>>())?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Connects the static peers of `network` and keeps them connected. Returns them for
+/// `admin_addPeer` and `admin_removePeer`.
+pub fn install_static_peers<N>(
+    config: StaticPeersConfig,
+    data_dir: &Path,
+    network: N,
+    executor: &TaskExecutor,
+) -> eyre::Result<Arc<StaticPeers<N>>>
+where
+    N: Peers + 'static,
+{
+    let file = data_dir.join(STATIC_PEERS_FILE);
+    let added = read_peers(&file)?.into_iter().map(|record| (record.id, record)).collect();
+    let peers = Arc::new(StaticPeers {
+        network,
+        configured: config.static_peers,
+        added: Mutex::new(added),
+        file,
+    });
+
+    let interval = Duration::from_secs(config.reconnect_interval.max(1));
+    let reconnecting = peers.clone();
+    executor.spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            reconnecting.reconnect().await;
+        }
+    });
+    Ok(peers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE_ID: &str = "6f8a80d14311c39f35f516fa664deaaaa13e85b2f7493f37f6144d86991ec012\
+                           937307647bd3b9a82abe2974e1407241d54947bbb39763a4cac9f77166ad92a0";
+
+    #[test]
+    fn test_cli_overrides_file() {
+        let file: StaticPeersConfig = toml::from_str(&format!(
+            "static-peers = [\"enode://{NODE_ID}@10.3.58.6:30303\"]\nreconnect-interval = 10"
+        ))
+        .unwrap();
+        assert_eq!(file.static_peers.len(), 1);
+
+        let args = StaticPeersArgs {
+            static_peers: vec![format!("enode://{NODE_ID}@bera-node-1:30303").parse().unwrap()],
+            reconnect_interval: None,
+        };
+        let config = file.with_args(&args);
+        assert_eq!(config.static_peers, args.static_peers);
+        assert_eq!(config.reconnect_interval, 10);
+    }
+
+    #[test]
+    fn test_added_peers_survive_restarts() {
+        let path = std::env::temp_dir().join(format!("bera-static-peers-{}", std::process::id()));
+        assert!(read_peers(&path).unwrap().is_empty());
+
+        let record: NodeRecord =
+            format!("enode://{NODE_ID}@10.3.58.6:30303?discport=30301").parse().unwrap();
+        write_peers(&path, [record].iter()).unwrap();
+        assert_eq!(read_peers(&path).unwrap(), vec![record]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,271 @@
+//! Transaction gossip rules.
+//!
+//! PoL transactions (type `0x7E`) are system transactions that only exist inside blocks; they must
+//! never travel through eth/68 transaction gossip. The pool cannot hold them (its transactions are
+//! Ethereum envelopes), so they are never announced or broadcast. Incoming, an announcement of type
+//! `0x7E` is rejected by [`BerachainAnnouncementFilter`] and penalizes the peer, and a full
+//! `Transactions` broadcast containing one fails the conversion to the pooled type, which the
+//! transactions manager reports as a bad transaction of that peer.
+//!
+//! Gas is cheap on Berachain, so peers can flood the node with transactions carrying large
+//! calldata. Announced non-blob transactions larger than `--tx-ingress.max-announced-size` (by
+//! default the pool's `--txpool.max-tx-input-bytes` plus [`ANNOUNCED_SIZE_OVERHEAD`]) are never
+//! fetched, since the pool would reject them anyway. With `--tx-ingress.bytes-per-second` every
+//! peer additionally gets a budget of announced and broadcast transaction bytes that refills
+//! continuously and allows bursts of one second worth; announcements and broadcasts over budget
+//! are dropped without penalizing the peer. Dropped announcements are counted in the
+//! `berachain_tx_ingress_*` metrics.
+
+use crate::{network::BerachainNetworkPrimitives, transaction::POL_TX_TYPE};
+use alloy_consensus::TxType;
+use alloy_primitives::B256;
+use alloy_rlp::Encodable;
+use clap::Args;
+use reth::tasks::TaskExecutor;
+use reth_eth_wire_types::NewPooledTransactionHashes;
+use reth_metrics::{Metrics, metrics::Counter};
+use reth_network::transactions::{
+    NetworkTransactionEvent,
+    config::{AnnouncementAcceptance, AnnouncementFilteringPolicy, StrictEthAnnouncementFilter},
+};
+use reth_network_peers::PeerId;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tracing::{debug, trace};
+
+/// Bytes allowed on top of the maximum calldata size for the rest of an announced transaction.
+pub const ANNOUNCED_SIZE_OVERHEAD: usize = 4 * 1024;
+
+/// Budgets of peers idle for this long are forgotten.
+const IDLE_PEER_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Transaction ingress metrics.
+#[derive(Metrics, Clone)]
+#[metrics(scope = "berachain.tx_ingress")]
+struct TxIngressMetrics {
+    /// Announced transactions rejected for their size.
+    oversized_announcements: Counter,
+    /// Announcement messages dropped because the peer exceeded its budget.
+    dropped_announcements: Counter,
+    /// Transaction broadcasts dropped because the peer exceeded its budget.
+    dropped_broadcasts: Counter,
+}
+
+/// Settings of the limits on transactions received from peers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TxIngressConfig {
+    /// Largest announced non-blob transaction fetched, in bytes. Derived from the pool's maximum
+    /// calldata size if unset.
+    pub max_announced_size: Option<usize>,
+    /// Announced and broadcast transaction bytes accepted per peer and second. Unlimited if unset.
+    pub bytes_per_second: Option<u64>,
+}
+
+impl TxIngressConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &TxIngressArgs) -> Self {
+        if let Some(max_announced_size) = args.max_announced_size {
+            self.max_announced_size = Some(max_announced_size);
+        }
+        if let Some(bytes_per_second) = args.bytes_per_second {
+            self.bytes_per_second = Some(bytes_per_second);
+        }
+        self
+    }
+}
+
+/// Command line arguments of the limits on transactions received from peers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Transaction ingress limits")]
+pub struct TxIngressArgs {
+    /// Largest announced non-blob transaction fetched from peers, in bytes [default:
+    /// --txpool.max-tx-input-bytes + 4096]
+    #[arg(long = "tx-ingress.max-announced-size", value_name = "BYTES")]
+    pub max_announced_size: Option<usize>,
+
+    /// Announced and broadcast transaction bytes accepted per peer and second
+    #[arg(long = "tx-ingress.bytes-per-second", value_name = "BYTES")]
+    pub bytes_per_second: Option<u64>,
+}
+
+/// Announcement filter of Berachain nodes: the Ethereum transaction types are accepted up to the
+/// maximum size, PoL and unknown types are rejected and penalize the announcing peer.
+#[derive(Debug, Clone)]
+pub struct BerachainAnnouncementFilter {
+    max_size: usize,
+    metrics: TxIngressMetrics,
+}
+
+impl Default for BerachainAnnouncementFilter {
+    fn default() -> Self {
+        Self::new(usize::MAX)
+    }
+}
+
+impl BerachainAnnouncementFilter {
+    /// Creates a filter rejecting non-blob transactions announced larger than `max_size` bytes.
+    pub fn new(max_size: usize) -> Self {
+        Self { max_size, metrics: TxIngressMetrics::default() }
+    }
+}
+
+impl AnnouncementFilteringPolicy for BerachainAnnouncementFilter {
+    fn decide_on_announcement(&self, ty: u8, hash: &B256, size: usize) -> AnnouncementAcceptance {
+        if ty == POL_TX_TYPE {
+            debug!(target: "net::tx", %hash, "Peer announced a PoL transaction");
+            return AnnouncementAcceptance::Reject { penalize_peer: true }
+        }
+        // Blob transactions are bounded by the pool's blob limits instead
+        if ty != TxType::Eip4844 as u8 && size > self.max_size {
+            trace!(target: "net::tx", %hash, size, "Peer announced an oversized transaction");
+            self.metrics.oversized_announcements.increment(1);
+            return AnnouncementAcceptance::Reject { penalize_peer: false }
+        }
+        StrictEthAnnouncementFilter::default().decide_on_announcement(ty, hash, size)
+    }
+}
+
+/// Remaining ingress budget of a peer.
+#[derive(Debug)]
+struct IngressBudget {
+    bytes: f64,
+    updated: Instant,
+}
+
+impl IngressBudget {
+    /// Takes `bytes` from the budget refilled at `rate` bytes per second, unless it is exhausted.
+    fn admit(&mut self, rate: u64, bytes: usize, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.updated = now;
+        self.bytes = (self.bytes + elapsed * rate as f64).min(rate as f64);
+        if self.bytes <= 0.0 {
+            return false
+        }
+        self.bytes -= bytes as f64;
+        true
+    }
+}
+
+/// Returns the sender of `event` and the transaction bytes it announced or broadcast, if it is a
+/// transaction announcement or broadcast.
+///
+/// eth/66 announcements carry no sizes; Berachain peers announce with eth/68.
+fn ingress(event: &NetworkTransactionEvent<BerachainNetworkPrimitives>) -> Option<(PeerId, usize)> {
+    match event {
+        NetworkTransactionEvent::IncomingTransactions { peer_id, msg } => {
+            Some((*peer_id, msg.length()))
+        }
+        NetworkTransactionEvent::IncomingPooledTransactionHashes { peer_id, msg } => match msg {
+            NewPooledTransactionHashes::Eth68(msg) => Some((*peer_id, msg.sizes.iter().sum())),
+            NewPooledTransactionHashes::Eth66(_) => Some((*peer_id, 0)),
+        },
+        _ => None,
+    }
+}
+
+/// Applies the per-peer ingress budget to the transaction events of the network, returning the
+/// events to handle.
+pub fn install_tx_ingress_limits(
+    config: TxIngressConfig,
+    mut events: mpsc::UnboundedReceiver<NetworkTransactionEvent<BerachainNetworkPrimitives>>,
+    executor: &TaskExecutor,
+) -> mpsc::UnboundedReceiver<NetworkTransactionEvent<BerachainNetworkPrimitives>> {
+    let Some(rate) = config.bytes_per_second else { return events };
+    let (to_manager, admitted) = mpsc::unbounded_channel();
+
+    executor.spawn_critical("p2p tx ingress limits", async move {
+        let metrics = TxIngressMetrics::default();
+        let mut peers = HashMap::<PeerId, IngressBudget>::new();
+        while let Some(event) = events.recv().await {
+            if let Some((peer_id, bytes)) = ingress(&event) {
+                let now = Instant::now();
+                if !peers.contains_key(&peer_id) {
+                    peers
+                        .retain(|_, budget| now.duration_since(budget.updated) < IDLE_PEER_TIMEOUT);
+                }
+                let budget = peers
+                    .entry(peer_id)
+                    .or_insert_with(|| IngressBudget { bytes: rate as f64, updated: now });
+                if !budget.admit(rate, bytes, now) {
+                    debug!(target: "net::tx", %peer_id, bytes, "Peer exceeded its ingress budget");
+                    match event {
+                        NetworkTransactionEvent::IncomingTransactions { .. } => {
+                            metrics.dropped_broadcasts.increment(1)
+                        }
+                        _ => metrics.dropped_announcements.increment(1),
+                    }
+                    continue
+                }
+            }
+            if to_manager.send(event).is_err() {
+                return
+            }
+        }
+    });
+    admitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pool::transaction::BerachainPooledTransactionVariant,
+        test_utils::compact_corpus,
+        transaction::{BerachainTxEnvelope, TxConversionError},
+    };
+
+    #[test]
+    fn test_pol_announcements_penalize_peer() {
+        let filter = BerachainAnnouncementFilter::default();
+        assert!(matches!(
+            filter.decide_on_announcement(POL_TX_TYPE, &B256::ZERO, 200),
+            AnnouncementAcceptance::Reject { penalize_peer: true }
+        ));
+        assert!(matches!(
+            filter.decide_on_announcement(TxType::Eip1559 as u8, &B256::ZERO, 200),
+            AnnouncementAcceptance::Accept
+        ));
+    }
+
+    #[test]
+    fn test_oversized_announcements_are_rejected() {
+        let filter = BerachainAnnouncementFilter::new(1024);
+        assert!(matches!(
+            filter.decide_on_announcement(TxType::Eip1559 as u8, &B256::ZERO, 1025),
+            AnnouncementAcceptance::Reject { penalize_peer: false }
+        ));
+        assert!(matches!(
+            filter.decide_on_announcement(TxType::Eip4844 as u8, &B256::ZERO, 131_072),
+            AnnouncementAcceptance::Accept
+        ));
+    }
+
+    #[test]
+    fn test_ingress_budget_refills() {
+        let start = Instant::now();
+        let mut budget = IngressBudget { bytes: 1000.0, updated: start };
+
+        assert!(budget.admit(1000, 3000, start));
+        assert!(!budget.admit(1000, 1, start + Duration::from_secs(1)));
+        assert!(budget.admit(1000, 1, start + Duration::from_millis(2100)));
+    }
+
+    #[test]
+    fn test_pol_broadcasts_are_not_importable() {
+        for entry in compact_corpus::tx_envelopes() {
+            let pooled = BerachainPooledTransactionVariant::try_from(entry.value.clone());
+            if matches!(entry.value, BerachainTxEnvelope::Berachain(_)) {
+                assert!(
+                    matches!(pooled, Err(TxConversionError::UnsupportedBerachainTransaction)),
+                    "{}",
+                    entry.name
+                );
+            }
+        }
+    }
+}
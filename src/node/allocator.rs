@@ -0,0 +1,123 @@
+//! Allocator statistics and heap profiling
+
+use reth_metrics::{Metrics, metrics::Gauge};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Allocator statistics, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocatorStats {
+    /// Bytes allocated by the node.
+    pub allocated: u64,
+    /// Bytes in pages holding allocations.
+    pub active: u64,
+    /// Bytes of allocator metadata.
+    pub metadata: u64,
+    /// Bytes of physical memory mapped by the allocator.
+    pub resident: u64,
+    /// Bytes of virtual memory mapped by the allocator.
+    pub mapped: u64,
+    /// Bytes of virtual memory kept for reuse instead of being returned to the system.
+    pub retained: u64,
+    /// Whether allocations are sampled, `None` if heap profiling is not built in.
+    pub heap_profiling: Option<bool>,
+}
+
+/// Heap profiling metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.allocator")]
+struct AllocatorMetrics {
+    /// Whether allocations are sampled for heap profiles.
+    heap_profiling: Gauge,
+}
+
+/// Returns the current allocator statistics.
+pub fn stats() -> eyre::Result<AllocatorStats> {
+    imp::stats()
+}
+
+/// Returns whether allocations are sampled, `None` if heap profiling is not built in.
+pub fn heap_profiling() -> Option<bool> {
+    imp::heap_profiling()
+}
+
+/// Pauses or resumes the sampling of allocations.
+pub fn set_heap_profiling(active: bool) -> eyre::Result<()> {
+    eyre::ensure!(heap_profiling().is_some(), "bera-reth was built without heap profiling");
+    imp::set_heap_profiling(active)?;
+    AllocatorMetrics::default().heap_profiling.set(if active { 1.0 } else { 0.0 });
+    Ok(())
+}
+
+/// Writes a heap profile of the sampled allocations to `path`.
+pub fn dump_heap_profile(path: &Path) -> eyre::Result<()> {
+    eyre::ensure!(heap_profiling() == Some(true), "heap profiling is not active");
+    imp::dump_heap_profile(path)
+}
+
+#[cfg(all(feature = "jemalloc", unix))]
+mod imp {
+    use super::AllocatorStats;
+    use std::{ffi::CString, os::unix::ffi::OsStrExt, path::Path};
+    use tikv_jemalloc_ctl::{epoch, raw, stats};
+
+    fn ctl<T>(result: Result<T, tikv_jemalloc_ctl::Error>) -> eyre::Result<T> {
+        result.map_err(|err| eyre::eyre!("jemalloc: {err}"))
+    }
+
+    pub(super) fn stats() -> eyre::Result<AllocatorStats> {
+        // statistics are cached until the epoch advances
+        ctl(epoch::advance())?;
+        Ok(AllocatorStats {
+            allocated: ctl(stats::allocated::read())? as u64,
+            active: ctl(stats::active::read())? as u64,
+            metadata: ctl(stats::metadata::read())? as u64,
+            resident: ctl(stats::resident::read())? as u64,
+            mapped: ctl(stats::mapped::read())? as u64,
+            retained: ctl(stats::retained::read())? as u64,
+            heap_profiling: heap_profiling(),
+        })
+    }
+
+    pub(super) fn heap_profiling() -> Option<bool> {
+        // SAFETY: both options are booleans
+        unsafe {
+            raw::read::<bool>(b"opt.prof\0").ok().filter(|prof| *prof)?;
+            raw::read::<bool>(b"prof.active\0").ok()
+        }
+    }
+
+    pub(super) fn set_heap_profiling(active: bool) -> eyre::Result<()> {
+        // SAFETY: `prof.active` is a boolean
+        ctl(unsafe { raw::write(b"prof.active\0", active) })
+    }
+
+    pub(super) fn dump_heap_profile(path: &Path) -> eyre::Result<()> {
+        let path = CString::new(path.as_os_str().as_bytes())?;
+        // SAFETY: `prof.dump` takes a NUL-terminated path that outlives the call
+        ctl(unsafe { raw::write(b"prof.dump\0", path.as_ptr()) })
+    }
+}
+
+#[cfg(not(all(feature = "jemalloc", unix)))]
+mod imp {
+    use super::AllocatorStats;
+    use std::path::Path;
+
+    pub(super) fn stats() -> eyre::Result<AllocatorStats> {
+        eyre::bail!("bera-reth was built without jemalloc")
+    }
+
+    pub(super) const fn heap_profiling() -> Option<bool> {
+        None
+    }
+
+    pub(super) fn set_heap_profiling(_active: bool) -> eyre::Result<()> {
+        eyre::bail!("bera-reth was built without jemalloc")
+    }
+
+    pub(super) fn dump_heap_profile(_path: &Path) -> eyre::Result<()> {
+        eyre::bail!("bera-reth was built without jemalloc")
+    }
+}
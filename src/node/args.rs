@@ -0,0 +1,102 @@
+//! Berachain-specific arguments of the `node` command
+
+use crate::{
+    engine::builder::BlockBuilderArgs,
+    network::{
+        discovery::DiscoveryArgs, serve_limits::ServeLimitsArgs, static_peers::StaticPeersArgs,
+        transactions::TxIngressArgs,
+    },
+    node::{
+        caches::CacheArgs, datadir::DatadirLayoutArgs, debug_bundle::DebugBundleArgs,
+        health::HealthArgs, runtime::RuntimeArgs, standby::StandbyArgs,
+        state_root_sampling::StateRootSamplingArgs, supervisor::SupervisorArgs, sync::SyncArgs,
+    },
+    pool::propagation::TxPropagationArgs,
+    rpc::{auth::EngineAuthArgs, logs::GetLogsArgs, response_cache::ResponseCacheArgs},
+    storage::compression::CompressionArgs,
+};
+use clap::Args;
+
+/// Extra arguments accepted by `bera-reth node`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct BerachainArgs {
+    /// Payload builder settings.
+    #[command(flatten)]
+    pub builder: BlockBuilderArgs,
+
+    /// Cache sizing after the memory of the machine.
+    #[command(flatten)]
+    pub caches: CacheArgs,
+
+    /// Per-table compression settings.
+    #[command(flatten)]
+    pub compression: CompressionArgs,
+
+    /// Placement of datadir parts on other volumes.
+    #[command(flatten)]
+    pub datadir: DatadirLayoutArgs,
+
+    /// Debug bundle capture.
+    #[command(flatten)]
+    pub debug_bundle: DebugBundleArgs,
+
+    /// DNS discovery trees.
+    #[command(flatten)]
+    pub discovery: DiscoveryArgs,
+
+    /// Engine API endpoint with JWT secret rotation.
+    #[command(flatten)]
+    pub engine_auth: EngineAuthArgs,
+
+    /// Parallel `eth_getLogs`.
+    #[command(flatten)]
+    pub get_logs: GetLogsArgs,
+
+    /// Health and readiness endpoints.
+    #[command(flatten)]
+    pub health: HealthArgs,
+
+    /// Static peers kept connected.
+    #[command(flatten)]
+    pub p2p: StaticPeersArgs,
+
+    /// Cache of RPC responses about finalized blocks.
+    #[command(flatten)]
+    pub response_cache: ResponseCacheArgs,
+
+    /// Thread pool sizing.
+    #[command(flatten)]
+    pub runtime: RuntimeArgs,
+
+    /// Limits on history served to peers.
+    #[command(flatten)]
+    pub serve_limits: ServeLimitsArgs,
+
+    /// Hot standby following a primary node.
+    #[command(flatten)]
+    pub standby: StandbyArgs,
+
+    /// Sampled re-verification of state roots.
+    #[command(flatten)]
+    pub state_root_sampling: StateRootSamplingArgs,
+
+    /// PID file and systemd readiness notification.
+    #[command(flatten)]
+    pub supervisor: SupervisorArgs,
+
+    /// Checkpoint sync from a trusted block hash.
+    #[command(flatten)]
+    pub sync: SyncArgs,
+
+    /// Limits on transactions received from peers.
+    #[command(flatten)]
+    pub tx_ingress: TxIngressArgs,
+
+    /// Transaction propagation policies by origin.
+    #[command(flatten)]
+    pub txpool: TxPropagationArgs,
+
+    /// Write the startup configuration report to `startup-report.json` in the datadir.
+    #[arg(long)]
+    pub write_startup_report: bool,
+}
@@ -0,0 +1,286 @@
+//! Cache sizing after the memory available to the node
+
+use crate::storage::header_cache::DEFAULT_HEADER_CACHE_SIZE;
+use clap::{ArgMatches, Args, parser::ValueSource};
+use reth_metrics::{Metrics, metrics::Gauge};
+use reth_node_core::args::{EngineArgs, RpcStateCacheArgs};
+use serde::{Deserialize, Serialize};
+
+/// Memory the default cache sizes of reth are meant for, in MiB.
+pub const REFERENCE_MEMORY_MB: u64 = 32 * 1024;
+
+/// Share of the memory given to the cross-block state cache.
+const CROSS_BLOCK_CACHE_SHARE: u64 = 8;
+
+/// Cache sizing settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CacheConfig {
+    /// Whether to size caches after the memory of the machine.
+    pub auto_size: bool,
+    /// Memory to size caches for, in MiB, instead of the detected memory.
+    pub memory_mb: Option<u64>,
+    /// Headers kept by the node's sealed header cache.
+    pub header_cache_size: Option<u32>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { auto_size: true, memory_mb: None, header_cache_size: None }
+    }
+}
+
+impl CacheConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &CacheArgs) -> Self {
+        if args.no_auto_size {
+            self.auto_size = false;
+        }
+        if let Some(memory_mb) = args.memory_mb {
+            self.memory_mb = Some(memory_mb);
+        }
+        if let Some(headers) = args.headers {
+            self.header_cache_size = Some(headers);
+        }
+        self
+    }
+
+    /// Sizes the caches of `engine` and `rpc_cache` and the header cache unless their size was
+    /// set explicitly, returning the memory they were sized for.
+    pub fn apply(
+        &mut self,
+        explicit: ExplicitCaches,
+        engine: &mut EngineArgs,
+        rpc_cache: &mut RpcStateCacheArgs,
+    ) -> Option<u64> {
+        if !self.auto_size {
+            return None
+        }
+        let memory_mb = self.memory_mb.or_else(detected_memory_mb)?;
+        let sizes = CacheSizes::for_memory(memory_mb);
+
+        if !explicit.cross_block_cache {
+            engine.cross_block_cache_size = sizes.cross_block_cache_mb;
+        }
+        if !explicit.rpc_max_blocks {
+            rpc_cache.max_blocks = sizes.max_blocks;
+        }
+        if !explicit.rpc_max_receipts {
+            rpc_cache.max_receipts = sizes.max_receipts;
+        }
+        if !explicit.rpc_max_headers {
+            rpc_cache.max_headers = sizes.max_headers;
+        }
+        let header_cache_size = *self.header_cache_size.get_or_insert(sizes.header_cache_size);
+
+        let metrics = CacheMetrics::default();
+        metrics.memory_mb.set(memory_mb as f64);
+        metrics.cross_block_cache_mb.set(engine.cross_block_cache_size as f64);
+        metrics.rpc_max_blocks.set(rpc_cache.max_blocks as f64);
+        metrics.rpc_max_receipts.set(rpc_cache.max_receipts as f64);
+        metrics.rpc_max_headers.set(rpc_cache.max_headers as f64);
+        metrics.header_cache_size.set(header_cache_size as f64);
+        Some(memory_mb)
+    }
+}
+
+/// Reth cache sizes given on the command line, which [`CacheConfig::apply`] keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExplicitCaches {
+    /// `--engine.cross-block-cache-size` was given.
+    pub cross_block_cache: bool,
+    /// `--rpc-cache.max-blocks` was given.
+    pub rpc_max_blocks: bool,
+    /// `--rpc-cache.max-receipts` was given.
+    pub rpc_max_receipts: bool,
+    /// `--rpc-cache.max-headers` was given.
+    pub rpc_max_headers: bool,
+}
+
+impl ExplicitCaches {
+    /// Reads the sizes given in the matches of the `node` command, on the command line or through
+    /// the environment. A size equal to reth's default still counts as given.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let explicit = |id: &str| {
+            matches.ids().any(|arg| arg == id) &&
+                matches
+                    .value_source(id)
+                    .is_some_and(|source| source != ValueSource::DefaultValue)
+        };
+        Self {
+            cross_block_cache: explicit("cross_block_cache_size"),
+            rpc_max_blocks: explicit("max_blocks"),
+            rpc_max_receipts: explicit("max_receipts"),
+            rpc_max_headers: explicit("max_headers"),
+        }
+    }
+}
+
+/// Cache sizes for an amount of memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheSizes {
+    /// Size of the cross-block state cache, in MiB.
+    pub cross_block_cache_mb: u64,
+    /// Blocks kept by the RPC cache.
+    pub max_blocks: u32,
+    /// Receipts of blocks kept by the RPC cache.
+    pub max_receipts: u32,
+    /// Headers kept by the RPC cache.
+    pub max_headers: u32,
+    /// Headers kept by the sealed header cache.
+    pub header_cache_size: u32,
+}
+
+impl CacheSizes {
+    /// Returns the sizes for `memory_mb` MiB: an eighth of the memory for the state cache, and
+    /// the RPC and header caches scaled from their defaults, between a quarter and four times them.
+    pub fn for_memory(memory_mb: u64) -> Self {
+        let scale = |default: u32| {
+            let scaled = default as u64 * memory_mb / REFERENCE_MEMORY_MB;
+            scaled.clamp(default as u64 / 4, default as u64 * 4) as u32
+        };
+        let defaults = RpcStateCacheArgs::default();
+        Self {
+            cross_block_cache_mb: (memory_mb / CROSS_BLOCK_CACHE_SHARE).clamp(256, 16 * 1024),
+            max_blocks: scale(defaults.max_blocks),
+            max_receipts: scale(defaults.max_receipts),
+            max_headers: scale(defaults.max_headers),
+            header_cache_size: scale(DEFAULT_HEADER_CACHE_SIZE),
+        }
+    }
+}
+
+/// Cache sizing metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.caches")]
+struct CacheMetrics {
+    /// Memory the caches were sized for, in MiB.
+    memory_mb: Gauge,
+    /// Size of the cross-block state cache, in MiB.
+    cross_block_cache_mb: Gauge,
+    /// Blocks kept by the RPC cache.
+    rpc_max_blocks: Gauge,
+    /// Receipts of blocks kept by the RPC cache.
+    rpc_max_receipts: Gauge,
+    /// Headers kept by the RPC cache.
+    rpc_max_headers: Gauge,
+    /// Headers kept by the sealed header cache.
+    header_cache_size: Gauge,
+}
+
+/// Command line arguments of the cache sizing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Caches")]
+pub struct CacheArgs {
+    /// Keep reth's default cache sizes instead of sizing them after the memory of the machine
+    #[arg(long = "cache.no-auto-size")]
+    pub no_auto_size: bool,
+
+    /// Memory to size caches for, in MiB [default: detected memory]
+    #[arg(long = "cache.memory-mb", value_name = "MIB")]
+    pub memory_mb: Option<u64>,
+
+    /// Headers kept by the sealed header cache [default: 1024, scaled with memory]
+    #[arg(
+        long = "cache.headers",
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub headers: Option<u32>,
+}
+
+/// Returns the memory available to the process in MiB: the smaller of the physical memory and
+/// the cgroup limit. Only detected on Linux.
+pub fn detected_memory_mb() -> Option<u64> {
+    let physical = std::fs::read_to_string("/proc/meminfo").ok().and_then(|s| parse_meminfo(&s));
+    let limit = ["/sys/fs/cgroup/memory.max", "/sys/fs/cgroup/memory/memory.limit_in_bytes"]
+        .into_iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .and_then(|s| parse_cgroup_limit(&s));
+    match (physical, limit) {
+        (Some(physical), Some(limit)) => Some(physical.min(limit)),
+        (physical, limit) => physical.or(limit),
+    }
+}
+
+/// Parses `MemTotal` of `/proc/meminfo` into MiB.
+fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let line = meminfo.lines().find_map(|line| line.strip_prefix("MemTotal:"))?;
+    let kib: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kib / 1024)
+}
+
+/// Parses a cgroup memory limit in bytes into MiB. `max` means no limit.
+fn parse_cgroup_limit(limit: &str) -> Option<u64> {
+    let bytes: u64 = limit.trim().parse().ok()?;
+    // cgroup v1 reports the largest page-aligned value when unlimited
+    (bytes < 1 << 60).then_some(bytes / (1024 * 1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_sizes_scale_with_memory() {
+        let defaults = RpcStateCacheArgs::default();
+        let reference = CacheSizes::for_memory(REFERENCE_MEMORY_MB);
+        assert_eq!(reference.cross_block_cache_mb, 4096);
+        assert_eq!(reference.max_blocks, defaults.max_blocks);
+
+        let small = CacheSizes::for_memory(8 * 1024);
+        assert_eq!(small.cross_block_cache_mb, 1024);
+        assert_eq!(small.max_blocks, defaults.max_blocks / 4);
+
+        let large = CacheSizes::for_memory(512 * 1024);
+        assert_eq!(large.cross_block_cache_mb, 16 * 1024);
+        assert_eq!(large.max_headers, defaults.max_headers * 4);
+    }
+
+    #[test]
+    fn test_explicit_sizes_are_kept() {
+        let mut config = CacheConfig { memory_mb: Some(8 * 1024), ..Default::default() };
+        // Explicit sizes are kept even when they equal reth's default
+        let explicit = ExplicitCaches { cross_block_cache: true, ..Default::default() };
+        let mut engine = EngineArgs::default();
+        let mut rpc_cache = RpcStateCacheArgs::default();
+        assert_eq!(config.apply(explicit, &mut engine, &mut rpc_cache), Some(8 * 1024));
+        assert_eq!(engine.cross_block_cache_size, EngineArgs::default().cross_block_cache_size);
+        assert_eq!(rpc_cache.max_blocks, RpcStateCacheArgs::default().max_blocks / 4);
+        assert_eq!(config.header_cache_size, Some(DEFAULT_HEADER_CACHE_SIZE / 4));
+
+        let mut config = CacheConfig { header_cache_size: Some(10), ..config };
+        config.apply(explicit, &mut engine, &mut rpc_cache);
+        assert_eq!(config.header_cache_size, Some(10));
+
+        let mut config = config.with_args(&CacheArgs { no_auto_size: true, ..Default::default() });
+        assert_eq!(config.apply(explicit, &mut engine, &mut RpcStateCacheArgs::default()), None);
+    }
+
+    #[test]
+    fn test_explicit_caches_from_matches() {
+        let matches = crate::cli::BerachainCli::command().get_matches_from([
+            "bera-reth",
+            "node",
+            "--engine.cross-block-cache-size",
+            &EngineArgs::default().cross_block_cache_size.to_string(),
+            "--rpc-cache.max-headers",
+            "10",
+        ]);
+        let (_, node) = matches.subcommand().unwrap();
+        assert_eq!(
+            ExplicitCaches::from_matches(node),
+            ExplicitCaches { cross_block_cache: true, rpc_max_headers: true, ..Default::default() }
+        );
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        let meminfo = "MemTotal:       16314564 kB\nMemFree:         1000000 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(15932));
+        assert_eq!(parse_cgroup_limit("max\n"), None);
+        assert_eq!(parse_cgroup_limit("8589934592\n"), Some(8192));
+        assert_eq!(parse_cgroup_limit("9223372036854771712\n"), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Structured stream of canonical chain changes
+
+use crate::primitives::BerachainPrimitives;
+use alloy_eips::BlockNumHash;
+use futures::StreamExt;
+use reth::{
+    providers::{CanonStateSubscriptions, Chain},
+    tasks::TaskExecutor,
+};
+use reth_metrics::{
+    Metrics,
+    metrics::{Counter, Histogram},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::info;
+
+/// Number of changes buffered for slow subscribers before they start missing changes.
+const CHANGES_CAPACITY: usize = 256;
+
+/// Canonical chain metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.canonical")]
+struct CanonicalMetrics {
+    /// Canonical chain changes that reverted blocks.
+    reorgs: Counter,
+    /// Number of blocks reverted by each reorg.
+    reorg_depth: Histogram,
+    /// Blocks reverted by reorgs.
+    reverted_blocks: Counter,
+}
+
+/// A change of the canonical chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanonicalChange {
+    /// Head of the canonical chain after the change.
+    pub new_head: BlockNumHash,
+    /// Last block kept from the previous canonical chain.
+    pub common_ancestor: BlockNumHash,
+    /// Blocks removed from the canonical chain, in ascending order.
+    pub reverted: Vec<BlockNumHash>,
+    /// Blocks added to the canonical chain, in ascending order.
+    pub committed: Vec<BlockNumHash>,
+    /// Number of reverted blocks, zero if the chain was only extended.
+    pub reorg_depth: u64,
+}
+
+impl CanonicalChange {
+    /// Creates the change from `common_ancestor` to the tip of `committed`.
+    pub fn new(
+        common_ancestor: BlockNumHash,
+        reverted: Vec<BlockNumHash>,
+        committed: Vec<BlockNumHash>,
+    ) -> Self {
+        Self {
+            new_head: committed.last().copied().unwrap_or(common_ancestor),
+            common_ancestor,
+            reorg_depth: reverted.len() as u64,
+            reverted,
+            committed,
+        }
+    }
+
+    /// Creates the change of a canonical state or ExEx notification, if any chain is set.
+    pub fn from_chains(
+        reverted: Option<&Chain<BerachainPrimitives>>,
+        committed: Option<&Chain<BerachainPrimitives>>,
+    ) -> Option<Self> {
+        let blocks = |chain: Option<&Chain<BerachainPrimitives>>| {
+            chain.map_or_else(Vec::new, |chain| {
+                chain.blocks_iter().map(|block| block.num_hash()).collect()
+            })
+        };
+        let common_ancestor = committed.or(reverted)?.fork_block();
+        Some(Self::new(common_ancestor, blocks(reverted), blocks(committed)))
+    }
+
+    /// Returns whether blocks were reverted.
+    pub const fn is_reorg(&self) -> bool {
+        self.reorg_depth > 0
+    }
+}
+
+/// Broadcast of the canonical chain changes of a node, fed once [`install_canonical_changes`] ran.
+#[derive(Debug)]
+pub struct CanonicalChanges {
+    sender: broadcast::Sender<CanonicalChange>,
+}
+
+impl Default for CanonicalChanges {
+    fn default() -> Self {
+        Self { sender: broadcast::channel(CHANGES_CAPACITY).0 }
+    }
+}
+
+impl CanonicalChanges {
+    /// Returns a receiver of all changes from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<CanonicalChange> {
+        self.sender.subscribe()
+    }
+}
+
+/// Starts broadcasting the canonical chain changes of `provider` through `changes`.
+pub fn install_canonical_changes<P>(
+    changes: Arc<CanonicalChanges>,
+    provider: P,
+    executor: &TaskExecutor,
+) where
+    P: CanonStateSubscriptions<Primitives = BerachainPrimitives>,
+{
+    let mut notifications = provider.canonical_state_stream();
+    let metrics = CanonicalMetrics::default();
+    executor.spawn(async move {
+        while let Some(notification) = notifications.next().await {
+            let reverted = notification.reverted();
+            let committed = notification.committed();
+            let Some(change) = CanonicalChange::from_chains(reverted.as_deref(), Some(&*committed))
+            else {
+                continue
+            };
+            if change.is_reorg() {
+                metrics.reorgs.increment(1);
+                metrics.reorg_depth.record(change.reorg_depth as f64);
+                metrics.reverted_blocks.increment(change.reorg_depth);
+                info!(
+                    target: "reth::canonical",
+                    depth = change.reorg_depth,
+                    common_ancestor = change.common_ancestor.number,
+                    new_head = ?change.new_head,
+                    "Canonical chain reorged"
+                );
+            }
+            // Sending only fails without subscribers
+            let _ = changes.sender.send(change);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+
+    fn block(number: u64, byte: u8) -> BlockNumHash {
+        BlockNumHash::new(number, B256::repeat_byte(byte))
+    }
+
+    #[test]
+    fn test_extension_is_not_a_reorg() {
+        let change = CanonicalChange::new(block(9, 9), vec![], vec![block(10, 1), block(11, 1)]);
+        assert_eq!(change.new_head, block(11, 1));
+        assert!(!change.is_reorg());
+    }
+
+    #[test]
+    fn test_reorg_depth_counts_reverted_blocks() {
+        let change = CanonicalChange::new(
+            block(9, 9),
+            vec![block(10, 1), block(11, 1)],
+            vec![block(10, 2), block(11, 2), block(12, 2)],
+        );
+        assert_eq!(change.reorg_depth, 2);
+        assert_eq!(change.new_head, block(12, 2));
+
+        // A revert without replacement leaves the common ancestor as head
+        let change = CanonicalChange::new(block(9, 9), vec![block(10, 1)], vec![]);
+        assert_eq!(change.new_head, block(9, 9));
+        assert!(change.is_reorg());
+    }
+
+    #[test]
+    fn test_change_serializes_for_rpc() {
+        let change = CanonicalChange::new(block(9, 9), vec![block(10, 1)], vec![block(10, 2)]);
+        let json = serde_json::to_value(&change).unwrap();
+        assert_eq!(json["reorgDepth"], 1);
+        assert_eq!(json["newHead"]["hash"], json["committed"][0]["hash"]);
+        assert_eq!(serde_json::from_value::<CanonicalChange>(json).unwrap(), change);
+    }
+}
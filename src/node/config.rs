@@ -0,0 +1,167 @@
+//! `[berachain]` section of the node configuration file
+
+use crate::{
+    engine::builder::BlockBuilderConfig,
+    network::{
+        discovery::DiscoveryConfig, serve_limits::ServeLimitsConfig,
+        static_peers::StaticPeersConfig, transactions::TxIngressConfig,
+    },
+    node::{
+        args::BerachainArgs, caches::CacheConfig, datadir::DatadirConfig,
+        debug_bundle::DebugBundleConfig, health::HealthConfig, runtime::RuntimeConfig,
+        standby::StandbyConfig, state_root_sampling::StateRootSamplingConfig,
+        supervisor::SupervisorConfig, sync::SyncConfig,
+    },
+    pool::propagation::TxPropagationConfig,
+    rpc::{auth::EngineAuthConfig, logs::GetLogsConfig, response_cache::ResponseCacheConfig},
+    storage::compression::CompressionConfig,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Berachain-specific node settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BerachainConfig {
+    /// Payload builder settings.
+    pub builder: BlockBuilderConfig,
+    /// Cache sizing after the memory of the machine.
+    pub caches: CacheConfig,
+    /// Per-table compression settings.
+    pub compression: CompressionConfig,
+    /// Placement of datadir parts on other volumes.
+    pub datadir: DatadirConfig,
+    /// Debug bundle capture.
+    pub debug_bundle: DebugBundleConfig,
+    /// DNS discovery trees.
+    pub discovery: DiscoveryConfig,
+    /// Engine API endpoint with JWT secret rotation.
+    pub engine_auth: EngineAuthConfig,
+    /// Parallel `eth_getLogs`.
+    pub get_logs: GetLogsConfig,
+    /// Health and readiness endpoints.
+    pub health: HealthConfig,
+    /// Static peers kept connected.
+    pub p2p: StaticPeersConfig,
+    /// Cache of RPC responses about finalized blocks.
+    pub response_cache: ResponseCacheConfig,
+    /// Thread pool sizing.
+    pub runtime: RuntimeConfig,
+    /// Limits on history served to peers.
+    pub serve_limits: ServeLimitsConfig,
+    /// Hot standby following a primary node.
+    pub standby: StandbyConfig,
+    /// Sampled re-verification of state roots.
+    pub state_root_sampling: StateRootSamplingConfig,
+    /// PID file and systemd readiness notification.
+    pub supervisor: SupervisorConfig,
+    /// Checkpoint sync from a trusted block hash.
+    pub sync: SyncConfig,
+    /// Limits on transactions received from peers.
+    pub tx_ingress: TxIngressConfig,
+    /// Transaction propagation policies by origin.
+    pub txpool: TxPropagationConfig,
+    /// Write the startup configuration report to the datadir.
+    pub write_startup_report: bool,
+}
+
+/// The parts of the configuration file read by bera-reth.
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    berachain: BerachainConfig,
+}
+
+impl BerachainConfig {
+    /// Parses the `[berachain]` section of a configuration file's contents.
+    pub fn from_toml(contents: &str) -> eyre::Result<Self> {
+        let file: ConfigFile = toml::from_str(contents)
+            .map_err(|err| eyre::eyre!("invalid [berachain] configuration: {err}"))?;
+        Ok(file.berachain)
+    }
+
+    /// Loads the `[berachain]` section from `path`, or the defaults if the file does not exist.
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                Self::from_toml(&contents).map_err(|err| eyre::eyre!("{}: {err}", path.display()))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(eyre::eyre!("failed to read {}: {err}", path.display())),
+        }
+    }
+
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(self, args: &BerachainArgs) -> Self {
+        Self {
+            builder: self.builder.with_args(&args.builder),
+            caches: self.caches.with_args(&args.caches),
+            compression: self.compression.with_args(&args.compression),
+            datadir: self.datadir.with_args(&args.datadir),
+            debug_bundle: self.debug_bundle.with_args(&args.debug_bundle),
+            discovery: self.discovery.with_args(&args.discovery),
+            engine_auth: self.engine_auth.with_args(&args.engine_auth),
+            get_logs: self.get_logs.with_args(&args.get_logs),
+            health: self.health.with_args(&args.health),
+            p2p: self.p2p.with_args(&args.p2p),
+            response_cache: self.response_cache.with_args(&args.response_cache),
+            runtime: self.runtime.with_args(&args.runtime),
+            serve_limits: self.serve_limits.with_args(&args.serve_limits),
+            standby: self.standby.with_args(&args.standby),
+            state_root_sampling: self.state_root_sampling.with_args(&args.state_root_sampling),
+            supervisor: self.supervisor.with_args(&args.supervisor),
+            sync: self.sync.with_args(&args.sync),
+            tx_ingress: self.tx_ingress.with_args(&args.tx_ingress),
+            txpool: self.txpool.with_args(&args.txpool),
+            write_startup_report: self.write_startup_report || args.write_startup_report,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::compression::{CompressionArgs, TableCompression};
+
+    #[test]
+    fn test_berachain_section_next_to_reth_sections() {
+        let config = BerachainConfig::from_toml(
+            r#"
+            [stages.headers]
+            downloader_max_concurrent_requests = 100
+
+            [berachain.compression]
+            blob-sidecars = "none"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.compression.blob_sidecars, TableCompression::None);
+
+        assert_eq!(BerachainConfig::from_toml("").unwrap(), BerachainConfig::default());
+    }
+
+    #[test]
+    fn test_invalid_berachain_section_is_rejected() {
+        assert!(BerachainConfig::from_toml("[berachain]\nunknown = 1").is_err());
+        assert!(
+            BerachainConfig::from_toml("[berachain.compression]\nblob-sidecars = \"zstd:99\"")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_overrides_file() {
+        let file = BerachainConfig::from_toml("[berachain.compression]\nblob-sidecars = \"none\"")
+            .unwrap();
+
+        let config = file.clone().with_args(&BerachainArgs::default());
+        assert_eq!(config.compression.blob_sidecars, TableCompression::None);
+
+        let args = BerachainArgs {
+            compression: CompressionArgs { blob_sidecars: Some(TableCompression::Zstd(7)) },
+            ..Default::default()
+        };
+        let config = file.with_args(&args);
+        assert_eq!(config.compression.blob_sidecars, TableCompression::Zstd(7));
+    }
+}
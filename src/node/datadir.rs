@@ -0,0 +1,69 @@
+//! Placement of datadir parts on separate volumes
+
+use clap::Args;
+use reth_node_core::args::DatadirArgs;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Locations of datadir parts that may live outside the datadir.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DatadirConfig {
+    /// Static files directory, used unless `--datadir.static-files` is given.
+    pub static_files: Option<PathBuf>,
+    /// Transaction pool blob store directory. Its contents are deleted on startup, so it must be
+    /// dedicated to the blob store.
+    pub blobstore: Option<PathBuf>,
+}
+
+impl DatadirConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &DatadirLayoutArgs) -> Self {
+        if let Some(blobstore) = &args.blobstore {
+            self.blobstore = Some(blobstore.clone());
+        }
+        self
+    }
+
+    /// Applies the static files location to reth's datadir arguments, unless they set one.
+    pub fn apply(&self, datadir: &mut DatadirArgs) {
+        if datadir.static_files_path.is_none() {
+            datadir.static_files_path = self.static_files.clone();
+        }
+    }
+}
+
+/// Command line arguments placing datadir parts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Datadir layout")]
+pub struct DatadirLayoutArgs {
+    /// Directory of the transaction pool blob store. Deleted on startup, so it must be dedicated
+    /// to the blob store [default: <DATADIR>/blobstore]
+    #[arg(long = "datadir.blobstore", value_name = "PATH")]
+    pub blobstore: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_static_files_flag_takes_precedence() {
+        let config = DatadirConfig {
+            static_files: Some("/hdd/static_files".into()),
+            blobstore: Some("/nvme/blobstore".into()),
+        };
+
+        let mut datadir = DatadirArgs::default();
+        config.apply(&mut datadir);
+        assert_eq!(datadir.static_files_path, Some("/hdd/static_files".into()));
+
+        let mut datadir =
+            DatadirArgs { static_files_path: Some("/flag".into()), ..Default::default() };
+        config.apply(&mut datadir);
+        assert_eq!(datadir.static_files_path, Some("/flag".into()));
+
+        let args = DatadirLayoutArgs { blobstore: Some("/flag/blobstore".into()) };
+        assert_eq!(config.with_args(&args).blobstore, Some("/flag/blobstore".into()));
+    }
+}
@@ -0,0 +1,430 @@
+//! Debug bundles for support escalation
+
+use crate::{
+    node::allocator::{self, AllocatorStats},
+    primitives::BerachainPrimitives,
+};
+use alloy_consensus::BlockHeader;
+use alloy_primitives::{B256, U256};
+use clap::Args;
+use futures::{Stream, StreamExt};
+use reth::{
+    api::BuiltPayload,
+    payload::{Events, PayloadBuilderHandle},
+    tasks::TaskExecutor,
+    transaction_pool::TransactionPool,
+};
+use reth_engine_primitives::ConsensusEngineEvent;
+use reth_payload_primitives::{PayloadBuilderAttributes, PayloadTypes};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::info;
+
+/// Default duration of the CPU profile, in seconds.
+pub const DEFAULT_PROFILE_SECONDS: u64 = 10;
+
+/// Number of engine and payload events kept for the next bundle.
+const RECENT_EVENTS: usize = 256;
+
+/// Directory of the bundles in the datadir.
+pub const DEBUG_BUNDLES_DIR: &str = "debug-bundles";
+
+/// Settings of debug bundle capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DebugBundleConfig {
+    /// Duration of the CPU profile of bundles captured on `SIGUSR1`, in seconds.
+    pub profile_seconds: u64,
+}
+
+impl Default for DebugBundleConfig {
+    fn default() -> Self {
+        Self { profile_seconds: DEFAULT_PROFILE_SECONDS }
+    }
+}
+
+impl DebugBundleConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &DebugBundleArgs) -> Self {
+        if let Some(profile_seconds) = args.profile_seconds {
+            self.profile_seconds = profile_seconds;
+        }
+        self
+    }
+}
+
+/// Command line arguments of debug bundle capture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Debug bundles")]
+pub struct DebugBundleArgs {
+    /// CPU profile duration of bundles captured on SIGUSR1, in seconds [default: 10]
+    #[arg(long = "debug-bundle.profile-seconds", value_name = "SECONDS")]
+    pub profile_seconds: Option<u64>,
+}
+
+/// A recorded engine or payload builder event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase", tag = "kind")]
+pub enum RecordedEvent {
+    /// Forkchoice update processed by the engine.
+    ForkchoiceUpdated {
+        /// Milliseconds since the Unix epoch.
+        at: u64,
+        /// Head block hash.
+        head: B256,
+        /// Safe block hash.
+        safe: B256,
+        /// Finalized block hash.
+        finalized: B256,
+        /// Outcome of the update.
+        status: String,
+    },
+    /// Payload received from the consensus client.
+    BlockReceived {
+        /// Milliseconds since the Unix epoch.
+        at: u64,
+        /// Block number.
+        number: u64,
+        /// Block hash.
+        hash: B256,
+    },
+    /// Canonical chain advanced.
+    CanonicalChainCommitted {
+        /// Milliseconds since the Unix epoch.
+        at: u64,
+        /// New head number.
+        number: u64,
+        /// New head hash.
+        hash: B256,
+        /// Time spent, in milliseconds.
+        elapsed_ms: u64,
+    },
+    /// Payload rejected as invalid.
+    InvalidBlock {
+        /// Milliseconds since the Unix epoch.
+        at: u64,
+        /// Block number.
+        number: u64,
+        /// Block hash.
+        hash: B256,
+    },
+    /// Payload job started.
+    PayloadJobStarted {
+        /// Milliseconds since the Unix epoch.
+        at: u64,
+        /// Payload id.
+        id: String,
+        /// Parent block hash.
+        parent: B256,
+        /// Timestamp of the payload.
+        timestamp: u64,
+    },
+    /// Payload built by a job.
+    PayloadBuilt {
+        /// Milliseconds since the Unix epoch.
+        at: u64,
+        /// Block number.
+        number: u64,
+        /// Block hash.
+        hash: B256,
+        /// Fees of the payload.
+        fees: U256,
+    },
+}
+
+/// Transaction pool sub-pool sizes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PoolSummary {
+    /// Executable transactions.
+    pub pending: usize,
+    /// Transactions waiting for a lower base fee.
+    pub basefee: usize,
+    /// Transactions with nonce gaps or insufficient balance.
+    pub queued: usize,
+    /// Blob transactions waiting for a lower blob fee.
+    pub blob: usize,
+    /// All transactions.
+    pub total: usize,
+}
+
+/// State collected into a bundle.
+#[derive(Debug, Default)]
+struct Recorder {
+    events: VecDeque<RecordedEvent>,
+    forkchoice: Option<RecordedEvent>,
+    payload_jobs_started: u64,
+    payloads_built: u64,
+}
+
+impl Recorder {
+    fn record(&mut self, event: RecordedEvent) {
+        if matches!(event, RecordedEvent::ForkchoiceUpdated { .. }) {
+            self.forkchoice = Some(event.clone());
+        }
+        if self.events.len() == RECENT_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+}
+
+/// Contents of `bundle.json`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Bundle<'a> {
+    client_version: &'static str,
+    captured_at: u64,
+    forkchoice: Option<&'a RecordedEvent>,
+    payload_jobs_started: u64,
+    payloads_built: u64,
+    pool: PoolSummary,
+    allocator: Option<AllocatorStats>,
+    events: &'a VecDeque<RecordedEvent>,
+}
+
+/// Debug bundle capture of a running node.
+pub struct DebugBundles {
+    dir: PathBuf,
+    config: DebugBundleConfig,
+    recorder: Mutex<Recorder>,
+    pool: Box<dyn Fn() -> PoolSummary + Send + Sync>,
+    capturing: AtomicBool,
+}
+
+impl std::fmt::Debug for DebugBundles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebugBundles").field("dir", &self.dir).finish_non_exhaustive()
+    }
+}
+
+impl DebugBundles {
+    fn record(&self, event: RecordedEvent) {
+        self.recorder.lock().expect("not poisoned").record(event);
+    }
+
+    /// Writes a bundle with a CPU profile of `profile_seconds` and returns its directory.
+    pub async fn capture(&self, profile_seconds: Option<u64>) -> eyre::Result<PathBuf> {
+        eyre::ensure!(
+            !self.capturing.swap(true, Ordering::AcqRel),
+            "a debug bundle is already being captured"
+        );
+        let result = self.write(profile_seconds.unwrap_or(self.config.profile_seconds)).await;
+        self.capturing.store(false, Ordering::Release);
+        result
+    }
+
+    async fn write(&self, profile_seconds: u64) -> eyre::Result<PathBuf> {
+        let captured_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let dir = self.dir.join(captured_at.to_string());
+        std::fs::create_dir_all(&dir)?;
+
+        let bundle = {
+            let recorder = self.recorder.lock().expect("not poisoned");
+            serde_json::to_vec_pretty(&Bundle {
+                client_version: env!("CARGO_PKG_VERSION"),
+                captured_at,
+                forkchoice: recorder.forkchoice.as_ref(),
+                payload_jobs_started: recorder.payload_jobs_started,
+                payloads_built: recorder.payloads_built,
+                pool: (self.pool)(),
+                allocator: allocator::stats().ok(),
+                events: &recorder.events,
+            })?
+        };
+        std::fs::write(dir.join("bundle.json"), bundle)?;
+        if allocator::heap_profiling() == Some(true) {
+            allocator::dump_heap_profile(&dir.join("heap.prof"))?;
+        }
+
+        if profile_seconds > 0 {
+            let path = dir.join("cpu.svg");
+            tokio::task::spawn_blocking(move || profile(path, profile_seconds)).await??;
+        }
+
+        info!(target: "reth::cli", dir = %dir.display(), "Captured debug bundle");
+        Ok(dir)
+    }
+}
+
+/// Samples the process for `seconds` and writes a flamegraph to `path`.
+#[cfg(unix)]
+fn profile(path: PathBuf, seconds: u64) -> eyre::Result<()> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(99)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()?;
+    std::thread::sleep(std::time::Duration::from_secs(seconds));
+    guard.report().build()?.flamegraph(std::fs::File::create(path)?)?;
+    Ok(())
+}
+
+/// CPU profiles are only supported on Unix.
+#[cfg(not(unix))]
+fn profile(_path: PathBuf, _seconds: u64) -> eyre::Result<()> {
+    Ok(())
+}
+
+/// Milliseconds since the Unix epoch.
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_millis() as u64)
+}
+
+/// Starts recording events for debug bundles and captures one on every `SIGUSR1`. Returns the
+/// capture for `admin_captureDebugBundle`.
+pub async fn install_debug_bundles<Pool, Ev, T>(
+    config: DebugBundleConfig,
+    data_dir: PathBuf,
+    pool: Pool,
+    engine_events: Ev,
+    payload_builder: &PayloadBuilderHandle<T>,
+    executor: &TaskExecutor,
+) -> eyre::Result<Arc<DebugBundles>>
+where
+    Pool: TransactionPool + 'static,
+    Ev: Stream<Item = ConsensusEngineEvent<BerachainPrimitives>> + Send + 'static,
+    T: PayloadTypes,
+{
+    let bundles = Arc::new(DebugBundles {
+        dir: data_dir.join(DEBUG_BUNDLES_DIR),
+        config,
+        recorder: Default::default(),
+        pool: Box::new(move || {
+            let size = pool.pool_size();
+            PoolSummary {
+                pending: size.pending,
+                basefee: size.basefee,
+                queued: size.queued,
+                blob: size.blob,
+                total: size.total,
+            }
+        }),
+        capturing: AtomicBool::new(false),
+    });
+
+    let recording = bundles.clone();
+    executor.spawn(async move {
+        let mut engine_events = std::pin::pin!(engine_events);
+        while let Some(event) = engine_events.next().await {
+            let at = now_ms();
+            let event = match event {
+                ConsensusEngineEvent::ForkchoiceUpdated(state, status) => {
+                    RecordedEvent::ForkchoiceUpdated {
+                        at,
+                        head: state.head_block_hash,
+                        safe: state.safe_block_hash,
+                        finalized: state.finalized_block_hash,
+                        status: format!("{status:?}"),
+                    }
+                }
+                ConsensusEngineEvent::BlockReceived(block) => {
+                    RecordedEvent::BlockReceived { at, number: block.number, hash: block.hash }
+                }
+                ConsensusEngineEvent::CanonicalChainCommitted(head, elapsed) => {
+                    RecordedEvent::CanonicalChainCommitted {
+                        at,
+                        number: head.number(),
+                        hash: head.hash(),
+                        elapsed_ms: elapsed.as_millis() as u64,
+                    }
+                }
+                ConsensusEngineEvent::InvalidBlock(block) => RecordedEvent::InvalidBlock {
+                    at,
+                    number: block.header().number(),
+                    hash: block.hash(),
+                },
+                _ => continue,
+            };
+            recording.record(event);
+        }
+    });
+
+    let payload_events = payload_builder.subscribe().await?.into_stream();
+    let recording = bundles.clone();
+    executor.spawn(async move {
+        let mut payload_events = std::pin::pin!(payload_events);
+        while let Some(event) = payload_events.next().await {
+            let at = now_ms();
+            let event = match event {
+                Ok(Events::Attributes(attributes)) => {
+                    recording.recorder.lock().expect("not poisoned").payload_jobs_started += 1;
+                    RecordedEvent::PayloadJobStarted {
+                        at,
+                        id: attributes.payload_id().to_string(),
+                        parent: attributes.parent(),
+                        timestamp: attributes.timestamp(),
+                    }
+                }
+                Ok(Events::BuiltPayload(payload)) => {
+                    recording.recorder.lock().expect("not poisoned").payloads_built += 1;
+                    RecordedEvent::PayloadBuilt {
+                        at,
+                        number: payload.block().header().number(),
+                        hash: payload.block().hash(),
+                        fees: payload.fees(),
+                    }
+                }
+                Err(_) => continue,
+            };
+            recording.record(event);
+        }
+    });
+
+    #[cfg(unix)]
+    let capturing = bundles.clone();
+    #[cfg(unix)]
+    executor.spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+        use tracing::warn;
+
+        let mut signals = match signal(SignalKind::user_defined1()) {
+            Ok(signals) => signals,
+            Err(err) => {
+                warn!(target: "reth::cli", %err, "Failed to listen for SIGUSR1");
+                return
+            }
+        };
+        while signals.recv().await.is_some() {
+            if let Err(err) = capturing.capture(None).await {
+                warn!(target: "reth::cli", %err, "Failed to capture debug bundle");
+            }
+        }
+    });
+
+    Ok(bundles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder_keeps_recent_events_and_forkchoice() {
+        let mut recorder = Recorder::default();
+        let forkchoice = RecordedEvent::ForkchoiceUpdated {
+            at: 0,
+            head: B256::repeat_byte(1),
+            safe: B256::ZERO,
+            finalized: B256::ZERO,
+            status: "Valid".to_string(),
+        };
+        recorder.record(forkchoice.clone());
+        for number in 0..RECENT_EVENTS as u64 {
+            recorder.record(RecordedEvent::BlockReceived { at: 0, number, hash: B256::ZERO });
+        }
+
+        assert_eq!(recorder.events.len(), RECENT_EVENTS);
+        assert!(matches!(recorder.events[0], RecordedEvent::BlockReceived { number: 0, .. }));
+        assert_eq!(recorder.forkchoice, Some(forkchoice));
+
+        let json = serde_json::to_value(&recorder.events[0]).unwrap();
+        assert_eq!(json["kind"], "blockReceived");
+    }
+}
@@ -74,6 +74,7 @@ where
                 self.chain_spec.clone(),
                 prev_proposer_pubkey,
                 evm_env.block_env.number,
+                timestamp,
                 base_fee,
             )?;
 
@@ -1,5 +1,7 @@
 use crate::{
     chainspec::BerachainChainSpec,
+    engine::latency::PayloadLatency,
+    hardforks::BerachainHardforks,
     node::evm::{
         assembler::BerachainBlockAssembler, block_context::BerachainBlockExecutionCtx,
         receipt::BerachainReceiptBuilder,
@@ -21,7 +23,8 @@ use reth_chainspec::EthChainSpec;
 use reth_evm::{ConfigureEvm, EthEvmFactory, EvmEnv, EvmEnvFor, ExecutionCtxFor};
 use reth_evm_ethereum::{revm_spec, revm_spec_by_timestamp_and_block_number};
 use reth_primitives_traits::{BlockTy, HeaderTy, SealedBlock, SealedHeader};
-use reth_rpc_eth_api::helpers::pending_block::BuildPendingEnv;
+use reth_rpc_eth_api::helpers::pending_block::{BuildPendingEnv, PendingEnvBuilder};
+use reth_rpc_eth_types::EthApiError;
 use std::{borrow::Cow, convert::Infallible, fmt::Debug, sync::Arc};
 
 #[derive(Debug, Clone)]
@@ -35,6 +38,8 @@ pub struct BerachainEvmConfig {
 
     /// Ethereum block assembler.
     pub block_assembler: BerachainBlockAssembler,
+    /// Payload latency breakdown the execution time of blocks is recorded in.
+    pub latency: Option<Arc<PayloadLatency>>,
 }
 
 impl BerachainEvmConfig {
@@ -48,9 +53,16 @@ impl BerachainEvmConfig {
             spec: chain_spec.clone(),
             block_assembler: BerachainBlockAssembler::new(chain_spec.clone()),
             evm_factory,
+            latency: None,
         }
     }
 
+    /// Records the execution time of blocks in `latency`.
+    pub fn with_latency(mut self, latency: Arc<PayloadLatency>) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
     /// Sets the extra data for the block assembler.
     pub fn with_extra_data(mut self, extra_data: Bytes) -> Self {
         self.block_assembler.extra_data = extra_data;
@@ -235,3 +247,90 @@ impl BuildPendingEnv<BerachainHeader> for BerachainNextBlockEnvAttributes {
         }
     }
 }
+
+impl BerachainNextBlockEnvAttributes {
+    /// Returns the attributes of the pending block built on `parent`.
+    ///
+    /// From Prague1 on the executor runs the PoL transaction first and the assembler prepends it,
+    /// as in canonical blocks. The proposer of `parent` is only known to the consensus client, so
+    /// the pending distribution is made for the last proposer recorded on chain, or for the zero
+    /// key when `parent` predates Prague1.
+    pub fn for_pending_block(
+        chain_spec: &BerachainChainSpec,
+        parent: &SealedHeader<BerachainHeader>,
+    ) -> Self {
+        let mut attributes = Self::build_pending_env(parent);
+        attributes.prev_proposer_pubkey = chain_spec
+            .is_prague1_active_at_timestamp(attributes.timestamp)
+            .then(|| parent.prev_proposer_pubkey.unwrap_or(BlsPublicKey::ZERO));
+        attributes
+    }
+}
+
+/// Builds the attributes of the pending block with
+/// [`BerachainNextBlockEnvAttributes::for_pending_block`].
+#[derive(Debug, Clone)]
+pub struct BerachainPendingEnvBuilder {
+    chain_spec: Arc<BerachainChainSpec>,
+}
+
+impl BerachainPendingEnvBuilder {
+    /// Creates a builder for the pending blocks of `chain_spec`.
+    pub const fn new(chain_spec: Arc<BerachainChainSpec>) -> Self {
+        Self { chain_spec }
+    }
+}
+
+impl<Evm> PendingEnvBuilder<Evm> for BerachainPendingEnvBuilder
+where
+    Evm: ConfigureEvm<
+            Primitives = BerachainPrimitives,
+            NextBlockEnvCtx = BerachainNextBlockEnvAttributes,
+        >,
+{
+    fn pending_env_attributes(
+        &self,
+        parent: &SealedHeader<BerachainHeader>,
+    ) -> Result<BerachainNextBlockEnvAttributes, EthApiError> {
+        Ok(BerachainNextBlockEnvAttributes::for_pending_block(&self.chain_spec, parent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_block_runs_pol_from_prague1() {
+        let mut genesis = crate::chainspec::BERACHAIN_DEV.genesis().clone();
+        genesis.config.extra_fields.insert(
+            "berachain".to_string(),
+            serde_json::json!({
+                "prague1": {
+                    "time": 100,
+                    "baseFeeChangeDenominator": 48,
+                    "minimumBaseFeeWei": 1000000000,
+                    "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+                }
+            }),
+        );
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
+        let pubkey = BlsPublicKey::repeat_byte(7);
+        let parent = |timestamp, prev_proposer_pubkey| {
+            SealedHeader::seal_slow(BerachainHeader {
+                timestamp,
+                prev_proposer_pubkey,
+                ..Default::default()
+            })
+        };
+        let pending = |parent| {
+            BerachainNextBlockEnvAttributes::for_pending_block(&chain_spec, &parent)
+                .prev_proposer_pubkey
+        };
+
+        assert_eq!(pending(parent(50, None)), None);
+        // The first Prague1 block has no proposer recorded before it
+        assert_eq!(pending(parent(99, None)), Some(BlsPublicKey::ZERO));
+        assert_eq!(pending(parent(100, Some(pubkey))), Some(pubkey));
+    }
+}
@@ -1,7 +1,13 @@
 use alloy_primitives::B256;
+use jsonrpsee_types::ErrorObjectOwned;
 use reth_evm::block::BlockExecutionError;
+use reth_payload_primitives::NewPayloadError;
 
 /// Berachain-specific execution errors.
+///
+/// Every variant has its own code, returned by [`BerachainExecutionError::code`] and reported in
+/// the `data` of Engine API errors, so the consensus client can tell failures apart without parsing
+/// messages. The codes start at -38100, clear of the -38001 to -38005 range of the Engine API.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum BerachainExecutionError {
     /// Previous proposer public key is required for Prague1 hardfork.
@@ -28,6 +34,9 @@ pub enum BerachainExecutionError {
     /// Missing POL transaction at index 0 in Prague1 block
     #[error("First transaction in Prague1 block must be a POL transaction")]
     MissingPolTransactionAtIndex0,
+    /// Hash of the block built from the payload differs from the hash of the payload.
+    #[error("Block hash mismatch: got {received_hash}, expected {expected_hash}")]
+    BlockHashMismatch { received_hash: B256, expected_hash: B256 },
 }
 
 impl BerachainExecutionError {
@@ -35,6 +44,29 @@ impl BerachainExecutionError {
     pub fn into_block_execution_error(self) -> BlockExecutionError {
         BlockExecutionError::other(self)
     }
+
+    /// Returns the JSON-RPC error code of the error.
+    pub const fn code(&self) -> i32 {
+        match self {
+            Self::MissingProposerPubkey => -38100,
+            Self::ProposerPubkeyNotAllowed => -38101,
+            Self::InvalidPolTransactionType => -38110,
+            Self::PolTransactionBeforePragueOne => -38111,
+            Self::MissingPolReceipts => -38112,
+            Self::PolTransactionHashMismatch { .. } => -38113,
+            Self::PolTransactionInvalidIndex { .. } => -38114,
+            Self::MissingPolTransactionAtIndex0 => -38115,
+            Self::BlockHashMismatch { .. } => -38120,
+        }
+    }
+
+    /// Returns the JSON-RPC error with the Engine API `code`, carrying the code and message of the
+    /// error in its data.
+    pub fn into_rpc_error(self, code: i32) -> ErrorObjectOwned {
+        let reason = self.to_string();
+        let data = BerachainErrorData { code: self.code(), reason: reason.clone() };
+        ErrorObjectOwned::owned(code, reason, Some(data))
+    }
 }
 
 impl From<BerachainExecutionError> for BlockExecutionError {
@@ -42,3 +74,58 @@ impl From<BerachainExecutionError> for BlockExecutionError {
         err.into_block_execution_error()
     }
 }
+
+impl From<BerachainExecutionError> for NewPayloadError {
+    fn from(err: BerachainExecutionError) -> Self {
+        Self::Other(Box::new(err))
+    }
+}
+
+/// `data` of the Engine API errors caused by a [`BerachainExecutionError`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BerachainErrorData {
+    /// Code of the error, as returned by [`BerachainExecutionError::code`].
+    pub code: i32,
+    /// Message of the error.
+    pub reason: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_are_distinct() {
+        let errors = [
+            BerachainExecutionError::MissingProposerPubkey,
+            BerachainExecutionError::ProposerPubkeyNotAllowed,
+            BerachainExecutionError::InvalidPolTransactionType,
+            BerachainExecutionError::PolTransactionBeforePragueOne,
+            BerachainExecutionError::MissingPolReceipts,
+            BerachainExecutionError::PolTransactionHashMismatch {
+                received_hash: B256::ZERO,
+                expected_hash: B256::ZERO,
+            },
+            BerachainExecutionError::PolTransactionInvalidIndex {
+                expected_index: 0,
+                actual_index: 1,
+            },
+            BerachainExecutionError::MissingPolTransactionAtIndex0,
+            BerachainExecutionError::BlockHashMismatch {
+                received_hash: B256::ZERO,
+                expected_hash: B256::ZERO,
+            },
+        ];
+        let mut codes: Vec<_> = errors.iter().map(BerachainExecutionError::code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+
+        let object = BerachainExecutionError::MissingProposerPubkey.into_rpc_error(-38003);
+        let reason = "Previous proposer public key is required for Prague1 hardfork";
+        assert_eq!(object.code(), -38003);
+        assert_eq!(object.message(), reason);
+        let data: BerachainErrorData = serde_json::from_str(object.data().unwrap().get()).unwrap();
+        assert_eq!(data, BerachainErrorData { code: -38100, reason: reason.to_string() });
+    }
+}
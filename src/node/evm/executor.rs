@@ -1,37 +1,45 @@
 use crate::{
     chainspec::BerachainChainSpec,
-    engine::validate_proposer_pubkey_prague1,
+    engine::{latency::PayloadLatency, validate_proposer_pubkey_prague1},
     hardforks::BerachainHardforks,
     node::evm::{
-        block_context::BerachainBlockExecutionCtx, config::BerachainEvmConfig,
-        error::BerachainExecutionError, receipt::BerachainReceiptBuilder,
+        block_context::BerachainBlockExecutionCtx,
+        config::BerachainEvmConfig,
+        error::BerachainExecutionError,
+        pol_metrics::{self, PolOutcome},
+        receipt::BerachainReceiptBuilder,
     },
     transaction::{BerachainTxEnvelope, BerachainTxType, pol::create_pol_transaction},
 };
 use alloy_consensus::Transaction;
 use alloy_eips::{Encodable2718, eip7685::Requests};
+use alloy_primitives::{Address, Bytes, TxKind, U256};
 use reth::{
     chainspec::{EthereumHardfork, EthereumHardforks},
     providers::BlockExecutionResult,
     revm::{
         DatabaseCommit, Inspector, State,
-        context::result::{ExecutionResult, ResultAndState},
+        context::{
+            TxEnv,
+            result::{ExecutionResult, ResultAndState},
+        },
+        handler::SYSTEM_ADDRESS,
     },
 };
 use reth_evm::{
-    Database, EthEvmFactory, Evm, EvmFactory, FromRecoveredTx, FromTxWithEncoded, OnStateHook,
+    Database, EthEvmFactory, Evm, EvmFactory, OnStateHook,
     block::{
         BlockExecutionError, BlockExecutor, BlockExecutorFactory, BlockExecutorFor,
         BlockValidationError, CommitChanges, ExecutableTx, StateChangePostBlockSource,
         StateChangeSource, SystemCaller,
     },
     eth::{
-        dao_fork, eip6110,
+        EthEvmContext, dao_fork, eip6110,
         receipt_builder::{ReceiptBuilder, ReceiptBuilderCtx},
     },
     state_change::{balance_increment_state, post_block_balance_increments},
 };
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, mem, ops::DerefMut, sync::Arc, time::Instant};
 
 #[derive(Debug)]
 pub struct BerachainBlockExecutor<'a, Evm> {
@@ -49,6 +57,10 @@ pub struct BerachainBlockExecutor<'a, Evm> {
     receipts: Vec<<BerachainReceiptBuilder as ReceiptBuilder>::Receipt>,
     /// Total gas used by transactions in this block.
     gas_used: u64,
+    /// When the executor was created, for the execution latency.
+    started: Instant,
+    /// Payload latency breakdown the execution time is recorded in.
+    latency: Option<&'a PayloadLatency>,
 }
 
 impl<'a, Evm> BerachainBlockExecutor<'a, Evm> {
@@ -66,23 +78,34 @@ impl<'a, Evm> BerachainBlockExecutor<'a, Evm> {
             gas_used: 0,
             system_caller: SystemCaller::new(spec.clone()),
             receipt_builder,
+            started: Instant::now(),
+            latency: None,
         }
     }
 
+    /// Records the execution time of the block in `latency`.
+    pub fn with_latency(mut self, latency: Option<&'a PayloadLatency>) -> Self {
+        self.latency = latency;
+        self
+    }
+
     /// Execute POL transaction as system call and manually capture receipt
-    fn execute_pol_transaction_with_receipt(&mut self) -> Result<(), BlockExecutionError>
+    fn execute_pol_transaction_with_receipt<DB>(&mut self) -> Result<(), BlockExecutionError>
     where
-        Evm: reth_evm::Evm,
-        <Evm as reth_evm::Evm>::DB: DatabaseCommit,
+        DB: reth::revm::Database + DatabaseCommit,
+        Evm: reth_evm::Evm<DB = DB, Tx = TxEnv> + DerefMut<Target = EthEvmContext<DB>>,
     {
-        use alloy_eips::eip7002::SYSTEM_ADDRESS;
-        use reth::revm::DatabaseCommit;
         use reth_evm::block::StateChangeSource;
 
         let timestamp = self.evm.block().timestamp.saturating_to();
 
         // Validate proposer pubkey presence for Prague1
-        validate_proposer_pubkey_prague1(&*self.spec, timestamp, self.ctx.prev_proposer_pubkey)?;
+        validate_proposer_pubkey_prague1(&*self.spec, timestamp, self.ctx.prev_proposer_pubkey)
+            .inspect_err(|err| {
+                if *err == BerachainExecutionError::MissingProposerPubkey {
+                    pol_metrics::record_missing_proposer_pubkey();
+                }
+            })?;
 
         // Check if Prague1 hardfork is active (after validation)
         if !self.spec.is_prague1_active_at_timestamp(timestamp) {
@@ -97,27 +120,33 @@ impl<'a, Evm> BerachainBlockExecutor<'a, Evm> {
             self.spec.clone(),
             prev_proposer_pubkey,
             self.evm.block().number,
+            timestamp,
             base_fee,
         )?;
-        let (calldata, pol_distributor_address) =
+        let (calldata, pol_distributor_address, gas_limit) =
             if let BerachainTxEnvelope::Berachain(pol_tx) = &pol_envelope {
-                (pol_tx.input.clone(), pol_tx.to)
+                (pol_tx.input.clone(), pol_tx.to, pol_tx.gas_limit)
             } else {
                 return Err(BerachainExecutionError::InvalidPolTransactionType.into());
             };
 
-        // Execute as system call (maintains zero gas cost and unlimited gas)
-        match self.evm.transact_system_call(
-            SYSTEM_ADDRESS,
-            pol_distributor_address,
-            calldata.clone(),
-        ) {
+        // Execute with the gas limit of the PoL transaction, without fees or a nonce check
+        match transact_pol(&mut self.evm, pol_distributor_address, calldata, gas_limit) {
             Ok(result_and_state) => {
                 tracing::debug!(target: "executor", ?result_and_state, "POL transaction executed successfully");
+                let outcome = if result_and_state.result.is_success() {
+                    PolOutcome::Succeeded
+                } else {
+                    PolOutcome::Reverted
+                };
+                pol_metrics::record_distribution(&prev_proposer_pubkey, outcome);
+                pol_metrics::record_gas_used(result_and_state.result.gas_used());
 
                 // Use the already-created POL envelope for receipt generation
 
-                // Build receipt manually for the system call
+                // Build receipt manually for the system call. It keeps the logs of the
+                // distribution, so they are part of the logs bloom and receipts root of the block
+                // and served by `eth_getLogs` and log filters like those of user transactions.
                 let receipt = self.receipt_builder.build_receipt(ReceiptBuilderCtx {
                     tx: &pol_envelope,
                     evm: &self.evm,
@@ -145,19 +174,65 @@ impl<'a, Evm> BerachainBlockExecutor<'a, Evm> {
             }
             Err(e) => {
                 tracing::error!(target: "executor", %e, "POL system call execution failed");
+                pol_metrics::record_distribution(&prev_proposer_pubkey, PolOutcome::Failed);
                 Err(BlockExecutionError::other(e))
             }
         }
     }
 }
 
+/// Executes the PoL transaction calling `distributor` with `input` on `evm`, without committing.
+///
+/// It runs from `SYSTEM_ADDRESS` with `gas_limit`, the gas limit of the PoL transaction. Like a
+/// system call it pays no fees, skips the nonce check and leaves `SYSTEM_ADDRESS` untouched.
+pub fn transact_pol<DB, E>(
+    evm: &mut E,
+    distributor: Address,
+    input: Bytes,
+    gas_limit: u64,
+) -> Result<ResultAndState<E::HaltReason>, E::Error>
+where
+    DB: reth::revm::Database,
+    E: Evm<DB = DB, Tx = TxEnv> + DerefMut<Target = EthEvmContext<DB>>,
+{
+    let tx = TxEnv {
+        tx_type: 0,
+        caller: SYSTEM_ADDRESS,
+        gas_limit,
+        gas_price: 0,
+        kind: TxKind::Call(distributor),
+        value: U256::ZERO,
+        data: input,
+        nonce: 0,
+        chain_id: None,
+        access_list: Default::default(),
+        gas_priority_fee: None,
+        blob_hashes: vec![],
+        max_fee_per_blob_gas: 0,
+        authorization_list: vec![],
+    };
+
+    let mut block_gas_limit = gas_limit;
+    let mut basefee = 0;
+    let mut disable_nonce_check = true;
+    mem::swap(&mut evm.block.gas_limit, &mut block_gas_limit);
+    mem::swap(&mut evm.block.basefee, &mut basefee);
+    mem::swap(&mut evm.cfg.disable_nonce_check, &mut disable_nonce_check);
+    let result = evm.transact(tx);
+    mem::swap(&mut evm.block.gas_limit, &mut block_gas_limit);
+    mem::swap(&mut evm.block.basefee, &mut basefee);
+    mem::swap(&mut evm.cfg.disable_nonce_check, &mut disable_nonce_check);
+
+    let mut result_and_state = result?;
+    result_and_state.state.remove(&SYSTEM_ADDRESS);
+    Ok(result_and_state)
+}
+
 impl<'db, DB, E> BlockExecutor for BerachainBlockExecutor<'_, E>
 where
     DB: Database + 'db,
-    E: Evm<
-            DB = &'db mut State<DB>,
-            Tx: FromRecoveredTx<BerachainTxEnvelope> + FromTxWithEncoded<BerachainTxEnvelope>,
-        >,
+    E: Evm<DB = &'db mut State<DB>, Tx = TxEnv>
+        + DerefMut<Target = EthEvmContext<&'db mut State<DB>>>,
 {
     type Transaction = BerachainTxEnvelope;
     type Receipt = reth_ethereum_primitives::Receipt<BerachainTxType>;
@@ -302,6 +377,13 @@ where
             })
         })?;
 
+        if let Some(latency) = self.latency {
+            latency.on_executed(
+                self.evm.block().number.saturating_to(),
+                self.ctx.parent_hash,
+                self.started.elapsed(),
+            );
+        }
         Ok((
             self.evm,
             BlockExecutionResult { receipts: self.receipts, requests, gas_used: self.gas_used },
@@ -341,5 +423,6 @@ impl BlockExecutorFactory for BerachainEvmConfig {
         I: Inspector<<Self::EvmFactory as EvmFactory>::Context<&'a mut State<DB>>> + 'a,
     {
         BerachainBlockExecutor::new(evm, ctx, self.spec.clone(), self.receipt_builder)
+            .with_latency(self.latency.as_deref())
     }
 }
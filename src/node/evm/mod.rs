@@ -5,12 +5,18 @@ mod block_context;
 pub mod config;
 pub mod error;
 pub mod executor;
+pub mod pol_metrics;
+pub mod profiler;
 pub mod receipt;
 
-use crate::node::{BerachainNode, evm::config::BerachainEvmConfig};
+use crate::{
+    engine::latency::PayloadLatency,
+    node::{BerachainNode, evm::config::BerachainEvmConfig},
+};
 use alloy_primitives::Bytes;
 use reth_evm::EthEvmFactory;
 use reth_node_builder::{BuilderContext, FullNodeTypes, components::ExecutorBuilder};
+use std::sync::Arc;
 
 /// Default extra data for Berachain blocks
 fn default_extra_data() -> String {
@@ -23,8 +29,11 @@ fn default_extra_data_bytes() -> Bytes {
 }
 
 /// Creates standard Ethereum EVM with Berachain chain spec
-#[derive(Debug, Default, Clone, Copy)]
-pub struct BerachainExecutorBuilder;
+#[derive(Debug, Default, Clone)]
+pub struct BerachainExecutorBuilder {
+    /// Payload latency breakdown shared with the engine validator.
+    pub latency: Arc<PayloadLatency>,
+}
 
 impl<Node> ExecutorBuilder<Node> for BerachainExecutorBuilder
 where
@@ -37,7 +46,8 @@ where
     async fn build_evm(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::EVM> {
         let evm_config =
             BerachainEvmConfig::new_with_evm_factory(ctx.chain_spec(), EthEvmFactory::default())
-                .with_extra_data(default_extra_data_bytes());
+                .with_extra_data(default_extra_data_bytes())
+                .with_latency(self.latency);
         Ok(evm_config)
     }
 }
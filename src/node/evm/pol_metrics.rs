@@ -0,0 +1,108 @@
+//! Metrics of PoL distributions
+
+use crate::primitives::header::BlsPublicKey;
+use alloy_primitives::{hex, keccak256};
+use reth_metrics::{
+    Metrics,
+    metrics::{Counter, Histogram},
+};
+use std::{sync::LazyLock, time::Duration};
+
+static METRICS: LazyLock<PolMetrics> = LazyLock::new(PolMetrics::default);
+
+/// PoL metrics not attributable to a proposer.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.pol")]
+struct PolMetrics {
+    /// Prague1 blocks executed without the previous proposer's public key.
+    missing_proposer_pubkey: Counter,
+    /// Distributions whose system call failed to execute, over all proposers.
+    execution_failures: Counter,
+    /// Gas used by the system call of each distribution.
+    gas_used: Histogram,
+    /// Time to build a Prague1 payload, including its distribution, in seconds.
+    payload_build_seconds: Histogram,
+}
+
+/// PoL metrics of a proposer, labeled with its hashed public key.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.pol")]
+struct ProposerPolMetrics {
+    /// Distributions that succeeded.
+    distributions_succeeded: Counter,
+    /// Distributions whose system call reverted or halted.
+    distributions_reverted: Counter,
+    /// Distributions whose system call failed to execute.
+    distributions_failed: Counter,
+    /// Blocks whose PoL transaction did not match the one expected for the proposer.
+    hash_mismatches: Counter,
+}
+
+impl ProposerPolMetrics {
+    fn of(proposer: &BlsPublicKey) -> Self {
+        Self::new_with_labels(&[("proposer", proposer_label(proposer))])
+    }
+}
+
+/// Outcome of a PoL distribution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolOutcome {
+    /// The system call succeeded.
+    Succeeded,
+    /// The system call reverted or halted.
+    Reverted,
+    /// The system call could not be executed.
+    Failed,
+}
+
+/// Returns the label of `proposer`: the first four bytes of the hash of its public key.
+pub fn proposer_label(proposer: &BlsPublicKey) -> String {
+    hex::encode_prefixed(&keccak256(proposer)[..4])
+}
+
+/// Records the outcome of the distribution of `proposer`.
+pub fn record_distribution(proposer: &BlsPublicKey, outcome: PolOutcome) {
+    let metrics = ProposerPolMetrics::of(proposer);
+    match outcome {
+        PolOutcome::Succeeded => metrics.distributions_succeeded.increment(1),
+        PolOutcome::Reverted => metrics.distributions_reverted.increment(1),
+        PolOutcome::Failed => {
+            metrics.distributions_failed.increment(1);
+            METRICS.execution_failures.increment(1);
+        }
+    }
+}
+
+/// Records the gas used by the system call of a distribution.
+pub fn record_gas_used(gas_used: u64) {
+    METRICS.gas_used.record(gas_used as f64);
+}
+
+/// Records the time taken to build a Prague1 payload.
+pub fn record_payload_build(elapsed: Duration) {
+    METRICS.payload_build_seconds.record(elapsed.as_secs_f64());
+}
+
+/// Records a Prague1 block executed without the previous proposer's public key.
+pub fn record_missing_proposer_pubkey() {
+    METRICS.missing_proposer_pubkey.increment(1);
+}
+
+/// Records a block whose PoL transaction did not match the one expected for `proposer`.
+pub fn record_hash_mismatch(proposer: &BlsPublicKey) {
+    ProposerPolMetrics::of(proposer).hash_mismatches.increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proposer_label_is_short_and_stable() {
+        let label = proposer_label(&BlsPublicKey::repeat_byte(1));
+        assert_eq!(label.len(), 10);
+        assert!(label.starts_with("0x"));
+        assert_eq!(label, proposer_label(&BlsPublicKey::repeat_byte(1)));
+        assert_ne!(label, proposer_label(&BlsPublicKey::repeat_byte(2)));
+    }
+}
@@ -0,0 +1,284 @@
+//! Gas and time profiling of block execution
+
+use crate::primitives::{BerachainBlock, BerachainPrimitives};
+use alloy_eips::BlockHashOrNumber;
+use alloy_primitives::{Address, BlockNumber};
+use reth::{
+    providers::{BlockReader, StateProviderFactory, TransactionVariant},
+    revm::{
+        Inspector, State,
+        bytecode::opcode::{self, OpCode},
+        database::StateProviderDatabase,
+        interpreter::{
+            Interpreter,
+            interpreter_types::{InputsTr, Jumps},
+        },
+    },
+};
+use reth_evm::{ConfigureEvm, block::BlockExecutor};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+/// Most blocks profiled by one `debug_profileExecution` call.
+pub const MAX_RPC_BLOCKS: u64 = 100;
+
+/// Gas and time spent in an opcode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpcodeProfile {
+    /// Name of the opcode.
+    pub opcode: String,
+    /// Times the opcode ran.
+    pub count: u64,
+    /// Gas charged by the opcode.
+    pub gas: u64,
+    /// Time spent in the opcode, in nanoseconds.
+    pub nanos: u64,
+}
+
+/// Gas and time spent in the code of a contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractProfile {
+    /// Address of the contract.
+    pub address: Address,
+    /// Interpreter steps in the code of the contract.
+    pub steps: u64,
+    /// Gas charged by the code of the contract.
+    pub gas: u64,
+    /// Time spent in the code of the contract, in nanoseconds.
+    pub nanos: u64,
+}
+
+/// Profile of the execution of a range of blocks, as returned by `debug_profileExecution`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionProfile {
+    /// First profiled block.
+    pub from: BlockNumber,
+    /// Last profiled block.
+    pub to: BlockNumber,
+    /// Opcodes, by decreasing gas.
+    pub opcodes: Vec<OpcodeProfile>,
+    /// Contracts, by decreasing gas.
+    pub contracts: Vec<ContractProfile>,
+}
+
+impl ExecutionProfile {
+    /// Returns the opcodes as CSV.
+    pub fn opcodes_csv(&self) -> String {
+        let mut csv = String::from("opcode,count,gas,nanos\n");
+        for op in &self.opcodes {
+            let _ = writeln!(csv, "{},{},{},{}", op.opcode, op.count, op.gas, op.nanos);
+        }
+        csv
+    }
+
+    /// Returns the contracts as CSV.
+    pub fn contracts_csv(&self) -> String {
+        let mut csv = String::from("address,steps,gas,nanos\n");
+        for contract in &self.contracts {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{}",
+                contract.address, contract.steps, contract.gas, contract.nanos
+            );
+        }
+        csv
+    }
+}
+
+/// Totals of a step counter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Totals {
+    count: u64,
+    gas: u64,
+    nanos: u64,
+}
+
+impl Totals {
+    fn add(&mut self, gas: u64, elapsed: Duration) {
+        self.count += 1;
+        self.gas += gas;
+        self.nanos += elapsed.as_nanos() as u64;
+    }
+}
+
+/// Step started but not yet ended.
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    opcode: u8,
+    contract: Address,
+    gas_remaining: u64,
+    started: Instant,
+}
+
+/// Inspector summing gas and time per opcode and per contract.
+#[derive(Debug, Default)]
+pub struct OpcodeProfiler {
+    opcodes: HashMap<u8, Totals>,
+    contracts: HashMap<Address, Totals>,
+    step: Option<Step>,
+}
+
+impl OpcodeProfiler {
+    /// Records a step of `opcode` in the code of `contract`.
+    fn record(&mut self, opcode: u8, contract: Address, gas: u64, elapsed: Duration) {
+        // the gas a call forwards is charged again by the opcodes of the callee
+        let gas = if is_call(opcode) { 0 } else { gas };
+        self.opcodes.entry(opcode).or_default().add(gas, elapsed);
+        self.contracts.entry(contract).or_default().add(gas, elapsed);
+    }
+
+    /// Returns the profile of blocks `from` to `to`.
+    pub fn into_profile(self, from: BlockNumber, to: BlockNumber) -> ExecutionProfile {
+        let mut opcodes: Vec<_> = self
+            .opcodes
+            .into_iter()
+            .map(|(op, totals)| OpcodeProfile {
+                opcode: OpCode::new(op)
+                    .map_or_else(|| format!("0x{op:02x}"), |op| op.as_str().to_string()),
+                count: totals.count,
+                gas: totals.gas,
+                nanos: totals.nanos,
+            })
+            .collect();
+        opcodes.sort_by(|a, b| b.gas.cmp(&a.gas).then(b.nanos.cmp(&a.nanos)));
+        let mut contracts: Vec<_> = self
+            .contracts
+            .into_iter()
+            .map(|(address, totals)| ContractProfile {
+                address,
+                steps: totals.count,
+                gas: totals.gas,
+                nanos: totals.nanos,
+            })
+            .collect();
+        contracts.sort_by(|a, b| b.gas.cmp(&a.gas).then(b.nanos.cmp(&a.nanos)));
+        ExecutionProfile { from, to, opcodes, contracts }
+    }
+}
+
+/// Returns whether `opcode` hands execution to other code.
+const fn is_call(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        opcode::CALL |
+            opcode::CALLCODE |
+            opcode::DELEGATECALL |
+            opcode::STATICCALL |
+            opcode::CREATE |
+            opcode::CREATE2
+    )
+}
+
+impl<CTX> Inspector<CTX> for OpcodeProfiler {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+        self.step = Some(Step {
+            opcode: interp.bytecode.opcode(),
+            contract: interp.input.target_address(),
+            gas_remaining: interp.gas.remaining(),
+            started: Instant::now(),
+        });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+        if let Some(step) = self.step.take() {
+            let gas = step.gas_remaining.saturating_sub(interp.gas.remaining());
+            self.record(step.opcode, step.contract, gas, step.started.elapsed());
+        }
+    }
+}
+
+/// Re-executes blocks `from` to `to` of `provider` with an [`OpcodeProfiler`].
+pub fn profile_blocks<P, E>(
+    provider: &P,
+    evm_config: &E,
+    from: BlockNumber,
+    to: BlockNumber,
+) -> eyre::Result<ExecutionProfile>
+where
+    P: BlockReader<Block = BerachainBlock> + StateProviderFactory,
+    E: ConfigureEvm<Primitives = BerachainPrimitives>,
+{
+    eyre::ensure!(from > 0 && from <= to, "invalid block range {from}..={to}");
+    let mut profiler = OpcodeProfiler::default();
+    for number in from..=to {
+        let block = provider
+            .recovered_block(BlockHashOrNumber::Number(number), TransactionVariant::WithHash)?
+            .ok_or_else(|| eyre::eyre!("block {number} not found"))?;
+        let state = provider.history_by_block_number(number - 1)?;
+        let mut db = State::builder()
+            .with_database(StateProviderDatabase::new(&state))
+            .with_bundle_update()
+            .build();
+        let evm_env = evm_config.evm_env(block.header());
+        let evm = evm_config.evm_with_env_and_inspector(&mut db, evm_env, &mut profiler);
+        let ctx = evm_config.context_for_block(block.sealed_block());
+        evm_config.create_executor(evm, ctx).execute_block(block.transactions_recovered())?;
+    }
+    Ok(profiler.into_profile(from, to))
+}
+
+/// Profiles block ranges of a running node.
+pub struct Profiler {
+    profile: Box<dyn Fn(BlockNumber, BlockNumber) -> eyre::Result<ExecutionProfile> + Send + Sync>,
+}
+
+impl std::fmt::Debug for Profiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Profiler").finish_non_exhaustive()
+    }
+}
+
+impl Profiler {
+    /// Creates a profiler re-executing the blocks of `provider` with `evm_config`.
+    pub fn new<P, E>(provider: P, evm_config: E) -> Self
+    where
+        P: BlockReader<Block = BerachainBlock> + StateProviderFactory + Send + Sync + 'static,
+        E: ConfigureEvm<Primitives = BerachainPrimitives> + 'static,
+    {
+        Self { profile: Box::new(move |from, to| profile_blocks(&provider, &evm_config, from, to)) }
+    }
+
+    /// Re-executes blocks `from` to `to`, blocking until they ran.
+    pub fn profile(&self, from: BlockNumber, to: BlockNumber) -> eyre::Result<ExecutionProfile> {
+        (self.profile)(from, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_sorts_and_skips_forwarded_gas() {
+        let mut profiler = OpcodeProfiler::default();
+        let token = Address::repeat_byte(1);
+        let vault = Address::repeat_byte(2);
+        profiler.record(opcode::SLOAD, token, 2_100, Duration::from_nanos(500));
+        profiler.record(opcode::SLOAD, vault, 100, Duration::from_nanos(50));
+        profiler.record(opcode::ADD, vault, 3, Duration::from_nanos(5));
+        profiler.record(opcode::CALL, vault, 1_000_000, Duration::from_nanos(70));
+
+        let profile = profiler.into_profile(1, 2);
+        assert_eq!(
+            profile.opcodes[0],
+            OpcodeProfile { opcode: "SLOAD".to_string(), count: 2, gas: 2_200, nanos: 550 }
+        );
+        assert_eq!(profile.opcodes.iter().find(|op| op.opcode == "CALL").unwrap().gas, 0);
+        assert_eq!(profile.contracts[0].address, token);
+        assert_eq!(
+            profile.contracts[1],
+            ContractProfile { address: vault, steps: 3, gas: 103, nanos: 125 }
+        );
+
+        let csv = profile.opcodes_csv();
+        assert!(csv.starts_with("opcode,count,gas,nanos\nSLOAD,2,2200,550\n"));
+        assert_eq!(profile.contracts_csv().lines().count(), 3);
+    }
+}
@@ -0,0 +1,122 @@
+//! PoL distributions for execution extensions
+
+use crate::{
+    primitives::{BerachainBlock, BerachainPrimitives, header::BlsPublicKey},
+    transaction::{BerachainTxEnvelope, pol::decode_pol_pubkey},
+};
+use alloy_consensus::BlockHeader;
+use alloy_eips::BlockNumHash;
+use alloy_primitives::{Address, TxHash};
+use futures::{Stream, StreamExt};
+use reth_exex::ExExNotification;
+use reth_primitives_traits::RecoveredBlock;
+use serde::{Deserialize, Serialize};
+
+/// PoL distribution executed in a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolEvent {
+    /// Block the distribution was executed in.
+    pub block: BlockNumHash,
+    /// Timestamp of the block.
+    pub timestamp: u64,
+    /// Hash of the PoL transaction.
+    pub tx_hash: TxHash,
+    /// PoL distributor contract called.
+    pub distributor: Address,
+    /// Proposer passed to `distributeFor`, the proposer of the parent block.
+    pub pubkey: BlsPublicKey,
+}
+
+impl PolEvent {
+    /// Returns the distribution of `block`, if it starts with a well-formed PoL transaction.
+    pub fn from_block(block: &RecoveredBlock<BerachainBlock>) -> Option<Self> {
+        let BerachainTxEnvelope::Berachain(pol_tx) = block.body().transactions.first()? else {
+            return None
+        };
+        Some(Self {
+            block: BlockNumHash::new(block.header().number(), block.hash()),
+            timestamp: block.header().timestamp(),
+            tx_hash: pol_tx.hash(),
+            distributor: pol_tx.to,
+            pubkey: decode_pol_pubkey(&pol_tx.input)?,
+        })
+    }
+}
+
+/// PoL distributions of an ExEx notification.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolNotification {
+    /// Distributions of the blocks removed from the canonical chain, oldest first.
+    pub reverted: Vec<PolEvent>,
+    /// Distributions of the blocks added to the canonical chain, oldest first.
+    pub committed: Vec<PolEvent>,
+    /// Tip of the committed blocks, to be reported back as `ExExEvent::FinishedHeight` once
+    /// handled.
+    pub committed_tip: Option<BlockNumHash>,
+}
+
+impl PolNotification {
+    /// Extracts the distributions of `notification`.
+    pub fn new(notification: &ExExNotification<BerachainPrimitives>) -> Self {
+        Self {
+            reverted: notification
+                .reverted_chain()
+                .map(|chain| chain.blocks_iter().filter_map(PolEvent::from_block).collect())
+                .unwrap_or_default(),
+            committed: notification
+                .committed_chain()
+                .map(|chain| chain.blocks_iter().filter_map(PolEvent::from_block).collect())
+                .unwrap_or_default(),
+            committed_tip: notification.committed_chain().map(|chain| chain.tip().num_hash()),
+        }
+    }
+}
+
+/// Maps the ExEx `notifications` to their PoL distributions.
+pub fn pol_notifications<S>(notifications: S) -> impl Stream<Item = eyre::Result<PolNotification>>
+where
+    S: Stream<Item = eyre::Result<ExExNotification<BerachainPrimitives>>>,
+{
+    notifications
+        .map(|notification| notification.map(|notification| PolNotification::new(&notification)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::BERACHAIN_DEV,
+        primitives::{BerachainBlockBody, BerachainHeader},
+        transaction::pol::create_pol_transaction,
+    };
+    use alloy_primitives::U256;
+    use reth::revm::handler::SYSTEM_ADDRESS;
+
+    #[test]
+    fn test_pol_event_from_block() {
+        let pubkey = BlsPublicKey::from([7u8; 48]);
+        let pol_tx =
+            create_pol_transaction(BERACHAIN_DEV.clone(), pubkey, U256::from(3), 0, 7).unwrap();
+        let header = BerachainHeader { number: 3, timestamp: 42, ..Default::default() };
+        let body = BerachainBlockBody { transactions: vec![pol_tx.clone()], ..Default::default() };
+        let block =
+            RecoveredBlock::new_unhashed(BerachainBlock { header, body }, vec![SYSTEM_ADDRESS]);
+
+        let event = PolEvent::from_block(&block).unwrap();
+        assert_eq!(event.block, BlockNumHash::new(3, block.hash()));
+        assert_eq!(event.timestamp, 42);
+        assert_eq!(event.tx_hash, *pol_tx.tx_hash());
+        assert_eq!(event.distributor, BERACHAIN_DEV.pol_contract());
+        assert_eq!(event.pubkey, pubkey);
+
+        let empty = RecoveredBlock::new_unhashed(
+            BerachainBlock {
+                header: BerachainHeader::default(),
+                body: BerachainBlockBody::default(),
+            },
+            vec![],
+        );
+        assert_eq!(PolEvent::from_block(&empty), None);
+    }
+}
@@ -0,0 +1,385 @@
+//! Fee history that survives restarts
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    primitives::{BerachainBlock, BerachainPrimitives},
+    transaction::BerachainTxType,
+};
+use alloy_consensus::BlockHeader;
+use alloy_primitives::{B256, BlockNumber};
+use alloy_rpc_types_eth::FeeHistory;
+use futures::StreamExt;
+use reth::{
+    providers::{BlockHashReader, CanonStateSubscriptions},
+    tasks::TaskExecutor,
+};
+use reth_chainspec::EthChainSpec;
+use reth_ethereum_primitives::Receipt;
+use reth_primitives_traits::{BlockBody, RecoveredBlock};
+use reth_rpc_eth_types::{
+    FeeHistoryCacheConfig, fee_history::calculate_reward_percentiles_for_block,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+use tracing::{debug, info, warn};
+
+/// File the rows are persisted to in the datadir.
+pub const FEE_HISTORY_FILE: &str = "fee-history.json.zst";
+
+/// Fees of a canonical block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistoryRow {
+    /// Block number.
+    pub number: BlockNumber,
+    /// Block hash.
+    pub hash: B256,
+    /// Base fee of the block.
+    pub base_fee_per_gas: u64,
+    /// Base fee of the next block.
+    pub next_base_fee_per_gas: u64,
+    /// Gas used relative to the gas limit.
+    pub gas_used_ratio: f64,
+    /// Blob base fee of the block, zero before Cancun.
+    pub base_fee_per_blob_gas: u128,
+    /// Blob base fee of the next block, zero before Cancun.
+    pub next_base_fee_per_blob_gas: u128,
+    /// Blob gas used relative to the blob gas limit.
+    pub blob_gas_used_ratio: f64,
+    /// Priority fees paid at each percentile step of the resolution.
+    pub rewards: Vec<u128>,
+}
+
+impl FeeHistoryRow {
+    /// Computes the row of `block` with its `receipts` at `resolution` steps per percent.
+    pub fn new(
+        block: &RecoveredBlock<BerachainBlock>,
+        receipts: &[Receipt<BerachainTxType>],
+        chain_spec: &BerachainChainSpec,
+        resolution: u64,
+    ) -> Self {
+        let header = block.header();
+        let base_fee = header.base_fee_per_gas().unwrap_or_default();
+        let blob_params = chain_spec.blob_params_at_timestamp(header.timestamp());
+        let percentiles = percentile_steps(resolution);
+        let rewards = calculate_reward_percentiles_for_block(
+            &percentiles,
+            header.gas_used(),
+            base_fee,
+            block.body().transactions(),
+            receipts,
+        )
+        .unwrap_or_default();
+        Self {
+            number: header.number(),
+            hash: block.hash(),
+            base_fee_per_gas: base_fee,
+            next_base_fee_per_gas: chain_spec
+                .next_block_base_fee(header, header.timestamp())
+                .unwrap_or_default(),
+            gas_used_ratio: ratio(header.gas_used(), header.gas_limit()),
+            base_fee_per_blob_gas: blob_params
+                .and_then(|params| header.blob_fee(params))
+                .unwrap_or_default(),
+            next_base_fee_per_blob_gas: header
+                .maybe_next_block_blob_fee(blob_params)
+                .unwrap_or_default(),
+            blob_gas_used_ratio: ratio(
+                header.blob_gas_used().unwrap_or_default(),
+                blob_params.map(|params| params.max_blob_gas_per_block()).unwrap_or_default(),
+            ),
+            rewards,
+        }
+    }
+}
+
+/// Returns `used / limit`, zero without limit.
+fn ratio(used: u64, limit: u64) -> f64 {
+    if limit == 0 { 0.0 } else { used as f64 / limit as f64 }
+}
+
+/// Returns the percentiles from 0 to 100 in steps of `1 / resolution`.
+fn percentile_steps(resolution: u64) -> Vec<f64> {
+    (0..=100 * resolution).map(|step| step as f64 / resolution as f64).collect()
+}
+
+/// Contents of [`FEE_HISTORY_FILE`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    resolution: u64,
+    rows: Vec<FeeHistoryRow>,
+}
+
+/// Rows of consecutive recent canonical blocks.
+#[derive(Debug)]
+pub struct FeeHistoryRows {
+    max_blocks: u64,
+    resolution: u64,
+    rows: RwLock<VecDeque<FeeHistoryRow>>,
+}
+
+impl Default for FeeHistoryRows {
+    fn default() -> Self {
+        Self::new(&FeeHistoryCacheConfig::default())
+    }
+}
+
+impl FeeHistoryRows {
+    /// Creates empty rows keeping `max_blocks` blocks at `resolution` steps per percent.
+    pub fn new(config: &FeeHistoryCacheConfig) -> Self {
+        Self {
+            max_blocks: config.max_blocks,
+            resolution: config.resolution,
+            rows: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Returns the number of kept rows.
+    pub fn len(&self) -> usize {
+        self.rows.read().expect("not poisoned").len()
+    }
+
+    /// Returns whether no rows are kept.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends the row of the next canonical block. A row at or below the last one replaces the
+    /// rows from its block on, a row after a gap replaces all rows.
+    pub fn insert(&self, row: FeeHistoryRow) {
+        let mut rows = self.rows.write().expect("not poisoned");
+        match rows.back().map(|last| last.number) {
+            Some(last) if row.number <= last => {
+                rows.retain(|kept| kept.number < row.number);
+            }
+            Some(last) if row.number > last + 1 => rows.clear(),
+            _ => {}
+        }
+        rows.push_back(row);
+        while rows.len() as u64 > self.max_blocks {
+            rows.pop_front();
+        }
+    }
+
+    /// Removes the rows of blocks after `number`.
+    pub fn revert_to(&self, number: BlockNumber) {
+        self.rows.write().expect("not poisoned").retain(|row| row.number <= number);
+    }
+
+    /// Returns the fee history of the `block_count` blocks up to `newest`, with rewards at
+    /// `percentiles`, if all of them are kept.
+    pub fn fee_history(
+        &self,
+        newest: BlockNumber,
+        block_count: u64,
+        percentiles: Option<&[f64]>,
+    ) -> Option<FeeHistory> {
+        let rows = self.rows.read().expect("not poisoned");
+        let first = rows.front()?.number;
+        let oldest = (newest + 1).checked_sub(block_count)?;
+        let start = oldest.checked_sub(first)? as usize;
+        let end = start + block_count as usize;
+        if block_count == 0 || end > rows.len() {
+            return None
+        }
+        let rows = rows.range(start..end);
+
+        let mut history = FeeHistory { oldest_block: oldest, ..Default::default() };
+        let mut reward = percentiles.map(|_| Vec::with_capacity(block_count as usize));
+        for row in rows {
+            history.base_fee_per_gas.push(row.base_fee_per_gas as u128);
+            history.gas_used_ratio.push(row.gas_used_ratio);
+            history.base_fee_per_blob_gas.push(row.base_fee_per_blob_gas);
+            history.blob_gas_used_ratio.push(row.blob_gas_used_ratio);
+            if let (Some(reward), Some(percentiles)) = (&mut reward, percentiles) {
+                reward.push(percentiles.iter().map(|p| self.approximate(row, *p)).collect());
+            }
+            if row.number == newest {
+                history.base_fee_per_gas.push(row.next_base_fee_per_gas as u128);
+                history.base_fee_per_blob_gas.push(row.next_base_fee_per_blob_gas);
+            }
+        }
+        history.reward = reward;
+        Some(history)
+    }
+
+    /// Returns the reward of `row` at the step of the resolution closest to `percentile`, like
+    /// reth's `FeeHistoryCache`.
+    fn approximate(&self, row: &FeeHistoryRow, percentile: f64) -> u128 {
+        let index = (percentile.clamp(0.0, 100.0) * self.resolution as f64).round() as usize;
+        row.rewards.get(index.min(row.rewards.len().saturating_sub(1))).copied().unwrap_or_default()
+    }
+
+    /// Writes the rows to `path`.
+    fn persist(&self, path: &Path) -> eyre::Result<()> {
+        let snapshot = Snapshot {
+            resolution: self.resolution,
+            rows: self.rows.read().expect("not poisoned").iter().cloned().collect(),
+        };
+        let json = serde_json::to_vec(&snapshot)?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, zstd::encode_all(json.as_slice(), 3)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Restores the rows written to `path` whose blocks are still canonical per `block_hash`.
+    fn restore(
+        &self,
+        path: &Path,
+        block_hash: impl Fn(BlockNumber) -> Option<B256>,
+    ) -> eyre::Result<usize> {
+        let snapshot: Snapshot =
+            serde_json::from_slice(&zstd::decode_all(std::fs::File::open(path)?)?)?;
+        if snapshot.resolution != self.resolution {
+            return Ok(0)
+        }
+        let mut restored = 0;
+        for row in snapshot.rows {
+            if block_hash(row.number) != Some(row.hash) {
+                break
+            }
+            self.insert(row);
+            restored += 1;
+        }
+        Ok(restored)
+    }
+}
+
+/// Restores the fee history `rows` persisted in `data_dir`, keeps them up to date with the
+/// canonical chain of `provider` and persists them again on shutdown.
+pub fn install_fee_history<P>(
+    rows: Arc<FeeHistoryRows>,
+    data_dir: &Path,
+    provider: P,
+    chain_spec: Arc<BerachainChainSpec>,
+    executor: &TaskExecutor,
+) where
+    P: CanonStateSubscriptions<Primitives = BerachainPrimitives> + BlockHashReader,
+{
+    let path: PathBuf = data_dir.join(FEE_HISTORY_FILE);
+    if path.exists() {
+        match rows.restore(&path, |number| provider.block_hash(number).ok().flatten()) {
+            Ok(restored) => {
+                info!(target: "reth::cli", restored, "Restored fee history");
+            }
+            Err(err) => warn!(target: "reth::cli", %err, "Failed to restore fee history"),
+        }
+    }
+
+    let mut notifications = provider.canonical_state_stream();
+    executor.spawn_critical_with_graceful_shutdown_signal(
+        "berachain fee history",
+        |shutdown| async move {
+            let resolution = rows.resolution;
+            let mut shutdown = std::pin::pin!(shutdown);
+            loop {
+                let notification = tokio::select! {
+                    guard = &mut shutdown => {
+                        let _guard = guard;
+                        match rows.persist(&path) {
+                            Ok(()) => debug!(target: "reth::cli", "Persisted fee history"),
+                            Err(err) => {
+                                warn!(target: "reth::cli", %err, "Failed to persist fee history")
+                            }
+                        }
+                        return
+                    }
+                    notification = notifications.next() => notification,
+                };
+                let Some(notification) = notification else { return };
+                if let Some(reverted) = notification.reverted() {
+                    rows.revert_to(reverted.fork_block().number);
+                }
+                for (block, receipts) in notification.committed().blocks_and_receipts() {
+                    rows.insert(FeeHistoryRow::new(block, receipts, &chain_spec, resolution));
+                }
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(number: BlockNumber) -> FeeHistoryRow {
+        FeeHistoryRow {
+            number,
+            hash: B256::with_last_byte(number as u8),
+            base_fee_per_gas: number * 10,
+            next_base_fee_per_gas: (number + 1) * 10,
+            gas_used_ratio: 0.5,
+            base_fee_per_blob_gas: 1,
+            next_base_fee_per_blob_gas: 1,
+            blob_gas_used_ratio: 0.0,
+            rewards: (0..=100).map(|p| p as u128).collect(),
+        }
+    }
+
+    fn rows(max_blocks: u64) -> FeeHistoryRows {
+        FeeHistoryRows::new(&FeeHistoryCacheConfig { max_blocks, resolution: 1 })
+    }
+
+    #[test]
+    fn test_fee_history_from_rows() {
+        let rows = rows(4);
+        for number in 1..=6 {
+            rows.insert(row(number));
+        }
+        assert_eq!(rows.len(), 4);
+
+        let history = rows.fee_history(5, 2, Some(&[10.0, 90.4])).unwrap();
+        assert_eq!(history.oldest_block, 4);
+        assert_eq!(history.base_fee_per_gas, vec![40, 50, 60]);
+        assert_eq!(history.base_fee_per_blob_gas.len(), 3);
+        assert_eq!(history.reward, Some(vec![vec![10, 90], vec![10, 90]]));
+
+        assert_eq!(rows.fee_history(6, 1, None).unwrap().base_fee_per_gas, vec![60, 70]);
+        assert!(rows.fee_history(6, 5, None).is_none());
+        assert!(rows.fee_history(7, 1, None).is_none());
+        assert!(rows.fee_history(6, 0, None).is_none());
+    }
+
+    #[test]
+    fn test_reorgs_and_gaps_replace_rows() {
+        let rows = rows(10);
+        for number in 1..=5 {
+            rows.insert(row(number));
+        }
+        rows.insert(FeeHistoryRow { base_fee_per_gas: 7, ..row(4) });
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows.fee_history(4, 1, None).unwrap().base_fee_per_gas[0], 7);
+
+        rows.revert_to(2);
+        assert_eq!(rows.len(), 2);
+        rows.insert(row(9));
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[test]
+    fn test_persist_and_restore_canonical_rows() {
+        let path = std::env::temp_dir().join(format!("bera-fee-history-{}", std::process::id()));
+        let rows = rows(10);
+        for number in 1..=5 {
+            rows.insert(row(number));
+        }
+        rows.persist(&path).unwrap();
+
+        // block 4 was reorged out while the node was down
+        let restored = self::rows(10);
+        let block_hash = |number| (number < 4).then(|| row(number).hash);
+        assert_eq!(restored.restore(&path, block_hash).unwrap(), 3);
+        assert!(restored.fee_history(3, 3, None).is_some());
+        assert!(restored.fee_history(4, 1, None).is_none());
+
+        let other = FeeHistoryRows::new(&FeeHistoryCacheConfig { max_blocks: 10, resolution: 4 });
+        assert_eq!(other.restore(&path, |number| Some(row(number).hash)).unwrap(), 0);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+//! Fee market metrics around the minimum base fee
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    primitives::{BerachainHeader, BerachainPrimitives},
+};
+use alloy_consensus::BlockHeader;
+use futures::StreamExt;
+use reth::{
+    providers::{CanonStateSubscriptions, HeaderProvider},
+    tasks::TaskExecutor,
+};
+use reth_chainspec::EthChainSpec;
+use reth_metrics::{
+    Metrics,
+    metrics::{Counter, Gauge},
+};
+use reth_primitives_traits::SealedHeader;
+use std::sync::Arc;
+
+/// Fee market metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.fee_market")]
+struct FeeMarketMetrics {
+    /// Base fee of the canonical head, in wei.
+    base_fee: Gauge,
+    /// Consecutive canonical blocks up to the head whose base fee is at the Prague1 floor.
+    consecutive_blocks_at_floor: Gauge,
+    /// Canonical blocks whose base fee is at the Prague1 floor.
+    blocks_at_floor: Counter,
+    /// Canonical blocks whose base fee was raised to the Prague1 floor, being below it under the
+    /// base fee parameters.
+    minimum_base_fee_clamps: Counter,
+    /// Gas used by the canonical head relative to its gas target, from 0 to the elasticity
+    /// multiplier.
+    gas_target_utilization: Gauge,
+}
+
+/// Fee market state of a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FeeMarketSample {
+    base_fee: u64,
+    at_floor: bool,
+    gas_target_utilization: f64,
+}
+
+impl FeeMarketSample {
+    /// Samples `header`, where `floor` is the minimum base fee if Prague1 is active.
+    fn new<H: BlockHeader>(header: &H, floor: Option<u64>, elasticity_multiplier: u128) -> Self {
+        let base_fee = header.base_fee_per_gas().unwrap_or_default();
+        let gas_target = header.gas_limit() as f64 / elasticity_multiplier.max(1) as f64;
+        Self {
+            base_fee,
+            at_floor: floor.is_some_and(|floor| base_fee <= floor),
+            gas_target_utilization: if gas_target > 0.0 {
+                header.gas_used() as f64 / gas_target
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Counts consecutive blocks at the base fee floor.
+#[derive(Debug, Default)]
+struct FloorStreak {
+    blocks: u64,
+}
+
+impl FloorStreak {
+    fn observe(&mut self, sample: &FeeMarketSample) -> u64 {
+        self.blocks = if sample.at_floor { self.blocks + 1 } else { 0 };
+        self.blocks
+    }
+}
+
+/// Starts updating the fee market metrics with every canonical block of `provider`.
+pub fn install_fee_market_metrics<P>(
+    provider: P,
+    chain_spec: Arc<BerachainChainSpec>,
+    executor: &TaskExecutor,
+) where
+    P: CanonStateSubscriptions<Primitives = BerachainPrimitives>
+        + HeaderProvider<Header = BerachainHeader>
+        + 'static,
+{
+    let mut notifications = provider.canonical_state_stream();
+    executor.spawn(async move {
+        let metrics = FeeMarketMetrics::default();
+        let mut streak = FloorStreak::default();
+        let mut parent: Option<SealedHeader<BerachainHeader>> = None;
+        while let Some(notification) = notifications.next().await {
+            // Reverted blocks are not subtracted, so after a reorg the streak may be off by the
+            // reorg depth until the next block above the floor
+            for block in notification.committed().blocks_iter() {
+                let timestamp = block.timestamp();
+                let floor = chain_spec.minimum_base_fee_at_timestamp(timestamp);
+                let elasticity =
+                    chain_spec.base_fee_params_at_timestamp(timestamp).elasticity_multiplier;
+                let sample = FeeMarketSample::new(block.header(), floor, elasticity);
+
+                // The previous block is the parent unless a reorg happened in between
+                let known = parent.take().filter(|parent| parent.hash() == block.parent_hash());
+                let parent_header = known.or_else(|| {
+                    provider.sealed_header(block.number().saturating_sub(1)).ok().flatten()
+                });
+                if parent_header.is_some_and(|parent| {
+                    chain_spec.is_base_fee_raised_to_minimum_after(parent.header())
+                }) {
+                    metrics.minimum_base_fee_clamps.increment(1);
+                }
+                parent = Some(block.clone_sealed_header());
+
+                if sample.at_floor {
+                    metrics.blocks_at_floor.increment(1);
+                }
+                metrics.consecutive_blocks_at_floor.set(streak.observe(&sample) as f64);
+                metrics.base_fee.set(sample.base_fee as f64);
+                metrics.gas_target_utilization.set(sample.gas_target_utilization);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::BerachainHeader;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn header(base_fee: u64, gas_used: u64) -> BerachainHeader {
+        BerachainHeader {
+            base_fee_per_gas: Some(base_fee),
+            gas_limit: 30_000_000,
+            gas_used,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_gas_target_utilization() {
+        let sample = FeeMarketSample::new(&header(GWEI, 30_000_000), Some(GWEI), 2);
+        assert_eq!(sample.gas_target_utilization, 2.0);
+        let sample = FeeMarketSample::new(&header(GWEI, 7_500_000), Some(GWEI), 2);
+        assert_eq!(sample.gas_target_utilization, 0.5);
+    }
+
+    #[test]
+    fn test_floor_streak() {
+        let mut streak = FloorStreak::default();
+        let at_floor = FeeMarketSample::new(&header(GWEI, 0), Some(GWEI), 2);
+        let above = FeeMarketSample::new(&header(2 * GWEI, 0), Some(GWEI), 2);
+        let before_prague1 = FeeMarketSample::new(&header(7, 0), None, 2);
+
+        assert_eq!(streak.observe(&at_floor), 1);
+        assert_eq!(streak.observe(&at_floor), 2);
+        assert_eq!(streak.observe(&above), 0);
+        assert_eq!(streak.observe(&at_floor), 1);
+        assert!(!before_prague1.at_floor);
+        assert_eq!(streak.observe(&before_prague1), 0);
+    }
+}
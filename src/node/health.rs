@@ -0,0 +1,389 @@
+//! `/health` and `/ready` HTTP endpoints for load balancers and Kubernetes probes
+
+use crate::primitives::BerachainPrimitives;
+use clap::Args;
+use futures::{Stream, StreamExt};
+use reth::{
+    network::NetworkInfo,
+    payload::{Events, PayloadBuilderHandle},
+    providers::{BlockNumReader, StageCheckpointReader},
+    tasks::TaskExecutor,
+};
+use reth_engine_primitives::ConsensusEngineEvent;
+use reth_payload_primitives::PayloadTypes;
+use reth_stages_types::StageId;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{debug, info};
+
+/// Default maximum distance, in blocks, between the local head and the consensus client's head.
+pub const DEFAULT_MAX_HEAD_DISTANCE: u64 = 2;
+
+/// Default maximum age, in seconds, of the last forkchoice update.
+pub const DEFAULT_MAX_FCU_AGE: u64 = 30;
+
+/// How long a connection may take to send its request before it is dropped.
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause after a failed `accept`, so running out of file descriptors doesn't spin the loop.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Settings of the health endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct HealthConfig {
+    /// Port of the endpoints. Disabled if unset.
+    pub port: Option<u16>,
+    /// Interface the endpoints listen on.
+    pub addr: IpAddr,
+    /// Maximum distance between the local head and the consensus client's head for readiness.
+    pub max_head_distance: u64,
+    /// Maximum age of the last forkchoice update for readiness, in seconds.
+    pub max_fcu_age: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            port: None,
+            addr: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            max_head_distance: DEFAULT_MAX_HEAD_DISTANCE,
+            max_fcu_age: DEFAULT_MAX_FCU_AGE,
+        }
+    }
+}
+
+impl HealthConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &HealthArgs) -> Self {
+        if let Some(port) = args.port {
+            self.port = Some(port);
+        }
+        if let Some(addr) = args.addr {
+            self.addr = addr;
+        }
+        if let Some(max_head_distance) = args.max_head_distance {
+            self.max_head_distance = max_head_distance;
+        }
+        if let Some(max_fcu_age) = args.max_fcu_age {
+            self.max_fcu_age = max_fcu_age;
+        }
+        self
+    }
+}
+
+/// Command line arguments of the health endpoints.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Health endpoints")]
+pub struct HealthArgs {
+    /// Serve `/health` and `/ready` on this port
+    #[arg(long = "health.port", value_name = "PORT")]
+    pub port: Option<u16>,
+
+    /// Interface of the health endpoints [default: 0.0.0.0]
+    #[arg(long = "health.addr", value_name = "ADDR")]
+    pub addr: Option<IpAddr>,
+
+    /// Maximum blocks behind the consensus client's head to be ready [default: 2]
+    #[arg(long = "health.max-head-distance", value_name = "BLOCKS")]
+    pub max_head_distance: Option<u64>,
+
+    /// Maximum age of the last forkchoice update to be ready, in seconds [default: 30]
+    #[arg(long = "health.max-fcu-age", value_name = "SECONDS")]
+    pub max_fcu_age: Option<u64>,
+}
+
+/// Consensus client activity observed through engine and payload builder events.
+#[derive(Debug, Default)]
+struct Activity {
+    /// When the last forkchoice update was received.
+    last_fcu: Option<Instant>,
+    /// Highest block received from the consensus client.
+    cl_head: Option<u64>,
+    /// Payload jobs started on behalf of the consensus client.
+    payloads_requested: u64,
+    /// Payloads built by those jobs.
+    payloads_built: u64,
+}
+
+/// Report served by both endpoints.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    /// Whether the node is ready to serve, see the module docs.
+    pub ready: bool,
+    /// First pipeline stage lagging behind, or `live` once the engine follows the chain.
+    pub stage: String,
+    /// Local canonical head.
+    pub head: u64,
+    /// Highest block received from the consensus client, if any.
+    pub cl_head: Option<u64>,
+    /// Blocks between the local head and `cl_head`.
+    pub head_distance: Option<u64>,
+    /// Seconds since the last forkchoice update, if any.
+    pub last_fcu_age: Option<u64>,
+    /// Share of payload jobs that produced a payload, if any were started.
+    pub payload_build_success_rate: Option<f64>,
+}
+
+/// Inputs of a [`HealthReport`].
+#[derive(Debug, Clone, Copy)]
+struct Snapshot<'a> {
+    lagging_stage: Option<StageId>,
+    head: u64,
+    activity: &'a Activity,
+    now: Instant,
+}
+
+impl HealthReport {
+    fn new(snapshot: Snapshot<'_>, config: &HealthConfig) -> Self {
+        let Snapshot { lagging_stage, head, activity, now } = snapshot;
+        let head_distance = activity.cl_head.map(|cl_head| cl_head.saturating_sub(head));
+        let last_fcu_age = activity.last_fcu.map(|at| now.duration_since(at).as_secs());
+        let payload_build_success_rate = (activity.payloads_requested > 0)
+            .then(|| activity.payloads_built as f64 / activity.payloads_requested as f64);
+
+        let ready = lagging_stage.is_none() &&
+            head_distance.is_some_and(|distance| distance <= config.max_head_distance) &&
+            last_fcu_age.is_some_and(|age| age <= config.max_fcu_age);
+
+        Self {
+            ready,
+            stage: lagging_stage.map_or_else(|| "live".to_string(), |stage| stage.to_string()),
+            head,
+            cl_head: activity.cl_head,
+            head_distance,
+            last_fcu_age,
+            payload_build_success_rate,
+        }
+    }
+}
+
+/// Serves the health endpoints, if enabled, and starts tracking consensus client activity.
+pub async fn install_health_endpoints<P, Net, Ev, T>(
+    config: HealthConfig,
+    provider: P,
+    network: Net,
+    engine_events: Ev,
+    payload_builder: &PayloadBuilderHandle<T>,
+    executor: &TaskExecutor,
+) -> eyre::Result<()>
+where
+    P: BlockNumReader + StageCheckpointReader + Send + Sync + 'static,
+    Net: NetworkInfo + Send + Sync + 'static,
+    Ev: Stream<Item = ConsensusEngineEvent<BerachainPrimitives>> + Send + 'static,
+    T: PayloadTypes,
+{
+    let Some(port) = config.port else { return Ok(()) };
+    let addr = SocketAddr::new(config.addr, port);
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|err| eyre::eyre!("failed to bind health endpoints {addr}: {err}"))?;
+    let activity = Arc::new(Mutex::new(Activity::default()));
+
+    let engine_activity = activity.clone();
+    executor.spawn(async move {
+        let mut engine_events = std::pin::pin!(engine_events);
+        while let Some(event) = engine_events.next().await {
+            let mut activity = engine_activity.lock().expect("not poisoned");
+            match event {
+                ConsensusEngineEvent::ForkchoiceUpdated(..) => {
+                    activity.last_fcu = Some(Instant::now())
+                }
+                ConsensusEngineEvent::BlockReceived(block) => {
+                    activity.cl_head = activity.cl_head.max(Some(block.number))
+                }
+                _ => {}
+            }
+        }
+    });
+
+    let payload_events = payload_builder.subscribe().await?.into_stream();
+    let payload_activity = activity.clone();
+    executor.spawn(async move {
+        let mut payload_events = std::pin::pin!(payload_events);
+        while let Some(event) = payload_events.next().await {
+            let mut activity = payload_activity.lock().expect("not poisoned");
+            match event {
+                Ok(Events::Attributes(_)) => activity.payloads_requested += 1,
+                Ok(Events::BuiltPayload(_)) => activity.payloads_built += 1,
+                Err(_) => {}
+            }
+        }
+    });
+
+    info!(target: "reth::cli", %addr, "Health endpoints started");
+    let provider = Arc::new(provider);
+    let network = Arc::new(network);
+    let connections = executor.clone();
+    executor.spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    debug!(target: "reth::cli", %err, "Failed to accept health connection");
+                    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    continue
+                }
+            };
+            // A slow client must not hold up the probes behind it
+            let (provider, network, activity) =
+                (provider.clone(), network.clone(), activity.clone());
+            connections.spawn(async move {
+                let report = current_report(&*provider, &*network, &activity, &config);
+                if let Err(err) = respond(stream, report).await {
+                    debug!(target: "reth::cli", %err, "Failed to answer health request");
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+/// Builds the report from the current node state.
+fn current_report<P, Net>(
+    provider: &P,
+    network: &Net,
+    activity: &Mutex<Activity>,
+    config: &HealthConfig,
+) -> eyre::Result<HealthReport>
+where
+    P: BlockNumReader + StageCheckpointReader,
+    Net: NetworkInfo,
+{
+    let lagging_stage = lagging_stage(provider)?;
+    let head = provider.best_block_number()?;
+    let activity = activity.lock().expect("not poisoned");
+    let snapshot = Snapshot { lagging_stage, head, activity: &activity, now: Instant::now() };
+    let mut report = HealthReport::new(snapshot, config);
+
+    // The network reports syncing while the pipeline or a backfill is running
+    if network.is_syncing() && report.stage == "live" {
+        report.stage = "backfill".to_string();
+        report.ready = false;
+    }
+    Ok(report)
+}
+
+/// Returns the first pipeline stage after the headers stage whose checkpoint is behind it.
+///
+/// Once the engine persists blocks it advances all checkpoints together, so no stage lags during
+/// live sync.
+fn lagging_stage<P: StageCheckpointReader>(provider: &P) -> eyre::Result<Option<StageId>> {
+    let checkpoint = |stage: StageId| -> eyre::Result<u64> {
+        let checkpoint = provider.get_stage_checkpoint(stage)?;
+        Ok(checkpoint.map(|checkpoint| checkpoint.block_number).unwrap_or_default())
+    };
+    let target = checkpoint(StageId::Headers)?;
+    for stage in StageId::ALL.into_iter().skip_while(|stage| *stage != StageId::Headers) {
+        if checkpoint(stage)? < target {
+            return Ok(Some(stage));
+        }
+    }
+    Ok(None)
+}
+
+/// Answers a single HTTP request on `stream`.
+async fn respond(mut stream: TcpStream, report: eyre::Result<HealthReport>) -> std::io::Result<()> {
+    let mut request = [0u8; 1024];
+    let read = tokio::time::timeout(REQUEST_READ_TIMEOUT, stream.read(&mut request))
+        .await
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+    let path = std::str::from_utf8(&request[..read])
+        .ok()
+        .and_then(|request| request.lines().next())
+        .and_then(|line| line.strip_prefix("GET "))
+        .and_then(|line| line.split_whitespace().next());
+
+    let (status, body) = match (path, report) {
+        (Some("/health"), Ok(report)) => ("200 OK", serde_json::to_string(&report)?),
+        (Some("/ready"), Ok(report)) if report.ready => ("200 OK", serde_json::to_string(&report)?),
+        (Some("/ready"), Ok(report)) => {
+            ("503 Service Unavailable", serde_json::to_string(&report)?)
+        }
+        (Some("/health" | "/ready"), Err(err)) => {
+            ("503 Service Unavailable", serde_json::json!({ "error": err.to_string() }).to_string())
+        }
+        _ => ("404 Not Found", String::new()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn report(lagging_stage: Option<StageId>, head: u64, activity: &Activity) -> HealthReport {
+        let now = activity.last_fcu.map_or_else(Instant::now, |at| at + Duration::from_secs(5));
+        HealthReport::new(
+            Snapshot { lagging_stage, head, activity, now },
+            &HealthConfig { port: Some(0), ..Default::default() },
+        )
+    }
+
+    #[test]
+    fn test_ready_once_following_the_consensus_client() {
+        let mut activity = Activity::default();
+        assert!(!report(None, 0, &activity).ready);
+
+        activity.last_fcu = Some(Instant::now());
+        activity.cl_head = Some(100);
+        let following = report(None, 99, &activity);
+        assert!(following.ready);
+        assert_eq!(following.stage, "live");
+        assert_eq!(following.head_distance, Some(1));
+        assert_eq!(following.last_fcu_age, Some(5));
+
+        let behind = report(None, 90, &activity);
+        assert!(!behind.ready);
+        assert_eq!(behind.head_distance, Some(10));
+
+        let syncing = report(Some(StageId::Execution), 99, &activity);
+        assert!(!syncing.ready);
+        assert_eq!(syncing.stage, StageId::Execution.to_string());
+    }
+
+    #[test]
+    fn test_payload_build_success_rate() {
+        let mut activity = Activity::default();
+        assert_eq!(report(None, 0, &activity).payload_build_success_rate, None);
+
+        activity.payloads_requested = 4;
+        activity.payloads_built = 3;
+        assert_eq!(report(None, 0, &activity).payload_build_success_rate, Some(0.75));
+    }
+
+    #[tokio::test]
+    async fn test_respond_over_tcp() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        for (request, status) in [("GET /ready HTTP/1.1", "503"), ("GET /metrics HTTP/1.1", "404")]
+        {
+            let mut client = TcpStream::connect(addr).await.unwrap();
+            client.write_all(format!("{request}\r\n\r\n").as_bytes()).await.unwrap();
+            let (stream, _) = listener.accept().await.unwrap();
+            respond(stream, Ok(report(None, 0, &Activity::default()))).await.unwrap();
+
+            let mut response = String::new();
+            client.read_to_string(&mut response).await.unwrap();
+            assert!(response.starts_with(&format!("HTTP/1.1 {status}")), "{response}");
+        }
+    }
+}
@@ -0,0 +1,292 @@
+//! Structured JSON logs with stable field names, optionally routed to files per target
+
+use clap::{Args, ValueEnum};
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{LineWriter, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+use tracing::{
+    Event, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+use tracing_subscriber::{
+    Layer,
+    fmt::{
+        format::Writer,
+        time::{FormatTime, SystemTime},
+    },
+    layer::Context,
+    registry::LookupSpan,
+};
+
+/// Groups of targets that can be routed to a file by name.
+pub const TARGET_GROUPS: [(&str, &[&str]); 3] = [
+    ("engine", &["engine", "consensus::engine", "rpc::engine", "payload_builder"]),
+    ("executor", &["executor", "evm", "sync::stages::execution"]),
+    ("pool", &["txpool", "transaction_pool", "net::tx"]),
+];
+
+/// Format of the logs printed to stdout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogOutputFormat {
+    /// reth's output, as configured with `--log.stdout.format`.
+    #[default]
+    Terminal,
+    /// One JSON object per event, with stable field names.
+    Json,
+}
+
+/// Events of a group of targets written to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRoute {
+    /// Name of a group in [`TARGET_GROUPS`], or a target prefix.
+    pub target: String,
+    /// File the events are appended to.
+    pub path: PathBuf,
+}
+
+impl LogRoute {
+    /// Returns the target prefixes routed to the file.
+    pub fn prefixes(&self) -> Vec<String> {
+        TARGET_GROUPS
+            .iter()
+            .find(|(group, _)| *group == self.target)
+            .map(|(_, prefixes)| prefixes.iter().map(|prefix| prefix.to_string()).collect())
+            .unwrap_or_else(|| vec![self.target.clone()])
+    }
+
+    /// Returns whether events of `target` are routed to the file.
+    pub fn matches(prefixes: &[String], target: &str) -> bool {
+        prefixes.iter().any(|prefix| {
+            target
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+    }
+}
+
+fn parse_route(route: &str) -> Result<LogRoute, String> {
+    match route.split_once('=') {
+        Some((target, path)) if !target.is_empty() && !path.is_empty() => {
+            Ok(LogRoute { target: target.to_string(), path: path.into() })
+        }
+        _ => Err(format!("expected TARGET=PATH, got `{route}`")),
+    }
+}
+
+/// Command line arguments of the JSON logs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Logging")]
+pub struct JsonLogArgs {
+    /// Format of the logs printed to stdout
+    #[arg(
+        long = "log.format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = LogOutputFormat::Terminal,
+        global = true
+    )]
+    pub format: LogOutputFormat,
+
+    /// Append the events of a target group (`engine`, `executor`, `pool`) or target prefix to a
+    /// file as JSON, e.g. `engine=/var/log/bera/engine.json`. Can be repeated
+    #[arg(
+        long = "log.route",
+        value_name = "TARGET=PATH",
+        value_parser = parse_route,
+        global = true
+    )]
+    pub routes: Vec<LogRoute>,
+}
+
+/// Returns the stable name of the field `name` of an event or span of `target`.
+pub fn stable_field<'a>(target: &str, name: &'a str) -> &'a str {
+    let pool =
+        ["txpool", "transaction_pool", "net::tx"].iter().any(|pool| target.starts_with(pool));
+    match name {
+        "hash" if pool => "tx_hash",
+        "hash" | "blockhash" | "block_hash" => "block_hash",
+        "transaction_hash" | "tx" | "txhash" => "tx_hash",
+        "peer" | "peer_id" | "remote_peer_id" => "peer_id",
+        "id" if target.starts_with("payload_builder") => "payload_id",
+        "payload" | "payloadid" => "payload_id",
+        name => name,
+    }
+}
+
+/// Fields of an event or span, under their stable names.
+#[derive(Debug, Default)]
+struct JsonFields {
+    target: &'static str,
+    fields: Map<String, Value>,
+}
+
+impl JsonFields {
+    fn new(metadata: &'static Metadata<'static>) -> Self {
+        Self { target: metadata.target(), fields: Map::new() }
+    }
+
+    fn insert(&mut self, field: &Field, value: Value) {
+        self.fields.insert(stable_field(self.target, field.name()).to_string(), value);
+    }
+}
+
+impl Visit for JsonFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{value:?}").into());
+    }
+}
+
+/// Layer writing events as JSON lines to `writer`.
+pub struct JsonLayer<W> {
+    writer: Mutex<W>,
+}
+
+impl<W> fmt::Debug for JsonLayer<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLayer").finish_non_exhaustive()
+    }
+}
+
+impl<W: Write> JsonLayer<W> {
+    /// Creates a layer writing to `writer`.
+    pub const fn new(writer: W) -> Self {
+        Self { writer: Mutex::new(writer) }
+    }
+}
+
+impl JsonLayer<LineWriter<File>> {
+    /// Creates a layer appending to the file of `route`.
+    pub fn file(route: &LogRoute) -> eyre::Result<Self> {
+        if let Some(dir) = route.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&route.path)
+            .map_err(|err| eyre::eyre!("failed to open {}: {err}", route.path.display()))?;
+        Ok(Self::new(LineWriter::new(file)))
+    }
+}
+
+/// Returns the JSON line of an event with `fields` inside spans named `spans`.
+fn format_line(
+    metadata: &Metadata<'_>,
+    spans: Vec<Value>,
+    mut fields: Map<String, Value>,
+) -> String {
+    let mut timestamp = String::new();
+    let _ = SystemTime.format_time(&mut Writer::new(&mut timestamp));
+    let mut line = Map::new();
+    line.insert("timestamp".into(), timestamp.into());
+    line.insert("level".into(), metadata.level().as_str().into());
+    line.insert("target".into(), metadata.target().into());
+    if let Some(message) = fields.remove("message") {
+        line.insert("message".into(), message);
+    }
+    if !spans.is_empty() {
+        line.insert("spans".into(), spans.into());
+    }
+    for (name, value) in fields {
+        line.entry(name).or_insert(value);
+    }
+    Value::Object(line).to_string()
+}
+
+impl<S, W> Layer<S> for JsonLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: Write + Send + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = JsonFields::new(attrs.metadata());
+        attrs.record(&mut fields);
+        span.extensions_mut().replace(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        if let Some(fields) = span.extensions_mut().get_mut::<JsonFields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut spans = Vec::new();
+        let mut fields = Map::new();
+        // fields of inner spans and of the event take precedence
+        for span in ctx.event_scope(event).into_iter().flat_map(|scope| scope.from_root()) {
+            spans.push(Value::from(span.name()));
+            if let Some(span_fields) = span.extensions().get::<JsonFields>() {
+                fields.extend(span_fields.fields.clone());
+            }
+        }
+        let mut event_fields = JsonFields::new(event.metadata());
+        event.record(&mut event_fields);
+        fields.extend(event_fields.fields);
+
+        let line = format_line(event.metadata(), spans, fields);
+        let mut writer = self.writer.lock().expect("not poisoned");
+        let _ = writeln!(writer, "{line}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_fields() {
+        assert_eq!(stable_field("rpc::engine", "block_hash"), "block_hash");
+        assert_eq!(stable_field("reth::cli", "hash"), "block_hash");
+        assert_eq!(stable_field("txpool", "hash"), "tx_hash");
+        assert_eq!(stable_field("net::tx", "hash"), "tx_hash");
+        assert_eq!(stable_field("net::peers", "peer"), "peer_id");
+        assert_eq!(stable_field("payload_builder", "id"), "payload_id");
+        assert_eq!(stable_field("net::peers", "id"), "id");
+        assert_eq!(stable_field("executor", "gas_used"), "gas_used");
+    }
+
+    #[test]
+    fn test_routes() {
+        let route = parse_route("engine=/var/log/bera/engine.json").unwrap();
+        assert_eq!(route.path, PathBuf::from("/var/log/bera/engine.json"));
+        let prefixes = route.prefixes();
+        assert!(LogRoute::matches(&prefixes, "rpc::engine"));
+        assert!(LogRoute::matches(&prefixes, "engine::tree"));
+        assert!(!LogRoute::matches(&prefixes, "engine_auth"));
+        assert!(!LogRoute::matches(&prefixes, "txpool"));
+
+        let custom = parse_route("net::peers=peers.json").unwrap();
+        assert_eq!(custom.prefixes(), vec!["net::peers".to_string()]);
+        assert!(parse_route("engine").is_err());
+        assert!(parse_route("=engine.json").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! Log verbosity that can be raised per target at runtime
+
+use crate::node::{
+    json_logs::{JsonLayer, JsonLogArgs, LogOutputFormat, LogRoute},
+    otlp::{self, OtlpArgs, OtlpGuard},
+};
+use reth_node_core::args::LogArgs;
+use reth_tracing::{FileWorkerGuard, Layers};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry,
+    filter::{FilterExt, filter_fn},
+    reload,
+};
+
+/// Per-target verbosity overrides of the stdout layer installed by [`init_tracing`].
+#[derive(Debug)]
+pub struct LogLevelOverrides {
+    handle: reload::Handle<EnvFilter, Registry>,
+    levels: Mutex<BTreeMap<String, LevelFilter>>,
+}
+
+impl LogLevelOverrides {
+    /// Sets the verbosity of `target` and its children. `off` removes the override.
+    pub fn set(&self, target: &str, level: LevelFilter) -> eyre::Result<()> {
+        eyre::ensure!(
+            !target.is_empty() && !target.contains([',', '=', '[', ']']),
+            "invalid log target `{target}`"
+        );
+        let mut levels = self.levels.lock().expect("not poisoned");
+        if level == LevelFilter::OFF {
+            levels.remove(target);
+        } else {
+            levels.insert(target.to_string(), level);
+        }
+        self.handle.reload(filter(&levels))?;
+        Ok(())
+    }
+
+    /// Returns the current overrides.
+    pub fn levels(&self) -> BTreeMap<String, LevelFilter> {
+        self.levels.lock().expect("not poisoned").clone()
+    }
+}
+
+/// Builds the filter enabling exactly the overridden targets.
+fn filter(levels: &BTreeMap<String, LevelFilter>) -> EnvFilter {
+    levels.iter().fold(EnvFilter::new("off"), |filter, (target, level)| {
+        filter.add_directive(format!("{target}={level}").parse().expect("validated target"))
+    })
+}
+
+/// Initializes tracing like reth does, plus the stdout layer serving the runtime overrides, the
+/// JSON logs and the optional span exporter. Returns the overrides of that layer, to be handed to
+/// the node serving `admin_setLogLevel`.
+///
+/// Must run before reth initializes tracing, which then keeps this subscriber.
+pub fn init_tracing(
+    logs: &LogArgs,
+    json: &JsonLogArgs,
+    otlp: &OtlpArgs,
+) -> eyre::Result<(Arc<LogLevelOverrides>, Option<FileWorkerGuard>, Option<OtlpGuard>)> {
+    let verbosity = logs.verbosity.directive().to_string().parse().unwrap_or(LevelFilter::INFO);
+    let (reloadable, handle) = reload::Layer::new(EnvFilter::new("off"));
+    let overrides = Arc::new(LogLevelOverrides { handle, levels: Default::default() });
+
+    let mut logs = logs.clone();
+    let mut layers = Layers::new();
+    match json.format {
+        LogOutputFormat::Terminal => {
+            // Events up to the configured verbosity are already printed by reth's stdout layer
+            let more_verbose =
+                filter_fn(move |metadata| LevelFilter::from_level(*metadata.level()) > verbosity);
+            layers.add_layer(
+                tracing_subscriber::fmt::layer().with_filter(reloadable.and(more_verbose)),
+            );
+        }
+        LogOutputFormat::Json => {
+            let configured = EnvFilter::builder()
+                .with_default_directive(verbosity.into())
+                .parse_lossy(&logs.log_stdout_filter);
+            logs.log_stdout_filter = "off".to_string();
+            layers.add_layer(
+                JsonLayer::new(std::io::stdout()).with_filter(configured.or(reloadable)),
+            );
+        }
+    }
+    for route in &json.routes {
+        let prefixes = route.prefixes();
+        let routed = filter_fn(move |metadata| {
+            LevelFilter::from_level(*metadata.level()) <= verbosity &&
+                LogRoute::matches(&prefixes, metadata.target())
+        });
+        layers.add_layer(JsonLayer::file(route)?.with_filter(routed));
+    }
+    let otlp = otlp::add_span_layer(otlp, &mut layers)?;
+    Ok((overrides, logs.init_tracing_with_layers(layers)?, otlp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_remove_overrides() {
+        let (_layer, handle) = reload::Layer::new(EnvFilter::new("off"));
+        let overrides = LogLevelOverrides { handle, levels: Default::default() };
+        assert_eq!(filter(&overrides.levels()).max_level_hint(), Some(LevelFilter::OFF));
+
+        overrides.set("executor", LevelFilter::DEBUG).unwrap();
+        overrides.set("payload_builder", LevelFilter::TRACE).unwrap();
+        assert_eq!(overrides.levels().len(), 2);
+        assert_eq!(filter(&overrides.levels()).max_level_hint(), Some(LevelFilter::TRACE));
+
+        overrides.set("payload_builder", LevelFilter::OFF).unwrap();
+        assert_eq!(
+            overrides.levels(),
+            BTreeMap::from([("executor".to_string(), LevelFilter::DEBUG)])
+        );
+
+        assert!(overrides.set("", LevelFilter::DEBUG).is_err());
+        assert!(overrides.set("executor=trace", LevelFilter::DEBUG).is_err());
+    }
+}
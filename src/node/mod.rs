@@ -1,25 +1,54 @@
 //! Berachain node implementation using Reth's component-based architecture
 
+pub mod allocator;
+pub mod args;
+pub mod caches;
+pub mod canonical;
+pub mod config;
+pub mod datadir;
+pub mod debug_bundle;
 pub mod evm;
+pub mod exex;
+pub mod fee_history;
+pub mod fee_market;
+pub mod health;
+pub mod json_logs;
+pub mod logging;
+pub mod otlp;
+pub mod runtime;
+pub mod shutdown;
+pub mod standby;
+pub mod startup_report;
+pub mod state_root_sampling;
+pub mod supervisor;
+pub mod sync;
+pub mod sync_progress;
 
 use crate::{
     chainspec::BerachainChainSpec,
-    consensus::BerachainConsensusBuilder,
+    consensus::{BerachainConsensusBuilder, quarantine::BadBlocks},
     engine::{
-        BerachainEngineTypes, builder::BerachainPayloadServiceBuilder,
-        rpc::BerachainEngineApiBuilder, validator::BerachainEngineValidatorBuilder,
+        BerachainEngineTypes, builder::BerachainPayloadServiceBuilder, latency::PayloadLatency,
+        report::BuilderReports, validator::BerachainEngineValidatorBuilder,
+    },
+    network::BerachainNetworkBuilder,
+    node::{
+        canonical::CanonicalChanges, config::BerachainConfig, evm::BerachainExecutorBuilder,
+        fee_history::FeeHistoryRows, logging::LogLevelOverrides, sync_progress::SyncProgress,
     },
-    node::evm::BerachainExecutorBuilder,
     pool::BerachainPoolBuilder,
     primitives::{BerachainHeader, BerachainPrimitives},
-    rpc::{BerachainAddOns, BerachainEthApiBuilder},
-    transaction::BerachainTxEnvelope,
+    rpc::{BerachainAddOns, BerachainEthApiBuilder, inflight::InflightCalls},
+    storage::{
+        BerachainStorage,
+        header_cache::{DEFAULT_HEADER_CACHE_SIZE, SealedHeaderCache},
+    },
+    transaction::{BerachainTxEnvelope, POL_TX_TYPE},
 };
 use alloy_consensus::error::ValueError;
 use alloy_rpc_types::TransactionRequest;
 use reth::{
     api::{BlockTy, FullNodeTypes, NodeTypes},
-    providers::EthStorage,
     rpc::compat::TryIntoSimTx,
 };
 use reth_engine_local::LocalPayloadAttributesBuilder;
@@ -28,29 +57,200 @@ use reth_node_builder::{
     DebugNode, Node, NodeAdapter, NodeComponentsBuilder,
     components::{BasicPayloadServiceBuilder, ComponentsBuilder},
 };
-use reth_node_ethereum::{EthereumNode, node::EthereumNetworkBuilder};
+use reth_node_ethereum::EthereumNode;
 use reth_payload_primitives::{PayloadAttributesBuilder, PayloadTypes};
 use std::sync::Arc;
 
 /// Type configuration for a regular Berachain node.
-
+///
+/// Carries the Berachain configuration its components and add-ons are built with, and the state
+/// they share: the [`BadBlocks`] quarantine of consensus, the engine validator and
+/// `admin_badBlocks`, the [`PayloadLatency`] breakdown of the executor and the engine validator,
+/// the [`LogLevelOverrides`] of `admin_setLogLevel`, the [`CanonicalChanges`] of
+/// `bera_subscribeCanonicalChanges`, the [`SyncProgress`] of `bera_syncStatus`, the
+/// [`BuilderReports`] of the payload builder and `bera_builderReport`, the [`InflightCalls`] of the
+/// RPC middleware, the database reader monitor and `admin_longRunningCalls`, the
+/// [`FeeHistoryRows`] of `eth_feeHistory`, and the [`SealedHeaderCache`] of the engine validator
+/// and the RPC. Every node gets its own, so several nodes can run in one process.
 #[derive(Debug, Default, Clone)]
 #[non_exhaustive]
-pub struct BerachainNode;
+pub struct BerachainNode {
+    config: BerachainConfig,
+    bad_blocks: Arc<BadBlocks>,
+    latency: Arc<PayloadLatency>,
+    log_levels: Option<Arc<LogLevelOverrides>>,
+    canonical_changes: Arc<CanonicalChanges>,
+    sync_progress: Arc<SyncProgress>,
+    builder_reports: Arc<BuilderReports>,
+    inflight_calls: Arc<InflightCalls>,
+    fee_history: Arc<FeeHistoryRows>,
+    header_cache: Arc<SealedHeaderCache>,
+}
 
-// Same as ETH Except we use BerachainChainSpec
+// Same as ETH Except we use BerachainChainSpec and BerachainStorage
 impl NodeTypes for BerachainNode {
     type Primitives = BerachainPrimitives;
     type ChainSpec = BerachainChainSpec;
     type StateCommitment = <EthereumNode as NodeTypes>::StateCommitment;
-    type Storage = EthStorage<BerachainTxEnvelope, BerachainHeader>;
+    type Storage = BerachainStorage;
     type Payload = BerachainEngineTypes;
 }
 
+/// Reth SDK ComponentsBuilder of a stock Berachain node, defining the core node architecture.
+///
+/// Each component handles a specific domain of blockchain node operations:
+///
+/// - **BerachainPoolBuilder**: Transaction pool management and validation
+///   - Maintains mempool of pending transactions
+///   - Validates transactions according to chain rules
+///   - Provides transactions for block building
+///
+/// - **`BasicPayloadServiceBuilder<BerachainPayloadServiceBuilder>`**: Block building and payload
+///   creation
+///   - Triggered by Engine API `forkchoice_updated` calls from consensus layer
+///   - Assembles transactions from pool into block payloads
+///   - Handles payload building jobs and manages build timeouts
+///   - Uses BerachainPayloadBuilder for Berachain-specific block construction
+///
+/// - **BerachainNetworkBuilder**: P2P networking and peer management
+///   - Handles block/transaction propagation via devp2p with Berachain headers and PoL transactions
+///   - Manages peer connections and discovery
+///   - Synchronizes blockchain state with network peers
+///
+/// - **BerachainExecutorBuilder**: EVM execution environment
+///   - Creates standard Ethereum EVM with Berachain chain specification
+///   - Executes transactions and manages state transitions
+///   - Handles hardfork logic including Prague1 minimum base fee
+///
+/// - **BerachainConsensusBuilder**: Block validation and consensus rules
+///   - Validates block headers, transactions, and state transitions
+///   - Enforces Ethereum consensus rules with Berachain extensions
+///   - Manages fork choice and canonical chain determination
+pub type BerachainComponentsBuilder<N> = ComponentsBuilder<
+    N,
+    BerachainPoolBuilder,
+    BasicPayloadServiceBuilder<BerachainPayloadServiceBuilder>,
+    BerachainNetworkBuilder,
+    BerachainExecutorBuilder,
+    BerachainConsensusBuilder,
+>;
+
+impl BerachainNode {
+    /// Creates a node whose components and add-ons are built with `config`.
+    pub fn new(config: BerachainConfig) -> Self {
+        let header_cache_size =
+            config.caches.header_cache_size.unwrap_or(DEFAULT_HEADER_CACHE_SIZE);
+        Self {
+            config,
+            header_cache: Arc::new(SealedHeaderCache::new(header_cache_size)),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the Berachain configuration of the node.
+    pub fn config(&self) -> &BerachainConfig {
+        &self.config
+    }
+
+    /// Returns the quarantine of blocks that failed Berachain-specific validation.
+    pub fn bad_blocks(&self) -> &Arc<BadBlocks> {
+        &self.bad_blocks
+    }
+
+    /// Returns the latency breakdown of `engine_newPayload`.
+    pub fn latency(&self) -> &Arc<PayloadLatency> {
+        &self.latency
+    }
+
+    /// Serves `admin_setLogLevel` with `log_levels`, as returned by [`logging::init_tracing`].
+    pub fn with_log_levels(mut self, log_levels: Arc<LogLevelOverrides>) -> Self {
+        self.log_levels = Some(log_levels);
+        self
+    }
+
+    /// Returns the log verbosity overrides, if tracing was initialized by bera-reth.
+    pub fn log_levels(&self) -> Option<&Arc<LogLevelOverrides>> {
+        self.log_levels.as_ref()
+    }
+
+    /// Returns the broadcast of canonical chain changes, fed once the add-ons are launched.
+    pub fn canonical_changes(&self) -> &Arc<CanonicalChanges> {
+        &self.canonical_changes
+    }
+
+    /// Returns the sync progress of the pipeline stages, sampled once the add-ons are launched.
+    pub fn sync_progress(&self) -> &Arc<SyncProgress> {
+        &self.sync_progress
+    }
+
+    /// Returns the inclusion reports of the payloads built by the node.
+    pub fn builder_reports(&self) -> &Arc<BuilderReports> {
+        &self.builder_reports
+    }
+
+    /// Returns the expensive RPC calls in flight.
+    pub fn inflight_calls(&self) -> &Arc<InflightCalls> {
+        &self.inflight_calls
+    }
+
+    /// Returns the fee history rows, restored and kept up to date once the node is launched.
+    pub fn fee_history(&self) -> &Arc<FeeHistoryRows> {
+        &self.fee_history
+    }
+
+    /// Returns the sealed header cache of the engine validator and the RPC.
+    pub fn header_cache(&self) -> &Arc<SealedHeaderCache> {
+        &self.header_cache
+    }
+
+    /// Returns the components builder of a stock Berachain node.
+    ///
+    /// This is the extension point for chains reusing bera-reth as a library: any component can be
+    /// replaced with the `ComponentsBuilder` setters (`pool`, `executor`, `payload`, `network`,
+    /// `consensus`) while the others keep their Berachain implementation.
+    ///
+    /// ```ignore
+    /// let node = BerachainNode::new(config);
+    /// let handle = builder
+    ///     .with_types::<BerachainNode>()
+    ///     .with_components(node.components().pool(MyPoolBuilder))
+    ///     .with_add_ons(BerachainAddOns::new(&node))
+    ///     .launch_with_debug_capabilities()
+    ///     .await?;
+    /// ```
+    ///
+    /// See `examples/custom_pool.rs` for a complete node.
+    pub fn components<N>(&self) -> BerachainComponentsBuilder<N>
+    where
+        N: FullNodeTypes<Types = Self>,
+    {
+        ComponentsBuilder::default()
+            .node_types()
+            .pool(BerachainPoolBuilder::new(&self.config))
+            .executor(BerachainExecutorBuilder { latency: self.latency.clone() })
+            .payload(BasicPayloadServiceBuilder::new(
+                BerachainPayloadServiceBuilder::new(&self.config)
+                    .with_reports(self.builder_reports.clone()),
+            ))
+            .network(BerachainNetworkBuilder::new(&self.config))
+            .consensus(BerachainConsensusBuilder { bad_blocks: self.bad_blocks.clone() })
+    }
+}
+
+/// Builds the Ethereum transaction of an `eth_simulateV1` call with a placeholder signature.
+///
+/// PoL transactions are inserted by the block proposer and executed as a system call in every
+/// simulated Prague1 block, so requests for one are rejected.
 impl TryIntoSimTx<BerachainTxEnvelope> for TransactionRequest {
     fn try_into_sim_tx(self) -> Result<BerachainTxEnvelope, ValueError<Self>> {
-        // TODO: Add support for simulation API
-        Err(ValueError::new(self, "Simulation API is not supported on bera-reth yet"))
+        if self.transaction_type == Some(POL_TX_TYPE) {
+            return Err(ValueError::new(
+                self,
+                "PoL transactions (type 0x7e) are inserted by the block proposer and cannot be \
+                 simulated",
+            ))
+        }
+        self.build_typed_simulate_transaction().map(BerachainTxEnvelope::Ethereum)
     }
 }
 
@@ -58,44 +258,8 @@ impl<N> Node<N> for BerachainNode
 where
     N: FullNodeTypes<Types = Self>,
 {
-    /// Reth SDK ComponentsBuilder defining the core node architecture.
-    ///
-    /// Each component handles a specific domain of blockchain node operations:
-    ///
-    /// - **EthereumPoolBuilder**: Transaction pool management and validation
-    ///   - Maintains mempool of pending transactions
-    ///   - Validates transactions according to chain rules
-    ///   - Provides transactions for block building
-    ///
-    /// - **`BasicPayloadServiceBuilder<BerachainPayloadServiceBuilder>`**: Block building and
-    ///   payload creation
-    ///   - Triggered by Engine API `forkchoice_updated` calls from consensus layer
-    ///   - Assembles transactions from pool into block payloads
-    ///   - Handles payload building jobs and manages build timeouts
-    ///   - Uses BerachainPayloadBuilder for Berachain-specific block construction
-    ///
-    /// - **EthereumNetworkBuilder**: P2P networking and peer management
-    ///   - Handles block/transaction propagation via devp2p
-    ///   - Manages peer connections and discovery
-    ///   - Synchronizes blockchain state with network peers
-    ///
-    /// - **BerachainExecutorBuilder**: EVM execution environment
-    ///   - Creates standard Ethereum EVM with Berachain chain specification
-    ///   - Executes transactions and manages state transitions
-    ///   - Handles hardfork logic including Prague1 minimum base fee
-    ///
-    /// - **EthereumConsensusBuilder**: Block validation and consensus rules
-    ///   - Validates block headers, transactions, and state transitions
-    ///   - Enforces Ethereum consensus rules with Berachain extensions
-    ///   - Manages fork choice and canonical chain determination
-    type ComponentsBuilder = ComponentsBuilder<
-        N,
-        BerachainPoolBuilder,
-        BasicPayloadServiceBuilder<BerachainPayloadServiceBuilder>,
-        EthereumNetworkBuilder,
-        BerachainExecutorBuilder,
-        BerachainConsensusBuilder,
-    >;
+    /// See [`BerachainComponentsBuilder`].
+    type ComponentsBuilder = BerachainComponentsBuilder<N>;
 
     /// Reth SDK AddOns providing RPC and Engine API interfaces.
     ///
@@ -112,19 +276,11 @@ where
     >;
 
     fn components_builder(&self) -> Self::ComponentsBuilder {
-        ComponentsBuilder::default()
-            .node_types()
-            .pool(BerachainPoolBuilder)
-            .executor(BerachainExecutorBuilder)
-            .payload(BasicPayloadServiceBuilder::new(BerachainPayloadServiceBuilder::default()))
-            .network(EthereumNetworkBuilder::default())
-            .consensus(BerachainConsensusBuilder)
+        self.components()
     }
 
     fn add_ons(&self) -> Self::AddOns {
-        BerachainAddOns::default()
-            .with_engine_validator(BerachainEngineValidatorBuilder::default())
-            .with_engine_api(BerachainEngineApiBuilder::<BerachainEngineValidatorBuilder>::default())
+        BerachainAddOns::new(self)
     }
 }
 
@@ -145,3 +301,35 @@ where
         LocalPayloadAttributesBuilder::new(Arc::new(chain_spec.clone()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{Transaction, TxType};
+    use alloy_primitives::{Address, U256};
+
+    #[test]
+    fn test_sim_tx_from_request() {
+        let request = TransactionRequest::default()
+            .to(Address::repeat_byte(0x11))
+            .value(U256::from(7))
+            .max_fee_per_gas(2_000_000_000)
+            .max_priority_fee_per_gas(1)
+            .gas_limit(21_000)
+            .nonce(3);
+        let BerachainTxEnvelope::Ethereum(tx) = request.try_into_sim_tx().unwrap() else {
+            panic!("expected an Ethereum transaction")
+        };
+        assert_eq!(tx.tx_type(), TxType::Eip1559);
+        assert_eq!(tx.nonce(), 3);
+        assert_eq!(tx.value(), U256::from(7));
+    }
+
+    #[test]
+    fn test_pol_sim_tx_is_rejected() {
+        let request =
+            TransactionRequest { transaction_type: Some(POL_TX_TYPE), ..Default::default() };
+        let err = request.try_into_sim_tx().unwrap_err();
+        assert!(err.to_string().contains("PoL"), "{err}");
+    }
+}
@@ -0,0 +1,114 @@
+//! Export of spans to an OpenTelemetry collector
+
+use clap::Args;
+use reth_tracing::Layers;
+
+/// Service name reported to the collector by default.
+pub const DEFAULT_SERVICE_NAME: &str = "bera-reth";
+
+/// Spans exported by default: Engine API calls and expensive RPC calls.
+pub const DEFAULT_FILTER: &str = "rpc::engine=debug,rpc::server=debug";
+
+/// Command line arguments of the OTLP span exporter.
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Tracing")]
+pub struct OtlpArgs {
+    /// OTLP/HTTP endpoint to export spans to, e.g. `http://localhost:4318/v1/traces`. Requires
+    /// the `otlp` feature
+    #[arg(long = "tracing.otlp-endpoint", value_name = "URL", global = true)]
+    pub endpoint: Option<String>,
+
+    /// Service name reported to the collector
+    #[arg(
+        long = "tracing.otlp-service-name",
+        value_name = "NAME",
+        default_value = DEFAULT_SERVICE_NAME,
+        global = true
+    )]
+    pub service_name: String,
+
+    /// Spans to export, in `RUST_LOG` syntax
+    #[arg(
+        long = "tracing.otlp-filter",
+        value_name = "FILTER",
+        default_value = DEFAULT_FILTER,
+        global = true
+    )]
+    pub filter: String,
+}
+
+/// Flushes the exported spans when dropped.
+#[derive(Debug)]
+pub struct OtlpGuard {
+    #[cfg(feature = "otlp")]
+    provider: opentelemetry_sdk::trace::SdkTracerProvider,
+}
+
+impl Drop for OtlpGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if let Err(err) = self.provider.shutdown() {
+            eprintln!("Failed to flush exported spans: {err}");
+        }
+    }
+}
+
+/// Adds the span exporter to `layers` if an endpoint is configured.
+pub fn add_span_layer(args: &OtlpArgs, layers: &mut Layers) -> eyre::Result<Option<OtlpGuard>> {
+    let Some(endpoint) = &args.endpoint else { return Ok(None) };
+    #[cfg(feature = "otlp")]
+    {
+        exporter::add_span_layer(endpoint, args, layers).map(Some)
+    }
+    #[cfg(not(feature = "otlp"))]
+    {
+        let _ = layers;
+        eyre::bail!("cannot export spans to {endpoint}: bera-reth was built without `otlp`")
+    }
+}
+
+#[cfg(feature = "otlp")]
+mod exporter {
+    use super::{OtlpArgs, OtlpGuard};
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::{Resource, trace::SdkTracerProvider};
+    use reth_tracing::Layers;
+    use tracing_subscriber::{EnvFilter, Layer};
+
+    pub(super) fn add_span_layer(
+        endpoint: &str,
+        args: &OtlpArgs,
+        layers: &mut Layers,
+    ) -> eyre::Result<OtlpGuard> {
+        let filter = EnvFilter::try_new(&args.filter)?;
+        let exporter = SpanExporter::builder().with_http().with_endpoint(endpoint).build()?;
+        let provider = SdkTracerProvider::builder()
+            .with_resource(Resource::builder().with_service_name(args.service_name.clone()).build())
+            .with_batch_exporter(exporter)
+            .build();
+        let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+        layers.add_layer(tracing_opentelemetry::layer().with_tracer(tracer).with_filter(filter));
+        Ok(OtlpGuard { provider })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        otlp: OtlpArgs,
+    }
+
+    #[test]
+    fn test_exporter_is_disabled_by_default() {
+        let args = Cli::parse_from(["bera-reth"]).otlp;
+        assert_eq!(args.endpoint, None);
+        assert_eq!(args.service_name, DEFAULT_SERVICE_NAME);
+        assert!(add_span_layer(&args, &mut Layers::new()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,114 @@
+//! Thread pool sizing of the node process
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+/// Default maximum number of blocking threads.
+pub const DEFAULT_BLOCKING_THREADS: usize = 64;
+
+/// Thread pool settings. Unset values use the Berachain defaults described in the module docs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RuntimeConfig {
+    /// Number of tokio worker threads.
+    pub tokio_workers: Option<usize>,
+    /// Maximum number of tokio blocking threads.
+    pub blocking_threads: Option<usize>,
+    /// Number of threads of the global rayon pool.
+    pub rayon_threads: Option<usize>,
+}
+
+impl RuntimeConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &RuntimeArgs) -> Self {
+        if let Some(tokio_workers) = args.tokio_workers {
+            self.tokio_workers = Some(tokio_workers);
+        }
+        if let Some(blocking_threads) = args.blocking_threads {
+            self.blocking_threads = Some(blocking_threads);
+        }
+        if let Some(rayon_threads) = args.rayon_threads {
+            self.rayon_threads = Some(rayon_threads);
+        }
+        self
+    }
+
+    /// Resolves the pool sizes for a machine with `cores` cores.
+    pub fn sizes(&self, cores: usize) -> RuntimeSizes {
+        let cores = cores.max(1);
+        RuntimeSizes {
+            tokio_workers: self.tokio_workers.unwrap_or(cores).max(1),
+            blocking_threads: self.blocking_threads.unwrap_or(DEFAULT_BLOCKING_THREADS).max(1),
+            rayon_threads: self.rayon_threads.unwrap_or(cores.saturating_sub(1)).max(1),
+        }
+    }
+
+    /// Builds the tokio runtime and the global rayon pool.
+    pub fn build(&self) -> eyre::Result<tokio::runtime::Runtime> {
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+        let sizes = self.sizes(cores);
+
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(sizes.rayon_threads)
+            .thread_name(|index| format!("rayon-{index}"))
+            .build_global()?;
+
+        Ok(tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(sizes.tokio_workers)
+            .max_blocking_threads(sizes.blocking_threads)
+            .enable_all()
+            .build()?)
+    }
+}
+
+/// Resolved thread pool sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeSizes {
+    /// Number of tokio worker threads.
+    pub tokio_workers: usize,
+    /// Maximum number of tokio blocking threads.
+    pub blocking_threads: usize,
+    /// Number of threads of the global rayon pool.
+    pub rayon_threads: usize,
+}
+
+/// Command line arguments of the thread pool sizing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Runtime")]
+pub struct RuntimeArgs {
+    /// Number of tokio worker threads [default: number of cores]
+    #[arg(long = "runtime.tokio-workers", value_name = "THREADS")]
+    pub tokio_workers: Option<usize>,
+
+    /// Maximum number of tokio blocking threads [default: 64]
+    #[arg(long = "runtime.blocking-threads", value_name = "THREADS")]
+    pub blocking_threads: Option<usize>,
+
+    /// Number of rayon threads for sender recovery and state roots [default: cores - 1]
+    #[arg(long = "runtime.rayon-threads", value_name = "THREADS")]
+    pub rayon_threads: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_berachain_defaults() {
+        let sizes = RuntimeConfig::default().sizes(4);
+        assert_eq!(
+            sizes,
+            RuntimeSizes { tokio_workers: 4, blocking_threads: 64, rayon_threads: 3 }
+        );
+
+        let sizes = RuntimeConfig::default().sizes(1);
+        assert_eq!(sizes.rayon_threads, 1);
+
+        let config = RuntimeConfig { rayon_threads: Some(8), ..Default::default() }
+            .with_args(&RuntimeArgs { tokio_workers: Some(2), ..Default::default() });
+        assert_eq!(
+            config.sizes(4),
+            RuntimeSizes { tokio_workers: 2, blocking_threads: 64, rayon_threads: 8 }
+        );
+    }
+}
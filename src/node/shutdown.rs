@@ -0,0 +1,60 @@
+//! Graceful shutdown hooks
+
+use alloy_eips::BlockNumHash;
+use reth::{
+    providers::{
+        BlockIdReader, BlockNumReader, ChainStateBlockWriter, DBProvider, DatabaseProviderFactory,
+    },
+    tasks::TaskExecutor,
+};
+use tracing::{error, info};
+
+/// Spawns a task that persists the forkchoice once the node starts shutting down gracefully.
+///
+/// The shutdown waits for the hook to finish.
+pub fn install_shutdown_hooks<P>(provider: P, executor: &TaskExecutor)
+where
+    P: BlockIdReader
+        + DatabaseProviderFactory<Provider: BlockNumReader, ProviderRW: ChainStateBlockWriter>
+        + Send
+        + Sync
+        + 'static,
+{
+    executor.spawn_critical_with_graceful_shutdown_signal(
+        "berachain shutdown hooks",
+        |shutdown| async move {
+            let _guard = shutdown.await;
+            match persist_forkchoice(&provider) {
+                Ok(()) => info!(target: "reth::cli", "Persisted forkchoice state on shutdown"),
+                Err(err) => {
+                    error!(target: "reth::cli", %err, "Failed to persist forkchoice state on shutdown")
+                }
+            }
+        },
+    );
+}
+
+/// Records the last safe and finalized blocks, capped at the last persisted block.
+pub fn persist_forkchoice<P>(provider: &P) -> eyre::Result<()>
+where
+    P: BlockIdReader
+        + DatabaseProviderFactory<Provider: BlockNumReader, ProviderRW: ChainStateBlockWriter>,
+{
+    let last_persisted = provider.database_provider_ro()?.last_block_number()?;
+    let capped = |block: Option<BlockNumHash>| block.map(|block| block.number.min(last_persisted));
+
+    let finalized = capped(provider.finalized_block_num_hash()?);
+    let safe = capped(provider.safe_block_num_hash()?);
+
+    let provider_rw = provider.database_provider_rw()?;
+    if let Some(finalized) = finalized {
+        provider_rw.save_finalized_block_number(finalized)?;
+    }
+    if let Some(safe) = safe {
+        provider_rw.save_safe_block_number(safe)?;
+    }
+    provider_rw.commit()?;
+
+    info!(target: "reth::cli", last_persisted, ?finalized, ?safe, "Saved forkchoice state");
+    Ok(())
+}
@@ -0,0 +1,247 @@
+//! Hot standby following a primary node instead of a consensus client
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    engine::{BerachainEngineTypes, payload::BerachainPayloadAttributes},
+    primitives::{BerachainHeader, BerachainPrimitives},
+    transaction::BerachainTxEnvelope,
+};
+use alloy_consensus::BlockHeader;
+use alloy_primitives::B256;
+use alloy_rpc_types::engine::ForkchoiceState;
+use clap::Args;
+use futures::{Stream, StreamExt};
+use jsonrpsee_core::{client::ClientT, rpc_params};
+use jsonrpsee_http_client::{HttpClient, HttpClientBuilder};
+use reth::tasks::TaskExecutor;
+use reth_engine_local::LocalPayloadAttributesBuilder;
+use reth_engine_primitives::{BeaconConsensusEngineHandle, ConsensusEngineEvent};
+use reth_payload_primitives::{EngineApiMessageVersion, PayloadAttributesBuilder, PayloadTypes};
+use reth_primitives_traits::Block as _;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+use tracing::{debug, info, warn};
+
+/// Default interval between polls of the primary, in milliseconds.
+pub const DEFAULT_POLL_INTERVAL: u64 = 500;
+
+/// Number of forwarded heads remembered to tell the follower's forkchoice updates apart.
+const SENT_HEADS: usize = 64;
+
+/// Settings of the hot standby mode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct StandbyConfig {
+    /// JSON-RPC URL of the primary. Standby mode is disabled if unset.
+    pub primary: Option<String>,
+    /// Interval between polls of the primary, in milliseconds.
+    pub poll_interval: u64,
+    /// Whether to start a payload job on every followed head.
+    pub warm_payloads: bool,
+}
+
+impl Default for StandbyConfig {
+    fn default() -> Self {
+        Self { primary: None, poll_interval: DEFAULT_POLL_INTERVAL, warm_payloads: false }
+    }
+}
+
+impl StandbyConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &StandbyArgs) -> Self {
+        if let Some(primary) = &args.primary {
+            self.primary = Some(primary.clone());
+        }
+        if let Some(poll_interval) = args.poll_interval {
+            self.poll_interval = poll_interval;
+        }
+        self.warm_payloads |= args.warm_payloads;
+        self
+    }
+}
+
+/// Command line arguments of the hot standby mode.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Hot standby")]
+pub struct StandbyArgs {
+    /// Follow the primary serving JSON-RPC over HTTP at this URL
+    #[arg(long = "standby.primary", value_name = "URL")]
+    pub primary: Option<String>,
+
+    /// Interval between polls of the primary, in milliseconds [default: 500]
+    #[arg(long = "standby.poll-interval", value_name = "MS")]
+    pub poll_interval: Option<u64>,
+
+    /// Start a payload job on every followed head to keep the payload builder warm
+    #[arg(long = "standby.warm-payloads")]
+    pub warm_payloads: bool,
+}
+
+/// Heads recently forwarded to the engine by the follower.
+#[derive(Debug, Default)]
+struct SentHeads(VecDeque<B256>);
+
+impl SentHeads {
+    fn insert(&mut self, hash: B256) {
+        if self.0.len() == SENT_HEADS {
+            self.0.pop_front();
+        }
+        self.0.push_back(hash);
+    }
+
+    fn contains(&self, hash: &B256) -> bool {
+        self.0.contains(hash)
+    }
+}
+
+/// Block fields read from the primary for its safe and finalized blocks.
+#[derive(Debug, Deserialize)]
+struct BlockHash {
+    hash: B256,
+}
+
+/// Starts following the configured primary, if any.
+pub fn install_standby<Ev>(
+    config: StandbyConfig,
+    chain_spec: Arc<BerachainChainSpec>,
+    engine: BeaconConsensusEngineHandle<BerachainEngineTypes>,
+    engine_events: Ev,
+    executor: &TaskExecutor,
+) -> eyre::Result<()>
+where
+    Ev: Stream<Item = ConsensusEngineEvent<BerachainPrimitives>> + Send + 'static,
+{
+    let Some(primary) = config.primary else { return Ok(()) };
+    let client = HttpClientBuilder::default()
+        .build(&primary)
+        .map_err(|err| eyre::eyre!("invalid standby primary {primary}: {err}"))?;
+
+    let sent = Arc::new(Mutex::new(SentHeads::default()));
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    let (watch_sent, watch_stopped) = (sent.clone(), stopped.clone());
+    executor.spawn(async move {
+        let mut engine_events = std::pin::pin!(engine_events);
+        while let Some(event) = engine_events.next().await {
+            let ConsensusEngineEvent::ForkchoiceUpdated(state, _) = event else { continue };
+            if !watch_sent.lock().expect("not poisoned").contains(&state.head_block_hash) {
+                info!(
+                    target: "reth::cli",
+                    head = %state.head_block_hash,
+                    "Forkchoice update from a consensus client, no longer following the primary"
+                );
+                watch_stopped.store(true, Ordering::Relaxed);
+                return
+            }
+        }
+    });
+
+    info!(target: "reth::cli", %primary, "Following primary as hot standby");
+    let follower = Follower {
+        client,
+        engine,
+        attributes: config.warm_payloads.then(|| LocalPayloadAttributesBuilder::new(chain_spec)),
+        sent,
+    };
+    let poll_interval = Duration::from_millis(config.poll_interval.max(1));
+    executor.spawn(async move {
+        let mut interval = tokio::time::interval(poll_interval);
+        let mut head = B256::ZERO;
+        while !stopped.load(Ordering::Relaxed) {
+            interval.tick().await;
+            match follower.follow(head).await {
+                Ok(new_head) => head = new_head,
+                Err(err) => warn!(target: "reth::cli", %err, "Failed to follow primary"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Forwards the primary's chain to the local engine.
+struct Follower {
+    client: HttpClient,
+    engine: BeaconConsensusEngineHandle<BerachainEngineTypes>,
+    attributes: Option<LocalPayloadAttributesBuilder<BerachainChainSpec>>,
+    sent: Arc<Mutex<SentHeads>>,
+}
+
+impl Follower {
+    /// Forwards the primary's head if it differs from `head`, returning the forwarded head.
+    async fn follow(&self, head: B256) -> eyre::Result<B256> {
+        let block: Option<alloy_rpc_types::Block<BerachainTxEnvelope, BerachainHeader>> =
+            self.client.request("eth_getBlockByNumber", rpc_params!["latest", true]).await?;
+        let block = block
+            .ok_or_else(|| eyre::eyre!("primary has no latest block"))?
+            .into_consensus_block()
+            .convert_transactions()
+            .seal_slow();
+        if block.hash() == head {
+            return Ok(head)
+        }
+
+        let (hash, number, timestamp) =
+            (block.hash(), block.header().number(), block.header().timestamp());
+        self.sent.lock().expect("not poisoned").insert(hash);
+        let status = self.engine.new_payload(BerachainEngineTypes::block_to_payload(block)).await?;
+        debug!(target: "reth::cli", number, %hash, ?status, "Forwarded block from primary");
+
+        let state = ForkchoiceState {
+            head_block_hash: hash,
+            safe_block_hash: self.block_hash("safe").await?,
+            finalized_block_hash: self.block_hash("finalized").await?,
+        };
+        let attributes: Option<BerachainPayloadAttributes> =
+            self.attributes.as_ref().map(|builder| builder.build(timestamp + 1));
+        self.engine
+            .fork_choice_updated(state, attributes, EngineApiMessageVersion::default())
+            .await?;
+        Ok(hash)
+    }
+
+    /// Returns the hash of the primary's `tag` block, or zero if it has none.
+    async fn block_hash(&self, tag: &str) -> eyre::Result<B256> {
+        let block: Option<BlockHash> =
+            self.client.request("eth_getBlockByNumber", rpc_params![tag, false]).await?;
+        Ok(block.map_or(B256::ZERO, |block| block.hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sent_heads_are_bounded() {
+        let mut sent = SentHeads::default();
+        for i in 0..=SENT_HEADS as u8 {
+            sent.insert(B256::with_last_byte(i));
+        }
+
+        assert!(!sent.contains(&B256::with_last_byte(0)));
+        assert!(sent.contains(&B256::with_last_byte(1)));
+        assert!(sent.contains(&B256::with_last_byte(SENT_HEADS as u8)));
+    }
+
+    #[test]
+    fn test_cli_overrides_file() {
+        let file = StandbyConfig {
+            primary: Some("http://primary:8545".to_string()),
+            warm_payloads: true,
+            ..Default::default()
+        };
+        let args = StandbyArgs { poll_interval: Some(250), ..Default::default() };
+
+        let config = file.with_args(&args);
+        assert_eq!(config.primary.as_deref(), Some("http://primary:8545"));
+        assert_eq!(config.poll_interval, 250);
+        assert!(config.warm_payloads);
+    }
+}
@@ -0,0 +1,161 @@
+//! Summary of the effective node configuration, logged once on startup
+
+use crate::{chainspec::BerachainChainSpec, node::config::BerachainConfig};
+use alloy_primitives::{Address, B256};
+use reth::{
+    chainspec::{ForkCondition, Hardforks},
+    rpc::server_types::RpcModuleSelection,
+};
+use reth_chainspec::EthChainSpec;
+use reth_node_core::node_config::NodeConfig;
+use serde::Serialize;
+use std::path::Path;
+use tracing::info;
+
+/// File name of the report in the datadir.
+pub const STARTUP_REPORT_FILE: &str = "startup-report.json";
+
+/// Effective configuration of a starting node.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupReport {
+    /// bera-reth version.
+    pub version: String,
+    /// Chain name or id.
+    pub chain: String,
+    /// Chain id.
+    pub chain_id: u64,
+    /// Genesis hash, including the Berachain header fields.
+    pub genesis_hash: B256,
+    /// Activation of every configured hardfork, Ethereum and Berachain.
+    pub hardforks: Vec<HardforkActivation>,
+    /// PoL distributor called by the PoL transaction.
+    pub pol_distributor: Address,
+    /// Minimum base fee once Prague1 is active, in wei.
+    pub prague1_minimum_base_fee: u64,
+    /// Pruning configuration, `null` for archive nodes.
+    pub pruning: serde_json::Value,
+    /// Enabled RPC transports and their namespaces.
+    pub rpc: RpcReport,
+    /// Berachain settings from the config file and command line.
+    pub berachain: BerachainConfig,
+}
+
+/// Activation condition of a hardfork.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HardforkActivation {
+    /// Hardfork name.
+    pub name: String,
+    /// Activation condition, e.g. `timestamp 1737381600`.
+    pub activation: String,
+}
+
+/// Enabled RPC transports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcReport {
+    /// Namespaces served over HTTP, if enabled.
+    pub http: Option<Vec<String>>,
+    /// Namespaces served over WebSocket, if enabled.
+    pub ws: Option<Vec<String>>,
+    /// Whether IPC is enabled; it serves all namespaces.
+    pub ipc: bool,
+    /// Engine API address.
+    pub auth: String,
+}
+
+impl StartupReport {
+    /// Collects the report from the node configuration.
+    pub fn new(config: &NodeConfig<BerachainChainSpec>, berachain: &BerachainConfig) -> Self {
+        let chain_spec = &config.chain;
+        let hardforks = chain_spec
+            .forks_iter()
+            .map(|(fork, condition)| HardforkActivation {
+                name: fork.name().to_string(),
+                activation: activation(condition),
+            })
+            .collect();
+
+        let namespaces = |enabled: bool, selection: &Option<RpcModuleSelection>| {
+            enabled.then(|| {
+                let selection = selection.clone().unwrap_or(RpcModuleSelection::Standard);
+                let mut namespaces: Vec<_> =
+                    selection.to_selection().into_iter().map(|module| module.to_string()).collect();
+                namespaces.sort();
+                namespaces
+            })
+        };
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            chain: chain_spec.chain().to_string(),
+            chain_id: chain_spec.chain_id(),
+            genesis_hash: chain_spec.genesis_hash(),
+            hardforks,
+            pol_distributor: chain_spec.pol_contract(),
+            prague1_minimum_base_fee: chain_spec.prague1_minimum_base_fee,
+            pruning: serde_json::to_value(config.prune_config()).unwrap_or_default(),
+            rpc: RpcReport {
+                http: namespaces(config.rpc.http, &config.rpc.http_api),
+                ws: namespaces(config.rpc.ws, &config.rpc.ws_api),
+                ipc: !config.rpc.ipcdisable,
+                auth: format!("{}:{}", config.rpc.auth_addr, config.rpc.auth_port),
+            },
+            berachain: berachain.clone(),
+        }
+    }
+
+    /// Logs the report as a single JSON document.
+    pub fn log(&self) {
+        let report = serde_json::to_string(self).expect("report serializes");
+        info!(target: "reth::cli", %report, "Startup configuration");
+    }
+
+    /// Writes the report to [`STARTUP_REPORT_FILE`] in `data_dir`.
+    pub fn write(&self, data_dir: &Path) -> eyre::Result<()> {
+        let path = data_dir.join(STARTUP_REPORT_FILE);
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .map_err(|err| eyre::eyre!("failed to write {}: {err}", path.display()))
+    }
+}
+
+/// Describes a fork condition.
+fn activation(condition: ForkCondition) -> String {
+    match condition {
+        ForkCondition::Block(block) => format!("block {block}"),
+        ForkCondition::Timestamp(timestamp) => format!("timestamp {timestamp}"),
+        ForkCondition::TTD { total_difficulty, activation_block_number, .. } => {
+            format!("total difficulty {total_difficulty} (block {activation_block_number})")
+        }
+        ForkCondition::Never => "never".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chainspec::BERACHAIN_DEV;
+
+    #[test]
+    fn test_report_includes_berachain_schedule() {
+        let config = NodeConfig::new(BERACHAIN_DEV.clone());
+        let report = StartupReport::new(&config, &BerachainConfig::default());
+
+        assert_eq!(report.chain_id, BERACHAIN_DEV.chain_id());
+        assert_eq!(report.genesis_hash, BERACHAIN_DEV.genesis_hash());
+        assert_eq!(report.pol_distributor, BERACHAIN_DEV.pol_contract());
+        assert!(
+            report
+                .hardforks
+                .iter()
+                .any(|fork| fork.name == "Prague1" && fork.activation == "timestamp 0"),
+            "{:?}",
+            report.hardforks
+        );
+        assert_eq!(report.rpc.http, None);
+        assert!(report.rpc.ipc);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json["berachain"]["compression"].is_object());
+    }
+}
@@ -0,0 +1,250 @@
+//! Sampled re-verification of state roots
+
+use crate::{
+    node::evm::config::BerachainEvmConfig,
+    primitives::{BerachainBlock, BerachainPrimitives},
+};
+use alloy_consensus::BlockHeader;
+use alloy_eips::BlockHashOrNumber;
+use alloy_primitives::{B256, BlockNumber};
+use clap::Args;
+use futures::StreamExt;
+use reth::{
+    providers::{
+        BlockReader, CanonStateSubscriptions, DatabaseProviderFactory, HashedPostStateProvider,
+        StateRootProvider, TransactionVariant, TryIntoHistoricalStateProvider,
+    },
+    revm::database::StateProviderDatabase,
+    tasks::TaskExecutor,
+};
+use reth_evm::{ConfigureEvm, execute::Executor};
+use reth_metrics::{
+    Metrics,
+    metrics::{Counter, Gauge},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{VecDeque, hash_map::RandomState},
+    hash::BuildHasher,
+    time::Instant,
+};
+use tracing::{debug, error, info};
+
+/// Sampled blocks waiting to be persisted, beyond which new samples are dropped.
+const MAX_PENDING: usize = 16;
+
+/// Settings of state root sampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct StateRootSamplingConfig {
+    /// Verify one canonical block in this many, on average. Disabled if zero.
+    pub one_in: u64,
+}
+
+impl Default for StateRootSamplingConfig {
+    fn default() -> Self {
+        Self { one_in: 1000 }
+    }
+}
+
+impl StateRootSamplingConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &StateRootSamplingArgs) -> Self {
+        if let Some(one_in) = args.one_in {
+            self.one_in = one_in;
+        }
+        self
+    }
+}
+
+/// Command line arguments of state root sampling.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "State root sampling")]
+pub struct StateRootSamplingArgs {
+    /// Re-verify the state root of one canonical block in this many from stored state, 0 to
+    /// disable [default: 1000]
+    #[arg(long = "state-root-sampling.one-in", value_name = "BLOCKS")]
+    pub one_in: Option<u64>,
+}
+
+/// State root sampling metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.state_root_sampling")]
+struct StateRootSamplingMetrics {
+    /// Sampled blocks whose state root was recomputed.
+    verified: Counter,
+    /// Sampled blocks whose recomputed state root differs from their header.
+    mismatches: Counter,
+    /// Sampled blocks that could not be re-executed, e.g. because their history was pruned.
+    failures: Counter,
+    /// Last verified block.
+    last_verified_block: Gauge,
+    /// Time spent verifying the last block, in seconds.
+    last_duration_seconds: Gauge,
+}
+
+/// Picks blocks at random and holds them until they are persisted.
+#[derive(Debug)]
+struct Sampler {
+    one_in: u64,
+    hasher: RandomState,
+    pending: VecDeque<BlockNumber>,
+}
+
+impl Sampler {
+    fn new(one_in: u64) -> Self {
+        Self { one_in, hasher: RandomState::new(), pending: VecDeque::new() }
+    }
+
+    /// Samples the block `number` with hash `hash` with a probability of one in `one_in`.
+    fn observe(&mut self, number: BlockNumber, hash: B256) {
+        if self.one_in == 0 || self.hasher.hash_one(hash) % self.one_in != 0 {
+            return
+        }
+        if self.pending.len() < MAX_PENDING {
+            self.pending.push_back(number);
+        }
+    }
+
+    /// Takes the sampled blocks up to `persisted`, ignoring blocks sampled twice after a reorg.
+    fn take_persisted(&mut self, persisted: BlockNumber) -> Vec<BlockNumber> {
+        let mut ready: Vec<_> = self.pending.iter().copied().filter(|n| *n <= persisted).collect();
+        self.pending.retain(|number| *number > persisted);
+        ready.sort_unstable();
+        ready.dedup();
+        ready
+    }
+}
+
+/// Block whose state root was recomputed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Verified {
+    expected: B256,
+    computed: B256,
+}
+
+/// Re-executes the persisted block `number` on the stored state of its parent and recomputes its
+/// state root.
+fn verify_block<P>(
+    provider: &P,
+    evm_config: &BerachainEvmConfig,
+    number: BlockNumber,
+) -> eyre::Result<Verified>
+where
+    P: DatabaseProviderFactory<
+        Provider: BlockReader<Block = BerachainBlock> + TryIntoHistoricalStateProvider,
+    >,
+{
+    eyre::ensure!(number > 0, "genesis has no parent state");
+    let block = provider
+        .database_provider_ro()?
+        .recovered_block(BlockHashOrNumber::Number(number), TransactionVariant::WithHash)?
+        .ok_or_else(|| eyre::eyre!("block {number} not found"))?;
+    let state = provider.database_provider_ro()?.try_into_history_at_block(number - 1)?;
+    let output = evm_config.executor(StateProviderDatabase::new(&state)).execute(&block)?;
+    let computed = state.state_root(state.hashed_post_state(&output.state))?;
+    Ok(Verified { expected: block.header().state_root(), computed })
+}
+
+/// Starts re-verifying the state roots of sampled canonical blocks of `provider`.
+pub fn install_state_root_sampling<P>(
+    config: StateRootSamplingConfig,
+    provider: P,
+    evm_config: BerachainEvmConfig,
+    executor: &TaskExecutor,
+) where
+    P: CanonStateSubscriptions<Primitives = BerachainPrimitives>
+        + DatabaseProviderFactory<
+            Provider: BlockReader<Block = BerachainBlock> + TryIntoHistoricalStateProvider,
+        > + Clone
+        + Send
+        + Sync
+        + 'static,
+{
+    if config.one_in == 0 {
+        return
+    }
+    info!(target: "reth::cli", one_in = config.one_in, "Sampling state roots");
+    let mut notifications = provider.canonical_state_stream();
+    executor.spawn(async move {
+        let metrics = StateRootSamplingMetrics::default();
+        let mut sampler = Sampler::new(config.one_in);
+        while let Some(notification) = notifications.next().await {
+            for block in notification.committed().blocks_iter() {
+                sampler.observe(block.number(), block.hash());
+            }
+            if sampler.pending.is_empty() {
+                continue
+            }
+            let persisted = match provider.database_provider_ro() {
+                Ok(db) => db.last_block_number().unwrap_or_default(),
+                Err(_) => continue,
+            };
+            for number in sampler.take_persisted(persisted) {
+                let provider = provider.clone();
+                let evm_config = evm_config.clone();
+                let started = Instant::now();
+                let task = move || verify_block(&provider, &evm_config, number);
+                let verified = tokio::task::spawn_blocking(task).await.map_err(eyre::Report::from);
+                match verified.and_then(|verified| verified) {
+                    Ok(Verified { expected, computed }) => {
+                        metrics.verified.increment(1);
+                        metrics.last_verified_block.set(number as f64);
+                        metrics.last_duration_seconds.set(started.elapsed().as_secs_f64());
+                        if expected == computed {
+                            debug!(target: "reth::cli", number, "Verified state root");
+                        } else {
+                            metrics.mismatches.increment(1);
+                            error!(
+                                target: "reth::cli",
+                                number,
+                                %expected,
+                                %computed,
+                                "State root recomputed from stored state does not match, the \
+                                 database may be corrupt"
+                            );
+                        }
+                    }
+                    Err(err) => {
+                        metrics.failures.increment(1);
+                        debug!(target: "reth::cli", number, %err, "Failed to verify state root");
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampling_rate() {
+        let mut never = Sampler::new(0);
+        let mut always = Sampler::new(1);
+        let mut some = Sampler::new(4);
+        let mut sampled = 0;
+        for number in 0..4000u64 {
+            let hash = B256::left_padding_from(&number.to_be_bytes());
+            never.observe(number, hash);
+            always.observe(number, hash);
+            some.observe(number, hash);
+            sampled += some.take_persisted(number).len();
+        }
+        assert!(never.pending.is_empty());
+        assert_eq!(always.pending.len(), MAX_PENDING);
+        assert!((800..1200).contains(&sampled), "{sampled} blocks sampled");
+    }
+
+    #[test]
+    fn test_waits_for_persistence() {
+        let mut sampler = Sampler::new(1);
+        for number in [10, 12, 11, 12] {
+            sampler.observe(number, B256::with_last_byte(number as u8));
+        }
+        assert!(sampler.take_persisted(9).is_empty());
+        assert_eq!(sampler.take_persisted(12), vec![10, 11, 12]);
+        assert!(sampler.pending.is_empty());
+    }
+}
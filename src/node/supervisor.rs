@@ -0,0 +1,172 @@
+//! Integration with process supervisors
+
+use crate::primitives::BerachainPrimitives;
+use clap::Args;
+use futures::{Stream, StreamExt};
+use reth::tasks::TaskExecutor;
+use reth_engine_primitives::ConsensusEngineEvent;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Settings of the process supervisor integration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SupervisorConfig {
+    /// File the process id is written to while the node runs.
+    pub pid_file: Option<PathBuf>,
+    /// Whether to report readiness to systemd through `NOTIFY_SOCKET`.
+    pub sd_notify: bool,
+}
+
+impl SupervisorConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &SupervisorArgs) -> Self {
+        if let Some(pid_file) = &args.pid_file {
+            self.pid_file = Some(pid_file.clone());
+        }
+        self.sd_notify |= args.sd_notify;
+        self
+    }
+}
+
+/// Command line arguments of the process supervisor integration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Supervisor")]
+pub struct SupervisorArgs {
+    /// Write the process id to this file while the node runs
+    #[arg(long = "supervisor.pid-file", value_name = "PATH")]
+    pub pid_file: Option<PathBuf>,
+
+    /// Notify systemd once the first forkchoice update was applied
+    #[arg(long = "supervisor.sd-notify")]
+    pub sd_notify: bool,
+}
+
+/// A PID file, removed when dropped.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Writes the id of this process to `path`.
+    ///
+    /// Fails if the file names another running process, and replaces it otherwise.
+    pub fn create(path: &Path) -> eyre::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                if let Ok(pid) = contents.trim().parse::<u32>() &&
+                    pid != std::process::id() &&
+                    process_exists(pid)
+                {
+                    eyre::bail!(
+                        "PID file {} belongs to running process {pid}; is another node using it?",
+                        path.display()
+                    )
+                }
+                warn!(target: "reth::cli", path = %path.display(), "Replacing stale PID file");
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => eyre::bail!("failed to read {}: {err}", path.display()),
+        }
+        std::fs::write(path, format!("{}\n", std::process::id()))
+            .map_err(|err| eyre::eyre!("failed to write {}: {err}", path.display()))?;
+        Ok(Self { path: path.to_path_buf() })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            let path = self.path.display();
+            warn!(target: "reth::cli", %path, %err, "Failed to remove PID file");
+        }
+    }
+}
+
+/// Returns whether a process with id `pid` is running.
+#[cfg(target_os = "linux")]
+fn process_exists(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Without procfs, PID files of other processes are assumed to be stale.
+#[cfg(not(target_os = "linux"))]
+fn process_exists(_pid: u32) -> bool {
+    false
+}
+
+/// Reports readiness to systemd after the first valid forkchoice update, and shutdown when the
+/// node stops, if enabled.
+pub fn install_sd_notify<Ev>(config: &SupervisorConfig, engine_events: Ev, executor: &TaskExecutor)
+where
+    Ev: Stream<Item = ConsensusEngineEvent<BerachainPrimitives>> + Send + 'static,
+{
+    if !config.sd_notify {
+        return
+    }
+    if std::env::var_os("NOTIFY_SOCKET").is_none() {
+        warn!(target: "reth::cli", "NOTIFY_SOCKET is not set, systemd will not be notified");
+        return
+    }
+
+    executor.spawn(async move {
+        let mut engine_events = std::pin::pin!(engine_events);
+        while let Some(event) = engine_events.next().await {
+            let ConsensusEngineEvent::ForkchoiceUpdated(state, status) = event else { continue };
+            if status.is_valid() {
+                info!(target: "reth::cli", head = %state.head_block_hash, "Notifying systemd");
+                notify(&format!("READY=1\nSTATUS=Following head {}", state.head_block_hash));
+                return
+            }
+        }
+    });
+    executor.spawn_critical_with_graceful_shutdown_signal(
+        "berachain sd_notify",
+        |shutdown| async move {
+            let _guard = shutdown.await;
+            notify("STOPPING=1");
+        },
+    );
+}
+
+/// Sends `state` to the systemd notification socket.
+#[cfg(unix)]
+fn notify(state: &str) {
+    let state: Vec<_> = state.lines().map(sd_notify::NotifyState::Custom).collect();
+    if let Err(err) = sd_notify::notify(false, &state) {
+        warn!(target: "reth::cli", %err, "Failed to notify systemd");
+    }
+}
+
+/// systemd notifications are only supported on Unix.
+#[cfg(not(unix))]
+fn notify(_state: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_file_lifecycle() {
+        let path = std::env::temp_dir().join(format!("bera-reth-{}.pid", std::process::id()));
+
+        // Stale files are replaced, and the file is removed on drop
+        std::fs::write(&path, "not a pid").unwrap();
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().trim(), std::process::id().to_string());
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_cli_overrides_file() {
+        let file = SupervisorConfig { pid_file: Some("/run/a.pid".into()), sd_notify: true };
+        let args = SupervisorArgs { pid_file: Some("/run/b.pid".into()), sd_notify: false };
+
+        let config = file.with_args(&args);
+        assert_eq!(config.pid_file, Some(PathBuf::from("/run/b.pid")));
+        assert!(config.sd_notify);
+    }
+}
@@ -0,0 +1,128 @@
+//! Checkpoint sync from a trusted block hash
+
+use crate::{
+    cli::init_state_snapshot::{init_from_snapshot, read_snapshot_header},
+    node::BerachainNode,
+};
+use alloy_primitives::B256;
+use clap::Args;
+use reth::providers::{BlockNumReader, ProviderFactory};
+use reth_config::config::EtlConfig;
+use reth_db::DatabaseEnv;
+use reth_node_builder::NodeTypesWithDBAdapter;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+use tracing::info;
+
+/// Settings of checkpoint sync.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SyncConfig {
+    /// Trusted hash of the block to sync from. Checkpoint sync is disabled if unset.
+    pub checkpoint: Option<B256>,
+    /// JSON header (or full block) of the checkpoint block.
+    pub checkpoint_header: Option<PathBuf>,
+    /// JSONL state dump taken at the checkpoint block.
+    pub checkpoint_state: Option<PathBuf>,
+    /// Whether to execute history up to the checkpoint instead of importing its state.
+    pub execute_history: bool,
+}
+
+impl SyncConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &SyncArgs) -> Self {
+        if let Some(checkpoint) = args.checkpoint {
+            self.checkpoint = Some(checkpoint);
+        }
+        if let Some(checkpoint_header) = &args.checkpoint_header {
+            self.checkpoint_header = Some(checkpoint_header.clone());
+        }
+        if let Some(checkpoint_state) = &args.checkpoint_state {
+            self.checkpoint_state = Some(checkpoint_state.clone());
+        }
+        self.execute_history |= args.execute_history;
+        self
+    }
+
+    /// Prepares the datadir for checkpoint sync, returning the block the pipeline has to sync to
+    /// if history is executed.
+    pub fn prepare(
+        &self,
+        provider_factory: &ProviderFactory<NodeTypesWithDBAdapter<BerachainNode, Arc<DatabaseEnv>>>,
+        etl: EtlConfig,
+    ) -> eyre::Result<Option<B256>> {
+        let Some(checkpoint) = self.checkpoint else { return Ok(None) };
+        let provider = provider_factory.provider()?;
+        if provider.block_number(checkpoint)?.is_some() {
+            info!(target: "reth::cli", %checkpoint, "Checkpoint block already synced");
+            return Ok(None)
+        }
+        let last_block_number = provider.last_block_number()?;
+        drop(provider);
+
+        if self.execute_history || last_block_number > 0 {
+            info!(target: "reth::cli", %checkpoint, "Executing history up to the checkpoint");
+            return Ok(Some(checkpoint))
+        }
+
+        let (Some(header), Some(state)) = (&self.checkpoint_header, &self.checkpoint_state) else {
+            eyre::bail!(
+                "checkpoint sync needs --sync.checkpoint-header and --sync.checkpoint-state, or \
+                 --sync.execute-history to execute history instead"
+            )
+        };
+        let header = read_snapshot_header(header)?;
+        eyre::ensure!(
+            header.hash() == checkpoint,
+            "checkpoint header hashes to {}, but the trusted checkpoint is {checkpoint}",
+            header.hash()
+        );
+        init_from_snapshot(provider_factory, header, state, etl)?;
+        Ok(None)
+    }
+}
+
+/// Command line arguments of checkpoint sync.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Checkpoint sync")]
+pub struct SyncArgs {
+    /// Sync from this trusted block hash instead of from genesis
+    #[arg(long = "sync.checkpoint", value_name = "HASH")]
+    pub checkpoint: Option<B256>,
+
+    /// JSON header of the checkpoint block, as served by `eth_getBlockByNumber`
+    #[arg(long = "sync.checkpoint-header", value_name = "FILE")]
+    pub checkpoint_header: Option<PathBuf>,
+
+    /// JSONL state dump taken at the checkpoint block
+    #[arg(long = "sync.checkpoint-state", value_name = "FILE")]
+    pub checkpoint_state: Option<PathBuf>,
+
+    /// Execute history up to the checkpoint instead of importing its state
+    #[arg(long = "sync.execute-history")]
+    pub execute_history: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_overrides_file() {
+        let file = SyncConfig {
+            checkpoint: Some(B256::repeat_byte(1)),
+            checkpoint_state: Some("/snapshots/state.jsonl".into()),
+            ..Default::default()
+        };
+        let args = SyncArgs {
+            checkpoint: Some(B256::repeat_byte(2)),
+            execute_history: true,
+            ..Default::default()
+        };
+
+        let config = file.with_args(&args);
+        assert_eq!(config.checkpoint, Some(B256::repeat_byte(2)));
+        assert_eq!(config.checkpoint_state, Some(PathBuf::from("/snapshots/state.jsonl")));
+        assert!(config.execute_history);
+    }
+}
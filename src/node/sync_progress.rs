@@ -0,0 +1,271 @@
+//! Sync progress of the pipeline stages with estimated time to sync
+
+use crate::primitives::BerachainPrimitives;
+use futures::{Stream, StreamExt};
+use reth::{providers::StageCheckpointReader, tasks::TaskExecutor};
+use reth_engine_primitives::ConsensusEngineEvent;
+use reth_metrics::{Metrics, metrics::Gauge};
+use reth_stages_types::StageId;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// Interval between two samples of the stage checkpoints.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Stages whose progress is tracked, in pipeline order.
+pub const TRACKED_STAGES: [StageId; 4] =
+    [StageId::Headers, StageId::Bodies, StageId::Execution, StageId::MerkleExecute];
+
+/// Weight of the latest sample in the smoothed rate of a stage.
+const RATE_SMOOTHING: f64 = 0.2;
+
+/// Overall sync metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.sync")]
+struct SyncMetrics {
+    /// Block the stages sync to.
+    target: Gauge,
+    /// Estimated seconds until all tracked stages reached the target.
+    eta_seconds: Gauge,
+}
+
+/// Metrics of a tracked stage, labeled with the stage.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.sync.stage")]
+struct StageMetrics {
+    /// Checkpoint of the stage.
+    checkpoint: Gauge,
+    /// Blocks processed per second while the stage runs.
+    blocks_per_second: Gauge,
+    /// Estimated seconds until the stage reached the target.
+    eta_seconds: Gauge,
+}
+
+/// Progress of a pipeline stage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageProgress {
+    /// Name of the stage.
+    pub stage: String,
+    /// Last block processed by the stage.
+    pub checkpoint: u64,
+    /// Blocks left until the target.
+    pub remaining: u64,
+    /// Blocks processed per second while the stage runs, once measured.
+    pub blocks_per_second: Option<f64>,
+    /// Estimated seconds until the stage reached the target, once its rate is measured.
+    pub eta_seconds: Option<u64>,
+}
+
+/// Progress of the sync, as returned by `bera_syncStatus`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    /// Whether any tracked stage is behind the target.
+    pub syncing: bool,
+    /// Block the stages sync to: the highest block known from the consensus client or headers.
+    pub target: u64,
+    /// Tracked stages, in pipeline order.
+    pub stages: Vec<StageProgress>,
+    /// Estimated seconds until all stages reached the target, once every lagging stage's rate is
+    /// measured. Stages run one after another, so this is the sum of their estimates.
+    pub eta_seconds: Option<u64>,
+}
+
+/// Smoothed processing rates of the tracked stages.
+#[derive(Debug, Default)]
+struct SyncTracker {
+    /// Previous sample: when it was taken and the checkpoints of the tracked stages.
+    last: Option<(Instant, [u64; TRACKED_STAGES.len()])>,
+    /// Smoothed blocks per second of the tracked stages.
+    rates: [Option<f64>; TRACKED_STAGES.len()],
+}
+
+impl SyncTracker {
+    /// Records a sample and returns the resulting progress.
+    fn update(
+        &mut self,
+        checkpoints: [u64; TRACKED_STAGES.len()],
+        target: u64,
+        now: Instant,
+    ) -> SyncStatus {
+        if let Some((at, last)) = self.last {
+            let elapsed = now.saturating_duration_since(at).as_secs_f64();
+            for (rate, (checkpoint, last)) in
+                self.rates.iter_mut().zip(checkpoints.iter().zip(last))
+            {
+                // Stages wait for the previous ones, so rates are only measured while advancing
+                if elapsed > 0.0 && *checkpoint > last {
+                    let sample = (checkpoint - last) as f64 / elapsed;
+                    *rate =
+                        Some(rate.map_or(sample, |rate| rate + RATE_SMOOTHING * (sample - rate)));
+                }
+            }
+        }
+        self.last = Some((now, checkpoints));
+
+        let target = checkpoints.into_iter().fold(target, u64::max);
+        let stages: Vec<_> = TRACKED_STAGES
+            .iter()
+            .zip(checkpoints.into_iter().zip(self.rates))
+            .map(|(stage, (checkpoint, rate))| {
+                let remaining = target - checkpoint;
+                let eta_seconds = if remaining == 0 {
+                    Some(0)
+                } else {
+                    rate.map(|rate| (remaining as f64 / rate).ceil() as u64)
+                };
+                StageProgress {
+                    stage: stage.to_string(),
+                    checkpoint,
+                    remaining,
+                    blocks_per_second: rate,
+                    eta_seconds,
+                }
+            })
+            .collect();
+
+        SyncStatus {
+            syncing: stages.iter().any(|stage| stage.remaining > 0),
+            target,
+            eta_seconds: stages.iter().map(|stage| stage.eta_seconds).sum(),
+            stages,
+        }
+    }
+}
+
+/// Latest sync progress of a node, sampled once [`install_sync_progress`] ran.
+#[derive(Debug, Default)]
+pub struct SyncProgress {
+    status: Mutex<Option<SyncStatus>>,
+}
+
+impl SyncProgress {
+    /// Returns the latest sampled progress, if a sample was taken.
+    pub fn status(&self) -> Option<SyncStatus> {
+        self.status.lock().expect("not poisoned").clone()
+    }
+}
+
+/// Starts sampling the sync progress of the stages of `provider` into `progress`.
+pub fn install_sync_progress<P, Ev>(
+    progress: Arc<SyncProgress>,
+    provider: P,
+    engine_events: Ev,
+    executor: &TaskExecutor,
+) where
+    P: StageCheckpointReader + Send + Sync + 'static,
+    Ev: Stream<Item = ConsensusEngineEvent<BerachainPrimitives>> + Send + 'static,
+{
+    let cl_head = Arc::new(AtomicU64::new(0));
+    let received = cl_head.clone();
+    executor.spawn(async move {
+        let mut engine_events = std::pin::pin!(engine_events);
+        while let Some(event) = engine_events.next().await {
+            if let ConsensusEngineEvent::BlockReceived(block) = event {
+                received.fetch_max(block.number, Ordering::Relaxed);
+            }
+        }
+    });
+
+    executor.spawn(async move {
+        let mut tracker = SyncTracker::default();
+        let metrics = SyncMetrics::default();
+        let stage_metrics = TRACKED_STAGES
+            .map(|stage| StageMetrics::new_with_labels(&[("stage", stage.to_string())]));
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let (checkpoints, target) = match read_checkpoints(&provider) {
+                Ok(sample) => sample,
+                Err(err) => {
+                    debug!(target: "reth::cli", %err, "Failed to read stage checkpoints");
+                    continue
+                }
+            };
+            let target = target.max(cl_head.load(Ordering::Relaxed));
+            let status = tracker.update(checkpoints, target, Instant::now());
+
+            metrics.target.set(status.target as f64);
+            metrics.eta_seconds.set(status.eta_seconds.unwrap_or_default() as f64);
+            for (metrics, stage) in stage_metrics.iter().zip(&status.stages) {
+                metrics.checkpoint.set(stage.checkpoint as f64);
+                metrics.blocks_per_second.set(stage.blocks_per_second.unwrap_or_default());
+                metrics.eta_seconds.set(stage.eta_seconds.unwrap_or_default() as f64);
+            }
+            *progress.status.lock().expect("not poisoned") = Some(status);
+        }
+    });
+}
+
+/// Reads the checkpoints of the tracked stages and the target of the running headers stage.
+fn read_checkpoints<P: StageCheckpointReader>(
+    provider: &P,
+) -> eyre::Result<([u64; TRACKED_STAGES.len()], u64)> {
+    let mut checkpoints = [0; TRACKED_STAGES.len()];
+    let mut target = 0;
+    for (stage, checkpoint) in TRACKED_STAGES.iter().zip(&mut checkpoints) {
+        let Some(stage_checkpoint) = provider.get_stage_checkpoint(*stage)? else { continue };
+        *checkpoint = stage_checkpoint.block_number;
+        if let Some(headers) = stage_checkpoint.headers_stage_checkpoint() {
+            target = headers.block_range.to;
+        }
+    }
+    Ok((checkpoints, target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eta_from_measured_rates() {
+        let mut tracker = SyncTracker::default();
+        let start = Instant::now();
+
+        let status = tracker.update([1000, 1000, 0, 0], 1000, start);
+        assert!(status.syncing);
+        assert_eq!(status.stages[0].eta_seconds, Some(0));
+        assert_eq!(status.stages[2].eta_seconds, None);
+        assert_eq!(status.eta_seconds, None);
+
+        // Execution advances while merkle waits for it
+        let status = tracker.update([1000, 1000, 100, 0], 1000, start + Duration::from_secs(10));
+        assert_eq!(status.stages[2].blocks_per_second, Some(10.0));
+        assert_eq!(status.stages[2].eta_seconds, Some(90));
+        assert_eq!(status.stages[3].eta_seconds, None);
+        assert_eq!(status.eta_seconds, None);
+
+        let status =
+            tracker.update([1000, 1000, 1000, 450], 1000, start + Duration::from_secs(100));
+        assert_eq!(status.stages[3].blocks_per_second, Some(5.0));
+        assert_eq!(status.stages[3].eta_seconds, Some(110));
+        assert_eq!(status.eta_seconds, Some(110));
+    }
+
+    #[test]
+    fn test_rates_are_smoothed() {
+        let mut tracker = SyncTracker::default();
+        let start = Instant::now();
+        tracker.update([0; 4], 1000, start);
+        tracker.update([100, 0, 0, 0], 1000, start + Duration::from_secs(1));
+        let status = tracker.update([300, 0, 0, 0], 1000, start + Duration::from_secs(2));
+        assert_eq!(status.stages[0].blocks_per_second, Some(120.0));
+    }
+
+    #[test]
+    fn test_synced_when_stages_reach_the_target() {
+        let mut tracker = SyncTracker::default();
+        let status = tracker.update([500; 4], 400, Instant::now());
+        assert!(!status.syncing);
+        assert_eq!(status.target, 500);
+        assert_eq!(status.eta_seconds, Some(0));
+    }
+}
@@ -0,0 +1,246 @@
+//! Blob store of the transaction pool, backed by the retained sidecars.
+//!
+//! The pool drops the sidecar of a blob transaction once its block is finalized, and reth serves
+//! `engine_getBlobsV1`/`V2` and reinjects the blob transactions of reorged blocks only from the
+//! pool's blob store. [`BerachainBlobStore`] answers the lookups the pool's store misses from the
+//! [retained sidecars](crate::storage::blob_sidecars) instead, so the sidecars of built blocks stay
+//! available to the consensus client, and a reorged blob transaction goes back into the pool with
+//! its sidecar rather than failing with `Eip4844MissingSidecar`.
+
+use crate::storage::blob_sidecars::{read_blob_transaction, read_sidecar};
+use alloy_eips::eip7594::BlobTransactionSidecarVariant;
+use alloy_primitives::{B256, TxHash};
+use alloy_rlp::Decodable;
+use alloy_rpc_types::engine::{BlobAndProofV1, BlobAndProofV2};
+use reth::providers::{DBProvider, DatabaseProviderFactory};
+use reth_transaction_pool::blobstore::{
+    BlobStore, BlobStoreCleanupStat, BlobStoreError, DiskFileBlobStore,
+};
+use std::{fmt, slice, sync::Arc};
+
+/// Blob sidecars kept after their transaction left the pool.
+pub trait RetainedSidecars: Send + Sync + 'static {
+    /// Returns the sidecar of the blob transaction `tx_hash`.
+    fn sidecar(&self, tx_hash: TxHash) -> Option<BlobTransactionSidecarVariant>;
+
+    /// Returns the hash of the transaction carrying the blob of `versioned_hash`.
+    fn blob_transaction(&self, versioned_hash: B256) -> Option<TxHash>;
+}
+
+/// Sidecars retained in the database of a provider.
+#[derive(Debug, Clone)]
+pub struct DatabaseSidecars<P>(pub P);
+
+impl<P> RetainedSidecars for DatabaseSidecars<P>
+where
+    P: DatabaseProviderFactory + Send + Sync + 'static,
+{
+    fn sidecar(&self, tx_hash: TxHash) -> Option<BlobTransactionSidecarVariant> {
+        let provider = self.0.database_provider_ro().ok()?;
+        let encoded = read_sidecar(provider.tx_ref(), tx_hash).ok()??;
+        BlobTransactionSidecarVariant::decode(&mut encoded.as_ref()).ok()
+    }
+
+    fn blob_transaction(&self, versioned_hash: B256) -> Option<TxHash> {
+        let provider = self.0.database_provider_ro().ok()?;
+        read_blob_transaction(provider.tx_ref(), versioned_hash).ok()?
+    }
+}
+
+/// Pool blob store falling back to [`RetainedSidecars`].
+#[derive(Clone)]
+pub struct BerachainBlobStore<S = DiskFileBlobStore> {
+    inner: S,
+    retained: Arc<dyn RetainedSidecars>,
+}
+
+impl<S: fmt::Debug> fmt::Debug for BerachainBlobStore<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BerachainBlobStore").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl<S> BerachainBlobStore<S> {
+    /// Serves the sidecars of `inner` and, once it dropped them, those of `retained`.
+    pub fn new(inner: S, retained: impl RetainedSidecars) -> Self {
+        Self { inner, retained: Arc::new(retained) }
+    }
+
+    /// Returns the retained sidecar holding the blob of `versioned_hash`.
+    fn retained_blob(&self, versioned_hash: B256) -> Option<BlobTransactionSidecarVariant> {
+        self.retained.sidecar(self.retained.blob_transaction(versioned_hash)?)
+    }
+
+    /// Returns the retained blob of `versioned_hash` with its EIP-4844 proof.
+    fn retained_v1(&self, versioned_hash: B256) -> Option<BlobAndProofV1> {
+        let sidecar = self.retained_blob(versioned_hash)?;
+        let (_, blob) = sidecar.as_eip4844()?.match_versioned_hashes(&[versioned_hash]).next()?;
+        Some(blob)
+    }
+
+    /// Returns the retained blob of `versioned_hash` with its cell proofs.
+    fn retained_v2(&self, versioned_hash: B256) -> Option<BlobAndProofV2> {
+        let sidecar = self.retained_blob(versioned_hash)?;
+        let (_, blob) = sidecar.as_eip7594()?.match_versioned_hashes(&[versioned_hash]).next()?;
+        Some(blob)
+    }
+}
+
+impl<S: BlobStore> BlobStore for BerachainBlobStore<S> {
+    fn insert(&self, tx: B256, data: BlobTransactionSidecarVariant) -> Result<(), BlobStoreError> {
+        self.inner.insert(tx, data)
+    }
+
+    fn insert_all(
+        &self,
+        txs: Vec<(B256, BlobTransactionSidecarVariant)>,
+    ) -> Result<(), BlobStoreError> {
+        self.inner.insert_all(txs)
+    }
+
+    fn delete(&self, tx: B256) -> Result<(), BlobStoreError> {
+        self.inner.delete(tx)
+    }
+
+    fn delete_all(&self, txs: Vec<B256>) -> Result<(), BlobStoreError> {
+        self.inner.delete_all(txs)
+    }
+
+    fn cleanup(&self) -> BlobStoreCleanupStat {
+        self.inner.cleanup()
+    }
+
+    fn get(&self, tx: B256) -> Result<Option<Arc<BlobTransactionSidecarVariant>>, BlobStoreError> {
+        if let Some(sidecar) = self.inner.get(tx)? {
+            return Ok(Some(sidecar))
+        }
+        Ok(self.retained.sidecar(tx).map(Arc::new))
+    }
+
+    fn contains(&self, tx: B256) -> Result<bool, BlobStoreError> {
+        Ok(self.inner.contains(tx)? || self.retained.sidecar(tx).is_some())
+    }
+
+    fn get_all(
+        &self,
+        txs: Vec<B256>,
+    ) -> Result<Vec<(B256, Arc<BlobTransactionSidecarVariant>)>, BlobStoreError> {
+        let mut sidecars = Vec::with_capacity(txs.len());
+        for tx in txs {
+            if let Some(sidecar) = self.get(tx)? {
+                sidecars.push((tx, sidecar));
+            }
+        }
+        Ok(sidecars)
+    }
+
+    fn get_exact(
+        &self,
+        txs: Vec<B256>,
+    ) -> Result<Vec<Arc<BlobTransactionSidecarVariant>>, BlobStoreError> {
+        txs.into_iter().map(|tx| self.get(tx)?.ok_or(BlobStoreError::MissingSidecar(tx))).collect()
+    }
+
+    fn get_by_versioned_hashes_v1(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Vec<Option<BlobAndProofV1>>, BlobStoreError> {
+        let mut blobs = self.inner.get_by_versioned_hashes_v1(versioned_hashes)?;
+        for (blob, versioned_hash) in blobs.iter_mut().zip(versioned_hashes) {
+            if blob.is_none() {
+                *blob = self.retained_v1(*versioned_hash);
+            }
+        }
+        Ok(blobs)
+    }
+
+    fn get_by_versioned_hashes_v2(
+        &self,
+        versioned_hashes: &[B256],
+    ) -> Result<Option<Vec<BlobAndProofV2>>, BlobStoreError> {
+        if let Some(blobs) = self.inner.get_by_versioned_hashes_v2(versioned_hashes)? {
+            return Ok(Some(blobs))
+        }
+        // All or nothing: look every blob up on its own to combine both stores
+        let mut blobs = Vec::with_capacity(versioned_hashes.len());
+        for versioned_hash in versioned_hashes {
+            let pooled = self.inner.get_by_versioned_hashes_v2(slice::from_ref(versioned_hash))?;
+            let blob = match pooled {
+                Some(mut pooled) => pooled.pop(),
+                None => self.retained_v2(*versioned_hash),
+            };
+            let Some(blob) = blob else { return Ok(None) };
+            blobs.push(blob);
+        }
+        Ok(Some(blobs))
+    }
+
+    fn data_size_hint(&self) -> Option<usize> {
+        self.inner.data_size_hint()
+    }
+
+    fn blobs_len(&self) -> usize {
+        self.inner.blobs_len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_eips::eip4844::{Blob, BlobTransactionSidecar, Bytes48};
+    use reth_transaction_pool::blobstore::InMemoryBlobStore;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Default)]
+    struct MapSidecars(HashMap<TxHash, BlobTransactionSidecarVariant>);
+
+    impl RetainedSidecars for MapSidecars {
+        fn sidecar(&self, tx_hash: TxHash) -> Option<BlobTransactionSidecarVariant> {
+            self.0.get(&tx_hash).cloned()
+        }
+
+        fn blob_transaction(&self, versioned_hash: B256) -> Option<TxHash> {
+            self.0.iter().find_map(|(tx_hash, sidecar)| {
+                sidecar.versioned_hashes().any(|hash| hash == versioned_hash).then_some(*tx_hash)
+            })
+        }
+    }
+
+    fn sidecar(byte: u8) -> BlobTransactionSidecarVariant {
+        BlobTransactionSidecarVariant::Eip4844(BlobTransactionSidecar::new(
+            vec![Blob::repeat_byte(byte)],
+            vec![Bytes48::repeat_byte(byte)],
+            vec![Bytes48::repeat_byte(byte)],
+        ))
+    }
+
+    #[test]
+    fn test_retained_sidecars_are_served() {
+        let pooled = TxHash::repeat_byte(1);
+        let retained = TxHash::repeat_byte(2);
+        let inner = InMemoryBlobStore::default();
+        inner.insert(pooled, sidecar(1)).unwrap();
+        let store =
+            BerachainBlobStore::new(inner, MapSidecars(HashMap::from([(retained, sidecar(2))])));
+
+        assert_eq!(*store.get(retained).unwrap().unwrap(), sidecar(2));
+        assert!(store.contains(retained).unwrap());
+        assert_eq!(store.get_all(vec![pooled, retained, TxHash::ZERO]).unwrap().len(), 2);
+        assert!(matches!(
+            store.get_exact(vec![retained, TxHash::ZERO]),
+            Err(BlobStoreError::MissingSidecar(hash)) if hash == TxHash::ZERO
+        ));
+
+        let versioned_hashes =
+            [sidecar(1), sidecar(2)].map(|sidecar| sidecar.versioned_hashes().next().unwrap());
+        let blobs = store
+            .get_by_versioned_hashes_v1(&[versioned_hashes[0], versioned_hashes[1], B256::ZERO])
+            .unwrap();
+        assert_eq!(blobs[0].as_ref().unwrap().blob, Box::new(Blob::repeat_byte(1)));
+        assert_eq!(blobs[1].as_ref().unwrap().blob, Box::new(Blob::repeat_byte(2)));
+        assert!(blobs[2].is_none());
+
+        // EIP-4844 sidecars carry no cell proofs
+        assert!(store.get_by_versioned_hashes_v2(&versioned_hashes).unwrap().is_none());
+    }
+}
@@ -1,13 +1,26 @@
+pub mod blobstore;
+pub mod propagation;
 pub mod transaction;
+pub mod validator;
 
 use crate::{
-    chainspec::BerachainChainSpec, pool::transaction::BerachainPooledTransaction,
+    chainspec::BerachainChainSpec,
+    node::config::BerachainConfig,
+    pool::{
+        blobstore::{BerachainBlobStore, DatabaseSidecars},
+        propagation::{OriginPropagation, TxPropagationConfig},
+        transaction::BerachainPooledTransaction,
+        validator::BerachainTransactionValidator,
+    },
     primitives::BerachainPrimitives,
 };
 use alloy_eips::{eip7840::BlobParams, merge::EPOCH_SLOTS};
 use reth::{
     api::NodeTypes,
-    transaction_pool::{EthTransactionPool, blobstore::DiskFileBlobStore},
+    transaction_pool::{
+        CoinbaseTipOrdering, EthTransactionValidator, Pool,
+        blobstore::{DiskFileBlobStore, DiskFileBlobStoreConfig},
+    },
 };
 use reth_chainspec::EthChainSpec;
 use reth_node_api::FullNodeTypes;
@@ -16,18 +29,43 @@ use reth_node_builder::{
     components::{PoolBuilder, TxPoolBuilder},
 };
 use reth_transaction_pool::TransactionValidationTaskExecutor;
-use std::{fmt::Debug, time::SystemTime};
+use std::{fmt::Debug, path::PathBuf, time::SystemTime};
 use tracing::{debug, info};
 
-#[derive(Debug, Default)]
-pub struct BerachainPoolBuilder;
+/// Transaction pool of a Berachain node.
+pub type BerachainTransactionPool<Client, S> = Pool<
+    TransactionValidationTaskExecutor<
+        OriginPropagation<
+            BerachainTransactionValidator<
+                EthTransactionValidator<Client, BerachainPooledTransaction>,
+            >,
+        >,
+    >,
+    CoinbaseTipOrdering<BerachainPooledTransaction>,
+    S,
+>;
+
+#[derive(Debug, Clone, Default)]
+pub struct BerachainPoolBuilder {
+    /// Blob store directory, the datadir's unless set.
+    pub blobstore: Option<PathBuf>,
+    /// Transaction propagation policies by origin.
+    pub propagation: TxPropagationConfig,
+}
+
+impl BerachainPoolBuilder {
+    /// Creates a pool builder with the blob store location and propagation policies of `config`.
+    pub fn new(config: &BerachainConfig) -> Self {
+        Self { blobstore: config.datadir.blobstore.clone(), propagation: config.txpool }
+    }
+}
 
 impl<Types, Node> PoolBuilder<Node> for BerachainPoolBuilder
 where
     Types: NodeTypes<ChainSpec = BerachainChainSpec, Primitives = BerachainPrimitives>,
     Node: FullNodeTypes<Types = Types>,
 {
-    type Pool = EthTransactionPool<Node::Provider, DiskFileBlobStore, BerachainPooledTransaction>;
+    type Pool = BerachainTransactionPool<Node::Provider, BerachainBlobStore>;
 
     async fn build_pool(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::Pool> {
         let pool_config = ctx.pool_config();
@@ -49,8 +87,17 @@ where
             Some((blob_params.target_blob_count * EPOCH_SLOTS * 2) as u32)
         };
 
-        let blob_store =
-            reth_node_builder::components::create_blob_store_with_cache(ctx, blob_cache_size)?;
+        let blob_store_path = self.blobstore.unwrap_or_else(|| ctx.config().datadir().blobstore());
+        let blob_store_config = match blob_cache_size {
+            Some(blob_cache_size) => {
+                DiskFileBlobStoreConfig::default().with_max_cached_entries(blob_cache_size)
+            }
+            None => DiskFileBlobStoreConfig::default(),
+        };
+        let blob_store = BerachainBlobStore::new(
+            DiskFileBlobStore::open(blob_store_path, blob_store_config)?,
+            DatabaseSidecars(ctx.provider().clone()),
+        );
 
         let validator = TransactionValidationTaskExecutor::eth_builder(ctx.provider().clone())
             .with_head_timestamp(ctx.head().timestamp)
@@ -60,7 +107,15 @@ where
             .set_tx_fee_cap(ctx.config().rpc.rpc_tx_fee_cap)
             .with_max_tx_gas_limit(ctx.config().txpool.max_tx_gas_limit)
             .with_additional_tasks(ctx.config().txpool.additional_validation_tasks)
-            .build_with_tasks(ctx.task_executor().clone(), blob_store.clone());
+            .build_with_tasks(ctx.task_executor().clone(), blob_store.clone())
+            .map(|validator| {
+                let validator = BerachainTransactionValidator::new(
+                    validator,
+                    ctx.chain_spec(),
+                    ctx.head().timestamp,
+                );
+                OriginPropagation::new(validator, self.propagation)
+            });
 
         let transaction_pool = TxPoolBuilder::new(ctx)
             .with_validator(validator)
@@ -0,0 +1,133 @@
+//! Propagation of pool transactions by origin.
+//!
+//! Validators accepting transactions over RPC may want to include them in their own blocks
+//! without announcing them to the public mesh first. Every valid transaction carries a
+//! `propagate` flag that the transactions manager honors; [`OriginPropagation`] clears it for the
+//! origins whose [`PropagationPolicy`] is [`Hold`](PropagationPolicy::Hold), so those
+//! transactions wait in the pool until they are included in a block. Transactions submitted with
+//! the private origin are never propagated, whatever the settings.
+
+use clap::Args;
+use reth_primitives_traits::{Block, SealedBlock};
+use reth_transaction_pool::{
+    TransactionOrigin, TransactionValidationOutcome, TransactionValidator,
+};
+use serde::{Deserialize, Serialize};
+
+/// Whether transactions of an origin are propagated to peers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PropagationPolicy {
+    /// Announce and broadcast to peers.
+    #[default]
+    Propagate,
+    /// Keep in the pool until included in a block.
+    Hold,
+}
+
+/// Propagation policies by transaction origin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TxPropagationConfig {
+    /// Transactions submitted over RPC.
+    pub local: PropagationPolicy,
+    /// Transactions received from peers.
+    pub external: PropagationPolicy,
+}
+
+impl TxPropagationConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &TxPropagationArgs) -> Self {
+        if args.no_locals_propagation {
+            self.local = PropagationPolicy::Hold;
+        }
+        if args.no_external_propagation {
+            self.external = PropagationPolicy::Hold;
+        }
+        self
+    }
+
+    /// Returns whether transactions of `origin` may be propagated.
+    pub const fn propagates(&self, origin: TransactionOrigin) -> bool {
+        let policy = match origin {
+            TransactionOrigin::Local => self.local,
+            TransactionOrigin::External => self.external,
+            TransactionOrigin::Private => return false,
+        };
+        matches!(policy, PropagationPolicy::Propagate)
+    }
+}
+
+/// Command line arguments of the transaction propagation policies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Transaction propagation")]
+pub struct TxPropagationArgs {
+    /// Keep transactions submitted over RPC in the pool until included instead of propagating
+    /// them to peers
+    #[arg(long = "txpool.no-locals-propagation")]
+    pub no_locals_propagation: bool,
+
+    /// Keep transactions received from peers in the pool until included instead of relaying them
+    #[arg(long = "txpool.no-external-propagation")]
+    pub no_external_propagation: bool,
+}
+
+/// Transaction validator clearing the `propagate` flag of transactions whose origin is held.
+#[derive(Debug, Clone)]
+pub struct OriginPropagation<V> {
+    inner: V,
+    config: TxPropagationConfig,
+}
+
+impl<V> OriginPropagation<V> {
+    /// Applies `config` to the transactions validated by `inner`.
+    pub const fn new(inner: V, config: TxPropagationConfig) -> Self {
+        Self { inner, config }
+    }
+}
+
+impl<V: TransactionValidator> TransactionValidator for OriginPropagation<V> {
+    type Transaction = V::Transaction;
+
+    async fn validate_transaction(
+        &self,
+        origin: TransactionOrigin,
+        transaction: Self::Transaction,
+    ) -> TransactionValidationOutcome<Self::Transaction> {
+        let mut outcome = self.inner.validate_transaction(origin, transaction).await;
+        if let TransactionValidationOutcome::Valid { propagate, .. } = &mut outcome {
+            *propagate &= self.config.propagates(origin);
+        }
+        outcome
+    }
+
+    fn on_new_head_block<B: Block>(&self, new_tip_block: &SealedBlock<B>) {
+        self.inner.on_new_head_block(new_tip_block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policies_by_origin() {
+        let config = TxPropagationConfig::default();
+        assert!(config.propagates(TransactionOrigin::Local));
+        assert!(config.propagates(TransactionOrigin::External));
+        assert!(!config.propagates(TransactionOrigin::Private));
+
+        let args = TxPropagationArgs { no_locals_propagation: true, ..Default::default() };
+        let config = config.with_args(&args);
+        assert!(!config.propagates(TransactionOrigin::Local));
+        assert!(config.propagates(TransactionOrigin::External));
+    }
+
+    #[test]
+    fn test_policies_from_toml() {
+        let config: TxPropagationConfig = toml::from_str("local = \"hold\"").unwrap();
+        assert_eq!(config.local, PropagationPolicy::Hold);
+        assert_eq!(config.external, PropagationPolicy::Propagate);
+        assert!(toml::from_str::<TxPropagationConfig>("private = \"propagate\"").is_err());
+    }
+}
@@ -284,3 +284,31 @@ impl EthPoolTransaction for BerachainPooledTransaction {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::BERACHAIN_DEV,
+        primitives::header::BlsPublicKey,
+        transaction::{TxConversionError, pol::create_pol_transaction},
+    };
+    use reth::revm::handler::SYSTEM_ADDRESS;
+
+    #[test]
+    fn test_pol_transactions_cannot_enter_the_pool() {
+        let pol_tx = create_pol_transaction(
+            BERACHAIN_DEV.clone(),
+            BlsPublicKey::from([7u8; 48]),
+            U256::from(3),
+            0,
+            7,
+        )
+        .unwrap();
+        let result = BerachainPooledTransaction::try_from_consensus(Recovered::new_unchecked(
+            pol_tx,
+            SYSTEM_ADDRESS,
+        ));
+        assert_eq!(result.unwrap_err(), TxConversionError::UnsupportedBerachainTransaction);
+    }
+}
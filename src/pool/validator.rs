@@ -0,0 +1,149 @@
+//! Berachain rules for pool admission.
+//!
+//! PoL transactions are system transactions inserted by the block proposer: they carry no
+//! signature and must never be gossiped or submitted. They cannot be decoded into a pooled
+//! transaction and PoL announcements are already rejected by the network, so rejecting them here
+//! only guards against a new path letting them through. Once Prague1 is active, the base fee never
+//! drops below the chain's minimum base fee, so transactions whose fee cap is below it could never
+//! be included and are rejected instead of sitting in the pool.
+
+use crate::{chainspec::BerachainChainSpec, transaction::POL_TX_TYPE};
+use alloy_consensus::{BlockHeader, Transaction};
+use alloy_eips::Typed2718;
+use reth_primitives_traits::{Block, SealedBlock};
+use reth_transaction_pool::{
+    PoolTransaction, TransactionOrigin, TransactionValidationOutcome, TransactionValidator,
+    error::{InvalidPoolTransactionError, PoolTransactionError},
+};
+use std::{
+    any::Any,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use thiserror::Error;
+
+/// Transaction rejected by the Berachain rules.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BerachainPoolError {
+    /// PoL transactions are created by the block proposer.
+    #[error(
+        "PoL transactions (type 0x7e) are created by the block proposer and cannot be submitted"
+    )]
+    PoLTransaction,
+    /// The fee cap is below the Prague1 minimum base fee.
+    #[error("max fee per gas {fee_cap} is below the minimum base fee of {minimum} wei")]
+    FeeCapBelowMinimumBaseFee {
+        /// Fee cap of the transaction.
+        fee_cap: u128,
+        /// Minimum base fee of the chain.
+        minimum: u64,
+    },
+}
+
+impl PoolTransactionError for BerachainPoolError {
+    fn is_bad_transaction(&self) -> bool {
+        matches!(self, Self::PoLTransaction)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Checks a transaction of type `ty` with `fee_cap` against the Berachain rules, where
+/// `minimum_base_fee` is set once Prague1 is active.
+pub fn check_transaction(
+    ty: u8,
+    fee_cap: u128,
+    minimum_base_fee: Option<u64>,
+) -> Result<(), BerachainPoolError> {
+    if ty == POL_TX_TYPE {
+        return Err(BerachainPoolError::PoLTransaction)
+    }
+    match minimum_base_fee {
+        Some(minimum) if fee_cap < minimum as u128 => {
+            Err(BerachainPoolError::FeeCapBelowMinimumBaseFee { fee_cap, minimum })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Transaction validator applying the Berachain rules before `inner`'s.
+#[derive(Debug)]
+pub struct BerachainTransactionValidator<V> {
+    inner: V,
+    chain_spec: Arc<BerachainChainSpec>,
+    head_timestamp: AtomicU64,
+}
+
+impl<V> BerachainTransactionValidator<V> {
+    /// Applies the rules of `chain_spec` to the transactions validated by `inner`, starting from a
+    /// head at `head_timestamp`.
+    pub const fn new(inner: V, chain_spec: Arc<BerachainChainSpec>, head_timestamp: u64) -> Self {
+        Self { inner, chain_spec, head_timestamp: AtomicU64::new(head_timestamp) }
+    }
+
+    /// Returns the minimum base fee of the next block if Prague1 is active at the head.
+    fn minimum_base_fee(&self) -> Option<u64> {
+        self.chain_spec.minimum_base_fee_at_timestamp(self.head_timestamp.load(Ordering::Relaxed))
+    }
+}
+
+impl<V: TransactionValidator> TransactionValidator for BerachainTransactionValidator<V> {
+    type Transaction = V::Transaction;
+
+    async fn validate_transaction(
+        &self,
+        origin: TransactionOrigin,
+        transaction: Self::Transaction,
+    ) -> TransactionValidationOutcome<Self::Transaction> {
+        if let Err(err) = check_transaction(
+            transaction.ty(),
+            transaction.max_fee_per_gas(),
+            self.minimum_base_fee(),
+        ) {
+            return TransactionValidationOutcome::Invalid(
+                transaction,
+                InvalidPoolTransactionError::other(err),
+            )
+        }
+        self.inner.validate_transaction(origin, transaction).await
+    }
+
+    fn on_new_head_block<B: Block>(&self, new_tip_block: &SealedBlock<B>) {
+        self.head_timestamp.store(new_tip_block.header().timestamp(), Ordering::Relaxed);
+        self.inner.on_new_head_block(new_tip_block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pol_transactions_are_rejected() {
+        assert_eq!(
+            check_transaction(POL_TX_TYPE, u128::MAX, None),
+            Err(BerachainPoolError::PoLTransaction)
+        );
+        assert!(BerachainPoolError::PoLTransaction.is_bad_transaction());
+        assert_eq!(check_transaction(2, 1, None), Ok(()));
+    }
+
+    #[test]
+    fn test_minimum_base_fee_after_prague1() {
+        let minimum = Some(1_000_000_000);
+        assert_eq!(check_transaction(2, 1_000_000_000, minimum), Ok(()));
+        let err = check_transaction(0, 999_999_999, minimum).unwrap_err();
+        assert_eq!(
+            err,
+            BerachainPoolError::FeeCapBelowMinimumBaseFee {
+                fee_cap: 999_999_999,
+                minimum: 1_000_000_000
+            }
+        );
+        assert!(!err.is_bad_transaction());
+    }
+}
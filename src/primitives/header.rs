@@ -79,13 +79,9 @@ pub struct BerachainHeader {
     /// The hash of the requests trie root, added in EIP-7685.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub requests_hash: Option<B256>,
-    /// Previous proposer public key for Berachain consensus.
-    #[serde(
-        default,
-        skip_serializing_if = "Option::is_none",
-        rename = "parentProposerPubkey",
-        alias = "prevProposerPubkey"
-    )]
+    /// Previous proposer public key for Berachain consensus. Always serialized, as `null` before
+    /// Prague1, so every block and header response carries the field.
+    #[serde(default, rename = "parentProposerPubkey", alias = "prevProposerPubkey")]
     pub prev_proposer_pubkey: Option<BlsPublicKey>,
     /// An arbitrary byte array containing data relevant to this block. This must be 32 bytes or
     /// fewer. Must be last for Compact derive.
@@ -496,6 +492,12 @@ struct CompactBerachainHeader {
     extra_data: Bytes,
 }
 
+/// Fields of [`BerachainHeader`] added after Cancun.
+///
+/// This extends reth's `HeaderExt` of the alloy [`Header`]: `requests_hash` comes first, so its
+/// flag bit and data in rows written by vanilla reth are read unchanged, and `prev_proposer_pubkey`
+/// takes a flag bit reth leaves unset. Headers of databases migrated from reth therefore decode
+/// without a proposer pubkey and need no format detection. New fields must only be appended.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Compact, Serialize, Deserialize)]
 pub(crate) struct BerachainHeaderExt {
     requests_hash: Option<B256>,
@@ -918,4 +920,49 @@ mod tests {
             "Header with prev_proposer_pubkey should be larger when compressed"
         );
     }
+
+    #[test]
+    fn test_reth_rows_are_rewritten_unchanged() {
+        let cancun = Header {
+            number: 7,
+            timestamp: 1_700_000_000,
+            base_fee_per_gas: Some(1_000_000_000),
+            withdrawals_root: Some(B256::repeat_byte(1)),
+            blob_gas_used: Some(131_072),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(B256::repeat_byte(2)),
+            extra_data: Bytes::from_static(b"reth"),
+            ..Default::default()
+        };
+        let prague = Header { requests_hash: Some(B256::repeat_byte(3)), ..cancun.clone() };
+
+        for reth_header in [cancun, prague] {
+            let mut reth_row = Vec::new();
+            reth_header.compress_to_buf(&mut reth_row);
+
+            let header = BerachainHeader::decompress(&reth_row).unwrap();
+            assert_eq!(header, BerachainHeader::from(reth_header.clone()));
+            assert_eq!(header.hash_slow(), reth_header.hash_slow());
+
+            let mut row = Vec::new();
+            header.compress_to_buf(&mut row);
+            assert_eq!(row, reth_row);
+        }
+    }
+
+    #[test]
+    fn test_parent_proposer_pubkey_is_always_serialized() {
+        let mut header = BerachainHeader::from(Header::default());
+        let json = serde_json::to_value(&header).unwrap();
+        assert_eq!(json["parentProposerPubkey"], serde_json::Value::Null);
+        assert_eq!(serde_json::from_value::<BerachainHeader>(json).unwrap(), header);
+
+        header.prev_proposer_pubkey = Some(BlsPublicKey::repeat_byte(7));
+        let json = serde_json::to_value(&header).unwrap();
+        assert_eq!(
+            json["parentProposerPubkey"],
+            serde_json::to_value(BlsPublicKey::repeat_byte(7)).unwrap()
+        );
+        assert_eq!(serde_json::from_value::<BerachainHeader>(json).unwrap(), header);
+    }
 }
@@ -0,0 +1,210 @@
+//! Berachain additions to the `admin` namespace.
+
+use crate::{
+    consensus::quarantine::{BadBlock, BadBlocks},
+    network::static_peers::StaticPeers,
+    node::{
+        allocator::{self, AllocatorStats},
+        debug_bundle::DebugBundles,
+        logging::LogLevelOverrides,
+    },
+    rpc::inflight::{InflightCall, InflightCalls, LONG_CALL_THRESHOLD},
+};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use jsonrpsee_types::{
+    ErrorObjectOwned,
+    error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+};
+use reth_network::Peers;
+use reth_network_peers::NodeRecord;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use tracing::{info, level_filters::LevelFilter};
+
+/// Runtime log configuration, served when the `admin` namespace is enabled.
+#[rpc(server, namespace = "admin")]
+pub trait LogLevelApi {
+    /// Sets the log verbosity of `target` (e.g. `executor`) and its children without restarting.
+    ///
+    /// `level` is one of `trace`, `debug`, `info`, `warn`, `error` or `off`, where `off` removes
+    /// the override. Returns all overrides in effect.
+    #[method(name = "setLogLevel")]
+    fn set_log_level(&self, target: String, level: String) -> RpcResult<BTreeMap<String, String>>;
+}
+
+/// Implementation of [`LogLevelApiServer`] backed by the node's [`LogLevelOverrides`].
+#[derive(Debug, Default, Clone)]
+pub struct LogLevel {
+    /// Overrides of the tracing subscriber, `None` if it was not initialized by bera-reth.
+    pub overrides: Option<Arc<LogLevelOverrides>>,
+}
+
+impl LogLevelApiServer for LogLevel {
+    fn set_log_level(&self, target: String, level: String) -> RpcResult<BTreeMap<String, String>> {
+        let invalid = |msg: String| ErrorObjectOwned::owned(INVALID_PARAMS_CODE, msg, None::<()>);
+
+        let overrides = self
+            .overrides
+            .as_ref()
+            .ok_or_else(|| invalid("runtime log levels are not available".to_string()))?;
+        let level: LevelFilter =
+            level.parse().map_err(|_| invalid(format!("invalid log level `{level}`")))?;
+        overrides.set(&target, level).map_err(|err| invalid(err.to_string()))?;
+        info!(target: "rpc::admin", %target, %level, "Changed log level");
+
+        Ok(overrides
+            .levels()
+            .into_iter()
+            .map(|(target, level)| (target, level.to_string()))
+            .collect())
+    }
+}
+
+/// Debug bundle capture, served when the `admin` namespace is enabled.
+#[rpc(server, namespace = "admin")]
+pub trait DebugBundleApi {
+    /// Writes a debug bundle to the datadir, with a CPU profile of `profileSeconds` (default
+    /// `--debug-bundle.profile-seconds`, `0` to skip it). Returns the bundle directory.
+    #[method(name = "captureDebugBundle")]
+    async fn capture_debug_bundle(&self, profile_seconds: Option<u64>) -> RpcResult<String>;
+}
+
+/// Implementation of [`DebugBundleApiServer`] backed by the node's [`DebugBundles`].
+#[derive(Debug, Clone)]
+pub struct DebugBundle {
+    /// Debug bundle capture of the node.
+    pub bundles: Arc<DebugBundles>,
+}
+
+#[async_trait::async_trait]
+impl DebugBundleApiServer for DebugBundle {
+    async fn capture_debug_bundle(&self, profile_seconds: Option<u64>) -> RpcResult<String> {
+        let internal = |msg: String| ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, msg, None::<()>);
+
+        let dir =
+            self.bundles.capture(profile_seconds).await.map_err(|err| internal(err.to_string()))?;
+        Ok(dir.display().to_string())
+    }
+}
+
+/// Quarantined blocks, served when the `admin` namespace is enabled.
+#[rpc(server, namespace = "admin")]
+pub trait BadBlocksApi {
+    /// Returns the blocks that failed Berachain-specific validation, with the reason, most
+    /// recently rejected first.
+    #[method(name = "badBlocks")]
+    fn bad_blocks(&self) -> RpcResult<Vec<BadBlock>>;
+}
+
+/// Implementation of [`BadBlocksApiServer`] backed by the node's [`BadBlocks`].
+#[derive(Debug, Default, Clone)]
+pub struct BadBlocksRpc {
+    /// Quarantine shared with consensus and the engine validator.
+    pub bad_blocks: Arc<BadBlocks>,
+}
+
+impl BadBlocksApiServer for BadBlocksRpc {
+    fn bad_blocks(&self) -> RpcResult<Vec<BadBlock>> {
+        Ok(self.bad_blocks.list())
+    }
+}
+
+/// Expensive RPC calls in flight, served when the `admin` namespace is enabled.
+#[rpc(server, namespace = "admin")]
+pub trait LongCallsApi {
+    /// Returns the `eth_getLogs`, `trace_*` and `debug_trace*` calls running for at least
+    /// `min_seconds` (default 60), oldest first.
+    #[method(name = "longRunningCalls")]
+    fn long_running_calls(&self, min_seconds: Option<u64>) -> RpcResult<Vec<InflightCall>>;
+
+    /// Aborts the call with `id`, returning whether it was still in flight.
+    #[method(name = "abortCall")]
+    fn abort_call(&self, id: u64) -> RpcResult<bool>;
+}
+
+/// Implementation of [`LongCallsApiServer`] backed by the node's [`InflightCalls`].
+#[derive(Debug, Default, Clone)]
+pub struct LongCalls {
+    /// Expensive calls registered by the RPC middleware of the node.
+    pub calls: Arc<InflightCalls>,
+}
+
+impl LongCallsApiServer for LongCalls {
+    fn long_running_calls(&self, min_seconds: Option<u64>) -> RpcResult<Vec<InflightCall>> {
+        let min_age = min_seconds.map_or(LONG_CALL_THRESHOLD, Duration::from_secs);
+        Ok(self.calls.running_for(min_age))
+    }
+
+    fn abort_call(&self, id: u64) -> RpcResult<bool> {
+        let aborted = self.calls.abort(id);
+        if aborted {
+            info!(target: "rpc::admin", id, "Aborted RPC call");
+        }
+        Ok(aborted)
+    }
+}
+
+/// Allocator statistics and heap profiling, served when the `admin` namespace is enabled.
+#[rpc(server, namespace = "admin")]
+pub trait AllocatorApi {
+    /// Returns the jemalloc statistics of the node.
+    #[method(name = "allocatorStats")]
+    fn allocator_stats(&self) -> RpcResult<AllocatorStats>;
+
+    /// Pauses or resumes the sampling of allocations of binaries built with `jemalloc-prof`.
+    /// Returns the statistics after the change.
+    #[method(name = "setHeapProfiling")]
+    fn set_heap_profiling(&self, active: bool) -> RpcResult<AllocatorStats>;
+}
+
+/// Implementation of [`AllocatorApiServer`] backed by the global allocator.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Allocator;
+
+impl AllocatorApiServer for Allocator {
+    fn allocator_stats(&self) -> RpcResult<AllocatorStats> {
+        allocator::stats().map_err(|err| internal_error(err.to_string()))
+    }
+
+    fn set_heap_profiling(&self, active: bool) -> RpcResult<AllocatorStats> {
+        allocator::set_heap_profiling(active).map_err(|err| internal_error(err.to_string()))?;
+        info!(target: "rpc::admin", active, "Set heap profiling");
+        self.allocator_stats()
+    }
+}
+
+/// Static peer management, replacing reth's `admin_addPeer` and `admin_removePeer` when the
+/// `admin` namespace is enabled.
+#[rpc(server, namespace = "admin")]
+pub trait StaticPeerApi {
+    /// Adds `record` as a static peer: it is kept connected and restored after restarts.
+    #[method(name = "addPeer")]
+    fn add_peer(&self, record: NodeRecord) -> RpcResult<bool>;
+
+    /// Removes the static peer `record` and disconnects it.
+    #[method(name = "removePeer")]
+    fn remove_peer(&self, record: NodeRecord) -> RpcResult<bool>;
+}
+
+/// Implementation of [`StaticPeerApiServer`] backed by the node's [`StaticPeers`].
+#[derive(Debug, Clone)]
+pub struct StaticPeer<N> {
+    /// Static peers of the node's network.
+    pub peers: Arc<StaticPeers<N>>,
+}
+
+impl<N: Peers + 'static> StaticPeerApiServer for StaticPeer<N> {
+    fn add_peer(&self, record: NodeRecord) -> RpcResult<bool> {
+        self.peers.add(record).map_err(|err| internal_error(err.to_string()))?;
+        Ok(true)
+    }
+
+    fn remove_peer(&self, record: NodeRecord) -> RpcResult<bool> {
+        self.peers.remove(record.id).map_err(|err| internal_error(err.to_string()))?;
+        Ok(true)
+    }
+}
+
+fn internal_error(msg: String) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, msg, None::<()>)
+}
@@ -1,19 +1,29 @@
 use crate::{
-    primitives::BerachainHeader,
-    rpc::receipt::BerachainReceiptEnvelope,
+    chainspec::BerachainChainSpec,
+    node::{evm::config::BerachainEvmConfig, fee_history::FeeHistoryRows},
+    pool::validator::BerachainPoolError,
+    primitives::{BerachainHeader, header::BlsPublicKey},
+    rpc::{
+        fee_floor::{FeeFloor, project_next_base_fee},
+        receipt::BerachainReceiptEnvelope,
+        trace,
+    },
     transaction::{BerachainTxEnvelope, BerachainTxType, POL_TX_TYPE},
 };
-use alloy_consensus::Transaction;
-use alloy_eips::eip2930::AccessList;
+use alloy_consensus::{BlockHeader, Transaction};
+use alloy_eips::{BlockNumberOrTag, eip2930::AccessList};
 use alloy_network::{
     BuildResult, Network, NetworkWallet, TransactionBuilder, TransactionBuilderError,
 };
 use alloy_primitives::{Address, B256, Bytes, ChainId, TxKind, U256};
-use alloy_rpc_types_eth::{Transaction as RpcTransaction, TransactionRequest};
+use alloy_rpc_types_eth::{FeeHistory, Transaction as RpcTransaction, TransactionRequest};
 use core::fmt;
 use derive_more::Deref;
 use reth::{
-    providers::{ProviderHeader, ProviderTx},
+    providers::{
+        BlockIdReader, BlockReaderIdExt, HeaderProvider, ProviderError, ProviderHeader, ProviderTx,
+    },
+    revm::{Database, DatabaseCommit, context::result::ResultAndState},
     rpc::compat::{RpcConvert, RpcTypes},
     tasks::{
         TaskSpawner,
@@ -21,7 +31,8 @@ use reth::{
     },
     transaction_pool::{PoolTransaction, TransactionPool},
 };
-use reth_evm::TxEnvFor;
+use reth_evm::{ConfigureEvm, Evm, EvmEnvFor, HaltReasonFor, InspectorFor, TxEnvFor};
+use reth_primitives_traits::{Recovered, SignedTransaction};
 use reth_rpc::eth::DevSigner;
 use reth_rpc_convert::SignableTxRequest;
 use reth_rpc_eth_api::{
@@ -40,6 +51,7 @@ use reth_rpc_eth_types::{
     utils::recover_raw_transaction,
 };
 use reth_transaction_pool::{AddedTransactionOutcome, TransactionOrigin};
+use std::sync::Arc;
 
 impl fmt::Display for BerachainTxType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -328,11 +340,31 @@ impl Network for BerachainNetwork {
         alloy_rpc_types_eth::Block<Self::TransactionResponse, Self::HeaderResponse>;
 }
 
+/// Berachain fields of [`BerachainNetwork`] header responses.
+pub trait BerachainHeaderResponse {
+    /// Returns the pubkey of the proposer of the parent block, set from Prague1 on.
+    fn parent_proposer_pubkey(&self) -> Option<BlsPublicKey>;
+}
+
+impl BerachainHeaderResponse for alloy_rpc_types_eth::Header<BerachainHeader> {
+    fn parent_proposer_pubkey(&self) -> Option<BlsPublicKey> {
+        self.inner.prev_proposer_pubkey
+    }
+}
+
 #[derive(Deref)]
 pub struct BerachainApi<N: RpcNodeCore, Rpc: RpcConvert> {
     /// All nested fields bundled together.
     #[deref]
     pub(super) inner: reth_rpc::EthApi<N, Rpc>,
+    /// Prague1 minimum fee applied to fee suggestions.
+    pub(super) fee_floor: FeeFloor,
+    /// Chain spec projecting the next block base fee of `eth_feeHistory`.
+    pub(super) chain_spec: Arc<BerachainChainSpec>,
+    /// Attributes of the pending block, including its PoL transaction from Prague1 on.
+    pub(super) pending_env: Arc<dyn PendingEnvBuilder<N::Evm>>,
+    /// Persisted fee history rows answering `eth_feeHistory` where reth's cache misses.
+    pub(super) fee_history: Arc<FeeHistoryRows>,
 }
 
 impl<N, Rpc> Clone for BerachainApi<N, Rpc>
@@ -341,7 +373,13 @@ where
     Rpc: RpcConvert,
 {
     fn clone(&self) -> Self {
-        Self { inner: self.inner.clone() }
+        Self {
+            inner: self.inner.clone(),
+            fee_floor: self.fee_floor,
+            chain_spec: self.chain_spec.clone(),
+            pending_env: self.pending_env.clone(),
+            fee_history: self.fee_history.clone(),
+        }
     }
 }
 
@@ -458,6 +496,9 @@ where
     ///
     /// Returns the hash of the transaction.
     async fn send_raw_transaction(&self, tx: Bytes) -> Result<B256, Self::Error> {
+        if tx.first() == Some(&POL_TX_TYPE) {
+            return Err(EthApiError::InvalidParams(BerachainPoolError::PoLTransaction.to_string()))
+        }
         let recovered = recover_raw_transaction(&tx)?;
 
         // broadcast raw transaction to subscribers if there is any.
@@ -524,7 +565,7 @@ where
 
 impl<N, Rpc> EthCall for BerachainApi<N, Rpc>
 where
-    N: RpcNodeCore,
+    N: RpcNodeCore<Evm = BerachainEvmConfig>,
     EthApiError: FromEvmError<N::Evm>,
     Rpc: RpcConvert<Primitives = N::Primitives, Error = EthApiError, TxEnv = TxEnvFor<N::Evm>>,
 {
@@ -532,7 +573,7 @@ where
 
 impl<N, Rpc> Call for BerachainApi<N, Rpc>
 where
-    N: RpcNodeCore,
+    N: RpcNodeCore<Evm = BerachainEvmConfig>,
     EthApiError: FromEvmError<N::Evm>,
     Rpc: RpcConvert<Primitives = N::Primitives, Error = EthApiError, TxEnv = TxEnvFor<N::Evm>>,
 {
@@ -545,11 +586,38 @@ where
     fn max_simulate_blocks(&self) -> u64 {
         self.inner.max_simulate_blocks()
     }
+
+    /// Replays PoL transactions as the block executor ran them.
+    fn replay_transactions_until<'a, DB, I>(
+        &self,
+        db: &mut DB,
+        evm_env: EvmEnvFor<Self::Evm>,
+        transactions: I,
+        target_tx_hash: B256,
+    ) -> Result<usize, Self::Error>
+    where
+        DB: Database<Error = ProviderError> + DatabaseCommit + core::fmt::Debug,
+        I: IntoIterator<Item = Recovered<&'a ProviderTx<Self::Provider>>>,
+    {
+        let mut evm = self.evm_config().evm_with_env(db, evm_env);
+        let mut index = 0;
+        for tx in transactions {
+            if *tx.tx_hash() == target_tx_hash {
+                break
+            }
+            let tx_env = self.evm_config().tx_env(tx);
+            let ResultAndState { state, .. } =
+                trace::transact(&mut evm, tx_env).map_err(Self::Error::from_evm_err)?;
+            evm.db_mut().commit(state);
+            index += 1;
+        }
+        Ok(index)
+    }
 }
 
 impl<N, Rpc> EstimateCall for BerachainApi<N, Rpc>
 where
-    N: RpcNodeCore,
+    N: RpcNodeCore<Evm = BerachainEvmConfig>,
     EthApiError: FromEvmError<N::Evm>,
     Rpc: RpcConvert<Primitives = N::Primitives, Error = EthApiError, TxEnv = TxEnvFor<N::Evm>>,
 {
@@ -561,6 +629,101 @@ where
     EthApiError: FromEvmError<N::Evm>,
     Rpc: RpcConvert<Primitives = N::Primitives, Error = EthApiError>,
 {
+    /// Raised to the minimum base fee once Prague1 is active. The suggested priority fee is paid
+    /// on top of the base fee and is left as reth suggests it.
+    fn gas_price(&self) -> impl Future<Output = Result<U256, Self::Error>> + Send {
+        async move {
+            let price = EthFees::gas_price(&self.inner).await?;
+            Ok(self.fee_floor.clamp(self.latest_timestamp()?, price))
+        }
+    }
+
+    /// Answers from the fee history rows restored on startup while reth's cache does not cover
+    /// the range yet. The next block base fee is recomputed with the Berachain base fee
+    /// parameters and minimum base fee active at the newest block, so it matches the base fee the
+    /// next block will carry across Prague1.
+    fn fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockNumberOrTag,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> impl Future<Output = Result<FeeHistory, Self::Error>> + Send {
+        async move {
+            let mut history = match self.restored_fee_history(
+                block_count,
+                newest_block,
+                reward_percentiles.as_deref(),
+            )? {
+                Some(history) => history,
+                None => {
+                    EthFees::fee_history(&self.inner, block_count, newest_block, reward_percentiles)
+                        .await?
+                }
+            };
+            let newest =
+                (history.oldest_block + history.gas_used_ratio.len() as u64).checked_sub(1);
+            if let Some(header) =
+                newest.map(|newest| self.provider().header_by_number(newest)).transpose()?.flatten()
+            {
+                project_next_base_fee(&self.chain_spec, &header, &mut history);
+            }
+            Ok(history)
+        }
+    }
+}
+
+impl<N, Rpc> BerachainApi<N, Rpc>
+where
+    N: RpcNodeCore,
+    EthApiError: FromEvmError<N::Evm>,
+    Rpc: RpcConvert<Primitives = N::Primitives, Error = EthApiError>,
+{
+    /// Returns the timestamp of the latest block.
+    fn latest_timestamp(&self) -> Result<u64, EthApiError> {
+        let header = self
+            .provider()
+            .latest_header()?
+            .ok_or(EthApiError::HeaderNotFound(BlockNumberOrTag::Latest.into()))?;
+        Ok(header.timestamp())
+    }
+
+    /// Returns the fee history from [`FeeHistoryRows`] if reth's cache misses part of the range
+    /// and the rows hold all of it. Requests reth would reject are left to reth.
+    fn restored_fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockNumberOrTag,
+        reward_percentiles: Option<&[f64]>,
+    ) -> Result<Option<FeeHistory>, EthApiError> {
+        let rows = &self.fee_history;
+        if newest_block.is_pending() || rows.is_empty() {
+            return Ok(None)
+        }
+        let invalid = |percentiles: &[f64]| {
+            percentiles.iter().any(|p| !(0.0..=100.0).contains(p)) ||
+                percentiles.windows(2).any(|pair| pair[0] > pair[1])
+        };
+        if reward_percentiles.is_some_and(invalid) {
+            return Ok(None)
+        }
+        let Some(newest) = self.provider().convert_block_number(newest_block)? else {
+            return Ok(None)
+        };
+
+        let oracle = self.gas_oracle().config();
+        let max_blocks = if reward_percentiles.is_some() {
+            oracle.max_reward_history
+        } else {
+            oracle.max_header_history
+        };
+        let block_count = block_count.min(max_blocks).min(newest + 1);
+        let oldest = newest + 1 - block_count;
+        let cache = self.fee_history_cache();
+        if cache.lower_bound() <= oldest && newest <= cache.upper_bound() {
+            return Ok(None)
+        }
+        Ok(rows.fee_history(newest, block_count, reward_percentiles))
+    }
 }
 
 impl<N, Rpc> EthState for BerachainApi<N, Rpc>
@@ -575,10 +738,25 @@ where
 
 impl<N, Rpc> Trace for BerachainApi<N, Rpc>
 where
-    N: RpcNodeCore,
+    N: RpcNodeCore<Evm = BerachainEvmConfig>,
     EthApiError: FromEvmError<N::Evm>,
     Rpc: RpcConvert<Primitives = N::Primitives>,
 {
+    /// Replays PoL transactions as the block executor ran them.
+    fn inspect<DB, I>(
+        &self,
+        db: DB,
+        evm_env: EvmEnvFor<Self::Evm>,
+        tx_env: TxEnvFor<Self::Evm>,
+        inspector: I,
+    ) -> Result<ResultAndState<HaltReasonFor<Self::Evm>>, Self::Error>
+    where
+        DB: Database<Error = ProviderError>,
+        I: InspectorFor<Self::Evm, DB>,
+    {
+        let mut evm = self.evm_config().evm_with_env_and_inspector(db, evm_env, inspector);
+        trace::transact(&mut evm, tx_env).map_err(Self::Error::from_evm_err)
+    }
 }
 
 impl<N, Rpc> LoadState for BerachainApi<N, Rpc>
@@ -618,6 +796,6 @@ where
 
     #[inline]
     fn pending_env_builder(&self) -> &dyn PendingEnvBuilder<Self::Evm> {
-        self.inner.pending_env_builder()
+        &*self.pending_env
     }
 }
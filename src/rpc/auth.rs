@@ -0,0 +1,248 @@
+//! Engine API endpoint accepting several JWT secrets.
+//!
+//! reth's auth server validates requests against the single `--authrpc.jwtsecret`, so changing the
+//! secret shared with the consensus client requires a restart of both. bera-reth can additionally
+//! serve the engine API on a second port that accepts any of a list of secret files, e.g. the
+//! active and the next secret, or one secret per consensus client when a standby is attached.
+//!
+//! The secret files are re-read on `SIGHUP` and when `admin_reloadJwtSecrets` is called on the
+//! endpoint itself. A rollover is then: add the next secret file and reload, switch the consensus
+//! clients over one at a time, remove the old file and reload again.
+
+use clap::Args;
+use http::{HeaderMap, StatusCode, header::AUTHORIZATION};
+use jsonrpsee_core::{RpcResult, server::RpcModule};
+use jsonrpsee_http_client::{HttpBody, HttpResponse};
+use jsonrpsee_proc_macros::rpc;
+use jsonrpsee_server::ServerBuilder;
+use jsonrpsee_types::{ErrorObjectOwned, error::INTERNAL_ERROR_CODE};
+use reth::tasks::TaskExecutor;
+use reth_rpc_layer::{AuthLayer, AuthValidator, JwtSecret};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+use tracing::info;
+
+/// Settings of the multi-secret engine API endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct EngineAuthConfig {
+    /// Port of the endpoint, on the `--authrpc.addr` interface. Disabled if unset.
+    pub port: Option<u16>,
+    /// Files holding the hex encoded secrets accepted by the endpoint.
+    pub jwt_secrets: Vec<PathBuf>,
+}
+
+impl EngineAuthConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &EngineAuthArgs) -> Self {
+        if let Some(port) = args.port {
+            self.port = Some(port);
+        }
+        if !args.jwt_secrets.is_empty() {
+            self.jwt_secrets = args.jwt_secrets.clone();
+        }
+        self
+    }
+}
+
+/// Command line arguments of the multi-secret engine API endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Engine API secret rotation")]
+pub struct EngineAuthArgs {
+    /// Serve the engine API on this additional port, accepting every `--engine-auth.jwtsecret`
+    #[arg(long = "engine-auth.port", value_name = "PORT")]
+    pub port: Option<u16>,
+
+    /// Secret file accepted by the `--engine-auth.port` endpoint. May be given several times
+    #[arg(long = "engine-auth.jwtsecret", value_name = "PATH")]
+    pub jwt_secrets: Vec<PathBuf>,
+}
+
+/// The set of secrets accepted by the endpoint, re-read from their files on reload.
+#[derive(Debug, Clone)]
+pub struct JwtSecrets {
+    paths: Arc<[PathBuf]>,
+    secrets: Arc<RwLock<Vec<JwtSecret>>>,
+}
+
+impl JwtSecrets {
+    /// Reads the secrets from `paths`.
+    pub fn load(paths: Vec<PathBuf>) -> eyre::Result<Self> {
+        let secrets = Self { paths: paths.into(), secrets: Default::default() };
+        secrets.reload()?;
+        Ok(secrets)
+    }
+
+    /// Re-reads all secret files, returning the number of secrets now accepted.
+    ///
+    /// The accepted secrets are only replaced if every file could be read.
+    pub fn reload(&self) -> eyre::Result<usize> {
+        eyre::ensure!(!self.paths.is_empty(), "no JWT secret files configured");
+        let secrets = self
+            .paths
+            .iter()
+            .map(|path| {
+                JwtSecret::from_file(path).map_err(|err| eyre::eyre!("{}: {err}", path.display()))
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        let count = secrets.len();
+        *self.secrets.write().expect("not poisoned") = secrets;
+        Ok(count)
+    }
+
+    /// Returns whether `jwt` is signed with any of the accepted secrets.
+    pub fn validate(&self, jwt: &str) -> bool {
+        self.secrets.read().expect("not poisoned").iter().any(|secret| secret.validate(jwt).is_ok())
+    }
+}
+
+/// [`AuthValidator`] accepting tokens signed with any secret of a [`JwtSecrets`] set.
+#[derive(Debug, Clone)]
+pub struct RotatingJwtValidator(JwtSecrets);
+
+impl AuthValidator for RotatingJwtValidator {
+    fn validate(&self, headers: &HeaderMap) -> Result<(), HttpResponse> {
+        let jwt = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match jwt {
+            Some(jwt) if self.0.validate(jwt) => Ok(()),
+            Some(_) => Err(unauthorized("invalid JWT")),
+            None => Err(unauthorized("missing or invalid authorization header")),
+        }
+    }
+}
+
+fn unauthorized(reason: &'static str) -> HttpResponse {
+    HttpResponse::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .body(HttpBody::from(reason))
+        .expect("static response is valid")
+}
+
+/// Secret management methods served on the multi-secret endpoint only.
+#[rpc(server, namespace = "admin")]
+pub trait EngineAuthAdminApi {
+    /// Re-reads the configured secret files, returning the number of accepted secrets.
+    #[method(name = "reloadJwtSecrets")]
+    fn reload_jwt_secrets(&self) -> RpcResult<usize>;
+}
+
+impl EngineAuthAdminApiServer for JwtSecrets {
+    fn reload_jwt_secrets(&self) -> RpcResult<usize> {
+        let count = self.reload().map_err(|err| {
+            ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>)
+        })?;
+        info!(target: "rpc::engine_auth", count, "Reloaded JWT secrets");
+        Ok(count)
+    }
+}
+
+/// Serves `engine_module` on the endpoint described by `config`, if enabled.
+///
+/// `engine_module` is the module of reth's auth server, so both endpoints share one engine API.
+pub async fn start_engine_auth_server(
+    config: EngineAuthConfig,
+    ip: IpAddr,
+    mut engine_module: RpcModule<()>,
+    executor: &TaskExecutor,
+) -> eyre::Result<()> {
+    let Some(port) = config.port else { return Ok(()) };
+    let secrets = JwtSecrets::load(config.jwt_secrets)?;
+    engine_module.merge(secrets.clone().into_rpc())?;
+
+    let addr = SocketAddr::new(ip, port);
+    let middleware =
+        tower::ServiceBuilder::new().layer(AuthLayer::new(RotatingJwtValidator(secrets.clone())));
+    let server = ServerBuilder::default()
+        .set_http_middleware(middleware)
+        .build(addr)
+        .await
+        .map_err(|err| eyre::eyre!("failed to bind engine auth endpoint {addr}: {err}"))?;
+    let handle = server.start(engine_module);
+    info!(
+        target: "rpc::engine_auth",
+        %addr,
+        secrets = secrets.paths.len(),
+        "Engine API endpoint with secret rotation started"
+    );
+
+    executor.spawn_critical("engine auth server", async move { handle.stopped().await });
+
+    #[cfg(unix)]
+    executor.spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+        use tracing::warn;
+
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(err) => {
+                warn!(target: "rpc::engine_auth", %err, "Failed to install SIGHUP handler");
+                return
+            }
+        };
+        while hangup.recv().await.is_some() {
+            match secrets.reload() {
+                Ok(count) => info!(target: "rpc::engine_auth", count, "Reloaded JWT secrets"),
+                Err(err) => warn!(target: "rpc::engine_auth", %err, "Failed to reload JWT secrets"),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_rpc_layer::Claims;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn token(secret: &JwtSecret) -> String {
+        let iat = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        secret.encode(&Claims { iat, exp: None }).unwrap()
+    }
+
+    fn headers(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_rotation_accepts_every_configured_secret() {
+        let dir = std::env::temp_dir().join(format!("bera-jwt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (active, next, unknown) =
+            (JwtSecret::random(), JwtSecret::random(), JwtSecret::random());
+        let active_path = dir.join("active.hex");
+        let next_path = dir.join("next.hex");
+        std::fs::write(&active_path, hex::encode(active.as_bytes())).unwrap();
+        std::fs::write(&next_path, hex::encode(next.as_bytes())).unwrap();
+
+        let secrets = JwtSecrets::load(vec![active_path.clone(), next_path]).unwrap();
+        let validator = RotatingJwtValidator(secrets.clone());
+        assert!(validator.validate(&headers(&token(&active))).is_ok());
+        assert!(validator.validate(&headers(&token(&next))).is_ok());
+        assert!(validator.validate(&headers(&token(&unknown))).is_err());
+        assert!(validator.validate(&HeaderMap::new()).is_err());
+
+        // Retire the active secret by replacing its file with the unknown one
+        std::fs::write(&active_path, hex::encode(unknown.as_bytes())).unwrap();
+        assert_eq!(secrets.reload().unwrap(), 2);
+        assert!(validator.validate(&headers(&token(&active))).is_err());
+        assert!(validator.validate(&headers(&token(&unknown))).is_ok());
+
+        // A failed reload keeps the current secrets
+        std::fs::remove_file(&active_path).unwrap();
+        assert!(secrets.reload().is_err());
+        assert!(validator.validate(&headers(&token(&unknown))).is_ok());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,29 @@
+//! Inclusion reports of locally built payloads, served next to the `eth` namespace.
+
+use crate::engine::report::{BuilderReport, BuilderReports};
+use alloy_primitives::BlockHash;
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use std::sync::Arc;
+
+/// Inclusion reports of the payload builder.
+#[rpc(server, namespace = "bera")]
+pub trait BuilderReportApi {
+    /// Returns what the builder included in and passed over for the block with `block_hash`, or
+    /// `null` if the block was not built by this node recently.
+    #[method(name = "builderReport")]
+    fn builder_report(&self, block_hash: BlockHash) -> RpcResult<Option<BuilderReport>>;
+}
+
+/// Implementation of [`BuilderReportApiServer`] backed by the node's [`BuilderReports`].
+#[derive(Debug, Default, Clone)]
+pub struct BuilderReportRpc {
+    /// Reports recorded by the payload builder of the node.
+    pub reports: Arc<BuilderReports>,
+}
+
+impl BuilderReportApiServer for BuilderReportRpc {
+    fn builder_report(&self, block_hash: BlockHash) -> RpcResult<Option<BuilderReport>> {
+        Ok(self.reports.get(&block_hash))
+    }
+}
@@ -0,0 +1,61 @@
+//! Subscription to canonical chain changes, served next to the `eth` namespace.
+
+use crate::node::canonical::{CanonicalChange, CanonicalChanges};
+use jsonrpsee_core::{
+    SubscriptionResult,
+    server::{PendingSubscriptionSink, SubscriptionMessage},
+};
+use jsonrpsee_proc_macros::rpc;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+/// Canonical chain changes, including the blocks replaced by reorgs.
+#[rpc(server, namespace = "bera")]
+pub trait CanonicalChangesApi {
+    /// Streams every change of the canonical chain as a [`CanonicalChange`].
+    #[subscription(
+        name = "subscribeCanonicalChanges" => "canonicalChange",
+        unsubscribe = "unsubscribeCanonicalChanges",
+        item = CanonicalChange
+    )]
+    async fn subscribe_canonical_changes(&self) -> SubscriptionResult;
+}
+
+/// Implementation of [`CanonicalChangesApiServer`] backed by the node's [`CanonicalChanges`].
+#[derive(Debug, Default, Clone)]
+pub struct CanonicalChangesSubscription {
+    /// Changes of the node's canonical chain.
+    pub changes: Arc<CanonicalChanges>,
+}
+
+#[async_trait::async_trait]
+impl CanonicalChangesApiServer for CanonicalChangesSubscription {
+    async fn subscribe_canonical_changes(
+        &self,
+        pending: PendingSubscriptionSink,
+    ) -> SubscriptionResult {
+        let mut changes = self.changes.subscribe();
+        let sink = pending.accept().await?;
+
+        loop {
+            let change = tokio::select! {
+                _ = sink.closed() => return Ok(()),
+                change = changes.recv() => change,
+            };
+            let change = match change {
+                Ok(change) => change,
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(target: "rpc::bera", skipped, "Subscriber missed canonical changes");
+                    continue
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            };
+            let msg =
+                SubscriptionMessage::new(sink.method_name(), sink.subscription_id(), &change)?;
+            if sink.send(msg).await.is_err() {
+                return Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Berachain chain metadata, served in the `bera` namespace.
+//!
+//! The PoL distributor and the Prague1 settings live in the `berachain` section of the genesis
+//! file and the proposer pubkey in a header field the `eth` namespace only shows on full blocks.
+//! [`ChainInfoRpc`] serves them directly, so operators and indexers don't need the genesis file.
+//! Canonical headers are read through the node's [`SealedHeaderCache`].
+
+use crate::{
+    genesis::{BerachainConfigError, BerachainForkConfig, BerachainGenesisConfig},
+    primitives::{BerachainHeader, header::BlsPublicKey},
+    storage::header_cache::SealedHeaderCache,
+};
+use alloy_consensus::BlockHeader;
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::{Address, U64};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use jsonrpsee_types::{ErrorObjectOwned, error::INTERNAL_ERROR_CODE};
+use reth::providers::BlockReaderIdExt;
+use reth_chainspec::{ChainSpecProvider, EthChainSpec};
+use std::sync::Arc;
+
+/// Berachain settings of the chain.
+#[rpc(server, namespace = "bera")]
+pub trait ChainInfoApi {
+    /// Returns the address of the PoL distributor contract.
+    #[method(name = "getPolDistributorAddress")]
+    fn pol_distributor_address(&self) -> RpcResult<Address>;
+
+    /// Returns the `berachain.prague1` section of the genesis.
+    #[method(name = "prague1Config")]
+    fn prague1_config(&self) -> RpcResult<BerachainForkConfig>;
+
+    /// Returns the pubkey of the proposer of the parent of `block`, recorded in its header from
+    /// Prague1 on, or `null` for unknown blocks and blocks before Prague1.
+    #[method(name = "getProposerPubkey")]
+    fn proposer_pubkey(&self, block: BlockNumberOrTag) -> RpcResult<Option<BlsPublicKey>>;
+
+    /// Returns the minimum base fee in wei of the next block, or `null` before Prague1.
+    #[method(name = "minimumBaseFee")]
+    fn minimum_base_fee(&self) -> RpcResult<Option<U64>>;
+}
+
+/// Implementation of [`ChainInfoApiServer`] reading the chain spec and headers of `provider`.
+#[derive(Debug, Clone)]
+pub struct ChainInfoRpc<P> {
+    provider: P,
+    headers: Arc<SealedHeaderCache>,
+    prague1: BerachainForkConfig,
+}
+
+impl<P: ChainSpecProvider<ChainSpec: EthChainSpec>> ChainInfoRpc<P> {
+    /// Serves the settings of the chain of `provider`, reading its headers through `headers`.
+    pub fn new(provider: P, headers: Arc<SealedHeaderCache>) -> Result<Self, BerachainConfigError> {
+        let extra_fields = &provider.chain_spec().genesis().config.extra_fields;
+        let prague1 = BerachainGenesisConfig::try_from(extra_fields)?.prague1;
+        Ok(Self { provider, headers, prague1 })
+    }
+}
+
+/// Returns the minimum base fee of a block built on a parent at `timestamp`, if Prague1 is active.
+fn minimum_base_fee_at(prague1: &BerachainForkConfig, timestamp: u64) -> Option<u64> {
+    (timestamp >= prague1.time).then_some(prague1.minimum_base_fee_wei)
+}
+
+fn internal_error(err: impl std::fmt::Display) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>)
+}
+
+impl<P> ChainInfoApiServer for ChainInfoRpc<P>
+where
+    P: BlockReaderIdExt<Header = BerachainHeader> + Send + Sync + 'static,
+{
+    fn pol_distributor_address(&self) -> RpcResult<Address> {
+        Ok(self.prague1.pol_distributor_address)
+    }
+
+    fn prague1_config(&self) -> RpcResult<BerachainForkConfig> {
+        Ok(self.prague1)
+    }
+
+    fn proposer_pubkey(&self, block: BlockNumberOrTag) -> RpcResult<Option<BlsPublicKey>> {
+        // The pending block is not canonical, so it is never cached
+        if block.is_pending() {
+            let header = self.provider.header_by_number_or_tag(block).map_err(internal_error)?;
+            return Ok(header.and_then(|header| header.prev_proposer_pubkey))
+        }
+        let Some(number) = self.provider.convert_block_number(block).map_err(internal_error)?
+        else {
+            return Ok(None)
+        };
+        let header = self.headers.sealed_header(&self.provider, number).map_err(internal_error)?;
+        Ok(header.and_then(|header| header.prev_proposer_pubkey))
+    }
+
+    fn minimum_base_fee(&self) -> RpcResult<Option<U64>> {
+        let latest = self
+            .provider
+            .latest_header()
+            .map_err(internal_error)?
+            .ok_or_else(|| internal_error("no canonical head"))?;
+        Ok(minimum_base_fee_at(&self.prague1, latest.timestamp()).map(U64::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimum_base_fee_from_prague1() {
+        let prague1 =
+            BerachainForkConfig { time: 100, ..BerachainGenesisConfig::default().prague1 };
+        assert_eq!(minimum_base_fee_at(&prague1, 99), None);
+        assert_eq!(minimum_base_fee_at(&prague1, 100), Some(prague1.minimum_base_fee_wei));
+    }
+}
@@ -0,0 +1,142 @@
+//! Minimum base fee applied to fee suggestions.
+//!
+//! reth's gas price oracle derives suggestions from Ethereum rules only, so on a quiet chain it
+//! suggests fees that the Prague1 minimum base fee makes impossible to include. [`FeeFloor`] raises
+//! `eth_gasPrice` to the minimum of the fork active at the block the suggestion builds on,
+//! Prague1's and, once scheduled and active, Prague2's. `eth_maxPriorityFeePerGas` is left alone:
+//! the tip is paid on top of the base fee, which already includes the minimum. The next block base
+//! fee of `eth_feeHistory` is recomputed by [`project_next_base_fee`] with the base fee change
+//! denominator of the active fork as well.
+
+use crate::{
+    chainspec::BerachainChainSpec,
+    hardforks::{BerachainHardfork, BerachainHardforks},
+};
+use alloy_consensus::BlockHeader;
+use alloy_primitives::U256;
+use alloy_rpc_types_eth::FeeHistory;
+use reth::chainspec::ForkCondition;
+
+/// Minimum fee suggested from Prague1 on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeFloor {
+    /// Activation of Prague1.
+    prague1: ForkCondition,
+    /// Minimum base fee in wei.
+    minimum: u64,
+    /// Activation and minimum base fee of Prague2, if scheduled.
+    prague2: Option<(ForkCondition, u64)>,
+}
+
+impl FeeFloor {
+    /// Returns the floor of `chain_spec`.
+    pub fn new(chain_spec: &BerachainChainSpec) -> Self {
+        Self {
+            prague1: chain_spec.berachain_fork_activation(BerachainHardfork::Prague1),
+            minimum: chain_spec.prague1_minimum_base_fee,
+            prague2: chain_spec.prague2.map(|prague2| {
+                (
+                    chain_spec.berachain_fork_activation(BerachainHardfork::Prague2),
+                    prague2.minimum_base_fee_wei,
+                )
+            }),
+        }
+    }
+
+    /// Returns the minimum fee of a block built on a parent at `timestamp`, if Prague1 is active.
+    pub fn minimum_at(&self, timestamp: u64) -> Option<u64> {
+        if let Some((prague2, minimum)) = self.prague2 {
+            if prague2.active_at_timestamp(timestamp) {
+                return Some(minimum)
+            }
+        }
+        self.prague1.active_at_timestamp(timestamp).then_some(self.minimum)
+    }
+
+    /// Raises `fee`, suggested for a block built on a parent at `timestamp`, to the minimum.
+    pub fn clamp(&self, timestamp: u64, fee: U256) -> U256 {
+        match self.minimum_at(timestamp) {
+            Some(minimum) => fee.max(U256::from(minimum)),
+            None => fee,
+        }
+    }
+}
+
+/// Replaces the next block base fee of `history`, whose newest block is `newest`, with the base
+/// fee [`BerachainChainSpec`] gives the block built on it. Base fees of past blocks are left as
+/// they were.
+pub fn project_next_base_fee(
+    chain_spec: &BerachainChainSpec,
+    newest: &impl BlockHeader,
+    history: &mut FeeHistory,
+) {
+    let Some(projected) = chain_spec.next_base_fee_after(newest) else { return };
+    if let Some(next) = history.base_fee_per_gas.last_mut() {
+        *next = projected as u128;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chainspec::BERACHAIN_DEV, primitives::BerachainHeader};
+    use reth_chainspec::EthChainSpec;
+
+    const FLOOR: FeeFloor = FeeFloor {
+        prague1: ForkCondition::Timestamp(100),
+        minimum: 1_000_000_000,
+        prague2: Some((ForkCondition::Timestamp(200), 3_000_000_000)),
+    };
+
+    #[test]
+    fn test_clamp_after_prague1() {
+        assert_eq!(FLOOR.clamp(99, U256::from(7)), U256::from(7));
+        assert_eq!(FLOOR.clamp(100, U256::from(7)), U256::from(1_000_000_000));
+        assert_eq!(FLOOR.clamp(100, U256::from(2_000_000_000u64)), U256::from(2_000_000_000u64));
+        assert_eq!(FLOOR.clamp(200, U256::from(2_000_000_000u64)), U256::from(3_000_000_000u64));
+    }
+
+    #[test]
+    fn test_project_next_base_fee_across_prague1() {
+        let mut genesis = BERACHAIN_DEV.genesis().clone();
+        genesis.config.extra_fields.insert(
+            "berachain".to_string(),
+            serde_json::json!({
+                "prague1": {
+                    "time": 100,
+                    "baseFeeChangeDenominator": 48,
+                    "minimumBaseFeeWei": 1000000000,
+                    "polDistributorAddress": "0x4200000000000000000000000000000000000042"
+                }
+            }),
+        );
+        let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
+        // A full block raises the base fee by 1/8 before Prague1 and by 1/48 after
+        let parent = |timestamp| BerachainHeader {
+            timestamp,
+            gas_used: 30_000_000,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(4_800_000_000),
+            ..Default::default()
+        };
+        let history = || FeeHistory {
+            base_fee_per_gas: vec![7, 8, 9],
+            oldest_block: 10,
+            ..Default::default()
+        };
+
+        let mut before = history();
+        project_next_base_fee(&chain_spec, &parent(99), &mut before);
+        assert_eq!(before.base_fee_per_gas, vec![7, 8, 5_400_000_000]);
+
+        let mut after = history();
+        project_next_base_fee(&chain_spec, &parent(100), &mut after);
+        assert_eq!(after.base_fee_per_gas, vec![7, 8, 4_900_000_000]);
+
+        // The minimum base fee applies from Prague1 on
+        let mut quiet = history();
+        let empty = BerachainHeader { gas_used: 0, base_fee_per_gas: Some(7), ..parent(100) };
+        project_next_base_fee(&chain_spec, &empty, &mut quiet);
+        assert_eq!(quiet.base_fee_per_gas, vec![7, 8, 1_000_000_000]);
+    }
+}
@@ -0,0 +1,133 @@
+//! Expensive RPC calls in flight.
+//!
+//! A trace or log query that runs for minutes keeps its MDBX read transaction open, and MDBX cannot
+//! reuse pages freed after the oldest open reader started, so the database file grows until the
+//! call ends. [`RpcSpanLayer`](super::spans::RpcSpanLayer) registers every expensive call with
+//! the node's [`InflightCalls`], which lets operators list the calls running for too long with
+//! `admin_longRunningCalls` and cancel one with `admin_abortCall`. Work the call already handed to
+//! the blocking or tracing pool finishes in the background, bounded by reth's
+//! `--db.read-transaction-timeout`.
+
+use futures::future::{AbortHandle, AbortRegistration};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Age from which a call is considered long-running.
+pub const LONG_CALL_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// An expensive call in flight, as returned by `admin_longRunningCalls`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InflightCall {
+    /// Id to abort the call with.
+    pub id: u64,
+    /// Method of the call.
+    pub method: String,
+    /// Parameters of the call, truncated.
+    pub params: String,
+    /// Seconds since the call started.
+    pub elapsed_seconds: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    method: String,
+    params: String,
+    started: Instant,
+    abort: AbortHandle,
+}
+
+/// Registry of the expensive calls in flight.
+#[derive(Debug, Default)]
+pub struct InflightCalls {
+    next_id: AtomicU64,
+    calls: Mutex<HashMap<u64, Entry>>,
+}
+
+impl InflightCalls {
+    /// Registers a call, returning a guard removing it when dropped and the registration to make
+    /// it abortable with.
+    pub fn register(
+        self: &Arc<Self>,
+        method: &str,
+        params: &str,
+    ) -> (CallGuard, AbortRegistration) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (abort, registration) = AbortHandle::new_pair();
+        let entry = Entry {
+            method: method.to_string(),
+            params: params.to_string(),
+            started: Instant::now(),
+            abort,
+        };
+        self.calls.lock().expect("not poisoned").insert(id, entry);
+        (CallGuard { calls: self.clone(), id }, registration)
+    }
+
+    /// Returns the calls running for at least `min_age`, oldest first.
+    pub fn running_for(&self, min_age: Duration) -> Vec<InflightCall> {
+        let calls = self.calls.lock().expect("not poisoned");
+        let mut calls: Vec<_> = calls
+            .iter()
+            .filter(|(_, entry)| entry.started.elapsed() >= min_age)
+            .map(|(id, entry)| InflightCall {
+                id: *id,
+                method: entry.method.clone(),
+                params: entry.params.clone(),
+                elapsed_seconds: entry.started.elapsed().as_secs(),
+            })
+            .collect();
+        calls.sort_by_key(|call| std::cmp::Reverse(call.elapsed_seconds));
+        calls
+    }
+
+    /// Aborts call `id`, returning whether it was in flight.
+    pub fn abort(&self, id: u64) -> bool {
+        let calls = self.calls.lock().expect("not poisoned");
+        calls.get(&id).inspect(|entry| entry.abort.abort()).is_some()
+    }
+}
+
+/// Removes a call from the [`InflightCalls`] when dropped.
+#[derive(Debug)]
+pub struct CallGuard {
+    calls: Arc<InflightCalls>,
+    id: u64,
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        self.calls.calls.lock().expect("not poisoned").remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::{Abortable, Aborted};
+
+    #[test]
+    fn test_register_list_and_abort() {
+        let calls = Arc::new(InflightCalls::default());
+        let (guard, registration) = calls.register("trace_block", "[\"latest\"]");
+        let running = calls.running_for(Duration::ZERO);
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].method, "trace_block");
+        assert!(calls.running_for(LONG_CALL_THRESHOLD).is_empty());
+
+        assert!(calls.abort(running[0].id));
+        let call = Abortable::new(futures::future::pending::<()>(), registration);
+        assert_eq!(futures::executor::block_on(call), Err(Aborted));
+
+        drop(guard);
+        assert!(calls.running_for(Duration::ZERO).is_empty());
+        assert!(!calls.abort(running[0].id));
+    }
+}
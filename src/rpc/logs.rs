@@ -0,0 +1,267 @@
+//! Parallel `eth_getLogs`.
+//!
+//! Indexers backfilling from genesis ask for logs over tens of thousands of blocks at once, which
+//! reth scans one block after the other. With `--get-logs.workers` above zero, [`ParallelLogs`]
+//! replaces `eth_getLogs`: ranges are split into chunks of `--get-logs.chunk-blocks` blocks, which
+//! are scanned on the blocking pool by at most that many workers shared by all calls. Each chunk
+//! checks the bloom of the filter against the header of each block and only reads the receipts of
+//! blocks that may contain matching logs. Chunks are merged in block order, so results are the
+//! same as reth's, and reth's `--rpc.max-blocks-per-filter` and `--rpc.max-logs-per-response`
+//! limits still apply.
+
+use alloy_consensus::BlockHeader;
+use alloy_primitives::BlockNumber;
+use alloy_rpc_types_eth::{Filter, FilterBlockOption, Log};
+use clap::Args;
+use futures::{StreamExt, stream};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use jsonrpsee_types::{
+    ErrorObjectOwned,
+    error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+};
+use reth::providers::BlockReaderIdExt;
+use reth_primitives_traits::SignedTransaction;
+use reth_rpc_eth_types::logs_utils::{ProviderOrBlock, append_matching_block_logs};
+use serde::{Deserialize, Serialize};
+use std::{ops::RangeInclusive, sync::Arc};
+use tokio::sync::Semaphore;
+
+/// Settings of parallel `eth_getLogs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct GetLogsConfig {
+    /// Chunks scanned at once over all calls. reth's `eth_getLogs` is served if zero.
+    pub workers: usize,
+    /// Blocks per chunk.
+    pub chunk_blocks: u64,
+}
+
+impl Default for GetLogsConfig {
+    fn default() -> Self {
+        Self { workers: 4, chunk_blocks: 1000 }
+    }
+}
+
+impl GetLogsConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &GetLogsArgs) -> Self {
+        if let Some(workers) = args.workers {
+            self.workers = workers;
+        }
+        if let Some(chunk_blocks) = args.chunk_blocks {
+            self.chunk_blocks = chunk_blocks;
+        }
+        self
+    }
+}
+
+/// Command line arguments of parallel `eth_getLogs`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "Parallel eth_getLogs")]
+pub struct GetLogsArgs {
+    /// Chunks of `eth_getLogs` ranges scanned at once, 0 to serve reth's implementation
+    /// [default: 4]
+    #[arg(long = "get-logs.workers", value_name = "COUNT")]
+    pub workers: Option<usize>,
+
+    /// Blocks per chunk of `eth_getLogs` ranges [default: 1000]
+    #[arg(long = "get-logs.chunk-blocks", value_name = "BLOCKS")]
+    pub chunk_blocks: Option<u64>,
+}
+
+/// `eth_getLogs` scanning block ranges in parallel.
+#[rpc(server, namespace = "eth")]
+pub trait GetLogsApi {
+    /// Returns the logs matching `filter`.
+    #[method(name = "getLogs")]
+    async fn logs(&self, filter: Filter) -> RpcResult<Vec<Log>>;
+}
+
+/// Implementation of [`GetLogsApiServer`] reading blocks and receipts from a provider.
+#[derive(Debug, Clone)]
+pub struct ParallelLogs<P> {
+    provider: P,
+    chunk_blocks: u64,
+    workers: Arc<Semaphore>,
+    max_workers: usize,
+    max_blocks: u64,
+    max_logs: usize,
+}
+
+impl<P> ParallelLogs<P> {
+    /// Creates the API of `config` over `provider`, `None` if reth's implementation is served.
+    ///
+    /// Calls spanning more than `max_blocks` blocks or returning more than `max_logs` logs fail.
+    pub fn new(provider: P, config: GetLogsConfig, max_blocks: u64, max_logs: u64) -> Option<Self> {
+        (config.workers > 0).then(|| Self {
+            provider,
+            chunk_blocks: config.chunk_blocks.max(1),
+            workers: Arc::new(Semaphore::new(config.workers)),
+            max_workers: config.workers,
+            max_blocks,
+            max_logs: max_logs.try_into().unwrap_or(usize::MAX),
+        })
+    }
+}
+
+impl<P> ParallelLogs<P>
+where
+    P: BlockReaderIdExt<Transaction: SignedTransaction>,
+{
+    /// Returns the blocks `filter` spans.
+    fn block_range(&self, filter: &Filter) -> RpcResult<RangeInclusive<BlockNumber>> {
+        let best = self.provider.best_block_number().map_err(internal_error)?;
+        let (from, to) = match filter.block_option {
+            FilterBlockOption::AtBlockHash(hash) => {
+                let number = self
+                    .provider
+                    .block_number(hash)
+                    .map_err(internal_error)?
+                    .ok_or_else(|| invalid_params(format!("unknown block {hash}")))?;
+                (number, number)
+            }
+            FilterBlockOption::Range { from_block, to_block } => {
+                // `pending` and blocks the node does not know of yet stand for the best block
+                let resolve = |block: Option<_>| -> RpcResult<BlockNumber> {
+                    let number = block
+                        .map(|block| self.provider.convert_block_number(block))
+                        .transpose()
+                        .map_err(internal_error)?
+                        .flatten();
+                    Ok(number.map_or(best, |number| number.min(best)))
+                };
+                (resolve(from_block)?, resolve(to_block)?)
+            }
+        };
+        if from > to {
+            return Err(invalid_params(format!("invalid block range {from}..={to}")))
+        }
+        if to - from >= self.max_blocks {
+            return Err(invalid_params(format!("query exceeds max block range {}", self.max_blocks)))
+        }
+        Ok(from..=to)
+    }
+}
+
+#[async_trait::async_trait]
+impl<P> GetLogsApiServer for ParallelLogs<P>
+where
+    P: BlockReaderIdExt<Transaction: SignedTransaction> + Clone + Send + Sync + 'static,
+{
+    async fn logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
+        let range = self.block_range(&filter)?;
+        let from = *range.start();
+        let filter = Arc::new(filter);
+        let scans = chunks(range, self.chunk_blocks).into_iter().map(|chunk| {
+            let provider = self.provider.clone();
+            let filter = filter.clone();
+            let workers = self.workers.clone();
+            async move {
+                let permit = workers.acquire_owned().await.expect("never closed");
+                let last = *chunk.end();
+                let logs = tokio::task::spawn_blocking(move || {
+                    let _permit = permit;
+                    scan(&provider, &filter, chunk)
+                })
+                .await
+                .map_err(internal_error)?
+                .map_err(internal_error)?;
+                RpcResult::Ok((last, logs))
+            }
+        });
+
+        let mut scans = stream::iter(scans).buffered(self.max_workers);
+        let mut logs = Vec::new();
+        let mut scanned = None;
+        while let Some(result) = scans.next().await {
+            let (last, chunk) = result?;
+            logs.extend(chunk);
+            if logs.len() > self.max_logs {
+                let mut msg = format!("query exceeds max results {}", self.max_logs);
+                if let Some(scanned) = scanned {
+                    msg.push_str(&format!(", retry with the range {from}-{scanned}"));
+                }
+                return Err(invalid_params(msg))
+            }
+            scanned = Some(last);
+        }
+        Ok(logs)
+    }
+}
+
+/// Splits `range` into consecutive chunks of at most `size` blocks.
+fn chunks(range: RangeInclusive<BlockNumber>, size: u64) -> Vec<RangeInclusive<BlockNumber>> {
+    let (mut start, end) = range.into_inner();
+    let mut chunks = Vec::new();
+    while start <= end {
+        let last = start.saturating_add(size - 1).min(end);
+        chunks.push(start..=last);
+        if last == u64::MAX {
+            break
+        }
+        start = last + 1;
+    }
+    chunks
+}
+
+/// Returns the logs of the blocks in `range` matching `filter`, in block order.
+fn scan<P>(
+    provider: &P,
+    filter: &Filter,
+    range: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<Log>>
+where
+    P: BlockReaderIdExt<Transaction: SignedTransaction>,
+{
+    let mut logs = Vec::new();
+    for header in provider.sealed_headers_range(range)? {
+        if !filter.matches_bloom(header.logs_bloom()) {
+            continue
+        }
+        let receipts = provider.receipts_by_block(header.hash().into())?.ok_or_else(|| {
+            eyre::eyre!("receipts of block {} are not available", header.number())
+        })?;
+        append_matching_block_logs(
+            &mut logs,
+            ProviderOrBlock::Provider(provider),
+            filter,
+            header.num_hash(),
+            &receipts,
+            false,
+            header.timestamp(),
+        )?;
+    }
+    Ok(logs)
+}
+
+fn invalid_params(msg: String) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INVALID_PARAMS_CODE, msg, None::<()>)
+}
+
+fn internal_error(err: impl std::fmt::Display) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_cover_range_in_order() {
+        assert_eq!(chunks(1..=2500, 1000), vec![1..=1000, 1001..=2000, 2001..=2500]);
+        assert_eq!(chunks(7..=7, 1000), vec![7..=7]);
+        assert_eq!(chunks(0..=3, 1), vec![0..=0, 1..=1, 2..=2, 3..=3]);
+        assert_eq!(chunks(u64::MAX - 1..=u64::MAX, 10), vec![u64::MAX - 1..=u64::MAX]);
+    }
+
+    #[test]
+    fn test_disabled_without_workers() {
+        let config = GetLogsConfig { workers: 0, chunk_blocks: 1000 };
+        assert!(ParallelLogs::new((), config, 100_000, 20_000).is_none());
+        let config = GetLogsConfig { workers: 2, chunk_blocks: 0 };
+        let logs = ParallelLogs::new((), config, 100_000, 20_000).unwrap();
+        assert_eq!(logs.chunk_blocks, 1);
+        assert_eq!(logs.max_workers, 2);
+    }
+}
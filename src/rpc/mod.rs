@@ -1,34 +1,102 @@
+pub mod admin;
 pub mod api;
+pub mod auth;
+pub mod builder;
+pub mod canonical;
+pub mod chain;
+pub mod fee_floor;
+pub mod inflight;
+pub mod logs;
+pub mod profiler;
+pub mod pubsub;
 pub mod receipt;
+pub mod response_cache;
+pub mod spans;
+pub mod sync;
+pub mod trace;
 
 use crate::{
-    engine::{BerachainExecutionData, rpc::BerachainEngineApiBuilder},
-    node::evm::config::BerachainNextBlockEnvAttributes,
+    chainspec::BerachainChainSpec,
+    consensus::quarantine::BadBlocks,
+    engine::{
+        BerachainExecutionData,
+        latency::{PayloadLatency, install_payload_latency},
+        report::BuilderReports,
+        rpc::BerachainEngineApiBuilder,
+        validator::BerachainEngineValidatorBuilder,
+    },
+    network::static_peers::{StaticPeersConfig, install_static_peers},
+    node::{
+        BerachainNode,
+        canonical::{CanonicalChanges, install_canonical_changes},
+        debug_bundle::{DebugBundleConfig, install_debug_bundles},
+        evm::{
+            config::{
+                BerachainEvmConfig, BerachainNextBlockEnvAttributes, BerachainPendingEnvBuilder,
+            },
+            profiler::Profiler,
+        },
+        fee_history::FeeHistoryRows,
+        logging::LogLevelOverrides,
+        sync_progress::{SyncProgress, install_sync_progress},
+    },
     primitives::BerachainPrimitives,
     rpc::{
+        admin::{
+            Allocator, AllocatorApiServer, BadBlocksApiServer, BadBlocksRpc, DebugBundle,
+            DebugBundleApiServer, LogLevel, LogLevelApiServer, LongCalls, LongCallsApiServer,
+            StaticPeer, StaticPeerApiServer,
+        },
         api::{BerachainApi, BerachainNetwork},
+        auth::{EngineAuthConfig, start_engine_auth_server},
+        builder::{BuilderReportApiServer, BuilderReportRpc},
+        canonical::{CanonicalChangesApiServer, CanonicalChangesSubscription},
+        chain::{ChainInfoApiServer, ChainInfoRpc},
+        fee_floor::FeeFloor,
+        inflight::InflightCalls,
+        logs::{GetLogsApiServer, GetLogsConfig, ParallelLogs},
+        profiler::{ProfilerApiServer, ProfilerRpc},
+        pubsub::BerachainPubSub,
         receipt::BerachainEthReceiptConverter,
+        response_cache::{ResponseCache, ResponseCacheLayer, install_response_cache},
+        spans::RpcSpanLayer,
+        sync::{SyncStatusApiServer, SyncStatusRpc},
     },
+    storage::header_cache::SealedHeaderCache,
 };
 use reth::{
-    api::{FullNodeComponents, HeaderTy, PrimitivesTy},
+    api::{FullNodeComponents, PrimitivesTy},
     chainspec::EthereumHardforks,
     revm::context::TxEnv,
-    rpc::{api::eth::FromEvmError, server_types::eth::EthApiError},
+    rpc::{
+        api::{EthPubSubApiServer, eth::FromEvmError},
+        server_types::{RethRpcModule, eth::EthApiError},
+    },
 };
 use reth_chainspec::{ChainSpecProvider, EthChainSpec};
 use reth_evm::{ConfigureEvm, EvmFactory, EvmFactoryFor, TxEnvFor};
 use reth_node_api::{AddOnsContext, FullNodeTypes, NodeAddOns, NodeTypes};
 use reth_node_builder::rpc::{
     EngineApiBuilder, EngineValidatorAddOn, EngineValidatorBuilder, EthApiBuilder, EthApiCtx,
-    RethRpcAddOns, RpcAddOns, RpcHandle,
+    RethRpcAddOns, RpcAddOns, RpcHandle, RpcModuleContainer,
 };
 use reth_rpc_convert::{RpcConvert, RpcConverter};
-use reth_rpc_eth_api::helpers::pending_block::BuildPendingEnv;
+use std::sync::Arc;
+use tower::layer::util::Stack;
 
 /// Builds `BerachainEthApi` for Berachain.
 #[derive(Debug, Default)]
-pub struct BerachainEthApiBuilder;
+pub struct BerachainEthApiBuilder {
+    /// Persisted fee history rows `eth_feeHistory` falls back to.
+    fee_history: Arc<FeeHistoryRows>,
+}
+
+impl BerachainEthApiBuilder {
+    /// Creates the builder answering `eth_feeHistory` from `fee_history` where reth's cache misses.
+    pub const fn new(fee_history: Arc<FeeHistoryRows>) -> Self {
+        Self { fee_history }
+    }
+}
 
 pub type BerachainEthRpcConverterFor<N> = RpcConverter<
     BerachainNetwork,
@@ -39,8 +107,8 @@ pub type BerachainEthRpcConverterFor<N> = RpcConverter<
 impl<N> EthApiBuilder<N> for BerachainEthApiBuilder
 where
     N: FullNodeComponents<
-            Types: NodeTypes<ChainSpec: EthereumHardforks, Primitives = BerachainPrimitives>,
-            Evm: ConfigureEvm<NextBlockEnvCtx: BuildPendingEnv<HeaderTy<N::Types>>>,
+            Types: NodeTypes<ChainSpec = BerachainChainSpec, Primitives = BerachainPrimitives>,
+            Evm = BerachainEvmConfig,
         >,
     BerachainEthRpcConverterFor<N>: RpcConvert<
             Primitives = PrimitivesTy<N::Types>,
@@ -69,7 +137,16 @@ where
             .gas_oracle_config(ctx.config.gas_oracle)
             .build();
 
-        Ok(BerachainApi { inner: api })
+        let chain_spec = ctx.components.provider().chain_spec();
+        let fee_floor = FeeFloor::new(&chain_spec);
+        let pending_env = Arc::new(BerachainPendingEnvBuilder::new(chain_spec.clone()));
+        Ok(BerachainApi {
+            inner: api,
+            fee_floor,
+            chain_spec,
+            pending_env,
+            fee_history: self.fee_history,
+        })
     }
 }
 
@@ -81,31 +158,88 @@ pub struct BerachainAddOns<
     EV,
     EB = BerachainEngineApiBuilder<EV>,
 > {
-    inner: RpcAddOns<N, EthB, EV, EB>,
+    inner: RpcAddOns<N, EthB, EV, EB, Stack<RpcSpanLayer, ResponseCacheLayer>>,
+    /// Engine API endpoint with JWT secret rotation.
+    engine_auth: EngineAuthConfig,
+    /// Parallel `eth_getLogs`.
+    get_logs: GetLogsConfig,
+    /// Cache of RPC responses about finalized blocks, `None` if it is disabled.
+    response_cache: Option<Arc<ResponseCache>>,
+    /// Quarantine served by `admin_badBlocks`.
+    bad_blocks: Arc<BadBlocks>,
+    /// Payload latency breakdown the insertion and persistence of blocks are recorded in.
+    latency: Arc<PayloadLatency>,
+    /// Log verbosity overrides served by `admin_setLogLevel`.
+    log_levels: Option<Arc<LogLevelOverrides>>,
+    /// Static peers kept connected and managed by `admin_addPeer` and `admin_removePeer`.
+    static_peers: StaticPeersConfig,
+    /// Canonical chain changes served by `bera_subscribeCanonicalChanges`.
+    canonical_changes: Arc<CanonicalChanges>,
+    /// Sync progress served by `bera_syncStatus`.
+    sync_progress: Arc<SyncProgress>,
+    /// Inclusion reports of the payload builder served by `bera_builderReport`.
+    builder_reports: Arc<BuilderReports>,
+    /// Expensive calls in flight served by `admin_longRunningCalls` and `admin_abortCall`.
+    inflight_calls: Arc<InflightCalls>,
+    /// Sealed headers shared with the engine validator, read by the `bera` namespace.
+    header_cache: Arc<SealedHeaderCache>,
+    /// Debug bundles captured on `SIGUSR1` and by `admin_captureDebugBundle`.
+    debug_bundle: DebugBundleConfig,
 }
 
-impl<N> Default
-    for BerachainAddOns<
-        N,
-        BerachainEthApiBuilder,
-        crate::engine::validator::BerachainEngineValidatorBuilder,
-    >
+impl<N> BerachainAddOns<N, BerachainEthApiBuilder, BerachainEngineValidatorBuilder>
 where
     N: FullNodeComponents,
     BerachainEthApiBuilder: EthApiBuilder<N>,
 {
-    fn default() -> Self {
+    /// Creates the add-ons of `node`, sharing its quarantine, latency breakdown, log levels,
+    /// canonical chain changes, sync progress, builder reports, calls in flight, fee history rows
+    /// and header cache and using its RPC settings.
+    pub fn new(node: &BerachainNode) -> Self {
+        let config = node.config();
+        let validator = BerachainEngineValidatorBuilder::new(
+            node.bad_blocks().clone(),
+            node.latency().clone(),
+            node.header_cache().clone(),
+        );
+        let response_cache = ResponseCache::new(config.response_cache).map(Arc::new);
         Self {
             inner: RpcAddOns::new(
-                BerachainEthApiBuilder,
-                crate::engine::validator::BerachainEngineValidatorBuilder::default(),
-                BerachainEngineApiBuilder::default(),
-                Default::default(),
+                BerachainEthApiBuilder::new(node.fee_history().clone()),
+                validator.clone(),
+                BerachainEngineApiBuilder::new(validator),
+                Stack::new(
+                    RpcSpanLayer::new(node.inflight_calls().clone()),
+                    ResponseCacheLayer::new(response_cache.clone()),
+                ),
             ),
+            engine_auth: config.engine_auth.clone(),
+            get_logs: config.get_logs,
+            response_cache,
+            bad_blocks: node.bad_blocks().clone(),
+            latency: node.latency().clone(),
+            log_levels: node.log_levels().cloned(),
+            static_peers: config.p2p.clone(),
+            canonical_changes: node.canonical_changes().clone(),
+            sync_progress: node.sync_progress().clone(),
+            builder_reports: node.builder_reports().clone(),
+            inflight_calls: node.inflight_calls().clone(),
+            header_cache: node.header_cache().clone(),
+            debug_bundle: config.debug_bundle,
         }
     }
 }
 
+impl<N> Default for BerachainAddOns<N, BerachainEthApiBuilder, BerachainEngineValidatorBuilder>
+where
+    N: FullNodeComponents,
+    BerachainEthApiBuilder: EthApiBuilder<N>,
+{
+    fn default() -> Self {
+        Self::new(&BerachainNode::default())
+    }
+}
+
 impl<N, EthB, EV, EB> BerachainAddOns<N, EthB, EV, EB>
 where
     N: FullNodeComponents,
@@ -116,8 +250,38 @@ where
     where
         T: Send,
     {
-        let Self { inner } = self;
-        BerachainAddOns { inner: inner.with_engine_api(engine_api_builder) }
+        let Self {
+            inner,
+            engine_auth,
+            get_logs,
+            response_cache,
+            bad_blocks,
+            latency,
+            log_levels,
+            static_peers,
+            canonical_changes,
+            sync_progress,
+            builder_reports,
+            inflight_calls,
+            header_cache,
+            debug_bundle,
+        } = self;
+        BerachainAddOns {
+            inner: inner.with_engine_api(engine_api_builder),
+            engine_auth,
+            get_logs,
+            response_cache,
+            bad_blocks,
+            latency,
+            log_levels,
+            static_peers,
+            canonical_changes,
+            sync_progress,
+            builder_reports,
+            inflight_calls,
+            header_cache,
+            debug_bundle,
+        }
     }
 
     /// Replace the engine validator builder.
@@ -128,8 +292,38 @@ where
     where
         T: Send,
     {
-        let Self { inner } = self;
-        BerachainAddOns { inner: inner.with_engine_validator(engine_validator_builder) }
+        let Self {
+            inner,
+            engine_auth,
+            get_logs,
+            response_cache,
+            bad_blocks,
+            latency,
+            log_levels,
+            static_peers,
+            canonical_changes,
+            sync_progress,
+            builder_reports,
+            inflight_calls,
+            header_cache,
+            debug_bundle,
+        } = self;
+        BerachainAddOns {
+            inner: inner.with_engine_validator(engine_validator_builder),
+            engine_auth,
+            get_logs,
+            response_cache,
+            bad_blocks,
+            latency,
+            log_levels,
+            static_peers,
+            canonical_changes,
+            sync_progress,
+            builder_reports,
+            inflight_calls,
+            header_cache,
+            debug_bundle,
+        }
     }
 }
 
@@ -157,7 +351,132 @@ where
         self,
         ctx: reth_node_api::AddOnsContext<'_, N>,
     ) -> eyre::Result<Self::Handle> {
-        self.inner.launch_add_ons(ctx).await
+        let Self {
+            inner,
+            engine_auth,
+            get_logs,
+            response_cache,
+            bad_blocks,
+            latency,
+            log_levels,
+            static_peers,
+            canonical_changes,
+            sync_progress,
+            builder_reports,
+            inflight_calls,
+            header_cache,
+            debug_bundle,
+        } = self;
+        let engine_auth_enabled = engine_auth.port.is_some();
+        let executor = ctx.node.task_executor().clone();
+        install_payload_latency(
+            latency,
+            ctx.engine_events.new_listener(),
+            ctx.node.provider().clone(),
+            &executor,
+        );
+        install_canonical_changes(
+            canonical_changes.clone(),
+            ctx.node.provider().clone(),
+            &executor,
+        );
+        install_sync_progress(
+            sync_progress.clone(),
+            ctx.node.provider().clone(),
+            ctx.engine_events.new_listener(),
+            &executor,
+        );
+        if let Some(response_cache) = response_cache {
+            install_response_cache(response_cache, ctx.node.provider().clone(), &executor);
+        }
+        let auth_ip = ctx.config.rpc.auth_addr;
+        let parallel_logs = ParallelLogs::new(
+            ctx.node.provider().clone(),
+            get_logs,
+            ctx.config.rpc.rpc_max_blocks_per_filter.unwrap_or_max(),
+            ctx.config.rpc.rpc_max_logs_per_response.unwrap_or_max(),
+        );
+        let chain_info = ChainInfoRpc::new(ctx.node.provider().clone(), header_cache)?;
+        let profiler =
+            Arc::new(Profiler::new(ctx.node.provider().clone(), ctx.node.evm_config().clone()));
+        let provider = ctx.node.provider().clone();
+        let (module_tx, module_rx) = tokio::sync::oneshot::channel();
+        let bad_blocks = BadBlocksRpc { bad_blocks };
+        let log_level = LogLevel { overrides: log_levels };
+        let debug_bundle = DebugBundle {
+            bundles: install_debug_bundles(
+                debug_bundle,
+                ctx.config.datadir().data_dir().to_path_buf(),
+                ctx.node.pool().clone(),
+                ctx.engine_events.new_listener(),
+                ctx.node.payload_builder_handle(),
+                &executor,
+            )
+            .await?,
+        };
+        let static_peer = StaticPeer {
+            peers: install_static_peers(
+                static_peers,
+                ctx.config.datadir().data_dir(),
+                ctx.node.network().clone(),
+                &executor,
+            )?,
+        };
+        let handle = inner
+            .launch_add_ons_with(ctx, move |container| {
+                let RpcModuleContainer { modules, auth_module, registry, .. } = container;
+                modules.merge_if_module_configured(RethRpcModule::Admin, log_level.into_rpc())?;
+                modules
+                    .merge_if_module_configured(RethRpcModule::Admin, debug_bundle.into_rpc())?;
+                modules.merge_if_module_configured(RethRpcModule::Admin, bad_blocks.into_rpc())?;
+                modules.merge_if_module_configured(
+                    RethRpcModule::Admin,
+                    LongCalls { calls: inflight_calls }.into_rpc(),
+                )?;
+                modules.merge_if_module_configured(RethRpcModule::Admin, Allocator.into_rpc())?;
+                modules.merge_if_module_configured(
+                    RethRpcModule::Eth,
+                    CanonicalChangesSubscription { changes: canonical_changes }.into_rpc(),
+                )?;
+                modules.merge_if_module_configured(
+                    RethRpcModule::Eth,
+                    SyncStatusRpc { progress: sync_progress }.into_rpc(),
+                )?;
+                modules.merge_if_module_configured(RethRpcModule::Eth, chain_info.into_rpc())?;
+                modules.merge_if_module_configured(
+                    RethRpcModule::Eth,
+                    BuilderReportRpc { reports: builder_reports }.into_rpc(),
+                )?;
+                modules.merge_if_module_configured(
+                    RethRpcModule::Debug,
+                    ProfilerRpc { profiler }.into_rpc(),
+                )?;
+                if let Some(parallel_logs) = parallel_logs {
+                    modules.add_or_replace_if_module_configured(
+                        RethRpcModule::Eth,
+                        parallel_logs.into_rpc(),
+                    )?;
+                }
+                modules.add_or_replace_if_module_configured(
+                    RethRpcModule::Admin,
+                    static_peer.into_rpc(),
+                )?;
+                let pubsub = BerachainPubSub::new(provider, registry.eth_handlers().pubsub.clone());
+                modules
+                    .add_or_replace_if_module_configured(RethRpcModule::Eth, pubsub.into_rpc())?;
+                if engine_auth_enabled {
+                    let _ = module_tx.send(auth_module.module_mut().clone());
+                }
+                Ok(())
+            })
+            .await?;
+
+        // Serve the engine API module of reth's auth server on the multi-secret endpoint as well
+        if let Ok(engine_module) = module_rx.await {
+            start_engine_auth_server(engine_auth, auth_ip, engine_module, &executor).await?;
+        }
+
+        Ok(handle)
     }
 }
 
@@ -0,0 +1,54 @@
+//! Execution profiles of block ranges, served next to the `debug` namespace.
+
+use crate::node::evm::profiler::{ExecutionProfile, MAX_RPC_BLOCKS, Profiler};
+use alloy_primitives::BlockNumber;
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use jsonrpsee_types::{
+    ErrorObjectOwned,
+    error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+};
+use std::sync::Arc;
+
+/// Gas and time profiling of block execution.
+#[rpc(server, namespace = "debug")]
+pub trait ProfilerApi {
+    /// Re-executes blocks `from` to `to` and returns the gas and time spent per opcode and per
+    /// contract. At most [`MAX_RPC_BLOCKS`] blocks are profiled per call.
+    #[method(name = "profileExecution")]
+    async fn profile_execution(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> RpcResult<ExecutionProfile>;
+}
+
+/// Implementation of [`ProfilerApiServer`] backed by the node's [`Profiler`].
+#[derive(Debug, Clone)]
+pub struct ProfilerRpc {
+    /// Profiler re-executing the blocks of the node.
+    pub profiler: Arc<Profiler>,
+}
+
+#[async_trait::async_trait]
+impl ProfilerApiServer for ProfilerRpc {
+    async fn profile_execution(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> RpcResult<ExecutionProfile> {
+        let error = |code, msg: String| ErrorObjectOwned::owned(code, msg, None::<()>);
+        if from == 0 || from > to {
+            return Err(error(INVALID_PARAMS_CODE, format!("invalid block range {from}..={to}")))
+        }
+        if to - from >= MAX_RPC_BLOCKS {
+            let msg = format!("at most {MAX_RPC_BLOCKS} blocks can be profiled at once");
+            return Err(error(INVALID_PARAMS_CODE, msg))
+        }
+        let profiler = self.profiler.clone();
+        tokio::task::spawn_blocking(move || profiler.profile(from, to))
+            .await
+            .map_err(|err| error(INTERNAL_ERROR_CODE, err.to_string()))?
+            .map_err(|err| error(INTERNAL_ERROR_CODE, err.to_string()))
+    }
+}
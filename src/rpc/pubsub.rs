@@ -0,0 +1,119 @@
+//! `eth_subscribe` with Berachain headers.
+//!
+//! `newHeads` notifications should carry the same header as `eth_getBlockByNumber`, the
+//! `HeaderResponse` of [`BerachainNetwork`](crate::rpc::api::BerachainNetwork), so subscribers
+//! see `parentProposerPubkey` next to the base fee. [`BerachainPubSub`] serves `newHeads` from the
+//! canonical state notifications of the node with the full [`BerachainHeader`] and hands every
+//! other subscription kind to reth's [`EthPubSub`].
+
+use crate::primitives::{BerachainBlock, BerachainHeader, BerachainPrimitives};
+use alloy_primitives::U256;
+use alloy_rpc_types_eth::{
+    Header,
+    pubsub::{Params, SubscriptionKind},
+};
+use futures::{StreamExt, stream};
+use jsonrpsee_core::{
+    SubscriptionResult,
+    server::{PendingSubscriptionSink, SubscriptionMessage},
+};
+use reth::{providers::CanonStateSubscriptions, rpc::api::EthPubSubApiServer};
+use reth_primitives_traits::RecoveredBlock;
+use reth_rpc::eth::EthPubSub;
+use reth_rpc_eth_api::{EthApiTypes, RpcTransaction};
+
+/// `eth_subscribe` serving `newHeads` with [`BerachainHeader`]s.
+#[derive(Debug, Clone)]
+pub struct BerachainPubSub<P, Eth> {
+    /// Source of the canonical state notifications.
+    provider: P,
+    /// reth's subscriptions, serving every other kind.
+    inner: EthPubSub<Eth>,
+}
+
+impl<P, Eth> BerachainPubSub<P, Eth> {
+    /// Serves `newHeads` from `provider` and the other subscriptions from `inner`.
+    pub const fn new(provider: P, inner: EthPubSub<Eth>) -> Self {
+        Self { provider, inner }
+    }
+}
+
+/// Returns the `newHeads` notification of `block`.
+pub fn rpc_header(block: &RecoveredBlock<BerachainBlock>) -> Header<BerachainHeader> {
+    Header::from_consensus(
+        block.clone_sealed_header().into(),
+        None,
+        Some(U256::from(block.rlp_length())),
+    )
+}
+
+#[async_trait::async_trait]
+impl<P, Eth> EthPubSubApiServer<RpcTransaction<Eth::NetworkTypes>> for BerachainPubSub<P, Eth>
+where
+    P: CanonStateSubscriptions<Primitives = BerachainPrimitives> + 'static,
+    Eth: EthApiTypes + 'static,
+    EthPubSub<Eth>: EthPubSubApiServer<RpcTransaction<Eth::NetworkTypes>>,
+{
+    async fn subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        kind: SubscriptionKind,
+        params: Option<Params>,
+    ) -> SubscriptionResult {
+        if !matches!(kind, SubscriptionKind::NewHeads) {
+            return self.inner.subscribe(pending, kind, params).await
+        }
+        let mut headers = self.provider.canonical_state_stream().flat_map(|notification| {
+            stream::iter(notification.committed().blocks_iter().map(rpc_header).collect::<Vec<_>>())
+        });
+        let sink = pending.accept().await?;
+
+        loop {
+            let header = tokio::select! {
+                _ = sink.closed() => return Ok(()),
+                header = headers.next() => header,
+            };
+            let Some(header) = header else { return Ok(()) };
+            let msg =
+                SubscriptionMessage::new(sink.method_name(), sink.subscription_id(), &header)?;
+            if sink.send(msg).await.is_err() {
+                return Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitives::{BerachainBlockBody, header::BlsPublicKey},
+        rpc::api::BerachainHeaderResponse,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_new_heads_keep_berachain_fields() {
+        let pubkey = BlsPublicKey::repeat_byte(7);
+        let header = BerachainHeader {
+            number: 5,
+            base_fee_per_gas: Some(1_000_000_000),
+            prev_proposer_pubkey: Some(pubkey),
+            ..Default::default()
+        };
+        let block = RecoveredBlock::new_unhashed(
+            BerachainBlock { header, body: BerachainBlockBody::default() },
+            vec![],
+        );
+
+        let header = rpc_header(&block);
+        assert_eq!(header.hash, block.hash());
+        assert_eq!(header.parent_proposer_pubkey(), Some(pubkey));
+
+        let value = serde_json::to_value(&header).unwrap();
+        assert_eq!(value["number"], json!("0x5"));
+        assert_eq!(value["baseFeePerGas"], json!("0x3b9aca00"));
+        assert_eq!(value["parentProposerPubkey"], json!(pubkey));
+        assert_eq!(serde_json::from_value::<Header<BerachainHeader>>(value).unwrap(), header);
+    }
+}
@@ -3,10 +3,11 @@ use crate::{
     transaction::{BerachainTxType, POL_TX_TYPE},
 };
 use alloy_consensus::{Eip658Value, Receipt, ReceiptWithBloom, TxReceipt, TxType, Typed2718};
-use alloy_eips::eip2718::{Decodable2718, Eip2718Result, Encodable2718, IsTyped2718};
+use alloy_eips::eip2718::{Decodable2718, Eip2718Error, Eip2718Result, Encodable2718, IsTyped2718};
 use alloy_primitives::Bloom;
-use alloy_rlp::BufMut;
+use alloy_rlp::{BufMut, Decodable, Encodable};
 use alloy_rpc_types_eth::{Log, TransactionReceipt};
+use reth::revm::handler::SYSTEM_ADDRESS;
 use reth_chainspec::EthChainSpec;
 use reth_primitives_traits::InMemorySize;
 use reth_rpc_convert::transaction::{ConvertReceiptInput, ReceiptConverter};
@@ -85,6 +86,35 @@ impl BerachainReceiptEnvelope {
             Self::Berachain(receipt) => &receipt.logs_bloom,
         }
     }
+
+    /// Returns the consensus form of the receipt, whose logs carry no RPC metadata.
+    fn consensus_receipt(&self) -> ReceiptWithBloom<Receipt<alloy_primitives::Log>> {
+        let receipt = self.as_receipt();
+        ReceiptWithBloom {
+            receipt: Receipt {
+                status: receipt.status,
+                cumulative_gas_used: receipt.cumulative_gas_used,
+                logs: receipt.logs.iter().map(|log| log.inner.clone()).collect(),
+            },
+            logs_bloom: *self.bloom(),
+        }
+    }
+
+    /// Decodes the RLP body of a receipt of type `ty`.
+    fn decode_typed(ty: BerachainTxType, buf: &mut &[u8]) -> Eip2718Result<Self> {
+        let ReceiptWithBloom { receipt, logs_bloom } =
+            ReceiptWithBloom::<Receipt<alloy_primitives::Log>>::decode(buf)?;
+        let receipt = Receipt {
+            status: receipt.status,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            logs: receipt
+                .logs
+                .into_iter()
+                .map(|inner| Log { inner, ..Default::default() })
+                .collect(),
+        };
+        Ok(Self::from_typed(ty, ReceiptWithBloom { receipt, logs_bloom }))
+    }
 }
 
 impl TxReceipt for BerachainReceiptEnvelope {
@@ -126,10 +156,12 @@ impl IsTyped2718 for BerachainReceiptEnvelope {
     }
 }
 
+/// Receipts are encoded as in Ethereum, with `0x7e` as the type of PoL receipts. Only the
+/// consensus fields of logs are encoded, so decoded logs carry no block or transaction metadata.
 impl Encodable2718 for BerachainReceiptEnvelope {
     fn encode_2718_len(&self) -> usize {
         let ty = self.ty();
-        (!matches!(ty, 0)) as usize + 64 // Approximate length, can be refined later
+        (!matches!(ty, 0)) as usize + self.consensus_receipt().length()
     }
 
     fn encode_2718(&self, out: &mut dyn BufMut) {
@@ -137,19 +169,20 @@ impl Encodable2718 for BerachainReceiptEnvelope {
         if !matches!(ty, 0) {
             out.put_u8(ty);
         }
-        // For now, skip encoding - this will be implemented later if needed
+        self.consensus_receipt().encode(out);
     }
 }
 
 impl Decodable2718 for BerachainReceiptEnvelope {
-    fn typed_decode(_ty: u8, _buf: &mut &[u8]) -> Eip2718Result<Self> {
-        // For now, return an error - this will be implemented later if needed
-        Err(alloy_eips::eip2718::Eip2718Error::UnexpectedType(_ty))
+    fn typed_decode(ty: u8, buf: &mut &[u8]) -> Eip2718Result<Self> {
+        match BerachainTxType::try_from(ty) {
+            Ok(tx_type) if ty != 0 => Self::decode_typed(tx_type, buf),
+            _ => Err(Eip2718Error::UnexpectedType(ty)),
+        }
     }
 
-    fn fallback_decode(_buf: &mut &[u8]) -> Eip2718Result<Self> {
-        // For now, return an error - this will be implemented later if needed
-        Err(alloy_eips::eip2718::Eip2718Error::UnexpectedType(0))
+    fn fallback_decode(buf: &mut &[u8]) -> Eip2718Result<Self> {
+        Self::decode_typed(BerachainTxType::Ethereum(TxType::Legacy), buf)
     }
 }
 
@@ -193,11 +226,109 @@ where
         for input in inputs {
             let tx_type = input.receipt.tx_type;
             let blob_params = self.chain_spec.blob_params_at_timestamp(input.meta.timestamp);
-            receipts.push(build_receipt(&input, blob_params, |receipt_with_bloom| {
+            let mut receipt = build_receipt(&input, blob_params, |receipt_with_bloom| {
                 BerachainReceiptEnvelope::from_typed(tx_type, receipt_with_bloom)
-            }));
+            });
+            if tx_type == BerachainTxType::Berachain {
+                into_system_receipt(&mut receipt);
+            }
+            receipts.push(receipt);
         }
 
         Ok(receipts)
     }
 }
+
+/// Turns the receipt of a PoL transaction into that of the system call it executes as: sent by the
+/// system address, consuming no block gas and paying nothing, so indexers don't attribute the base
+/// fee carried by the transaction to anyone.
+fn into_system_receipt(receipt: &mut TransactionReceipt<BerachainReceiptEnvelope>) {
+    receipt.from = SYSTEM_ADDRESS;
+    receipt.effective_gas_price = 0;
+    receipt.gas_used = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::ReceiptEnvelope;
+    use alloy_primitives::{Address, B256, LogData, bytes};
+
+    fn receipt_with_log(tx_type: BerachainTxType) -> BerachainReceiptEnvelope {
+        let inner = alloy_primitives::Log {
+            address: Address::repeat_byte(5),
+            data: LogData::new_unchecked(vec![B256::repeat_byte(6)], bytes!("0badc0de")),
+        };
+        let logs_bloom = alloy_primitives::logs_bloom([&inner]);
+        let receipt = Receipt {
+            status: true.into(),
+            cumulative_gas_used: 42_000,
+            logs: vec![Log { inner, ..Default::default() }],
+        };
+        BerachainReceiptEnvelope::from_typed(tx_type, ReceiptWithBloom { receipt, logs_bloom })
+    }
+
+    #[test]
+    fn test_receipt_2718_round_trip() {
+        for tx_type in [
+            BerachainTxType::Ethereum(TxType::Legacy),
+            BerachainTxType::Ethereum(TxType::Eip1559),
+            BerachainTxType::Ethereum(TxType::Eip7702),
+            BerachainTxType::Berachain,
+        ] {
+            let receipt = receipt_with_log(tx_type);
+            let encoded = receipt.encoded_2718();
+            assert_eq!(encoded.len(), receipt.encode_2718_len());
+            let decoded = BerachainReceiptEnvelope::decode_2718(&mut encoded.as_slice()).unwrap();
+            assert_eq!(decoded, receipt);
+        }
+    }
+
+    #[test]
+    fn test_receipt_2718_matches_ethereum() {
+        let receipt = receipt_with_log(BerachainTxType::Ethereum(TxType::Eip1559));
+        let ethereum = ReceiptEnvelope::Eip1559(receipt.consensus_receipt());
+        assert_eq!(receipt.encoded_2718(), ethereum.encoded_2718());
+
+        let pol = receipt_with_log(BerachainTxType::Berachain).encoded_2718();
+        assert_eq!(pol[0], POL_TX_TYPE);
+        assert_eq!(pol[1..], receipt.encoded_2718()[1..]);
+    }
+
+    #[test]
+    fn test_receipt_2718_rejects_unknown_types() {
+        let encoded = receipt_with_log(BerachainTxType::Berachain).encoded_2718();
+        for ty in [0, 5, 0x7f] {
+            let mut bytes = encoded.clone();
+            bytes[0] = ty;
+            assert!(BerachainReceiptEnvelope::decode_2718(&mut bytes.as_slice()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_pol_receipt_is_a_system_receipt() {
+        let envelope = BerachainReceiptEnvelope::from_typed(
+            BerachainTxType::Berachain,
+            Receipt::<Log> { status: true.into(), cumulative_gas_used: 0, logs: vec![] },
+        );
+        let mut receipt = TransactionReceipt {
+            inner: envelope,
+            transaction_hash: B256::repeat_byte(1),
+            transaction_index: Some(0),
+            block_hash: Some(B256::repeat_byte(2)),
+            block_number: Some(1),
+            gas_used: 21_000,
+            effective_gas_price: 1_000_000_000,
+            blob_gas_used: None,
+            blob_gas_price: None,
+            from: Address::repeat_byte(3),
+            to: Some(Address::repeat_byte(4)),
+            contract_address: None,
+        };
+        into_system_receipt(&mut receipt);
+        assert_eq!(receipt.from, SYSTEM_ADDRESS);
+        assert_eq!(receipt.effective_gas_price, 0);
+        assert_eq!(receipt.gas_used, 0);
+        assert_eq!(receipt.to, Some(Address::repeat_byte(4)));
+    }
+}
@@ -0,0 +1,399 @@
+//! Cache of RPC responses about finalized blocks.
+//!
+//! Public endpoints answer the same explorer queries over and over: blocks by hash, receipts and
+//! traces of old blocks. Responses about finalized blocks never change, so with
+//! `--response-cache.size-mb` [`ResponseCacheLayer`] keeps them in memory, keyed by method and
+//! parameters, and answers repeated calls without touching the database. A response is cached
+//! once the block it is about, read from the parameters or the `number`/`blockNumber` field of
+//! the result, is finalized. Calls naming a block by tag (`latest`, `safe`, ...), `null` results
+//! and errors are never cached. Entries expire after `--response-cache.ttl-seconds`, and the least
+//! recently used ones are evicted beyond the size limit.
+//!
+//! Hits and misses are counted by the `berachain_rpc_response_cache_*` metrics.
+
+use alloy_primitives::BlockNumber;
+use clap::Args;
+use futures::StreamExt;
+use jsonrpsee_core::{
+    middleware::{Batch, Notification, RpcServiceT},
+    server::MethodResponse,
+};
+use jsonrpsee_types::{Request, ResponsePayload};
+use reth::{
+    providers::{BlockIdReader, CanonStateSubscriptions},
+    tasks::TaskExecutor,
+};
+use reth_metrics::{
+    Metrics,
+    metrics::{Counter, Gauge},
+};
+use schnellru::{ByLength, LruMap};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, value::RawValue};
+use std::{
+    future::Future,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Methods whose responses are cached once their block is finalized.
+const CACHED_METHODS: [&str; 8] = [
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getBlockReceipts",
+    "eth_getTransactionByHash",
+    "eth_getTransactionReceipt",
+    "trace_block",
+    "trace_transaction",
+    "debug_traceBlockByNumber",
+];
+
+/// Settings of the response cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ResponseCacheConfig {
+    /// Memory for cached responses, in MiB. Disabled if zero.
+    pub size_mb: u64,
+    /// Seconds a response stays cached.
+    pub ttl_seconds: u64,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self { size_mb: 0, ttl_seconds: 3600 }
+    }
+}
+
+impl ResponseCacheConfig {
+    /// Overrides settings with those given on the command line.
+    pub fn with_args(mut self, args: &ResponseCacheArgs) -> Self {
+        if let Some(size_mb) = args.size_mb {
+            self.size_mb = size_mb;
+        }
+        if let Some(ttl_seconds) = args.ttl_seconds {
+            self.ttl_seconds = ttl_seconds;
+        }
+        self
+    }
+}
+
+/// Command line arguments of the response cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Args)]
+#[command(next_help_heading = "RPC response cache")]
+pub struct ResponseCacheArgs {
+    /// Memory for cached responses about finalized blocks, in MiB [default: 0, disabled]
+    #[arg(long = "response-cache.size-mb", value_name = "MIB")]
+    pub size_mb: Option<u64>,
+
+    /// Seconds a response stays cached [default: 3600]
+    #[arg(long = "response-cache.ttl-seconds", value_name = "SECONDS")]
+    pub ttl_seconds: Option<u64>,
+}
+
+/// Response cache metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.rpc.response_cache")]
+struct ResponseCacheMetrics {
+    /// Calls answered from the cache.
+    hits: Counter,
+    /// Cacheable calls that were not cached.
+    misses: Counter,
+    /// Cached responses.
+    entries: Gauge,
+    /// Bytes of cached responses.
+    size_bytes: Gauge,
+}
+
+#[derive(Debug)]
+struct Entry {
+    result: Box<RawValue>,
+    inserted: Instant,
+}
+
+#[derive(Debug)]
+struct Entries {
+    map: LruMap<String, Entry, ByLength>,
+    size: usize,
+}
+
+/// Responses about finalized blocks, by method and parameters.
+pub struct ResponseCache {
+    max_size: usize,
+    ttl: Duration,
+    entries: Mutex<Entries>,
+    /// Highest finalized block, kept up to date by [`install_response_cache`].
+    finalized: AtomicU64,
+    metrics: ResponseCacheMetrics,
+}
+
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache").field("max_size", &self.max_size).finish_non_exhaustive()
+    }
+}
+
+impl ResponseCache {
+    /// Creates the cache of `config`, `None` if it is disabled.
+    pub fn new(config: ResponseCacheConfig) -> Option<Self> {
+        (config.size_mb > 0).then(|| Self {
+            max_size: (config.size_mb * 1024 * 1024) as usize,
+            ttl: Duration::from_secs(config.ttl_seconds),
+            entries: Mutex::new(Entries { map: LruMap::new(ByLength::new(u32::MAX)), size: 0 }),
+            finalized: AtomicU64::new(0),
+            metrics: ResponseCacheMetrics::default(),
+        })
+    }
+
+    /// Returns the cached result of `key`, counting a hit or a miss.
+    fn get(&self, key: &str) -> Option<Box<RawValue>> {
+        let mut entries = self.entries.lock().expect("not poisoned");
+        let fresh = entries
+            .map
+            .get(key)
+            .map(|entry| (entry.inserted.elapsed() < self.ttl).then(|| entry.result.clone()));
+        if let Some(None) = fresh {
+            let expired = entries.map.remove(key).expect("present");
+            entries.size -= key.len() + expired.result.get().len();
+        }
+        let result = fresh.flatten();
+        let counter = if result.is_some() { &self.metrics.hits } else { &self.metrics.misses };
+        counter.increment(1);
+        result
+    }
+
+    /// Caches `result` under `key`, evicting the least recently used entries beyond the size.
+    fn insert(&self, key: String, result: Box<RawValue>) {
+        let size = key.len() + result.get().len();
+        if size > self.max_size {
+            return
+        }
+        let mut entries = self.entries.lock().expect("not poisoned");
+        if let Some(replaced) = entries.map.remove(&key) {
+            entries.size -= key.len() + replaced.result.get().len();
+        }
+        entries.map.insert(key, Entry { result, inserted: Instant::now() });
+        entries.size += size;
+        while entries.size > self.max_size {
+            let Some((key, evicted)) = entries.map.pop_oldest() else { break };
+            entries.size -= key.len() + evicted.result.get().len();
+        }
+        self.metrics.entries.set(entries.map.len() as f64);
+        self.metrics.size_bytes.set(entries.size as f64);
+    }
+}
+
+/// Returns the cache key of a call of `method` with `params`, if its response may be cached.
+fn cache_key(method: &str, params: Option<&str>) -> Option<String> {
+    if !CACHED_METHODS.contains(&method) {
+        return None
+    }
+    let params = params.unwrap_or("[]");
+    // blocks named by tag move with the chain
+    if matches!(first_param(params), Some(Value::String(block)) if !block.starts_with("0x")) {
+        return None
+    }
+    Some(format!("{method}:{params}"))
+}
+
+/// Returns the first of the positional `params`.
+fn first_param(params: &str) -> Option<Value> {
+    serde_json::from_str::<Vec<Value>>(params).ok()?.into_iter().next()
+}
+
+/// Returns the block number of a quantity, as a hex string or an integer.
+fn parse_number(value: &Value) -> Option<BlockNumber> {
+    match value {
+        Value::String(hex) => u64::from_str_radix(hex.strip_prefix("0x")?, 16).ok(),
+        value => value.as_u64(),
+    }
+}
+
+/// Block a result is about.
+#[derive(Debug, Deserialize)]
+struct Located {
+    #[serde(alias = "blockNumber")]
+    number: Option<Value>,
+}
+
+/// Results naming their block, alone or in a list.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LocatedResult {
+    One(Located),
+    Many(Vec<Located>),
+}
+
+/// Returns the block a call with `params` and `result` is about.
+fn block_number(params: Option<&str>, result: &RawValue) -> Option<BlockNumber> {
+    if let Some(number) = params.and_then(first_param).as_ref().and_then(parse_number) {
+        return Some(number)
+    }
+    let located = match serde_json::from_str(result.get()).ok()? {
+        LocatedResult::One(located) => located,
+        LocatedResult::Many(list) => list.into_iter().next()?,
+    };
+    parse_number(&located.number?)
+}
+
+/// Result of a successful response.
+#[derive(Debug, Deserialize)]
+struct Success<'a> {
+    #[serde(borrow)]
+    result: &'a RawValue,
+}
+
+/// Returns the result of `response` to a call with `params` if it is about a block up to
+/// `finalized`.
+fn finalized_result(
+    response: &MethodResponse,
+    params: Option<&str>,
+    finalized: BlockNumber,
+) -> Option<Box<RawValue>> {
+    if !response.is_success() {
+        return None
+    }
+    let Success { result } = serde_json::from_str(response.as_result()).ok()?;
+    let number = block_number(params, result)?;
+    (result.get() != "null" && number <= finalized).then(|| result.to_owned())
+}
+
+/// RPC middleware answering repeated calls about finalized blocks from the [`ResponseCache`].
+#[derive(Debug, Clone, Default)]
+pub struct ResponseCacheLayer {
+    cache: Option<Arc<ResponseCache>>,
+}
+
+impl ResponseCacheLayer {
+    /// Creates the middleware of `cache`, passing calls through if it is `None`.
+    pub fn new(cache: Option<Arc<ResponseCache>>) -> Self {
+        Self { cache }
+    }
+}
+
+impl<S> tower::Layer<S> for ResponseCacheLayer {
+    type Service = ResponseCacheService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ResponseCacheService { inner, cache: self.cache.clone() }
+    }
+}
+
+/// Service created by [`ResponseCacheLayer`].
+#[derive(Debug, Clone)]
+pub struct ResponseCacheService<S> {
+    inner: S,
+    cache: Option<Arc<ResponseCache>>,
+}
+
+impl<S> RpcServiceT for ResponseCacheService<S>
+where
+    S: RpcServiceT<MethodResponse = MethodResponse> + Send + Sync,
+{
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = S::NotificationResponse;
+    type BatchResponse = S::BatchResponse;
+
+    fn call<'a>(&self, request: Request<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let cached = self.cache.clone().and_then(|cache| {
+            let params = request.params();
+            let params = params.as_str().map(str::to_string);
+            let key = cache_key(request.method_name(), params.as_deref())?;
+            Some((cache, key, params))
+        });
+        let hit = cached.as_ref().and_then(|(cache, key, _)| cache.get(key));
+        let id = request.id().into_owned();
+        let call = hit.is_none().then(|| self.inner.call(request));
+
+        async move {
+            let Some(call) = call else {
+                let result = ResponsePayload::success(hit.expect("cache hit"));
+                return MethodResponse::response(id, result, usize::MAX)
+            };
+            let response = call.await;
+            if let Some((cache, key, params)) = cached {
+                let finalized = cache.finalized.load(Ordering::Relaxed);
+                if let Some(result) = finalized_result(&response, params.as_deref(), finalized) {
+                    cache.insert(key, result);
+                }
+            }
+            response
+        }
+    }
+
+    fn batch<'a>(&self, requests: Batch<'a>) -> impl Future<Output = S::BatchResponse> + Send + 'a {
+        self.inner.batch(requests)
+    }
+
+    fn notification<'a>(
+        &self,
+        notification: Notification<'a>,
+    ) -> impl Future<Output = S::NotificationResponse> + Send + 'a {
+        self.inner.notification(notification)
+    }
+}
+
+/// Tracks the finalized block of `provider` in `cache`, which caches responses up to it.
+pub fn install_response_cache<P>(cache: Arc<ResponseCache>, provider: P, executor: &TaskExecutor)
+where
+    P: CanonStateSubscriptions + BlockIdReader + Send + Sync + 'static,
+{
+    let mut notifications = provider.canonical_state_stream();
+    executor.spawn(async move {
+        while notifications.next().await.is_some() {
+            if let Ok(Some(finalized)) = provider.finalized_block_number() {
+                cache.finalized.fetch_max(finalized, Ordering::Relaxed);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(json: &str) -> Box<RawValue> {
+        RawValue::from_string(json.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_cache_keys() {
+        let hash = "[\"0x5c3d8f1e0f0e2a8c2f0d4d6f1a0b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d\",false]";
+        assert!(cache_key("eth_getBlockByHash", Some(hash)).is_some());
+        assert!(cache_key("eth_getBlockByNumber", Some("[\"0x10\",true]")).is_some());
+        assert!(cache_key("eth_getBlockByNumber", Some("[\"latest\",true]")).is_none());
+        assert!(cache_key("debug_traceBlockByNumber", Some("[\"finalized\"]")).is_none());
+        assert!(cache_key("eth_getBalance", Some("[\"0x00\",\"0x10\"]")).is_none());
+    }
+
+    #[test]
+    fn test_block_numbers_from_params_and_results() {
+        assert_eq!(block_number(Some("[\"0x10\",{}]"), &raw("[]")), Some(16));
+        assert_eq!(block_number(None, &raw("{\"number\":\"0x20\",\"hash\":\"0x01\"}")), Some(32));
+        assert_eq!(block_number(None, &raw("{\"blockNumber\":\"0x21\"}")), Some(33));
+        assert_eq!(block_number(Some("[\"0xabc0\"]"), &raw("[]")), Some(0xabc0));
+        assert_eq!(block_number(None, &raw("[{\"blockNumber\":34,\"type\":\"call\"}]")), Some(34));
+        assert_eq!(block_number(None, &raw("[]")), None);
+        assert_eq!(block_number(None, &raw("{\"blockNumber\":null}")), None);
+    }
+
+    #[test]
+    fn test_size_limit_and_ttl() {
+        let cache =
+            ResponseCache::new(ResponseCacheConfig { size_mb: 1, ttl_seconds: 3600 }).unwrap();
+        let large = raw(&format!("\"{}\"", "a".repeat(600 * 1024)));
+        cache.insert("a".to_string(), large.clone());
+        cache.insert("b".to_string(), large);
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_some());
+
+        let expired =
+            ResponseCache::new(ResponseCacheConfig { size_mb: 1, ttl_seconds: 0 }).unwrap();
+        expired.insert("a".to_string(), raw("1"));
+        assert!(expired.get("a").is_none());
+        assert_eq!(expired.entries.lock().unwrap().size, 0);
+        assert!(ResponseCache::new(ResponseCacheConfig::default()).is_none());
+    }
+}
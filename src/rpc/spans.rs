@@ -0,0 +1,135 @@
+//! Spans around expensive RPC calls.
+//!
+//! Log queries and tracing calls can keep a node busy for seconds. [`RpcSpanLayer`] wraps each of
+//! them in a `rpc::server` span carrying the method and its (truncated) parameters, so an exported
+//! trace shows which request a slow database or EVM span belongs to, and registers them with the
+//! node's [`InflightCalls`] so they can be listed and aborted. Other calls pass through untouched.
+
+use crate::rpc::inflight::InflightCalls;
+use futures::future::Abortable;
+use jsonrpsee_core::{
+    middleware::{Batch, Notification, RpcServiceT},
+    server::MethodResponse,
+};
+use jsonrpsee_types::{ErrorObject, Request, error::INTERNAL_ERROR_CODE};
+use std::{future::Future, sync::Arc};
+use tracing::{Instrument, Span, debug_span};
+
+/// Longest prefix of the parameters recorded on a span, in bytes.
+const MAX_RECORDED_PARAMS: usize = 256;
+
+/// Returns whether calls of `method` are expensive enough to be traced.
+fn is_expensive(method: &str) -> bool {
+    method == "eth_getLogs" ||
+        method == "debug_profileExecution" ||
+        method.starts_with("trace_") ||
+        method.starts_with("debug_trace")
+}
+
+/// Returns the longest prefix of `params` not exceeding [`MAX_RECORDED_PARAMS`] bytes.
+fn truncate(params: &str) -> &str {
+    let mut end = params.len().min(MAX_RECORDED_PARAMS);
+    while !params.is_char_boundary(end) {
+        end -= 1;
+    }
+    &params[..end]
+}
+
+/// RPC middleware wrapping expensive calls in a span.
+#[derive(Debug, Clone, Default)]
+pub struct RpcSpanLayer {
+    calls: Arc<InflightCalls>,
+}
+
+impl RpcSpanLayer {
+    /// Creates the middleware registering expensive calls with `calls`.
+    pub const fn new(calls: Arc<InflightCalls>) -> Self {
+        Self { calls }
+    }
+}
+
+impl<S> tower::Layer<S> for RpcSpanLayer {
+    type Service = RpcSpanService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RpcSpanService { inner, calls: self.calls.clone() }
+    }
+}
+
+/// Service created by [`RpcSpanLayer`].
+#[derive(Debug, Clone)]
+pub struct RpcSpanService<S> {
+    inner: S,
+    calls: Arc<InflightCalls>,
+}
+
+impl<S> RpcServiceT for RpcSpanService<S>
+where
+    S: RpcServiceT<MethodResponse = MethodResponse> + Send + Sync,
+{
+    type MethodResponse = MethodResponse;
+    type NotificationResponse = S::NotificationResponse;
+    type BatchResponse = S::BatchResponse;
+
+    fn call<'a>(&self, request: Request<'a>) -> impl Future<Output = MethodResponse> + Send + 'a {
+        let expensive = is_expensive(request.method_name());
+        let params = request.params();
+        let params = truncate(params.as_str().unwrap_or_default());
+        let span = expensive.then(|| {
+            debug_span!(target: "rpc::server", "rpc_call", method = request.method_name(), params)
+        });
+        let registered = expensive.then(|| {
+            let (guard, registration) = self.calls.register(request.method_name(), params);
+            (guard, registration, request.id().into_owned())
+        });
+        let call = self.inner.call(request);
+
+        let call = async move {
+            let Some((_guard, registration, id)) = registered else { return call.await };
+            Abortable::new(call, registration).await.unwrap_or_else(|_| {
+                let error = ErrorObject::owned(
+                    INTERNAL_ERROR_CODE,
+                    "call aborted by an operator",
+                    None::<()>,
+                );
+                MethodResponse::error(id, error)
+            })
+        };
+        call.instrument(span.unwrap_or_else(Span::none))
+    }
+
+    fn batch<'a>(&self, requests: Batch<'a>) -> impl Future<Output = S::BatchResponse> + Send + 'a {
+        self.inner.batch(requests)
+    }
+
+    fn notification<'a>(
+        &self,
+        notification: Notification<'a>,
+    ) -> impl Future<Output = S::NotificationResponse> + Send + 'a {
+        self.inner.notification(notification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expensive_methods() {
+        assert!(is_expensive("eth_getLogs"));
+        assert!(is_expensive("trace_block"));
+        assert!(is_expensive("debug_traceTransaction"));
+        assert!(is_expensive("debug_profileExecution"));
+        assert!(!is_expensive("eth_blockNumber"));
+        assert!(!is_expensive("debug_getRawBlock"));
+    }
+
+    #[test]
+    fn test_truncate_at_char_boundary() {
+        assert_eq!(truncate("[\"0x1\"]"), "[\"0x1\"]");
+        let params = "é".repeat(MAX_RECORDED_PARAMS);
+        assert_eq!(truncate(&params).len(), MAX_RECORDED_PARAMS);
+        let params = format!("x{}", "é".repeat(MAX_RECORDED_PARAMS));
+        assert_eq!(truncate(&params).len(), MAX_RECORDED_PARAMS - 1);
+    }
+}
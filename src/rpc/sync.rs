@@ -0,0 +1,28 @@
+//! Structured sync progress, served next to the `eth` namespace.
+
+use crate::node::sync_progress::{SyncProgress, SyncStatus};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use std::sync::Arc;
+
+/// Sync progress of the pipeline stages.
+#[rpc(server, namespace = "bera")]
+pub trait SyncStatusApi {
+    /// Returns the progress and estimated time to sync of the headers, bodies, execution and
+    /// merkle stages, or `null` until the first sample was taken.
+    #[method(name = "syncStatus")]
+    fn sync_status(&self) -> RpcResult<Option<SyncStatus>>;
+}
+
+/// Implementation of [`SyncStatusApiServer`] backed by the node's [`SyncProgress`].
+#[derive(Debug, Default, Clone)]
+pub struct SyncStatusRpc {
+    /// Sync progress sampled by the node.
+    pub progress: Arc<SyncProgress>,
+}
+
+impl SyncStatusApiServer for SyncStatusRpc {
+    fn sync_status(&self) -> RpcResult<Option<SyncStatus>> {
+        Ok(self.progress.status())
+    }
+}
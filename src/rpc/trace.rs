@@ -0,0 +1,75 @@
+//! PoL transactions in the `debug` and `trace` namespaces.
+//!
+//! The block executor runs the PoL transaction of a block from `SYSTEM_ADDRESS` with
+//! [`transact_pol`]: it pays no fees, does not bump a nonce and its gas is not counted. reth's
+//! tracers replay every transaction of a block with a regular `transact`, which rejects the PoL
+//! transaction for its nonce and its missing balance. [`transact`] replays it with
+//! [`transact_pol`] instead, so `debug_traceTransaction`, `debug_traceBlock*` and `trace_block`
+//! see the execution the block actually had.
+
+use crate::{node::evm::executor::transact_pol, transaction::POL_TX_TYPE};
+use alloy_primitives::TxKind;
+use reth::revm::{
+    Database,
+    context::{TxEnv, result::ResultAndState},
+};
+use reth_evm::{Evm, eth::EthEvmContext};
+use std::ops::DerefMut;
+
+/// Executes `tx_env` on `evm` without committing, as the block executor does if it is a PoL
+/// transaction.
+pub fn transact<DB, E>(
+    evm: &mut E,
+    tx_env: TxEnv,
+) -> Result<ResultAndState<E::HaltReason>, E::Error>
+where
+    DB: Database,
+    E: Evm<DB = DB, Tx = TxEnv> + DerefMut<Target = EthEvmContext<DB>>,
+{
+    match tx_env.kind {
+        TxKind::Call(distributor) if tx_env.tx_type == POL_TX_TYPE => {
+            transact_pol(evm, distributor, tx_env.data, tx_env.gas_limit)
+        }
+        _ => evm.transact(tx_env),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::BERACHAIN_DEV,
+        primitives::header::BlsPublicKey,
+        transaction::{BerachainTxEnvelope, pol::create_pol_transaction},
+    };
+    use alloy_primitives::{Address, U256};
+    use reth::revm::{
+        db::{CacheDB, EmptyDB},
+        handler::SYSTEM_ADDRESS,
+    };
+    use reth_evm::{EthEvmFactory, EvmEnv, EvmFactory, FromRecoveredTx};
+
+    #[test]
+    fn test_pol_transaction_is_replayed_without_fees_or_nonce() {
+        let envelope = create_pol_transaction(
+            BERACHAIN_DEV.clone(),
+            BlsPublicKey::from([7u8; 48]),
+            U256::from(3),
+            0,
+            7,
+        )
+        .unwrap();
+        let BerachainTxEnvelope::Berachain(pol_tx) = envelope else { panic!("not a PoL tx") };
+        let tx_env = TxEnv::from_recovered_tx(pol_tx.inner(), Address::repeat_byte(1));
+        assert_eq!(tx_env.caller, SYSTEM_ADDRESS);
+
+        let mut evm =
+            EthEvmFactory::default().create_evm(CacheDB::<EmptyDB>::default(), EvmEnv::default());
+        // A regular transaction is rejected for its nonce and the fees it cannot pay
+        assert!(evm.transact(tx_env.clone()).is_err());
+
+        let ResultAndState { result, state } = transact(&mut evm, tx_env).unwrap();
+        assert!(result.is_success());
+        assert!(!state.contains_key(&SYSTEM_ADDRESS));
+    }
+}
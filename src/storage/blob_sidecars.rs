@@ -0,0 +1,356 @@
+//! Blob sidecars of locally built blocks.
+//!
+//! The pool drops the sidecar of a blob transaction once the block including it is finalized,
+//! after which the node could no longer serve it to peers. When bera-reth builds a block itself,
+//! the payload builder keeps the sidecars of the included transactions in memory, and
+//! [`install_sidecar_retention`] moves them to the [`BlobSidecars`] table in its own transaction
+//! once the block becomes canonical. The versioned hashes of their blobs are indexed in
+//! [`BlobTransactions`], so `engine_getBlobs*` can serve them through the
+//! [pool's blob store](crate::pool::blobstore) too. [`BlockRetainedBlobs`] records which
+//! transactions of a block have retained sidecars, so reverting or unwinding the block removes
+//! them again, and sidecars are pruned once their block is [`SIDECAR_RETENTION_BLOCKS`] below the
+//! tip.
+
+use crate::{
+    primitives::{BerachainBlockBody, BerachainPrimitives},
+    storage::{
+        compression::TableCompression,
+        tables::{
+            BlobSidecars, BlobTransactions, BlockRetainedBlobs, RetainedBlobs, StoredBlobSidecar,
+        },
+    },
+};
+use alloy_consensus::{BlockHeader, Transaction, Typed2718};
+use alloy_eips::eip7594::BlobTransactionSidecarVariant;
+use alloy_primitives::{B256, BlockNumber, Bytes, TxHash};
+use futures::StreamExt;
+use reth::{
+    providers::{
+        CanonStateNotification, CanonStateSubscriptions, DBProvider, DatabaseProviderFactory,
+        ProviderResult,
+    },
+    tasks::TaskExecutor,
+};
+use reth_db_api::{
+    DatabaseError,
+    cursor::DbCursorRO,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_metrics::{Metrics, metrics::Counter};
+use schnellru::{ByLength, LruMap};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// Number of sidecars of built blocks kept in memory until their block is canonical.
+pub const DEFAULT_LOCAL_SIDECARS: u32 = 256;
+
+/// Number of blocks below the tip whose retained sidecars are kept, about 18 days of 2 second
+/// slots like the blob retention window of the consensus layer.
+pub const SIDECAR_RETENTION_BLOCKS: u64 = 786_432;
+
+/// Blob sidecar retention metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.blob_sidecars")]
+struct RetentionMetrics {
+    /// Sidecars of built blocks stored when the block became canonical.
+    retained: Counter,
+    /// Retained sidecars removed because their block left the retention window.
+    pruned: Counter,
+}
+
+/// Sidecars of transactions included in locally built payloads, by transaction hash.
+pub struct LocalSidecars {
+    sidecars: Mutex<LruMap<TxHash, Bytes, ByLength>>,
+    /// Codec the sidecars are stored with.
+    codec: TableCompression,
+    metrics: RetentionMetrics,
+}
+
+impl std::fmt::Debug for LocalSidecars {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalSidecars").finish_non_exhaustive()
+    }
+}
+
+impl LocalSidecars {
+    /// Creates a map holding up to `capacity` sidecars, which are stored with `codec`.
+    pub fn new(capacity: u32, codec: TableCompression) -> Self {
+        Self {
+            sidecars: Mutex::new(LruMap::new(ByLength::new(capacity))),
+            codec,
+            metrics: RetentionMetrics::default(),
+        }
+    }
+
+    /// Keeps the sidecar of `tx_hash`, which was included in a built payload.
+    pub fn insert(&self, tx_hash: TxHash, sidecar: &BlobTransactionSidecarVariant) {
+        // Payloads are rebuilt several times per slot with mostly the same transactions
+        if self.sidecars.lock().expect("not poisoned").peek(&tx_hash).is_some() {
+            return
+        }
+        let encoded = alloy_rlp::encode(sidecar).into();
+        self.sidecars.lock().expect("not poisoned").insert(tx_hash, encoded);
+    }
+
+    /// Removes and returns the sidecar of `tx_hash`.
+    pub fn take(&self, tx_hash: &TxHash) -> Option<Bytes> {
+        self.sidecars.lock().expect("not poisoned").remove(tx_hash)
+    }
+
+    /// Returns whether the sidecar of `tx_hash` is held.
+    pub fn contains(&self, tx_hash: &TxHash) -> bool {
+        self.sidecars.lock().expect("not poisoned").peek(tx_hash).is_some()
+    }
+
+    /// Stores the sidecars of the blob transactions in the numbered `bodies` that were built
+    /// locally.
+    pub fn retain<'a, TX: DbTxMut>(
+        &self,
+        tx: &TX,
+        bodies: impl IntoIterator<Item = (BlockNumber, &'a BerachainBlockBody)>,
+    ) -> Result<(), DatabaseError> {
+        for (number, body) in bodies {
+            let mut retained = RetainedBlobs::default();
+            for transaction in body.transactions.iter().filter(|tx| tx.is_eip4844()) {
+                let tx_hash = *transaction.hash();
+                let Some(encoded) = self.take(&tx_hash) else { continue };
+                tx.put::<BlobSidecars>(tx_hash, StoredBlobSidecar::new(&encoded, self.codec))?;
+                for versioned_hash in transaction.blob_versioned_hashes().unwrap_or_default() {
+                    tx.put::<BlobTransactions>(*versioned_hash, tx_hash)?;
+                    retained.versioned_hashes.push(*versioned_hash);
+                }
+                retained.tx_hashes.push(tx_hash);
+                self.metrics.retained.increment(1);
+            }
+            if !retained.tx_hashes.is_empty() {
+                tx.put::<BlockRetainedBlobs>(number, retained)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stores the sidecars of the built blocks committed by `notification` and removes those of
+    /// the reverted blocks and of the blocks that left the retention window.
+    fn on_canonical<P>(
+        &self,
+        provider: &P,
+        notification: &CanonStateNotification<BerachainPrimitives>,
+    ) -> ProviderResult<()>
+    where
+        P: DatabaseProviderFactory<ProviderRW: DBProvider<Tx: DbTxMut>>,
+    {
+        let committed = notification.committed();
+        let built = committed
+            .blocks_iter()
+            .flat_map(|block| &block.body().transactions)
+            .any(|transaction| self.contains(transaction.hash()));
+        let reverted = notification.reverted();
+        if !built && reverted.is_none() {
+            return Ok(())
+        }
+
+        let provider_rw = provider.database_provider_rw()?;
+        let tx = provider_rw.tx_ref();
+        if let Some(reverted) = reverted {
+            // A later built block may include the reverted transactions again
+            for transaction in reverted.blocks_iter().flat_map(|block| &block.body().transactions) {
+                if let Some(encoded) = read_sidecar(tx, *transaction.hash())? {
+                    self.sidecars
+                        .lock()
+                        .expect("not poisoned")
+                        .insert(*transaction.hash(), encoded);
+                }
+            }
+            remove_sidecars_above(tx, reverted.fork_block().number)?;
+        }
+        self.retain(tx, committed.blocks_iter().map(|block| (block.number(), block.body())))?;
+        let pruned = remove_sidecars_below(
+            tx,
+            committed.tip().number().saturating_sub(SIDECAR_RETENTION_BLOCKS),
+        )?;
+        self.metrics.pruned.increment(pruned as u64);
+        provider_rw.commit()?;
+        Ok(())
+    }
+}
+
+/// Moves the sidecars of built blocks held in `sidecars` to the database as the blocks become
+/// canonical.
+///
+/// Runs outside of reth's persistence, in a write transaction of its own for the notifications
+/// that commit a built block or revert blocks.
+pub fn install_sidecar_retention<P>(
+    sidecars: Arc<LocalSidecars>,
+    provider: P,
+    executor: &TaskExecutor,
+) where
+    P: CanonStateSubscriptions<Primitives = BerachainPrimitives>
+        + DatabaseProviderFactory<ProviderRW: DBProvider<Tx: DbTxMut>>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+{
+    let mut notifications = provider.canonical_state_stream();
+    executor.spawn(async move {
+        while let Some(notification) = notifications.next().await {
+            let (sidecars, provider) = (sidecars.clone(), provider.clone());
+            let task = move || sidecars.on_canonical(&provider, &notification);
+            let retained = tokio::task::spawn_blocking(task).await.map_err(eyre::Report::from);
+            if let Err(err) = retained.and_then(|retained| retained.map_err(Into::into)) {
+                warn!(target: "reth::cli", %err, "Failed to retain blob sidecars");
+            }
+        }
+    });
+}
+
+/// Removes the sidecars retained for the blocks above `block`.
+pub fn remove_sidecars_above<TX: DbTxMut + DbTx>(
+    tx: &TX,
+    block: BlockNumber,
+) -> Result<(), DatabaseError> {
+    let removed = tx
+        .cursor_read::<BlockRetainedBlobs>()?
+        .walk(Some(block + 1))?
+        .collect::<Result<Vec<_>, _>>()?;
+    remove_retained(tx, removed)?;
+    Ok(())
+}
+
+/// Removes the sidecars retained for the blocks below `block`, returning how many were removed.
+pub fn remove_sidecars_below<TX: DbTxMut + DbTx>(
+    tx: &TX,
+    block: BlockNumber,
+) -> Result<usize, DatabaseError> {
+    let removed = tx
+        .cursor_read::<BlockRetainedBlobs>()?
+        .walk_range(..block)?
+        .collect::<Result<Vec<_>, _>>()?;
+    remove_retained(tx, removed)
+}
+
+/// Removes the `retained` sidecars and their index entries, returning how many were removed.
+fn remove_retained<TX: DbTxMut>(
+    tx: &TX,
+    retained: Vec<(BlockNumber, RetainedBlobs)>,
+) -> Result<usize, DatabaseError> {
+    let mut removed = 0;
+    for (number, retained) in retained {
+        removed += retained.tx_hashes.len();
+        for tx_hash in retained.tx_hashes {
+            tx.delete::<BlobSidecars>(tx_hash, None)?;
+        }
+        for versioned_hash in retained.versioned_hashes {
+            tx.delete::<BlobTransactions>(versioned_hash, None)?;
+        }
+        tx.delete::<BlockRetainedBlobs>(number, None)?;
+    }
+    Ok(removed)
+}
+
+/// Reads the stored sidecar of `tx_hash`, in its network encoding.
+pub fn read_sidecar<TX: DbTx>(tx: &TX, tx_hash: TxHash) -> Result<Option<Bytes>, DatabaseError> {
+    tx.get::<BlobSidecars>(tx_hash)?.map(|stored| stored.decode()).transpose()
+}
+
+/// Returns the transaction whose stored sidecar holds the blob of `versioned_hash`.
+pub fn read_blob_transaction<TX: DbTx>(
+    tx: &TX,
+    versioned_hash: B256,
+) -> Result<Option<TxHash>, DatabaseError> {
+    tx.get::<BlobTransactions>(versioned_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::create_tables, transaction::BerachainTxEnvelope};
+    use alloy_consensus::{Signed, TxEip4844, TxEip4844Variant, TxEnvelope};
+    use alloy_eips::eip4844::BlobTransactionSidecar;
+    use alloy_primitives::Signature;
+    use alloy_rlp::Decodable;
+    use reth_db::test_utils::create_test_rw_db;
+    use reth_db_api::database::Database;
+
+    fn blob_body(byte: u8) -> BerachainBlockBody {
+        let tx = TxEip4844 {
+            nonce: byte as u64,
+            blob_versioned_hashes: vec![B256::repeat_byte(byte)],
+            ..Default::default()
+        };
+        let signed = Signed::new_unhashed(tx, Signature::test_signature());
+        let envelope = TxEnvelope::Eip4844(signed.map(TxEip4844Variant::TxEip4844));
+        BerachainBlockBody {
+            transactions: vec![BerachainTxEnvelope::Ethereum(envelope)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sidecars_are_taken_once() {
+        let sidecars = LocalSidecars::new(2, TableCompression::default());
+        let sidecar = BlobTransactionSidecarVariant::Eip4844(BlobTransactionSidecar::default());
+        sidecars.insert(TxHash::repeat_byte(1), &sidecar);
+        sidecars.insert(TxHash::repeat_byte(1), &sidecar);
+
+        let encoded = sidecars.take(&TxHash::repeat_byte(1)).unwrap();
+        assert_eq!(BlobTransactionSidecarVariant::decode(&mut encoded.as_ref()).unwrap(), sidecar);
+        assert!(sidecars.take(&TxHash::repeat_byte(1)).is_none());
+    }
+
+    #[test]
+    fn test_oldest_sidecars_are_evicted() {
+        let sidecars = LocalSidecars::new(2, TableCompression::default());
+        let sidecar = BlobTransactionSidecarVariant::Eip4844(BlobTransactionSidecar::default());
+        for byte in 1..=3 {
+            sidecars.insert(TxHash::repeat_byte(byte), &sidecar);
+        }
+        assert!(sidecars.take(&TxHash::repeat_byte(1)).is_none());
+        assert!(sidecars.take(&TxHash::repeat_byte(3)).is_some());
+    }
+
+    #[test]
+    fn test_unwind_removes_retained_sidecars() {
+        let db = create_test_rw_db();
+        create_tables(db.db()).unwrap();
+        let sidecars = LocalSidecars::new(4, TableCompression::default());
+        let sidecar = BlobTransactionSidecarVariant::Eip4844(BlobTransactionSidecar::default());
+        let bodies = [blob_body(1), blob_body(2)];
+        let hashes = bodies.each_ref().map(|body| *body.transactions[0].hash());
+        for hash in hashes {
+            sidecars.insert(hash, &sidecar);
+        }
+
+        let tx = db.tx_mut().unwrap();
+        sidecars.retain(&tx, [(1, &bodies[0]), (2, &bodies[1])]).unwrap();
+        remove_sidecars_above(&tx, 1).unwrap();
+
+        assert!(read_sidecar(&tx, hashes[0]).unwrap().is_some());
+        assert_eq!(read_blob_transaction(&tx, B256::repeat_byte(1)).unwrap(), Some(hashes[0]));
+        assert!(read_sidecar(&tx, hashes[1]).unwrap().is_none());
+        assert!(read_blob_transaction(&tx, B256::repeat_byte(2)).unwrap().is_none());
+        assert!(tx.get::<BlockRetainedBlobs>(2).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_expired_sidecars_are_pruned() {
+        let db = create_test_rw_db();
+        create_tables(db.db()).unwrap();
+        let sidecars = LocalSidecars::new(4, TableCompression::default());
+        let sidecar = BlobTransactionSidecarVariant::Eip4844(BlobTransactionSidecar::default());
+        let bodies = [blob_body(1), blob_body(2)];
+        let hashes = bodies.each_ref().map(|body| *body.transactions[0].hash());
+        for hash in hashes {
+            sidecars.insert(hash, &sidecar);
+        }
+
+        let tx = db.tx_mut().unwrap();
+        sidecars.retain(&tx, [(1, &bodies[0]), (2, &bodies[1])]).unwrap();
+        assert_eq!(remove_sidecars_below(&tx, 2).unwrap(), 1);
+
+        assert!(read_sidecar(&tx, hashes[0]).unwrap().is_none());
+        assert!(read_blob_transaction(&tx, B256::repeat_byte(1)).unwrap().is_none());
+        assert!(read_sidecar(&tx, hashes[1]).unwrap().is_some());
+        assert_eq!(remove_sidecars_below(&tx, 2).unwrap(), 0);
+    }
+}
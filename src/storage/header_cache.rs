@@ -0,0 +1,179 @@
+//! Node-wide cache of sealed Berachain headers.
+//!
+//! Hashing a [`BerachainHeader`] RLP-encodes and keccaks it on every call, and consensus clients
+//! re-send the same payload to `engine_newPayload` (e.g. after a restart or a forkchoice retry).
+//! Every [`BerachainNode`](crate::node::BerachainNode) keeps one cache shared by the engine
+//! validator, which seals payload headers through it, and the RPC handlers, which read headers
+//! through it instead of going to the database. Sealing a header that is already cached costs an
+//! equality check instead of a hash. Reads by number resolve the canonical hash first, so a reorg
+//! never serves a stale header.
+
+use crate::primitives::BerachainHeader;
+use alloy_primitives::{B256, BlockNumber};
+use reth::providers::{BlockHashReader, HeaderProvider, ProviderResult};
+use reth_metrics::{Metrics, metrics::Counter};
+use reth_primitives_traits::SealedHeader;
+use schnellru::{ByLength, LruMap};
+use std::sync::Mutex;
+
+/// Number of headers kept unless configured otherwise.
+pub const DEFAULT_HEADER_CACHE_SIZE: u32 = 1_024;
+
+/// Header cache metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.header_cache")]
+struct HeaderCacheMetrics {
+    /// Lookups and seals served from the cache.
+    hits: Counter,
+    /// Lookups and seals that missed the cache.
+    misses: Counter,
+}
+
+#[derive(Debug)]
+struct Inner {
+    by_hash: LruMap<B256, SealedHeader<BerachainHeader>, ByLength>,
+    by_number: LruMap<BlockNumber, B256, ByLength>,
+}
+
+/// LRU of sealed headers keyed by hash, with a number index to avoid re-hashing.
+pub struct SealedHeaderCache {
+    inner: Mutex<Inner>,
+    metrics: HeaderCacheMetrics,
+}
+
+impl std::fmt::Debug for SealedHeaderCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SealedHeaderCache").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl Default for SealedHeaderCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_HEADER_CACHE_SIZE)
+    }
+}
+
+impl SealedHeaderCache {
+    /// Creates a cache holding up to `capacity` headers.
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                by_hash: LruMap::new(ByLength::new(capacity)),
+                by_number: LruMap::new(ByLength::new(capacity)),
+            }),
+            metrics: HeaderCacheMetrics::default(),
+        }
+    }
+
+    /// Seals `header`, reusing the cached hash if the same header was sealed before.
+    pub fn seal(&self, header: BerachainHeader) -> SealedHeader<BerachainHeader> {
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+
+        let cached_hash = inner.by_number.get(&header.number).copied();
+        if let Some(cached) = cached_hash
+            .and_then(|hash| inner.by_hash.get(&hash))
+            .filter(|cached| cached.header() == &header)
+        {
+            self.metrics.hits.increment(1);
+            return cached.clone();
+        }
+
+        self.metrics.misses.increment(1);
+        let sealed = SealedHeader::seal_slow(header);
+        inner.insert(sealed.clone());
+        sealed
+    }
+
+    /// Returns the cached header with the given hash.
+    pub fn get(&self, hash: &B256) -> Option<SealedHeader<BerachainHeader>> {
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        let cached = inner.by_hash.get(hash).cloned();
+        if cached.is_some() {
+            self.metrics.hits.increment(1);
+        } else {
+            self.metrics.misses.increment(1);
+        }
+        cached
+    }
+
+    /// Returns the header with the given hash, reading it from `provider` on a miss.
+    pub fn sealed_header_by_hash<P>(
+        &self,
+        provider: &P,
+        hash: B256,
+    ) -> ProviderResult<Option<SealedHeader<BerachainHeader>>>
+    where
+        P: HeaderProvider<Header = BerachainHeader>,
+    {
+        if let Some(cached) = self.get(&hash) {
+            return Ok(Some(cached))
+        }
+        let Some(header) = provider.sealed_header_by_hash(hash)? else { return Ok(None) };
+        self.inner.lock().unwrap_or_else(|err| err.into_inner()).insert(header.clone());
+        Ok(Some(header))
+    }
+
+    /// Returns the canonical header at `number`, reading it from `provider` on a miss.
+    pub fn sealed_header<P>(
+        &self,
+        provider: &P,
+        number: BlockNumber,
+    ) -> ProviderResult<Option<SealedHeader<BerachainHeader>>>
+    where
+        P: HeaderProvider<Header = BerachainHeader> + BlockHashReader,
+    {
+        let Some(hash) = provider.block_hash(number)? else { return Ok(None) };
+        self.sealed_header_by_hash(provider, hash)
+    }
+}
+
+impl Inner {
+    fn insert(&mut self, header: SealedHeader<BerachainHeader>) {
+        self.by_number.insert(header.number, header.hash());
+        self.by_hash.insert(header.hash(), header);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::Header;
+
+    fn header(number: BlockNumber) -> BerachainHeader {
+        BerachainHeader::from(Header { number, ..Default::default() })
+    }
+
+    #[test]
+    fn test_seal_reuses_cached_hash() {
+        let cache = SealedHeaderCache::new(8);
+        let first = cache.seal(header(1));
+        assert_eq!(first.hash(), header(1).hash_slow());
+
+        let again = cache.seal(header(1));
+        assert_eq!(again, first);
+        assert_eq!(cache.get(&first.hash()), Some(first));
+    }
+
+    #[test]
+    fn test_seal_detects_different_header_at_same_number() {
+        let cache = SealedHeaderCache::new(8);
+        let original = cache.seal(header(1));
+
+        let mut modified = header(1);
+        modified.gas_limit = 1;
+        let sealed = cache.seal(modified.clone());
+
+        assert_ne!(sealed.hash(), original.hash());
+        assert_eq!(sealed.hash(), modified.hash_slow());
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = SealedHeaderCache::new(2);
+        let evicted = cache.seal(header(1)).hash();
+        cache.seal(header(2));
+        cache.seal(header(3));
+
+        assert_eq!(cache.get(&evicted), None);
+    }
+}
@@ -0,0 +1,221 @@
+//! Berachain chain storage: reth's Ethereum storage plus Berachain-specific tables.
+//!
+//! [`BerachainStorage`] delegates body reads and writes to [`EthStorage`] and maintains the
+//! auxiliary [`tables`] in the same database transaction, so they are committed and unwound
+//! together with the canonical chain. Sidecars of locally built blocks are stored by a separate
+//! consumer of canonical state notifications and only removed here on unwind, see
+//! [`blob_sidecars`].
+//!
+//! The tables are created when the node launches and by every bera-reth command opening the
+//! database for writing. Stock reth commands (`import`, `stage run`, ...) only create reth's own
+//! tables, so the body writer also creates missing tables in its transaction before using them.
+
+pub mod blob_sidecars;
+pub mod compression;
+pub mod header_cache;
+pub mod readers;
+pub mod tables;
+pub mod version;
+
+use crate::{
+    primitives::{BerachainBlock, BerachainBlockBody, BerachainHeader, BerachainPrimitives},
+    storage::{
+        blob_sidecars::remove_sidecars_above,
+        tables::{BerachainTables, PolDistribution, PolDistributions, ProposerStatistics},
+    },
+    transaction::{BerachainTxEnvelope, pol::decode_pol_pubkey},
+};
+use alloy_primitives::{BlockNumber, keccak256};
+use reth::providers::{
+    BlockBodyReader, BlockBodyWriter, ChainStorage, ChainStorageReader, ChainStorageWriter,
+    DBProvider, DatabaseProvider, EthStorage, NodeTypesForProvider, ProviderResult, ReadBodyInput,
+    StorageLocation,
+};
+use reth_db::{
+    DatabaseEnv,
+    mdbx::{DatabaseFlags, RW, tx::Tx},
+};
+use reth_db_api::{
+    DatabaseError,
+    cursor::DbCursorRO,
+    table::TableInfo,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_node_api::NodeTypes;
+use std::any::Any;
+
+/// Storage strategy for [`BerachainNode`](crate::node::BerachainNode).
+#[derive(Debug, Default, Clone)]
+pub struct BerachainStorage(EthStorage<BerachainTxEnvelope, BerachainHeader>);
+
+/// Creates the [`BerachainTables`] in `db` if they do not exist yet.
+///
+/// Runs wherever bera-reth opens the database for writing: at launch, in [`TestNode`] and in the
+/// Berachain commands.
+///
+/// [`TestNode`]: crate::test_utils::node::TestNode
+pub fn create_tables(db: &DatabaseEnv) -> Result<(), DatabaseError> {
+    db.create_tables_for::<BerachainTables>()
+}
+
+/// Creates the [`BerachainTables`] missing in `tx`, if it is a read-write MDBX transaction.
+///
+/// Covers databases opened by stock reth commands, which do not know about these tables.
+fn create_tables_in<TX: 'static>(tx: &TX) -> Result<(), DatabaseError> {
+    let Some(tx) = (tx as &dyn Any).downcast_ref::<Tx<RW>>() else { return Ok(()) };
+    for table in BerachainTables::ALL {
+        // Opening an existing table with the create flag is a lookup
+        tx.inner
+            .create_db(Some(table.name()), DatabaseFlags::default())
+            .map_err(|err| DatabaseError::CreateTable(err.into()))?;
+    }
+    Ok(())
+}
+
+/// Returns the PoL distribution carried by `body`, if it starts with a PoL transaction.
+pub fn pol_distribution(body: &BerachainBlockBody) -> Option<PolDistribution> {
+    let BerachainTxEnvelope::Berachain(pol_tx) = body.transactions.first()? else { return None };
+    let pubkey = decode_pol_pubkey(&pol_tx.input)?;
+    Some(PolDistribution { tx_hash: pol_tx.hash(), pubkey })
+}
+
+impl<Provider> BlockBodyWriter<Provider, BerachainBlockBody> for BerachainStorage
+where
+    Provider: DBProvider<Tx: DbTxMut + 'static>,
+    EthStorage<BerachainTxEnvelope, BerachainHeader>: BlockBodyWriter<Provider, BerachainBlockBody>,
+{
+    fn write_block_bodies(
+        &self,
+        provider: &Provider,
+        bodies: Vec<(BlockNumber, Option<BerachainBlockBody>)>,
+        write_to: StorageLocation,
+    ) -> ProviderResult<()> {
+        let tx = provider.tx_ref();
+        create_tables_in(tx)?;
+        for (number, body) in &bodies {
+            let Some(distribution) = body.as_ref().and_then(pol_distribution) else { continue };
+
+            // Bodies may be rewritten (e.g. after a crash mid-sync); only count new entries.
+            if tx.get::<PolDistributions>(*number)?.is_none() {
+                let key = keccak256(distribution.pubkey);
+                let mut stats = tx.get::<ProposerStatistics>(key)?.unwrap_or_default();
+                stats.distributions += 1;
+                tx.put::<ProposerStatistics>(key, stats)?;
+            }
+            tx.put::<PolDistributions>(*number, distribution)?;
+        }
+        self.0.write_block_bodies(provider, bodies, write_to)
+    }
+
+    fn remove_block_bodies_above(
+        &self,
+        provider: &Provider,
+        block: BlockNumber,
+        remove_from: StorageLocation,
+    ) -> ProviderResult<()> {
+        let tx = provider.tx_ref();
+        create_tables_in(tx)?;
+        let removed = tx
+            .cursor_read::<PolDistributions>()?
+            .walk(Some(block + 1))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (number, distribution) in removed {
+            let key = keccak256(distribution.pubkey);
+            if let Some(mut stats) = tx.get::<ProposerStatistics>(key)? {
+                stats.distributions = stats.distributions.saturating_sub(1);
+                if stats.distributions == 0 {
+                    tx.delete::<ProposerStatistics>(key, None)?;
+                } else {
+                    tx.put::<ProposerStatistics>(key, stats)?;
+                }
+            }
+            tx.delete::<PolDistributions>(number, None)?;
+        }
+        remove_sidecars_above(tx, block)?;
+
+        self.0.remove_block_bodies_above(provider, block, remove_from)
+    }
+}
+
+impl<Provider> BlockBodyReader<Provider> for BerachainStorage
+where
+    EthStorage<BerachainTxEnvelope, BerachainHeader>:
+        BlockBodyReader<Provider, Block = BerachainBlock>,
+{
+    type Block = BerachainBlock;
+
+    fn read_block_bodies(
+        &self,
+        provider: &Provider,
+        inputs: Vec<ReadBodyInput<'_, Self::Block>>,
+    ) -> ProviderResult<Vec<BerachainBlockBody>> {
+        self.0.read_block_bodies(provider, inputs)
+    }
+}
+
+impl ChainStorage<BerachainPrimitives> for BerachainStorage {
+    fn reader<TX, Types>(
+        &self,
+    ) -> impl ChainStorageReader<DatabaseProvider<TX, Types>, BerachainPrimitives>
+    where
+        TX: DbTx + 'static,
+        Types: NodeTypesForProvider<Primitives = BerachainPrimitives>,
+    {
+        self
+    }
+
+    fn writer<TX, Types>(
+        &self,
+    ) -> impl ChainStorageWriter<DatabaseProvider<TX, Types>, BerachainPrimitives>
+    where
+        TX: DbTxMut + DbTx + 'static,
+        Types: NodeTypes<Primitives = BerachainPrimitives>,
+    {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::compact_corpus,
+        transaction::{PoLTx, pol::PoLDistributor},
+    };
+    use alloy_primitives::{Bytes, Sealed};
+    use alloy_sol_types::SolCall;
+
+    fn pol_body(input: Bytes) -> BerachainBlockBody {
+        let pol_tx = PoLTx { input, ..Default::default() };
+        BerachainBlockBody {
+            transactions: vec![BerachainTxEnvelope::Berachain(Sealed::new(pol_tx))],
+            ommers: Vec::new(),
+            withdrawals: None,
+        }
+    }
+
+    #[test]
+    fn test_pol_distribution_from_body() {
+        let pubkey = crate::primitives::header::BlsPublicKey::repeat_byte(7);
+        let input = PoLDistributor::distributeForCall { pubkey: pubkey.into() }.abi_encode();
+        let body = pol_body(input.into());
+
+        let distribution = pol_distribution(&body).unwrap();
+        assert_eq!(distribution.pubkey, pubkey);
+        assert_eq!(distribution.tx_hash, *body.transactions[0].hash());
+    }
+
+    #[test]
+    fn test_pol_distribution_ignores_non_pol_bodies() {
+        assert_eq!(pol_distribution(&pol_body(Bytes::new())), None);
+
+        let transactions = compact_corpus::tx_envelopes()
+            .into_iter()
+            .map(|entry| entry.value)
+            .filter(|tx| !matches!(tx, BerachainTxEnvelope::Berachain(_)))
+            .collect();
+        let body = BerachainBlockBody { transactions, ommers: Vec::new(), withdrawals: None };
+        assert_eq!(pol_distribution(&body), None);
+    }
+}
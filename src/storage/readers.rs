@@ -0,0 +1,83 @@
+//! Monitoring of MDBX readers.
+//!
+//! Long-lived read transactions, typically held by stuck RPC trace calls, keep MDBX from reusing
+//! freed pages and bloat the database file. Every [`SAMPLE_INTERVAL`] the node exports the
+//! number of readers and free pages as `berachain_db_*` metrics, together with the expensive RPC
+//! calls running longer than [`LONG_CALL_THRESHOLD`], and warns once about each such call.
+
+use crate::rpc::inflight::{InflightCalls, LONG_CALL_THRESHOLD};
+use reth::tasks::TaskExecutor;
+use reth_db::DatabaseEnv;
+use reth_metrics::{Metrics, metrics::Gauge};
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tracing::{debug, warn};
+
+/// Interval between two samples of the readers.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Reader metrics.
+#[derive(Metrics)]
+#[metrics(scope = "berachain.db")]
+struct ReaderMetrics {
+    /// Reader slots in use.
+    readers: Gauge,
+    /// Reader slots available.
+    max_readers: Gauge,
+    /// Pages on the free list.
+    freelist_pages: Gauge,
+    /// Expensive RPC calls running longer than the threshold.
+    long_rpc_calls: Gauge,
+    /// Age of the oldest expensive RPC call in flight, in seconds.
+    oldest_rpc_call_seconds: Gauge,
+}
+
+/// Starts sampling the readers of `db` and the expensive RPC calls in flight in `calls`.
+pub fn install_reader_monitor(
+    db: Arc<DatabaseEnv>,
+    calls: Arc<InflightCalls>,
+    executor: &TaskExecutor,
+) {
+    executor.spawn(async move {
+        let metrics = ReaderMetrics::default();
+        let mut warned = HashSet::new();
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match db.info() {
+                Ok(info) => {
+                    metrics.readers.set(info.num_readers() as f64);
+                    metrics.max_readers.set(info.max_readers() as f64);
+                }
+                Err(err) => debug!(target: "reth::cli", %err, "Failed to read database info"),
+            }
+            match db.freelist() {
+                Ok(pages) => metrics.freelist_pages.set(pages as f64),
+                Err(err) => debug!(target: "reth::cli", %err, "Failed to read database free list"),
+            }
+
+            let running = calls.running_for(Duration::ZERO);
+            metrics
+                .oldest_rpc_call_seconds
+                .set(running.first().map(|call| call.elapsed_seconds).unwrap_or_default() as f64);
+            let long: Vec<_> = running
+                .into_iter()
+                .filter(|call| call.elapsed_seconds >= LONG_CALL_THRESHOLD.as_secs())
+                .collect();
+            metrics.long_rpc_calls.set(long.len() as f64);
+            for call in &long {
+                if warned.insert(call.id) {
+                    warn!(
+                        target: "reth::cli",
+                        id = call.id,
+                        method = %call.method,
+                        params = %call.params,
+                        elapsed = call.elapsed_seconds,
+                        "RPC call holds a database read transaction for long, abort it with \
+                         admin_abortCall if it is stuck"
+                    );
+                }
+            }
+            warned.retain(|id| long.iter().any(|call| call.id == *id));
+        }
+    });
+}
@@ -0,0 +1,247 @@
+//! Auxiliary tables stored next to reth's stock tables.
+
+use crate::{primitives::header::BlsPublicKey, storage::compression::TableCompression};
+use alloy_primitives::{B256, BlockNumber, Bytes, TxHash};
+use bytes::BufMut;
+use reth_codecs::Compact;
+use reth_db_api::{
+    DatabaseError,
+    table::{Compress, Decompress, Table, TableInfo, TableSet},
+};
+use serde::{Deserialize, Serialize};
+
+/// Implements [`Compress`] and [`Decompress`] through the type's [`Compact`] encoding.
+macro_rules! impl_compression_for_compact {
+    ($($name:ty),+) => {
+        $(
+            impl Compress for $name {
+                type Compressed = Vec<u8>;
+
+                fn compress_to_buf<B: BufMut + AsMut<[u8]>>(&self, buf: &mut B) {
+                    let _ = Compact::to_compact(self, buf);
+                }
+            }
+
+            impl Decompress for $name {
+                fn decompress(value: &[u8]) -> Result<Self, DatabaseError> {
+                    let (obj, _) = Compact::from_compact(value, value.len());
+                    Ok(obj)
+                }
+            }
+        )+
+    };
+}
+
+/// PoL distribution recorded for a block.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Compact, Serialize, Deserialize)]
+pub struct PolDistribution {
+    /// Hash of the PoL system transaction.
+    pub tx_hash: TxHash,
+    /// Proposer the distribution was made for.
+    pub pubkey: BlsPublicKey,
+}
+
+/// Aggregated per-proposer statistics, keyed by `keccak256(pubkey)`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Compact, Serialize, Deserialize)]
+pub struct ProposerStats {
+    /// Number of canonical blocks carrying a distribution for this proposer.
+    pub distributions: u64,
+}
+
+/// Blob sidecar retained after its transaction left the pool, in its network encoding.
+///
+/// Holds the value as stored, encoded with the codec configured in
+/// [`CompressionConfig::blob_sidecars`](crate::storage::compression::CompressionConfig).
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct StoredBlobSidecar {
+    stored: Bytes,
+}
+
+impl StoredBlobSidecar {
+    /// Encodes the RLP encoded `BlobTransactionSidecarVariant` `encoded` with `codec`.
+    pub fn new(encoded: &[u8], codec: TableCompression) -> Self {
+        Self { stored: codec.encode(encoded).into() }
+    }
+
+    /// Returns the RLP encoded `BlobTransactionSidecarVariant`.
+    pub fn decode(&self) -> Result<Bytes, DatabaseError> {
+        Ok(TableCompression::decode(&self.stored)?.into())
+    }
+
+    /// Returns the size of the stored value in bytes.
+    pub fn stored_len(&self) -> usize {
+        self.stored.len()
+    }
+}
+
+/// Blob transactions of a block whose sidecars are in [`BlobSidecars`].
+#[derive(Debug, Clone, PartialEq, Eq, Default, Compact, Serialize, Deserialize)]
+pub struct RetainedBlobs {
+    /// Hashes of the transactions whose sidecars were retained.
+    pub tx_hashes: Vec<TxHash>,
+    /// Versioned hashes of their blobs, as indexed in [`BlobTransactions`].
+    pub versioned_hashes: Vec<B256>,
+}
+
+impl_compression_for_compact!(PolDistribution, ProposerStats, RetainedBlobs);
+
+impl Compress for StoredBlobSidecar {
+    type Compressed = Vec<u8>;
+
+    fn compress_to_buf<B: BufMut + AsMut<[u8]>>(&self, buf: &mut B) {
+        buf.put_slice(&self.stored);
+    }
+}
+
+impl Decompress for StoredBlobSidecar {
+    fn decompress(value: &[u8]) -> Result<Self, DatabaseError> {
+        Ok(Self { stored: Bytes::copy_from_slice(value) })
+    }
+}
+
+/// Block number to the PoL distribution executed in that block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PolDistributions;
+
+impl Table for PolDistributions {
+    const NAME: &'static str = "BerachainPolDistributions";
+    const DUPSORT: bool = false;
+    type Key = BlockNumber;
+    type Value = PolDistribution;
+}
+
+/// `keccak256(pubkey)` to the proposer's distribution statistics.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProposerStatistics;
+
+impl Table for ProposerStatistics {
+    const NAME: &'static str = "BerachainProposerStats";
+    const DUPSORT: bool = false;
+    type Key = B256;
+    type Value = ProposerStats;
+}
+
+/// Transaction hash to the blob sidecar of that transaction.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlobSidecars;
+
+impl Table for BlobSidecars {
+    const NAME: &'static str = "BerachainBlobSidecars";
+    const DUPSORT: bool = false;
+    type Key = TxHash;
+    type Value = StoredBlobSidecar;
+}
+
+/// Versioned hash of a blob to the hash of the transaction whose sidecar is in [`BlobSidecars`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlobTransactions;
+
+impl Table for BlobTransactions {
+    const NAME: &'static str = "BerachainBlobTransactions";
+    const DUPSORT: bool = false;
+    type Key = B256;
+    type Value = TxHash;
+}
+
+/// Block number to the blob transactions of that block with retained sidecars.
+///
+/// Lets unwinds and retention pruning find the [`BlobSidecars`] rows of a block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockRetainedBlobs;
+
+impl Table for BlockRetainedBlobs {
+    const NAME: &'static str = "BerachainBlockRetainedBlobs";
+    const DUPSORT: bool = false;
+    type Key = BlockNumber;
+    type Value = RetainedBlobs;
+}
+
+/// Every table defined by bera-reth.
+#[derive(Debug, Clone, Copy)]
+pub enum BerachainTables {
+    /// [`PolDistributions`]
+    PolDistributions,
+    /// [`ProposerStatistics`]
+    ProposerStatistics,
+    /// [`BlobSidecars`]
+    BlobSidecars,
+    /// [`BlobTransactions`]
+    BlobTransactions,
+    /// [`BlockRetainedBlobs`]
+    BlockRetainedBlobs,
+}
+
+impl BerachainTables {
+    /// All tables.
+    pub const ALL: [Self; 5] = [
+        Self::PolDistributions,
+        Self::ProposerStatistics,
+        Self::BlobSidecars,
+        Self::BlobTransactions,
+        Self::BlockRetainedBlobs,
+    ];
+}
+
+impl TableInfo for BerachainTables {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::PolDistributions => PolDistributions::NAME,
+            Self::ProposerStatistics => ProposerStatistics::NAME,
+            Self::BlobSidecars => BlobSidecars::NAME,
+            Self::BlobTransactions => BlobTransactions::NAME,
+            Self::BlockRetainedBlobs => BlockRetainedBlobs::NAME,
+        }
+    }
+
+    fn is_dupsort(&self) -> bool {
+        match self {
+            Self::PolDistributions => PolDistributions::DUPSORT,
+            Self::ProposerStatistics => ProposerStatistics::DUPSORT,
+            Self::BlobSidecars => BlobSidecars::DUPSORT,
+            Self::BlobTransactions => BlobTransactions::DUPSORT,
+            Self::BlockRetainedBlobs => BlockRetainedBlobs::DUPSORT,
+        }
+    }
+}
+
+impl TableSet for BerachainTables {
+    fn tables() -> Box<dyn Iterator<Item = Box<dyn TableInfo>>> {
+        Box::new(Self::ALL.into_iter().map(|table| Box::new(table) as Box<dyn TableInfo>))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_names_are_unique() {
+        let mut names: Vec<_> = BerachainTables::ALL.iter().map(TableInfo::name).collect();
+        names.extend(reth_db_api::Tables::ALL.iter().map(|table| table.name()));
+        let total = names.len();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), total, "Berachain table names must not clash with reth tables");
+    }
+
+    #[test]
+    fn test_value_compression_roundtrip() {
+        let distribution =
+            PolDistribution { tx_hash: B256::repeat_byte(1), pubkey: BlsPublicKey::repeat_byte(2) };
+        let stats = ProposerStats { distributions: 42 };
+        let sidecar = StoredBlobSidecar::new(&[1, 2, 3], TableCompression::default());
+        let blobs = RetainedBlobs {
+            tx_hashes: vec![TxHash::repeat_byte(3)],
+            versioned_hashes: vec![B256::repeat_byte(4), B256::repeat_byte(5)],
+        };
+
+        assert_eq!(
+            PolDistribution::decompress(&distribution.clone().compress()).unwrap(),
+            distribution
+        );
+        assert_eq!(ProposerStats::decompress(&stats.clone().compress()).unwrap(), stats);
+        assert_eq!(StoredBlobSidecar::decompress(&sidecar.clone().compress()).unwrap(), sidecar);
+        assert_eq!(sidecar.decode().unwrap(), Bytes::from_static(&[1, 2, 3]));
+        assert_eq!(RetainedBlobs::decompress(&blobs.clone().compress()).unwrap(), blobs);
+    }
+}
@@ -0,0 +1,131 @@
+//! Version stamp of a datadir.
+//!
+//! Every node start checks `bera-reth.version.json` in the datadir before anything is read from
+//! the database, then rewrites it with the running version. A datadir written with a newer
+//! Berachain storage format (e.g. before a downgrade) or for another chain is refused with an
+//! explanation, instead of failing later with decode panics. Datadirs without a stamp predate it
+//! and are stamped with the current format.
+
+use alloy_primitives::B256;
+use reth_db::version::DB_VERSION;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File name of the stamp in the datadir.
+pub const VERSION_FILE: &str = "bera-reth.version.json";
+
+/// Version of the Berachain-specific storage encodings: the compact encodings of headers, PoL
+/// transactions and the [`tables`](crate::storage::tables). Bumped on every change that older
+/// releases cannot decode.
+pub const STORAGE_FORMAT_VERSION: u64 = 1;
+
+/// Contents of [`VERSION_FILE`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatadirVersion {
+    /// bera-reth version that last opened the datadir.
+    pub client_version: String,
+    /// reth database schema version.
+    pub db_version: u64,
+    /// Berachain storage format, see [`STORAGE_FORMAT_VERSION`].
+    pub storage_format: u64,
+    /// Chain id of the datadir.
+    pub chain_id: u64,
+    /// Genesis hash of the datadir.
+    pub genesis_hash: B256,
+}
+
+impl DatadirVersion {
+    /// Returns the stamp written by this binary.
+    pub fn current(chain_id: u64, genesis_hash: B256) -> Self {
+        Self {
+            client_version: env!("CARGO_PKG_VERSION").to_string(),
+            db_version: DB_VERSION,
+            storage_format: STORAGE_FORMAT_VERSION,
+            chain_id,
+            genesis_hash,
+        }
+    }
+
+    /// Checks that a datadir stamped with `self` can be opened by `current`.
+    pub fn ensure_compatible(&self, current: &Self) -> eyre::Result<()> {
+        eyre::ensure!(
+            self.chain_id == current.chain_id && self.genesis_hash == current.genesis_hash,
+            "datadir belongs to chain {} with genesis {}, but the node runs chain {} with genesis \
+             {}; check --chain and --datadir",
+            self.chain_id,
+            self.genesis_hash,
+            current.chain_id,
+            current.genesis_hash
+        );
+        eyre::ensure!(
+            self.storage_format <= current.storage_format && self.db_version <= current.db_version,
+            "datadir was written by bera-reth {} (storage format {}, database version {}), which \
+             this bera-reth {} (storage format {}, database version {}) cannot read; upgrade \
+             bera-reth or restore a snapshot taken with this version",
+            self.client_version,
+            self.storage_format,
+            self.db_version,
+            current.client_version,
+            current.storage_format,
+            current.db_version
+        );
+        eyre::ensure!(
+            self.storage_format == current.storage_format,
+            "datadir uses storage format {} of bera-reth {}, which this bera-reth {} no longer \
+             reads; re-sync or restore a snapshot taken with this version",
+            self.storage_format,
+            self.client_version,
+            current.client_version
+        );
+        Ok(())
+    }
+}
+
+/// Checks the stamp of the datadir at `data_dir` against `current` and writes `current`.
+pub fn check_and_stamp(data_dir: &Path, current: &DatadirVersion) -> eyre::Result<()> {
+    let path = data_dir.join(VERSION_FILE);
+    match std::fs::read(&path) {
+        Ok(contents) => {
+            let stamp: DatadirVersion = serde_json::from_slice(&contents)
+                .map_err(|err| eyre::eyre!("invalid {}: {err}", path.display()))?;
+            stamp.ensure_compatible(current)?;
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => eyre::bail!("failed to read {}: {err}", path.display()),
+    }
+    std::fs::write(&path, serde_json::to_vec_pretty(current)?)
+        .map_err(|err| eyre::eyre!("failed to write {}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stamp_checks() {
+        let dir = std::env::temp_dir().join(format!("bera-version-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let current = DatadirVersion::current(80094, B256::repeat_byte(1));
+
+        // Unstamped datadirs are stamped, and stamps of this version accepted
+        check_and_stamp(&dir, &current).unwrap();
+        check_and_stamp(&dir, &current).unwrap();
+
+        let newer = DatadirVersion {
+            client_version: "9.9.9".to_string(),
+            storage_format: STORAGE_FORMAT_VERSION + 1,
+            ..current.clone()
+        };
+        let err = newer.ensure_compatible(&current).unwrap_err().to_string();
+        assert!(err.contains("bera-reth 9.9.9"), "{err}");
+
+        let other_chain = DatadirVersion::current(80069, B256::repeat_byte(2));
+        assert!(other_chain.ensure_compatible(&current).is_err());
+
+        std::fs::write(dir.join(VERSION_FILE), serde_json::to_vec(&newer).unwrap()).unwrap();
+        assert!(check_and_stamp(&dir, &current).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,420 @@
+//! Deterministic corpus covering every Berachain [`Compact`] codec.
+//!
+//! The same values back the storage roundtrip tests below and the `compact` criterion benchmarks,
+//! so any change to an on-disk format is gated on both correctness and encode/decode throughput.
+//! Entries are fully deterministic (no random fields) to keep benchmark runs comparable.
+
+use crate::{
+    primitives::{BerachainHeader, header::BlsPublicKey},
+    transaction::{BerachainTxEnvelope, BerachainTxType, PoLTx},
+};
+use alloy_consensus::{
+    Signed, TxEip1559, TxEip2930, TxEip4844, TxEip4844Variant, TxEip7702, TxEnvelope, TxLegacy,
+    TxType,
+};
+use alloy_eips::{
+    eip2930::{AccessList, AccessListItem},
+    eip7002::SYSTEM_ADDRESS,
+    eip7702::{Authorization, SignedAuthorization},
+};
+use alloy_primitives::{
+    Address, B64, B256, Bloom, Bytes, ChainId, Log, Sealed, Signature, TxKind, U256, hex,
+};
+use reth_codecs::Compact;
+use reth_ethereum_primitives::Receipt;
+
+/// Receipt type persisted by bera-reth.
+pub type BerachainReceipt = Receipt<BerachainTxType>;
+
+/// Empty ommers hash, used by every post-merge header.
+const EMPTY_OMMERS_HASH: B256 =
+    alloy_primitives::b256!("0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347");
+
+/// Chain id used by corpus transactions (Bepolia).
+const CORPUS_CHAIN_ID: ChainId = 80069;
+
+/// A named corpus value.
+#[derive(Debug, Clone)]
+pub struct CorpusEntry<T> {
+    /// Human readable name, used in assertion messages and benchmark ids.
+    pub name: &'static str,
+    /// The value under test.
+    pub value: T,
+}
+
+impl<T> CorpusEntry<T> {
+    const fn new(name: &'static str, value: T) -> Self {
+        Self { name, value }
+    }
+}
+
+/// Encodes `value` with [`Compact`], decodes it back and returns the decoded value together with
+/// the encoded bytes.
+pub fn compact_roundtrip<T: Compact>(value: &T) -> (T, Vec<u8>) {
+    let mut buf = Vec::new();
+    let len = value.to_compact(&mut buf);
+    let (decoded, _) = T::from_compact(&buf, len);
+    (decoded, buf)
+}
+
+/// Headers spanning every fork shape bera-reth stores, from pre-London through Prague1.
+pub fn headers() -> Vec<CorpusEntry<BerachainHeader>> {
+    let base = BerachainHeader {
+        parent_hash: B256::repeat_byte(0x01),
+        ommers_hash: EMPTY_OMMERS_HASH,
+        beneficiary: Address::repeat_byte(0x02),
+        state_root: B256::repeat_byte(0x03),
+        transactions_root: B256::repeat_byte(0x04),
+        receipts_root: B256::repeat_byte(0x05),
+        logs_bloom: Bloom::repeat_byte(0x06),
+        difficulty: U256::ZERO,
+        number: 1_000_000,
+        gas_limit: 30_000_000,
+        gas_used: 21_000,
+        timestamp: 1_737_381_600,
+        mix_hash: B256::repeat_byte(0x07),
+        nonce: B64::ZERO,
+        extra_data: Bytes::from_static(b"bera-reth"),
+        ..Default::default()
+    };
+
+    let london = BerachainHeader { base_fee_per_gas: Some(7), ..base.clone() };
+    let cancun = BerachainHeader {
+        withdrawals_root: Some(B256::repeat_byte(0x08)),
+        blob_gas_used: Some(131_072),
+        excess_blob_gas: Some(262_144),
+        parent_beacon_block_root: Some(B256::repeat_byte(0x09)),
+        ..london.clone()
+    };
+    let prague = BerachainHeader { requests_hash: Some(B256::repeat_byte(0x0a)), ..cancun.clone() };
+    let prague1 = BerachainHeader {
+        base_fee_per_gas: Some(1_000_000_000),
+        prev_proposer_pubkey: Some(BlsPublicKey::repeat_byte(0x0b)),
+        ..prague.clone()
+    };
+
+    vec![
+        CorpusEntry::new("pre_london", base.clone()),
+        CorpusEntry::new("london", london),
+        CorpusEntry::new("cancun", cancun.clone()),
+        CorpusEntry::new("prague", prague),
+        CorpusEntry::new("prague1", prague1.clone()),
+        CorpusEntry::new(
+            "prague1_without_requests_hash",
+            BerachainHeader { requests_hash: None, ..prague1.clone() },
+        ),
+        CorpusEntry::new(
+            "prague1_zero_pubkey_genesis",
+            BerachainHeader {
+                number: 0,
+                prev_proposer_pubkey: Some(BlsPublicKey::ZERO),
+                ..prague1.clone()
+            },
+        ),
+        CorpusEntry::new(
+            "max_extra_data",
+            BerachainHeader { extra_data: Bytes::from(vec![0xff; 32]), ..prague1 },
+        ),
+        CorpusEntry::new(
+            "empty_extra_data",
+            BerachainHeader { extra_data: Bytes::new(), ..cancun },
+        ),
+        CorpusEntry::new(
+            "max_scalars",
+            BerachainHeader {
+                number: u64::MAX,
+                gas_limit: u64::MAX,
+                gas_used: u64::MAX,
+                timestamp: u64::MAX,
+                base_fee_per_gas: Some(u64::MAX),
+                difficulty: U256::MAX,
+                nonce: B64::repeat_byte(0xff),
+                ..base
+            },
+        ),
+    ]
+}
+
+/// Transaction envelopes covering every Ethereum type and the PoL system transaction.
+pub fn tx_envelopes() -> Vec<CorpusEntry<BerachainTxEnvelope>> {
+    let signature = Signature::new(U256::from(1u64), U256::from(2u64), false);
+    let access_list = AccessList(vec![AccessListItem {
+        address: Address::repeat_byte(0x11),
+        storage_keys: vec![B256::repeat_byte(0x12), B256::repeat_byte(0x13)],
+    }]);
+
+    let legacy = TxLegacy {
+        chain_id: Some(CORPUS_CHAIN_ID),
+        nonce: 1,
+        gas_price: 20_000_000_000,
+        gas_limit: 21_000,
+        to: TxKind::Call(Address::repeat_byte(0x21)),
+        value: U256::from(100),
+        input: Bytes::new(),
+    };
+    let legacy_pre_eip155 = TxLegacy { chain_id: None, ..legacy.clone() };
+    let eip2930 = TxEip2930 {
+        chain_id: CORPUS_CHAIN_ID,
+        nonce: 2,
+        gas_price: 25_000_000_000,
+        gas_limit: 50_000,
+        to: TxKind::Create,
+        value: U256::ZERO,
+        access_list: access_list.clone(),
+        input: Bytes::from_static(&hex!("6080604052")),
+    };
+    let eip1559 = TxEip1559 {
+        chain_id: CORPUS_CHAIN_ID,
+        nonce: 3,
+        gas_limit: 100_000,
+        max_fee_per_gas: 50_000_000_000,
+        max_priority_fee_per_gas: 2_000_000_000,
+        to: TxKind::Call(Address::repeat_byte(0x31)),
+        value: U256::from(300),
+        access_list: access_list.clone(),
+        input: Bytes::from_static(&hex!("a9059cbb")),
+    };
+    let eip4844 = TxEip4844 {
+        chain_id: CORPUS_CHAIN_ID,
+        nonce: 4,
+        gas_limit: 40_000,
+        max_fee_per_gas: 60_000_000_000,
+        max_priority_fee_per_gas: 3_000_000_000,
+        to: Address::repeat_byte(0x41),
+        value: U256::from(400),
+        access_list: AccessList::default(),
+        blob_versioned_hashes: vec![B256::repeat_byte(0x42), B256::repeat_byte(0x43)],
+        max_fee_per_blob_gas: 15_000_000_000,
+        input: Bytes::new(),
+    };
+    let eip7702 = TxEip7702 {
+        chain_id: CORPUS_CHAIN_ID,
+        nonce: 5,
+        gas_limit: 80_000,
+        max_fee_per_gas: 70_000_000_000,
+        max_priority_fee_per_gas: 4_000_000_000,
+        to: Address::repeat_byte(0x51),
+        value: U256::from(500),
+        access_list: AccessList::default(),
+        authorization_list: vec![SignedAuthorization::new_unchecked(
+            Authorization {
+                chain_id: U256::from(CORPUS_CHAIN_ID),
+                address: Address::repeat_byte(0x52),
+                nonce: 6,
+            },
+            1,
+            U256::from(3u64),
+            U256::from(4u64),
+        )],
+        input: Bytes::new(),
+    };
+
+    let pol = PoLTx {
+        chain_id: CORPUS_CHAIN_ID,
+        from: SYSTEM_ADDRESS,
+        to: Address::repeat_byte(0x61),
+        nonce: 999_999,
+        gas_limit: 30_000_000,
+        gas_price: 1_000_000_000,
+        // distributeFor(bytes) with a 48 byte pubkey
+        input: Bytes::from([hex!("7ad6ed9b").as_slice(), &[0x62; 48]].concat()),
+    };
+    let pol_empty_input = PoLTx { nonce: 0, input: Bytes::new(), ..pol.clone() };
+
+    vec![
+        CorpusEntry::new(
+            "legacy",
+            BerachainTxEnvelope::Ethereum(TxEnvelope::Legacy(Signed::new_unhashed(
+                legacy, signature,
+            ))),
+        ),
+        CorpusEntry::new(
+            "legacy_pre_eip155",
+            BerachainTxEnvelope::Ethereum(TxEnvelope::Legacy(Signed::new_unhashed(
+                legacy_pre_eip155,
+                signature,
+            ))),
+        ),
+        CorpusEntry::new(
+            "eip2930_create",
+            BerachainTxEnvelope::Ethereum(TxEnvelope::Eip2930(Signed::new_unhashed(
+                eip2930, signature,
+            ))),
+        ),
+        CorpusEntry::new(
+            "eip1559",
+            BerachainTxEnvelope::Ethereum(TxEnvelope::Eip1559(Signed::new_unhashed(
+                eip1559, signature,
+            ))),
+        ),
+        CorpusEntry::new(
+            "eip4844",
+            BerachainTxEnvelope::Ethereum(TxEnvelope::Eip4844(Signed::new_unhashed(
+                TxEip4844Variant::TxEip4844(eip4844),
+                signature,
+            ))),
+        ),
+        CorpusEntry::new(
+            "eip7702",
+            BerachainTxEnvelope::Ethereum(TxEnvelope::Eip7702(Signed::new_unhashed(
+                eip7702, signature,
+            ))),
+        ),
+        CorpusEntry::new("pol", BerachainTxEnvelope::Berachain(Sealed::new(pol))),
+        CorpusEntry::new(
+            "pol_empty_input",
+            BerachainTxEnvelope::Berachain(Sealed::new(pol_empty_input)),
+        ),
+    ]
+}
+
+/// Every [`BerachainTxType`] variant.
+pub fn tx_types() -> Vec<CorpusEntry<BerachainTxType>> {
+    vec![
+        CorpusEntry::new("legacy", BerachainTxType::Ethereum(TxType::Legacy)),
+        CorpusEntry::new("eip2930", BerachainTxType::Ethereum(TxType::Eip2930)),
+        CorpusEntry::new("eip1559", BerachainTxType::Ethereum(TxType::Eip1559)),
+        CorpusEntry::new("eip4844", BerachainTxType::Ethereum(TxType::Eip4844)),
+        CorpusEntry::new("eip7702", BerachainTxType::Ethereum(TxType::Eip7702)),
+        CorpusEntry::new("pol", BerachainTxType::Berachain),
+    ]
+}
+
+/// Receipts for every transaction type, with and without logs.
+pub fn receipts() -> Vec<CorpusEntry<BerachainReceipt>> {
+    let log = Log::new_unchecked(
+        Address::repeat_byte(0x71),
+        vec![B256::repeat_byte(0x72), B256::repeat_byte(0x73)],
+        Bytes::from_static(&[0x74; 64]),
+    );
+
+    tx_types()
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, CorpusEntry { name, value: tx_type })| {
+            let cumulative_gas_used = 21_000 * (i as u64 + 1);
+            [
+                CorpusEntry::new(
+                    name,
+                    Receipt {
+                        tx_type,
+                        success: true,
+                        cumulative_gas_used,
+                        logs: vec![log.clone()],
+                    },
+                ),
+                CorpusEntry::new(
+                    name,
+                    Receipt { tx_type, success: false, cumulative_gas_used, logs: vec![] },
+                ),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::compact_fuzz::to_reth;
+    use alloy_consensus::Header;
+
+    /// Decoding must reproduce the value and re-encoding must reproduce the exact bytes.
+    fn assert_stable<T: Compact + PartialEq + core::fmt::Debug>(entries: Vec<CorpusEntry<T>>) {
+        for CorpusEntry { name, value } in entries {
+            let (decoded, encoded) = compact_roundtrip(&value);
+            assert_eq!(decoded, value, "{name}: decoded value mismatch");
+
+            let (_, reencoded) = compact_roundtrip(&decoded);
+            assert_eq!(reencoded, encoded, "{name}: re-encoding is not byte-identical");
+        }
+    }
+
+    #[test]
+    fn test_header_corpus_roundtrip() {
+        assert_stable(headers());
+    }
+
+    #[test]
+    fn test_tx_envelope_corpus_roundtrip() {
+        assert_stable(tx_envelopes());
+    }
+
+    #[test]
+    fn test_tx_type_corpus_roundtrip() {
+        assert_stable(tx_types());
+    }
+
+    #[test]
+    fn test_receipt_corpus_roundtrip() {
+        assert_stable(receipts());
+    }
+
+    /// Headers without Berachain extension fields must be stored exactly like reth stores alloy
+    /// headers.
+    #[test]
+    fn test_header_corpus_matches_reth_encoding() {
+        for CorpusEntry { name, value } in headers() {
+            if value.prev_proposer_pubkey.is_some() {
+                continue;
+            }
+            let reth_header = Header {
+                parent_hash: value.parent_hash,
+                ommers_hash: value.ommers_hash,
+                beneficiary: value.beneficiary,
+                state_root: value.state_root,
+                transactions_root: value.transactions_root,
+                receipts_root: value.receipts_root,
+                logs_bloom: value.logs_bloom,
+                difficulty: value.difficulty,
+                number: value.number,
+                gas_limit: value.gas_limit,
+                gas_used: value.gas_used,
+                timestamp: value.timestamp,
+                extra_data: value.extra_data.clone(),
+                mix_hash: value.mix_hash,
+                nonce: value.nonce,
+                base_fee_per_gas: value.base_fee_per_gas,
+                withdrawals_root: value.withdrawals_root,
+                blob_gas_used: value.blob_gas_used,
+                excess_blob_gas: value.excess_blob_gas,
+                parent_beacon_block_root: value.parent_beacon_block_root,
+                requests_hash: value.requests_hash,
+            };
+
+            let (_, bera_bytes) = compact_roundtrip(&value);
+            let (_, reth_bytes) = compact_roundtrip(&reth_header);
+            assert_eq!(bera_bytes, reth_bytes, "{name}: header storage format diverged from reth");
+        }
+    }
+
+    /// Ethereum transactions must be stored exactly like reth stores `TransactionSigned`.
+    #[test]
+    fn test_tx_envelope_corpus_matches_reth_encoding() {
+        for CorpusEntry { name, value } in tx_envelopes() {
+            let BerachainTxEnvelope::Ethereum(tx) = &value else { continue };
+            let reth_tx = to_reth(tx.clone());
+
+            let (_, bera_bytes) = compact_roundtrip(&value);
+            let (_, reth_bytes) = compact_roundtrip(&reth_tx);
+            assert_eq!(bera_bytes, reth_bytes, "{name}: tx storage format diverged from reth");
+        }
+    }
+
+    /// Receipts for Ethereum transaction types must be stored exactly like reth receipts.
+    #[test]
+    fn test_receipt_corpus_matches_reth_encoding() {
+        for CorpusEntry { name, value } in receipts() {
+            let BerachainTxType::Ethereum(tx_type) = value.tx_type else { continue };
+            let reth_receipt = Receipt {
+                tx_type,
+                success: value.success,
+                cumulative_gas_used: value.cumulative_gas_used,
+                logs: value.logs.clone(),
+            };
+
+            let (_, bera_bytes) = compact_roundtrip(&value);
+            let (_, reth_bytes) = compact_roundtrip(&reth_receipt);
+            assert_eq!(bera_bytes, reth_bytes, "{name}: receipt storage format diverged from reth");
+        }
+    }
+}
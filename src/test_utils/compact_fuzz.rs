@@ -0,0 +1,256 @@
+//! Randomized storage compatibility checks for [`BerachainTxEnvelope`].
+//!
+//! The fixed [`compact_corpus`](super::compact_corpus) pins one value per transaction shape. This
+//! module generates arbitrary Ethereum and PoL transactions from a seed instead, with fields that
+//! are zero, small or full width so every bit of the compact headers is exercised, and
+//! [`check_tx_envelope`] asserts for each of them that:
+//!
+//! - Ethereum transactions are compacted byte for byte like reth's `TransactionSigned`, so rows
+//!   written by either client decode with the other one;
+//! - every envelope decodes back to itself and re-encodes to the same bytes.
+//!
+//! A reth upgrade that changes its transaction codec fails these checks before it reaches a
+//! datadir. [`check_seed`] is also a `test-fuzz` target, so the seeds captured by the unit tests
+//! seed `cargo test-fuzz check_seed` for longer runs.
+
+use crate::{
+    test_utils::{compact_corpus::compact_roundtrip, diff_fuzz::Rng},
+    transaction::{BerachainTxEnvelope, PoLTx},
+};
+use alloy_consensus::{
+    Signed, TxEip1559, TxEip2930, TxEip4844, TxEip4844Variant, TxEip7702, TxEnvelope, TxLegacy,
+};
+use alloy_eips::{
+    eip2930::{AccessList, AccessListItem},
+    eip7002::SYSTEM_ADDRESS,
+    eip7702::{Authorization, SignedAuthorization},
+};
+use alloy_primitives::{Bytes, Sealed, Signature, TxKind, U256};
+use reth_codecs::Compact;
+use reth_ethereum_primitives::TransactionSigned;
+
+/// Returns a number that is zero, a single byte or full width, each a third of the time.
+fn number(rng: &mut Rng) -> u64 {
+    match rng.below(3) {
+        0 => 0,
+        1 => rng.below(256),
+        _ => rng.next_u64(),
+    }
+}
+
+/// Returns a fee, up to 128 bits wide.
+fn fee(rng: &mut Rng) -> u128 {
+    ((number(rng) as u128) << 64) | number(rng) as u128
+}
+
+/// Returns a value, up to 256 bits wide.
+fn value(rng: &mut Rng) -> U256 {
+    U256::from_limbs([number(rng), number(rng), number(rng), number(rng)])
+}
+
+/// Returns up to 64 random bytes.
+fn input(rng: &mut Rng) -> Bytes {
+    let len = rng.below(65) as usize;
+    rng.bytes(len).into()
+}
+
+/// Returns an access list of up to two entries with up to two keys each.
+fn access_list(rng: &mut Rng) -> AccessList {
+    AccessList(
+        (0..rng.below(3))
+            .map(|_| AccessListItem {
+                address: rng.address(),
+                storage_keys: (0..rng.below(3)).map(|_| rng.b256()).collect(),
+            })
+            .collect(),
+    )
+}
+
+/// Returns a signature with random components.
+fn signature(rng: &mut Rng) -> Signature {
+    Signature::new(value(rng), value(rng), rng.below(2) == 1)
+}
+
+/// Returns a signed Ethereum transaction of a random type.
+pub fn ethereum_tx(rng: &mut Rng) -> TxEnvelope {
+    let signature = signature(rng);
+    let to = match rng.below(4) {
+        0 => TxKind::Create,
+        _ => TxKind::Call(rng.address()),
+    };
+    match rng.below(5) {
+        0 => TxEnvelope::Legacy(Signed::new_unhashed(
+            TxLegacy {
+                chain_id: (rng.below(2) == 1).then(|| number(rng)),
+                nonce: number(rng),
+                gas_price: fee(rng),
+                gas_limit: number(rng),
+                to,
+                value: value(rng),
+                input: input(rng),
+            },
+            signature,
+        )),
+        1 => TxEnvelope::Eip2930(Signed::new_unhashed(
+            TxEip2930 {
+                chain_id: number(rng),
+                nonce: number(rng),
+                gas_price: fee(rng),
+                gas_limit: number(rng),
+                to,
+                value: value(rng),
+                access_list: access_list(rng),
+                input: input(rng),
+            },
+            signature,
+        )),
+        2 => TxEnvelope::Eip1559(Signed::new_unhashed(
+            TxEip1559 {
+                chain_id: number(rng),
+                nonce: number(rng),
+                gas_limit: number(rng),
+                max_fee_per_gas: fee(rng),
+                max_priority_fee_per_gas: fee(rng),
+                to,
+                value: value(rng),
+                access_list: access_list(rng),
+                input: input(rng),
+            },
+            signature,
+        )),
+        3 => TxEnvelope::Eip4844(
+            Signed::new_unhashed(
+                TxEip4844 {
+                    chain_id: number(rng),
+                    nonce: number(rng),
+                    gas_limit: number(rng),
+                    max_fee_per_gas: fee(rng),
+                    max_priority_fee_per_gas: fee(rng),
+                    to: rng.address(),
+                    value: value(rng),
+                    access_list: access_list(rng),
+                    blob_versioned_hashes: (0..rng.below(7)).map(|_| rng.b256()).collect(),
+                    max_fee_per_blob_gas: fee(rng),
+                    input: input(rng),
+                },
+                signature,
+            )
+            .map(TxEip4844Variant::TxEip4844),
+        ),
+        _ => TxEnvelope::Eip7702(Signed::new_unhashed(
+            TxEip7702 {
+                chain_id: number(rng),
+                nonce: number(rng),
+                gas_limit: number(rng),
+                max_fee_per_gas: fee(rng),
+                max_priority_fee_per_gas: fee(rng),
+                to: rng.address(),
+                value: value(rng),
+                access_list: access_list(rng),
+                authorization_list: (0..rng.below(3))
+                    .map(|_| {
+                        SignedAuthorization::new_unchecked(
+                            Authorization {
+                                chain_id: value(rng),
+                                address: rng.address(),
+                                nonce: number(rng),
+                            },
+                            rng.below(2) as u8,
+                            value(rng),
+                            value(rng),
+                        )
+                    })
+                    .collect(),
+                input: input(rng),
+            },
+            signature,
+        )),
+    }
+}
+
+/// Returns a PoL transaction with random fields.
+pub fn pol_tx(rng: &mut Rng) -> PoLTx {
+    PoLTx {
+        chain_id: number(rng),
+        from: SYSTEM_ADDRESS,
+        to: rng.address(),
+        nonce: number(rng),
+        gas_limit: number(rng),
+        gas_price: fee(rng),
+        input: input(rng),
+    }
+}
+
+/// Returns an Ethereum or, one time in four, a PoL envelope.
+pub fn tx_envelope(rng: &mut Rng) -> BerachainTxEnvelope {
+    match rng.below(4) {
+        0 => BerachainTxEnvelope::Berachain(Sealed::new(pol_tx(rng))),
+        _ => BerachainTxEnvelope::Ethereum(ethereum_tx(rng)),
+    }
+}
+
+/// Returns `tx` as reth stores it.
+pub fn to_reth(tx: TxEnvelope) -> TransactionSigned {
+    match tx {
+        TxEnvelope::Legacy(tx) => TransactionSigned::Legacy(tx),
+        TxEnvelope::Eip2930(tx) => TransactionSigned::Eip2930(tx),
+        TxEnvelope::Eip1559(tx) => TransactionSigned::Eip1559(tx),
+        TxEnvelope::Eip4844(tx) => {
+            let (tx, signature, hash) = tx.into_parts();
+            TransactionSigned::Eip4844(Signed::new_unchecked(tx.into(), signature, hash))
+        }
+        TxEnvelope::Eip7702(tx) => TransactionSigned::Eip7702(tx),
+    }
+}
+
+/// Asserts that `tx` roundtrips through its compact encoding and, for Ethereum transactions, that
+/// the encoding is interchangeable with reth's.
+pub fn check_tx_envelope(tx: &BerachainTxEnvelope) {
+    let (decoded, encoded) = compact_roundtrip(tx);
+    assert_eq!(&decoded, tx, "decoded envelope differs");
+    assert_eq!(compact_roundtrip(&decoded).1, encoded, "re-encoding is not byte-identical");
+
+    let BerachainTxEnvelope::Ethereum(eth_tx) = tx else { return };
+    let reth_tx = to_reth(eth_tx.clone());
+    let mut reth_encoded = Vec::new();
+    let len = reth_tx.to_compact(&mut reth_encoded);
+    assert_eq!(reth_encoded, encoded, "storage encoding diverged from reth");
+
+    // Rows written by reth decode as the same transaction, and the other way around
+    let (from_reth, _) = BerachainTxEnvelope::from_compact(&reth_encoded, len);
+    assert_eq!(&from_reth, tx, "reth row decodes differently");
+    let (from_berachain, _) = TransactionSigned::from_compact(&encoded, len);
+    assert_eq!(from_berachain, reth_tx, "row is decoded differently by reth");
+}
+
+/// Checks the envelope generated from `seed`.
+#[test_fuzz::test_fuzz]
+pub fn check_seed(seed: u64) {
+    check_tx_envelope(&tx_envelope(&mut Rng::new(seed)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_envelopes_match_reth_encoding() {
+        for seed in 0..2_000 {
+            check_seed(seed);
+        }
+    }
+
+    #[test]
+    fn test_generator_covers_every_type() {
+        let mut rng = Rng::new(0);
+        let mut seen = [false; 6];
+        for _ in 0..500 {
+            let index = match tx_envelope(&mut rng) {
+                BerachainTxEnvelope::Ethereum(tx) => tx.tx_type() as usize,
+                BerachainTxEnvelope::Berachain(_) => 5,
+            };
+            seen[index] = true;
+        }
+        assert_eq!(seen, [true; 6]);
+    }
+}
@@ -0,0 +1,526 @@
+//! Differential fuzzing against bera-geth.
+//!
+//! The `bera-diff-fuzz` binary (feature `diff-fuzz`) builds random blocks on an in-process
+//! [`TestNode`] and replays them on an external bera-geth initialized with the same genesis.
+//! Blocks are filled with random valid transactions (transfers, precompile calls with random input,
+//! contract creations from random init code, and fee caps equal to the next base fee) and random
+//! withdrawals, and proposed by a random validator each. Their timestamps straddle Prague1, so the
+//! blocks around the boundary exercise the minimum base fee, the base fee denominator change and
+//! the first PoL transactions.
+//!
+//! Each block is handed to bera-geth with the engine API method BeaconKit would use. A block it
+//! rejects means the two clients disagree on its state root, receipts or validity, and ends the
+//! run; otherwise the JSON of `eth_getBlockByNumber` and `eth_getBlockReceipts` is compared field
+//! by field. Everything random derives from `--seed`, so a divergence can be replayed.
+
+use crate::{
+    chainspec::{BerachainChainSpec, BerachainChainSpecParser},
+    engine::{
+        mock_consensus::{EngineClient, engine_client},
+        payload::BerachainPayloadAttributes,
+    },
+    hardforks::{BerachainHardfork, BerachainHardforks},
+    primitives::{BerachainBlock, header::BlsPublicKey},
+    test_utils::node::TestNode,
+};
+use alloy_consensus::{
+    BlockHeader, SignableTransaction, TxEip1559, TxEip2930, TxEnvelope, TxLegacy,
+};
+use alloy_eips::{
+    eip2718::Encodable2718,
+    eip2930::{AccessList, AccessListItem},
+    eip4895::Withdrawal,
+    eip7685::EMPTY_REQUESTS_HASH,
+};
+use alloy_network::TxSignerSync;
+use alloy_primitives::{Address, B256, Bytes, TxKind, U64, U256};
+use alloy_rpc_types::engine::{ExecutionPayloadV3, ForkchoiceState, PayloadStatus};
+use alloy_signer_local::PrivateKeySigner;
+use clap::Parser;
+use jsonrpsee_core::{client::ClientT, params::ArrayParams, rpc_params};
+use jsonrpsee_http_client::{HttpClient, HttpClientBuilder};
+use reth::chainspec::EthereumHardforks;
+use reth_chainspec::EthChainSpec;
+use reth_cli::chainspec::ChainSpecParser;
+use reth_node_ethereum::engine::EthPayloadAttributes;
+use reth_primitives_traits::SealedBlock;
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Arguments of `bera-diff-fuzz`.
+#[derive(Debug, Parser)]
+#[command(about = "Differential fuzzing of bera-reth against bera-geth")]
+pub struct DiffFuzzArgs {
+    /// Genesis file bera-geth was initialized with
+    #[arg(long, value_name = "CHAIN_OR_PATH", value_parser = BerachainChainSpecParser::parser())]
+    pub chain: Arc<BerachainChainSpec>,
+
+    /// HTTP JSON-RPC URL of bera-geth
+    #[arg(long = "geth.http", value_name = "URL", default_value = "http://127.0.0.1:8545")]
+    pub geth_http: String,
+
+    /// Engine API URL of bera-geth
+    #[arg(long = "geth.authrpc", value_name = "URL", default_value = "http://127.0.0.1:8551")]
+    pub geth_authrpc: String,
+
+    /// File holding the hex encoded engine API secret of bera-geth
+    #[arg(long = "geth.jwtsecret", value_name = "FILE")]
+    pub geth_jwt_secret: PathBuf,
+
+    /// Private key of an account funded in the genesis. Can be repeated
+    #[arg(long = "key", value_name = "HEX", required = true)]
+    pub keys: Vec<B256>,
+
+    /// Seed of the run, random if unset
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Blocks to build
+    #[arg(long, default_value_t = 32)]
+    pub blocks: u64,
+
+    /// Transactions sent before each block
+    #[arg(long, default_value_t = 8)]
+    pub txs_per_block: usize,
+
+    /// JSON field left out of RPC comparisons, e.g. `size`. Can be repeated
+    #[arg(long = "ignore-field", value_name = "NAME")]
+    pub ignore_fields: Vec<String>,
+}
+
+/// Deterministic random numbers (splitmix64).
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Creates a generator from `seed`.
+    pub const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns the next number.
+    pub const fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number below `bound`, which must not be zero.
+    pub const fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// Returns `len` random bytes.
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next_u64() as u8).collect()
+    }
+
+    /// Returns a random hash.
+    pub fn b256(&mut self) -> B256 {
+        B256::from_slice(&self.bytes(32))
+    }
+
+    /// Returns a random address.
+    pub fn address(&mut self) -> Address {
+        Address::from_slice(&self.bytes(20))
+    }
+}
+
+/// Returns the timestamps of `count` blocks built on a parent at `parent`.
+///
+/// Blocks are a second apart, except that if Prague1 activates later, the middle block is moved
+/// to its activation time so that half of the blocks are built on each side of the boundary.
+pub fn timestamps(parent: u64, prague1: Option<u64>, count: u64) -> Vec<u64> {
+    let half = count / 2;
+    (0..count)
+        .map(|index| match prague1 {
+            Some(prague1) if index >= half && prague1 > parent + half => prague1 + index - half,
+            _ => parent + 1 + index,
+        })
+        .collect()
+}
+
+/// Appends the paths at which `reth` and `geth` differ to `diffs`, skipping fields in `ignore`.
+pub fn diff_json(
+    path: &str,
+    reth: &Value,
+    geth: &Value,
+    ignore: &[String],
+    diffs: &mut Vec<String>,
+) {
+    match (reth, geth) {
+        (Value::Object(reth), Value::Object(geth)) => {
+            let keys: BTreeSet<_> = reth.keys().chain(geth.keys()).collect();
+            for key in keys.into_iter().filter(|key| !ignore.contains(key)) {
+                let path = format!("{path}.{key}");
+                match (reth.get(key), geth.get(key)) {
+                    (Some(reth), Some(geth)) => diff_json(&path, reth, geth, ignore, diffs),
+                    (Some(reth), None) => diffs.push(format!("{path}: reth {reth}, geth absent")),
+                    (None, Some(geth)) => diffs.push(format!("{path}: reth absent, geth {geth}")),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(reth), Value::Array(geth)) if reth.len() == geth.len() => {
+            for (index, (reth, geth)) in reth.iter().zip(geth).enumerate() {
+                diff_json(&format!("{path}[{index}]"), reth, geth, ignore, diffs);
+            }
+        }
+        (reth, geth) if reth != geth => diffs.push(format!("{path}: reth {reth}, geth {geth}")),
+        _ => {}
+    }
+}
+
+/// Random valid transactions from a set of funded accounts.
+#[derive(Debug)]
+struct TxGenerator {
+    chain_id: u64,
+    accounts: Vec<(PrivateKeySigner, u64)>,
+}
+
+impl TxGenerator {
+    /// Returns a signed transaction paying at least `base_fee`, encoded for
+    /// `eth_sendRawTransaction`.
+    fn generate(&mut self, rng: &mut Rng, base_fee: u128) -> eyre::Result<Bytes> {
+        let account = rng.below(self.accounts.len() as u64) as usize;
+        let (signer, nonce) = &mut self.accounts[account];
+        let sender = signer.address();
+        let tip = rng.below(1_000_000_000) as u128;
+        let max_fee = 2 * base_fee + tip;
+        let value = U256::from(rng.below(1_000_000));
+        let input: Bytes = rng.bytes(rng.below(128) as usize).into();
+
+        let envelope: TxEnvelope = match rng.below(5) {
+            // transfer to a random address
+            0 => {
+                let mut tx = TxLegacy {
+                    chain_id: Some(self.chain_id),
+                    nonce: *nonce,
+                    gas_price: max_fee,
+                    gas_limit: 21_000,
+                    to: TxKind::Call(rng.address()),
+                    value,
+                    input: Bytes::new(),
+                };
+                let signature = signer.sign_transaction_sync(&mut tx)?;
+                tx.into_signed(signature).into()
+            }
+            // precompile with random input and access list
+            1 => {
+                let precompile = Address::with_last_byte(1 + rng.below(0x11) as u8);
+                let item = AccessListItem { address: precompile, storage_keys: vec![rng.b256()] };
+                let mut tx = TxEip2930 {
+                    chain_id: self.chain_id,
+                    nonce: *nonce,
+                    gas_price: max_fee,
+                    gas_limit: 1_000_000,
+                    to: TxKind::Call(precompile),
+                    value: U256::ZERO,
+                    access_list: AccessList(vec![item]),
+                    input,
+                };
+                let signature = signer.sign_transaction_sync(&mut tx)?;
+                tx.into_signed(signature).into()
+            }
+            kind => {
+                let (to, max_fee, tip) = match kind {
+                    // contract creation from random init code, which mostly halts
+                    2 => (TxKind::Create, max_fee, tip),
+                    // fee cap equal to the base fee, without a tip
+                    3 => (TxKind::Call(rng.address()), base_fee, 0),
+                    // call to the sender itself
+                    _ => (TxKind::Call(sender), max_fee, tip),
+                };
+                let mut tx = TxEip1559 {
+                    chain_id: self.chain_id,
+                    nonce: *nonce,
+                    gas_limit: 1_000_000,
+                    max_fee_per_gas: max_fee,
+                    max_priority_fee_per_gas: tip,
+                    to,
+                    value,
+                    access_list: AccessList::default(),
+                    input,
+                };
+                let signature = signer.sign_transaction_sync(&mut tx)?;
+                tx.into_signed(signature).into()
+            }
+        };
+        *nonce += 1;
+        Ok(envelope.encoded_2718().into())
+    }
+
+    /// Reloads the nonces of the accounts from the state of `client`.
+    async fn sync_nonces(&mut self, client: &HttpClient) -> eyre::Result<()> {
+        for (signer, nonce) in &mut self.accounts {
+            let count: U64 = client
+                .request("eth_getTransactionCount", rpc_params![signer.address(), "latest"])
+                .await?;
+            *nonce = count.to();
+        }
+        Ok(())
+    }
+}
+
+/// Returns random payload attributes for a block at `timestamp`.
+fn attributes(
+    chain_spec: &BerachainChainSpec,
+    rng: &mut Rng,
+    timestamp: u64,
+) -> BerachainPayloadAttributes {
+    let withdrawals = (0..rng.below(3))
+        .map(|index| Withdrawal {
+            index,
+            validator_index: rng.below(1000),
+            address: rng.address(),
+            amount: rng.below(1_000_000_000),
+        })
+        .collect();
+    BerachainPayloadAttributes {
+        inner: EthPayloadAttributes {
+            timestamp,
+            prev_randao: rng.b256(),
+            suggested_fee_recipient: rng.address(),
+            withdrawals: Some(withdrawals),
+            parent_beacon_block_root: Some(rng.b256()),
+        },
+        prev_proposer_pubkey: chain_spec
+            .is_prague1_active_at_timestamp(timestamp)
+            .then(|| BlsPublicKey::from_slice(&rng.bytes(48))),
+    }
+}
+
+/// bera-geth, reached over JSON-RPC and the engine API.
+struct Geth {
+    rpc: HttpClient,
+    engine: EngineClient,
+}
+
+impl Geth {
+    /// Hands `block` to bera-geth with the `newPayload` version BeaconKit would use.
+    async fn new_payload(
+        &self,
+        chain_spec: &BerachainChainSpec,
+        block: &SealedBlock<BerachainBlock>,
+    ) -> eyre::Result<PayloadStatus> {
+        let header = block.header();
+        let timestamp = header.timestamp();
+        let payload = ExecutionPayloadV3::from_block_unchecked(block.hash(), &block.clone_block());
+        let versioned_hashes: Vec<B256> =
+            block.body().blob_versioned_hashes_iter().copied().collect();
+        let beacon_root = header.parent_beacon_block_root().unwrap_or_default();
+
+        let mut params = ArrayParams::new();
+        params.insert(payload)?;
+        params.insert(versioned_hashes)?;
+        params.insert(beacon_root)?;
+        let method = if chain_spec.is_prague_active_at_timestamp(timestamp) {
+            eyre::ensure!(
+                header.requests_hash() == Some(EMPTY_REQUESTS_HASH),
+                "block {} has execution requests, which cannot be forwarded",
+                header.number()
+            );
+            params.insert(Vec::<Bytes>::new())?;
+            match block.prev_proposer_pubkey {
+                Some(pubkey) => {
+                    params.insert(pubkey)?;
+                    "engine_newPayloadV4P11"
+                }
+                None => "engine_newPayloadV4",
+            }
+        } else {
+            "engine_newPayloadV3"
+        };
+        Ok(self.engine.request(method, params).await?)
+    }
+
+    /// Makes `hash` bera-geth's head.
+    async fn fork_choice_updated(&self, hash: B256) -> eyre::Result<()> {
+        let state = ForkchoiceState {
+            head_block_hash: hash,
+            safe_block_hash: hash,
+            finalized_block_hash: hash,
+        };
+        let _: Value = self
+            .engine
+            .request("engine_forkchoiceUpdatedV3", rpc_params![state, Value::Null])
+            .await?;
+        Ok(())
+    }
+}
+
+/// Disagreement between bera-reth and bera-geth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    /// bera-geth did not accept a block bera-reth built.
+    Rejected {
+        /// Number of the block.
+        number: u64,
+        /// Status returned by bera-geth.
+        status: String,
+    },
+    /// An RPC method returned different JSON.
+    Rpc {
+        /// Number of the block queried.
+        number: u64,
+        /// RPC method.
+        method: &'static str,
+        /// Paths at which the responses differ.
+        diffs: Vec<String>,
+    },
+}
+
+/// Result of a run.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Seed the run can be replayed with.
+    pub seed: u64,
+    /// Blocks accepted by both clients.
+    pub blocks: u64,
+    /// Transactions accepted by bera-reth's pool.
+    pub transactions: u64,
+    /// Transactions refused by bera-reth's pool, e.g. replacements of pending ones.
+    pub refused: u64,
+    /// Disagreements found.
+    pub divergences: Vec<Divergence>,
+}
+
+/// Runs the differential fuzzer.
+pub async fn run(args: DiffFuzzArgs) -> eyre::Result<Report> {
+    let seed = match args.seed {
+        Some(seed) => seed,
+        None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos() as u64,
+    };
+    let mut rng = Rng::new(seed);
+    let chain_spec = args.chain;
+
+    let geth = Geth {
+        rpc: HttpClientBuilder::default().build(&args.geth_http)?,
+        engine: engine_client(&args.geth_authrpc, &args.geth_jwt_secret)?,
+    };
+    let genesis: Value =
+        geth.rpc.request("eth_getBlockByNumber", rpc_params!["0x0", false]).await?;
+    eyre::ensure!(
+        genesis["hash"] == Value::from(chain_spec.genesis_hash().to_string()),
+        "bera-geth runs another genesis: {}",
+        genesis["hash"]
+    );
+    let head: U64 = geth.rpc.request("eth_blockNumber", rpc_params![]).await?;
+    eyre::ensure!(head.is_zero(), "bera-geth must be at genesis, its head is block {head}");
+
+    let mut node = TestNode::spawn_with_chain_spec(chain_spec.clone()).await?;
+    let reth = node.rpc_client();
+    let accounts = args.keys.iter().map(|key| Ok((PrivateKeySigner::from_bytes(key)?, 0)));
+    let mut generator = TxGenerator {
+        chain_id: chain_spec.chain_id(),
+        accounts: accounts.collect::<eyre::Result<_>>()?,
+    };
+    generator.sync_nonces(&reth).await?;
+
+    let prague1 = chain_spec.berachain_fork_activation(BerachainHardfork::Prague1).as_timestamp();
+    let mut report = Report { seed, ..Default::default() };
+    for timestamp in timestamps(chain_spec.genesis_header().timestamp(), prague1, args.blocks) {
+        let base_fee = chain_spec.next_block_base_fee(node.head().header(), timestamp);
+        for _ in 0..args.txs_per_block {
+            let raw = generator.generate(&mut rng, base_fee.unwrap_or_default().into())?;
+            let sent: Result<B256, _> =
+                reth.request("eth_sendRawTransaction", rpc_params![raw]).await;
+            match sent {
+                Ok(_) => report.transactions += 1,
+                Err(_) => report.refused += 1,
+            }
+        }
+
+        let block = node.advance_block_with(attributes(&chain_spec, &mut rng, timestamp)).await?;
+        let number = block.number();
+        let status = geth.new_payload(&chain_spec, &block).await?;
+        if !status.is_valid() {
+            let status = format!("{:?}", status.status);
+            report.divergences.push(Divergence::Rejected { number, status });
+            break
+        }
+        geth.fork_choice_updated(block.hash()).await?;
+        report.blocks += 1;
+
+        let tag = format!("{number:#x}");
+        let queries = [
+            ("eth_getBlockByNumber", rpc_params![&tag, true]),
+            ("eth_getBlockReceipts", rpc_params![&tag]),
+        ];
+        for (method, params) in queries {
+            let from_reth: Value = reth.request(method, params.clone()).await?;
+            let from_geth: Value = geth.rpc.request(method, params).await?;
+            let mut diffs = Vec::new();
+            diff_json("", &from_reth, &from_geth, &args.ignore_fields, &mut diffs);
+            if !diffs.is_empty() {
+                report.divergences.push(Divergence::Rpc { number, method, diffs });
+            }
+        }
+        generator.sync_nonces(&reth).await?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{Transaction, transaction::SignerRecoverable};
+    use alloy_eips::Decodable2718;
+    use serde_json::json;
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+        let values: Vec<_> = (0..8).map(|_| a.next_u64()).collect();
+        assert_eq!(values, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(Rng::new(8).next_u64(), values[0]);
+        assert!((0..1000).all(|_| a.below(5) < 5));
+    }
+
+    #[test]
+    fn test_timestamps_straddle_prague1() {
+        assert_eq!(timestamps(100, None, 4), vec![101, 102, 103, 104]);
+        assert_eq!(timestamps(100, Some(50), 4), vec![101, 102, 103, 104]);
+        assert_eq!(timestamps(100, Some(1000), 4), vec![101, 102, 1000, 1001]);
+        // Prague1 within reach of consecutive blocks
+        assert_eq!(timestamps(100, Some(102), 4), vec![101, 102, 103, 104]);
+        assert_eq!(timestamps(100, Some(1000), 0), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_diff_json() {
+        let reth = json!({ "hash": "0x01", "size": "0x10", "logs": [{ "data": "0x" }], "a": 1 });
+        let geth = json!({ "hash": "0x01", "size": "0x11", "logs": [{ "data": "0x00" }], "b": 1 });
+        let mut diffs = Vec::new();
+        diff_json("", &reth, &geth, &["size".to_string()], &mut diffs);
+        assert_eq!(
+            diffs,
+            vec![
+                ".a: reth 1, geth absent",
+                ".b: reth absent, geth 1",
+                ".logs[0].data: reth \"0x\", geth \"0x00\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generated_transactions_are_signed_by_funded_accounts() {
+        let signer = PrivateKeySigner::from_bytes(&B256::with_last_byte(1)).unwrap();
+        let mut generator = TxGenerator { chain_id: 80069, accounts: vec![(signer.clone(), 5)] };
+        let mut rng = Rng::new(1);
+        for _ in 0..20 {
+            let raw = generator.generate(&mut rng, 1_000_000_000).unwrap();
+            let tx = TxEnvelope::decode_2718(&mut raw.as_ref()).unwrap();
+            assert_eq!(tx.recover_signer(), Ok(signer.address()));
+            assert!(tx.max_fee_per_gas() >= 1_000_000_000);
+        }
+        assert_eq!(generator.accounts[0].1, 25);
+    }
+}
@@ -0,0 +1,312 @@
+//! Runner of execution-spec-tests blockchain fixtures.
+//!
+//! [`BlockchainTest`] is a fixture of the `blockchain_tests` format published by
+//! [execution-spec-tests](https://github.com/ethereum/execution-spec-tests): a genesis, a list of
+//! RLP-encoded blocks, some of which must be rejected, and the hash of the expected head. [`run`]
+//! launches a [`TestNode`] on the fixture's genesis, hands each block to the engine as
+//! `newPayload` followed by a forkchoice update, and compares the resulting head.
+//!
+//! Berachain is not Ethereum, so some fixtures cannot apply: they are skipped with the
+//! [`Exclusion`] explaining why. The chain spec of a fixture keeps Prague1 disabled, so the suites
+//! check the Ethereum behavior Berachain inherits; Prague1 itself (minimum base fee, PoL
+//! transactions and `prevProposerPubkey`) is covered by the unit and e2e tests of this crate.
+
+use crate::{
+    chainspec::BerachainChainSpec, genesis::BerachainGenesisConfig, primitives::BerachainBlock,
+    test_utils::node::TestNode,
+};
+use alloy_genesis::{ChainConfig, Genesis, GenesisAccount};
+use alloy_primitives::{Address, B64, B256, Bytes, U64, U256, address};
+use alloy_rlp::Decodable;
+use reth_chainspec::EthChainSpec;
+use reth_primitives_traits::SealedBlock;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Deposit contract the fixtures of Prague and later expect deposit requests from.
+const DEPOSIT_CONTRACT_ADDRESS: Address = address!("00000000219ab540356cbb839cbe05303d7705fa");
+
+/// Fixture networks supported by Berachain, with the Prague and Osaka activation times.
+const NETWORKS: [(&str, Option<u64>, Option<u64>); 5] = [
+    ("Cancun", None, None),
+    ("CancunToPragueAtTime15k", Some(15_000), None),
+    ("Prague", Some(0), None),
+    ("PragueToOsakaAtTime15k", Some(0), Some(15_000)),
+    ("Osaka", Some(0), Some(0)),
+];
+
+/// Reason a fixture is not run against Berachain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exclusion {
+    /// The fixture runs on a network before Cancun or transitions to a fork up to Cancun, while
+    /// Berachain activates every fork up to Cancun at genesis.
+    PreCancunNetwork,
+    /// The fixture seals blocks with proof of work, while Berachain merges at genesis.
+    ProofOfWork,
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PreCancunNetwork => f.write_str("network before Cancun"),
+            Self::ProofOfWork => f.write_str("proof of work"),
+        }
+    }
+}
+
+/// Header of the genesis block of a fixture.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureHeader {
+    /// Beneficiary.
+    pub coinbase: Address,
+    /// Difficulty.
+    pub difficulty: U256,
+    /// Extra data.
+    pub extra_data: Bytes,
+    /// Gas limit.
+    pub gas_limit: U64,
+    /// Mix hash.
+    pub mix_hash: B256,
+    /// Nonce.
+    pub nonce: B64,
+    /// Timestamp.
+    pub timestamp: U64,
+    /// Base fee.
+    pub base_fee_per_gas: Option<U256>,
+    /// Blob gas used.
+    pub blob_gas_used: Option<U64>,
+    /// Excess blob gas.
+    pub excess_blob_gas: Option<U64>,
+    /// Hash of the header.
+    pub hash: B256,
+}
+
+/// Block of a fixture.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureBlock {
+    /// RLP encoding of the block.
+    pub rlp: Bytes,
+    /// Why the block must be rejected, if it must.
+    #[serde(default)]
+    pub expect_exception: Option<String>,
+}
+
+/// Chain configuration of a fixture.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FixtureConfig {
+    /// Chain id, mainnet's if unset.
+    #[serde(default)]
+    pub chainid: Option<U64>,
+}
+
+/// Fixture of the `blockchain_tests` format.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainTest {
+    /// Name of the fork schedule, e.g. `Prague` or `CancunToPragueAtTime15k`.
+    pub network: String,
+    /// `NoProof` unless blocks are sealed with proof of work.
+    #[serde(default)]
+    pub seal_engine: Option<String>,
+    /// Header of the genesis block.
+    pub genesis_block_header: FixtureHeader,
+    /// Accounts at genesis.
+    pub pre: BTreeMap<Address, GenesisAccount>,
+    /// Blocks to import, in order.
+    pub blocks: Vec<FixtureBlock>,
+    /// Hash of the head once all blocks are imported.
+    pub lastblockhash: B256,
+    /// Chain configuration.
+    #[serde(default)]
+    pub config: FixtureConfig,
+}
+
+impl BlockchainTest {
+    /// Returns why the fixture is not run against Berachain, if it is not.
+    pub fn exclusion(&self) -> Option<Exclusion> {
+        if self.seal_engine.as_deref().is_some_and(|engine| engine != "NoProof") {
+            return Some(Exclusion::ProofOfWork)
+        }
+        if !NETWORKS.iter().any(|(network, ..)| *network == self.network) {
+            return Some(Exclusion::PreCancunNetwork)
+        }
+        None
+    }
+
+    /// Returns the Berachain chain spec of the fixture, with Prague1 disabled.
+    pub fn chain_spec(&self) -> eyre::Result<Arc<BerachainChainSpec>> {
+        let (_, prague_time, osaka_time) = NETWORKS
+            .iter()
+            .find(|(network, ..)| *network == self.network)
+            .copied()
+            .ok_or_else(|| eyre::eyre!("unsupported network {}", self.network))?;
+
+        let mut berachain = BerachainGenesisConfig::default();
+        berachain.prague1.time = u64::MAX;
+        let mut config = ChainConfig {
+            chain_id: self.config.chainid.map_or(1, |id| id.to()),
+            terminal_total_difficulty: Some(U256::ZERO),
+            terminal_total_difficulty_passed: true,
+            shanghai_time: Some(0),
+            cancun_time: Some(0),
+            prague_time,
+            osaka_time,
+            deposit_contract_address: Some(DEPOSIT_CONTRACT_ADDRESS),
+            ..Default::default()
+        };
+        config.extra_fields.insert("berachain".to_string(), serde_json::to_value(berachain)?);
+
+        let header = &self.genesis_block_header;
+        let mut genesis = Genesis::default()
+            .with_nonce(u64::from_be_bytes(header.nonce.0))
+            .with_timestamp(header.timestamp.to())
+            .with_extra_data(header.extra_data.clone())
+            .with_gas_limit(header.gas_limit.to())
+            .with_difficulty(header.difficulty)
+            .with_mix_hash(header.mix_hash)
+            .with_coinbase(header.coinbase)
+            .with_base_fee(header.base_fee_per_gas.map(|fee| fee.saturating_to()))
+            .with_excess_blob_gas(header.excess_blob_gas.map(|gas| gas.to()))
+            .with_blob_gas_used(header.blob_gas_used.map(|gas| gas.to()))
+            .extend_accounts(self.pre.clone());
+        genesis.config = config;
+        Ok(Arc::new(BerachainChainSpec::try_from(genesis)?))
+    }
+}
+
+/// Outcome of a fixture that did not fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Every block was imported or rejected as expected and the head matches.
+    Passed,
+    /// The fixture does not apply to Berachain.
+    Skipped(Exclusion),
+}
+
+/// Runs `test` against a fresh in-process node.
+pub async fn run(test: &BlockchainTest) -> eyre::Result<Outcome> {
+    if let Some(exclusion) = test.exclusion() {
+        return Ok(Outcome::Skipped(exclusion))
+    }
+    let chain_spec = test.chain_spec()?;
+    let genesis = chain_spec.genesis_hash();
+    eyre::ensure!(
+        genesis == test.genesis_block_header.hash,
+        "genesis hash {genesis} differs from the fixture's {}",
+        test.genesis_block_header.hash
+    );
+
+    let mut node = TestNode::spawn_with_chain_spec(chain_spec).await?;
+    for (index, block) in test.blocks.iter().enumerate() {
+        let imported = match BerachainBlock::decode(&mut block.rlp.as_ref()) {
+            Ok(decoded) => node.import_block(SealedBlock::seal_slow(decoded)).await,
+            Err(err) => Err(err.into()),
+        };
+        match (imported, &block.expect_exception) {
+            (Ok(()), None) | (Err(_), Some(_)) => {}
+            (Ok(()), Some(exception)) => {
+                eyre::bail!("block {index} imported, expected {exception}")
+            }
+            (Err(err), None) => return Err(err.wrap_err(format!("block {index}"))),
+        }
+    }
+
+    let head = node.head().hash();
+    eyre::ensure!(head == test.lastblockhash, "head {head}, expected {}", test.lastblockhash);
+    Ok(Outcome::Passed)
+}
+
+/// Reads the fixtures of a fixture file, by test name.
+pub fn load(path: &Path) -> eyre::Result<BTreeMap<String, BlockchainTest>> {
+    let json = std::fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|err| eyre::eyre!("{}: {err}", path.display()))
+}
+
+/// Returns the JSON files under `path`, or `path` itself if it is a file, in sorted order.
+pub fn fixture_files(path: &Path) -> eyre::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()])
+    }
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(fixture_files(&path)?);
+        } else if path.extension().is_some_and(|extension| extension == "json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardforks::BerachainHardforks;
+
+    fn fixture(network: &str) -> BlockchainTest {
+        serde_json::from_value(serde_json::json!({
+            "network": network,
+            "sealEngine": "NoProof",
+            "genesisBlockHeader": {
+                "coinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                "difficulty": "0x00",
+                "extraData": "0x00",
+                "gasLimit": "0x016345785d8a0000",
+                "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "nonce": "0x0000000000000000",
+                "timestamp": "0x00",
+                "baseFeePerGas": "0x07",
+                "blobGasUsed": "0x00",
+                "excessBlobGas": "0x00",
+                "hash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+            },
+            "pre": {
+                "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": {
+                    "nonce": "0x00",
+                    "balance": "0x3635c9adc5dea00000",
+                    "code": "0x",
+                    "storage": { "0x01": "0x02" }
+                }
+            },
+            "blocks": [{ "rlp": "0xc0", "expectException": "RLP_STRUCTURES_ENCODING" }],
+            "lastblockhash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "config": { "chainid": "0x01" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_exclusions() {
+        assert_eq!(fixture("Cancun").exclusion(), None);
+        assert_eq!(fixture("CancunToPragueAtTime15k").exclusion(), None);
+        assert_eq!(fixture("Shanghai").exclusion(), Some(Exclusion::PreCancunNetwork));
+        assert_eq!(
+            fixture("ShanghaiToCancunAtTime15k").exclusion(),
+            Some(Exclusion::PreCancunNetwork)
+        );
+        let mut pow = fixture("Prague");
+        pow.seal_engine = Some("Ethash".to_string());
+        assert_eq!(pow.exclusion(), Some(Exclusion::ProofOfWork));
+    }
+
+    #[test]
+    fn test_chain_spec_keeps_prague1_disabled() {
+        let test = fixture("CancunToPragueAtTime15k");
+        assert_eq!(test.blocks[0].expect_exception.as_deref(), Some("RLP_STRUCTURES_ENCODING"));
+        let chain_spec = test.chain_spec().unwrap();
+        assert_eq!(chain_spec.chain_id(), 1);
+        assert_eq!(chain_spec.genesis_header().base_fee_per_gas, Some(7));
+        assert!(chain_spec.genesis().alloc.values().all(|account| account.storage.is_some()));
+        assert!(!chain_spec.is_prague1_active_at_timestamp(1_000_000));
+        assert!(fixture("Shanghai").chain_spec().is_err());
+    }
+}
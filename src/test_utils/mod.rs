@@ -0,0 +1,9 @@
+//! Shared fixtures for unit tests, integration tests and benchmarks.
+//!
+//! Enabled with the `test-utils` feature; always available under `cfg(test)`.
+
+pub mod compact_corpus;
+pub mod compact_fuzz;
+pub mod diff_fuzz;
+pub mod eest;
+pub mod node;
@@ -0,0 +1,280 @@
+//! In-process Berachain node for integration tests.
+//!
+//! [`TestNode::spawn`] launches a full [`BerachainNode`] on [`BERACHAIN_DEV`] (Prague1 active at
+//! genesis) with a temporary datadir and unused ports. Blocks are produced by driving the engine
+//! the way a consensus client does: a forkchoice update with payload attributes, the built payload
+//! handed back as `newPayload`, and a forkchoice update to the new head. JSON-RPC is served over
+//! HTTP with every namespace enabled, so downstream projects can test against a real node without
+//! docker-compose. Nodes can also be peered with [`TestNode::connect`] and synced from each other
+//! over devp2p with [`TestNode::sync_to`], the way a fresh node catches up with the network.
+//! [`TestNode::send_transfer`] submits a transfer from the [`dev_signer`] to fill blocks:
+//!
+//! ```ignore
+//! let mut node = TestNode::spawn().await?;
+//! node.send_transfer(0, Address::repeat_byte(7)).await?;
+//! node.advance_blocks(3).await?;
+//! let number: U64 = node.rpc_client().request("eth_blockNumber", rpc_params![]).await?;
+//! ```
+
+use crate::{
+    chainspec::{BERACHAIN_DEV, BerachainChainSpec},
+    engine::{
+        BerachainEngineTypes, payload::BerachainPayloadAttributes,
+        validator::BerachainEngineValidatorBuilder,
+    },
+    node::{BerachainNode, config::BerachainConfig},
+    primitives::BerachainBlock,
+    rpc::{BerachainAddOns, BerachainEthApiBuilder},
+    storage::create_tables,
+};
+use alloy_consensus::{BlockHeader, SignableTransaction, TxEip1559, TxEnvelope};
+use alloy_eips::{Encodable2718, eip2930::AccessList};
+use alloy_network::TxSignerSync;
+use alloy_primitives::{Address, B256, Bytes, TxKind, U256, b256};
+use alloy_rpc_types::engine::ForkchoiceState;
+use alloy_signer_local::PrivateKeySigner;
+use jsonrpsee_core::{client::ClientT, rpc_params};
+use jsonrpsee_http_client::HttpClient;
+use reth::{
+    payload::PayloadBuilderHandle, rpc::server_types::RpcModuleSelection, tasks::TaskManager,
+};
+use reth_chainspec::EthChainSpec;
+use reth_db::{DatabaseEnv, test_utils::TempDatabase};
+use reth_engine_local::LocalPayloadAttributesBuilder;
+use reth_engine_primitives::BeaconConsensusEngineHandle;
+use reth_network::{Peers, PeersInfo};
+use reth_node_builder::{FullNode, NodeAdapter, NodeBuilder, NodeHandle, RethFullAdapter};
+use reth_node_core::{args::RpcServerArgs, node_config::NodeConfig};
+use reth_payload_primitives::{
+    EngineApiMessageVersion, PayloadAttributesBuilder, PayloadKind, PayloadTypes,
+};
+use reth_primitives_traits::SealedBlock;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Private key of the first account funded by the [`BERACHAIN_DEV`] genesis.
+pub const DEV_PRIVATE_KEY: B256 =
+    b256!("0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80");
+
+/// Returns the signer of the first account funded by the [`BERACHAIN_DEV`] genesis.
+pub fn dev_signer() -> PrivateKeySigner {
+    PrivateKeySigner::from_bytes(&DEV_PRIVATE_KEY).expect("valid private key")
+}
+
+/// Returns the encoded EIP-1559 transfer of 1 wei to `to`, signed by `signer` with `nonce`.
+pub fn signed_transfer(
+    signer: &PrivateKeySigner,
+    chain_id: u64,
+    nonce: u64,
+    to: Address,
+) -> eyre::Result<Bytes> {
+    let mut tx = TxEip1559 {
+        chain_id,
+        nonce,
+        gas_limit: 21_000,
+        max_fee_per_gas: 10_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        to: TxKind::Call(to),
+        value: U256::from(1),
+        access_list: AccessList::default(),
+        input: Bytes::new(),
+    };
+    let signature = signer.sign_transaction_sync(&mut tx)?;
+    Ok(TxEnvelope::from(tx.into_signed(signature)).encoded_2718().into())
+}
+
+/// Database of a [`TestNode`], removed with its datadir when dropped.
+pub type TestDatabase = Arc<TempDatabase<DatabaseEnv>>;
+
+/// Components of a [`TestNode`].
+pub type TestNodeAdapter = NodeAdapter<RethFullAdapter<TestDatabase, BerachainNode>>;
+
+/// Launched node of a [`TestNode`].
+pub type TestFullNode = FullNode<
+    TestNodeAdapter,
+    BerachainAddOns<TestNodeAdapter, BerachainEthApiBuilder, BerachainEngineValidatorBuilder>,
+>;
+
+/// Berachain node running in the test process.
+pub struct TestNode {
+    /// The launched node.
+    pub node: TestFullNode,
+    engine: BeaconConsensusEngineHandle<BerachainEngineTypes>,
+    payload_builder: PayloadBuilderHandle<BerachainEngineTypes>,
+    attributes: LocalPayloadAttributesBuilder<BerachainChainSpec>,
+    head: SealedBlock<BerachainBlock>,
+    // shuts the node down when dropped
+    _tasks: TaskManager,
+}
+
+impl TestNode {
+    /// Launches a node on [`BERACHAIN_DEV`].
+    pub async fn spawn() -> eyre::Result<Self> {
+        Self::spawn_with_chain_spec(BERACHAIN_DEV.clone()).await
+    }
+
+    /// Launches a node on `chain_spec`.
+    pub async fn spawn_with_chain_spec(chain_spec: Arc<BerachainChainSpec>) -> eyre::Result<Self> {
+        Self::spawn_with_config(chain_spec, BerachainConfig::default()).await
+    }
+
+    /// Launches a node on `chain_spec` with the Berachain settings of `berachain`.
+    pub async fn spawn_with_config(
+        chain_spec: Arc<BerachainChainSpec>,
+        berachain: BerachainConfig,
+    ) -> eyre::Result<Self> {
+        let tasks = TaskManager::current();
+        let rpc = RpcServerArgs::default()
+            .with_unused_ports()
+            .with_http()
+            .with_http_api(RpcModuleSelection::All);
+        let config = NodeConfig::new(chain_spec.clone()).with_unused_ports().with_rpc(rpc);
+        let builder = NodeBuilder::new(config).testing_node(tasks.executor());
+        create_tables(builder.db().db())?;
+        let NodeHandle { node, node_exit_future: _ } =
+            builder.node(BerachainNode::new(berachain)).launch().await?;
+
+        let genesis = chain_spec.sealed_genesis_header();
+        Ok(Self {
+            engine: node.add_ons_handle.beacon_engine_handle.clone(),
+            payload_builder: node.payload_builder_handle.clone(),
+            attributes: LocalPayloadAttributesBuilder::new(chain_spec),
+            head: SealedBlock::from_sealed_parts(genesis, Default::default()),
+            node,
+            _tasks: tasks,
+        })
+    }
+
+    /// Returns a client of the node's HTTP JSON-RPC server.
+    pub fn rpc_client(&self) -> HttpClient {
+        self.node.rpc_server_handle().http_client().expect("HTTP server is enabled")
+    }
+
+    /// Returns the URL of the node's HTTP JSON-RPC server.
+    pub fn rpc_url(&self) -> String {
+        self.node.rpc_server_handle().http_url().expect("HTTP server is enabled")
+    }
+
+    /// Sends a [`signed_transfer`] of the [`dev_signer`] with `nonce` to `to` over JSON-RPC and
+    /// returns its hash.
+    pub async fn send_transfer(&self, nonce: u64, to: Address) -> eyre::Result<B256> {
+        let raw = signed_transfer(&dev_signer(), self.node.chain_spec().chain_id(), nonce, to)?;
+        Ok(self.rpc_client().request("eth_sendRawTransaction", rpc_params![raw]).await?)
+    }
+
+    /// Returns the canonical head produced through this harness.
+    pub const fn head(&self) -> &SealedBlock<BerachainBlock> {
+        &self.head
+    }
+
+    /// Builds a block on the head with default payload attributes and makes it canonical.
+    pub async fn advance_block(&mut self) -> eyre::Result<SealedBlock<BerachainBlock>> {
+        let attributes = self.attributes.build(self.head.timestamp() + 1);
+        self.advance_block_with(attributes).await
+    }
+
+    /// Builds `count` blocks on the head and makes them canonical.
+    pub async fn advance_blocks(&mut self, count: usize) -> eyre::Result<()> {
+        for _ in 0..count {
+            self.advance_block().await?;
+        }
+        Ok(())
+    }
+
+    /// Builds a block on the head with `attributes` and makes it canonical.
+    pub async fn advance_block_with(
+        &mut self,
+        attributes: BerachainPayloadAttributes,
+    ) -> eyre::Result<SealedBlock<BerachainBlock>> {
+        let updated = self
+            .engine
+            .fork_choice_updated(self.forkchoice(), Some(attributes), EngineApiMessageVersion::V3)
+            .await?;
+        let payload_id = updated
+            .payload_id
+            .ok_or_else(|| eyre::eyre!("no payload started: {:?}", updated.payload_status))?;
+        let payload = self
+            .payload_builder
+            .resolve_kind(payload_id, PayloadKind::WaitForPending)
+            .await
+            .ok_or_else(|| eyre::eyre!("payload {payload_id} not found"))??;
+        let block = (*payload.block).clone();
+        self.import_block(block.clone()).await?;
+        Ok(block)
+    }
+
+    /// Hands `block` to the engine as `newPayload` and makes it canonical.
+    ///
+    /// Fails if the engine rejects the block, in which case the head is unchanged.
+    pub async fn import_block(&mut self, block: SealedBlock<BerachainBlock>) -> eyre::Result<()> {
+        let number = block.number();
+        let status =
+            self.engine.new_payload(BerachainEngineTypes::block_to_payload(block.clone())).await?;
+        eyre::ensure!(status.is_valid(), "block {number} rejected: {status:?}");
+        self.head = block;
+        let updated = self
+            .engine
+            .fork_choice_updated(self.forkchoice(), None, EngineApiMessageVersion::V3)
+            .await?;
+        eyre::ensure!(
+            updated.is_valid(),
+            "block {number} not made canonical: {:?}",
+            updated.payload_status
+        );
+        Ok(())
+    }
+
+    /// Adds `peer` to the peers of the node.
+    pub fn connect(&self, peer: &Self) {
+        let record = peer.node.network.local_node_record();
+        self.node.network.add_peer(record.id, record.tcp_addr());
+    }
+
+    /// Points the engine at `block`, known to a connected peer, and waits until the node has
+    /// downloaded and executed the chain up to it, at most `timeout`.
+    ///
+    /// The engine answers `SYNCING` while headers and bodies are downloaded by the pipeline, so
+    /// the forkchoice update is repeated until it turns valid.
+    pub async fn sync_to(
+        &mut self,
+        block: SealedBlock<BerachainBlock>,
+        timeout: Duration,
+    ) -> eyre::Result<()> {
+        let hash = block.hash();
+        let state = ForkchoiceState {
+            head_block_hash: hash,
+            safe_block_hash: hash,
+            finalized_block_hash: hash,
+        };
+        let deadline = Instant::now() + timeout;
+        loop {
+            let updated =
+                self.engine.fork_choice_updated(state, None, EngineApiMessageVersion::V3).await?;
+            if updated.is_valid() {
+                self.head = block;
+                return Ok(())
+            }
+            eyre::ensure!(
+                !updated.is_invalid(),
+                "block {} rejected: {:?}",
+                block.number(),
+                updated.payload_status
+            );
+            eyre::ensure!(
+                Instant::now() < deadline,
+                "block {} not synced in {timeout:?}: {:?}",
+                block.number(),
+                updated.payload_status
+            );
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Returns the forkchoice state with the head as head, safe and finalized block.
+    fn forkchoice(&self) -> ForkchoiceState {
+        let head: B256 = self.head.hash();
+        ForkchoiceState { head_block_hash: head, safe_block_hash: head, finalized_block_hash: head }
+    }
+}
@@ -520,10 +520,11 @@ impl reth_codecs::Compact for BerachainTxEnvelope {
 }
 
 impl FromRecoveredTx<PoLTx> for TxEnv {
-    fn from_recovered_tx(tx: &PoLTx, caller: Address) -> Self {
+    /// PoL transactions are system calls, so the caller is always `SYSTEM_ADDRESS`.
+    fn from_recovered_tx(tx: &PoLTx, _caller: Address) -> Self {
         Self {
             tx_type: tx.ty(),
-            caller,
+            caller: SYSTEM_ADDRESS,
             gas_limit: tx.gas_limit(),
             gas_price: tx.gas_price().unwrap_or_default(),
             kind: tx.kind(),
@@ -670,20 +671,16 @@ impl SignableTxRequest<BerachainTxEnvelope> for TransactionRequest {
     }
 }
 
-impl From<BerachainTxEnvelope> for EthereumTxEnvelope<alloy_consensus::TxEip4844Variant> {
-    fn from(berachain_tx: BerachainTxEnvelope) -> Self {
+impl TryFrom<BerachainTxEnvelope> for EthereumTxEnvelope<alloy_consensus::TxEip4844Variant> {
+    type Error = TxConversionError;
+
+    fn try_from(berachain_tx: BerachainTxEnvelope) -> Result<Self, Self::Error> {
         match berachain_tx {
-            BerachainTxEnvelope::Ethereum(tx) => match tx {
-                TxEnvelope::Legacy(tx) => EthereumTxEnvelope::Legacy(tx),
-                TxEnvelope::Eip2930(tx) => EthereumTxEnvelope::Eip2930(tx),
-                TxEnvelope::Eip1559(tx) => EthereumTxEnvelope::Eip1559(tx),
-                TxEnvelope::Eip4844(tx) => EthereumTxEnvelope::Eip4844(tx),
-                TxEnvelope::Eip7702(tx) => EthereumTxEnvelope::Eip7702(tx),
-            },
+            BerachainTxEnvelope::Ethereum(tx) => Ok(tx),
+            // PoL transactions have no Ethereum counterpart, callers keep them as Berachain
+            // envelopes instead
             BerachainTxEnvelope::Berachain(_) => {
-                // For now, we can't convert PoL transactions to Ethereum format
-                // This should be handled at a higher level
-                panic!("Cannot convert Berachain PoL transaction to Ethereum format")
+                Err(TxConversionError::UnsupportedBerachainTransaction)
             }
         }
     }
@@ -692,7 +689,7 @@ impl From<BerachainTxEnvelope> for EthereumTxEnvelope<alloy_consensus::TxEip4844
 #[cfg(test)]
 mod compact_envelope_tests {
     use super::*;
-    use alloy_consensus::{TxEip1559, TxEip2930, TxEip4844, TxEip7702, TxLegacy};
+    use alloy_consensus::{TxEip1559, TxEip2930, TxEip4844, TxEip4844Variant, TxEip7702, TxLegacy};
     use alloy_eips::{eip2930::AccessList, eip4844::Bytes48};
     use alloy_primitives::{Address, B256, Bytes, ChainId, TxKind, U256};
     use reth_codecs::alloy::transaction::CompactEnvelope;
@@ -1105,6 +1102,24 @@ mod compact_envelope_tests {
         assert_eq!(eth_len, bera_len, "Serialized length must be identical");
     }
 
+    #[test]
+    fn test_try_into_ethereum_envelope() {
+        let EthereumTxEnvelope::Legacy(signed) = create_legacy_envelope() else {
+            panic!("Expected legacy")
+        };
+        let envelope = BerachainTxEnvelope::Ethereum(TxEnvelope::Legacy(signed.clone()));
+        assert_eq!(
+            EthereumTxEnvelope::<TxEip4844Variant>::try_from(envelope),
+            Ok(EthereumTxEnvelope::Legacy(signed))
+        );
+
+        let pol = BerachainTxEnvelope::Berachain(Sealed::new(create_test_pol_tx()));
+        assert_eq!(
+            EthereumTxEnvelope::<TxEip4844Variant>::try_from(pol),
+            Err(TxConversionError::UnsupportedBerachainTransaction)
+        );
+    }
+
     // Helper functions to create test envelopes
     fn create_legacy_envelope() -> EthereumTxEnvelope<TxEip4844> {
         let tx = TxLegacy {
@@ -11,19 +11,29 @@ use reth_chainspec::EthChainSpec;
 use reth_evm::block::{BlockExecutionError, InternalBlockExecutionError};
 use std::sync::Arc;
 
+/// Default gas limit of the PoL transaction. Chains can set another with `polTxGasLimit` in the
+/// `berachain.prague1` genesis section.
 pub const POL_TX_GAS_LIMIT: u64 = 30_000_000;
 
+sol! {
+    interface PoLDistributor {
+        function distributeFor(bytes calldata pubkey) external;
+    }
+}
+
+/// Extracts the proposer pubkey from `distributeFor` calldata, if well formed.
+pub fn decode_pol_pubkey(input: &[u8]) -> Option<BlsPublicKey> {
+    let call = PoLDistributor::distributeForCall::abi_decode(input).ok()?;
+    (call.pubkey.len() == BlsPublicKey::len_bytes()).then(|| BlsPublicKey::from_slice(&call.pubkey))
+}
+
 pub fn create_pol_transaction(
     chain_spec: Arc<BerachainChainSpec>,
     prev_proposer_pubkey: BlsPublicKey,
     block_number: U256,
+    timestamp: u64,
     base_fee: u64,
 ) -> Result<BerachainTxEnvelope, BlockExecutionError> {
-    sol! {
-        interface PoLDistributor {
-            function distributeFor(bytes calldata pubkey) external;
-        }
-    }
     let distribute_call =
         PoLDistributor::distributeForCall { pubkey: Bytes::from(prev_proposer_pubkey) };
     let calldata = distribute_call.abi_encode();
@@ -41,12 +51,12 @@ pub fn create_pol_transaction(
     let pol_tx = PoLTx {
         chain_id: chain_spec.chain_id(),
         from: SYSTEM_ADDRESS,
-        to: chain_spec.pol_contract(),
+        to: chain_spec.pol_contract_at_timestamp(timestamp),
         input: Bytes::from(calldata),
         nonce,
-        gas_limit: POL_TX_GAS_LIMIT, // this is the env value used in revm for system calls
-        gas_price: base_fee.into(),  /* gas price is set to the base fee for RPC
-                                      * compatability reasons */
+        gas_limit: chain_spec.pol_tx_gas_limit_at_timestamp(timestamp),
+        gas_price: base_fee.into(), /* gas price is set to the base fee for RPC
+                                     * compatability reasons */
     };
 
     Ok(BerachainTxEnvelope::Berachain(Sealed::new(pol_tx)))
@@ -57,12 +67,14 @@ pub fn validate_pol_transaction(
     chain_spec: Arc<BerachainChainSpec>,
     expected_pubkey: BlsPublicKey,
     block_number: U256,
+    timestamp: u64,
     base_fee: u64,
 ) -> Result<(), ConsensusError> {
-    let expected_tx = create_pol_transaction(chain_spec, expected_pubkey, block_number, base_fee)
-        .map_err(|e| {
-        ConsensusError::Other(format!("Failed to create expected PoL transaction: {e}"))
-    })?;
+    let expected_tx =
+        create_pol_transaction(chain_spec, expected_pubkey, block_number, timestamp, base_fee)
+            .map_err(|e| {
+                ConsensusError::Other(format!("Failed to create expected PoL transaction: {e}"))
+            })?;
 
     let expected_sealed_pol_tx = match expected_tx {
         BerachainTxEnvelope::Berachain(sealed_tx) => sealed_tx,
@@ -0,0 +1,116 @@
+//! Wire-format compatibility with BeaconKit
+//!
+//! Every file in `tests/fixtures/beacon-kit` is a JSON-RPC message exchanged between BeaconKit and
+//! the execution client. Each test parses the message into the types bera-reth uses on that
+//! method and checks that serializing them again gives back the same JSON, so a renamed field or a
+//! changed encoding on either side fails here instead of on a live network.
+//!
+//! Run with: `cargo test --test beacon_kit_fixtures`
+
+use alloy_eips::eip7685::RequestsOrHash;
+use alloy_genesis::Genesis;
+use alloy_primitives::{B256, U256};
+use alloy_rpc_types::engine::{
+    ExecutionPayloadEnvelopeV4, ExecutionPayloadV3, ForkchoiceState, ForkchoiceUpdated,
+};
+use alloy_rpc_types_eth::{Block, Header, Transaction};
+use bera_reth::{
+    chainspec::BerachainChainSpec,
+    cli::test_genesis,
+    engine::payload::BerachainPayloadAttributes,
+    primitives::{BerachainHeader, header::BlsPublicKey},
+    rpc::api::BerachainHeaderResponse,
+    transaction::BerachainTxEnvelope,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Reads the fixture `name`.
+fn fixture(name: &str) -> Value {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/beacon-kit").join(name);
+    let contents = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()));
+    serde_json::from_str(&contents).unwrap()
+}
+
+/// Parses `value` as `T` and checks that it serializes back to the same JSON.
+fn round_trip<T: Serialize + DeserializeOwned>(value: &Value) -> T {
+    let parsed: T = serde_json::from_value(value.clone())
+        .unwrap_or_else(|err| panic!("failed to parse {value}: {err}"));
+    assert_eq!(&serde_json::to_value(&parsed).unwrap(), value, "re-serialized JSON differs");
+    parsed
+}
+
+#[test]
+fn test_forkchoice_updated_v3_p11() {
+    let request = fixture("engine_forkchoiceUpdatedV3P11.json");
+    let (state, attributes): (ForkchoiceState, BerachainPayloadAttributes) =
+        round_trip(&request["params"]);
+    assert_eq!(state.head_block_hash, state.finalized_block_hash);
+    assert!(attributes.prev_proposer_pubkey.is_some());
+    assert_eq!(attributes.inner.withdrawals.map(|withdrawals| withdrawals.len()), Some(1));
+
+    let response = fixture("engine_forkchoiceUpdatedV3P11_response.json");
+    let updated: ForkchoiceUpdated = round_trip(&response["result"]);
+    assert!(updated.is_valid());
+    assert!(updated.payload_id.is_some());
+}
+
+#[test]
+fn test_new_payload_v3() {
+    let request = fixture("engine_newPayloadV3.json");
+    let (payload, versioned_hashes, _root): (ExecutionPayloadV3, Vec<B256>, B256) =
+        round_trip(&request["params"]);
+    assert_eq!(payload.payload_inner.payload_inner.block_number, 42);
+    assert_eq!(payload.blob_gas_used, 0x20000);
+    assert_eq!(versioned_hashes.len(), 1);
+}
+
+#[test]
+fn test_new_payload_v4_p11() {
+    let request = fixture("engine_newPayloadV4P11.json");
+    let (payload, versioned_hashes, _root, requests, pubkey): (
+        ExecutionPayloadV3,
+        Vec<B256>,
+        B256,
+        RequestsOrHash,
+        BlsPublicKey,
+    ) = round_trip(&request["params"]);
+    assert_eq!(payload.payload_inner.withdrawals.len(), 1);
+    assert!(versioned_hashes.is_empty());
+    assert!(matches!(requests, RequestsOrHash::Requests(requests) if requests.is_empty()));
+    assert_ne!(pubkey, BlsPublicKey::ZERO);
+}
+
+#[test]
+fn test_get_payload_v4_p11() {
+    let response = fixture("engine_getPayloadV4P11_response.json");
+    let envelope: ExecutionPayloadEnvelopeV4 = round_trip(&response["result"]);
+    let payload = &envelope.envelope_inner.execution_payload.payload_inner.payload_inner;
+    assert_eq!(payload.base_fee_per_gas, U256::from(10_000_000_000u64));
+    assert!(envelope.execution_requests.is_empty());
+}
+
+#[test]
+fn test_get_block_by_number() {
+    let response = fixture("eth_getBlockByNumber_response.json");
+    let block: Block<Transaction<BerachainTxEnvelope>, Header<BerachainHeader>> =
+        round_trip(&response["result"]);
+    assert_eq!(block.header.number, 42);
+    assert!(block.header.parent_proposer_pubkey().is_some());
+    assert_eq!(block.transactions.len(), 1);
+}
+
+#[test]
+fn test_devnet_genesis() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/eth-genesis.json");
+    let contents = std::fs::read_to_string(path).unwrap();
+    let round_trip = test_genesis::round_trip(&contents).unwrap();
+    assert_eq!(round_trip.chain_id, 80087);
+
+    let genesis: Genesis = serde_json::from_str(&contents).unwrap();
+    let chain_spec = BerachainChainSpec::try_from(genesis).unwrap();
+    assert_eq!(chain_spec.prague1_minimum_base_fee, 10_000_000_000);
+}
@@ -17,6 +17,7 @@ use reth_ethereum_engine_primitives::EthPayloadAttributes;
 use reth_payload_primitives::PayloadBuilderAttributes;
 use std::{str::FromStr, sync::Arc};
 
+pub mod provider_tests;
 pub mod transaction_tests;
 
 const TEST_PRIVATE_KEY: &str = "0xfffdbb37105441e14b0ee6330d855d8504ff39e705c3afa8f859ac9865f99306";
@@ -28,7 +29,7 @@ pub async fn setup_test_boilerplate() -> eyre::Result<(TaskManager, Arc<Berachai
     let genesis_path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/eth-genesis.json");
     let genesis_json = std::fs::read_to_string(genesis_path).expect("Failed to read genesis file");
     let genesis = parse_genesis(&genesis_json).expect("Failed to parse genesis");
-    let chain_spec = Arc::new(BerachainChainSpec::from(genesis));
+    let chain_spec = Arc::new(BerachainChainSpec::try_from(genesis)?);
 
     Ok((tasks, chain_spec))
 }
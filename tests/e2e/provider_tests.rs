@@ -0,0 +1,102 @@
+//! Provider consistency tests: Berachain blocks must read back identically from the in-memory
+//! canonical state and from the database once persisted.
+
+use crate::e2e::{berachain_payload_attributes, setup_test_boilerplate};
+use bera_reth::{
+    node::BerachainNode,
+    transaction::{BerachainTxEnvelope, BerachainTxType},
+};
+use reth::providers::{
+    BlockNumReader, BlockReader, DatabaseProviderFactory, HeaderProvider, ReceiptProvider,
+};
+use reth_e2e_test_utils::node::NodeTestContext;
+use reth_node_builder::{NodeBuilder, NodeHandle};
+use reth_node_core::{args::RpcServerArgs, node_config::NodeConfig};
+use std::time::Duration;
+
+/// Upper bound for the engine to flush in-memory blocks to the database.
+const PERSISTENCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Blocks built on top of the checked block, enough to push it past the persistence threshold.
+const BLOCKS_UNTIL_PERSISTED: usize = 4;
+
+async fn wait_for_persistence<P>(provider: &P, block: u64) -> eyre::Result<()>
+where
+    P: DatabaseProviderFactory<Provider: BlockNumReader>,
+{
+    tokio::time::timeout(PERSISTENCE_TIMEOUT, async {
+        while provider.database_provider_ro()?.last_block_number()? < block {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Ok::<_, eyre::Report>(())
+    })
+    .await?
+}
+
+#[tokio::test]
+async fn test_in_memory_block_matches_persisted_block() -> eyre::Result<()> {
+    let (tasks, chain_spec) = setup_test_boilerplate().await?;
+    let executor = tasks.executor();
+
+    let node_config = NodeConfig::new(chain_spec.clone())
+        .with_unused_ports()
+        .with_rpc(RpcServerArgs::default().with_unused_ports().with_http());
+
+    let NodeHandle { node, node_exit_future: _ } = NodeBuilder::new(node_config)
+        .testing_node(executor.clone())
+        .node(BerachainNode::default())
+        .launch()
+        .await?;
+
+    let mut ctx = NodeTestContext::new(node, berachain_payload_attributes).await?;
+    let provider = ctx.inner.provider.clone();
+
+    let payload = ctx.advance_block().await?;
+    let block = payload.block().clone();
+    let number = block.number;
+    let pol_hash = *block.body().transactions[0].hash();
+
+    // Freshly built blocks are served from the canonical in-memory state
+    let in_memory = provider
+        .canonical_in_memory_state()
+        .state_by_number(number)
+        .expect("New block should be held in memory before persistence");
+    let in_memory_header = in_memory.block_ref().recovered_block().clone_sealed_header();
+    let in_memory_receipts = in_memory.executed_block_receipts();
+
+    assert_eq!(in_memory_header.hash(), block.hash());
+    assert!(in_memory_header.prev_proposer_pubkey.is_some());
+    assert_eq!(in_memory_receipts[0].tx_type, BerachainTxType::Berachain);
+
+    let overlay_receipt =
+        provider.receipt_by_hash(pol_hash)?.expect("PoL receipt should be served from memory");
+    assert_eq!(overlay_receipt, in_memory_receipts[0]);
+
+    for _ in 0..BLOCKS_UNTIL_PERSISTED {
+        ctx.advance_block().await?;
+    }
+    wait_for_persistence(&provider, number).await?;
+
+    let db = provider.database_provider_ro()?;
+
+    // Header: the Compact encoding must preserve every hashed field, including the pubkey
+    let db_header = db.sealed_header(number)?.expect("Persisted header should exist");
+    assert_eq!(db_header.hash(), block.hash());
+    assert_eq!(db_header.header(), block.header());
+    assert_eq!(db_header.header().hash_slow(), block.hash(), "Decoded header should rehash");
+
+    // Body: the PoL transaction keeps its position and hash
+    let db_block = db.block(number.into())?.expect("Persisted block should exist");
+    assert!(matches!(db_block.body.transactions[0], BerachainTxEnvelope::Berachain(_)));
+    let db_tx_hashes: Vec<_> = db_block.body.transactions.iter().map(|tx| *tx.hash()).collect();
+    let tx_hashes: Vec<_> = block.body().transactions.iter().map(|tx| *tx.hash()).collect();
+    assert_eq!(db_tx_hashes, tx_hashes);
+
+    // Receipts: 0x7E receipts read back from the database exactly as they were held in memory
+    let db_receipts =
+        db.receipts_by_block(number.into())?.expect("Persisted receipts should exist");
+    assert_eq!(db_receipts, in_memory_receipts);
+    assert_eq!(provider.receipt_by_hash(pol_hash)?, Some(overlay_receipt));
+
+    Ok(())
+}
@@ -260,3 +260,43 @@ async fn test_pol_transaction_auto_inclusion() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_pol_transaction_is_indexed_by_hash() -> eyre::Result<()> {
+    use reth::providers::TransactionsProvider;
+
+    let (tasks, chain_spec) = setup_test_boilerplate().await?;
+    let executor = tasks.executor();
+
+    let node_config = NodeConfig::new(chain_spec.clone())
+        .with_unused_ports()
+        .with_rpc(RpcServerArgs::default().with_unused_ports().with_http());
+
+    let NodeHandle { node, node_exit_future: _ } = NodeBuilder::new(node_config)
+        .testing_node(executor.clone())
+        .node(BerachainNode::default())
+        .launch()
+        .await?;
+
+    let mut ctx = NodeTestContext::new(node, berachain_payload_attributes).await?;
+
+    let payload = ctx.advance_block().await?;
+    let block = payload.block();
+    let pol_hash = *block.body().transactions[0].hash();
+
+    // PoL transactions bypass the pool, so block insertion is the only place that indexes them
+    let (pol_tx, meta) = ctx
+        .rpc
+        .inner
+        .eth_api()
+        .provider()
+        .transaction_by_hash_with_meta(pol_hash)?
+        .expect("PoL transaction should be resolvable by hash");
+
+    assert!(matches!(pol_tx, BerachainTxEnvelope::Berachain(_)));
+    assert_eq!(meta.index, 0, "PoL transaction should be indexed at position 0");
+    assert_eq!(meta.block_number, block.number);
+    assert_eq!(meta.block_hash, block.hash());
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! execution-spec-tests blockchain fixtures run against bera-reth
+//!
+//! Run with:
+//! `EEST_FIXTURES=fixtures/blockchain_tests cargo test --features test-utils --test eest`
+//!
+//! `EEST_FIXTURES` is a fixture file or a directory searched for fixture files, e.g. the
+//! `blockchain_tests` directory of an execution-spec-tests release. `EEST_FILTER` restricts the
+//! run to tests whose name contains it. Without `EEST_FIXTURES` the test does nothing.
+
+use bera_reth::test_utils::eest::{self, Outcome};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn blockchain_tests() -> eyre::Result<()> {
+    let Some(fixtures) = std::env::var_os("EEST_FIXTURES").map(PathBuf::from) else {
+        eprintln!("EEST_FIXTURES is not set, skipping");
+        return Ok(())
+    };
+    let filter = std::env::var("EEST_FILTER").unwrap_or_default();
+
+    let (mut passed, mut skipped, mut failed) = (0, 0, Vec::new());
+    for file in eest::fixture_files(&fixtures)? {
+        for (name, test) in eest::load(&file)? {
+            if !name.contains(&filter) {
+                continue
+            }
+            match eest::run(&test).await {
+                Ok(Outcome::Passed) => passed += 1,
+                Ok(Outcome::Skipped(_)) => skipped += 1,
+                Err(err) => failed.push(format!("{name}: {err:#}")),
+            }
+        }
+    }
+
+    println!("{passed} passed, {skipped} skipped, {} failed", failed.len());
+    for failure in &failed {
+        println!("FAILED {failure}");
+    }
+    eyre::ensure!(failed.is_empty(), "{} fixtures failed", failed.len());
+    Ok(())
+}
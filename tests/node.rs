@@ -0,0 +1,182 @@
+//! Full-node tests against an in-process [`TestNode`]
+//!
+//! Run with: `cargo test --features test-utils --test node`
+
+use alloy_consensus::BlockHeader;
+use alloy_genesis::GenesisAccount;
+use alloy_primitives::{Address, B256, Bloom, BloomInput, Bytes, U64};
+use bera_reth::{
+    chainspec::{BERACHAIN_DEV, BerachainChainSpec},
+    test_utils::node::{TestNode, dev_signer},
+    transaction::BerachainTxEnvelope,
+};
+use jsonrpsee_core::{client::ClientT, rpc_params};
+use reth::{
+    providers::{BlockReader, HeaderProvider},
+    revm::handler::SYSTEM_ADDRESS,
+};
+use reth_chainspec::EthChainSpec;
+use serde_json::{Value, json};
+use std::{sync::Arc, time::Duration};
+
+#[tokio::test]
+async fn test_advance_blocks_through_engine() -> eyre::Result<()> {
+    let mut node = TestNode::spawn().await?;
+    node.advance_blocks(2).await?;
+    assert_eq!(node.head().number(), 2);
+    assert!(node.head().prev_proposer_pubkey.is_some());
+
+    let number: U64 = node.rpc_client().request("eth_blockNumber", rpc_params![]).await?;
+    assert_eq!(number, U64::from(2));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fresh_node_syncs_pol_blocks_from_peer() -> eyre::Result<()> {
+    let mut source = TestNode::spawn().await?;
+    source.advance_blocks(5).await?;
+
+    let mut fresh = TestNode::spawn().await?;
+    fresh.connect(&source);
+    fresh.sync_to(source.head().clone(), Duration::from_secs(60)).await?;
+
+    for number in 1..=5 {
+        let expected = source.node.provider.sealed_header(number)?.unwrap();
+        let synced = fresh.node.provider.sealed_header(number)?.unwrap();
+        assert_eq!(synced.hash(), expected.hash());
+        assert_eq!(synced.prev_proposer_pubkey, expected.prev_proposer_pubkey);
+
+        let block = fresh.node.provider.block_by_number(number)?.unwrap();
+        assert!(matches!(block.body.transactions[0], BerachainTxEnvelope::Berachain(_)));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pol_receipt_of_built_block() -> eyre::Result<()> {
+    let mut node = TestNode::spawn().await?;
+    let client = node.rpc_client();
+    node.send_transfer(0, Address::repeat_byte(7)).await?;
+    node.advance_block().await?;
+
+    let receipts: Vec<Value> = client.request("eth_getBlockReceipts", rpc_params!["0x1"]).await?;
+    assert_eq!(receipts.len(), 2);
+    let pol = &receipts[0];
+    assert_eq!(pol["type"], json!("0x7e"));
+    assert_eq!(pol["transactionIndex"], json!("0x0"));
+    assert_eq!(pol["from"], json!(SYSTEM_ADDRESS));
+    assert_eq!(pol["gasUsed"], json!("0x0"));
+    assert_eq!(pol["cumulativeGasUsed"], json!("0x0"));
+
+    // The user transaction starts from the gas used by the PoL transaction
+    let user = &receipts[1];
+    assert_eq!(user["transactionIndex"], json!("0x1"));
+    assert_eq!(user["gasUsed"], json!("0x5208"));
+    assert_eq!(user["cumulativeGasUsed"], json!("0x5208"));
+
+    let by_hash: Value = client
+        .request("eth_getTransactionReceipt", rpc_params![pol["transactionHash"].clone()])
+        .await?;
+    assert_eq!(&by_hash, pol);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_trace_block_with_pol_transaction() -> eyre::Result<()> {
+    let mut node = TestNode::spawn().await?;
+    let client = node.rpc_client();
+    let user_hash = node.send_transfer(0, Address::repeat_byte(7)).await?;
+    node.advance_block().await?;
+
+    let call_tracer = json!({ "tracer": "callTracer" });
+    let traces: Vec<Value> =
+        client.request("debug_traceBlockByNumber", rpc_params!["0x1", call_tracer.clone()]).await?;
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces[0]["result"]["from"], json!(SYSTEM_ADDRESS));
+    assert_eq!(traces[0]["result"]["to"], json!(BERACHAIN_DEV.pol_contract()));
+    assert_eq!(traces[1]["txHash"], json!(user_hash));
+
+    // Tracing the user transaction replays the PoL transaction before it
+    let user: Value =
+        client.request("debug_traceTransaction", rpc_params![user_hash, call_tracer]).await?;
+    assert_eq!(user["from"], json!(dev_signer().address()));
+    assert_eq!(user["gasUsed"], json!("0x5208"));
+
+    let parity: Vec<Value> = client.request("trace_block", rpc_params!["0x1"]).await?;
+    assert_eq!(parity[0]["transactionPosition"], json!(0));
+    assert_eq!(parity[0]["action"]["from"], json!(SYSTEM_ADDRESS));
+    assert_eq!(parity.last().unwrap()["transactionHash"], json!(user_hash));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_txpool_namespace_serves_berachain_envelopes() -> eyre::Result<()> {
+    let node = TestNode::spawn().await?;
+    let client = node.rpc_client();
+    let hash = node.send_transfer(0, Address::repeat_byte(7)).await?;
+    let sender = dev_signer().address();
+
+    let status: Value = client.request("txpool_status", rpc_params![]).await?;
+    assert_eq!(status, json!({ "pending": "0x1", "queued": "0x0" }));
+
+    let content: Value = client.request("txpool_content", rpc_params![]).await?;
+    let pooled = &content["pending"][sender.to_checksum(None)]["0"];
+    assert_eq!(pooled["hash"], json!(hash));
+    assert_eq!(pooled["type"], json!("0x2"));
+    assert_eq!(pooled["from"], json!(sender));
+
+    let from: Value = client.request("txpool_contentFrom", rpc_params![sender]).await?;
+    assert_eq!(from["pending"]["0"]["hash"], json!(hash));
+
+    let inspect: Value = client.request("txpool_inspect", rpc_params![]).await?;
+    assert!(inspect["pending"][sender.to_checksum(None)]["0"].is_string());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pol_distribution_logs_are_indexed() -> eyre::Result<()> {
+    const TOPIC: B256 = B256::repeat_byte(0x11);
+    // Distributor emitting `LOG1(0, 0, TOPIC)` on every call
+    let mut code = vec![0x7f];
+    code.extend_from_slice(TOPIC.as_slice());
+    code.extend_from_slice(&[0x60, 0x00, 0x60, 0x00, 0xa1, 0x00]);
+    let distributor = BERACHAIN_DEV.pol_contract();
+    let mut genesis = BERACHAIN_DEV.genesis().clone();
+    genesis.alloc.insert(distributor, GenesisAccount::default().with_code(Some(Bytes::from(code))));
+    let chain_spec = Arc::new(BerachainChainSpec::try_from(genesis)?);
+
+    let mut node = TestNode::spawn_with_chain_spec(chain_spec).await?;
+    let client = node.rpc_client();
+    let filter_id: Value =
+        client.request("eth_newFilter", rpc_params![json!({ "address": distributor })]).await?;
+    node.advance_block().await?;
+
+    let block: Value = client.request("eth_getBlockByNumber", rpc_params!["0x1", false]).await?;
+    let bloom: Bloom = serde_json::from_value(block["logsBloom"].clone())?;
+    assert!(bloom.contains_input(BloomInput::Raw(distributor.as_slice())));
+    assert!(bloom.contains_input(BloomInput::Raw(TOPIC.as_slice())));
+
+    let receipts: Vec<Value> = client.request("eth_getBlockReceipts", rpc_params!["0x1"]).await?;
+    let receipt_logs = receipts[0]["logs"].as_array().unwrap();
+    assert_eq!(receipt_logs.len(), 1);
+    assert_eq!(receipt_logs[0]["address"], json!(distributor));
+    assert_eq!(receipt_logs[0]["topics"], json!([TOPIC]));
+    assert_eq!(receipt_logs[0]["transactionIndex"], json!("0x0"));
+    assert_eq!(receipt_logs[0]["logIndex"], json!("0x0"));
+
+    let filter = json!({
+        "fromBlock": "0x1",
+        "toBlock": "0x1",
+        "address": distributor,
+        "topics": [TOPIC],
+    });
+    let logs: Vec<Value> = client.request("eth_getLogs", rpc_params![filter]).await?;
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0]["transactionHash"], block["transactions"][0]);
+    assert_eq!(&logs[0], &receipt_logs[0]);
+
+    let changes: Vec<Value> =
+        client.request("eth_getFilterChanges", rpc_params![filter_id]).await?;
+    assert_eq!(changes, logs);
+    Ok(())
+}